@@ -0,0 +1,398 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+    sync::Arc,
+};
+
+use cstree::{TextRange, TextSize};
+use lsp_types::Location;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Workspace,
+};
+
+static PDF_OUTPUT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\pdfoutput\s*=\s*1").unwrap());
+
+static WRITE18_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\write\s*18").unwrap());
+
+/// arXiv only detects `\pdfoutput=1` within the first lines of the root document.
+const PDF_OUTPUT_LINES: usize = 5;
+
+const GRAPHICS_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg"];
+
+const SHELL_ESCAPE_PACKAGES: &[&str] = &[
+    "auto-pst-pdf",
+    "gnuplottex",
+    "minted",
+    "pdftricks",
+    "pythontex",
+    "svg",
+];
+
+/// A construct that arXiv rejects or that leads to a different output on arXiv.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArxivIssue {
+    pub location: Location,
+    pub message: String,
+}
+
+/// Checks the root document and the given documents of a project for issues
+/// that prevent a submission to arXiv.
+pub fn find_arxiv_issues(root_document: &Document, documents: &[Arc<Document>]) -> Vec<ArxivIssue> {
+    let mut issues = Vec::new();
+    if root_document.data.as_latex().is_some()
+        && !root_document
+            .text
+            .lines()
+            .take(PDF_OUTPUT_LINES)
+            .any(|line| PDF_OUTPUT_REGEX.is_match(strip_comment(line)))
+    {
+        push_issue(
+            &mut issues,
+            root_document,
+            TextRange::default(),
+            "`\\pdfoutput=1` is missing from the first lines of the root document; \
+             arXiv will compile the project with LaTeX instead of pdfLaTeX"
+                .into(),
+        );
+    }
+
+    for document in documents {
+        if let Some(name) = document
+            .uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+            .filter(|name| !is_valid_file_name(name))
+        {
+            push_issue(
+                &mut issues,
+                document,
+                TextRange::default(),
+                format!(
+                    "The file name `{}` contains spaces or non-ASCII characters that arXiv does not accept",
+                    name
+                ),
+            );
+        }
+
+        if let Some(data) = document.data.as_latex() {
+            find_include_issues(document, &data.root, &mut issues);
+            find_graphics_path_issues(document, &data.root, &mut issues);
+            find_write18_issues(document, &mut issues);
+        }
+    }
+    issues
+}
+
+/// Writes the issues of every project in the workspace as `path:line:column: message`
+/// and returns the number of issues.
+pub fn write_arxiv_issues(
+    workspace: &dyn Workspace,
+    base_dir: &Path,
+    writer: &mut impl Write,
+) -> io::Result<usize> {
+    let mut lines = Vec::new();
+    for root_document in workspace.documents().into_iter().filter(|document| {
+        document
+            .data
+            .as_latex()
+            .map_or(false, |data| data.extras.has_document_environment)
+    }) {
+        let subset = match workspace.subset(Arc::clone(&root_document.uri)) {
+            Some(subset) => subset,
+            None => continue,
+        };
+
+        for issue in find_arxiv_issues(&root_document, &subset.documents) {
+            let path = match issue.location.uri.to_file_path() {
+                Ok(path) => path,
+                Err(()) => continue,
+            };
+
+            let path = path.strip_prefix(base_dir).unwrap_or(&path);
+            let position = issue.location.range.start;
+            lines.push((
+                path.to_string_lossy().into_owned(),
+                position.line,
+                position.character,
+                issue.message,
+            ));
+        }
+    }
+
+    lines.sort();
+    lines.dedup();
+    for (path, line, character, message) in &lines {
+        writeln!(
+            writer,
+            "{}:{}:{}: {}",
+            path,
+            line + 1,
+            character + 1,
+            message
+        )?;
+    }
+    Ok(lines.len())
+}
+
+fn find_include_issues(
+    document: &Document,
+    root: &latex::SyntaxNode,
+    issues: &mut Vec<ArxivIssue>,
+) {
+    for include in root.descendants().filter_map(latex::Include::cast) {
+        let kind = include.syntax().kind();
+        for path in include
+            .path_list()
+            .into_iter()
+            .flat_map(|paths| paths.keys())
+        {
+            let name = path.to_string();
+            let range = path.small_range();
+            match kind {
+                latex::PACKAGE_INCLUDE => {
+                    if SHELL_ESCAPE_PACKAGES.contains(&name.as_str()) {
+                        push_issue(
+                            issues,
+                            document,
+                            range,
+                            format!(
+                                "The `{}` package requires shell escape which is disabled on arXiv",
+                                name
+                            ),
+                        );
+                    }
+                    continue;
+                }
+                latex::CLASS_INCLUDE => continue,
+                latex::SVG_INCLUDE | latex::INKSCAPE_INCLUDE => {
+                    push_issue(
+                        issues,
+                        document,
+                        range,
+                        "arXiv cannot convert SVG images; include a PDF version instead".into(),
+                    );
+                }
+                latex::GRAPHICS_INCLUDE => {
+                    if let Some(extension) = Path::new(&name)
+                        .extension()
+                        .map(|extension| extension.to_string_lossy().to_lowercase())
+                        .filter(|extension| !GRAPHICS_EXTENSIONS.contains(&extension.as_str()))
+                    {
+                        push_issue(
+                            issues,
+                            document,
+                            range,
+                            format!(
+                                "arXiv does not support `.{}` images with pdfLaTeX; \
+                                 convert them to PDF, PNG or JPEG",
+                                extension
+                            ),
+                        );
+                    }
+                }
+                _ => {}
+            }
+
+            check_path(document, range, &name, issues);
+        }
+    }
+}
+
+fn find_graphics_path_issues(
+    document: &Document,
+    root: &latex::SyntaxNode,
+    issues: &mut Vec<ArxivIssue>,
+) {
+    for command in root
+        .descendants()
+        .filter_map(latex::GenericCommand::cast)
+        .filter(|command| command.name().map(|name| name.text()) == Some("\\graphicspath"))
+    {
+        for group in command
+            .syntax()
+            .descendants()
+            .filter_map(latex::CurlyGroup::cast)
+            .filter(|group| {
+                group
+                    .syntax()
+                    .children()
+                    .all(|child| latex::CurlyGroup::cast(child).is_none())
+            })
+        {
+            if let Some(path) = group.content_text() {
+                check_path(document, group.small_range(), &path, issues);
+            }
+        }
+    }
+}
+
+fn find_write18_issues(document: &Document, issues: &mut Vec<ArxivIssue>) {
+    let mut offset = 0;
+    for line in document.text.split_inclusive('\n') {
+        for mat in WRITE18_REGEX.find_iter(strip_comment(line)) {
+            let range = TextRange::new(
+                TextSize::from((offset + mat.start()) as u32),
+                TextSize::from((offset + mat.end()) as u32),
+            );
+
+            push_issue(
+                issues,
+                document,
+                range,
+                "`\\write18` requires shell escape which is disabled on arXiv".into(),
+            );
+        }
+        offset += line.len();
+    }
+}
+
+fn check_path(document: &Document, range: TextRange, path: &str, issues: &mut Vec<ArxivIssue>) {
+    if is_absolute_path(path) {
+        push_issue(
+            issues,
+            document,
+            range,
+            format!(
+                "The absolute path `{}` cannot be resolved on arXiv; use a path relative to the root document",
+                path
+            ),
+        );
+    } else if !is_valid_file_name(path) {
+        push_issue(
+            issues,
+            document,
+            range,
+            format!(
+                "The path `{}` contains spaces or non-ASCII characters that arXiv does not accept",
+                path
+            ),
+        );
+    }
+}
+
+fn push_issue(
+    issues: &mut Vec<ArxivIssue>,
+    document: &Document,
+    range: TextRange,
+    message: String,
+) {
+    issues.push(ArxivIssue {
+        location: Location::new(
+            document.uri.as_ref().clone().into(),
+            document.line_index.line_col_lsp_range(range),
+        ),
+        message,
+    });
+}
+
+fn is_absolute_path(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    path.starts_with('/')
+        || path.starts_with('~')
+        || path.starts_with('\\')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+}
+
+fn is_valid_file_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii() && !c.is_whitespace())
+}
+
+/// Removes the comment of a line while keeping escaped percent signs.
+fn strip_comment(line: &str) -> &str {
+    let mut previous = None;
+    for (i, c) in line.char_indices() {
+        if c == '%' && previous != Some('\\') {
+            return &line[..i];
+        }
+        previous = Some(c);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{DocumentLanguage, RangeExt, ServerContext, Uri};
+
+    use super::*;
+
+    fn parse(name: &str, text: &str) -> Arc<Document> {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join(name)).unwrap();
+        Arc::new(Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        ))
+    }
+
+    fn messages(root_document: &Arc<Document>) -> Vec<String> {
+        find_arxiv_issues(root_document, &[Arc::clone(root_document)])
+            .into_iter()
+            .map(|issue| issue.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_valid() {
+        let document = parse(
+            "main.tex",
+            "\\pdfoutput=1\n\\documentclass{article}\n\\usepackage{graphicx}\n\\begin{document}\n\\includegraphics{images/foo.pdf}\n\\end{document}",
+        );
+        assert!(messages(&document).is_empty());
+    }
+
+    #[test]
+    fn test_missing_pdf_output() {
+        let document = parse(
+            "main.tex",
+            "% \\pdfoutput=1\n\\documentclass{article}\n\\begin{document}\n\\end{document}",
+        );
+        assert_eq!(messages(&document).len(), 1);
+    }
+
+    #[test]
+    fn test_graphics() {
+        let document = parse(
+            "main.tex",
+            "\\pdfoutput=1\n\\includegraphics{/home/foo/bar.png}\n\\includegraphics{plot.eps}\n\\includegraphics{grüße}\n\\includesvg{drawing}",
+        );
+
+        let issues = find_arxiv_issues(&document, &[Arc::clone(&document)]);
+        let lines: Vec<_> = issues
+            .iter()
+            .map(|issue| issue.location.range.start.line)
+            .collect();
+        assert_eq!(lines, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_shell_escape() {
+        let document = parse(
+            "main.tex",
+            "\\pdfoutput=1\n\\usepackage{minted}\n\\immediate\\write18{ls} % \\write18",
+        );
+
+        let issues = find_arxiv_issues(&document, &[Arc::clone(&document)]);
+        let ranges: Vec<_> = issues.iter().map(|issue| issue.location.range).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                Range::new_simple(1, 12, 1, 18),
+                Range::new_simple(2, 10, 2, 18),
+            ]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use std::sync::Arc;
+
+use cstree::{TextRange, TextSize};
+
+use crate::{
+    syntax::{
+        latex::{self, ExplicitLink, HasCurly},
+        CstNode,
+    },
+    Document, Uri, WorkspaceSubset,
+};
+
+/// A part of a document with its own bibliography like a `bibunit` environment
+/// of the `bibunits` package or a `refsection` environment of `biblatex`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct BibUnit {
+    pub range: TextRange,
+
+    /// The names of the bibliographies that belong to the unit.
+    pub resources: Vec<String>,
+
+    /// Whether the entries of the global bibliographies can be cited inside of the unit.
+    pub inherits_global: bool,
+}
+
+pub fn find_bib_units(root: &latex::SyntaxNode) -> Vec<BibUnit> {
+    let mut units = Vec::new();
+    for environment in root.descendants().filter_map(latex::Environment::cast) {
+        let begin = match environment.begin() {
+            Some(begin) => begin,
+            None => continue,
+        };
+
+        let name = begin
+            .name()
+            .and_then(|name| name.key())
+            .map(|name| name.to_string());
+
+        let (resources, inherits_global) = match name.as_deref() {
+            Some("bibunit") => {
+                let resources = environment
+                    .syntax()
+                    .descendants()
+                    .filter_map(latex::GenericCommand::cast)
+                    .filter(|command| {
+                        command
+                            .name()
+                            .map_or(false, |name| name.text() == "\\putbib")
+                    })
+                    .flat_map(|command| words(command.syntax()))
+                    .collect();
+                (resources, false)
+            }
+            Some("refsection") => (
+                begin
+                    .options()
+                    .map(|options| words(options.syntax()))
+                    .unwrap_or_default(),
+                true,
+            ),
+            _ => continue,
+        };
+
+        units.push(BibUnit {
+            range: environment.small_range(),
+            resources,
+            inherits_global,
+        });
+    }
+    units
+}
+
+/// Returns the bibliographies that can be cited at the given position of a document
+/// or `None` if the citation is not restricted to a unit of the project.
+pub fn find_bibliographies_in_scope(
+    subset: &WorkspaceSubset,
+    document: &Document,
+    offset: TextSize,
+) -> Option<Vec<Arc<Uri>>> {
+    BibScopes::new(subset, document)?.find(offset)
+}
+
+/// The bibliography units of a document together with the bibliographies of the project.
+/// They only need to be collected once to look up the scopes of all citations of the document.
+pub struct BibScopes<'a> {
+    subset: &'a WorkspaceSubset,
+    document: &'a Document,
+    units: Vec<BibUnit>,
+    default_links: Vec<&'a ExplicitLink>,
+    global_links: Vec<(&'a Document, &'a ExplicitLink)>,
+    is_chapterbib_loaded: bool,
+}
+
+impl<'a> BibScopes<'a> {
+    pub fn new(subset: &'a WorkspaceSubset, document: &'a Document) -> Option<Self> {
+        let data = document.data.as_latex()?;
+        let mut default_links = Vec::new();
+        let mut global_links = Vec::new();
+        for other in &subset.documents {
+            let other_data = match other.data.as_latex() {
+                Some(other_data) => other_data,
+                None => continue,
+            };
+
+            let other_units = find_bib_units(&other_data.root);
+            let other_default_resources: Vec<_> = other_data
+                .root
+                .descendants()
+                .filter_map(latex::GenericCommand::cast)
+                .filter(|command| {
+                    command
+                        .name()
+                        .map_or(false, |name| name.text() == "\\defaultbibliography")
+                })
+                .flat_map(|command| {
+                    command
+                        .syntax()
+                        .children()
+                        .find_map(latex::CurlyGroup::cast)
+                        .and_then(|group| group.content_text())
+                        .into_iter()
+                        .flat_map(|text| {
+                            text.split(',')
+                                .map(|name| name.trim().to_string())
+                                .collect::<Vec<_>>()
+                        })
+                })
+                .collect();
+
+            for link in other_data
+                .extras
+                .explicit_links
+                .iter()
+                .filter(|link| link.kind == latex::ExplicitLinkKind::Bibtex)
+            {
+                if other_default_resources.contains(&link.stem.to_string()) {
+                    default_links.push(link);
+                } else if !other_units
+                    .iter()
+                    .any(|unit| unit.range.contains_range(link.stem_range))
+                {
+                    global_links.push((&**other, link));
+                }
+            }
+        }
+
+        let is_chapterbib_loaded = subset
+            .documents
+            .iter()
+            .filter_map(|document| document.data.as_latex())
+            .flat_map(|data| data.extras.explicit_links.iter())
+            .any(|link| link.kind == latex::ExplicitLinkKind::Package && link.stem == "chapterbib");
+
+        Some(Self {
+            subset,
+            document,
+            units: find_bib_units(&data.root),
+            default_links,
+            global_links,
+            is_chapterbib_loaded,
+        })
+    }
+
+    /// Returns the bibliographies that can be cited at the given position of the document
+    /// or `None` if the citation is not restricted to a unit of the project.
+    pub fn find(&self, offset: TextSize) -> Option<Vec<Arc<Uri>>> {
+        let data = self.document.data.as_latex()?;
+        let unit = self
+            .units
+            .iter()
+            .filter(|unit| unit.range.contains(offset))
+            .last();
+
+        let targets: Vec<_> = match unit {
+            Some(unit) => {
+                let mut targets: Vec<_> = data
+                    .extras
+                    .explicit_links
+                    .iter()
+                    .filter(|link| {
+                        unit.range.contains_range(link.stem_range)
+                            && unit.resources.contains(&link.stem.to_string())
+                    })
+                    .flat_map(|link| link.targets.iter().cloned())
+                    .collect();
+
+                if unit.inherits_global {
+                    targets.extend(
+                        self.global_links
+                            .iter()
+                            .flat_map(|(_, link)| link.targets.iter().cloned()),
+                    );
+                } else if targets.is_empty() {
+                    targets.extend(
+                        self.default_links
+                            .iter()
+                            .flat_map(|link| link.targets.iter().cloned()),
+                    );
+                }
+                targets
+            }
+            None => {
+                // With `chapterbib`, every included file can have its own bibliography.
+                if !self.is_chapterbib_loaded || data.extras.has_document_environment {
+                    return None;
+                }
+
+                let targets: Vec<_> = self
+                    .global_links
+                    .iter()
+                    .filter(|(other, _)| other.uri == self.document.uri)
+                    .flat_map(|(_, link)| link.targets.iter().cloned())
+                    .collect();
+
+                if targets.is_empty() {
+                    return None;
+                }
+                targets
+            }
+        };
+
+        Some(
+            self.subset
+                .documents
+                .iter()
+                .filter(|document| document.data.as_bibtex().is_some())
+                .filter(|document| targets.contains(&document.uri))
+                .map(|document| Arc::clone(&document.uri))
+                .collect(),
+        )
+    }
+}
+
+fn words(node: &latex::SyntaxNode) -> Vec<String> {
+    node.descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == latex::WORD)
+        .map(|token| token.text().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{
+        create_workspace_fast, DocumentLanguage, ServerContext, Workspace, WorkspaceSource,
+    };
+
+    use super::*;
+
+    fn uri(name: &str) -> Arc<Uri> {
+        Arc::new(Uri::from_file_path(std::env::temp_dir().join(name)).unwrap())
+    }
+
+    /// Opens the given files and returns the names of the bibliographies
+    /// that are in scope at the first occurrence of the marker in the given document.
+    fn find_scope(files: &[(&str, &str)], name: &str, marker: &str) -> Option<Vec<String>> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        for (name, text) in files {
+            let language = DocumentLanguage::by_path(&std::env::temp_dir().join(name)).unwrap();
+            workspace.open(
+                uri(name),
+                text.trim().to_string(),
+                language,
+                WorkspaceSource::Client,
+            );
+        }
+
+        let document = workspace.get(&uri(name)).unwrap();
+        let subset = workspace.subset(uri(name)).unwrap();
+        let offset = TextSize::from(document.text.find(marker).unwrap() as u32);
+        let mut scope: Vec<_> = find_bibliographies_in_scope(&subset, &document, offset)?
+            .iter()
+            .filter_map(|uri| uri.path_segments()?.last().map(ToString::to_string))
+            .collect();
+
+        scope.sort();
+        Some(scope)
+    }
+
+    const REFSECTIONS: &str = indoc! {r#"
+        \documentclass{article}
+        \usepackage{biblatex}
+        \addbibresource{global.bib}
+        \begin{document}
+        \cite{outside}
+        \begin{refsection}[local.bib]
+        \cite{section}
+        \begin{refsegment}
+        \cite{segment}
+        \end{refsegment}
+        \end{refsection}
+        \end{document}
+    "#};
+
+    const BIBLIOGRAPHIES: &[(&str, &str)] = &[
+        ("global.bib", "@article{foo,}"),
+        ("local.bib", "@article{bar,}"),
+        ("other.bib", "@article{baz,}"),
+    ];
+
+    #[test]
+    fn test_refsection() {
+        let mut files = vec![("main.tex", REFSECTIONS)];
+        files.extend_from_slice(BIBLIOGRAPHIES);
+        assert_eq!(find_scope(&files, "main.tex", "{outside}"), None);
+        assert_eq!(
+            find_scope(&files, "main.tex", "{section}"),
+            Some(vec!["global.bib".to_string(), "local.bib".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_refsegment() {
+        let mut files = vec![("main.tex", REFSECTIONS)];
+        files.extend_from_slice(BIBLIOGRAPHIES);
+        assert_eq!(
+            find_scope(&files, "main.tex", "{segment}"),
+            find_scope(&files, "main.tex", "{section}")
+        );
+    }
+
+    #[test]
+    fn test_chapterbib() {
+        let mut files = vec![
+            (
+                "main.tex",
+                indoc! {r#"
+                    \documentclass{report}
+                    \usepackage{chapterbib}
+                    \begin{document}
+                    \include{chapter1}
+                    \include{chapter2}
+                    \end{document}
+                "#},
+            ),
+            ("chapter1.tex", "\\cite{bar}\n\\bibliography{local}"),
+            ("chapter2.tex", "\\cite{baz}\n\\bibliography{other}"),
+        ];
+        files.extend_from_slice(BIBLIOGRAPHIES);
+
+        assert_eq!(
+            find_scope(&files, "chapter1.tex", "bar"),
+            Some(vec!["local.bib".to_string()])
+        );
+        assert_eq!(
+            find_scope(&files, "chapter2.tex", "baz"),
+            Some(vec!["other.bib".to_string()])
+        );
+        assert_eq!(find_scope(&files, "main.tex", "include"), None);
+    }
+}
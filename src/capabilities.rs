@@ -12,6 +12,8 @@ pub trait ClientCapabilitiesExt {
     fn has_pull_configuration_support(&self) -> bool;
 
     fn has_push_configuration_support(&self) -> bool;
+
+    fn has_embedded_documents_support(&self) -> bool;
 }
 
 impl ClientCapabilitiesExt for ClientCapabilities {
@@ -55,6 +57,14 @@ impl ClientCapabilitiesExt for ClientCapabilities {
             .and_then(|cap| cap.dynamic_registration)
             == Some(true)
     }
+
+    fn has_embedded_documents_support(&self) -> bool {
+        self.experimental
+            .as_ref()
+            .and_then(|cap| cap.get("embeddedDocuments"))
+            .and_then(|cap| cap.as_bool())
+            == Some(true)
+    }
 }
 
 #[cfg(test)]
@@ -23,6 +23,81 @@ static DOI_URL_PATTERN: &str = r#"https://doi.org/\[.*\]\(.*\)"#;
 
 static DOI_URL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(DOI_URL_PATTERN).unwrap());
 
+/// The part of a bibliography entry that is printed by a citation command.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CitationFields {
+    Reference,
+    Full,
+    Title,
+    Author,
+    Year,
+    Url,
+}
+
+impl CitationFields {
+    pub fn from_command(name: &str) -> Self {
+        match name.trim_end_matches('*').to_lowercase().as_str() {
+            "\\fullcite" | "\\footfullcite" => Self::Full,
+            "\\citetitle" => Self::Title,
+            "\\citeauthor" => Self::Author,
+            "\\citeyear" | "\\citeyearpar" | "\\citedate" => Self::Year,
+            "\\citeurl" => Self::Url,
+            _ => Self::Reference,
+        }
+    }
+}
+
+/// Renders the fields of an entry that are printed by the given kind of citation command.
+pub fn render_citation_fields(
+    root: &bibtex::SyntaxNode,
+    key: &str,
+    fields: CitationFields,
+) -> Option<MarkupContent> {
+    let value = match fields {
+        CitationFields::Reference => return render_citation(root, key),
+        CitationFields::Full => {
+            let mut markdown = render_citation(root, key)?.value;
+            markdown.push('\n');
+            for (name, value) in entry_fields(root, key)? {
+                markdown.push_str(&format!("\n- **{}**: {}", name, value));
+            }
+            markdown
+        }
+        CitationFields::Title => {
+            let reference = convert_to_ris(root, key)?;
+            format!("*{}*", reference.title.or(reference.primary_title)?)
+        }
+        CitationFields::Author => {
+            let reference = convert_to_ris(root, key)?;
+            let authors = if reference.authors.is_empty() {
+                reference.editors
+            } else {
+                reference.authors
+            };
+
+            if authors.is_empty() {
+                return None;
+            }
+            authors.join("; ")
+        }
+        CitationFields::Year => {
+            let reference = convert_to_ris(root, key)?;
+            let year = reference.year.or(reference.date)?;
+            year.split('/').next()?.to_string()
+        }
+        CitationFields::Url => {
+            let reference = convert_to_ris(root, key)?;
+            let url = reference.url?;
+            format!("<{}>", url)
+        }
+    };
+
+    Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value,
+    })
+}
+
 pub fn render_citation(root: &bibtex::SyntaxNode, key: &str) -> Option<MarkupContent> {
     let ris_reference = convert_to_ris(root, key)?;
     let doi_url = get_doi_url_markdown(&ris_reference);
@@ -53,6 +128,34 @@ pub fn render_citation(root: &bibtex::SyntaxNode, key: &str) -> Option<MarkupCon
     Some(content)
 }
 
+/// Renders the preview of an entry that is shown when resolving a citation completion item:
+/// the formatted reference followed by the keywords and the abstract of the entry.
+pub fn render_citation_preview(root: &bibtex::SyntaxNode, key: &str) -> Option<MarkupContent> {
+    let mut sections: Vec<String> = render_citation(root, key)
+        .map(|content| content.value)
+        .into_iter()
+        .collect();
+
+    let fields = entry_fields(root, key).unwrap_or_default();
+    for (title, name) in [("Keywords", "keywords"), ("Abstract", "abstract")] {
+        if let Some((_, value)) = fields
+            .iter()
+            .find(|(field_name, value)| field_name == name && !value.is_empty())
+        {
+            sections.push(format!("**{}**: {}", title, value));
+        }
+    }
+
+    if sections.is_empty() {
+        return None;
+    }
+
+    Some(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: sections.join("\n\n"),
+    })
+}
+
 fn convert_to_ris(root: &bibtex::SyntaxNode, key: &str) -> Option<RisReference> {
     let mut bib_code = String::new();
     for string in root
@@ -81,6 +184,34 @@ fn convert_to_ris(root: &bibtex::SyntaxNode, key: &str) -> Option<RisReference>
         .find(|reference| reference.id.as_ref().map(AsRef::as_ref) == Some(key))
 }
 
+/// Returns the names and the values of the fields of an entry without the braces of the values.
+fn entry_fields(root: &bibtex::SyntaxNode, key: &str) -> Option<Vec<(String, String)>> {
+    let entry = root
+        .children()
+        .filter_map(bibtex::Entry::cast)
+        .find(|entry| entry.key().map(|key| key.to_string()).as_deref() == Some(key))?;
+
+    let fields = entry
+        .fields()
+        .filter_map(|field| {
+            let name = field.name()?.text().to_lowercase();
+            let value = field
+                .value()?
+                .syntax()
+                .to_string()
+                .chars()
+                .filter(|c| !matches!(c, '{' | '}' | '"'))
+                .collect::<String>()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            Some((name, value))
+        })
+        .collect();
+
+    Some(fields)
+}
+
 fn get_doi_url_markdown(ris_reference: &RisReference) -> Option<String> {
     ris_reference
         .doi
@@ -157,6 +288,74 @@ mod tests {
         assert_eq!(actual_md, expected_md);
     }
 
+    #[test]
+    fn test_preview() {
+        let document = bibtex::parse(
+            r#"
+                @article{foo,
+                    author = {Foo Bar},
+                    title = {Baz Qux},
+                    year = {2020},
+                    keywords = {foo, bar},
+                    abstract = {Lorem {ipsum}
+                                dolor sit amet.}
+                }
+            "#,
+        );
+
+        let actual_md = render_citation_preview(&document.root, "foo").unwrap();
+
+        let expected_md = MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "Bar, Foo. (2020). *Baz Qux*.\n\n**Keywords**: foo, bar\n\n**Abstract**: Lorem ipsum dolor sit amet.".into(),
+        };
+
+        assert_eq!(actual_md, expected_md);
+    }
+
+    #[test]
+    fn test_fields() {
+        let document = bibtex::parse(
+            r#"
+                @article{foo,
+                    author = {Foo Bar},
+                    title = {Baz Qux},
+                    year = {2020}
+                }
+            "#,
+        );
+
+        let render = |fields| {
+            render_citation_fields(&document.root, "foo", fields)
+                .unwrap()
+                .value
+        };
+
+        assert_eq!(render(CitationFields::Title), "*Baz Qux*");
+        assert_eq!(render(CitationFields::Author), "Bar, Foo");
+        assert_eq!(render(CitationFields::Year), "2020");
+        assert_eq!(
+            render(CitationFields::Full),
+            "Bar, Foo. (2020). *Baz Qux*.\n\n- **author**: Foo Bar\n- **title**: Baz Qux\n- **year**: 2020"
+        );
+    }
+
+    #[test]
+    fn test_fields_from_command() {
+        assert_eq!(
+            CitationFields::from_command("\\fullcite"),
+            CitationFields::Full
+        );
+        assert_eq!(
+            CitationFields::from_command("\\Citeauthor*"),
+            CitationFields::Author
+        );
+        assert_eq!(
+            CitationFields::from_command("\\parencite"),
+            CitationFields::Reference
+        );
+    }
+
     #[test]
     fn test_unknown_key() {
         let document = bibtex::parse("");
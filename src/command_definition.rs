@@ -0,0 +1,144 @@
+use crate::{
+    component_db::COMPONENT_DATABASE,
+    syntax::{latex, CstNode},
+    Document, WorkspaceSubset,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CommandDefinitionKind {
+    /// `\newcommand` or `\DeclareMathOperator`, which fail if the command exists.
+    New,
+    /// `\renewcommand` or `\DeclareRobustCommand`, which always replace the command.
+    Renew,
+    /// `\providecommand`, which is ignored if the command exists.
+    Provide,
+}
+
+impl CommandDefinitionKind {
+    pub fn from_command(command: &str) -> Self {
+        match command.trim_end_matches('*') {
+            "\\renewcommand" | "\\DeclareRobustCommand" => Self::Renew,
+            "\\providecommand" => Self::Provide,
+            _ => Self::New,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CommandDefinitionInfo<'a> {
+    pub document: &'a Document,
+    pub definition: latex::CommandDefinition<'a>,
+    pub kind: CommandDefinitionKind,
+    /// Whether the definition has no effect because the command already exists.
+    pub is_ignored: bool,
+    /// Whether the command has this definition after the whole project has been processed.
+    pub is_effective: bool,
+}
+
+/// Finds the definitions of a command in the order of the project and determines
+/// which one is effective, taking into account that `\providecommand` and `\newcommand`
+/// do not replace existing commands, including the ones that are defined by packages.
+pub fn find_command_definitions<'a>(
+    subset: &'a WorkspaceSubset,
+    name: &str,
+) -> Vec<CommandDefinitionInfo<'a>> {
+    let mut definitions: Vec<_> = subset
+        .documents
+        .iter()
+        .filter_map(|document| Some((document, document.data.as_latex()?)))
+        .flat_map(|(document, data)| {
+            data.root
+                .descendants()
+                .filter_map(latex::CommandDefinition::cast)
+                .filter(|definition| {
+                    definition
+                        .name()
+                        .and_then(|name| name.command())
+                        .map(|name| name.text())
+                        == Some(name)
+                })
+                .filter_map(move |definition| {
+                    Some(CommandDefinitionInfo {
+                        document: document.as_ref(),
+                        kind: CommandDefinitionKind::from_command(definition.command()?.text()),
+                        definition,
+                        is_ignored: false,
+                        is_effective: false,
+                    })
+                })
+        })
+        .collect();
+
+    let mut is_defined = COMPONENT_DATABASE
+        .linked_components(subset)
+        .iter()
+        .flat_map(|component| component.commands.iter())
+        .any(|command| Some(command.name.as_str()) == name.strip_prefix('\\'));
+
+    let mut effective = None;
+    for (i, definition) in definitions.iter_mut().enumerate() {
+        if definition.kind == CommandDefinitionKind::Renew || !is_defined {
+            effective = Some(i);
+            is_defined = true;
+        } else {
+            definition.is_ignored = true;
+        }
+    }
+
+    if let Some(i) = effective {
+        definitions[i].is_effective = true;
+    }
+
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{DocumentLanguage, ServerContext, Uri};
+
+    use super::*;
+
+    fn create_subset(text: &str) -> WorkspaceSubset {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap();
+        let document = Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        );
+
+        WorkspaceSubset {
+            documents: vec![Arc::new(document)],
+        }
+    }
+
+    fn find_effective(text: &str, name: &str) -> Vec<bool> {
+        let subset = create_subset(text);
+        find_command_definitions(&subset, name)
+            .into_iter()
+            .map(|definition| definition.is_effective)
+            .collect()
+    }
+
+    #[test]
+    fn test_provide_after_new() {
+        let text = "\\newcommand{\\foo}{foo}\n\\providecommand{\\foo}{bar}";
+        assert_eq!(find_effective(text, "\\foo"), vec![true, false]);
+    }
+
+    #[test]
+    fn test_renew_after_provide() {
+        let text =
+            "\\providecommand{\\foo}{foo}\n\\renewcommand{\\foo}{bar}\n\\newcommand{\\foo}{baz}";
+        assert_eq!(find_effective(text, "\\foo"), vec![false, true, false]);
+    }
+
+    #[test]
+    fn test_provide_existing_command() {
+        let text = "\\providecommand{\\section}{foo}";
+        assert_eq!(find_effective(text, "\\section"), vec![false]);
+    }
+}
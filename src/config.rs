@@ -1,15 +1,37 @@
-use std::sync::{Mutex, RwLock};
+use std::{fs, path::PathBuf, sync::Mutex};
 
 use crossbeam_channel::Sender;
 use log::{error, warn};
 use lsp_server::Message;
 use lsp_types::{
-    notification::{DidChangeConfiguration, Notification},
+    notification::{DidChangeConfiguration, Notification, ShowMessage},
     request::{RegisterCapability, WorkspaceConfiguration},
-    ClientCapabilities, ConfigurationItem, ConfigurationParams, Registration, RegistrationParams,
+    ClientCapabilities, ConfigurationItem, ConfigurationParams, MessageType, Registration,
+    RegistrationParams, ShowMessageParams,
 };
+use serde_json::Value;
 
-use crate::{client::send_request, req_queue::ReqQueue, ClientCapabilitiesExt, Options};
+use crate::{
+    client::{send_notification, send_request},
+    req_queue::ReqQueue,
+    BuildOptions, ChktexOptions, ClientCapabilitiesExt, CommandOptions, ForwardSearchOptions,
+    LatexindentOptions, LintRule, LintSeverity, MathPreviewOptions, Options, PlatformOverrides,
+    ServerContext,
+};
+
+/// The name of the configuration file inside of the project root directory.
+pub const PROJECT_CONFIG_FILE: &str = "texlab.toml";
+
+/// Option names of older versions and their replacements.
+const DEPRECATED_OPTIONS: &[(&str, &str)] = &[
+    ("latex.build", "build"),
+    ("latex.forwardSearch", "forwardSearch"),
+    ("latex.lint.onChange", "chktex.onEdit"),
+    ("latex.lint.onSave", "chktex.onOpenAndSave"),
+    ("latex.rootDirectory", "rootDirectory"),
+    ("bibtex.formatting.lineLength", "formatterLineLength"),
+    ("bibtex.formatting.formatter", "bibtexFormatter"),
+];
 
 pub fn register_config_capability(
     req_queue: &Mutex<ReqQueue>,
@@ -41,13 +63,13 @@ pub fn register_config_capability(
     }
 }
 
-pub fn pull_config(
-    req_queue: &Mutex<ReqQueue>,
-    sender: &Sender<Message>,
-    options: &RwLock<Options>,
-    client_capabilities: &ClientCapabilities,
-) {
-    if !client_capabilities.has_pull_configuration_support() {
+pub fn pull_config(req_queue: &Mutex<ReqQueue>, sender: &Sender<Message>, context: &ServerContext) {
+    if !context
+        .client_capabilities
+        .lock()
+        .unwrap()
+        .has_pull_configuration_support()
+    {
         return;
     }
 
@@ -61,16 +83,7 @@ pub fn pull_config(
     match send_request::<WorkspaceConfiguration>(req_queue, sender, params) {
         Ok(mut json) => {
             let value = json.pop().expect("invalid configuration request");
-            let new_options = match serde_json::from_value(value) {
-                Ok(new_options) => new_options,
-                Err(why) => {
-                    warn!("Invalid configuration section \"texlab\": {}", why);
-                    Options::default()
-                }
-            };
-
-            let mut options = options.write().unwrap();
-            *options = new_options;
+            apply_config(sender, context, value);
         }
         Err(why) => {
             error!("Retrieving configuration failed: {}", why);
@@ -78,13 +91,401 @@ pub fn pull_config(
     };
 }
 
-pub fn push_config(options: &RwLock<Options>, config: serde_json::Value) {
+pub fn push_config(sender: &Sender<Message>, context: &ServerContext, config: Value) {
+    apply_config(sender, context, config);
+}
+
+fn apply_config(sender: &Sender<Message>, context: &ServerContext, config: Value) {
+    *context.client_config.lock().unwrap() = config;
+    reload_config(sender, context);
+}
+
+/// Returns the path of the project configuration file
+/// inside of the root directory of the project.
+pub fn project_config_path(context: &ServerContext) -> PathBuf {
+    let root_directory = context
+        .client_config
+        .lock()
+        .unwrap()
+        .get("rootDirectory")
+        .and_then(Value::as_str)
+        .map(|dir| context.current_directory.join(dir))
+        .unwrap_or_else(|| context.current_directory.clone());
+
+    root_directory.join(PROJECT_CONFIG_FILE)
+}
+
+/// Recomputes the options by merging the project configuration file
+/// with the settings of the editor. The settings of the editor take precedence.
+/// If the merged configuration is invalid, the previous options are kept.
+pub fn reload_config(sender: &Sender<Message>, context: &ServerContext) {
+    let client_config = context.client_config.lock().unwrap().clone();
+    let mut warnings = Vec::new();
+    let mut config = Value::Object(Default::default());
+
+    let path = project_config_path(context);
+    if let Ok(text) = fs::read_to_string(&path) {
+        match parse_project_config(&text) {
+            Ok(project_config) => {
+                warnings.extend(
+                    validate_config(&project_config)
+                        .into_iter()
+                        .map(|warning| format!("{}: {}", PROJECT_CONFIG_FILE, warning)),
+                );
+                config = project_config;
+            }
+            Err(why) => warnings.push(format!("Invalid {}: {}", PROJECT_CONFIG_FILE, why)),
+        }
+    }
+
+    warnings.extend(validate_config(&client_config));
+    merge_config(&mut config, client_config);
+
     match serde_json::from_value(config) {
-        Ok(new_options) => {
-            *options.write().unwrap() = new_options;
+        Ok(new_options) => *context.options.write().unwrap() = new_options,
+        Err(why) => warnings.push(format!(
+            "Invalid configuration section \"texlab\" (keeping the previous settings): {}",
+            why
+        )),
+    };
+
+    report_config_warnings(sender, context, warnings);
+}
+
+fn parse_project_config(text: &str) -> anyhow::Result<Value> {
+    let value: toml::Value = toml::from_str(text)?;
+    Ok(serde_json::to_value(value)?)
+}
+
+/// Merges the given settings into the base configuration.
+/// Objects are merged recursively and `null` values do not override the base.
+fn merge_config(base: &mut Value, config: Value) {
+    match (base, config) {
+        (_, Value::Null) => {}
+        (Value::Object(base), Value::Object(config)) => {
+            for (key, value) in config.into_iter().filter(|(_, value)| !value.is_null()) {
+                match base.get_mut(&key) {
+                    Some(base) => merge_config(base, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
         }
-        Err(why) => {
-            error!("Invalid configuration: {}", why);
+        (base, config) => *base = config,
+    }
+}
+
+/// Shows the warnings that have not been reported for the previous configuration
+/// because the configuration is pulled before every request.
+fn report_config_warnings(
+    sender: &Sender<Message>,
+    context: &ServerContext,
+    warnings: Vec<String>,
+) {
+    let mut last_warnings = context.config_warnings.lock().unwrap();
+    for warning in warnings
+        .iter()
+        .filter(|warning| !last_warnings.contains(warning))
+    {
+        warn!("{}", warning);
+        let params = ShowMessageParams {
+            typ: MessageType::Warning,
+            message: format!("TexLab: {}", warning),
+        };
+
+        if let Err(why) = send_notification::<ShowMessage>(sender, params) {
+            error!("Failed to report configuration warning: {}", why);
         }
+    }
+
+    *last_warnings = warnings;
+}
+
+/// Generates the schema of the configuration by serializing
+/// an instance of the options where every optional value is present.
+fn options_schema() -> Value {
+    let options = Options {
+        root_directory: Some(PathBuf::new()),
+        aux_directory: Some(PathBuf::new()),
+        formatter_line_length: Some(0),
+        diagnostics_delay: Some(0),
+        completion_limit: Some(0),
+        symbol_limit: Some(0),
+        build: BuildOptions {
+            executable: Some(String::new()),
+            args: Some(vec![String::new()]),
+            forward_search_delay: Some(0),
+            platform: platform_overrides(),
+            ..BuildOptions::default()
+        },
+        chktex: ChktexOptions {
+            executable: Some(String::new()),
+            ..ChktexOptions::default()
+        },
+        latexindent: LatexindentOptions {
+            executable: Some(String::new()),
+            local: Some(String::new()),
+            ..LatexindentOptions::default()
+        },
+        forward_search: Some(ForwardSearchOptions {
+            executable: Some(String::new()),
+            args: Some(vec![String::new()]),
+            process_name: Some(String::new()),
+            platform: platform_overrides(),
+        }),
+        math_preview: MathPreviewOptions {
+            executable: Some(String::new()),
+            args: Some(vec![String::new()]),
+        },
+        lint_rules: vec![LintRule {
+            pattern: Some(String::new()),
+            command: Some(String::new()),
+            message: String::new(),
+            severity: LintSeverity::default(),
+            replacement: Some(String::new()),
+        }],
+        ..Options::default()
     };
+
+    let mut schema = serde_json::to_value(options).unwrap();
+    if let Some(latex) = schema.get_mut("latex").and_then(Value::as_object_mut) {
+        for commands in latex.values_mut() {
+            *commands = Value::Array(vec![Value::String(String::new())]);
+        }
+    }
+    schema
+}
+
+fn platform_overrides() -> PlatformOverrides {
+    let command = CommandOptions {
+        executable: Some(String::new()),
+        args: Some(vec![String::new()]),
+    };
+
+    PlatformOverrides {
+        windows: Some(command.clone()),
+        macos: Some(command.clone()),
+        linux: Some(command),
+    }
+}
+
+/// Checks the given configuration section for unknown keys,
+/// deprecated option names and values of the wrong type.
+fn validate_config(config: &Value) -> Vec<String> {
+    let mut warnings = Vec::new();
+    validate_value(config, &options_schema(), "", &mut warnings);
+    warnings
+}
+
+fn validate_value(value: &Value, schema: &Value, path: &str, warnings: &mut Vec<String>) {
+    match (value, schema) {
+        (Value::Null, _) => {}
+        (Value::Object(object), Value::Object(schema_object)) => {
+            for (key, value) in object {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+
+                if let Some(schema) = schema_object.get(key) {
+                    validate_value(value, schema, &path, warnings);
+                } else if let Some((_, replacement)) =
+                    DEPRECATED_OPTIONS.iter().find(|(name, _)| *name == path)
+                {
+                    warnings.push(format!(
+                        "The option `texlab.{}` is deprecated, use `texlab.{}` instead",
+                        path, replacement
+                    ));
+                } else if DEPRECATED_OPTIONS
+                    .iter()
+                    .any(|(name, _)| name.starts_with(&format!("{}.", path)))
+                {
+                    validate_value(value, &Value::Object(Default::default()), &path, warnings);
+                } else {
+                    let suggestion = schema_object
+                        .keys()
+                        .find(|name| normalize_key(name) == normalize_key(key));
+
+                    warnings.push(match suggestion {
+                        Some(name) => {
+                            format!("Unknown option `texlab.{}`, did you mean `{}`?", path, name)
+                        }
+                        None => format!("Unknown option `texlab.{}`", path),
+                    });
+                }
+            }
+        }
+        (Value::Array(array), Value::Array(schema_array)) => {
+            if let Some(schema) = schema_array.first() {
+                for value in array {
+                    validate_value(value, schema, path, warnings);
+                }
+            }
+        }
+        (Value::Bool(_), Value::Bool(_))
+        | (Value::Number(_), Value::Number(_))
+        | (Value::String(_), Value::String(_)) => {}
+        (_, Value::Null) => {}
+        (value, schema) => {
+            warnings.push(format!(
+                "Expected {} for option `texlab.{}` but found {}",
+                type_name(schema),
+                path,
+                type_name(value)
+            ));
+        }
+    }
+}
+
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_valid() {
+        let config = json!({
+            "build": { "executable": "tectonic", "args": ["%f"], "onSave": true },
+            "forwardSearch": {
+                "executable": "zathura",
+                "args": [],
+                "macos": { "executable": "/Applications/Skim.app/Contents/SharedSupport/displayline" }
+            },
+            "latex": { "citationCommands": ["mycite"] },
+            "auxDirectory": null
+        });
+
+        assert_eq!(validate_config(&config), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_unknown_key() {
+        let config = json!({ "build": { "on_save": true, "foo": 1 } });
+        assert_eq!(
+            validate_config(&config),
+            vec![
+                "Unknown option `texlab.build.foo`".to_string(),
+                "Unknown option `texlab.build.on_save`, did you mean `onSave`?".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_mismatch() {
+        let config = json!({ "build": { "args": "-pdf" }, "diagnosticsDelay": "300" });
+        assert_eq!(
+            validate_config(&config),
+            vec![
+                "Expected an array for option `texlab.build.args` but found a string".to_string(),
+                "Expected a number for option `texlab.diagnosticsDelay` but found a string"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_project_config() {
+        let config = parse_project_config(indoc! {
+            r#"
+                [build]
+                executable = "tectonic"
+                args = ["%f"]
+
+                [latex]
+                citationCommands = ["mycite"]
+            "#
+        })
+        .unwrap();
+
+        assert_eq!(
+            config,
+            json!({
+                "build": { "executable": "tectonic", "args": ["%f"] },
+                "latex": { "citationCommands": ["mycite"] }
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_config() {
+        let mut config = json!({
+            "build": { "executable": "tectonic", "onSave": true },
+            "chktex": { "onEdit": true }
+        });
+
+        merge_config(
+            &mut config,
+            json!({
+                "build": { "executable": "latexmk", "args": null },
+                "chktex": null,
+                "auxDirectory": "build"
+            }),
+        );
+
+        assert_eq!(
+            config,
+            json!({
+                "build": { "executable": "latexmk", "onSave": true },
+                "chktex": { "onEdit": true },
+                "auxDirectory": "build"
+            })
+        );
+    }
+
+    #[test]
+    fn test_deprecated() {
+        let config = json!({
+            "latex": { "build": { "executable": "latexmk" }, "lint": { "onSave": true } }
+        });
+        assert_eq!(
+            validate_config(&config),
+            vec![
+                "The option `texlab.latex.build` is deprecated, use `texlab.build` instead"
+                    .to_string(),
+                "The option `texlab.latex.lint.onSave` is deprecated, use `texlab.chktex.onOpenAndSave` instead"
+                    .to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_keeps_options() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let context = ServerContext::new(temp_dir.path().to_path_buf());
+        let (sender, receiver) = crossbeam_channel::unbounded();
+
+        *context.client_config.lock().unwrap() = json!({ "build": { "executable": "tectonic" } });
+        reload_config(&sender, &context);
+        assert!(receiver.try_recv().is_err());
+
+        *context.client_config.lock().unwrap() = json!({ "build": { "executable": 1 } });
+        reload_config(&sender, &context);
+        assert_eq!(
+            context.options.read().unwrap().build.executable(),
+            "tectonic"
+        );
+        assert!(receiver.try_recv().is_ok());
+    }
 }
@@ -7,6 +7,8 @@ use lsp_types::{ClientCapabilities, ClientInfo};
 
 use crate::{
     distro::{DistributionKind, Resolver},
+    internal_state::PanicReports,
+    progress::ProgressTokens,
     Options,
 };
 
@@ -18,6 +20,11 @@ pub struct ServerContext {
     pub client_capabilities: Mutex<ClientCapabilities>,
     pub client_info: Mutex<Option<ClientInfo>>,
     pub options: RwLock<Options>,
+    pub client_config: Mutex<serde_json::Value>,
+    pub config_warnings: Mutex<Vec<String>>,
+    pub progress_tokens: ProgressTokens,
+    pub panic_reports: PanicReports,
+    pub preview_directory: Mutex<Option<tempfile::TempDir>>,
 }
 
 impl ServerContext {
@@ -29,6 +36,11 @@ impl ServerContext {
             client_capabilities: Mutex::default(),
             client_info: Mutex::default(),
             options: RwLock::default(),
+            client_config: Mutex::new(serde_json::Value::Null),
+            config_warnings: Mutex::default(),
+            progress_tokens: ProgressTokens::default(),
+            panic_reports: PanicReports::default(),
+            preview_directory: Mutex::default(),
         }
     }
 }
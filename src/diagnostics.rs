@@ -1,55 +1,278 @@
+mod bib_units;
 mod bibtex;
 mod build_log;
 mod chktex;
+mod commands;
 mod debouncer;
+mod floats;
+mod includes;
+mod index_entries;
+mod label_kinds;
+mod language_tool;
 mod latex;
+mod lint_rules;
+mod makeindex;
+mod markdown;
+mod math_labels;
+mod packages;
+mod placeholders;
+mod tables;
+mod ties;
+mod todos;
+mod unicode;
+mod units;
+mod whitespace;
 
 use std::sync::Arc;
 
+use cstree::TextSize;
 use lsp_types::Diagnostic;
 use multimap::MultiMap;
 use rustc_hash::FxHashMap;
 
-use crate::{Options, Uri, Workspace};
+use crate::{is_generated_file, is_latex_source, Document, LineIndexExt, Options, Uri, Workspace};
 
-pub use self::debouncer::{DiagnosticsDebouncer, DiagnosticsMessage};
+pub use self::{
+    debouncer::{DiagnosticsDebouncer, DiagnosticsMessage},
+    includes::{INCLUDE_EXTENSION_CODE, NESTED_INCLUDE_CODE, PREAMBLE_INCLUDE_CODE},
+    label_kinds::LABEL_KIND_CODE,
+    language_tool::{check_language_tool, LANGUAGE_TOOL_SOURCE},
+    lint_rules::LINT_RULE_CODE,
+    math_labels::{
+        find_math_context, MathContext, INLINE_LABEL_CODE, NUMBERED_ENVIRONMENTS,
+        UNNUMBERED_LABEL_CODE,
+    },
+    packages::{find_package_insert_position, MISSING_PACKAGE_CODE},
+    placeholders::PLACEHOLDER_TEXT_CODE,
+    ties::{FOOTNOTE_TIE_CODE, MISSING_TIE_CODE},
+    unicode::{suspicious_char_replacement, HOMOGLYPH_CODE, INVISIBLE_CHARACTER_CODE},
+    whitespace::{MIXED_LINE_ENDINGS_CODE, TRAILING_WHITESPACE_CODE},
+};
 
 use self::{
-    bibtex::analyze_bibtex_static, build_log::analyze_build_log_static,
-    chktex::analyze_latex_chktex, latex::analyze_latex_static,
+    bib_units::analyze_latex_bib_units, bibtex::analyze_bibtex_static,
+    build_log::analyze_build_log_static, chktex::analyze_latex_chktex,
+    commands::analyze_command_definitions, floats::analyze_latex_floats,
+    includes::analyze_latex_includes, index_entries::analyze_index_entries,
+    label_kinds::analyze_latex_label_kinds, latex::analyze_latex_static,
+    lint_rules::analyze_latex_lint_rules, makeindex::analyze_makeindex_logs,
+    markdown::analyze_markdown_static, math_labels::analyze_latex_math_labels,
+    packages::analyze_latex_packages, placeholders::analyze_latex_placeholders,
+    tables::analyze_latex_tables, ties::analyze_latex_ties, todos::analyze_latex_todos,
+    unicode::analyze_unicode_characters, units::analyze_latex_units,
+    whitespace::analyze_whitespace,
 };
 
+/// Documents with longer lines (usually generated by tools) only get the diagnostics
+/// of the build log and the parser since the other checks do not scale well.
+const MAX_LINE_LENGTH: u32 = 100_000;
+
+fn has_long_lines(document: &Document) -> bool {
+    let text_len = TextSize::of(document.text.as_str());
+    document.line_index.max_line_length(text_len) > TextSize::from(MAX_LINE_LENGTH)
+}
+
 #[derive(Default)]
 pub struct DiagnosticsManager {
     static_diagnostics: FxHashMap<Arc<Uri>, MultiMap<Arc<Uri>, Diagnostic>>,
     chktex_diagnostics: MultiMap<Arc<Uri>, Diagnostic>,
+    /// The diagnostics of LanguageTool and the text that they refer to.
+    language_tool_diagnostics: FxHashMap<Arc<Uri>, (String, Vec<Diagnostic>)>,
 }
 
 impl DiagnosticsManager {
-    pub fn update_static(&mut self, workspace: &dyn Workspace, uri: Arc<Uri>) {
+    pub fn update_static(&mut self, workspace: &dyn Workspace, uri: Arc<Uri>, options: &Options) {
+        // Generated files are rewritten by every build, so their diagnostics are not useful.
+        if is_generated_file(&uri) {
+            self.static_diagnostics.remove(&uri);
+            return;
+        }
+
         let mut diagnostics_by_uri = MultiMap::new();
         analyze_build_log_static(workspace, &mut diagnostics_by_uri, &uri);
+        analyze_makeindex_logs(workspace, &mut diagnostics_by_uri, &uri);
         analyze_bibtex_static(workspace, &mut diagnostics_by_uri, &uri);
-        analyze_latex_static(workspace, &mut diagnostics_by_uri, &uri);
+
+        // Packages, classes and generated files are not checked like the documents of the user.
+        let is_latex_source = is_latex_source(&uri);
+        if is_latex_source {
+            analyze_latex_static(workspace, &mut diagnostics_by_uri, &uri);
+        }
+
+        if workspace
+            .get(&uri)
+            .map_or(false, |document| has_long_lines(&document))
+        {
+            log::info!("Skipping the analysis of {} because of its long lines", uri);
+            self.static_diagnostics.insert(uri, diagnostics_by_uri);
+            return;
+        }
+
+        if is_latex_source {
+            analyze_latex_floats(
+                workspace,
+                &mut diagnostics_by_uri,
+                &uri,
+                &options.float_diagnostics,
+            );
+            analyze_latex_ties(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_units(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_command_definitions(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_includes(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_math_labels(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_tables(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_index_entries(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_bib_units(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_label_kinds(workspace, &mut diagnostics_by_uri, &uri);
+            analyze_latex_packages(workspace, &mut diagnostics_by_uri, &uri);
+        }
+
+        analyze_latex_todos(workspace, &mut diagnostics_by_uri, &uri);
+        analyze_latex_placeholders(workspace, &mut diagnostics_by_uri, &uri);
+        analyze_markdown_static(workspace, &mut diagnostics_by_uri, &uri);
+        analyze_unicode_characters(workspace, &mut diagnostics_by_uri, &uri);
+        analyze_latex_lint_rules(workspace, &mut diagnostics_by_uri, &uri, options);
+        analyze_whitespace(workspace, &mut diagnostics_by_uri, &uri);
         self.static_diagnostics.insert(uri, diagnostics_by_uri);
     }
 
     pub fn update_chktex(&mut self, workspace: &dyn Workspace, uri: Arc<Uri>, options: &Options) {
+        if is_generated_file(&uri) {
+            return;
+        }
+
         analyze_latex_chktex(workspace, &mut self.chktex_diagnostics, &uri, options);
     }
 
-    pub fn publish(&self, uri: Arc<Uri>) -> Vec<Diagnostic> {
+    /// Stores the LanguageTool diagnostics of the given version of a document.
+    /// They are only published as long as the text of the document does not change
+    /// since their ranges (and the replacements of the quick fixes) would be wrong afterwards.
+    pub fn update_language_tool(&mut self, document: &Document, diagnostics: Vec<Diagnostic>) {
+        self.language_tool_diagnostics.insert(
+            Arc::clone(&document.uri),
+            (document.text.clone(), diagnostics),
+        );
+    }
+
+    pub fn publish(&self, document: &Document) -> Vec<Diagnostic> {
+        let uri = &document.uri;
         let mut all_diagnostics = Vec::new();
         for diagnostics_by_uri in self.static_diagnostics.values() {
-            if let Some(diagnostics) = diagnostics_by_uri.get_vec(&uri) {
+            if let Some(diagnostics) = diagnostics_by_uri.get_vec(uri) {
                 all_diagnostics.append(&mut diagnostics.clone());
             }
         }
 
-        if let Some(diagnostics) = self.chktex_diagnostics.get_vec(&uri) {
+        if let Some(diagnostics) = self.chktex_diagnostics.get_vec(uri) {
             all_diagnostics.append(&mut diagnostics.clone());
         }
 
+        if let Some((text, diagnostics)) = self.language_tool_diagnostics.get(uri) {
+            if *text == document.text {
+                all_diagnostics.append(&mut diagnostics.clone());
+            }
+        }
+
+        if let Some(data) = document.data.as_latex() {
+            let code_chunks = &data.extras.code_chunks;
+            all_diagnostics.retain(|diagnostic| {
+                let offset = document.line_index.offset_lsp(diagnostic.range.start);
+                !code_chunks
+                    .iter()
+                    .any(|chunk| chunk.contains_inclusive(offset))
+            });
+        }
+
         all_diagnostics
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{NumberOrString, Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_math_label_codes(file_name: &str, text: &str) -> Vec<NumberOrString> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join(file_name)).unwrap());
+        let document = workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut manager = DiagnosticsManager::default();
+        manager.update_static(&workspace, uri, &Options::default());
+        manager
+            .publish(&document)
+            .into_iter()
+            .filter_map(|diagnostic| diagnostic.code)
+            .filter(|code| *code == NumberOrString::Number(UNNUMBERED_LABEL_CODE))
+            .collect()
+    }
+
+    #[test]
+    fn test_literate_document() {
+        let text = "\\begin{equation*}\\label{eq:foo}x\\end{equation*}\n<<>>=\nx <- 1\n@\n";
+        let expected = vec![NumberOrString::Number(UNNUMBERED_LABEL_CODE)];
+        assert_eq!(find_math_label_codes("main.tex", text), expected);
+        assert_eq!(find_math_label_codes("main.Rnw", text), expected);
+        assert_eq!(find_math_label_codes("main.Pnw", text), expected);
+        assert!(find_math_label_codes("main.sty", text).is_empty());
+    }
+
+    #[test]
+    fn test_generated_file() {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let text = "\\contentsline {section}{TODO: Foo}{1}   \n";
+        let mut manager = DiagnosticsManager::default();
+        for (file_name, is_empty) in [("main.tex", false), ("main.toc", true)] {
+            let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join(file_name)).unwrap());
+            let document = workspace.open(
+                Arc::clone(&uri),
+                text.to_string(),
+                DocumentLanguage::Latex,
+                WorkspaceSource::Client,
+            );
+
+            manager.update_static(&workspace, uri, &Options::default());
+            assert_eq!(manager.publish(&document).is_empty(), is_empty);
+        }
+    }
+
+    #[test]
+    fn test_outdated_language_tool() {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        let parse = |text: &str| {
+            Document::parse(
+                Arc::clone(&context),
+                Arc::clone(&uri),
+                text.to_string(),
+                DocumentLanguage::Latex,
+            )
+        };
+
+        let old_document = parse("This are wrong.");
+        let diagnostic = Diagnostic {
+            source: Some(LANGUAGE_TOOL_SOURCE.to_string()),
+            ..Diagnostic::new_simple(
+                Range::new(Position::new(0, 5), Position::new(0, 8)),
+                "Use \"is\".".to_string(),
+            )
+        };
+
+        let mut manager = DiagnosticsManager::default();
+        manager.update_language_tool(&old_document, vec![diagnostic.clone()]);
+        assert_eq!(manager.publish(&old_document), vec![diagnostic]);
+
+        let new_document = parse("Foo. This are wrong.");
+        assert!(manager.publish(&new_document).is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    syntax::{bibtex, latex, CstNode},
+    BibScopes, Document, LineIndexExt, Uri, Workspace,
+};
+
+/// Reports citations of entries that exist in the project
+/// but not in the bibliographies of the unit that contains the citation.
+pub fn analyze_latex_bib_units(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let project_keys = entry_keys(subset.documents.iter().map(|document| &**document));
+    let scopes = BibScopes::new(&subset, &document)?;
+    let mut keys_by_scope = FxHashMap::default();
+
+    for citation in data.root.descendants().filter_map(latex::Citation::cast) {
+        let scope = match scopes.find(citation.small_range().start()) {
+            Some(scope) => scope,
+            None => continue,
+        };
+
+        let scope_keys = keys_by_scope.entry(scope).or_insert_with_key(|scope| {
+            entry_keys(
+                subset
+                    .documents
+                    .iter()
+                    .filter(|document| scope.contains(&document.uri))
+                    .map(|document| &**document),
+            )
+        });
+
+        for key in citation
+            .key_list()
+            .into_iter()
+            .flat_map(|list| list.keys().collect::<Vec<_>>())
+        {
+            let name = key.to_string();
+            if !project_keys.contains(&name) || scope_keys.contains(&name) {
+                continue;
+            }
+
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document.line_index.line_col_lsp_range(key.small_range()),
+                    severity: Some(DiagnosticSeverity::Warning),
+                    code: Some(NumberOrString::Number(33)),
+                    code_description: None,
+                    source: Some("texlab".to_string()),
+                    message: format!(
+                        "The entry \"{}\" is not part of the bibliography of this unit",
+                        name
+                    ),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                },
+            );
+        }
+    }
+
+    Some(())
+}
+
+fn entry_keys<'a>(documents: impl Iterator<Item = &'a Document>) -> FxHashSet<String> {
+    documents
+        .filter_map(|document| document.data.as_bibtex())
+        .flat_map(|data| data.root.children().filter_map(bibtex::Entry::cast))
+        .filter_map(|entry| Some(entry.key()?.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{
+        create_workspace_fast, DocumentLanguage, RangeExt, ServerContext, WorkspaceSource,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_refsection() {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri =
+            |name: &str| Arc::new(Uri::from_file_path(std::env::temp_dir().join(name)).unwrap());
+        let files = [
+            (
+                "main.tex",
+                indoc! {r#"
+                    \documentclass{article}
+                    \usepackage{biblatex}
+                    \begin{document}
+                    \cite{bar}
+                    \begin{refsection}[local.bib]
+                    \cite{foo, bar}
+                    \end{refsection}
+                    \begin{refsection}[other.bib]
+                    \cite{bar}
+                    \end{refsection}
+                    \end{document}"#},
+            ),
+            ("local.bib", "@article{foo,}"),
+            ("other.bib", "@article{bar,}"),
+        ];
+
+        for (name, text) in &files {
+            let language = DocumentLanguage::by_path(&std::env::temp_dir().join(name)).unwrap();
+            workspace.open(
+                uri(name),
+                text.to_string(),
+                language,
+                WorkspaceSource::Client,
+            );
+        }
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_bib_units(&workspace, &mut diagnostics_by_uri, &uri("main.tex"));
+        let ranges: Vec<_> = diagnostics_by_uri
+            .get_vec(&uri("main.tex"))
+            .unwrap()
+            .iter()
+            .map(|diagnostic| diagnostic.range)
+            .collect();
+
+        assert_eq!(ranges, vec![Range::new_simple(5, 11, 5, 14)]);
+    }
+}
@@ -11,7 +11,7 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use tempfile::tempdir;
 
-use crate::{Options, RangeExt, Uri, Workspace};
+use crate::{Options, Placeholders, RangeExt, Uri, Workspace};
 
 pub fn analyze_latex_chktex(
     workspace: &dyn Workspace,
@@ -40,10 +40,18 @@ pub fn analyze_latex_chktex(
         })
         .unwrap_or_else(|| ".".into());
 
+    let tex_file = document.uri.to_file_path().ok();
+    let placeholders = Placeholders {
+        tex_file: tex_file.as_deref(),
+        workspace_folder: Some(&current_dir),
+        ..Placeholders::default()
+    };
+    let executable = placeholders.expand(&options.chktex.executable());
+
     diagnostics_by_uri.remove(uri);
     diagnostics_by_uri.insert_many(
         Arc::clone(&document.uri),
-        lint(&document.text, &current_dir).unwrap_or_default(),
+        lint(&executable, &document.text, &current_dir).unwrap_or_default(),
     );
     Some(())
 }
@@ -51,7 +59,7 @@ pub fn analyze_latex_chktex(
 pub static LINE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new("(\\d+):(\\d+):(\\d+):(\\w+):(\\w+):(.*)").unwrap());
 
-fn lint(text: &str, current_dir: &Path) -> io::Result<Vec<Diagnostic>> {
+fn lint(executable: &str, text: &str, current_dir: &Path) -> io::Result<Vec<Diagnostic>> {
     let directory = tempdir()?;
     fs::write(directory.path().join("file.tex"), text)?;
     let _ = fs::copy(
@@ -59,7 +67,7 @@ fn lint(text: &str, current_dir: &Path) -> io::Result<Vec<Diagnostic>> {
         directory.path().join("chktexrc"),
     );
 
-    let output = Command::new("chktex")
+    let output = Command::new(executable)
         .args(&["-I0", "-f%l:%c:%d:%k:%n:%m\n", "file.tex"])
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use cstree::TextRange;
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+};
+use multimap::MultiMap;
+
+use crate::{
+    component_db::COMPONENT_DATABASE,
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, Uri, Workspace,
+};
+
+struct CommandDefinition {
+    document: Arc<Document>,
+    command: String,
+    name: String,
+    name_range: TextRange,
+    implementation: String,
+}
+
+pub fn analyze_command_definitions(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let definitions: Vec<_> = subset
+        .documents
+        .iter()
+        .flat_map(|document| find_definitions(document))
+        .collect();
+
+    let components = COMPONENT_DATABASE.linked_components(&subset);
+    for definition in definitions
+        .iter()
+        .filter(|definition| definition.document.uri == document.uri)
+    {
+        let existing_component = components.iter().find(|component| {
+            component
+                .commands
+                .iter()
+                .any(|command| command.name == definition.name[1..])
+        });
+
+        if let Some(component) = existing_component {
+            if definition.command.starts_with("\\newcommand") {
+                let origin = match component.file_names.first() {
+                    Some(file_name) => file_name.to_string(),
+                    None => "the LaTeX kernel".to_string(),
+                };
+
+                diagnostics_by_uri.insert(
+                    Arc::clone(&document.uri),
+                    Diagnostic {
+                        range: document
+                            .line_index
+                            .line_col_lsp_range(definition.name_range),
+                        severity: Some(DiagnosticSeverity::Warning),
+                        code: Some(NumberOrString::Number(19)),
+                        code_description: None,
+                        source: Some("texlab".to_string()),
+                        message: format!(
+                            "Command {} is already defined by {}; use \\renewcommand or \\providecommand",
+                            definition.name, origin
+                        ),
+                        related_information: None,
+                        tags: None,
+                        data: None,
+                    },
+                );
+            }
+        }
+
+        let conflicts: Vec<_> = definitions
+            .iter()
+            .filter(|other| {
+                other.document.uri != definition.document.uri
+                    && other.name == definition.name
+                    && other.implementation != definition.implementation
+                    && !other.command.starts_with("\\providecommand")
+                    && !definition.command.starts_with("\\providecommand")
+            })
+            .map(|other| DiagnosticRelatedInformation {
+                location: Location::new(
+                    other.document.uri.as_ref().clone().into(),
+                    other
+                        .document
+                        .line_index
+                        .line_col_lsp_range(other.name_range),
+                ),
+                message: format!("{} is defined here", other.name),
+            })
+            .collect();
+
+        if !conflicts.is_empty() {
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document
+                        .line_index
+                        .line_col_lsp_range(definition.name_range),
+                    severity: Some(DiagnosticSeverity::Warning),
+                    code: Some(NumberOrString::Number(20)),
+                    code_description: None,
+                    source: Some("texlab".to_string()),
+                    message: format!(
+                        "Command {} is defined differently in another file",
+                        definition.name
+                    ),
+                    related_information: Some(conflicts),
+                    tags: None,
+                    data: None,
+                },
+            );
+        }
+    }
+
+    Some(())
+}
+
+fn find_definitions(document: &Arc<Document>) -> Vec<CommandDefinition> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    data.root
+        .descendants()
+        .filter_map(latex::CommandDefinition::cast)
+        .filter_map(|definition| {
+            let command = definition.command()?.text().to_string();
+            let name = definition.name()?.command()?;
+            let implementation = definition
+                .implementation()?
+                .syntax()
+                .text()
+                .to_string()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            Some(CommandDefinition {
+                document: Arc::clone(document),
+                command,
+                name: name.text().to_string(),
+                name_range: name.text_range(),
+                implementation,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(main: &str, other: &str) -> Vec<Diagnostic> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::parse("http://example.com/main.tex").unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            main.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        workspace.open(
+            Arc::new(Uri::parse("http://example.com/other.tex").unwrap()),
+            other.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_command_definitions(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri.remove(&uri).unwrap_or_default()
+    }
+
+    #[test]
+    fn test_existing_command() {
+        let diagnostics = find_diagnostics(r#"\newcommand{\section}{foo}"#, "");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(NumberOrString::Number(19)));
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 12), Position::new(0, 20))
+        );
+        assert!(diagnostics[0].message.contains("the LaTeX kernel"));
+    }
+
+    #[test]
+    fn test_existing_command_redefined() {
+        assert!(find_diagnostics(r#"\renewcommand{\section}{foo}"#, "").is_empty());
+        assert!(find_diagnostics(r#"\providecommand{\section}{foo}"#, "").is_empty());
+    }
+
+    #[test]
+    fn test_new_command() {
+        assert!(find_diagnostics(r#"\newcommand{\foo}{bar}"#, "").is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_definitions() {
+        let diagnostics = find_diagnostics(
+            r#"\include{other}\newcommand{\foo}{bar}"#,
+            r#"\newcommand{\foo}{baz}"#,
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].code, Some(NumberOrString::Number(20)));
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 27), Position::new(0, 31))
+        );
+
+        let related_information = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(related_information.len(), 1);
+        assert_eq!(
+            related_information[0].location.uri.as_str(),
+            "http://example.com/other.tex"
+        );
+        assert_eq!(
+            related_information[0].location.range,
+            Range::new(Position::new(0, 12), Position::new(0, 16))
+        );
+    }
+
+    #[test]
+    fn test_equal_definitions() {
+        let main = r#"\include{other}\newcommand{\foo}{bar}"#;
+        assert!(find_diagnostics(main, r#"\newcommand{\foo}{bar}"#).is_empty());
+        assert!(find_diagnostics(main, r#"\providecommand{\foo}{baz}"#).is_empty());
+    }
+}
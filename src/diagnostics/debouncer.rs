@@ -7,7 +7,7 @@ use std::{
 use chashmap::CHashMap;
 use crossbeam_channel::Sender;
 
-use crate::{Document, ServerContext, Uri, Workspace};
+use crate::{internal_state::catch_panic, Document, ServerContext, Uri, Workspace};
 
 pub enum DiagnosticsMessage {
     Analyze {
@@ -54,10 +54,17 @@ impl DiagnosticsDebouncer {
 
                 let last_task_time_by_uri = Arc::clone(&last_task_time_by_uri);
                 let action = action.clone();
+                let context = Arc::clone(&context);
                 pool.execute(move || {
                     thread::sleep(Duration::from_millis(delay));
                     last_task_time_by_uri.insert(Arc::clone(&document.uri), Instant::now());
-                    action(workspace, document);
+                    let _ = catch_panic(
+                        &context,
+                        "diagnostics",
+                        Some(&document),
+                        serde_json::Value::Null,
+                        || action(workspace, Arc::clone(&document)),
+                    );
                 });
             }
         });
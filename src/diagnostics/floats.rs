@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use cstree::TextRange;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{
+    syntax::{
+        latex::{self, HasBrack, HasKeyValueBody},
+        CstNode,
+    },
+    Document, FloatDiagnosticsOptions, LineIndexExt, Uri, Workspace,
+};
+
+const FLOAT_ENVIRONMENTS: &[&str] = &["figure", "figure*", "table", "table*"];
+
+/// Lengths that the content of a float should not exceed.
+const MAX_LENGTHS: &[&str] = &[
+    "\\textwidth",
+    "\\linewidth",
+    "\\columnwidth",
+    "\\textheight",
+];
+
+pub fn analyze_latex_floats(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+    options: &FloatDiagnosticsOptions,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    for environment in data.root.descendants().filter_map(latex::Environment::cast) {
+        analyze_float(&document, diagnostics_by_uri, environment, options);
+    }
+
+    Some(())
+}
+
+fn analyze_float(
+    document: &Document,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    environment: latex::Environment,
+    options: &FloatDiagnosticsOptions,
+) -> Option<()> {
+    let begin = environment.begin()?;
+    let name = begin.name()?.key()?;
+    let name_text = name.to_string();
+    if !FLOAT_ENVIRONMENTS.contains(&name_text.as_str()) {
+        return None;
+    }
+
+    let mut report = |range: TextRange, code: i32, message: String| {
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(range),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    };
+
+    let captions: Vec<_> = environment
+        .syntax()
+        .descendants()
+        .filter_map(latex::Caption::cast)
+        .collect();
+
+    let labels: Vec<_> = environment
+        .syntax()
+        .descendants()
+        .filter_map(latex::LabelDefinition::cast)
+        .collect();
+
+    if options.missing_caption && captions.is_empty() {
+        report(
+            name.small_range(),
+            11,
+            format!("Missing \\caption in \"{}\"", name_text),
+        );
+    }
+
+    if options.missing_label && labels.is_empty() {
+        report(
+            name.small_range(),
+            12,
+            format!("Missing \\label in \"{}\"", name_text),
+        );
+    }
+
+    if options.label_before_caption {
+        // The label refers to the caption that precedes it inside of the same environment.
+        for label in &labels {
+            let scope = enclosing_environment(label.syntax());
+            let start = label.small_range().start();
+            let first_caption = captions
+                .iter()
+                .filter(|caption| enclosing_environment(caption.syntax()) == scope)
+                .map(|caption| caption.small_range().start())
+                .min();
+
+            if first_caption.map_or(false, |caption| caption > start) {
+                report(
+                    label.small_range(),
+                    13,
+                    "\\label appears before \\caption and refers to the wrong counter".to_string(),
+                );
+            }
+        }
+    }
+
+    if options.placement {
+        let group = begin.options();
+        let placement = group.and_then(|group| group.content_text());
+        if let Some((group, placement)) = group.zip(placement) {
+            if matches!(placement.as_str(), "h" | "h!") {
+                report(
+                    group.small_range(),
+                    14,
+                    format!(
+                        "Float specifier \"{}\" is changed to \"{}t\", consider using \"htbp\"",
+                        placement, placement
+                    ),
+                );
+            }
+        }
+    }
+
+    if options.oversized_content {
+        for include in environment
+            .syntax()
+            .descendants()
+            .filter_map(latex::Include::cast)
+            .filter(|include| include.syntax().kind() == latex::GRAPHICS_INCLUDE)
+        {
+            analyze_graphics_size(include, &mut report);
+        }
+    }
+
+    Some(())
+}
+
+fn analyze_graphics_size(
+    include: latex::Include,
+    report: &mut impl FnMut(TextRange, i32, String),
+) -> Option<()> {
+    for pair in include.options()?.body()?.pairs() {
+        let key = match pair.key() {
+            Some(key) => key.to_string(),
+            None => continue,
+        };
+        if key != "width" && key != "height" {
+            continue;
+        }
+
+        let value = match pair.value() {
+            Some(value) => value,
+            None => continue,
+        };
+        let value_text = value.syntax().text().to_string();
+        let value_text = value_text.trim();
+        let (factor, length) = match MAX_LENGTHS
+            .iter()
+            .find_map(|length| Some((value_text.strip_suffix(length)?, *length)))
+        {
+            Some((factor, length)) => (factor.trim(), length),
+            None => continue,
+        };
+
+        let factor = if factor.is_empty() {
+            1.0
+        } else {
+            match factor.parse::<f64>() {
+                Ok(factor) => factor,
+                Err(_) => continue,
+            }
+        };
+
+        if factor > 1.0 {
+            report(
+                value.small_range(),
+                15,
+                format!("Content of the float exceeds {}", length),
+            );
+        }
+    }
+    Some(())
+}
+
+fn enclosing_environment(node: &latex::SyntaxNode) -> Option<TextRange> {
+    node.ancestors()
+        .find(|node| node.kind() == latex::ENVIRONMENT)
+        .map(|node| node.text_range())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_codes(text: &str, options: &FloatDiagnosticsOptions) -> Vec<Option<NumberOrString>> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_floats(&workspace, &mut diagnostics_by_uri, &uri, options);
+        diagnostics_by_uri
+            .get_vec(&uri)
+            .map(|diagnostics| diagnostics.iter().map(|d| d.code.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    fn codes(codes: &[i32]) -> Vec<Option<NumberOrString>> {
+        codes
+            .iter()
+            .map(|code| Some(NumberOrString::Number(*code)))
+            .collect()
+    }
+
+    #[test]
+    fn test_valid_float() {
+        let text = "\\begin{figure}\\caption{Foo}\\label{fig:foo}\\end{figure}";
+        let options = FloatDiagnosticsOptions {
+            placement: true,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert!(find_codes(text, &options).is_empty());
+    }
+
+    #[test]
+    fn test_missing_caption() {
+        let text = "\\begin{figure}\\label{fig:foo}\\end{figure}";
+        assert_eq!(
+            find_codes(text, &FloatDiagnosticsOptions::default()),
+            codes(&[11])
+        );
+
+        let options = FloatDiagnosticsOptions {
+            missing_caption: false,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert!(find_codes(text, &options).is_empty());
+    }
+
+    #[test]
+    fn test_missing_label() {
+        let text = "\\begin{table}\\caption{Foo}\\end{table}";
+        assert_eq!(
+            find_codes(text, &FloatDiagnosticsOptions::default()),
+            codes(&[12])
+        );
+
+        let options = FloatDiagnosticsOptions {
+            missing_label: false,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert!(find_codes(text, &options).is_empty());
+    }
+
+    #[test]
+    fn test_label_before_caption() {
+        let text = "\\begin{figure}\\label{fig:foo}\\caption{Foo}\\end{figure}";
+        assert_eq!(
+            find_codes(text, &FloatDiagnosticsOptions::default()),
+            codes(&[13])
+        );
+
+        let options = FloatDiagnosticsOptions {
+            label_before_caption: false,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert!(find_codes(text, &options).is_empty());
+    }
+
+    #[test]
+    fn test_placement() {
+        let text = "\\begin{figure}[h]\\caption{Foo}\\label{fig:foo}\\end{figure}";
+        assert!(find_codes(text, &FloatDiagnosticsOptions::default()).is_empty());
+
+        let options = FloatDiagnosticsOptions {
+            placement: true,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert_eq!(find_codes(text, &options), codes(&[14]));
+    }
+
+    #[test]
+    fn test_oversized_content() {
+        let text = "\\begin{figure}\\includegraphics[width=1.5\\textwidth]{foo}\\caption{Foo}\\label{fig:foo}\\end{figure}";
+        assert_eq!(
+            find_codes(text, &FloatDiagnosticsOptions::default()),
+            codes(&[15])
+        );
+
+        let options = FloatDiagnosticsOptions {
+            oversized_content: false,
+            ..FloatDiagnosticsOptions::default()
+        };
+        assert!(find_codes(text, &options).is_empty());
+    }
+
+    #[test]
+    fn test_oversized_content_invalid_factor() {
+        let text = "\\begin{figure}\\includegraphics[width=\\x\\textwidth, height=2\\textheight]{foo}\\caption{Foo}\\label{fig:foo}\\end{figure}";
+        assert_eq!(
+            find_codes(text, &FloatDiagnosticsOptions::default()),
+            codes(&[15])
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use std::sync::Arc;
+
+use cstree::TextSize;
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+};
+use multimap::MultiMap;
+
+use crate::{
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, Uri, Workspace,
+};
+
+/// The diagnostic code of an `\include` in the preamble.
+pub const PREAMBLE_INCLUDE_CODE: i32 = 22;
+
+/// The diagnostic code of an `\include` inside of a file that is included with `\include`.
+pub const NESTED_INCLUDE_CODE: i32 = 23;
+
+/// The diagnostic code of an `\include` with a file extension.
+pub const INCLUDE_EXTENSION_CODE: i32 = 24;
+
+pub fn analyze_latex_includes(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let includes: Vec<_> = data
+        .root
+        .descendants()
+        .filter_map(latex::Include::cast)
+        .filter(|include| is_include_command(*include))
+        .collect();
+
+    if includes.is_empty() {
+        return Some(());
+    }
+
+    let document_start = find_document_start(&data.root);
+    let parent = workspace
+        .documents()
+        .into_iter()
+        .filter(|parent| parent.uri != document.uri)
+        .find_map(|parent| find_parent_include(&parent, &document.uri));
+
+    for include in includes {
+        let command = match include.command() {
+            Some(command) => command,
+            None => continue,
+        };
+
+        let start = include.syntax().text_range().start();
+        let (code, message, related_information) =
+            if document_start.map_or(false, |document_start| start < document_start) {
+                (
+                    PREAMBLE_INCLUDE_CODE,
+                    "\\include cannot be used in the preamble; use \\input instead".to_string(),
+                    None,
+                )
+            } else if let Some(location) = &parent {
+                (
+                    NESTED_INCLUDE_CODE,
+                    "\\include cannot be nested; use \\input instead".to_string(),
+                    Some(vec![DiagnosticRelatedInformation {
+                        location: location.clone(),
+                        message: "The file is loaded with \\include here".to_string(),
+                    }]),
+                )
+            } else if include.path_list().map_or(false, |paths| {
+                paths.keys().any(|path| has_extension(&path.to_string()))
+            }) {
+                (
+                    INCLUDE_EXTENSION_CODE,
+                    "\\include does not accept file extensions; use \\input instead".to_string(),
+                    None,
+                )
+            } else {
+                continue;
+            };
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(command.text_range()),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
+
+fn is_include_command(include: latex::Include) -> bool {
+    include.syntax().kind() == latex::LATEX_INCLUDE
+        && include.command().map(|command| command.text()) == Some("\\include")
+}
+
+fn find_document_start(root: &latex::SyntaxNode) -> Option<TextSize> {
+    let environment = root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .find(|environment| {
+            environment
+                .begin()
+                .and_then(|begin| begin.name())
+                .and_then(|name| name.key())
+                .map_or(false, |name| name.to_string() == "document")
+        })?;
+
+    Some(environment.syntax().text_range().start())
+}
+
+/// Finds the `\include` of the given parent document that loads the child document.
+fn find_parent_include(parent: &Document, child_uri: &Uri) -> Option<Location> {
+    let data = parent.data.as_latex()?;
+    data.extras
+        .explicit_links
+        .iter()
+        .filter(|link| link.kind == latex::ExplicitLinkKind::Latex)
+        .filter(|link| {
+            link.targets
+                .iter()
+                .any(|target| target.as_ref() == child_uri)
+        })
+        .find(|link| {
+            data.root
+                .token_at_offset(link.stem_range.start())
+                .right_biased()
+                .and_then(|token| token.parent().ancestors().find_map(latex::Include::cast))
+                .map_or(false, is_include_command)
+        })
+        .map(|link| {
+            Location::new(
+                parent.uri.as_ref().clone().into(),
+                parent.line_index.line_col_lsp_range(link.stem_range),
+            )
+        })
+}
+
+fn has_extension(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    file_name
+        .rfind('.')
+        .map_or(false, |index| index > 0 && index + 1 < file_name.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(files: Vec<(&str, &str)>, name: &str) -> Vec<Diagnostic> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        for (file_name, text) in files {
+            workspace.open(
+                Arc::new(Uri::parse(&format!("http://example.com/{}", file_name)).unwrap()),
+                text.to_string(),
+                DocumentLanguage::Latex,
+                WorkspaceSource::Client,
+            );
+        }
+
+        let uri = Arc::new(Uri::parse(&format!("http://example.com/{}", name)).unwrap());
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_includes(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri.remove(&uri).unwrap_or_default()
+    }
+
+    fn range(start: u32, end: u32) -> Range {
+        Range::new(Position::new(0, start), Position::new(0, end))
+    }
+
+    #[test]
+    fn test_valid_include() {
+        let files = vec![
+            (
+                "main.tex",
+                r#"\documentclass{article}\begin{document}\include{chapter}\end{document}"#,
+            ),
+            ("chapter.tex", r#"\input{section}"#),
+        ];
+
+        assert!(find_diagnostics(files.clone(), "main.tex").is_empty());
+        assert!(find_diagnostics(files, "chapter.tex").is_empty());
+    }
+
+    #[test]
+    fn test_preamble() {
+        let diagnostics = find_diagnostics(
+            vec![(
+                "main.tex",
+                r#"\documentclass{article}\include{chapter}\begin{document}\end{document}"#,
+            )],
+            "main.tex",
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::Number(PREAMBLE_INCLUDE_CODE))
+        );
+        assert_eq!(diagnostics[0].range, range(23, 31));
+    }
+
+    #[test]
+    fn test_nested() {
+        let diagnostics = find_diagnostics(
+            vec![
+                (
+                    "main.tex",
+                    r#"\begin{document}\include{chapter}\end{document}"#,
+                ),
+                ("chapter.tex", r#"\include{section}"#),
+            ],
+            "chapter.tex",
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::Number(NESTED_INCLUDE_CODE))
+        );
+        assert_eq!(diagnostics[0].range, range(0, 8));
+
+        let related_information = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(
+            related_information[0].location.uri.as_str(),
+            "http://example.com/main.tex"
+        );
+        assert_eq!(related_information[0].location.range, range(25, 32));
+    }
+
+    #[test]
+    fn test_nested_in_input() {
+        let files = vec![
+            (
+                "main.tex",
+                r#"\begin{document}\input{chapter}\end{document}"#,
+            ),
+            ("chapter.tex", r#"\include{section}"#),
+        ];
+
+        assert!(find_diagnostics(files, "chapter.tex").is_empty());
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let files = vec![(
+            "main.tex",
+            r#"\begin{document}\include{missing}\end{document}"#,
+        )];
+
+        assert!(find_diagnostics(files, "main.tex").is_empty());
+    }
+
+    #[test]
+    fn test_extension() {
+        let diagnostics = find_diagnostics(
+            vec![(
+                "main.tex",
+                r#"\begin{document}\include{chapter.tex}\end{document}"#,
+            )],
+            "main.tex",
+        );
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].code,
+            Some(NumberOrString::Number(INCLUDE_EXTENSION_CODE))
+        );
+        assert_eq!(diagnostics[0].range, range(16, 24));
+    }
+
+    #[test]
+    fn test_has_extension() {
+        assert!(has_extension("chapter.tex"));
+        assert!(has_extension("../chapters/intro.tex"));
+        assert!(!has_extension("chapter"));
+        assert!(!has_extension("../chapters/intro"));
+        assert!(!has_extension(".hidden"));
+        assert!(!has_extension("chapter."));
+    }
+}
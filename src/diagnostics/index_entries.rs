@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString,
+};
+use multimap::MultiMap;
+
+use crate::{find_index_entries, LineIndexExt, Uri, Workspace};
+
+pub fn analyze_index_entries(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let entries = find_index_entries(&data.root);
+    if entries.is_empty() {
+        return Some(());
+    }
+
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let all_entries: Vec<_> = subset
+        .documents
+        .iter()
+        .filter_map(|document| Some((document, document.data.as_latex()?)))
+        .flat_map(|(document, data)| {
+            find_index_entries(&data.root)
+                .into_iter()
+                .map(move |entry| (document, entry))
+        })
+        .collect();
+
+    for entry in entries {
+        let key = entry.key();
+        let mut variants: Vec<String> = Vec::new();
+        let mut related_information = Vec::new();
+        for (other_document, other_entry) in &all_entries {
+            let other_key = other_entry.key();
+            if other_key == key
+                || other_key.to_lowercase() != key.to_lowercase()
+                || variants.contains(&other_key)
+            {
+                continue;
+            }
+
+            related_information.push(DiagnosticRelatedInformation {
+                location: Location::new(
+                    other_document.uri.as_ref().clone().into(),
+                    other_document
+                        .line_index
+                        .line_col_lsp_range(other_entry.range),
+                ),
+                message: format!("\"{}\" is used here", other_key),
+            });
+            variants.push(other_key);
+        }
+
+        if variants.is_empty() {
+            continue;
+        }
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(entry.range),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(27)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!(
+                    "Index entry \"{}\" differs only in capitalization from \"{}\"",
+                    key,
+                    variants.join("\", \"")
+                ),
+                related_information: Some(related_information),
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(main: &str, other: &str) -> Vec<Diagnostic> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::parse("http://example.com/main.tex").unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            main.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        workspace.open(
+            Arc::new(Uri::parse("http://example.com/other.tex").unwrap()),
+            other.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_index_entries(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri.remove(&uri).unwrap_or_default()
+    }
+
+    fn range(start: u32, end: u32) -> Range {
+        Range::new(Position::new(0, start), Position::new(0, end))
+    }
+
+    #[test]
+    fn test_capitalization() {
+        let diagnostics = find_diagnostics(r#"\index{Foo}\index{foo}\index{foo}"#, "");
+        let ranges: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range)
+            .collect();
+        assert_eq!(ranges, vec![range(7, 10), range(18, 21), range(29, 32)]);
+        assert_eq!(
+            diagnostics[0].message,
+            "Index entry \"Foo\" differs only in capitalization from \"foo\""
+        );
+        assert_eq!(
+            diagnostics[0].related_information.as_ref().unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_same_key() {
+        assert!(find_diagnostics(r#"\index{foo}\index{foo|textbf}\index{foo@Foo}"#, "").is_empty());
+        assert!(find_diagnostics(r#"\index{Foo!bar}\index{foo}"#, "").is_empty());
+    }
+
+    #[test]
+    fn test_other_file() {
+        let diagnostics = find_diagnostics(r#"\include{other}\index{Foo}"#, r#"\index{foo}"#);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range, range(22, 25));
+        assert_eq!(diagnostics[0].code, Some(NumberOrString::Number(27)));
+
+        let related_information = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(
+            related_information[0].location,
+            Location::new(
+                Uri::parse("http://example.com/other.tex").unwrap().into(),
+                range(7, 10)
+            )
+        );
+    }
+
+    #[test]
+    fn test_unrelated_file() {
+        assert!(find_diagnostics(r#"\index{Foo}"#, r#"\index{foo}"#).is_empty());
+    }
+}
@@ -0,0 +1,155 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{
+    adapt_kind_name, find_label_kind, find_label_prefixes, find_reference_word,
+    syntax::{latex, CstNode},
+    LineIndexExt, Uri, Workspace,
+};
+
+/// The diagnostic code of a reference whose label does not match the preceding word
+/// like `Table~\ref{fig:foo}`.
+pub const LABEL_KIND_CODE: i32 = 34;
+
+pub fn analyze_latex_label_kinds(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let mut prefixes = None;
+    for reference in data
+        .root
+        .descendants()
+        .filter_map(latex::LabelReference::cast)
+    {
+        let (range, word_kind) = match reference
+            .command()
+            .and_then(|command| find_reference_word(&document.text, command.text_range().start()))
+        {
+            Some(word) => word,
+            None => continue,
+        };
+
+        let prefixes = prefixes.get_or_insert_with(|| find_label_prefixes(&subset));
+        let label_kind = match reference
+            .name_list()
+            .and_then(|names| names.keys().next())
+            .and_then(|name| find_label_kind(&subset, prefixes, &name.to_string()))
+        {
+            Some(label_kind) => label_kind,
+            None => continue,
+        };
+
+        if label_kind == word_kind {
+            continue;
+        }
+
+        let word = &document.text[std::ops::Range::<usize>::from(range)];
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(range),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(LABEL_KIND_CODE)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!(
+                    "\"{}\" does not match the kind of the referenced label ({})",
+                    word, label_kind
+                ),
+                related_information: None,
+                tags: None,
+                data: Some(serde_json::Value::String(adapt_kind_name(label_kind, word))),
+            },
+        );
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(text: &str) -> Vec<(Range, Option<serde_json::Value>)> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_label_kinds(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri
+            .get_vec(&uri)
+            .map(|diagnostics| {
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| (diagnostic.range, diagnostic.data.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    const DEFINITIONS: &str = indoc! {r#"
+        \section{Foo}\label{sec:foo}
+        \begin{figure}\caption{Bar}\label{fig:bar}\end{figure}
+        \begin{table}\caption{Baz}\label{tab:baz}\end{table}
+        \begin{equation}\label{eq:qux}x\end{equation}
+        \begin{figure}\caption{Quux}\label{quux}\end{figure}
+    "#};
+
+    #[test]
+    fn test_matching_kinds() {
+        let text = format!(
+            "{}{}",
+            DEFINITIONS,
+            "Section~\\ref{sec:foo}, Fig.~\\ref{fig:bar}, Table~\\ref{tab:baz}, Eq.~\\ref{eq:qux}"
+        );
+        assert!(find_diagnostics(&text).is_empty());
+    }
+
+    #[test]
+    fn test_mismatch() {
+        let text = format!("{}{}", DEFINITIONS, "see table~\\ref{fig:bar}");
+        assert_eq!(
+            find_diagnostics(&text),
+            vec![(
+                Range::new(Position::new(5, 4), Position::new(5, 9)),
+                Some(serde_json::Value::String("figure".into()))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unprefixed_label() {
+        let text = format!("{}{}", DEFINITIONS, "Figure~\\ref{quux}\nTable~\\ref{quux}");
+        assert_eq!(
+            find_diagnostics(&text),
+            vec![(
+                Range::new(Position::new(6, 0), Position::new(6, 5)),
+                Some(serde_json::Value::String("Figure".into()))
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unknown_word() {
+        let text = format!("{}{}", DEFINITIONS, "Foo~\\ref{fig:bar}");
+        assert!(find_diagnostics(&text).is_empty());
+    }
+}
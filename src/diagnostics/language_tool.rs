@@ -0,0 +1,561 @@
+use std::{
+    io::{self, Read, Write},
+    net::{TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use serde::Deserialize;
+use serde_json::json;
+use url::{form_urlencoded, Url};
+
+use crate::{
+    extract_prose, find_natural_languages, is_generated_file, Document, LanguageToolOptions,
+    LineIndexExt, NaturalLanguages, Options, ProseSegment, Workspace,
+};
+
+pub const LANGUAGE_TOOL_SOURCE: &str = "LanguageTool";
+
+/// The maximum number of replacements that are offered as quick fixes.
+const MAX_REPLACEMENTS: usize = 5;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+const READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The languages of `babel` and `polyglossia` and the corresponding language codes of LanguageTool.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("english", "en-US"),
+    ("american", "en-US"),
+    ("USenglish", "en-US"),
+    ("british", "en-GB"),
+    ("UKenglish", "en-GB"),
+    ("australian", "en-AU"),
+    ("canadian", "en-CA"),
+    ("newzealand", "en-NZ"),
+    ("german", "de-DE"),
+    ("ngerman", "de-DE"),
+    ("austrian", "de-AT"),
+    ("naustrian", "de-AT"),
+    ("swissgerman", "de-CH"),
+    ("nswissgerman", "de-CH"),
+    ("french", "fr"),
+    ("francais", "fr"),
+    ("spanish", "es"),
+    ("italian", "it"),
+    ("portuguese", "pt-PT"),
+    ("portuges", "pt-PT"),
+    ("brazil", "pt-BR"),
+    ("brazilian", "pt-BR"),
+    ("dutch", "nl"),
+    ("polish", "pl-PL"),
+    ("russian", "ru-RU"),
+    ("ukrainian", "uk-UA"),
+    ("catalan", "ca-ES"),
+    ("swedish", "sv"),
+    ("danish", "da-DK"),
+    ("greek", "el-GR"),
+    ("irish", "ga-IE"),
+    ("slovak", "sk-SK"),
+    ("slovenian", "sl-SI"),
+    ("romanian", "ro-RO"),
+    ("japanese", "ja-JP"),
+    ("chinese", "zh-CN"),
+];
+
+#[derive(Debug, Deserialize)]
+struct CheckResponse {
+    matches: Vec<Match>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Match {
+    message: String,
+    offset: usize,
+    length: usize,
+    #[serde(default)]
+    replacements: Vec<Replacement>,
+    rule: Rule,
+}
+
+#[derive(Debug, Deserialize)]
+struct Replacement {
+    value: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Rule {
+    id: String,
+}
+
+/// Checks the prose of a document with a LanguageTool server.
+/// Each language of the document (as declared with `babel` or `polyglossia`) is checked separately.
+pub fn check_language_tool(
+    workspace: &dyn Workspace,
+    document: &Document,
+    options: &Options,
+) -> Option<Vec<Diagnostic>> {
+    let prose_options = &options.prose;
+    let options = &options.language_tool;
+    let endpoint = Url::parse(&format!(
+        "{}/v2/check",
+        options.url.as_deref()?.trim_end_matches('/')
+    ))
+    .ok()?;
+
+    if is_generated_file(&document.uri) {
+        return None;
+    }
+
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(std::sync::Arc::clone(&document.uri))?;
+    let related: Vec<_> = subset
+        .documents
+        .iter()
+        .skip(1)
+        .filter_map(|document| document.data.as_latex())
+        .map(|data| &data.root)
+        .collect();
+
+    let natural_languages = find_natural_languages(&data.root, &related);
+    let segments = extract_prose(&data.root, prose_options);
+
+    let mut diagnostics = Vec::new();
+    for language in find_language_codes(&segments, &natural_languages, options) {
+        let annotation = create_annotation(
+            &document.text,
+            &segments,
+            &natural_languages,
+            options,
+            &language,
+        );
+
+        let mut body = form_urlencoded::Serializer::new(String::new());
+        body.append_pair("language", &language)
+            .append_pair("data", &annotation.to_string());
+        if !options.disabled_rules.is_empty() {
+            body.append_pair("disabledRules", &options.disabled_rules.join(","));
+        }
+
+        let response = match post_form(&endpoint, &body.finish()) {
+            Ok(response) => response,
+            Err(why) => {
+                log::warn!(
+                    "Failed to check the document with LanguageTool ({}): {}",
+                    language,
+                    why
+                );
+                continue;
+            }
+        };
+
+        match serde_json::from_slice::<CheckResponse>(&response) {
+            Ok(response) => diagnostics.extend(convert_matches(document, response.matches)),
+            Err(why) => log::warn!("Invalid response of LanguageTool: {}", why),
+        }
+    }
+
+    Some(diagnostics)
+}
+
+fn language_code(
+    segment: &ProseSegment,
+    natural_languages: &NaturalLanguages,
+    options: &LanguageToolOptions,
+) -> String {
+    natural_languages
+        .language_at(segment.range().start())
+        .and_then(|name| LANGUAGES.iter().find(|(babel_name, _)| *babel_name == name))
+        .map(|(_, code)| code.to_string())
+        .or_else(|| options.language.clone())
+        .unwrap_or_else(|| "auto".to_string())
+}
+
+fn find_language_codes(
+    segments: &[ProseSegment],
+    natural_languages: &NaturalLanguages,
+    options: &LanguageToolOptions,
+) -> Vec<String> {
+    let mut codes = Vec::new();
+    for segment in segments {
+        if let ProseSegment::Text(_) = segment {
+            let code = language_code(segment, natural_languages, options);
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+    codes
+}
+
+/// Creates the annotated text of LanguageTool. The text of the other languages is sent as markup,
+/// so that the offsets of the matches always refer to the document.
+fn create_annotation(
+    text: &str,
+    segments: &[ProseSegment],
+    natural_languages: &NaturalLanguages,
+    options: &LanguageToolOptions,
+    language: &str,
+) -> serde_json::Value {
+    let annotation: Vec<_> = segments
+        .iter()
+        .map(|segment| match segment {
+            ProseSegment::Text(range)
+                if language_code(segment, natural_languages, options) == language =>
+            {
+                json!({ "text": &text[*range] })
+            }
+            ProseSegment::Text(range) => json!({ "markup": &text[*range] }),
+            ProseSegment::Markup {
+                range,
+                interpret_as,
+            } => json!({ "markup": &text[*range], "interpretAs": interpret_as }),
+        })
+        .collect();
+
+    json!({ "annotation": annotation })
+}
+
+fn convert_matches(document: &Document, mut matches: Vec<Match>) -> Vec<Diagnostic> {
+    matches.sort_by_key(|m| m.offset);
+    let mut offsets = Utf16Offsets::new(&document.text);
+    matches
+        .into_iter()
+        .map(|m| {
+            let start = offsets.to_utf8(m.offset);
+            let end = offsets.to_utf8(m.offset + m.length);
+            let replacements: Vec<_> = m
+                .replacements
+                .into_iter()
+                .take(MAX_REPLACEMENTS)
+                .map(|replacement| serde_json::Value::String(replacement.value))
+                .collect();
+
+            Diagnostic {
+                range: document
+                    .line_index
+                    .line_col_lsp_range(TextRange::new(start, end)),
+                severity: Some(DiagnosticSeverity::Information),
+                code: Some(NumberOrString::String(m.rule.id)),
+                code_description: None,
+                source: Some(LANGUAGE_TOOL_SOURCE.to_string()),
+                message: m.message,
+                related_information: None,
+                tags: None,
+                data: Some(serde_json::Value::Array(replacements)),
+            }
+        })
+        .collect()
+}
+
+/// Converts the UTF-16 offsets of LanguageTool (which is written in Java) to byte offsets.
+/// Converting the offsets in ascending order only needs one pass over the text.
+struct Utf16Offsets<'a> {
+    text: &'a str,
+    chars: std::str::CharIndices<'a>,
+    utf16_offset: usize,
+    utf8_offset: usize,
+}
+
+impl<'a> Utf16Offsets<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            chars: text.char_indices(),
+            utf16_offset: 0,
+            utf8_offset: 0,
+        }
+    }
+
+    fn to_utf8(&mut self, utf16_offset: usize) -> TextSize {
+        if utf16_offset < self.utf16_offset {
+            *self = Self::new(self.text);
+        }
+
+        while self.utf16_offset < utf16_offset {
+            match self.chars.next() {
+                Some((index, c)) => {
+                    self.utf16_offset += c.len_utf16();
+                    self.utf8_offset = index + c.len_utf8();
+                }
+                None => break,
+            }
+        }
+
+        TextSize::from(self.utf8_offset as u32)
+    }
+}
+
+/// Sends a form to a LanguageTool server and returns the body of the response.
+/// Only plain HTTP is supported since the server is expected to run locally.
+fn post_form(url: &Url, body: &str) -> io::Result<Vec<u8>> {
+    if url.scheme() != "http" {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "only http URLs are supported",
+        ));
+    }
+
+    let host = url
+        .host_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "missing host"))?;
+    let port = url.port_or_known_default().unwrap_or(80);
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "unknown host"))?;
+
+    let mut stream = TcpStream::connect_timeout(&address, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}:{}\r\nAccept: application/json\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        url.path(),
+        host,
+        port,
+        body.len(),
+        body
+    )?;
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+    parse_response(&response)
+}
+
+fn parse_response(response: &[u8]) -> io::Result<Vec<u8>> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let header_end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or_else(|| invalid("incomplete response"))?;
+
+    let header = String::from_utf8_lossy(&response[..header_end]).to_lowercase();
+    let body = &response[header_end + 4..];
+    let status = header.split_whitespace().nth(1).unwrap_or_default();
+    if status != "200" {
+        return Err(invalid(&format!(
+            "unexpected status: {}",
+            String::from_utf8_lossy(body)
+        )));
+    }
+
+    let header_value = |name: &str| {
+        header
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .find(|(key, _)| key.trim() == name)
+            .map(|(_, value)| value.trim())
+    };
+
+    if header_value("transfer-encoding") != Some("chunked") {
+        // Without a length, the body ends when the server closes the connection.
+        return match header_value("content-length") {
+            Some(length) => {
+                let length: usize = length
+                    .parse()
+                    .map_err(|_| invalid("invalid content length"))?;
+                body.get(..length)
+                    .map(|body| body.to_vec())
+                    .ok_or_else(|| invalid("incomplete body"))
+            }
+            None => Ok(body.to_vec()),
+        };
+    }
+
+    let mut content = Vec::new();
+    let mut rest = body;
+    loop {
+        let line_end = rest
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .ok_or_else(|| invalid("invalid chunk"))?;
+        let size = std::str::from_utf8(&rest[..line_end])
+            .ok()
+            .and_then(|line| usize::from_str_radix(line.split(';').next()?.trim(), 16).ok())
+            .ok_or_else(|| invalid("invalid chunk size"))?;
+
+        if size == 0 {
+            return Ok(content);
+        }
+
+        let chunk = rest
+            .get(line_end + 2..line_end + 2 + size)
+            .ok_or_else(|| invalid("incomplete chunk"))?;
+        content.extend_from_slice(chunk);
+        rest = rest.get(line_end + 4 + size..).unwrap_or_default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{net::TcpListener, thread};
+
+    use indoc::indoc;
+
+    use crate::{syntax::latex, DocumentLanguage, ProseOptions, ServerContext, Uri};
+
+    use super::*;
+
+    #[test]
+    fn test_annotation() {
+        let text = indoc! {r#"
+            \usepackage[ngerman, english]{babel}
+            \begin{document}
+            This are $x$. \foreignlanguage{ngerman}{Grüße}
+            \end{document}"#};
+
+        let root = latex::parse(text).root;
+        let natural_languages = find_natural_languages(&root, &[]);
+        let segments = extract_prose(&root, &ProseOptions::default());
+        let options = LanguageToolOptions::default();
+        assert_eq!(
+            find_language_codes(&segments, &natural_languages, &options),
+            vec!["en-US", "de-DE"]
+        );
+
+        let annotation = create_annotation(text, &segments, &natural_languages, &options, "en-US");
+        let annotation = annotation["annotation"].as_array().unwrap();
+        let text_parts: String = annotation
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect();
+
+        assert_eq!(text_parts.trim(), "This are .");
+        assert!(annotation
+            .iter()
+            .any(|part| part["markup"] == "$x$" && part["interpretAs"] == "X"));
+    }
+
+    #[test]
+    fn test_lua_chunks() {
+        let text = indoc! {r#"
+            \begin{document}
+            Foo \directlua{tex.print("Helo wrold")} bar.
+            \begin{luacode}
+            tex.print("Helo wrold")
+            \end{luacode}
+            \end{document}"#};
+
+        let root = latex::parse_with_options(
+            text,
+            &latex::find_lua_chunks(text),
+            &latex::CustomCommands::default(),
+        )
+        .root;
+
+        let natural_languages = find_natural_languages(&root, &[]);
+        let segments = extract_prose(&root, &ProseOptions::default());
+        let options = LanguageToolOptions::default();
+        let annotation = create_annotation(text, &segments, &natural_languages, &options, "en-US");
+        let text_parts: String = annotation["annotation"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect();
+
+        assert!(text_parts.contains("Foo"));
+        assert!(!text_parts.contains("wrold"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let document = Document::parse(
+            std::sync::Arc::new(ServerContext::new(std::env::temp_dir())),
+            std::sync::Arc::new(Uri::parse("file:///main.tex").unwrap()),
+            "Grüße 😀 This are wrong.".to_string(),
+            DocumentLanguage::Latex,
+        );
+
+        let response: CheckResponse = serde_json::from_str(
+            r#"{"matches": [{
+                "message": "Use \"is\".",
+                "offset": 14,
+                "length": 3,
+                "replacements": [{ "value": "is" }],
+                "rule": { "id": "THIS_NNS_VB" }
+            }]}"#,
+        )
+        .unwrap();
+
+        let diagnostics = convert_matches(&document, response.matches);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            document.line_index.offset_lsp_range(diagnostics[0].range),
+            TextRange::new(18.into(), 21.into())
+        );
+        assert_eq!(diagnostics[0].data, Some(json!(["is"])));
+    }
+
+    #[test]
+    fn test_response_status() {
+        let response = b"HTTP/1.1 400 Bad Request\r\nContent-Length: 7\r\n\r\nInvalid";
+        let error = parse_response(response).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("Invalid"));
+    }
+
+    #[test]
+    fn test_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n{\"a\": 1}\r\n";
+        assert_eq!(parse_response(response).unwrap(), b"{\"a\": 1}");
+
+        let response = b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n{\"a\"";
+        assert!(parse_response(response).is_err());
+    }
+
+    #[test]
+    fn test_missing_content_length() {
+        let response = b"HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\r\n{\"a\": 1}";
+        assert_eq!(parse_response(response).unwrap(), b"{\"a\": 1}");
+    }
+
+    #[test]
+    fn test_truncated_response() {
+        assert!(parse_response(b"HTTP/1.1 200 OK\r\nContent-Len").is_err());
+
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n8\r\n{\"a\"";
+        assert!(parse_response(response).is_err());
+    }
+
+    #[test]
+    fn test_post_form() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = Url::parse(&format!(
+            "http://{}/v2/check",
+            listener.local_addr().unwrap()
+        ))
+        .unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !request.ends_with(b"language=en-US") {
+                let count = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..count]);
+            }
+
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        assert_eq!(post_form(&url, "language=en-US").unwrap(), b"{}");
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /v2/check HTTP/1.1\r\n"));
+        assert!(request.contains("Content-Length: 14\r\n"));
+    }
+
+    #[test]
+    fn test_chunked_response() {
+        let response = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n{\"a\"\r\n3\r\n: 1\r\n1\r\n}\r\n0\r\n\r\n";
+        assert_eq!(parse_response(response).unwrap(), b"{\"a\": 1}");
+    }
+}
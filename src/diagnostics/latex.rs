@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
 use cstree::TextRange;
-use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
 use multimap::MultiMap;
+use rustc_hash::FxHashSet;
 
 use crate::{
     syntax::{latex, CstNode},
@@ -15,10 +16,6 @@ pub fn analyze_latex_static(
     uri: &Uri,
 ) -> Option<()> {
     let document = workspace.get(uri)?;
-    if !document.uri.as_str().ends_with(".tex") {
-        return None;
-    }
-
     let data = document.data.as_latex()?;
 
     for node in data.root.descendants() {
@@ -47,6 +44,71 @@ pub fn analyze_latex_static(
             });
     }
 
+    analyze_unused_counters(workspace, &document, diagnostics_by_uri);
+    Some(())
+}
+
+fn analyze_unused_counters(
+    workspace: &dyn Workspace,
+    document: &Document,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+) -> Option<()> {
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+
+    let mut used_counters = FxHashSet::default();
+    for data in subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for node in data.root.descendants() {
+            if let Some(name) = latex::CounterReference::cast(node)
+                .and_then(|counter| counter.name())
+                .and_then(|name| name.key())
+            {
+                used_counters.insert(name.to_string());
+            } else if let Some(name) = latex::CounterDefinition::cast(node)
+                .and_then(|counter| counter.parent())
+                .and_then(|name| name.key())
+            {
+                used_counters.insert(name.to_string());
+            }
+        }
+
+        for name in &data.extras.command_names {
+            if let Some(name) = name.strip_prefix("\\the") {
+                used_counters.insert(name.to_string());
+            }
+        }
+    }
+
+    for name in data
+        .root
+        .descendants()
+        .filter_map(latex::CounterDefinition::cast)
+        .filter_map(|counter| counter.name())
+        .filter_map(|name| name.key())
+    {
+        let name_text = name.to_string();
+        if !used_counters.contains(&name_text) {
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document.line_index.line_col_lsp_range(name.small_range()),
+                    severity: Some(DiagnosticSeverity::Hint),
+                    code: Some(NumberOrString::Number(10)),
+                    code_description: None,
+                    source: Some("texlab".to_string()),
+                    message: format!("Unused counter \"{}\"", name_text),
+                    related_information: None,
+                    tags: Some(vec![DiagnosticTag::Unnecessary]),
+                    data: None,
+                },
+            );
+        }
+    }
+
     Some(())
 }
 
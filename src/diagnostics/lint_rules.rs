@@ -0,0 +1,259 @@
+use std::sync::{Arc, Mutex, PoisonError};
+
+use cstree::TextRange;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    syntax::latex, Document, LineIndexExt, LintRule, LintSeverity, Options, Uri, Workspace,
+};
+
+/// The diagnostic code of the matches of the lint rules in the configuration.
+/// The replacement of the rule is stored in the data of the diagnostic.
+pub const LINT_RULE_CODE: i32 = 36;
+
+static COMPILED_PATTERNS: Lazy<Mutex<CompiledPatterns>> = Lazy::new(Mutex::default);
+
+pub fn analyze_latex_lint_rules(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+    options: &Options,
+) -> Option<()> {
+    if options.lint_rules.is_empty() {
+        return None;
+    }
+
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let patterns = compile_patterns(&options.lint_rules);
+    for (rule, pattern) in options.lint_rules.iter().zip(patterns.iter()) {
+        for (range, replacement) in find_rule_matches(&document, &data.root, rule, pattern.as_ref())
+        {
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document.line_index.line_col_lsp_range(range),
+                    severity: Some(match rule.severity {
+                        LintSeverity::Error => DiagnosticSeverity::Error,
+                        LintSeverity::Warning => DiagnosticSeverity::Warning,
+                        LintSeverity::Information => DiagnosticSeverity::Information,
+                        LintSeverity::Hint => DiagnosticSeverity::Hint,
+                    }),
+                    code: Some(NumberOrString::Number(LINT_RULE_CODE)),
+                    code_description: None,
+                    source: Some("texlab".to_string()),
+                    message: rule.message.clone(),
+                    related_information: None,
+                    tags: None,
+                    data: replacement.map(serde_json::Value::String),
+                },
+            );
+        }
+    }
+
+    Some(())
+}
+
+/// The compiled patterns of the last configuration of lint rules.
+/// The rules are only compiled again after the configuration changes.
+#[derive(Default)]
+struct CompiledPatterns {
+    rules: Vec<LintRule>,
+    patterns: Arc<Vec<Option<Regex>>>,
+}
+
+impl CompiledPatterns {
+    /// Returns the compiled pattern of every rule (if any).
+    fn get(&mut self, rules: &[LintRule]) -> Arc<Vec<Option<Regex>>> {
+        if self.rules != rules {
+            let patterns = rules
+                .iter()
+                .map(|rule| {
+                    let pattern = rule.pattern.as_ref()?;
+                    match Regex::new(pattern) {
+                        Ok(regex) => Some(regex),
+                        Err(why) => {
+                            log::warn!("Invalid lint rule pattern {}: {}", pattern, why);
+                            None
+                        }
+                    }
+                })
+                .collect();
+
+            self.rules = rules.to_vec();
+            self.patterns = Arc::new(patterns);
+        }
+
+        Arc::clone(&self.patterns)
+    }
+}
+
+fn compile_patterns(rules: &[LintRule]) -> Arc<Vec<Option<Regex>>> {
+    COMPILED_PATTERNS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(rules)
+}
+
+fn find_rule_matches(
+    document: &Document,
+    root: &latex::SyntaxNode,
+    rule: &LintRule,
+    pattern: Option<&Regex>,
+) -> Vec<(TextRange, Option<String>)> {
+    let mut matches = Vec::new();
+    if let Some(regex) = pattern {
+        find_pattern_matches(document, root, rule, regex, &mut matches);
+    }
+
+    if let Some(command) = &rule.command {
+        find_command_matches(root, rule, command, &mut matches);
+    }
+
+    matches
+}
+
+/// Finds the matches of a regular expression outside of comments.
+/// The replacement may refer to the capture groups of the expression like `$1`.
+fn find_pattern_matches(
+    document: &Document,
+    root: &latex::SyntaxNode,
+    rule: &LintRule,
+    regex: &Regex,
+    matches: &mut Vec<(TextRange, Option<String>)>,
+) {
+    for captures in regex.captures_iter(&document.text) {
+        let full_match = captures.get(0).unwrap();
+        if full_match.as_str().is_empty() {
+            continue;
+        }
+
+        let range = TextRange::new(
+            (full_match.start() as u32).into(),
+            (full_match.end() as u32).into(),
+        );
+
+        let is_comment = root
+            .token_at_offset(range.start())
+            .right_biased()
+            .map_or(false, |token| token.kind() == latex::COMMENT);
+
+        if !is_comment {
+            let replacement = rule.replacement.as_ref().map(|replacement| {
+                let mut text = String::new();
+                captures.expand(replacement, &mut text);
+                text
+            });
+
+            matches.push((range, replacement));
+        }
+    }
+}
+
+fn find_command_matches(
+    root: &latex::SyntaxNode,
+    rule: &LintRule,
+    command: &str,
+    matches: &mut Vec<(TextRange, Option<String>)>,
+) {
+    let name = format!("\\{}", command.strip_prefix('\\').unwrap_or(command));
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind().is_command_name() && token.text() == name)
+    {
+        matches.push((token.text_range(), rule.replacement.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lsp_types::Range;
+
+    use crate::{DocumentLanguage, RangeExt, ServerContext};
+
+    use super::*;
+
+    fn analyze(text: &str, rules: Vec<LintRule>) -> Vec<(Range, Option<String>)> {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap();
+        let document = Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        );
+
+        let data = document.data.as_latex().unwrap();
+        let patterns = compile_patterns(&rules);
+        rules
+            .iter()
+            .zip(patterns.iter())
+            .flat_map(|(rule, pattern)| {
+                find_rule_matches(&document, &data.root, rule, pattern.as_ref())
+            })
+            .map(|(range, replacement)| {
+                (document.line_index.line_col_lsp_range(range), replacement)
+            })
+            .collect()
+    }
+
+    fn rule(pattern: Option<&str>, command: Option<&str>, replacement: Option<&str>) -> LintRule {
+        LintRule {
+            pattern: pattern.map(Into::into),
+            command: command.map(Into::into),
+            message: "Foo".into(),
+            severity: LintSeverity::Warning,
+            replacement: replacement.map(Into::into),
+        }
+    }
+
+    #[test]
+    fn test_pattern() {
+        let actual = analyze(
+            "e.g. foo\n% e.g. bar",
+            vec![rule(Some(r"(e)\.g\. "), None, Some("${1}.g.\\ "))],
+        );
+
+        assert_eq!(
+            actual,
+            vec![(Range::new_simple(0, 0, 0, 5), Some("e.g.\\ ".into()))]
+        );
+    }
+
+    #[test]
+    fn test_command() {
+        let actual = analyze(
+            "{\\bf foo} % \\bf",
+            vec![rule(None, Some("bf"), Some("\\bfseries"))],
+        );
+
+        assert_eq!(
+            actual,
+            vec![(Range::new_simple(0, 1, 0, 4), Some("\\bfseries".into()))]
+        );
+    }
+
+    #[test]
+    fn test_compile_patterns() {
+        let rules = vec![
+            rule(Some("foo"), None, None),
+            rule(Some("("), None, None),
+            rule(None, Some("bf"), None),
+        ];
+
+        let mut compiled_patterns = CompiledPatterns::default();
+        let patterns = compiled_patterns.get(&rules);
+        assert_eq!(
+            patterns.iter().map(Option::is_some).collect::<Vec<_>>(),
+            vec![true, false, false]
+        );
+        assert!(Arc::ptr_eq(&patterns, &compiled_patterns.get(&rules)));
+        assert!(!Arc::ptr_eq(&patterns, &compiled_patterns.get(&rules[1..])));
+    }
+}
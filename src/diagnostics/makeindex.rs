@@ -0,0 +1,332 @@
+use std::{fs, path::Path, sync::Arc};
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+use multimap::MultiMap;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Uri, Workspace,
+};
+
+static UNDEFINED_GLOSSARY_ENTRY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Glossary entry `([^']+)' has not been defined").unwrap());
+
+static MAKEINDEX_MESSAGE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(!!|\*\*|##) (.*?) \((?:file|input) = ([^,]+), line = (\d+)").unwrap()
+});
+
+static INDEX_ENTRY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\\(?:indexentry|glossaryentry)\{(.*)\}\{[^}]*\}\s*$").unwrap());
+
+/// A message of a `makeindex` transcript (`.ilg` or `.glg`).
+struct MakeindexMessage {
+    severity: DiagnosticSeverity,
+    message: String,
+    file: String,
+    line: usize,
+}
+
+/// Reports undefined glossary entries of the build log and the errors
+/// of the `makeindex` and `makeglossaries` transcripts (`.ilg` and `.glg`) of the last build.
+pub fn analyze_makeindex_logs(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    log_uri: &Uri,
+) -> Option<()> {
+    let log_document = workspace.get(log_uri)?;
+    log_document.data.as_build_log()?;
+
+    let (root_document, source) = workspace.documents().into_iter().find_map(|document| {
+        let links = &document.data.as_latex()?.extras.implicit_links;
+        if document.uri.as_str().ends_with(".aux") {
+            None
+        } else if links.log.iter().any(|uri| uri.as_ref() == log_uri) {
+            Some((document, None))
+        } else if links.ilg.iter().any(|uri| uri.as_ref() == log_uri) {
+            Some((document, Some("makeindex")))
+        } else if links.glg.iter().any(|uri| uri.as_ref() == log_uri) {
+            Some((document, Some("makeglossaries")))
+        } else {
+            None
+        }
+    })?;
+
+    let subset = workspace.subset(Arc::clone(&root_document.uri))?;
+    match source {
+        None => analyze_undefined_glossary_entries(
+            &log_document.text,
+            &subset.documents,
+            diagnostics_by_uri,
+        ),
+        Some(source) => {
+            let transcript_path = log_uri.to_file_path().ok()?;
+            for message in parse_makeindex_messages(&log_document.text) {
+                for (uri, range) in
+                    find_index_commands(&transcript_path, &message, &subset.documents)
+                {
+                    diagnostics_by_uri.insert(
+                        uri,
+                        Diagnostic {
+                            range,
+                            severity: Some(message.severity),
+                            code: None,
+                            code_description: None,
+                            source: Some(source.to_string()),
+                            message: message.message.clone(),
+                            related_information: None,
+                            tags: None,
+                            data: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+fn analyze_undefined_glossary_entries(
+    log: &str,
+    documents: &[Arc<Document>],
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+) {
+    let mut names: Vec<_> = UNDEFINED_GLOSSARY_ENTRY_REGEX
+        .captures_iter(log)
+        .map(|captures| captures[1].to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    for document in documents {
+        let data = match document.data.as_latex() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for name in data
+            .root
+            .descendants()
+            .filter_map(latex::GlossaryEntryReference::cast)
+            .filter_map(|reference| reference.name()?.key())
+            .filter(|name| names.contains(&name.to_string()))
+        {
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document.line_index.line_col_lsp_range(name.small_range()),
+                    severity: Some(DiagnosticSeverity::Error),
+                    code: None,
+                    code_description: None,
+                    source: Some("latex".to_string()),
+                    message: format!("Glossary entry \"{}\" is not defined", name.to_string()),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                },
+            );
+        }
+    }
+}
+
+fn parse_makeindex_messages(transcript: &str) -> Vec<MakeindexMessage> {
+    let lines: Vec<_> = transcript.lines().collect();
+    let mut messages = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let captures = match MAKEINDEX_MESSAGE_REGEX.captures(line) {
+            Some(captures) => captures,
+            None => continue,
+        };
+
+        let severity = if &captures[1] == "##" {
+            DiagnosticSeverity::Warning
+        } else {
+            DiagnosticSeverity::Error
+        };
+
+        let details = lines
+            .get(i + 1)
+            .map(|line| line.trim())
+            .and_then(|line| line.strip_prefix("--"))
+            .map(str::trim)
+            .unwrap_or_default();
+
+        let message = if details.is_empty() {
+            captures[2].to_string()
+        } else {
+            format!("{}: {}", &captures[2], details)
+        };
+
+        messages.push(MakeindexMessage {
+            severity,
+            message,
+            file: captures[3].trim().to_string(),
+            line: captures[4].parse().unwrap_or_default(),
+        });
+    }
+    messages
+}
+
+/// Finds the `\index` commands that produced the erroneous line of the index file.
+/// Messages that cannot be traced back to a command are dropped.
+fn find_index_commands(
+    transcript_path: &Path,
+    message: &MakeindexMessage,
+    documents: &[Arc<Document>],
+) -> Vec<(Arc<Uri>, Range)> {
+    let index_path = match transcript_path.parent() {
+        Some(directory) => directory.join(&message.file),
+        None => return Vec::new(),
+    };
+
+    let entry = match fs::read_to_string(index_path).ok().and_then(|text| {
+        let line = text.lines().nth(message.line.checked_sub(1)?)?;
+        let entry = &INDEX_ENTRY_REGEX.captures(line)?[1];
+        Some(strip_encap(entry).trim().to_string())
+    }) {
+        Some(entry) => entry,
+        None => return Vec::new(),
+    };
+
+    let mut locations = Vec::new();
+    for document in documents {
+        let data = match document.data.as_latex() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for command in data
+            .root
+            .descendants()
+            .filter_map(latex::GenericCommand::cast)
+            .filter(|command| command.name().map(|name| name.text()) == Some("\\index"))
+            .filter(|command| {
+                command
+                    .syntax()
+                    .children()
+                    .find_map(latex::CurlyGroup::cast)
+                    .and_then(|group| group.content_text())
+                    .map_or(false, |text| strip_encap(&text).trim() == entry)
+            })
+        {
+            locations.push((
+                Arc::clone(&document.uri),
+                document
+                    .line_index
+                    .line_col_lsp_range(command.small_range()),
+            ));
+        }
+    }
+    locations
+}
+
+/// Removes the page encapsulator (like `|textbf` or the `|hyperpage` of `hyperref`) from an index entry.
+/// A bar that is quoted with `"` belongs to the entry itself.
+fn strip_encap(entry: &str) -> &str {
+    let mut is_quoted = false;
+    for (i, c) in entry.char_indices() {
+        match c {
+            '"' if !is_quoted => is_quoted = true,
+            '|' if !is_quoted => return &entry[..i],
+            _ => is_quoted = false,
+        }
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use indoc::indoc;
+    use lsp_types::Position;
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    const TRANSCRIPT: &str = indoc! {r#"
+        This is makeindex, version 2.15 [TeX Live 2021] (kpathsea + Thai support).
+        Scanning input file main.idx...
+        !! Input index error (file = main.idx, line = 2):
+           -- Extra `@' at position 4 of first argument.
+        done (1 entries accepted, 1 rejected)."#};
+
+    fn find_diagnostics(directory: PathBuf, text: &str) -> MultiMap<Arc<Uri>, Diagnostic> {
+        let cx = Arc::new(ServerContext::new(directory.clone()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        workspace.open(
+            Arc::new(Uri::from_file_path(directory.join("main.tex")).unwrap()),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let transcript_uri = Arc::new(Uri::from_file_path(directory.join("main.ilg")).unwrap());
+        workspace.open(
+            Arc::clone(&transcript_uri),
+            TRANSCRIPT.to_string(),
+            DocumentLanguage::BuildLog,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_makeindex_logs(&workspace, &mut diagnostics_by_uri, &transcript_uri);
+        diagnostics_by_uri
+    }
+
+    #[test]
+    fn test_parse_messages() {
+        let messages = parse_makeindex_messages(TRANSCRIPT);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].severity, DiagnosticSeverity::Error);
+        assert_eq!(
+            messages[0].message,
+            "Input index error: Extra `@' at position 4 of first argument."
+        );
+        assert_eq!(messages[0].file, "main.idx");
+        assert_eq!(messages[0].line, 2);
+    }
+
+    #[test]
+    fn test_strip_encap() {
+        assert_eq!(strip_encap("foo|hyperpage"), "foo");
+        assert_eq!(strip_encap("foo|("), "foo");
+        assert_eq!(strip_encap("a\"|b|textbf"), "a\"|b");
+        assert_eq!(strip_encap("foo"), "foo");
+    }
+
+    #[test]
+    fn test_index_error() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(
+            directory.path().join("main.idx"),
+            "\\indexentry{foo|hyperpage}{1}\n\\indexentry{b@r@z|hyperpage}{1}\n",
+        )
+        .unwrap();
+
+        let diagnostics_by_uri =
+            find_diagnostics(directory.path().to_path_buf(), "\\index{foo}\\index{b@r@z}");
+        let uri = Uri::from_file_path(directory.path().join("main.tex")).unwrap();
+        let diagnostics = diagnostics_by_uri.get_vec(&uri).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 11), Position::new(0, 24))
+        );
+        assert_eq!(diagnostics[0].source.as_deref(), Some("makeindex"));
+    }
+
+    #[test]
+    fn test_unresolved_index_error() {
+        let directory = tempfile::tempdir().unwrap();
+        let diagnostics_by_uri =
+            find_diagnostics(directory.path().to_path_buf(), "\\index{foo}\\index{b@r@z}");
+        assert!(diagnostics_by_uri.is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+use rustc_hash::FxHashSet;
+
+use crate::{
+    syntax::{bibtex, CstNode},
+    LineIndexExt, Uri, Workspace,
+};
+
+pub fn analyze_markdown_static(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_markdown()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+
+    let mut keys = FxHashSet::default();
+    let mut has_bibliography = false;
+    for document in &subset.documents {
+        if let Some(data) = document.data.as_bibtex() {
+            has_bibliography = true;
+            keys.extend(
+                data.root
+                    .children()
+                    .filter_map(bibtex::Entry::cast)
+                    .filter_map(|entry| entry.key())
+                    .map(|key| key.to_string()),
+            );
+        } else if let Some(data) = document.data.as_markdown() {
+            keys.extend(data.parse.labels.iter().map(|label| label.text.clone()));
+        }
+    }
+
+    if !has_bibliography {
+        return None;
+    }
+
+    for citation in &data.parse.citations {
+        if !keys.contains(&citation.text) {
+            diagnostics_by_uri.insert(
+                Arc::clone(&document.uri),
+                Diagnostic {
+                    range: document.line_index.line_col_lsp_range(citation.range),
+                    severity: Some(DiagnosticSeverity::Warning),
+                    code: Some(NumberOrString::Number(9)),
+                    code_description: None,
+                    source: Some("texlab".to_string()),
+                    message: format!(
+                        "Undefined citation or cross-reference \"{}\"",
+                        citation.text
+                    ),
+                    related_information: None,
+                    tags: None,
+                    data: None,
+                },
+            );
+        }
+    }
+
+    Some(())
+}
@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{
+    syntax::{latex, CstNode},
+    LineIndexExt, Uri, Workspace,
+};
+
+/// The diagnostic code of a `\label` inside of an unnumbered display formula.
+pub const UNNUMBERED_LABEL_CODE: i32 = 25;
+
+/// The diagnostic code of a `\label` inside of an inline formula.
+pub const INLINE_LABEL_CODE: i32 = 26;
+
+/// The math environments that number their formulas unless they are starred.
+pub const NUMBERED_ENVIRONMENTS: &[&str] = &[
+    "equation", "align", "alignat", "gather", "multline", "flalign", "eqnarray", "xalignat",
+];
+
+pub fn analyze_latex_math_labels(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    for label in data
+        .root
+        .descendants()
+        .filter_map(latex::LabelDefinition::cast)
+    {
+        let (code, message) = match find_math_context(label.syntax()) {
+            Some(MathContext::Unnumbered(node)) if !has_tag(node) => (
+                UNNUMBERED_LABEL_CODE,
+                "The label refers to an unnumbered formula; use a numbered environment instead",
+            ),
+            Some(MathContext::Inline(_)) => (
+                INLINE_LABEL_CODE,
+                "The label inside of an inline formula refers to the surrounding section",
+            ),
+            _ => continue,
+        };
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(label.small_range()),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: message.to_string(),
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
+
+/// The innermost formula that determines the number of a label.
+pub enum MathContext<'a> {
+    Numbered(&'a latex::SyntaxNode),
+    Unnumbered(&'a latex::SyntaxNode),
+    Inline(&'a latex::SyntaxNode),
+}
+
+pub fn find_math_context(node: &latex::SyntaxNode) -> Option<MathContext> {
+    node.ancestors().find_map(|node| match node.kind() {
+        latex::EQUATION => Some(MathContext::Unnumbered(node)),
+        latex::FORMULA if node.first_token().map(|token| token.text()) == Some("$$") => {
+            Some(MathContext::Unnumbered(node))
+        }
+        latex::FORMULA => Some(MathContext::Inline(node)),
+        latex::ENVIRONMENT => {
+            let name = latex::Environment::cast(node)?
+                .begin()?
+                .name()?
+                .key()?
+                .to_string();
+
+            if name == "math" {
+                Some(MathContext::Inline(node))
+            } else if name == "displaymath" {
+                Some(MathContext::Unnumbered(node))
+            } else if NUMBERED_ENVIRONMENTS.contains(&name.as_str()) {
+                Some(MathContext::Numbered(node))
+            } else if NUMBERED_ENVIRONMENTS.contains(&name.trim_end_matches('*')) {
+                Some(MathContext::Unnumbered(node))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    })
+}
+
+/// Checks if the formula is numbered manually with `\tag`.
+fn has_tag(node: &latex::SyntaxNode) -> bool {
+    node.descendants()
+        .filter_map(latex::GenericCommand::cast)
+        .filter_map(|command| command.name())
+        .any(|name| name.text() == "\\tag" || name.text() == "\\tag*")
+}
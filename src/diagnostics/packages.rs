@@ -0,0 +1,287 @@
+use std::sync::Arc;
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+use rustc_hash::FxHashSet;
+
+use crate::{
+    component_db::COMPONENT_DATABASE,
+    find_command_definitions,
+    syntax::{
+        latex::{self, ExplicitLinkKind},
+        CstNode,
+    },
+    Document, LineIndexExt, Uri, Workspace, WorkspaceSubset,
+};
+
+/// The diagnostic code of a command whose package is not loaded.
+/// The name of the package is stored in the data of the diagnostic.
+pub const MISSING_PACKAGE_CODE: i32 = 39;
+
+/// Commands that are commonly used without loading their package.
+/// The first package is suggested if none of the packages is loaded.
+const PREREQUISITES: &[(&str, &[&str])] = &[
+    ("\\includegraphics", &["graphicx", "graphics"]),
+    ("\\toprule", &["booktabs"]),
+    ("\\midrule", &["booktabs"]),
+    ("\\bottomrule", &["booktabs"]),
+    ("\\cmidrule", &["booktabs"]),
+    ("\\multirow", &["multirow"]),
+    ("\\href", &["hyperref"]),
+    ("\\autoref", &["hyperref"]),
+    ("\\url", &["url", "hyperref", "xurl"]),
+    ("\\cref", &["cleveref"]),
+    ("\\Cref", &["cleveref"]),
+    ("\\eqref", &["amsmath"]),
+    ("\\text", &["amsmath", "amstext"]),
+    ("\\mathbb", &["amssymb", "amsfonts"]),
+    ("\\textcolor", &["xcolor", "color"]),
+    ("\\definecolor", &["xcolor", "color"]),
+    ("\\qty", &["siunitx"]),
+    ("\\SI", &["siunitx"]),
+    ("\\si", &["siunitx"]),
+    ("\\num", &["siunitx"]),
+    ("\\enquote", &["csquotes"]),
+    ("\\usetikzlibrary", &["tikz"]),
+    ("\\lipsum", &["lipsum"]),
+    ("\\todo", &["todonotes"]),
+];
+
+/// Packages that are loaded by a class or package although the component database
+/// does not list them as references. Other components without references are treated as unknown.
+const IMPLIED_PACKAGES: &[(&str, &[&str])] = &[
+    ("article.cls", &[]),
+    ("report.cls", &[]),
+    ("book.cls", &[]),
+    ("letter.cls", &[]),
+    (
+        "beamer.cls",
+        &[
+            "graphicx", "graphics", "hyperref", "url", "xcolor", "color", "amsmath", "amstext",
+            "amssymb", "amsfonts",
+        ],
+    ),
+    ("pgf.sty", &["graphicx", "graphics", "xcolor", "color"]),
+    (
+        "tikz.sty",
+        &["pgf", "graphicx", "graphics", "xcolor", "color"],
+    ),
+    (
+        "pgfplots.sty",
+        &["tikz", "pgf", "graphicx", "graphics", "xcolor", "color"],
+    ),
+];
+
+fn find_implied_packages(file_name: &str) -> Option<&'static [&'static str]> {
+    IMPLIED_PACKAGES
+        .iter()
+        .find(|(name, _)| *name == file_name)
+        .map(|(_, packages)| *packages)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct MissingPackage {
+    pub range: TextRange,
+    pub command: &'static str,
+    pub package: &'static str,
+}
+
+pub fn analyze_latex_packages(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    for missing in find_missing_packages(&subset, &document) {
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(missing.range),
+                severity: Some(DiagnosticSeverity::Warning),
+                code: Some(NumberOrString::Number(MISSING_PACKAGE_CODE)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!(
+                    "{} requires the {} package",
+                    missing.command, missing.package
+                ),
+                related_information: None,
+                tags: None,
+                data: Some(serde_json::Value::String(missing.package.to_string())),
+            },
+        );
+    }
+
+    Some(())
+}
+
+/// Finds the first usage of each command of the document whose package is not loaded by the project.
+/// Nothing is reported if the project does not contain a root document or loads a package
+/// that is not known (or whose references are not known), since it might load the package itself.
+pub fn find_missing_packages(subset: &WorkspaceSubset, document: &Document) -> Vec<MissingPackage> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    let has_root = subset.documents.iter().any(|other| {
+        other
+            .data
+            .as_latex()
+            .map_or(false, |data| data.extras.has_document_environment)
+    });
+
+    let has_unknown_package = subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.explicit_links.iter())
+        .filter(|link| {
+            matches!(
+                link.kind,
+                ExplicitLinkKind::Package | ExplicitLinkKind::Class
+            )
+        })
+        .any(|link| {
+            let is_known = link.as_component_name().map_or(false, |name| {
+                find_implied_packages(&name).is_some()
+                    || COMPONENT_DATABASE
+                        .find(&name)
+                        .map_or(false, |component| !component.references.is_empty())
+            });
+
+            let is_local = link.targets.iter().any(|target| {
+                subset
+                    .documents
+                    .iter()
+                    .any(|document| document.uri == *target)
+            });
+
+            !is_known && !is_local
+        });
+
+    if !has_root || has_unknown_package {
+        return Vec::new();
+    }
+
+    let mut loaded_packages = FxHashSet::default();
+    for file_name in COMPONENT_DATABASE
+        .linked_components(subset)
+        .into_iter()
+        .flat_map(|component| component.file_names.iter())
+    {
+        if let Some(package) = file_name.strip_suffix(".sty") {
+            loaded_packages.insert(package.to_string());
+        }
+
+        for package in find_implied_packages(file_name).unwrap_or_default() {
+            loaded_packages.insert(package.to_string());
+        }
+    }
+
+    let mut reported_commands = FxHashSet::default();
+    let mut missing_packages = Vec::new();
+    for token in data
+        .root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind().is_command_name())
+    {
+        let name = token.text().trim_end_matches('*');
+        let (command, packages) = match PREREQUISITES.iter().find(|(command, _)| *command == name) {
+            Some(prerequisite) => *prerequisite,
+            None => continue,
+        };
+
+        if packages
+            .iter()
+            .any(|package| loaded_packages.contains(*package))
+            || !reported_commands.insert(command)
+            || !find_command_definitions(subset, command).is_empty()
+        {
+            continue;
+        }
+
+        missing_packages.push(MissingPackage {
+            range: token.text_range(),
+            command,
+            package: packages[0],
+        });
+    }
+
+    missing_packages
+}
+
+/// Returns the position after the last `\usepackage` (or the `\documentclass`) of the preamble
+/// where a new package can be loaded.
+pub fn find_package_insert_position(root: &latex::SyntaxNode) -> Option<TextSize> {
+    root.descendants()
+        .take_while(|node| {
+            latex::Environment::cast(node)
+                .and_then(|environment| environment.begin()?.name()?.key())
+                .map_or(true, |name| name.to_string() != "document")
+        })
+        .filter(|node| matches!(node.kind(), latex::PACKAGE_INCLUDE | latex::CLASS_INCLUDE))
+        .filter_map(latex::Include::cast)
+        .last()
+        .map(|include| include.small_range().end())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DocumentLanguage, ServerContext};
+
+    use super::*;
+
+    fn find_commands(text: &str) -> Vec<&'static str> {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap();
+        let document = Arc::new(Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        ));
+
+        let subset = WorkspaceSubset {
+            documents: vec![Arc::clone(&document)],
+        };
+
+        find_missing_packages(&subset, &document)
+            .into_iter()
+            .map(|missing| missing.command)
+            .collect()
+    }
+
+    #[test]
+    fn test_missing_package() {
+        let text = "\\documentclass{article}\n\\begin{document}\n\\includegraphics{foo}\n\\includegraphics{bar}\n\\toprule\n\\end{document}";
+        assert_eq!(find_commands(text), vec!["\\includegraphics", "\\toprule"]);
+    }
+
+    #[test]
+    fn test_loaded_package() {
+        let text = "\\documentclass{article}\n\\usepackage{graphicx}\n\\begin{document}\n\\includegraphics{foo}\n\\end{document}";
+        assert!(find_commands(text).is_empty());
+    }
+
+    #[test]
+    fn test_beamer() {
+        let text = "\\documentclass{beamer}\n\\begin{document}\n\\includegraphics{foo}\n\\href{bar}{baz}\n\\url{qux}\n\\textcolor{red}{x}\n\\toprule\n\\end{document}";
+        assert_eq!(find_commands(text), vec!["\\toprule"]);
+    }
+
+    #[test]
+    fn test_tikz() {
+        let text = "\\documentclass{article}\n\\usepackage{tikz}\n\\definecolor{foo}{rgb}{1,0,0}\n\\begin{document}\n\\textcolor{foo}{x}\n\\includegraphics{bar}\n\\end{document}";
+        assert!(find_commands(text).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_references() {
+        let text = "\\documentclass{article}\n\\usepackage{fontspec}\n\\begin{document}\n\\includegraphics{foo}\n\\end{document}";
+        assert!(find_commands(text).is_empty());
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{count_words, LineIndexExt, Uri, Workspace};
+
+/// The diagnostic code of placeholder text like `\lipsum` or `\blindtext`.
+pub const PLACEHOLDER_TEXT_CODE: i32 = 35;
+
+pub fn analyze_latex_placeholders(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let count = count_words(&data.root);
+    let total_words = count.words + count.placeholder_words();
+    for placeholder in &count.placeholders {
+        let share = placeholder.word_count() * 100 / total_words.max(1);
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(placeholder.range),
+                severity: Some(DiagnosticSeverity::Hint),
+                code: Some(NumberOrString::Number(PLACEHOLDER_TEXT_CODE)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!(
+                    "Placeholder text of about {} words ({}% of the document)",
+                    placeholder.word_count(),
+                    share
+                ),
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
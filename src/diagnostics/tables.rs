@@ -0,0 +1,346 @@
+use std::{iter::Peekable, str::Chars, sync::Arc};
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Uri, Workspace,
+};
+
+/// The environments with a column specification and the index of its argument.
+const TABLE_ENVIRONMENTS: &[(&str, usize)] = &[
+    ("tabular", 0),
+    ("tabular*", 1),
+    ("tabularx", 1),
+    ("tabulary", 1),
+    ("array", 0),
+    ("longtable", 0),
+];
+
+/// Commands that do not create a cell when they make up a row on their own.
+const RULE_COMMANDS: &[&str] = &[
+    "\\hline",
+    "\\cline",
+    "\\toprule",
+    "\\midrule",
+    "\\bottomrule",
+    "\\cmidrule",
+    "\\addlinespace",
+    "\\morecmidrules",
+    "\\specialrule",
+    "\\noalign",
+    "\\endhead",
+    "\\endfirsthead",
+    "\\endfoot",
+    "\\endlastfoot",
+];
+
+const ROW_END_COMMANDS: &[&str] = &["\\\\", "\\tabularnewline"];
+
+pub fn analyze_latex_tables(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let is_booktabs_loaded = subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.explicit_links.iter())
+        .any(|link| link.kind == latex::ExplicitLinkKind::Package && link.stem == "booktabs");
+
+    for environment in data.root.descendants().filter_map(latex::Environment::cast) {
+        analyze_table(
+            &document,
+            diagnostics_by_uri,
+            environment,
+            is_booktabs_loaded,
+        );
+    }
+
+    Some(())
+}
+
+fn analyze_table(
+    document: &Document,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    environment: latex::Environment,
+    is_booktabs_loaded: bool,
+) -> Option<()> {
+    let name = environment.begin()?.name()?.key()?.to_string();
+    let (_, spec_index) = TABLE_ENVIRONMENTS
+        .iter()
+        .find(|(table_name, _)| *table_name == name)?;
+
+    let mut report = |range: TextRange, severity, code: i32, message: String| {
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(range),
+                severity: Some(severity),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    };
+
+    let spec = environment
+        .syntax()
+        .children()
+        .filter_map(latex::CurlyGroup::cast)
+        .nth(*spec_index)?;
+    let column_count = count_columns(&spec.content_text()?);
+    let body_start = spec.small_range().end();
+
+    let mut width = 1;
+    let mut has_content = false;
+    for node in environment
+        .syntax()
+        .children()
+        .filter(|node| node.text_range().start() >= body_start)
+    {
+        if let Some(text) = latex::Text::cast(node) {
+            for token in text
+                .syntax()
+                .children_with_tokens()
+                .filter_map(|child| child.into_token())
+                .filter(|token| token.kind() == latex::WORD)
+            {
+                has_content = true;
+                for (i, _) in token.text().match_indices('&') {
+                    width += 1;
+                    let start = token.text_range().start() + TextSize::from(i as u32);
+                    match column_count {
+                        Some(column_count) if width == column_count + 1 => report(
+                            TextRange::at(start, TextSize::from(1)),
+                            DiagnosticSeverity::Warning,
+                            28,
+                            format!(
+                                "Extra alignment tab: the table has only {} columns",
+                                column_count
+                            ),
+                        ),
+                        _ => {}
+                    }
+                }
+            }
+        } else if let Some(command) = latex::GenericCommand::cast(node) {
+            match command.name().map_or("", |name| name.text()) {
+                name if ROW_END_COMMANDS.contains(&name) => {
+                    if let Some(column_count) = column_count {
+                        if has_content && width < column_count {
+                            report(
+                                command.small_range(),
+                                DiagnosticSeverity::Information,
+                                40,
+                                format!("The row has {} of {} columns", width, column_count),
+                            );
+                        }
+                    }
+
+                    width = 1;
+                    has_content = false;
+                }
+                "\\hline" if is_booktabs_loaded => {
+                    report(
+                        command.small_range(),
+                        DiagnosticSeverity::Hint,
+                        30,
+                        "Use \\toprule, \\midrule or \\bottomrule of booktabs instead of \\hline"
+                            .to_string(),
+                    );
+                }
+                "\\multicolumn" => {
+                    has_content = true;
+                    let span: usize = command
+                        .syntax()
+                        .children()
+                        .find_map(latex::CurlyGroup::cast)
+                        .and_then(|group| group.content_text())
+                        .and_then(|text| text.parse().ok())
+                        .unwrap_or(1);
+
+                    let end = width + span.saturating_sub(1);
+                    if let Some(column_count) = column_count.filter(|count| end > *count) {
+                        report(
+                            command.small_range(),
+                            DiagnosticSeverity::Warning,
+                            29,
+                            format!(
+                                "\\multicolumn spans the columns {} to {} but the table has only {} columns",
+                                width, end, column_count
+                            ),
+                        );
+                    }
+                    width = end;
+                }
+                name if RULE_COMMANDS.contains(&name) => {}
+                _ => has_content = true,
+            }
+        } else if latex::End::cast(node).is_none() {
+            has_content = true;
+        }
+    }
+
+    Some(())
+}
+
+/// Counts the columns of a column specification like `l|p{3cm}*{2}{c}`.
+/// Returns `None` if the specification contains commands.
+fn count_columns(spec: &str) -> Option<usize> {
+    let mut chars = spec.chars().peekable();
+    let mut count = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '|' | ':' => {}
+            '@' | '!' | '>' | '<' => {
+                group(&mut chars)?;
+            }
+            'p' | 'm' | 'b' => {
+                group(&mut chars)?;
+                count += 1;
+            }
+            'w' | 'W' => {
+                group(&mut chars)?;
+                group(&mut chars)?;
+                count += 1;
+            }
+            'D' => {
+                group(&mut chars)?;
+                group(&mut chars)?;
+                group(&mut chars)?;
+                count += 1;
+            }
+            'S' | 's' => {
+                skip_whitespace(&mut chars);
+                if chars.peek() == Some(&'[') {
+                    chars.by_ref().find(|c| *c == ']')?;
+                }
+                count += 1;
+            }
+            '*' => {
+                let repetitions: usize = group(&mut chars)?.trim().parse().ok()?;
+                let columns = repetitions.checked_mul(count_columns(&group(&mut chars)?)?)?;
+                count = count.checked_add(columns)?;
+            }
+            c if c.is_whitespace() => {}
+            c if c.is_ascii_alphabetic() => count += 1,
+            _ => return None,
+        }
+    }
+    Some(count)
+}
+
+/// Reads the content of the group that follows in a column specification.
+fn group(chars: &mut Peekable<Chars>) -> Option<String> {
+    skip_whitespace(chars);
+    if chars.next()? != '{' {
+        return None;
+    }
+
+    let mut depth = 1;
+    let mut content = String::new();
+    for c in chars {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(content);
+                }
+            }
+            _ => {}
+        }
+        content.push(c);
+    }
+    None
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.peek().map_or(false, |c| c.is_whitespace()) {
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_codes(text: &str) -> Vec<Option<NumberOrString>> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_tables(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri
+            .get_vec(&uri)
+            .map(|diagnostics| diagnostics.iter().map(|d| d.code.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    fn codes(codes: &[i32]) -> Vec<Option<NumberOrString>> {
+        codes
+            .iter()
+            .map(|code| Some(NumberOrString::Number(*code)))
+            .collect()
+    }
+
+    #[test]
+    fn test_count_columns() {
+        assert_eq!(count_columns("l|p{3cm}*{2}{c}"), Some(4));
+        assert_eq!(count_columns("@{}l@{\\quad}r@{}"), Some(2));
+        assert_eq!(count_columns("*{3}{|c}|"), Some(3));
+        assert_eq!(count_columns("*{2}{*{2}{c}}"), Some(4));
+        assert_eq!(count_columns("\\bfseries c"), None);
+        assert_eq!(count_columns("*{18446744073709551615}{cc}"), None);
+    }
+
+    #[test]
+    fn test_valid_table() {
+        let text = "\\begin{tabular}{@{}l*{2}{c}@{}} a & b & c \\\\ d & e & f \\\\ \\end{tabular}";
+        assert!(find_codes(text).is_empty());
+    }
+
+    #[test]
+    fn test_extra_alignment_tab() {
+        let text = "\\begin{tabular}{*{2}{c}} a & b & c \\\\ \\end{tabular}";
+        assert_eq!(find_codes(text), codes(&[28]));
+    }
+
+    #[test]
+    fn test_incomplete_row() {
+        let text = "\\begin{tabular}{lll} a & b \\\\ \\end{tabular}";
+        assert_eq!(find_codes(text), codes(&[40]));
+    }
+
+    #[test]
+    fn test_multicolumn() {
+        let text = "\\begin{tabular}{ll} \\multicolumn{2}{c}{a} \\\\ \\end{tabular}";
+        assert!(find_codes(text).is_empty());
+
+        let text = "\\begin{tabular}{ll} a & \\multicolumn{2}{c}{b} \\\\ \\end{tabular}";
+        assert_eq!(find_codes(text), codes(&[29]));
+    }
+}
@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{
+    syntax::{
+        latex::{self, HasKeyValueBody},
+        CstNode,
+    },
+    LineIndexExt, Uri, Workspace,
+};
+
+/// The diagnostic code of a missing `~` before a reference or citation.
+pub const MISSING_TIE_CODE: i32 = 16;
+
+/// The diagnostic code of a `~` before a citation that is typeset as a footnote.
+pub const FOOTNOTE_TIE_CODE: i32 = 17;
+
+/// Commands that should be bound to the preceding word with `~`.
+const TIED_COMMANDS: &[&str] = &["\\ref", "\\eqref", "\\pageref", "\\cite"];
+
+const FOOTNOTE_CITATION_COMMANDS: &[&str] = &[
+    "\\footcite",
+    "\\footfullcite",
+    "\\footcitetext",
+    "\\smartcite",
+    "\\Smartcite",
+];
+
+const AUTOCITE_COMMANDS: &[&str] = &["\\autocite", "\\Autocite", "\\autocite*", "\\Autocite*"];
+
+pub fn analyze_latex_ties(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    let is_footnote_autocite = subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .any(|data| has_footnote_autocite(&data.root));
+
+    let text = &document.text;
+    for node in data.root.descendants() {
+        let command = match latex::LabelReference::cast(node)
+            .and_then(|reference| reference.command())
+            .or_else(|| latex::Citation::cast(node).and_then(|citation| citation.command()))
+        {
+            Some(command) => command,
+            None => continue,
+        };
+
+        let name = command.text();
+        let start = command.text_range().start();
+        let before = &text[..usize::from(start)];
+        let (code, range, message) = if TIED_COMMANDS.contains(&name) {
+            let space = before.trim_end_matches(&[' ', '\t'][..]);
+            if space.len() == before.len()
+                || !space.ends_with(|c: char| c.is_alphanumeric() || c == '.')
+            {
+                continue;
+            }
+
+            let range = TextRange::new(TextSize::of(space), start);
+            let message = format!("Use a non-breaking space (~) before {}", name);
+            (MISSING_TIE_CODE, range, message)
+        } else if FOOTNOTE_CITATION_COMMANDS.contains(&name)
+            || (is_footnote_autocite && AUTOCITE_COMMANDS.contains(&name))
+        {
+            if !before.ends_with('~') {
+                continue;
+            }
+
+            let range = TextRange::new(start - TextSize::of('~'), start);
+            let message = format!(
+                "The footnote mark of {} is separated by a non-breaking space",
+                name
+            );
+            (FOOTNOTE_TIE_CODE, range, message)
+        } else {
+            continue;
+        };
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(range),
+                severity: Some(DiagnosticSeverity::Information),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
+
+/// Checks if `biblatex` is loaded with a style that typesets `\autocite` as a footnote.
+fn has_footnote_autocite(root: &latex::SyntaxNode) -> bool {
+    root.descendants()
+        .filter_map(latex::Include::cast)
+        .filter(|include| include.syntax().kind() == latex::PACKAGE_INCLUDE)
+        .filter(|include| {
+            include.path_list().map_or(false, |paths| {
+                paths.keys().any(|path| path.to_string() == "biblatex")
+            })
+        })
+        .filter_map(|include| include.options()?.body())
+        .flat_map(|body| body.pairs())
+        .any(|pair| {
+            let key = pair.key().map(|key| key.to_string());
+            let value = pair
+                .value()
+                .map(|value| value.syntax().text().to_string().trim().to_string());
+
+            match (key.as_deref(), value.as_deref()) {
+                (Some("autocite"), Some(value)) => value == "footnote",
+                (Some("style"), Some(value)) => {
+                    value.starts_with("verbose") || value.starts_with("authortitle")
+                }
+                _ => false,
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(text: &str) -> Vec<(Option<NumberOrString>, Range)> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_latex_ties(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri
+            .get_vec(&uri)
+            .map(|diagnostics| {
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| (diagnostic.code.clone(), diagnostic.range))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn range(start: u32, end: u32) -> Range {
+        Range::new(Position::new(0, start), Position::new(0, end))
+    }
+
+    #[test]
+    fn test_tie() {
+        assert!(find_diagnostics("see Fig.~\\ref{fig:foo}").is_empty());
+    }
+
+    #[test]
+    fn test_missing_tie() {
+        assert_eq!(
+            find_diagnostics("see Fig. \\ref{fig:foo} (\\cite{bar})"),
+            vec![(Some(NumberOrString::Number(MISSING_TIE_CODE)), range(8, 9))]
+        );
+    }
+
+    #[test]
+    fn test_line_start() {
+        assert!(find_diagnostics("see Fig.\n\\ref{fig:foo}").is_empty());
+    }
+
+    #[test]
+    fn test_footnote_tie() {
+        assert_eq!(
+            find_diagnostics("foo~\\footcite{bar}"),
+            vec![(Some(NumberOrString::Number(FOOTNOTE_TIE_CODE)), range(3, 4))]
+        );
+    }
+
+    #[test]
+    fn test_footnote_autocite() {
+        let text = "\\usepackage[style=verbose]{biblatex}\nfoo~\\autocite{bar}";
+        assert_eq!(
+            find_diagnostics(text),
+            vec![(
+                Some(NumberOrString::Number(FOOTNOTE_TIE_CODE)),
+                Range::new(Position::new(1, 3), Position::new(1, 4))
+            )]
+        );
+
+        assert!(find_diagnostics("foo~\\autocite{bar}").is_empty());
+    }
+}
@@ -0,0 +1,39 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{find_todos, LineIndexExt, Uri, Workspace};
+
+pub fn analyze_latex_todos(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    for todo in find_todos(&data.root, &data.extras.code_chunks) {
+        let message = if todo.text.is_empty() {
+            todo.kind
+        } else {
+            format!("{}: {}", todo.kind, todo.text)
+        };
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(todo.range),
+                severity: Some(DiagnosticSeverity::Hint),
+                code: Some(NumberOrString::Number(21)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
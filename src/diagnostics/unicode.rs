@@ -0,0 +1,246 @@
+use std::sync::Arc;
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{LineIndexExt, Uri, Workspace};
+
+/// The diagnostic code of an invisible character like a zero-width space.
+pub const INVISIBLE_CHARACTER_CODE: i32 = 31;
+
+/// The diagnostic code of a letter that looks like a Latin letter inside of a Latin word.
+pub const HOMOGLYPH_CODE: i32 = 32;
+
+/// Invisible characters together with their name and replacement.
+const INVISIBLE_CHARACTERS: &[(char, &str, &str)] = &[
+    ('\u{00A0}', "non-breaking space", "~"),
+    ('\u{00AD}', "soft hyphen", "\\-"),
+    ('\u{200B}', "zero-width space", ""),
+    ('\u{200C}', "zero-width non-joiner", ""),
+    ('\u{200D}', "zero-width joiner", ""),
+    ('\u{2060}', "word joiner", ""),
+    ('\u{202F}', "narrow non-breaking space", "\\,"),
+    ('\u{FEFF}', "zero-width no-break space", ""),
+];
+
+/// Cyrillic and Greek letters that look like Latin letters.
+const HOMOGLYPHS: &[(char, &str)] = &[
+    ('а', "a"),
+    ('е', "e"),
+    ('і', "i"),
+    ('ј', "j"),
+    ('о', "o"),
+    ('р', "p"),
+    ('с', "c"),
+    ('ѕ', "s"),
+    ('у', "y"),
+    ('х', "x"),
+    ('А', "A"),
+    ('В', "B"),
+    ('Е', "E"),
+    ('І', "I"),
+    ('Ј', "J"),
+    ('К', "K"),
+    ('М', "M"),
+    ('Н', "H"),
+    ('О', "O"),
+    ('Р', "P"),
+    ('С', "C"),
+    ('Ѕ', "S"),
+    ('Т', "T"),
+    ('Х', "X"),
+    ('Α', "A"),
+    ('Β', "B"),
+    ('Ε', "E"),
+    ('Ζ', "Z"),
+    ('Η', "H"),
+    ('Ι', "I"),
+    ('Κ', "K"),
+    ('Μ', "M"),
+    ('Ν', "N"),
+    ('Ο', "O"),
+    ('Ρ', "P"),
+    ('Τ', "T"),
+    ('Υ', "Y"),
+    ('Χ', "X"),
+    ('ο', "o"),
+];
+
+/// Returns the text that replaces an invisible character or a homoglyph.
+pub fn suspicious_char_replacement(c: char) -> Option<&'static str> {
+    INVISIBLE_CHARACTERS
+        .iter()
+        .find(|(other, _, _)| *other == c)
+        .map(|(_, _, replacement)| *replacement)
+        .or_else(|| {
+            HOMOGLYPHS
+                .iter()
+                .find(|(other, _)| *other == c)
+                .map(|(_, replacement)| *replacement)
+        })
+}
+
+pub fn analyze_unicode_characters(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    if document.data.as_latex().is_none() && document.data.as_bibtex().is_none() {
+        return None;
+    }
+
+    let text = &document.text;
+    let mut previous = None;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+        let is_latin = |c: Option<char>| c.map_or(false, |c| c.is_ascii_alphabetic());
+        let (code, severity, message) = if let Some((_, name, _)) = INVISIBLE_CHARACTERS
+            .iter()
+            .find(|(other, _, _)| *other == c)
+        {
+            let severity = if c == '\u{00A0}' {
+                DiagnosticSeverity::Information
+            } else {
+                DiagnosticSeverity::Warning
+            };
+
+            (
+                INVISIBLE_CHARACTER_CODE,
+                severity,
+                format!("Invisible character: {} (U+{:04X})", name, c as u32),
+            )
+        } else if let Some((_, latin)) = HOMOGLYPHS
+            .iter()
+            .find(|(other, _)| *other == c)
+            .filter(|_| is_latin(previous) || is_latin(next))
+        {
+            (
+                HOMOGLYPH_CODE,
+                DiagnosticSeverity::Warning,
+                format!(
+                    "The character \"{}\" (U+{:04X}) looks like the Latin letter \"{}\"",
+                    c, c as u32, latin
+                ),
+            )
+        } else {
+            previous = Some(c);
+            continue;
+        };
+
+        previous = Some(c);
+        let range = TextRange::at(TextSize::from(i as u32), TextSize::of(c));
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(range),
+                severity: Some(severity),
+                code: Some(NumberOrString::Number(code)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message,
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, Range};
+
+    use crate::{create_workspace_fast, DocumentLanguage, ServerContext, WorkspaceSource};
+
+    use super::*;
+
+    fn find_diagnostics(
+        text: &str,
+    ) -> Vec<(Option<NumberOrString>, Option<DiagnosticSeverity>, Range)> {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+
+        let mut diagnostics_by_uri = MultiMap::new();
+        analyze_unicode_characters(&workspace, &mut diagnostics_by_uri, &uri);
+        diagnostics_by_uri
+            .get_vec(&uri)
+            .map(|diagnostics| {
+                diagnostics
+                    .iter()
+                    .map(|diagnostic| {
+                        (
+                            diagnostic.code.clone(),
+                            diagnostic.severity,
+                            diagnostic.range,
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn range(start: u32, end: u32) -> Range {
+        Range::new(Position::new(0, start), Position::new(0, end))
+    }
+
+    #[test]
+    fn test_invisible_character() {
+        assert_eq!(
+            find_diagnostics("foo\u{200B}bar"),
+            vec![(
+                Some(NumberOrString::Number(INVISIBLE_CHARACTER_CODE)),
+                Some(DiagnosticSeverity::Warning),
+                range(3, 4)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_non_breaking_space() {
+        assert_eq!(
+            find_diagnostics("Fig.\u{00A0}\\ref{fig:foo}"),
+            vec![(
+                Some(NumberOrString::Number(INVISIBLE_CHARACTER_CODE)),
+                Some(DiagnosticSeverity::Information),
+                range(4, 5)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_homoglyph() {
+        assert_eq!(
+            find_diagnostics("\\ref{fig:cаt}"),
+            vec![(
+                Some(NumberOrString::Number(HOMOGLYPH_CODE)),
+                Some(DiagnosticSeverity::Warning),
+                range(10, 11)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_cyrillic_word() {
+        assert!(find_diagnostics("Привет, мир, сорт").is_empty());
+    }
+
+    #[test]
+    fn test_replacement() {
+        assert_eq!(suspicious_char_replacement('\u{00A0}'), Some("~"));
+        assert_eq!(suspicious_char_replacement('\u{200B}'), Some(""));
+        assert_eq!(suspicious_char_replacement('о'), Some("o"));
+        assert_eq!(suspicious_char_replacement('o'), None);
+    }
+}
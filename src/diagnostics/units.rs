@@ -0,0 +1,38 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{find_quantities, is_siunitx_loaded, LineIndexExt, Uri, Workspace};
+
+pub fn analyze_latex_units(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    let data = document.data.as_latex()?;
+    let subset = workspace.subset(Arc::clone(&document.uri))?;
+    if !is_siunitx_loaded(&subset.documents) {
+        return None;
+    }
+
+    for quantity in find_quantities(&data.root, &document.text) {
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(quantity.range),
+                severity: Some(DiagnosticSeverity::Information),
+                code: Some(NumberOrString::Number(18)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!("Use {} to typeset the quantity", quantity.to_qty()),
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
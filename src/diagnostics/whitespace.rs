@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, NumberOrString};
+use multimap::MultiMap;
+
+use crate::{find_whitespace_issues, DocumentLanguage, LineEnding, LineIndexExt, Uri, Workspace};
+
+pub const TRAILING_WHITESPACE_CODE: i32 = 37;
+
+pub const MIXED_LINE_ENDINGS_CODE: i32 = 38;
+
+pub fn analyze_whitespace(
+    workspace: &dyn Workspace,
+    diagnostics_by_uri: &mut MultiMap<Arc<Uri>, Diagnostic>,
+    uri: &Uri,
+) -> Option<()> {
+    let document = workspace.get(uri)?;
+    if !matches!(
+        document.language(),
+        DocumentLanguage::Latex | DocumentLanguage::Bibtex
+    ) {
+        return None;
+    }
+
+    let issues = find_whitespace_issues(&document);
+    for range in &issues.trailing_whitespace {
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(*range),
+                severity: Some(DiagnosticSeverity::Hint),
+                code: Some(NumberOrString::Number(TRAILING_WHITESPACE_CODE)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: "Trailing whitespace".to_string(),
+                related_information: None,
+                tags: Some(vec![DiagnosticTag::Unnecessary]),
+                data: None,
+            },
+        );
+    }
+
+    if let Some(range) = issues.mixed_line_endings.first() {
+        let (expected, actual) = match issues.line_ending {
+            LineEnding::Lf => ("LF", "CRLF"),
+            LineEnding::CrLf => ("CRLF", "LF"),
+        };
+
+        diagnostics_by_uri.insert(
+            Arc::clone(&document.uri),
+            Diagnostic {
+                range: document.line_index.line_col_lsp_range(*range),
+                severity: Some(DiagnosticSeverity::Hint),
+                code: Some(NumberOrString::Number(MIXED_LINE_ENDINGS_CODE)),
+                code_description: None,
+                source: Some("texlab".to_string()),
+                message: format!("Mixed line endings ({} instead of {})", actual, expected),
+                related_information: None,
+                tags: None,
+                data: None,
+            },
+        );
+    }
+
+    Some(())
+}
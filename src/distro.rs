@@ -1,16 +1,20 @@
+mod info;
 mod kpsewhich;
 mod miktex;
 mod texlive;
 
-use std::process::{Command, Stdio};
-
 use anyhow::Result;
 use derive_more::Display;
 use log::warn;
+use serde::{Deserialize, Serialize};
 
-pub use kpsewhich::Resolver;
+pub use self::{
+    info::{DistroInfo, ToolInfo},
+    kpsewhich::Resolver,
+};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Display)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum DistributionKind {
     #[display(fmt = "TeXLive")]
     Texlive,
@@ -25,43 +29,40 @@ pub enum DistributionKind {
 #[derive(Debug, Clone)]
 pub struct Distribution {
     pub kind: DistributionKind,
+    pub version: Option<String>,
     pub resolver: Resolver,
 }
 
 impl Distribution {
     pub fn detect() -> Self {
-        let kind = match Command::new("latex").arg("--version").output() {
-            Ok(output) => {
-                let stdout = String::from_utf8_lossy(&output.stdout);
-                if stdout.contains("TeX Live") {
-                    DistributionKind::Texlive
-                } else if stdout.contains("MiKTeX") {
-                    DistributionKind::Miktex
-                } else {
-                    DistributionKind::Unknown
-                }
-            }
-            Err(_) => {
-                if Command::new("tectonic")
-                    .arg("--version")
-                    .stdout(Stdio::null())
-                    .stderr(Stdio::null())
-                    .status()
-                    .is_ok()
-                {
-                    DistributionKind::Tectonic
-                } else {
-                    DistributionKind::Unknown
-                }
-            }
-        };
-
+        let (kind, version) = Self::detect_kind();
         let resolver = match kind {
             DistributionKind::Texlive => Self::load_resolver(texlive::load_resolver),
             DistributionKind::Miktex => Self::load_resolver(miktex::load_resolver),
             DistributionKind::Tectonic | DistributionKind::Unknown => Resolver::default(),
         };
-        Self { kind, resolver }
+        Self {
+            kind,
+            version,
+            resolver,
+        }
+    }
+
+    pub fn detect_kind() -> (DistributionKind, Option<String>) {
+        match info::find_version("latex") {
+            Some(version) => {
+                let kind = match version.as_deref() {
+                    Some(line) if line.contains("TeX Live") => DistributionKind::Texlive,
+                    Some(line) if line.contains("MiKTeX") => DistributionKind::Miktex,
+                    _ => DistributionKind::Unknown,
+                };
+                (kind, version)
+            }
+            None => match info::find_version("tectonic") {
+                Some(version) => (DistributionKind::Tectonic, version),
+                None => (DistributionKind::Unknown, None),
+            },
+        }
     }
 
     fn load_resolver(loader: impl FnOnce() -> Result<Resolver>) -> Resolver {
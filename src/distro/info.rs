@@ -0,0 +1,131 @@
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Distribution, DistributionKind};
+
+const TOOLS: &[&str] = &["latexmk", "biber", "chktex", "latexindent"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistroInfo {
+    pub kind: DistributionKind,
+    pub version: Option<String>,
+    pub tools: Vec<ToolInfo>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolInfo {
+    pub name: String,
+    pub available: bool,
+    pub version: Option<String>,
+    pub install_hint: Option<String>,
+}
+
+impl DistroInfo {
+    pub fn detect(build_executable: &str) -> Self {
+        let (kind, version) = Distribution::detect_kind();
+        Self::new(kind, version, build_executable)
+    }
+
+    pub fn new(kind: DistributionKind, version: Option<String>, build_executable: &str) -> Self {
+        let mut names = vec![build_executable];
+        names.extend(TOOLS.iter().filter(|name| **name != build_executable));
+
+        let tools = names
+            .into_iter()
+            .map(|name| {
+                let version = find_version(name);
+                ToolInfo {
+                    name: name.to_string(),
+                    available: version.is_some(),
+                    install_hint: version.is_none().then(|| make_install_hint(kind, name)),
+                    version: version.flatten(),
+                }
+            })
+            .collect();
+
+        Self {
+            kind,
+            version,
+            tools,
+        }
+    }
+
+    pub fn missing_tools(&self) -> impl Iterator<Item = &ToolInfo> {
+        self.tools.iter().filter(|tool| !tool.available)
+    }
+}
+
+pub(super) fn find_version(executable: &str) -> Option<Option<String>> {
+    let output = Command::new(executable)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    Some(first_line(&String::from_utf8_lossy(&output.stdout)))
+}
+
+fn first_line(output: &str) -> Option<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToString::to_string)
+}
+
+fn make_install_hint(kind: DistributionKind, name: &str) -> String {
+    match kind {
+        DistributionKind::Texlive => format!("Run `tlmgr install {}`.", name),
+        DistributionKind::Miktex => format!(
+            "Install the `{}` package with the MiKTeX Console or `mpm --install={}`.",
+            name, name
+        ),
+        DistributionKind::Tectonic if name == "latexmk" => {
+            "Tectonic does not use latexmk; set `texlab.build.executable` to `tectonic`.".into()
+        }
+        DistributionKind::Tectonic | DistributionKind::Unknown => format!(
+            "Install `{}` or a TeX distribution that provides it (TeX Live, MiKTeX) and make sure it is in the PATH.",
+            name
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_line() {
+        let output = "\nbiber version: 2.17\nCopyright\n";
+        assert_eq!(first_line(output).as_deref(), Some("biber version: 2.17"));
+        assert_eq!(first_line(""), None);
+    }
+
+    #[test]
+    fn test_missing_tool() {
+        let info = DistroInfo::new(
+            DistributionKind::Texlive,
+            None,
+            "texlab-nonexistent-build-tool",
+        );
+
+        let missing: Vec<_> = info.missing_tools().map(|tool| &tool.name).collect();
+        assert!(missing.contains(&&"texlab-nonexistent-build-tool".to_string()));
+        assert_eq!(
+            info.tools[0].install_hint.as_deref(),
+            Some("Run `tlmgr install texlab-nonexistent-build-tool`.")
+        );
+    }
+
+    #[test]
+    fn test_tectonic_hint() {
+        assert_eq!(
+            make_install_hint(DistributionKind::Tectonic, "latexmk"),
+            "Tectonic does not use latexmk; set `texlab.build.executable` to `tectonic`."
+        );
+    }
+}
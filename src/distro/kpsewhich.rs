@@ -6,7 +6,7 @@ use std::{
     process::Command,
 };
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use rustc_hash::FxHashMap;
 use smol_str::SmolStr;
 
@@ -42,6 +42,56 @@ pub fn parse_database(
     Ok(Resolver::new(files_by_name))
 }
 
+/// Adds the files of the personal TEXMF tree (`TEXMFHOME`) to the resolver.
+/// Unlike the trees of the distribution, this tree usually does not have a file name database,
+/// so it is searched directly.
+pub fn add_home_directory(resolver: &mut Resolver) -> Result<()> {
+    let directory = PathBuf::from(run(&["-var-value", "TEXMFHOME"])?);
+    add_directory(resolver, &directory);
+    Ok(())
+}
+
+fn add_directory(resolver: &mut Resolver, directory: &Path) {
+    let mut paths = Vec::new();
+    read_directory(directory, 0, &mut paths);
+    for path in paths {
+        if DocumentLanguage::by_path(&path).is_some() {
+            if let Some(name) = path.file_name().and_then(OsStr::to_str).map(Into::into) {
+                resolver.files_by_name.insert(name, path);
+            }
+        }
+    }
+}
+
+const MAX_DIRECTORY_DEPTH: usize = 8;
+
+fn read_directory(directory: &Path, depth: usize, paths: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        let is_hidden = path
+            .file_name()
+            .and_then(OsStr::to_str)
+            .map_or(true, |name| name.starts_with('.'));
+
+        if is_hidden {
+            continue;
+        }
+
+        if path.is_dir() {
+            if depth < MAX_DIRECTORY_DEPTH {
+                read_directory(&path, depth + 1, paths);
+            }
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
 fn make_absolute(root_directories: &[PathBuf], relative_path: &Path) -> Option<PathBuf> {
     for dir in root_directories.iter().rev() {
         if let Ok(path) = fs::canonicalize(dir.join(&relative_path)) {
@@ -67,8 +117,44 @@ fn run(args: impl IntoIterator<Item = impl AsRef<OsStr>>) -> Result<String> {
     let result = String::from_utf8(output.stdout)?
         .lines()
         .next()
-        .unwrap()
+        .context("kpsewhich did not return a result")?
         .into();
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_home_directory() {
+        let home = tempfile::tempdir().unwrap();
+        let write = |path: PathBuf| {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        };
+
+        write(home.path().join("tex/latex/foo/foo.sty"));
+        write(home.path().join("tex/latex/.git/bar.sty"));
+        write(home.path().join("tex/latex/.baz.sty"));
+
+        let mut deep = home.path().to_path_buf();
+        for i in 0..MAX_DIRECTORY_DEPTH {
+            deep.push(i.to_string());
+        }
+        write(deep.join("qux.sty"));
+        write(deep.join("too-deep/quux.sty"));
+
+        let mut resolver = Resolver::default();
+        add_directory(&mut resolver, home.path());
+
+        let mut names: Vec<_> = resolver.files_by_name.keys().map(SmolStr::as_str).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["foo.sty", "qux.sty"]);
+        assert_eq!(
+            resolver.files_by_name["foo.sty"],
+            home.path().join("tex/latex/foo/foo.sty")
+        );
+    }
+}
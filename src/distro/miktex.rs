@@ -7,12 +7,16 @@ use std::{
 
 use anyhow::{Context, Result};
 use byteorder::{LittleEndian, ReadBytesExt};
+use log::warn;
 
 use super::kpsewhich::{self, Resolver};
 
 pub fn load_resolver() -> Result<Resolver> {
     let root_directories = kpsewhich::root_directories()?;
-    let resolver = kpsewhich::parse_database(&root_directories, read_database)?;
+    let mut resolver = kpsewhich::parse_database(&root_directories, read_database)?;
+    if let Err(why) = kpsewhich::add_home_directory(&mut resolver) {
+        warn!("Failed to read TEXMFHOME: {}", why);
+    }
     Ok(resolver)
 }
 
@@ -0,0 +1,137 @@
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// The options of the `inputenc` package and the labels of the corresponding encodings.
+const INPUT_ENCODINGS: &[(&str, &str)] = &[
+    ("utf8", "utf-8"),
+    ("utf8x", "utf-8"),
+    ("ascii", "windows-1252"),
+    ("latin1", "iso-8859-1"),
+    ("latin2", "iso-8859-2"),
+    ("latin3", "iso-8859-3"),
+    ("latin4", "iso-8859-4"),
+    ("latin5", "iso-8859-9"),
+    ("latin9", "iso-8859-15"),
+    ("latin10", "iso-8859-16"),
+    ("ansinew", "windows-1252"),
+    ("cp1250", "windows-1250"),
+    ("cp1251", "windows-1251"),
+    ("cp1252", "windows-1252"),
+    ("cp1257", "windows-1257"),
+    ("koi8-r", "koi8-r"),
+    ("applemac", "macintosh"),
+];
+
+static INPUTENC_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^[^%\n]*?\\usepackage\s*\[([^\]]*)\]\s*\{inputenc\}").unwrap());
+
+/// Returns the encoding that is declared with `\usepackage[...]{inputenc}` in the given text.
+pub fn find_input_encoding(text: &str) -> Option<&'static Encoding> {
+    let captures = INPUTENC_REGEX.captures_iter(text).last()?;
+
+    // Like `inputenc`, the last known option wins.
+    captures[1]
+        .split(',')
+        .map(str::trim)
+        .filter_map(|option| {
+            INPUT_ENCODINGS
+                .iter()
+                .find(|(name, _)| *name == option)
+                .and_then(|(_, label)| Encoding::for_label(label.as_bytes()))
+        })
+        .next_back()
+}
+
+/// Decodes the contents of a file on disk.
+/// Files that declare a legacy encoding with `inputenc` are decoded with that encoding.
+/// Otherwise, the file is read as UTF-8 and falls back to Windows-1252 if it is not valid UTF-8.
+pub fn decode_text(data: &[u8]) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_length)) = Encoding::for_bom(data) {
+        let (text, _) = encoding.decode_without_bom_handling(&data[bom_length..]);
+        return (text.into_owned(), encoding);
+    }
+
+    let declared = find_input_encoding(&String::from_utf8_lossy(data));
+    let encoding = match (declared, std::str::from_utf8(data)) {
+        (Some(encoding), _) => encoding,
+        (None, Ok(_)) => UTF_8,
+        (None, Err(_)) => WINDOWS_1252,
+    };
+
+    let (text, _) = encoding.decode_without_bom_handling(data);
+    if encoding != UTF_8 {
+        log::info!("Decoding file with the {} encoding", encoding.name());
+    }
+
+    (text.into_owned(), encoding)
+}
+
+/// Encodes the text with the encoding of the document it was taken from
+/// so that the file can be read by the TeX engine.
+pub fn encode_text(text: &str, encoding: &'static Encoding) -> Vec<u8> {
+    let (data, _, _) = encoding.output_encoding().encode(text);
+    data.into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::Arc};
+
+    use crate::{
+        create_workspace_fast, DocumentLanguage, ServerContext, Uri, Workspace, WorkspaceSource,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_latin1() {
+        let mut data = b"\\usepackage[T1]{fontenc}\n\\usepackage[latin1]{inputenc}\n".to_vec();
+        data.extend_from_slice(&[0x47, 0x72, 0xFC, 0xDF, 0x65]);
+
+        let (text, encoding) = decode_text(&data);
+        assert_eq!(encoding, WINDOWS_1252);
+        assert!(text.ends_with("Grüße"));
+        assert_eq!(encode_text(&text, encoding), data);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let (text, encoding) = decode_text(&[0x47, 0x72, 0xFC, 0xDF, 0x65]);
+        assert_eq!(encoding, WINDOWS_1252);
+        assert_eq!(text, "Grüße");
+    }
+
+    #[test]
+    fn test_utf8() {
+        let (text, encoding) = decode_text("% \\usepackage[latin1]{inputenc}\nGrüße".as_bytes());
+        assert_eq!(encoding, UTF_8);
+        assert!(text.ends_with("Grüße"));
+    }
+
+    #[test]
+    fn test_document_encoding() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("main.tex");
+        fs::write(&path, &[0x47, 0x72, 0xFC, 0xDF, 0x65]).unwrap();
+
+        let context = Arc::new(ServerContext::new(temp_dir.path().to_path_buf()));
+        let workspace = create_workspace_fast(context).unwrap();
+        let document = workspace.load(path.clone()).unwrap().unwrap();
+        assert_eq!(document.encoding, WINDOWS_1252);
+
+        // The editor sends the decoded text, so the encoding of the file is kept.
+        let uri = Arc::new(Uri::from_file_path(path).unwrap());
+        let document = workspace.open(
+            uri,
+            "Grüße!".to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+        assert_eq!(document.encoding, WINDOWS_1252);
+        assert_eq!(
+            encode_text(&document.text, document.encoding),
+            b"Gr\xFC\xDFe!"
+        );
+    }
+}
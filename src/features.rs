@@ -1,22 +1,58 @@
+mod arxiv_check;
+mod bibliography_export;
 mod build;
+mod build_log;
+mod citation_analysis;
+mod code_action;
+mod color;
 #[cfg(feature = "completion")]
 mod completion;
 mod cursor;
 mod definition;
+mod detex;
+mod embedded;
+mod figure;
+mod file_create;
+mod file_rename;
+mod flatten;
 mod folding;
 mod formatting;
 mod forward_search;
+mod generated;
 mod highlight;
 mod hover;
+mod inlay_hint;
+mod language_regions;
 mod link;
 mod lsp_kinds;
+mod matrix;
+mod new_document;
+mod normalize_labels;
+mod outline_export;
+mod output_status;
+mod paste;
+mod pdf_location;
+mod preamble;
+mod preview_math;
 mod reference;
 mod rename;
 #[cfg(feature = "semantic")]
 mod semantic;
+mod standalone;
+mod structure;
+mod structured_search;
 mod symbol;
-
-use std::sync::Arc;
+mod table;
+mod texdoc;
+mod todos;
+mod usage_report;
+mod viewer_setup;
+mod workspace_edit;
+
+use std::{
+    path::{Component, Path},
+    sync::Arc,
+};
 
 use crate::{Document, ServerContext, Workspace, WorkspaceSubset};
 
@@ -25,17 +61,90 @@ pub use self::completion::{complete, CompletionItemData, COMPLETION_LIMIT};
 #[cfg(feature = "semantic")]
 pub use self::semantic::{find_semantic_tokens_range, legend};
 pub use self::{
-    build::{BuildEngine, BuildParams, BuildResult, BuildStatus},
+    arxiv_check::{check_arxiv, CheckArxivParams, CHECK_ARXIV_COMMAND},
+    bibliography_export::{
+        export_bibliography, ExportBibliographyParams, EXPORT_BIBLIOGRAPHY_COMMAND,
+    },
+    build::{
+        enable_shell_escape, find_disabled_shell_escape, BuildEngine, BuildParams, BuildResult,
+        BuildStatus, EnableShellEscapeParams, ENABLE_SHELL_ESCAPE_COMMAND,
+    },
+    build_log::{
+        build_log_file_uri, build_log_uri, find_build_log_links, open_build_log, BuildLogDocument,
+        BuildLogParams, BUILD_LOG_SCHEME,
+    },
+    citation_analysis::{
+        analyze_citations, CitationAnalysis, CitationAnalysisParams, CitationUsage,
+        CoCitationCluster,
+    },
+    code_action::find_code_actions,
+    color::{find_color_presentations, find_document_colors},
     definition::goto_definition,
-    folding::find_foldings,
+    detex::{detex, DetexMapping, DetexParams, DetexResult},
+    embedded::{
+        extract_embedded_documents, find_embedded_documents, DidChangeEmbeddedDocumentsParams,
+        EmbeddedDocument, EmbeddedDocumentsParams,
+    },
+    figure::{create_figure_snippet, FigureSnippet, InsertFigureParams, INSERT_FIGURE_COMMAND},
+    file_create::{create_files, template_directory},
+    file_rename::rename_files,
+    flatten::{
+        flatten_project, FlattenProjectParams, FlattenProjectResult, FLATTEN_PROJECT_COMMAND,
+    },
+    folding::{
+        find_foldings, fold_all_comments, fold_all_environments, FoldAllParams,
+        FOLD_ALL_COMMENTS_COMMAND, FOLD_ALL_ENVIRONMENTS_COMMAND,
+    },
     formatting::format_source_code,
     forward_search::{execute_forward_search, ForwardSearchResult, ForwardSearchStatus},
+    generated::{find_generated_links, find_generated_references, GeneratedReference},
     highlight::find_document_highlights,
     hover::find_hover,
+    inlay_hint::{find_inlay_hints, InlayHint, InlayHintParams},
+    language_regions::{
+        find_language_regions, LanguageRegionLocation, LanguageRegions, LanguageRegionsParams,
+    },
     link::find_document_links,
+    matrix::{create_matrix_snippet, InsertMatrixParams, MatrixSnippet, INSERT_MATRIX_COMMAND},
+    new_document::{create_new_document, NewDocumentParams, NEW_DOCUMENT_COMMAND},
+    normalize_labels::{
+        normalize_labels, LabelConflict, LabelRename, NormalizeLabelsParams, NormalizeLabelsResult,
+        NORMALIZE_LABELS_COMMAND,
+    },
+    outline_export::{
+        export_outline, ExportOutlineParams, OutlineEntry, OutlineFormat, EXPORT_OUTLINE_COMMAND,
+    },
+    output_status::{find_output_status, OutputState, OutputStatus, OutputStatusParams},
+    paste::{convert_paste, ConvertPasteParams, CONVERT_PASTE_COMMAND},
+    pdf_location::{locate_in_pdf, LocateInPdfParams, PdfLocation},
+    preamble::{
+        extract_shared_preamble, find_shared_preambles, ExtractPreambleParams, SharedPreamble,
+        SharedPreamblesParams, EXTRACT_PREAMBLE_COMMAND,
+    },
+    preview_math::{MathPreviewer, PreviewMathParams},
     reference::find_all_references,
     rename::{prepare_rename_all, rename_all},
-    symbol::{find_document_symbols, find_workspace_symbols},
+    standalone::{
+        preview_environment, PreviewEnvironmentParams, PreviewEnvironmentResult,
+        PREVIEW_ENVIRONMENT_COMMAND,
+    },
+    structure::{
+        extract_environment, find_environments, find_matching_pair, find_next_section,
+        find_sections, EnvironmentContent, EnvironmentLocation, NextSectionParams, SectionLocation,
+        SectionsParams,
+    },
+    structured_search::{
+        structured_search, SearchMatch, SearchScope, StructuredSearchParams, SEARCH_LIMIT,
+    },
+    symbol::{find_document_symbols, find_workspace_symbols, SYMBOL_LIMIT},
+    table::{convert_table, ConvertTableParams, CONVERT_TABLE_COMMAND},
+    texdoc::{show_documentation, DocumentationFile, SHOW_DOCUMENTATION_COMMAND},
+    todos::{find_project_todos, TodoFile, TodoItem, TodosParams},
+    usage_report::{
+        create_usage_report, UsageEntry, UsageReport, UsageReportItem, UsageReportParams,
+        USAGE_REPORT_COMMAND,
+    },
+    viewer_setup::{find_viewer_setups, ViewerKind, ViewerSetup, ViewerSetupParams},
 };
 
 #[derive(Clone)]
@@ -50,6 +159,31 @@ impl<P> FeatureRequest<P> {
     pub fn main_document(&self) -> &Document {
         &self.subset.documents[0]
     }
+
+    /// Returns the document of the subset that contains the `document` environment,
+    /// falling back to the main document.
+    pub fn root_document(&self) -> &Document {
+        self.subset
+            .documents
+            .iter()
+            .find(|document| {
+                document
+                    .data
+                    .as_latex()
+                    .map_or(false, |data| data.extras.has_document_environment)
+            })
+            .map(|document| document.as_ref())
+            .unwrap_or_else(|| self.main_document())
+    }
+}
+
+/// Returns `true` if the path is relative and stays inside the directory it is joined to.
+/// Used to validate file names that the client passes to commands which create files.
+pub(crate) fn is_nested_path(path: &Path) -> bool {
+    path.components().next().is_some()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
 }
 
 #[cfg(test)]
@@ -57,9 +191,10 @@ mod testing {
     use std::{path::PathBuf, sync::Arc};
 
     use lsp_types::{
-        ClientCapabilities, ClientInfo, CompletionParams, DocumentFormattingParams,
+        ClientCapabilities, ClientInfo, CodeActionContext, CodeActionParams, Color,
+        ColorPresentationParams, CompletionParams, DocumentColorParams, DocumentFormattingParams,
         DocumentHighlightParams, DocumentLinkParams, FoldingRangeParams, FormattingOptions,
-        GotoDefinitionParams, HoverParams, PartialResultParams, Position, ReferenceContext,
+        GotoDefinitionParams, HoverParams, PartialResultParams, Position, Range, ReferenceContext,
         ReferenceParams, RenameParams, TextDocumentIdentifier, TextDocumentPositionParams,
         WorkDoneProgressParams,
     };
@@ -268,5 +403,237 @@ mod testing {
             };
             self.request(params)
         }
+
+        pub fn position(self) -> FeatureRequest<TextDocumentPositionParams> {
+            let params = TextDocumentPositionParams::new(
+                self.identifier(),
+                Position::new(self.line, self.character),
+            );
+            self.request(params)
+        }
+
+        pub fn embedded_documents(self) -> FeatureRequest<EmbeddedDocumentsParams> {
+            let params = EmbeddedDocumentsParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn convert_paste(self, range: Range, text: &str) -> FeatureRequest<ConvertPasteParams> {
+            let params = ConvertPasteParams {
+                text_document: self.identifier(),
+                range,
+                text: text.to_string(),
+            };
+            self.request(params)
+        }
+
+        pub fn convert_table(self, range: Range) -> FeatureRequest<ConvertTableParams> {
+            let params = ConvertTableParams {
+                text_document: self.identifier(),
+                range,
+                file: None,
+                booktabs: false,
+                siunitx: false,
+            };
+            self.request(params)
+        }
+
+        pub fn normalize_labels(self) -> FeatureRequest<NormalizeLabelsParams> {
+            let params = NormalizeLabelsParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn shared_preambles(self) -> FeatureRequest<SharedPreamblesParams> {
+            let params = SharedPreamblesParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn extract_preamble(
+            self,
+            package_name: Option<&str>,
+        ) -> FeatureRequest<ExtractPreambleParams> {
+            let params = ExtractPreambleParams {
+                text_document: self.identifier(),
+                package_name: package_name.map(ToString::to_string),
+            };
+            self.request(params)
+        }
+
+        pub fn export_bibliography(
+            self,
+            file_name: Option<&str>,
+        ) -> FeatureRequest<ExportBibliographyParams> {
+            let params = ExportBibliographyParams {
+                text_document: self.identifier(),
+                file_name: file_name.map(ToString::to_string),
+            };
+            self.request(params)
+        }
+
+        pub fn export_outline(
+            self,
+            file_name: Option<&str>,
+        ) -> FeatureRequest<ExportOutlineParams> {
+            let params = ExportOutlineParams {
+                text_document: self.identifier(),
+                format: OutlineFormat::Markdown,
+                file_name: file_name.map(ToString::to_string),
+            };
+            self.request(params)
+        }
+
+        pub fn check_arxiv(self) -> FeatureRequest<CheckArxivParams> {
+            let params = CheckArxivParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn flatten_project(self) -> FeatureRequest<FlattenProjectParams> {
+            let params = FlattenProjectParams {
+                text_document: self.identifier(),
+                output_directory: None,
+            };
+            self.request(params)
+        }
+
+        pub fn code_action(self) -> FeatureRequest<CodeActionParams> {
+            let position = Position::new(self.line, self.character);
+            let params = CodeActionParams {
+                text_document: self.identifier(),
+                range: Range::new(position, position),
+                context: CodeActionContext::default(),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            self.request(params)
+        }
+
+        pub fn insert_matrix(
+            self,
+            params: InsertMatrixParams,
+        ) -> FeatureRequest<InsertMatrixParams> {
+            self.request(params)
+        }
+
+        pub fn insert_figure(
+            self,
+            params: InsertFigureParams,
+        ) -> FeatureRequest<InsertFigureParams> {
+            self.request(params)
+        }
+
+        pub fn citation_analysis(self) -> FeatureRequest<CitationAnalysisParams> {
+            let params = CitationAnalysisParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn build_log(self) -> FeatureRequest<BuildLogParams> {
+            let params = BuildLogParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn locate_in_pdf(self) -> FeatureRequest<LocateInPdfParams> {
+            let params = LocateInPdfParams {
+                text_document: self.identifier(),
+                positions: vec![Position::new(self.line, self.character)],
+            };
+            self.request(params)
+        }
+
+        pub fn output_status(self) -> FeatureRequest<OutputStatusParams> {
+            let params = OutputStatusParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn todos(self) -> FeatureRequest<TodosParams> {
+            let params = TodosParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn usage_report(self) -> FeatureRequest<UsageReportParams> {
+            let params = UsageReportParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn document_color(self) -> FeatureRequest<DocumentColorParams> {
+            let params = DocumentColorParams {
+                text_document: self.identifier(),
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            self.request(params)
+        }
+
+        pub fn color_presentation(
+            self,
+            range: Range,
+            color: Color,
+        ) -> FeatureRequest<ColorPresentationParams> {
+            let params = ColorPresentationParams {
+                text_document: self.identifier(),
+                color,
+                range,
+                work_done_progress_params: WorkDoneProgressParams::default(),
+                partial_result_params: PartialResultParams::default(),
+            };
+            self.request(params)
+        }
+
+        pub fn language_regions(self) -> FeatureRequest<LanguageRegionsParams> {
+            let params = LanguageRegionsParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn inlay_hints(self) -> FeatureRequest<InlayHintParams> {
+            let params = InlayHintParams {
+                text_document: self.identifier(),
+                range: None,
+            };
+            self.request(params)
+        }
+
+        pub fn detex(self, range: Option<Range>) -> FeatureRequest<DetexParams> {
+            let params = DetexParams {
+                text_document: self.identifier(),
+                range,
+            };
+            self.request(params)
+        }
+
+        pub fn sections(self) -> FeatureRequest<SectionsParams> {
+            let params = SectionsParams {
+                text_document: self.identifier(),
+            };
+            self.request(params)
+        }
+
+        pub fn next_section(self, backward: bool) -> FeatureRequest<NextSectionParams> {
+            let params = NextSectionParams {
+                text_document_position: TextDocumentPositionParams::new(
+                    self.identifier(),
+                    Position::new(self.line, self.character),
+                ),
+                backward,
+            };
+            self.request(params)
+        }
     }
 }
@@ -0,0 +1,51 @@
+use cancellation::CancellationToken;
+use lsp_types::TextDocumentIdentifier;
+use serde::{Deserialize, Serialize};
+
+use crate::arxiv::{find_arxiv_issues, ArxivIssue};
+
+use super::FeatureRequest;
+
+pub const CHECK_ARXIV_COMMAND: &str = "texlab.checkArxiv";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckArxivParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+pub fn check_arxiv(
+    request: FeatureRequest<CheckArxivParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<ArxivIssue>> {
+    cancellation_token.result().ok()?;
+    let root_document = request.root_document();
+
+    Some(find_arxiv_issues(root_document, &request.subset.documents))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_included_document() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    "\\pdfoutput=1\n\\documentclass{article}\n\\begin{document}\n\\input{chapter}\n\\end{document}",
+                ),
+                ("chapter.tex", "\\includegraphics{plot.eps}"),
+            ])
+            .main("chapter.tex")
+            .build()
+            .check_arxiv();
+
+        let issues = check_arxiv(request, CancellationToken::none()).unwrap();
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].location.uri.as_str().ends_with("chapter.tex"));
+    }
+}
@@ -0,0 +1,280 @@
+use cancellation::CancellationToken;
+use lsp_types::{
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextDocumentIdentifier, TextEdit, Url, WorkspaceEdit,
+};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{bibtex, latex, CstNode},
+    DocumentData,
+};
+
+use super::FeatureRequest;
+
+pub const EXPORT_BIBLIOGRAPHY_COMMAND: &str = "texlab.exportBibliography";
+
+/// Fields whose values refer to the keys of other entries.
+const CROSSREF_FIELDS: &[&str] = &["crossref", "xref"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportBibliographyParams {
+    pub text_document: TextDocumentIdentifier,
+
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+/// Creates a `.bib` file next to the given document that contains only the entries
+/// that are cited in the project (including the entries they cross-reference)
+/// and the `@string` definitions used by them.
+pub fn export_bibliography(
+    request: FeatureRequest<ExportBibliographyParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let main_document = request.main_document();
+    let file_name = match request
+        .params
+        .file_name
+        .as_deref()
+        .map(|name| name.trim_end_matches(".bib"))
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) => name.to_string(),
+        None => {
+            let document_name = main_document.uri.path_segments()?.last()?;
+            let stem = document_name
+                .rsplit_once('.')
+                .map_or(document_name, |(stem, _)| stem);
+            format!("{}-export", stem)
+        }
+    };
+
+    let (cites_all, mut keys) = find_cited_keys(&request);
+    cancellation_token.result().ok()?;
+
+    let entries: Vec<_> = request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_bibtex())
+        .flat_map(|data| data.root.children().filter_map(bibtex::Entry::cast))
+        .filter_map(|entry| Some((entry.key()?.to_string().to_lowercase(), entry)))
+        .collect();
+
+    let mut queue: Vec<_> = entries
+        .iter()
+        .filter(|(key, _)| cites_all || keys.contains(key))
+        .map(|(_, entry)| *entry)
+        .collect();
+
+    let mut targets = FxHashSet::default();
+    while let Some(entry) = queue.pop() {
+        for target in crossref_keys(entry) {
+            if targets.insert(target.clone()) && !cites_all && keys.insert(target.clone()) {
+                queue.extend(
+                    entries
+                        .iter()
+                        .filter(|(key, _)| *key == target)
+                        .map(|(_, entry)| *entry),
+                );
+            }
+        }
+    }
+
+    // BibTeX requires cross-referenced entries to appear after the entries referring to them.
+    let (target_entries, mut exported): (Vec<_>, Vec<_>) = entries
+        .into_iter()
+        .filter(|(key, _)| cites_all || keys.contains(key))
+        .partition(|(key, _)| targets.contains(key));
+    exported.extend(target_entries);
+    let exported: Vec<_> = exported.into_iter().map(|(_, entry)| entry).collect();
+
+    let macros: FxHashSet<_> = exported
+        .iter()
+        .flat_map(|entry| entry.syntax().descendants())
+        .filter_map(bibtex::Token::cast)
+        .filter_map(|token| token.syntax().first_token())
+        .filter(|token| token.kind() == bibtex::WORD)
+        .map(|token| token.text().to_lowercase())
+        .collect();
+
+    let mut text = String::new();
+    for document in &request.subset.documents {
+        if let Some(data) = document.data.as_bibtex() {
+            for node in data.root.children() {
+                let is_used = if let Some(string) = bibtex::String::cast(node) {
+                    string
+                        .name()
+                        .map_or(false, |name| macros.contains(&name.text().to_lowercase()))
+                } else {
+                    bibtex::Preamble::cast(node).is_some()
+                };
+
+                if is_used {
+                    text.push_str(node.text().to_string().trim());
+                    text.push_str("\n\n");
+                }
+            }
+        }
+    }
+
+    for entry in &exported {
+        text.push_str(entry.syntax().text().to_string().trim());
+        text.push_str("\n\n");
+    }
+
+    let text = format!("{}\n", text.trim_end());
+    let uri: Url = main_document.uri.join(&format!("{}.bib", file_name)).ok()?;
+
+    let operations = vec![
+        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+            uri: uri.clone(),
+            options: Some(CreateFileOptions {
+                overwrite: Some(true),
+                ignore_if_exists: Some(false),
+            }),
+            annotation_id: None,
+        })),
+        DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: vec![OneOf::Left(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                text,
+            ))],
+        }),
+    ];
+
+    Some(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+/// Returns the lowercase keys that are cited in the project
+/// and whether all entries are cited with `\nocite{*}`.
+fn find_cited_keys(
+    request: &FeatureRequest<ExportBibliographyParams>,
+) -> (bool, FxHashSet<String>) {
+    let mut cites_all = false;
+    let mut keys = FxHashSet::default();
+    for document in &request.subset.documents {
+        match &document.data {
+            DocumentData::Latex(data) => {
+                for key in data
+                    .root
+                    .descendants()
+                    .filter_map(latex::Citation::cast)
+                    .filter_map(|citation| citation.key_list())
+                    .flat_map(|keys| keys.keys())
+                {
+                    let key = key.to_string();
+                    if key == "*" {
+                        cites_all = true;
+                    } else {
+                        keys.insert(key.to_lowercase());
+                    }
+                }
+            }
+            DocumentData::Markdown(data) => {
+                for citation in &data.parse.citations {
+                    keys.insert(citation.text.to_lowercase());
+                }
+            }
+            DocumentData::Bibtex(_) | DocumentData::BuildLog(_) => {}
+        }
+    }
+    (cites_all, keys)
+}
+
+fn crossref_keys(entry: bibtex::Entry) -> Vec<String> {
+    entry
+        .fields()
+        .filter(|field| {
+            field.name().map_or(false, |name| {
+                CROSSREF_FIELDS.contains(&name.text().to_lowercase().as_str())
+            })
+        })
+        .filter_map(|field| field.value())
+        .map(|value| {
+            value
+                .syntax()
+                .text()
+                .to_string()
+                .trim_matches(|c: char| c == '{' || c == '}' || c == '"' || c.is_whitespace())
+                .to_lowercase()
+        })
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn export(main: &str, bib: &str, file_name: Option<&str>) -> (Url, String) {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", main), ("main.bib", bib)])
+            .main("main.tex")
+            .build();
+
+        let edit = export_bibliography(
+            tester.export_bibliography(file_name),
+            CancellationToken::none(),
+        )
+        .unwrap();
+
+        let operations = match edit.document_changes {
+            Some(DocumentChanges::Operations(operations)) => operations,
+            _ => panic!("expected document change operations"),
+        };
+
+        match &operations[1] {
+            DocumentChangeOperation::Edit(edit) => match &edit.edits[0] {
+                OneOf::Left(text_edit) => {
+                    (edit.text_document.uri.clone(), text_edit.new_text.clone())
+                }
+                OneOf::Right(_) => panic!("expected a text edit"),
+            },
+            DocumentChangeOperation::Op(_) => panic!("expected a document edit"),
+        }
+    }
+
+    #[test]
+    fn test_cited_entries_and_crossrefs() {
+        let (uri, text) = export(
+            "\\cite{foo}\n\\bibliography{main}",
+            "@string{acm = {ACM}}\n@string{ieee = {IEEE}}\n\
+             @book{bar, publisher = acm}\n\
+             @inproceedings{foo, crossref = {bar}}\n\
+             @article{baz, publisher = ieee}",
+            None,
+        );
+
+        assert!(uri.as_str().ends_with("/main-export.bib"));
+        assert_eq!(
+            text,
+            "@string{acm = {ACM}}\n\n\
+             @inproceedings{foo, crossref = {bar}}\n\n\
+             @book{bar, publisher = acm}\n"
+        );
+    }
+
+    #[test]
+    fn test_nocite_all() {
+        let (uri, text) = export(
+            "\\nocite{*}\n\\bibliography{main}",
+            "@article{foo,}\n@article{bar,}",
+            Some("submission.bib"),
+        );
+
+        assert!(uri.as_str().ends_with("/submission.bib"));
+        assert_eq!(text, "@article{foo,}\n\n@article{bar,}\n");
+    }
+}
@@ -1,9 +1,16 @@
+mod miktex;
+mod shell_escape;
+
 use std::{
     io::{BufRead, BufReader, Read},
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use anyhow::Result;
@@ -11,17 +18,19 @@ use cancellation::CancellationToken;
 use chashmap::CHashMap;
 use crossbeam_channel::Sender;
 use encoding_rs_io::DecodeReaderBytesBuilder;
+use log::{info, warn};
 use lsp_types::{
-    notification::{LogMessage, Progress},
-    LogMessageParams, NumberOrString, Position, ProgressParams, ProgressParamsValue,
-    TextDocumentIdentifier, TextDocumentPositionParams, WorkDoneProgress, WorkDoneProgressBegin,
-    WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+    notification::LogMessage, LogMessageParams, Position, TextDocumentIdentifier,
+    TextDocumentPositionParams,
 };
+use rustc_hash::FxHashSet;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
-use uuid::Uuid;
 
-use crate::{client, req_queue::ReqQueue, ClientCapabilitiesExt, DocumentLanguage, Uri};
+use crate::{
+    client, distro::DistributionKind, progress::ProgressReporter, req_queue::ReqQueue,
+    DocumentLanguage, Options, Placeholders, Uri,
+};
 
 use super::{forward_search, FeatureRequest};
 
@@ -46,87 +55,33 @@ pub struct BuildResult {
     pub status: BuildStatus,
 }
 
-struct ProgressReporter<'a> {
-    supports_progress: bool,
-    req_queue: &'a Mutex<ReqQueue>,
-    lsp_sender: Sender<lsp_server::Message>,
-    token: &'a str,
-}
-
-impl<'a> ProgressReporter<'a> {
-    pub fn start(&self, uri: &Uri) -> Result<()> {
-        if self.supports_progress {
-            client::send_request::<lsp_types::request::WorkDoneProgressCreate>(
-                self.req_queue,
-                &self.lsp_sender,
-                WorkDoneProgressCreateParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                },
-            )?;
-            client::send_notification::<Progress>(
-                &self.lsp_sender,
-                ProgressParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
-                        WorkDoneProgressBegin {
-                            title: "Building".to_string(),
-                            message: Some(uri.as_str().to_string()),
-                            cancellable: Some(false),
-                            percentage: None,
-                        },
-                    )),
-                },
-            )?;
-        };
-        Ok(())
-    }
-}
+pub const ENABLE_SHELL_ESCAPE_COMMAND: &str = "texlab.enableShellEscape";
 
-impl<'a> Drop for ProgressReporter<'a> {
-    fn drop(&mut self) {
-        if self.supports_progress {
-            let _ = client::send_notification::<Progress>(
-                &self.lsp_sender,
-                ProgressParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(
-                        WorkDoneProgressEnd { message: None },
-                    )),
-                },
-            );
-        }
-    }
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnableShellEscapeParams {
+    pub text_document: TextDocumentIdentifier,
 }
 
 #[derive(Default)]
 pub struct BuildEngine {
     lock: Mutex<()>,
     pub positions_by_uri: CHashMap<Arc<Uri>, Position>,
+    forward_search_generation: Arc<AtomicUsize>,
+    shell_escape_warnings: Mutex<FxHashSet<PathBuf>>,
 }
 
 impl BuildEngine {
     pub fn build(
-        &self,
+        self: &Arc<Self>,
         request: FeatureRequest<BuildParams>,
-        cancellation_token: &CancellationToken,
-        req_queue: &Mutex<ReqQueue>,
+        _cancellation_token: &CancellationToken,
+        req_queue: &Arc<Mutex<ReqQueue>>,
         lsp_sender: &Sender<lsp_server::Message>,
     ) -> Result<BuildResult> {
         let lock = self.lock.lock().unwrap();
 
-        let document = request
-            .subset
-            .documents
-            .iter()
-            .find(|document| {
-                if let Some(data) = document.data.as_latex() {
-                    data.extras.has_document_environment
-                } else {
-                    false
-                }
-            })
-            .map(|document| document.as_ref())
-            .unwrap_or_else(|| request.main_document());
+        let document = request.root_document();
 
         if document.language() != DocumentLanguage::Latex {
             return Ok(BuildResult {
@@ -141,51 +96,28 @@ impl BuildEngine {
         }
         let path = document.uri.to_file_path().unwrap();
 
-        let supports_progress = {
-            request
-                .context
-                .client_capabilities
-                .lock()
-                .unwrap()
-                .has_work_done_progress_support()
-        };
-
-        let token = format!("texlab-build-{}", Uuid::new_v4());
-        let progress_reporter = ProgressReporter {
-            supports_progress,
+        let progress_reporter = ProgressReporter::begin(
+            &request.context,
             req_queue,
-            lsp_sender: lsp_sender.clone(),
-            token: &token,
-        };
-        progress_reporter.start(&document.uri)?;
+            lsp_sender,
+            "Building",
+            Some(document.uri.as_str().to_string()),
+            false,
+        );
 
         let options = { request.context.options.read().unwrap().clone() };
+        self.check_shell_escape(&request, &options, &path, lsp_sender);
+
+        let (success, output) = run_build_tool(&options, &path, lsp_sender)?;
+        let missing_files = if !success
+            && options.build.install_missing_packages
+            && *request.context.distro_kind.lock().unwrap() == DistributionKind::Miktex
+        {
+            miktex::find_missing_files(&output)
+        } else {
+            Vec::new()
+        };
 
-        let build_dir = options
-            .root_directory
-            .as_ref()
-            .map(AsRef::as_ref)
-            .or_else(|| path.parent())
-            .unwrap();
-
-        let args: Vec<_> = options
-            .build
-            .args()
-            .into_iter()
-            .map(|arg| replace_placeholder(arg, &path))
-            .collect();
-
-        let mut process = Command::new(options.build.executable())
-            .args(args)
-            .stdin(Stdio::null())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .current_dir(build_dir)
-            .spawn()?;
-
-        let log_handle = capture_output(&mut process, lsp_sender);
-        let success = process.wait().map(|status| status.success())?;
-        log_handle.join().unwrap();
         let status = if success {
             BuildStatus::SUCCESS
         } else {
@@ -195,62 +127,290 @@ impl BuildEngine {
         drop(progress_reporter);
         drop(lock);
 
-        if options.build.forward_search_after {
-            let request = FeatureRequest {
-                params: TextDocumentPositionParams {
-                    position: self
-                        .positions_by_uri
-                        .get(&request.main_document().uri)
-                        .map(|guard| guard.clone())
-                        .unwrap_or_default(),
-                    text_document: TextDocumentIdentifier::new(
-                        request.main_document().uri.as_ref().clone().into(),
-                    ),
-                },
-                context: request.context,
-                workspace: request.workspace,
-                subset: request.subset,
-            };
-            forward_search::execute_forward_search(request, cancellation_token);
+        if !missing_files.is_empty() {
+            self.offer_package_install(request, missing_files, req_queue, lsp_sender);
+        } else if status == BuildStatus::SUCCESS && options.build.forward_search_after {
+            self.schedule_forward_search(request, &options);
         }
 
         Ok(BuildResult { status })
     }
+
+    /// Offers to install the missing MiKTeX packages after a failed build
+    /// and builds the project again if the user agrees.
+    /// The offer is made on its own thread, so that a client that never answers does not block the build.
+    fn offer_package_install(
+        self: &Arc<Self>,
+        request: FeatureRequest<BuildParams>,
+        missing_files: Vec<String>,
+        req_queue: &Arc<Mutex<ReqQueue>>,
+        lsp_sender: &Sender<lsp_server::Message>,
+    ) {
+        let engine = Arc::clone(self);
+        let req_queue = Arc::clone(req_queue);
+        let lsp_sender = lsp_sender.clone();
+        thread::spawn(move || {
+            let mut packages = Vec::new();
+            for package in missing_files
+                .iter()
+                .filter_map(|file| miktex::find_package(file))
+            {
+                if !packages.contains(&package) {
+                    packages.push(package);
+                }
+            }
+
+            if packages.is_empty() {
+                return;
+            }
+
+            match miktex::confirm_install(&req_queue, &lsp_sender, &packages) {
+                Ok(true) if miktex::install_packages(&packages) => {
+                    if let Err(why) =
+                        engine.build(request, CancellationToken::none(), &req_queue, &lsp_sender)
+                    {
+                        warn!("Failed to build the project again: {}", why);
+                    }
+                }
+                Ok(_) => {}
+                Err(why) => warn!(
+                    "Failed to offer the installation of missing packages: {}",
+                    why
+                ),
+            }
+        });
+    }
+
+    /// Warns once per project if a package requires shell escape but the build does not enable it.
+    /// The build does not wait for the user to read the warning.
+    fn check_shell_escape(
+        &self,
+        request: &FeatureRequest<BuildParams>,
+        options: &Options,
+        path: &Path,
+        lsp_sender: &Sender<lsp_server::Message>,
+    ) {
+        let build_dir = build_directory(options, path);
+        let packages = shell_escape::find_shell_escape_packages(&request.subset);
+        if packages.is_empty()
+            || shell_escape::is_shell_escape_enabled(&options.build.args(), build_dir)
+            || !self
+                .shell_escape_warnings
+                .lock()
+                .unwrap()
+                .insert(build_dir.to_path_buf())
+        {
+            return;
+        }
+
+        if let Err(why) =
+            shell_escape::show_shell_escape_warning(lsp_sender, &packages, is_latexmk(options))
+        {
+            warn!("Failed to show the shell escape warning: {}", why);
+        }
+    }
+
+    /// Executes a forward search after the configured delay
+    /// unless another build finishes in the meantime or the previewer is not running.
+    fn schedule_forward_search(&self, request: FeatureRequest<BuildParams>, options: &Options) {
+        let generation = self
+            .forward_search_generation
+            .fetch_add(1, Ordering::SeqCst)
+            + 1;
+        let current_generation = Arc::clone(&self.forward_search_generation);
+        let delay = Duration::from_millis(options.build.forward_search_delay.unwrap_or(300));
+        let process_name = options
+            .forward_search
+            .as_ref()
+            .and_then(|options| options.process_name.clone());
+
+        let request = FeatureRequest {
+            params: TextDocumentPositionParams {
+                position: self
+                    .positions_by_uri
+                    .get(&request.main_document().uri)
+                    .map(|guard| guard.clone())
+                    .unwrap_or_default(),
+                text_document: TextDocumentIdentifier::new(
+                    request.main_document().uri.as_ref().clone().into(),
+                ),
+            },
+            context: request.context,
+            workspace: request.workspace,
+            subset: request.subset,
+        };
+
+        thread::spawn(move || {
+            thread::sleep(delay);
+            if current_generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+
+            if let Some(process_name) = process_name {
+                if !is_process_running(&process_name) {
+                    info!(
+                        "Skipping forward search because {} is not running",
+                        process_name
+                    );
+                    return;
+                }
+            }
+
+            forward_search::execute_forward_search(request, CancellationToken::none());
+        });
+    }
+}
+
+/// Returns the build directory and the packages that require shell escape
+/// if the project is built with `latexmk` but does not enable shell escape yet.
+pub fn find_disabled_shell_escape<P>(
+    request: &FeatureRequest<P>,
+) -> Option<(PathBuf, Vec<String>)> {
+    let document = request.root_document();
+    if document.uri.scheme() != "file" {
+        return None;
+    }
+
+    let path = document.uri.to_file_path().ok()?;
+    let options = { request.context.options.read().unwrap().clone() };
+    if !is_latexmk(&options) {
+        return None;
+    }
+
+    let build_dir = build_directory(&options, &path);
+    let packages = shell_escape::find_shell_escape_packages(&request.subset);
+    if packages.is_empty()
+        || shell_escape::is_shell_escape_enabled(&options.build.args(), build_dir)
+    {
+        return None;
+    }
+
+    Some((build_dir.to_path_buf(), packages))
+}
+
+/// Enables shell escape in the `.latexmkrc` file of the project.
+/// Returns `false` if the project does not need it or if the file cannot be written.
+pub fn enable_shell_escape(
+    request: FeatureRequest<EnableShellEscapeParams>,
+    _cancellation_token: &CancellationToken,
+) -> bool {
+    let (build_dir, _) = match find_disabled_shell_escape(&request) {
+        Some(result) => result,
+        None => return false,
+    };
+
+    match shell_escape::enable_shell_escape(&build_dir) {
+        Ok(()) => true,
+        Err(why) => {
+            warn!("Failed to enable shell escape: {}", why);
+            false
+        }
+    }
+}
+
+fn is_latexmk(options: &Options) -> bool {
+    Path::new(&options.build.executable())
+        .file_stem()
+        .map_or(false, |name| name == "latexmk")
+}
+
+/// Checks if a process with the given name is running.
+/// If the process list cannot be queried, the process is assumed to be running.
+fn is_process_running(name: &str) -> bool {
+    let is_running = if cfg!(windows) {
+        Command::new("tasklist")
+            .args(&["/NH", "/FI", &format!("IMAGENAME eq {}*", name)])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .to_lowercase()
+                    .contains(&name.to_lowercase())
+            })
+    } else {
+        Command::new("pgrep")
+            .args(&["-x", name])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+    };
+
+    is_running.unwrap_or(true)
+}
+
+fn build_directory<'a>(options: &'a Options, path: &'a Path) -> &'a Path {
+    options
+        .root_directory
+        .as_ref()
+        .map(AsRef::as_ref)
+        .or_else(|| path.parent())
+        .unwrap()
+}
+
+fn run_build_tool(
+    options: &Options,
+    path: &Path,
+    lsp_sender: &Sender<lsp_server::Message>,
+) -> Result<(bool, Vec<String>)> {
+    let build_dir = build_directory(options, path);
+    let placeholders = Placeholders {
+        tex_file: Some(path),
+        workspace_folder: Some(build_dir),
+        ..Placeholders::default()
+    };
+
+    let args: Vec<_> = options
+        .build
+        .args()
+        .iter()
+        .map(|arg| placeholders.expand(arg))
+        .collect();
+
+    let mut process = Command::new(placeholders.expand(&options.build.executable()))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .current_dir(build_dir)
+        .spawn()?;
+
+    let log_handle = capture_output(&mut process, lsp_sender);
+    let success = process.wait().map(|status| status.success())?;
+    let output = log_handle.join().unwrap();
+    Ok((success, output))
 }
 
 fn capture_output(
     process: &mut std::process::Child,
     lsp_sender: &Sender<lsp_server::Message>,
-) -> JoinHandle<()> {
+) -> JoinHandle<Vec<String>> {
     let (log_sender, log_receiver) = crossbeam_channel::unbounded();
     track_output(process.stdout.take().unwrap(), log_sender.clone());
     track_output(process.stderr.take().unwrap(), log_sender);
     let log_handle = {
         let lsp_sender = lsp_sender.clone();
         thread::spawn(move || {
+            let mut output = Vec::new();
             for message in &log_receiver {
                 client::send_notification::<LogMessage>(
                     &lsp_sender,
                     LogMessageParams {
-                        message,
+                        message: message.clone(),
                         typ: lsp_types::MessageType::Log,
                     },
                 )
                 .unwrap();
+                output.push(message);
             }
+            output
         })
     };
     log_handle
 }
 
-fn replace_placeholder(arg: String, file: &Path) -> String {
-    if arg.starts_with('"') || arg.ends_with('"') {
-        arg
-    } else {
-        arg.replace("%f", &file.to_string_lossy())
-    }
-}
-
 fn track_output(output: impl Read + Send + 'static, sender: Sender<String>) -> JoinHandle<()> {
     let reader = BufReader::new(
         DecodeReaderBytesBuilder::new()
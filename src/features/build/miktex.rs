@@ -0,0 +1,160 @@
+use std::{
+    process::{Command, Stdio},
+    sync::Mutex,
+};
+
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use log::{info, warn};
+use lsp_types::{
+    request::ShowMessageRequest, MessageActionItem, MessageType, ShowMessageRequestParams,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{client, req_queue::ReqQueue};
+
+const INSTALL_ACTION: &str = "Install and rebuild";
+
+/// Files whose MiKTeX package is not named after the file.
+const PACKAGES_BY_FILE: &[(&str, &str)] = &[
+    ("tikz.sty", "pgf"),
+    ("pgf.sty", "pgf"),
+    ("pgfplotstable.sty", "pgfplots"),
+    ("graphicx.sty", "graphics"),
+    ("color.sty", "graphics"),
+    ("keyval.sty", "graphics"),
+    ("amssymb.sty", "amsfonts"),
+    ("amsthm.sty", "amscls"),
+    ("array.sty", "tools"),
+    ("tabularx.sty", "tools"),
+    ("longtable.sty", "tools"),
+    ("multicol.sty", "tools"),
+    ("calc.sty", "tools"),
+    ("xspace.sty", "tools"),
+    ("bm.sty", "tools"),
+    ("afterpage.sty", "tools"),
+    ("varioref.sty", "tools"),
+    ("subcaption.sty", "caption"),
+    ("xparse.sty", "l3packages"),
+    ("expl3.sty", "l3kernel"),
+    ("scrartcl.cls", "koma-script"),
+    ("scrreprt.cls", "koma-script"),
+    ("scrbook.cls", "koma-script"),
+    ("scrlttr2.cls", "koma-script"),
+];
+
+static MISSING_FILE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        "(?:LaTeX|Package [a-zA-Z_\\-]+) Error: File `(?P<file>[^']+\\.(?:sty|cls))' not found",
+    )
+    .unwrap()
+});
+
+/// Returns the names of the packages and classes that the build could not find.
+pub fn find_missing_files(output: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    for line in output {
+        for captures in MISSING_FILE_REGEX.captures_iter(line) {
+            let file = captures["file"].to_string();
+            if !files.contains(&file) {
+                files.push(file);
+            }
+        }
+    }
+    files
+}
+
+/// Returns the MiKTeX package that contains the given file.
+/// The candidate is checked with `miktex packages info` since most packages are named after their main file
+/// but some are not (like `tikz.sty` in the `pgf` package).
+pub fn find_package(file: &str) -> Option<String> {
+    let package = PACKAGES_BY_FILE
+        .iter()
+        .find(|(name, _)| *name == file)
+        .map(|(_, package)| package.to_string())
+        .or_else(|| Some(file.rsplit_once('.')?.0.to_string()))?;
+
+    let status = Command::new("miktex")
+        .args(&["packages", "info", &package])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    match status {
+        Ok(status) if status.success() => Some(package),
+        Ok(_) => {
+            warn!("Failed to find the MiKTeX package of {}", file);
+            None
+        }
+        Err(why) => {
+            warn!("Failed to run miktex: {}", why);
+            None
+        }
+    }
+}
+
+/// Asks the user to install the packages. This blocks until the user answers,
+/// so it must not be called while the build holds the lock.
+pub fn confirm_install(
+    req_queue: &Mutex<ReqQueue>,
+    lsp_sender: &Sender<lsp_server::Message>,
+    packages: &[String],
+) -> Result<bool> {
+    let params = ShowMessageRequestParams {
+        typ: MessageType::Info,
+        message: format!(
+            "The build failed because of missing MiKTeX packages: {}",
+            packages.join(", ")
+        ),
+        actions: Some(vec![MessageActionItem {
+            title: INSTALL_ACTION.to_string(),
+        }]),
+    };
+
+    let action = client::send_request::<ShowMessageRequest>(req_queue, lsp_sender, params)?;
+    Ok(action.map_or(false, |action| action.title == INSTALL_ACTION))
+}
+
+pub fn install_packages(packages: &[String]) -> bool {
+    packages.iter().all(|package| {
+        info!("Installing MiKTeX package: {}", package);
+        let status = Command::new("miktex")
+            .args(&["packages", "install", package])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+
+        match status {
+            Ok(status) if status.success() => true,
+            Ok(_) => {
+                warn!("Failed to install MiKTeX package: {}", package);
+                false
+            }
+            Err(why) => {
+                warn!("Failed to run miktex: {}", why);
+                false
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_files() {
+        let output = vec![
+            "! LaTeX Error: File `tikz.sty' not found.".to_string(),
+            "Type X to quit or <RETURN> to proceed,".to_string(),
+            "! LaTeX Error: File `foo.cls' not found.".to_string(),
+            "! LaTeX Error: File `tikz.sty' not found.".to_string(),
+            "! LaTeX Error: File `image.png' not found.".to_string(),
+        ];
+
+        assert_eq!(find_missing_files(&output), vec!["tikz.sty", "foo.cls"]);
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use anyhow::Result;
+use crossbeam_channel::Sender;
+use lsp_types::{notification::ShowMessage, MessageType, ShowMessageParams};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{client, syntax::latex::ExplicitLinkKind, WorkspaceSubset};
+
+/// Packages that run external programs (like `pygmentize` or `gnuplot`) during the build.
+/// `epstopdf` only needs it if the distribution does not allow `repstopdf` in restricted mode.
+const SHELL_ESCAPE_PACKAGES: &[&str] = &["minted", "gnuplottex", "epstopdf", "auto-pst-pdf", "svg"];
+
+/// The configuration files of `latexmk` in the directory of the build.
+const LATEXMK_CONFIG_FILES: &[&str] = &[".latexmkrc", "latexmkrc"];
+
+static SHELL_ESCAPE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?:^|[\s='"])--?(?:shell-escape|enable-write18)\b"#).unwrap());
+
+pub fn find_shell_escape_packages(subset: &WorkspaceSubset) -> Vec<String> {
+    let mut packages = Vec::new();
+    for document in &subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            for link in &data.extras.explicit_links {
+                if link.kind == ExplicitLinkKind::Package
+                    && SHELL_ESCAPE_PACKAGES.contains(&link.stem.as_str())
+                    && !packages.iter().any(|package| package == &link.stem)
+                {
+                    packages.push(link.stem.to_string());
+                }
+            }
+        }
+    }
+    packages
+}
+
+/// Checks if the build arguments or the `latexmk` configuration of the project enable shell escape.
+/// Comment lines of the configuration are ignored.
+pub fn is_shell_escape_enabled(args: &[String], build_dir: &Path) -> bool {
+    args.iter().any(|arg| SHELL_ESCAPE_REGEX.is_match(arg))
+        || LATEXMK_CONFIG_FILES.iter().any(|name| {
+            fs::read_to_string(build_dir.join(name)).map_or(false, |text| {
+                text.lines()
+                    .filter(|line| !line.trim_start().starts_with('#'))
+                    .any(|line| SHELL_ESCAPE_REGEX.is_match(line))
+            })
+        })
+}
+
+/// Warns the user that the build will probably fail without waiting for an answer.
+/// If the build uses `latexmk`, the warning points to the code action that enables shell escape
+/// in the `.latexmkrc` file of the project.
+pub fn show_shell_escape_warning(
+    lsp_sender: &Sender<lsp_server::Message>,
+    packages: &[String],
+    is_latexmk: bool,
+) -> Result<()> {
+    let mut message = format!(
+        "The following packages require shell escape but the build arguments do not contain -shell-escape: {}",
+        packages.join(", ")
+    );
+
+    if is_latexmk {
+        message.push_str(
+            ". Use the code action on the package or the texlab.enableShellEscape command to enable it in the .latexmkrc file of the project.",
+        );
+    } else {
+        message.push_str(". Add -shell-escape to texlab.build.args to fix the build.");
+    }
+
+    let params = ShowMessageParams {
+        typ: MessageType::Warning,
+        message,
+    };
+
+    client::send_notification::<ShowMessage>(lsp_sender, params)
+}
+
+/// Appends `-shell-escape` to the options of all engines in the `.latexmkrc` file of the project.
+/// `set_tex_cmds` keeps the engines that are selected by the configuration or the build arguments.
+pub fn enable_shell_escape(build_dir: &Path) -> io::Result<()> {
+    let path = LATEXMK_CONFIG_FILES
+        .iter()
+        .map(|name| build_dir.join(name))
+        .find(|path| path.exists())
+        .unwrap_or_else(|| build_dir.join(LATEXMK_CONFIG_FILES[0]));
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file)?;
+    writeln!(file, "# The packages of this project require shell escape.")?;
+    writeln!(file, "set_tex_cmds('-shell-escape %O %S');")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_escape_args() {
+        let build_dir = Path::new("/this/directory/does/not/exist");
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert!(is_shell_escape_enabled(
+            &args(&["-pdf", "-shell-escape", "%f"]),
+            build_dir
+        ));
+        assert!(is_shell_escape_enabled(
+            &args(&["-pdflatex=pdflatex --shell-escape %O %S", "%f"]),
+            build_dir
+        ));
+        assert!(!is_shell_escape_enabled(
+            &args(&["-pdf", "-no-shell-escape", "%f"]),
+            build_dir
+        ));
+    }
+
+    #[test]
+    fn test_shell_escape_config() {
+        let build_dir = tempfile::tempdir().unwrap();
+        fs::write(
+            build_dir.path().join(".latexmkrc"),
+            "# $pdflatex = 'pdflatex -shell-escape %O %S';\n$pdf_mode = 1;\n",
+        )
+        .unwrap();
+        assert!(!is_shell_escape_enabled(&[], build_dir.path()));
+
+        enable_shell_escape(build_dir.path()).unwrap();
+        let text = fs::read_to_string(build_dir.path().join(".latexmkrc")).unwrap();
+        assert!(text.starts_with("# $pdflatex"));
+        assert!(text.ends_with("set_tex_cmds('-shell-escape %O %S');\n"));
+        assert!(is_shell_escape_enabled(&[], build_dir.path()));
+    }
+}
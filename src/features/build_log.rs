@@ -0,0 +1,221 @@
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{DocumentLink, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{syntax::build_log::BuildErrorLevel, Document, LineIndexExt, Uri, Workspace};
+
+use super::FeatureRequest;
+
+/// The URI scheme of the virtual documents that contain the build log of a project.
+pub const BUILD_LOG_SCHEME: &str = "texlab-log";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildLogParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BuildLogDocument {
+    pub uri: Url,
+    pub text: String,
+    pub links: Vec<DocumentLink>,
+}
+
+/// Converts the URI of a log file into the URI of its virtual document.
+pub fn build_log_uri(log_uri: &Uri) -> Option<Url> {
+    let path = log_uri.as_str().strip_prefix("file:")?;
+    Url::parse(&format!("{}:{}", BUILD_LOG_SCHEME, path)).ok()
+}
+
+/// Converts the URI of a virtual build log document back into the URI of the log file.
+pub fn build_log_file_uri(uri: &Url) -> Option<Uri> {
+    let path = uri
+        .as_str()
+        .strip_prefix(BUILD_LOG_SCHEME)?
+        .strip_prefix(':')?;
+    Uri::parse(&format!("file:{}", path)).ok()
+}
+
+/// Returns the build log of the project of the given document as a virtual document
+/// where every message links to its location in the sources.
+pub fn open_build_log(
+    request: FeatureRequest<BuildLogParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<BuildLogDocument> {
+    let log_document = if request.main_document().data.as_build_log().is_some() {
+        request.subset.documents[0].clone()
+    } else {
+        let root_document = request.root_document();
+
+        root_document
+            .data
+            .as_latex()?
+            .extras
+            .implicit_links
+            .log
+            .iter()
+            .find_map(|uri| request.workspace.get(uri))?
+    };
+
+    cancellation_token.result().ok()?;
+    Some(BuildLogDocument {
+        uri: build_log_uri(&log_document.uri)?,
+        text: log_document.text.clone(),
+        links: find_build_log_links(request.workspace.as_ref(), &log_document),
+    })
+}
+
+/// Links the messages of a build log to the files and lines they refer to.
+pub fn find_build_log_links(
+    workspace: &dyn Workspace,
+    log_document: &Document,
+) -> Vec<DocumentLink> {
+    let mut links = Vec::new();
+    let parse = match log_document.data.as_build_log() {
+        Some(parse) => parse,
+        None => return links,
+    };
+
+    let root_document = match workspace.documents().into_iter().find(|document| {
+        !document.uri.as_str().ends_with(".aux")
+            && document.data.as_latex().map_or(false, |data| {
+                data.extras
+                    .implicit_links
+                    .log
+                    .iter()
+                    .any(|uri| uri == &log_document.uri)
+            })
+    }) {
+        Some(document) => document,
+        None => return links,
+    };
+
+    let text = &log_document.text;
+    let mut used_lines = Vec::new();
+    for error in &parse.errors {
+        let range = match find_message_line(text, &error.message, &used_lines) {
+            Some(range) => range,
+            None => continue,
+        };
+        used_lines.push(range.start());
+
+        let mut target: Url = match error
+            .relative_path
+            .to_str()
+            .and_then(|path| root_document.uri.join(path).ok())
+        {
+            Some(target) => target,
+            None => continue,
+        };
+
+        if let Some(line) = error.line {
+            target.set_fragment(Some(&format!("L{}", line + 1)));
+        }
+
+        let level = match error.level {
+            BuildErrorLevel::Error => "Error",
+            BuildErrorLevel::Warning => "Warning",
+        };
+
+        let location = match error.line {
+            Some(line) => format!("{}:{}", error.relative_path.display(), line + 1),
+            None => error.relative_path.display().to_string(),
+        };
+
+        links.push(DocumentLink {
+            range: log_document.line_index.line_col_lsp_range(range),
+            target: Some(target),
+            tooltip: Some(format!("{}: {}", level, location)),
+            data: None,
+        });
+    }
+
+    links.sort_by_key(|link| (link.range.start.line, link.range.start.character));
+    links
+}
+
+/// Finds the line of the raw log that contains the beginning of a message.
+/// The log parser joins wrapped lines, so only a prefix of the message is searched.
+fn find_message_line(text: &str, message: &str, used_lines: &[TextSize]) -> Option<TextRange> {
+    let prefix: String = message.chars().take(30).collect();
+    if prefix.trim().is_empty() {
+        return None;
+    }
+
+    text.match_indices(prefix.as_str()).find_map(|(index, _)| {
+        let start = text[..index].rfind('\n').map_or(0, |i| i + 1);
+        let end = text[index..]
+            .find(|c| c == '\r' || c == '\n')
+            .map_or(text.len(), |i| index + i);
+
+        let range = TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32));
+        if used_lines.contains(&range.start()) {
+            None
+        } else {
+            Some(range)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_links() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    "\\documentclass{article}\n\\begin{document}\n\\end{document}",
+                ),
+                (
+                    "main.log",
+                    indoc!(
+                        r#"
+                            (./main.tex
+                            LaTeX Warning: Reference `foo' on page 1 undefined on input line 3.
+                            ! Undefined control sequence.
+                            l.7 \foo
+                            )
+                        "#
+                    ),
+                ),
+            ])
+            .main("main.tex")
+            .build();
+
+        let main_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let log_uri = tester.uri("main.log");
+        let document = open_build_log(tester.build_log(), CancellationToken::none()).unwrap();
+        assert_eq!(document.uri.scheme(), BUILD_LOG_SCHEME);
+        assert_eq!(
+            build_log_file_uri(&document.uri).as_ref(),
+            Some(log_uri.as_ref())
+        );
+
+        let links: Vec<_> = document
+            .links
+            .into_iter()
+            .map(|link| (link.range, link.target.unwrap()))
+            .collect();
+
+        let mut error_target = main_uri.clone();
+        error_target.set_fragment(Some("L7"));
+        assert_eq!(
+            links,
+            vec![
+                (Range::new_simple(1, 0, 1, 67), main_uri),
+                (Range::new_simple(2, 0, 2, 29), error_target),
+            ]
+        );
+    }
+}
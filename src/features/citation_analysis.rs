@@ -0,0 +1,279 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{Location, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{bibtex, latex, CstNode},
+    DocumentData, LineIndexExt,
+};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationAnalysisParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationAnalysis {
+    pub entries: Vec<CitationUsage>,
+    pub clusters: Vec<CoCitationCluster>,
+    pub uncited: Vec<String>,
+    pub commented_only: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CitationUsage {
+    pub key: String,
+    pub definitions: Vec<Location>,
+    pub references: Vec<Location>,
+    pub commented_references: Vec<Location>,
+}
+
+/// A group of entries that are (transitively) cited together
+/// in the same citation command.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoCitationCluster {
+    pub keys: Vec<String>,
+    pub citations: usize,
+}
+
+pub fn analyze_citations(
+    request: FeatureRequest<CitationAnalysisParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<CitationAnalysis> {
+    let mut usages: BTreeMap<String, CitationUsage> = BTreeMap::new();
+    let mut groups: Vec<BTreeSet<String>> = Vec::new();
+    for document in &request.subset.documents {
+        cancellation_token.result().ok()?;
+        let uri: Url = document.uri.as_ref().clone().into();
+        let location =
+            |range| Location::new(uri.clone(), document.line_index.line_col_lsp_range(range));
+
+        match &document.data {
+            DocumentData::Latex(data) => {
+                for (keys, is_commented) in find_citations(&data.root, &data.extras.code_chunks) {
+                    let mut group = BTreeSet::new();
+                    for (key, range) in keys {
+                        let usage = usages.entry(key.clone()).or_default();
+                        if is_commented {
+                            usage.commented_references.push(location(range));
+                        } else {
+                            usage.references.push(location(range));
+                            group.insert(key);
+                        }
+                    }
+
+                    if group.len() > 1 {
+                        groups.push(group);
+                    }
+                }
+            }
+            DocumentData::Bibtex(data) => {
+                for key in data
+                    .root
+                    .children()
+                    .filter_map(bibtex::Entry::cast)
+                    .filter_map(|entry| entry.key())
+                {
+                    usages
+                        .entry(key.to_string())
+                        .or_default()
+                        .definitions
+                        .push(location(key.small_range()));
+                }
+            }
+            DocumentData::Markdown(data) => {
+                for citation in &data.parse.citations {
+                    usages
+                        .entry(citation.text.to_string())
+                        .or_default()
+                        .references
+                        .push(location(citation.range));
+                }
+            }
+            DocumentData::BuildLog(_) => {}
+        }
+    }
+
+    let entries: Vec<_> = usages
+        .into_iter()
+        .map(|(key, usage)| CitationUsage { key, ..usage })
+        .collect();
+
+    let defined = entries.iter().filter(|entry| !entry.definitions.is_empty());
+    let uncited = defined
+        .clone()
+        .filter(|entry| entry.references.is_empty() && entry.commented_references.is_empty())
+        .map(|entry| entry.key.clone())
+        .collect();
+
+    let commented_only = defined
+        .filter(|entry| entry.references.is_empty() && !entry.commented_references.is_empty())
+        .map(|entry| entry.key.clone())
+        .collect();
+
+    Some(CitationAnalysis {
+        entries,
+        clusters: find_clusters(groups),
+        uncited,
+        commented_only,
+    })
+}
+
+/// Returns the keys of every citation command together with a flag
+/// that indicates whether the command is inside of a comment.
+/// The code chunks are lexed as comments but are not commented-out LaTeX.
+fn find_citations(
+    root: &latex::SyntaxNode,
+    code_chunks: &[TextRange],
+) -> Vec<(Vec<(String, TextRange)>, bool)> {
+    let mut citations = Vec::new();
+    for element in root.descendants_with_tokens() {
+        if let Some(citation) = element
+            .as_node()
+            .and_then(|node| latex::Citation::cast(node))
+        {
+            citations.push((citation_keys(citation, TextSize::from(0)), false));
+        } else if let Some(comment) = element.as_token().filter(|token| {
+            token.kind() == latex::COMMENT
+                && !code_chunks
+                    .iter()
+                    .any(|chunk| chunk.contains_range(token.text_range()))
+        }) {
+            let offset = comment.text_range().start() + TextSize::from(1);
+            let text = comment.text().get(1..).unwrap_or_default();
+            let root = latex::parse(text).root;
+            for citation in root.descendants().filter_map(latex::Citation::cast) {
+                citations.push((citation_keys(citation, offset), true));
+            }
+        }
+    }
+    citations
+}
+
+fn citation_keys(citation: latex::Citation, offset: TextSize) -> Vec<(String, TextRange)> {
+    citation
+        .key_list()
+        .into_iter()
+        .flat_map(|keys| keys.keys())
+        .map(|key| (key.to_string(), key.small_range() + offset))
+        .filter(|(key, _)| key != "*")
+        .collect()
+}
+
+/// Merges the keys of the citation commands that share at least one key.
+fn find_clusters(groups: Vec<BTreeSet<String>>) -> Vec<CoCitationCluster> {
+    let mut clusters: Vec<(BTreeSet<String>, usize)> = Vec::new();
+    for mut keys in groups {
+        let mut citations = 1;
+        let mut i = 0;
+        while i < clusters.len() {
+            if clusters[i].0.is_disjoint(&keys) {
+                i += 1;
+            } else {
+                let (other_keys, other_citations) = clusters.remove(i);
+                keys.extend(other_keys);
+                citations += other_citations;
+            }
+        }
+
+        clusters.push((keys, citations));
+    }
+
+    let mut clusters: Vec<_> = clusters
+        .into_iter()
+        .map(|(keys, citations)| CoCitationCluster {
+            keys: keys.into_iter().collect(),
+            citations,
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| a.keys.cmp(&b.keys));
+    clusters
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_analysis() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \addbibresource{main.bib}
+                        \cite{foo,bar} \cite{bar,baz} \cite{qux}
+                        % \cite{quux}
+                    "#},
+                ),
+                (
+                    "main.bib",
+                    r#"@article{foo,} @article{bar,} @article{baz,} @article{qux,} @article{quux,} @article{corge,}"#,
+                ),
+            ])
+            .main("main.tex")
+            .build();
+        let tex_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+
+        let analysis =
+            analyze_citations(tester.citation_analysis(), CancellationToken::none()).unwrap();
+
+        assert_eq!(
+            analysis.clusters,
+            vec![CoCitationCluster {
+                keys: vec!["bar".into(), "baz".into(), "foo".into()],
+                citations: 2,
+            }]
+        );
+        assert_eq!(analysis.uncited, vec!["corge"]);
+        assert_eq!(analysis.commented_only, vec!["quux"]);
+
+        let quux = analysis
+            .entries
+            .iter()
+            .find(|entry| entry.key == "quux")
+            .unwrap();
+        assert_eq!(
+            quux.commented_references,
+            vec![Location::new(tex_uri, Range::new_simple(2, 8, 2, 12))]
+        );
+    }
+
+    #[test]
+    fn test_lua_chunks() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \addbibresource{main.bib}
+                        \directlua{tex.print("\\cite{foo}")}
+                    "#},
+                ),
+                ("main.bib", r#"@article{foo,}"#),
+            ])
+            .main("main.tex")
+            .build();
+
+        let analysis =
+            analyze_citations(tester.citation_analysis(), CancellationToken::none()).unwrap();
+
+        assert!(analysis.commented_only.is_empty());
+        assert_eq!(analysis.uncited, vec!["foo"]);
+    }
+}
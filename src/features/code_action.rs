@@ -0,0 +1,58 @@
+mod booktabs;
+mod citation_sort;
+mod enum_labels;
+mod equation;
+mod font;
+mod formula;
+mod include;
+mod label_kind;
+mod language_tool;
+mod lint_rule;
+mod math_label;
+mod package;
+mod quantity;
+mod shell_escape;
+mod tie;
+mod unicode;
+mod whitespace;
+
+use cancellation::CancellationToken;
+use lsp_types::{CodeActionOrCommand, CodeActionParams};
+
+use self::{
+    booktabs::find_booktabs_actions, citation_sort::find_citation_sort_actions,
+    enum_labels::find_enum_label_actions, equation::find_equation_actions, font::find_font_actions,
+    formula::find_formula_actions, include::find_include_actions,
+    label_kind::find_label_kind_actions, language_tool::find_language_tool_actions,
+    lint_rule::find_lint_rule_actions, math_label::find_math_label_actions,
+    package::find_package_actions, quantity::find_quantity_actions,
+    shell_escape::find_shell_escape_actions, tie::find_tie_actions, unicode::find_unicode_actions,
+    whitespace::find_whitespace_actions,
+};
+
+use super::FeatureRequest;
+
+pub fn find_code_actions(
+    request: FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<CodeActionOrCommand>> {
+    let mut actions = Vec::new();
+    find_formula_actions(&request, cancellation_token, &mut actions);
+    find_equation_actions(&request, cancellation_token, &mut actions);
+    find_booktabs_actions(&request, cancellation_token, &mut actions);
+    find_font_actions(&request, cancellation_token, &mut actions);
+    find_tie_actions(&request, cancellation_token, &mut actions);
+    find_quantity_actions(&request, cancellation_token, &mut actions);
+    find_include_actions(&request, cancellation_token, &mut actions);
+    find_math_label_actions(&request, cancellation_token, &mut actions);
+    find_citation_sort_actions(&request, cancellation_token, &mut actions);
+    find_unicode_actions(&request, cancellation_token, &mut actions);
+    find_enum_label_actions(&request, cancellation_token, &mut actions);
+    find_label_kind_actions(&request, cancellation_token, &mut actions);
+    find_lint_rule_actions(&request, cancellation_token, &mut actions);
+    find_language_tool_actions(&request, cancellation_token, &mut actions);
+    find_whitespace_actions(&request, cancellation_token, &mut actions);
+    find_package_actions(&request, cancellation_token, &mut actions);
+    find_shell_escape_actions(&request, cancellation_token, &mut actions);
+    Some(actions)
+}
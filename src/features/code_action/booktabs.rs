@@ -0,0 +1,200 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{
+    features::FeatureRequest,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+/// Offers to rewrite a `tabular` with `\hline` and vertical rules
+/// to the style of the `booktabs` package.
+pub fn find_booktabs_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+    let text = &main_document.text;
+
+    let (environment, spec_index) = data
+        .root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .filter(|environment| environment.small_range().contains_range(range))
+        .filter_map(|environment| {
+            let name = environment.begin()?.name()?.key()?.to_string();
+            match name.as_str() {
+                "tabular" => Some((environment, 0)),
+                "tabular*" | "tabularx" => Some((environment, 1)),
+                _ => None,
+            }
+        })
+        .last()?;
+
+    cancellation_token.result().ok()?;
+
+    let spec = environment
+        .syntax()
+        .children()
+        .filter_map(latex::CurlyGroup::cast)
+        .nth(spec_index)?
+        .small_range();
+
+    let rules: Vec<_> = environment
+        .syntax()
+        .children()
+        .filter_map(latex::GenericCommand::cast)
+        .filter_map(|command| {
+            let name = command.name()?;
+            match name.text() {
+                "\\hline" => Some((name.text().to_string(), command.small_range())),
+                "\\cline" => Some((name.text().to_string(), name.text_range())),
+                _ => None,
+            }
+        })
+        .collect();
+
+    if !text[spec].contains('|') && rules.is_empty() {
+        return None;
+    }
+
+    let mut edits = vec![TextEdit::new(
+        main_document.line_index.line_col_lsp_range(spec),
+        text[spec].replace('|', ""),
+    )];
+
+    let body_start = spec.end();
+    let body_end = environment.end()?.small_range().start();
+    let is_blank = |start, end| text[TextRange::new(start, end)].trim().is_empty();
+
+    // Consecutive rules like `\hline\hline` are merged into a single rule.
+    let mut runs: Vec<(String, TextRange)> = Vec::new();
+    for (name, range) in rules {
+        match runs.last_mut() {
+            Some((last_name, last_range))
+                if name == "\\hline"
+                    && last_name == "\\hline"
+                    && is_blank(last_range.end(), range.start()) =>
+            {
+                *last_range = last_range.cover(range);
+            }
+            _ => runs.push((name, range)),
+        }
+    }
+
+    for (name, range) in runs {
+        let new_text = if name == "\\cline" {
+            "\\cmidrule".to_string()
+        } else if is_blank(body_start, range.start()) {
+            "\\toprule".to_string()
+        } else if is_blank(range.end(), body_end) {
+            "\\bottomrule".to_string()
+        } else {
+            "\\midrule".to_string()
+        };
+
+        edits.push(TextEdit::new(
+            main_document.line_index.line_col_lsp_range(range),
+            new_text,
+        ));
+    }
+
+    let mut changes = HashMap::new();
+    changes.insert(request.params.text_document.uri.clone(), edits);
+    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Convert table to booktabs style".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..CodeAction::default()
+    }));
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn apply_action(text: &str, line: u32, character: u32) -> Option<String> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(line)
+            .character(character)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_booktabs_actions(&request, CancellationToken::none(), &mut actions);
+        let action = match actions.pop()? {
+            CodeActionOrCommand::CodeAction(action) => action,
+            CodeActionOrCommand::Command(_) => return None,
+        };
+
+        let mut edits = action.edit?.changes?.into_iter().next()?.1;
+        let line_index = &request.main_document().line_index;
+        edits.sort_by_key(|edit| std::cmp::Reverse(line_index.offset_lsp(edit.range.start)));
+
+        let mut text = text.to_string();
+        for edit in edits {
+            let range = line_index.offset_lsp_range(edit.range);
+            text.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        }
+        Some(text)
+    }
+
+    #[test]
+    fn test_hline() {
+        let text = indoc! {r#"
+            \begin{table}
+                \begin{tabular}{|l|r|}
+                    \hline\hline
+                    Foo & Bar \\
+                    \hline
+                    Baz & Qux \\
+                    \cline{1-2}
+                    Quux & 1 \\
+                    \hline
+                \end{tabular}
+                \caption{Foo}
+            \end{table}
+        "#};
+
+        let expected = indoc! {r#"
+            \begin{table}
+                \begin{tabular}{lr}
+                    \toprule
+                    Foo & Bar \\
+                    \midrule
+                    Baz & Qux \\
+                    \cmidrule{1-2}
+                    Quux & 1 \\
+                    \bottomrule
+                \end{tabular}
+                \caption{Foo}
+            \end{table}
+        "#};
+
+        assert_eq!(apply_action(text, 3, 10).as_deref(), Some(expected));
+    }
+
+    #[test]
+    fn test_booktabs() {
+        let text = r#"\begin{tabular}{lr}\toprule Foo & Bar \\ \bottomrule\end{tabular}"#;
+        assert_eq!(apply_action(text, 0, 30), None);
+    }
+}
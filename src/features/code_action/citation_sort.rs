@@ -0,0 +1,263 @@
+use std::{collections::HashMap, fs, sync::Arc};
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    features::FeatureRequest,
+    syntax::{bibtex, latex, CstNode},
+    Document, LineIndexExt,
+};
+
+static BIBITEM_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\(?:bibitem\s*(?:\[[^\]]*\])?|entry)\s*\{([^}]+)\}").unwrap());
+
+/// Offers to sort the keys of a citation with multiple keys like
+/// `natbib` does with the `sort&compress` option.
+pub fn find_citation_sort_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let keys: Vec<_> = data
+        .root
+        .descendants()
+        .filter_map(latex::Citation::cast)
+        .find(|citation| citation.small_range().intersect(range).is_some())?
+        .key_list()?
+        .keys()
+        .map(|key| (key.small_range(), key.to_string()))
+        .collect();
+
+    if keys.len() < 2 {
+        return None;
+    }
+
+    cancellation_token.result().ok()?;
+    let bibliography_order = find_bibliography_order(request);
+    let entries = find_sort_keys(&request.subset.documents);
+
+    let names: Vec<_> = keys.iter().map(|(_, name)| name.as_str()).collect();
+    let mut by_bibliography = names.clone();
+    by_bibliography.sort_by_key(|name| {
+        bibliography_order
+            .iter()
+            .position(|other| other == name)
+            .unwrap_or(usize::MAX)
+    });
+
+    let mut by_author = names.clone();
+    by_author.sort_by_cached_key(|name| {
+        let sort_key = entries.get(*name);
+        (sort_key.is_none(), sort_key.cloned().unwrap_or_default())
+    });
+
+    for (title, sorted_names) in vec![
+        ("Sort citations by bibliography order", by_bibliography),
+        ("Sort citations by author and year", by_author),
+    ] {
+        if sorted_names == names {
+            continue;
+        }
+
+        let edits = keys
+            .iter()
+            .zip(sorted_names)
+            .filter(|((_, name), new_name)| name.as_str() != *new_name)
+            .map(|((range, _), new_name)| {
+                TextEdit::new(
+                    main_document.line_index.line_col_lsp_range(*range),
+                    new_name.to_string(),
+                )
+            })
+            .collect();
+
+        let mut changes = HashMap::new();
+        changes.insert(request.params.text_document.uri.clone(), edits);
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+/// Returns the keys in the order of the generated bibliography of the last build.
+/// Falls back to the order of the entries in the bibliography databases.
+fn find_bibliography_order(request: &FeatureRequest<CodeActionParams>) -> Vec<String> {
+    let bbl_keys = request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.implicit_links.aux.iter())
+        .filter_map(|uri| uri.to_file_path().ok())
+        .find_map(|path| fs::read_to_string(path.with_extension("bbl")).ok())
+        .map(|text| {
+            BIBITEM_REGEX
+                .captures_iter(&text)
+                .map(|captures| captures[1].trim().to_string())
+                .collect::<Vec<_>>()
+        })
+        .filter(|keys| !keys.is_empty());
+
+    bbl_keys.unwrap_or_else(|| {
+        request
+            .subset
+            .documents
+            .iter()
+            .filter_map(|document| document.data.as_bibtex())
+            .flat_map(|data| data.root.children().filter_map(bibtex::Entry::cast))
+            .filter_map(|entry| Some(entry.key()?.to_string()))
+            .collect()
+    })
+}
+
+/// Maps the keys of the bibliography entries to the last name of their first author and their year.
+fn find_sort_keys(documents: &[Arc<Document>]) -> HashMap<String, (String, String)> {
+    let mut sort_keys = HashMap::new();
+    for data in documents
+        .iter()
+        .filter_map(|document| document.data.as_bibtex())
+    {
+        for entry in data.root.children().filter_map(bibtex::Entry::cast) {
+            let key = match entry.key() {
+                Some(key) => key.to_string(),
+                None => continue,
+            };
+
+            let field = |names: &[&str]| {
+                names.iter().find_map(|name| {
+                    entry
+                        .fields()
+                        .find(|field| {
+                            field.name().map_or(false, |field_name| {
+                                field_name.text().eq_ignore_ascii_case(name)
+                            })
+                        })
+                        .and_then(|field| field.value())
+                        .map(|value| value_text(&value))
+                })
+            };
+
+            let author = field(&["author", "editor"])
+                .map(|authors| last_name(&authors))
+                .unwrap_or_default();
+
+            let year = field(&["year", "date"])
+                .map(|year| year.chars().take(4).collect())
+                .unwrap_or_default();
+
+            sort_keys.insert(key, (author, year));
+        }
+    }
+    sort_keys
+}
+
+fn value_text(value: &bibtex::Value) -> String {
+    value
+        .syntax()
+        .text()
+        .to_string()
+        .chars()
+        .filter(|c| !matches!(c, '{' | '}' | '"'))
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Returns the last name of the first author of a BibTeX name list.
+fn last_name(authors: &str) -> String {
+    let first_author = authors.split(" and ").next().unwrap_or_default().trim();
+    let name = match first_author.split_once(',') {
+        Some((last_name, _)) => last_name.trim(),
+        None => first_author.rsplit(' ').next().unwrap_or_default(),
+    };
+    name.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn find_edits(tex: &str, bib: &str) -> Vec<(String, Vec<String>)> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", tex), ("main.bib", bib)])
+            .main("main.tex")
+            .line(0)
+            .character(8)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_citation_sort_actions(&request, CancellationToken::none(), &mut actions);
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let edits = action.edit?.changes?.into_iter().next()?.1;
+                    Some((
+                        action.title,
+                        edits.into_iter().map(|edit| edit.new_text).collect(),
+                    ))
+                }
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort() {
+        let edits = find_edits(
+            "\\cite{baz, foo,bar}\n\\bibliography{main}",
+            "@article{foo, author = {Zed, Anna}, year = 2001}\n\
+             @article{bar, author = {Bob Miller and Carl Smith}, year = 1999}\n\
+             @article{baz, author = {Bob Miller}, year = 1990}",
+        );
+
+        assert_eq!(
+            edits,
+            vec![
+                (
+                    "Sort citations by bibliography order".to_string(),
+                    vec!["foo".to_string(), "bar".to_string(), "baz".to_string()]
+                ),
+                (
+                    "Sort citations by author and year".to_string(),
+                    vec!["bar".to_string(), "foo".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sorted() {
+        let edits = find_edits(
+            "\\cite{foo,bar}\n\\bibliography{main}",
+            "@article{foo, author = {Anna Able}}\n@article{bar, author = {Bob Miller}}",
+        );
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn test_last_name() {
+        assert_eq!(last_name("Miller, Bob and Carl Smith"), "miller");
+        assert_eq!(last_name("Carl Smith and Bob Miller"), "smith");
+    }
+}
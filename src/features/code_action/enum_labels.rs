@@ -0,0 +1,269 @@
+use std::{collections::HashMap, convert::TryFrom};
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{
+    features::FeatureRequest,
+    format_alph, format_roman,
+    syntax::{
+        latex::{self, HasBrack},
+        CstNode,
+    },
+    LineIndexExt,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum LabelStyle {
+    Arabic,
+    LowerAlph,
+    UpperAlph,
+    LowerRoman,
+    UpperRoman,
+}
+
+/// A manual item label like `(a)` that is split into the counter and its surroundings.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct LabelPattern {
+    prefix: String,
+    style: LabelStyle,
+    start: u32,
+    suffix: String,
+}
+
+impl LabelPattern {
+    fn parse(label: &str) -> Option<Self> {
+        let end = label
+            .char_indices()
+            .filter(|(_, c)| c.is_ascii_alphanumeric())
+            .last()
+            .map(|(i, _)| i + 1)?;
+
+        let start = label[..end]
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| c.is_ascii_alphanumeric())
+            .last()
+            .map(|(i, _)| i)?;
+
+        let counter = &label[start..end];
+        let (style, value) = if let Ok(value) = counter.parse() {
+            (LabelStyle::Arabic, value)
+        } else if counter == "i" || (counter.len() > 1 && is_roman(counter, false)) {
+            (LabelStyle::LowerRoman, parse_roman(counter)?)
+        } else if counter == "I" || (counter.len() > 1 && is_roman(counter, true)) {
+            (LabelStyle::UpperRoman, parse_roman(counter)?)
+        } else if counter.len() == 1 && counter.chars().all(|c| c.is_ascii_lowercase()) {
+            (
+                LabelStyle::LowerAlph,
+                u32::from(counter.as_bytes()[0] - b'a' + 1),
+            )
+        } else if counter.len() == 1 && counter.chars().all(|c| c.is_ascii_uppercase()) {
+            (
+                LabelStyle::UpperAlph,
+                u32::from(counter.as_bytes()[0] - b'A' + 1),
+            )
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            prefix: label[..start].to_string(),
+            style,
+            start: value,
+            suffix: label[end..].to_string(),
+        })
+    }
+
+    fn format(&self, index: u32) -> Option<String> {
+        let value = self.start + index;
+        let counter = match self.style {
+            LabelStyle::Arabic => value.to_string(),
+            LabelStyle::LowerAlph if value <= 26 => format_alph(value).to_lowercase(),
+            LabelStyle::UpperAlph if value <= 26 => format_alph(value),
+            LabelStyle::LowerAlph | LabelStyle::UpperAlph => return None,
+            LabelStyle::LowerRoman => format_roman(value).to_lowercase(),
+            LabelStyle::UpperRoman => format_roman(value),
+        };
+
+        Some(format!("{}{}{}", self.prefix, counter, self.suffix))
+    }
+}
+
+/// Offers to renumber the manual labels of the items of an `enumerate` environment
+/// sequentially, starting with the label of the first item.
+pub fn find_enum_label_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let environment = data
+        .root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .filter(|environment| environment.small_range().contains_range(range))
+        .filter(|environment| {
+            environment
+                .begin()
+                .and_then(|begin| begin.name())
+                .and_then(|name| name.key())
+                .map_or(false, |name| name.to_string() == "enumerate")
+        })
+        .last()?;
+
+    cancellation_token.result().ok()?;
+
+    let mut labels = Vec::new();
+    for item in environment
+        .syntax()
+        .children()
+        .filter_map(latex::EnumItem::cast)
+    {
+        let group = item.label()?;
+        let range = TextRange::new(
+            group.left_brack()?.text_range().end(),
+            group.right_brack()?.text_range().start(),
+        );
+        labels.push((range, group.content_text()?));
+    }
+
+    let pattern = LabelPattern::parse(&labels.first()?.1)?;
+    let mut edits = Vec::new();
+    for (index, (range, label)) in labels.iter().enumerate() {
+        let new_label = pattern.format(index as u32)?;
+        if *label != new_label {
+            edits.push(TextEdit::new(
+                main_document.line_index.line_col_lsp_range(*range),
+                new_label,
+            ));
+        }
+    }
+
+    if edits.is_empty() {
+        return None;
+    }
+
+    let mut changes = HashMap::new();
+    changes.insert(request.params.text_document.uri.clone(), edits);
+    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Renumber item labels".to_string(),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..CodeAction::default()
+    }));
+
+    Some(())
+}
+
+fn is_roman(text: &str, uppercase: bool) -> bool {
+    text.chars().all(|c| {
+        let c = if uppercase { c } else { c.to_ascii_uppercase() };
+        "IVXLCDM".contains(c)
+    }) && text.chars().all(|c| c.is_ascii_uppercase() == uppercase)
+}
+
+fn parse_roman(text: &str) -> Option<u32> {
+    let values: Vec<u32> = text
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'I' => Some(1),
+            'V' => Some(5),
+            'X' => Some(10),
+            'L' => Some(50),
+            'C' => Some(100),
+            'D' => Some(500),
+            'M' => Some(1000),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+
+    let mut value = 0;
+    for (i, current) in values.iter().enumerate() {
+        match values.get(i + 1) {
+            Some(next) if next > current => value -= *current as i64,
+            _ => value += *current as i64,
+        }
+    }
+
+    u32::try_from(value).ok().filter(|value| *value > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn find_new_labels(text: &str) -> Vec<String> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(1)
+            .character(0)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_enum_label_actions(&request, CancellationToken::none(), &mut actions);
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => action.edit?.changes?.into_iter().next(),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .flat_map(|(_, edits)| edits.into_iter().map(|edit| edit.new_text))
+            .collect()
+    }
+
+    #[test]
+    fn test_alph() {
+        let labels = find_new_labels(indoc!(
+            r#"
+                \begin{enumerate}
+                    \item[(a)] Foo
+                    \item[(c)] Bar
+                    \item[(c)] Baz
+                \end{enumerate}
+            "#
+        ));
+        assert_eq!(labels, vec!["(b)"]);
+    }
+
+    #[test]
+    fn test_roman() {
+        let labels = find_new_labels(indoc!(
+            r#"
+                \begin{enumerate}
+                    \item[iii.] Foo
+                    \item[iii.] Bar
+                \end{enumerate}
+            "#
+        ));
+        assert_eq!(labels, vec!["iv."]);
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(
+            LabelPattern::parse("Step 1:"),
+            Some(LabelPattern {
+                prefix: "Step ".into(),
+                style: LabelStyle::Arabic,
+                start: 1,
+                suffix: ":".into(),
+            })
+        );
+        assert_eq!(LabelPattern::parse("(1a)"), None);
+    }
+}
@@ -0,0 +1,367 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{
+    features::FeatureRequest,
+    indentation,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+const OPERATOR_COMMANDS: &[&str] = &[
+    "\\approx", "\\cdot", "\\equiv", "\\ge", "\\geq", "\\le", "\\leq", "\\mp", "\\neq", "\\pm",
+    "\\sim", "\\times", "\\to",
+];
+
+/// Offers to break an `equation` that does not fit into a single line
+/// by converting it to a `split` or `multline` environment.
+pub fn find_equation_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+    let text = &main_document.text;
+
+    let environment = data
+        .root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .filter(|environment| environment.small_range().contains_range(range))
+        .last()?;
+
+    let name = environment.begin()?.name()?.key()?.to_string();
+    let star = match name.as_str() {
+        "equation" => "",
+        "equation*" => "*",
+        _ => return None,
+    };
+
+    cancellation_token.result().ok()?;
+
+    let line_length = request
+        .context
+        .options
+        .read()
+        .unwrap()
+        .formatter_line_length
+        .map(|value| {
+            if value <= 0 {
+                usize::MAX
+            } else {
+                value as usize
+            }
+        })
+        .unwrap_or(80);
+
+    let environment_range = environment.small_range();
+    let environment_text = &text[environment_range];
+    if environment_text
+        .lines()
+        .all(|line| line.chars().count() <= line_length)
+    {
+        return None;
+    }
+
+    let body_start = environment.begin()?.small_range().end();
+    let body_end = environment.end()?.small_range().start();
+    let labels: Vec<_> = environment
+        .syntax()
+        .children()
+        .filter_map(latex::LabelDefinition::cast)
+        .map(|label| label.small_range())
+        .collect();
+
+    let mut content = String::new();
+    let mut start = body_start;
+    for label in &labels {
+        content.push_str(&text[TextRange::new(start, label.start())]);
+        start = label.end();
+    }
+    content.push_str(&text[TextRange::new(start, body_end)]);
+    let content = content.trim();
+    if content.contains("\\\\") || content.contains("\\begin") {
+        return None;
+    }
+
+    let indent = indentation::line_indent(text, environment_range.start().into());
+    let width = line_length
+        .saturating_sub(indent.chars().count() + 8)
+        .max(20);
+    let lines = break_lines(content, width);
+    if lines.len() < 2 {
+        return None;
+    }
+
+    let labels: Vec<_> = labels.into_iter().map(|label| &text[label]).collect();
+    let unit = indentation::unit(main_document);
+    let candidates = vec![
+        (
+            "Break equation with split",
+            make_split(indent, &unit, star, &labels, &lines),
+        ),
+        (
+            "Break equation with multline",
+            make_multline(indent, &unit, star, &labels, &lines),
+        ),
+    ];
+
+    for (title, new_text) in candidates {
+        let range = main_document
+            .line_index
+            .line_col_lsp_range(environment_range);
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(range, new_text)],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+fn make_split(indent: &str, unit: &str, star: &str, labels: &[&str], lines: &[&str]) -> String {
+    let relation = lines[0].find('=').filter(|&i| is_top_level(lines[0], i));
+    let mut new_text = format!("\\begin{{equation{}}}\n", star);
+    for label in labels {
+        new_text.push_str(&format!("{}{}{}\n", indent, unit, label));
+    }
+
+    new_text.push_str(&format!("{}{}\\begin{{split}}\n", indent, unit));
+    for (i, line) in lines.iter().enumerate() {
+        let line = match (i, relation) {
+            (0, Some(relation)) => format!("{}&{}", &line[..relation], &line[relation..]),
+            (0, None) => format!("&{}", line),
+            (_, Some(_)) => format!("&\\quad {}", line),
+            (_, None) => format!("&{}", line),
+        };
+
+        let end = if i + 1 < lines.len() { " \\\\" } else { "" };
+        new_text.push_str(&format!("{}{}{}{}{}\n", indent, unit, unit, line, end));
+    }
+
+    new_text.push_str(&format!("{}{}\\end{{split}}\n", indent, unit));
+    new_text.push_str(&format!("{}\\end{{equation{}}}", indent, star));
+    new_text
+}
+
+fn make_multline(indent: &str, unit: &str, star: &str, labels: &[&str], lines: &[&str]) -> String {
+    let mut new_text = format!("\\begin{{multline{}}}\n", star);
+    for label in labels {
+        new_text.push_str(&format!("{}{}{}\n", indent, unit, label));
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        let end = if i + 1 < lines.len() { " \\\\" } else { "" };
+        new_text.push_str(&format!("{}{}{}{}\n", indent, unit, line, end));
+    }
+
+    new_text.push_str(&format!("{}\\end{{multline{}}}", indent, star));
+    new_text
+}
+
+/// Splits the formula into lines that fit into the given width
+/// by breaking before binary operators at the top level.
+fn break_lines(content: &str, width: usize) -> Vec<&str> {
+    let candidates = find_break_points(content);
+    let length = |start: usize, end: usize| content[start..end].chars().count();
+
+    let mut breaks = Vec::new();
+    let mut line_start = 0;
+    let mut previous = None;
+    for candidate in candidates.into_iter().chain(std::iter::once(content.len())) {
+        if length(line_start, candidate) > width {
+            if let Some(previous) = previous.filter(|&previous| previous > line_start) {
+                breaks.push(previous);
+                line_start = previous;
+            }
+        }
+        previous = Some(candidate);
+    }
+
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for end in breaks.into_iter().chain(std::iter::once(content.len())) {
+        lines.push(content[start..end].trim());
+        start = end;
+    }
+    lines
+}
+
+fn find_break_points(content: &str) -> Vec<usize> {
+    tokenize(content)
+        .into_iter()
+        .filter(|&(i, token, depth)| {
+            depth == 0
+                && (matches!(token, "+" | "-" | "=" | "<" | ">")
+                    || OPERATOR_COMMANDS.contains(&token))
+                && is_binary(content, i)
+        })
+        .map(|(i, _, _)| i)
+        .collect()
+}
+
+/// Splits the formula into characters and command names together with their nesting depth.
+/// Braces, `\left`...`\right` and `\begin`...`\end` are treated as nesting.
+fn tokenize(content: &str) -> Vec<(usize, &str, i32)> {
+    let mut tokens = Vec::new();
+    let mut depth = 0;
+    let mut chars = content.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let mut end = i + c.len_utf8();
+        if c == '\\' {
+            while let Some((j, c)) = chars.peek().copied() {
+                if !c.is_ascii_alphabetic() {
+                    break;
+                }
+                end = j + 1;
+                chars.next();
+            }
+        }
+
+        let token = &content[i..end];
+        match token {
+            "{" | "\\left" | "\\begin" => depth += 1,
+            "}" | "\\right" | "\\end" => depth -= 1,
+            _ => {}
+        }
+        tokens.push((i, token, depth));
+    }
+    tokens
+}
+
+/// Checks if the operator at the given position has a left operand.
+fn is_binary(content: &str, index: usize) -> bool {
+    content[..index]
+        .trim_end()
+        .chars()
+        .last()
+        .map_or(false, |c| !"({[=<>,&^_+-".contains(c))
+}
+
+fn is_top_level(content: &str, index: usize) -> bool {
+    tokenize(content)
+        .into_iter()
+        .find(|&(i, _, _)| i == index)
+        .map_or(false, |(_, _, depth)| depth == 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_break_lines() {
+        assert_eq!(
+            break_lines("a = b + c + d - e", 8),
+            vec!["a = b", "+ c + d", "- e"]
+        );
+        assert_eq!(break_lines("-a = {b + c}", 5), vec!["-a", "= {b + c}"]);
+    }
+
+    #[test]
+    fn test_break_points_nesting() {
+        let content = "a = \\left( b + c \\right) - \\begin{pmatrix} d \\\\ -e \\end{pmatrix} + f";
+        assert_eq!(
+            find_break_points(content)
+                .into_iter()
+                .map(|i| &content[i..i + 1])
+                .collect::<Vec<_>>(),
+            vec!["=", "-", "+"]
+        );
+        assert_eq!(
+            break_lines("x = \\left( a + b + c \\right) + d", 12),
+            vec!["x", "= \\left( a + b + c \\right)", "+ d"]
+        );
+    }
+
+    #[test]
+    fn test_short_equation() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\begin{equation}a = b\\end{equation}")])
+            .main("main.tex")
+            .line(0)
+            .character(17)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_equation_actions(&request, CancellationToken::none(), &mut actions);
+        assert!(actions.is_empty());
+    }
+
+    #[test]
+    fn test_long_equation() {
+        let text = indoc! {r#"
+            \begin{equation}
+                \label{eq:foo}
+                f(x) = \alpha_1 x_1 + \alpha_2 x_2 + \alpha_3 x_3 + \alpha_4 x_4 + \alpha_5 x_5 + \alpha_6 x_6 + \alpha_7 x_7
+            \end{equation}
+        "#};
+
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(2)
+            .character(8)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_equation_actions(&request, CancellationToken::none(), &mut actions);
+        let new_texts: Vec<_> = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let edit = action.edit?.changes?.into_iter().next()?.1.pop()?;
+                    Some(edit.new_text)
+                }
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect();
+
+        assert_eq!(
+            new_texts,
+            vec![
+                [
+                    "\\begin{equation}",
+                    "    \\label{eq:foo}",
+                    "    \\begin{split}",
+                    "        f(x) &= \\alpha_1 x_1 + \\alpha_2 x_2 + \\alpha_3 x_3 + \\alpha_4 x_4 \\\\",
+                    "        &\\quad + \\alpha_5 x_5 + \\alpha_6 x_6 + \\alpha_7 x_7",
+                    "    \\end{split}",
+                    "\\end{equation}",
+                ]
+                .join("\n"),
+                [
+                    "\\begin{multline}",
+                    "    \\label{eq:foo}",
+                    "    f(x) = \\alpha_1 x_1 + \\alpha_2 x_2 + \\alpha_3 x_3 + \\alpha_4 x_4 \\\\",
+                    "    + \\alpha_5 x_5 + \\alpha_6 x_6 + \\alpha_7 x_7",
+                    "\\end{multline}",
+                ]
+                .join("\n"),
+            ]
+        );
+    }
+}
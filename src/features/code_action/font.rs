@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{
+    features::FeatureRequest,
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt,
+};
+
+/// The obsolete font switches of LaTeX 2.09 and the commands that replace them.
+const FONT_COMMANDS: &[(&str, &str)] = &[
+    ("\\bf", "\\textbf"),
+    ("\\it", "\\textit"),
+    ("\\rm", "\\textrm"),
+    ("\\sc", "\\textsc"),
+    ("\\sf", "\\textsf"),
+    ("\\sl", "\\textsl"),
+    ("\\tt", "\\texttt"),
+];
+
+struct FontFix {
+    name: TextRange,
+    old_command: &'static str,
+    new_command: &'static str,
+    edits: Vec<(TextRange, String)>,
+}
+
+/// Offers to replace obsolete font switches like `{\bf ...}`
+/// with the corresponding commands like `\textbf{...}`.
+pub fn find_font_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let fixes: Vec<_> = data
+        .root
+        .descendants()
+        .filter_map(latex::GenericCommand::cast)
+        .filter_map(|command| find_font_fix(&main_document.text, command))
+        .collect();
+
+    cancellation_token.result().ok()?;
+
+    if let Some(fix) = fixes.iter().find(|fix| fix.name.intersect(range).is_some()) {
+        actions.push(make_action(
+            request,
+            main_document,
+            format!(
+                "Replace {} with {}{{...}}",
+                fix.old_command, fix.new_command
+            ),
+            CodeActionKind::QUICKFIX,
+            &[fix],
+        ));
+    }
+
+    if !fixes.is_empty() {
+        let fixes: Vec<_> = fixes.iter().collect();
+        actions.push(make_action(
+            request,
+            main_document,
+            "Replace all obsolete font commands".to_string(),
+            CodeActionKind::SOURCE_FIX_ALL,
+            &fixes,
+        ));
+    }
+
+    Some(())
+}
+
+fn make_action(
+    request: &FeatureRequest<CodeActionParams>,
+    document: &Document,
+    title: String,
+    kind: CodeActionKind,
+    fixes: &[&FontFix],
+) -> CodeActionOrCommand {
+    let edits = fixes
+        .iter()
+        .flat_map(|fix| fix.edits.iter())
+        .map(|(range, new_text)| {
+            TextEdit::new(
+                document.line_index.line_col_lsp_range(*range),
+                new_text.clone(),
+            )
+        })
+        .collect();
+
+    let mut changes = HashMap::new();
+    changes.insert(request.params.text_document.uri.clone(), edits);
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title,
+        kind: Some(kind),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..CodeAction::default()
+    })
+}
+
+/// Computes the edits that turn the font switch into a command
+/// whose argument spans the rest of the enclosing group or environment.
+fn find_font_fix(text: &str, command: latex::GenericCommand) -> Option<FontFix> {
+    let name = command.name()?;
+    let (old_command, new_command) = FONT_COMMANDS
+        .iter()
+        .copied()
+        .find(|(old_command, _)| *old_command == name.text())?;
+
+    // Font switches inside of formulas are left alone because they cannot become text commands.
+    let scope = command.syntax().ancestors().skip(1).find(|node| {
+        matches!(
+            node.kind(),
+            latex::CURLY_GROUP | latex::ENVIRONMENT | latex::FORMULA | latex::EQUATION
+        )
+    })?;
+
+    // The group is replaced entirely if the switch is its first child: `{\bf foo}` => `\textbf{foo}`.
+    let (open, close) = if let Some(group) = latex::CurlyGroup::cast(scope) {
+        let open = group.left_curly()?.text_range();
+        (Some(open), group.right_curly()?.text_range().start())
+    } else if let Some(environment) = latex::Environment::cast(scope) {
+        let begin = environment.begin()?.small_range().end();
+        let end = environment.end()?.small_range().start();
+        let body = &text[TextRange::new(begin, end)];
+        (None, begin + TextSize::of(body.trim_end()))
+    } else {
+        return None;
+    };
+
+    let name_range = name.text_range();
+    let rest = &text[TextRange::new(name_range.end(), close.max(name_range.end()))];
+    let argument_start =
+        name_range.end() + TextSize::of(&rest[..rest.len() - rest.trim_start().len()]);
+
+    let edits = match open.filter(|open| {
+        text[TextRange::new(open.end(), name_range.start())]
+            .trim()
+            .is_empty()
+    }) {
+        Some(open) => vec![(
+            TextRange::new(open.start(), argument_start),
+            format!("{}{{", new_command),
+        )],
+        None => vec![
+            (
+                TextRange::new(name_range.start(), argument_start),
+                format!("{}{{", new_command),
+            ),
+            (TextRange::empty(close.max(argument_start)), "}".to_string()),
+        ],
+    };
+
+    Some(FontFix {
+        name: name_range,
+        old_command,
+        new_command,
+        edits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn apply_action(text: &str, title: &str) -> Option<String> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(0)
+            .character(2)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_font_actions(&request, CancellationToken::none(), &mut actions);
+        let action = actions.into_iter().find_map(|action| match action {
+            CodeActionOrCommand::CodeAction(action) if action.title == title => Some(action),
+            _ => None,
+        })?;
+
+        let mut edits = action.edit?.changes?.into_iter().next()?.1;
+        let line_index = &request.main_document().line_index;
+        edits.sort_by_key(|edit| std::cmp::Reverse(line_index.offset_lsp(edit.range.start)));
+
+        let mut text = text.to_string();
+        for edit in edits {
+            let range = line_index.offset_lsp_range(edit.range);
+            text.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        }
+        Some(text)
+    }
+
+    #[test]
+    fn test_quick_fix() {
+        let actual = apply_action(r#"{\bf Foo} {\it Bar}"#, "Replace \\bf with \\textbf{...}");
+        assert_eq!(actual.as_deref(), Some(r#"\textbf{Foo} {\it Bar}"#));
+    }
+
+    #[test]
+    fn test_fix_all() {
+        let text = indoc! {r#"
+            {\bf\it Foo} {Bar \sc Baz}
+            \begin{center}
+                \tt Qux
+            \end{center}
+            $\rm x$
+        "#};
+
+        let expected = indoc! {r#"
+            \textbf{\textit{Foo}} {Bar \textsc{Baz}}
+            \begin{center}
+                \texttt{Qux}
+            \end{center}
+            $\rm x$
+        "#};
+
+        let actual = apply_action(text, "Replace all obsolete font commands");
+        assert_eq!(actual.as_deref(), Some(expected));
+    }
+}
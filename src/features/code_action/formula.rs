@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    features::FeatureRequest,
+    indentation,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+/// Offers to convert the inline formula under the cursor into display math,
+/// an `equation` environment with a new label or an `align` environment.
+pub fn find_formula_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let formula = data
+        .root
+        .descendants()
+        .filter_map(latex::Formula::cast)
+        .find(|formula| {
+            let formula_range = formula.small_range();
+            formula_range.contains_range(range) || range.contains_range(formula_range)
+        })?;
+
+    cancellation_token.result().ok()?;
+
+    let mut delimiters = formula
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == latex::DOLLAR);
+    let open = delimiters.next()?;
+    let close = delimiters.last()?;
+    let text = &main_document.text;
+    let content =
+        text[usize::from(open.text_range().end())..usize::from(close.text_range().start())].trim();
+    if content.is_empty() {
+        return None;
+    }
+
+    let label = make_label(request);
+    let candidates = vec![
+        (
+            "Convert to display math",
+            "\\[",
+            vec![content.to_string()],
+            "\\]",
+        ),
+        (
+            "Convert to equation environment",
+            "\\begin{equation}",
+            vec![content.to_string(), format!("\\label{{{}}}", label)],
+            "\\end{equation}",
+        ),
+        (
+            "Convert to align environment",
+            "\\begin{align}",
+            split_equations(content),
+            "\\end{align}",
+        ),
+    ];
+
+    let unit = indentation::unit(main_document);
+    for (title, begin, lines, end) in candidates {
+        let (edit_range, new_text) =
+            make_edit(text, formula.small_range(), &unit, begin, &lines, end);
+        let range = main_document.line_index.line_col_lsp_range(edit_range);
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(range, new_text)],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::REFACTOR_REWRITE),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+/// Creates a label of the form `eq:<number>` that is not used in the project yet.
+fn make_label(request: &FeatureRequest<CodeActionParams>) -> String {
+    let names: FxHashSet<_> = request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.label_names.iter())
+        .filter(|name| name.is_definition)
+        .map(|name| name.text.as_str())
+        .collect();
+
+    (1..)
+        .map(|number| format!("eq:{}", number))
+        .find(|label| !names.contains(label.as_str()))
+        .unwrap()
+}
+
+/// Splits the formula at the top-level `=` signs and aligns them.
+fn split_equations(content: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in content.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            '=' if depth == 0 => {
+                parts.push(content[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(content[start..].trim());
+
+    match parts.as_slice() {
+        [] | [_] => vec![content.to_string()],
+        [lhs, rhs @ ..] => {
+            let mut lines: Vec<_> = rhs.iter().map(|part| format!("&= {}", part)).collect();
+            lines[0] = format!("{} {}", lhs, lines[0]);
+            let count = lines.len();
+            for line in &mut lines[..count - 1] {
+                line.push_str(" \\\\");
+            }
+            lines
+        }
+    }
+}
+
+/// Places the block on separate lines and keeps the indentation of the current line.
+fn make_edit(
+    text: &str,
+    range: TextRange,
+    unit: &str,
+    begin: &str,
+    lines: &[String],
+    end: &str,
+) -> (TextRange, String) {
+    let start = usize::from(range.start());
+    let end_offset = usize::from(range.end());
+    let line_start = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = text[end_offset..]
+        .find('\n')
+        .map_or(text.len(), |i| end_offset + i);
+
+    let indent = indentation::line_indent(text, start);
+
+    let mut new_text = String::new();
+    let mut edit_start = start;
+    let before = &text[line_start..start];
+    if !before.trim().is_empty() {
+        edit_start = line_start + before.trim_end().len();
+        new_text.push('\n');
+        new_text.push_str(indent);
+    }
+
+    new_text.push_str(begin);
+    new_text.push('\n');
+    for line in lines {
+        new_text.push_str(indent);
+        new_text.push_str(unit);
+        new_text.push_str(line);
+        new_text.push('\n');
+    }
+    new_text.push_str(indent);
+    new_text.push_str(end);
+
+    let mut edit_end = end_offset;
+    let after = &text[end_offset..line_end];
+    if !after.trim().is_empty() {
+        edit_end = line_end - after.trim_start().len();
+        new_text.push('\n');
+        new_text.push_str(indent);
+    }
+
+    let edit_range = TextRange::new(
+        TextSize::from(edit_start as u32),
+        TextSize::from(edit_end as u32),
+    );
+    (edit_range, new_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn find_new_texts(text: &str, character: u32) -> Vec<(String, String)> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(0)
+            .character(character)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_formula_actions(&request, CancellationToken::none(), &mut actions);
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => {
+                    let edit = action.edit?.changes?.into_iter().next()?.1.pop()?;
+                    Some((action.title, edit.new_text))
+                }
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_no_formula() {
+        assert!(find_new_texts("Foo", 1).is_empty());
+    }
+
+    #[test]
+    fn test_inline_formula() {
+        let actual = find_new_texts("Foo $a = b + c = d$ bar", 6);
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    "Convert to display math".to_string(),
+                    "\n\\[\n\ta = b + c = d\n\\]\n".to_string()
+                ),
+                (
+                    "Convert to equation environment".to_string(),
+                    "\n\\begin{equation}\n\ta = b + c = d\n\t\\label{eq:1}\n\\end{equation}\n"
+                        .to_string()
+                ),
+                (
+                    "Convert to align environment".to_string(),
+                    "\n\\begin{align}\n\ta &= b + c \\\\\n\t&= d\n\\end{align}\n".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_equations() {
+        assert_eq!(split_equations("a"), vec!["a"]);
+        assert_eq!(split_equations("f_{x=1} = 2"), vec!["f_{x=1} &= 2"]);
+    }
+}
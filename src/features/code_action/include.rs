@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{INCLUDE_EXTENSION_CODE, NESTED_INCLUDE_CODE, PREAMBLE_INCLUDE_CODE},
+    features::FeatureRequest,
+};
+
+/// Offers to replace an `\include` that is reported by the diagnostics
+/// of the client context with `\input`.
+pub fn find_include_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    request.main_document().data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| {
+            matches!(
+                diagnostic.code,
+                Some(NumberOrString::Number(
+                    PREAMBLE_INCLUDE_CODE | NESTED_INCLUDE_CODE | INCLUDE_EXTENSION_CODE
+                ))
+            )
+        })
+    {
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(diagnostic.range, "\\input".to_string())],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: "Replace \\include with \\input".to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Diagnostic, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_include_extension() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\include{chapter.tex}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build()
+            .code_action();
+
+        let range = Range::new_simple(0, 0, 0, 8);
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(INCLUDE_EXTENSION_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "\\include appends the .tex extension".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_include_actions(&request, CancellationToken::none(), &mut actions);
+        let edit = match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit,
+            _ => None,
+        };
+
+        let uri = request.params.text_document.uri.clone();
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, "\\input".into())]);
+        assert_eq!(edit, Some(WorkspaceEdit::new(expected_changes)));
+    }
+}
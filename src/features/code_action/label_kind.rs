@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{diagnostics::LABEL_KIND_CODE, features::FeatureRequest, LineIndexExt};
+
+/// Offers to replace the word in front of a reference with the kind of the referenced label
+/// that is reported by the diagnostics of the client context.
+pub fn find_label_kind_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| diagnostic.code == Some(NumberOrString::Number(LABEL_KIND_CODE)))
+    {
+        let new_text = match diagnostic.data.as_ref().and_then(|data| data.as_str()) {
+            Some(new_text) => new_text,
+            None => continue,
+        };
+
+        let range = main_document.line_index.offset_lsp_range(diagnostic.range);
+        let word = match main_document
+            .text
+            .get(std::ops::Range::<usize>::from(range))
+        {
+            Some(word) => word,
+            None => continue,
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(diagnostic.range, new_text.to_string())],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Replace \"{}\" with \"{}\"", word, new_text),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{diagnostics::DiagnosticsManager, features::testing::FeatureTester, Options};
+
+    use super::*;
+
+    #[test]
+    fn test_figure() {
+        let tester = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\begin{figure}\\caption{Foo}\\label{fig:foo}\\end{figure}\nsee Tab.~\\ref{fig:foo}",
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(5)
+            .build();
+
+        let uri = tester.uri("main.tex");
+        let mut request = tester.code_action();
+        let mut manager = DiagnosticsManager::default();
+        manager.update_static(
+            request.workspace.as_ref(),
+            Arc::clone(&uri),
+            &Options::default(),
+        );
+        request.params.context.diagnostics = manager.publish(request.main_document());
+
+        let mut actions = Vec::new();
+        find_label_kind_actions(&request, CancellationToken::none(), &mut actions);
+        let titles: Vec<_> = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action.title),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect();
+
+        assert_eq!(titles, vec!["Replace \"Tab.\" with \"Figure\""]);
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{diagnostics::LANGUAGE_TOOL_SOURCE, features::FeatureRequest};
+
+/// Offers the replacements of the LanguageTool matches
+/// that are reported by the diagnostics of the client context.
+pub fn find_language_tool_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some(LANGUAGE_TOOL_SOURCE))
+    {
+        let replacements = diagnostic
+            .data
+            .as_ref()
+            .and_then(|data| data.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|replacement| replacement.as_str());
+
+        for (i, new_text) in replacements.enumerate() {
+            let mut changes = HashMap::new();
+            changes.insert(
+                request.params.text_document.uri.clone(),
+                vec![TextEdit::new(diagnostic.range, new_text.to_string())],
+            );
+
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Replace with \"{}\"", new_text),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                is_preferred: Some(i == 0),
+                ..CodeAction::default()
+            }));
+        }
+    }
+
+    Some(())
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{diagnostics::LINT_RULE_CODE, features::FeatureRequest};
+
+/// Offers to apply the replacement of the user-defined lint rules
+/// that are reported by the diagnostics of the client context.
+pub fn find_lint_rule_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| diagnostic.code == Some(NumberOrString::Number(LINT_RULE_CODE)))
+    {
+        let new_text = match diagnostic.data.as_ref().and_then(|data| data.as_str()) {
+            Some(new_text) => new_text,
+            None => continue,
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(diagnostic.range, new_text.to_string())],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Replace with \"{}\"", new_text),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Diagnostic, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    fn find_edits(data: Option<serde_json::Value>) -> Option<Vec<TextEdit>> {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", "{\\bf foo}")])
+            .main("main.tex")
+            .line(0)
+            .character(1)
+            .build()
+            .code_action();
+
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(LINT_RULE_CODE)),
+            source: Some("texlab".to_string()),
+            data,
+            ..Diagnostic::new_simple(Range::new_simple(0, 1, 0, 4), "Use \\bfseries".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_lint_rule_actions(&request, CancellationToken::none(), &mut actions);
+        let uri = request.params.text_document.uri.clone();
+        match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit?.changes?.remove(&uri),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_replacement() {
+        assert_eq!(
+            find_edits(Some(serde_json::Value::String("\\bfseries".into()))),
+            Some(vec![TextEdit::new(
+                Range::new_simple(0, 1, 0, 4),
+                "\\bfseries".into()
+            )])
+        );
+    }
+
+    #[test]
+    fn test_without_replacement() {
+        assert_eq!(find_edits(None), None);
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{find_math_context, MathContext, INLINE_LABEL_CODE, UNNUMBERED_LABEL_CODE},
+    features::FeatureRequest,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+/// Offers to switch a formula with a `\label` that is reported by the diagnostics
+/// of the client context to the corresponding numbered environment.
+pub fn find_math_label_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| {
+            matches!(
+                diagnostic.code,
+                Some(NumberOrString::Number(
+                    UNNUMBERED_LABEL_CODE | INLINE_LABEL_CODE
+                ))
+            )
+        })
+    {
+        let range = main_document.line_index.offset_lsp_range(diagnostic.range);
+        let label = match data
+            .root
+            .descendants()
+            .filter_map(latex::LabelDefinition::cast)
+            .find(|label| label.small_range() == range)
+        {
+            Some(label) => label,
+            None => continue,
+        };
+
+        let (name, edits) = match find_math_context(label.syntax()).and_then(make_numbered) {
+            Some(result) => result,
+            None => continue,
+        };
+
+        let edits = edits
+            .into_iter()
+            .map(|(range, new_text)| {
+                TextEdit::new(main_document.line_index.line_col_lsp_range(range), new_text)
+            })
+            .collect();
+
+        let mut changes = HashMap::new();
+        changes.insert(request.params.text_document.uri.clone(), edits);
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Change to numbered {} environment", name),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+fn make_numbered(context: MathContext) -> Option<(String, Vec<(TextRange, String)>)> {
+    let node = match context {
+        MathContext::Numbered(_) => return None,
+        MathContext::Unnumbered(node) | MathContext::Inline(node) => node,
+    };
+
+    if let Some(environment) = latex::Environment::cast(node) {
+        let begin_name = environment.begin()?.name()?.key()?;
+        let end_name = environment.end()?.name()?.key()?;
+        let name = match begin_name.to_string().as_str() {
+            "math" | "displaymath" => "equation".to_string(),
+            name => name.trim_end_matches('*').to_string(),
+        };
+
+        let edits = vec![
+            (begin_name.small_range(), name.clone()),
+            (end_name.small_range(), name.clone()),
+        ];
+        Some((name, edits))
+    } else {
+        let first = node.first_token()?;
+        let last = node.last_token()?;
+        if first.text_range() == last.text_range()
+            || !matches!(last.kind(), latex::DOLLAR | latex::END_EQUATION_NAME)
+        {
+            return None;
+        }
+
+        let edits = vec![
+            (first.text_range(), "\\begin{equation}".to_string()),
+            (last.text_range(), "\\end{equation}".to_string()),
+        ];
+        Some(("equation".to_string(), edits))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Diagnostic, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    fn find_edits(text: &str, code: i32, range: Range) -> Option<Vec<TextEdit>> {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(range.start.line)
+            .character(range.start.character)
+            .build()
+            .code_action();
+
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(code)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "label".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_math_label_actions(&request, CancellationToken::none(), &mut actions);
+        let uri = request.params.text_document.uri.clone();
+        match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit?.changes?.remove(&uri),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_starred_environment() {
+        let edits = find_edits(
+            "\\begin{align*}\nx \\label{eq:foo}\n\\end{align*}",
+            UNNUMBERED_LABEL_CODE,
+            Range::new_simple(1, 2, 1, 16),
+        );
+
+        assert_eq!(
+            edits,
+            Some(vec![
+                TextEdit::new(Range::new_simple(0, 7, 0, 13), "align".into()),
+                TextEdit::new(Range::new_simple(2, 5, 2, 11), "align".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display_math() {
+        let edits = find_edits(
+            "\\[x \\label{eq:foo}\\]",
+            UNNUMBERED_LABEL_CODE,
+            Range::new_simple(0, 4, 0, 18),
+        );
+
+        assert_eq!(
+            edits,
+            Some(vec![
+                TextEdit::new(Range::new_simple(0, 0, 0, 2), "\\begin{equation}".into()),
+                TextEdit::new(Range::new_simple(0, 18, 0, 20), "\\end{equation}".into()),
+            ])
+        );
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{find_package_insert_position, MISSING_PACKAGE_CODE},
+    features::FeatureRequest,
+    LineIndexExt,
+};
+
+/// Offers to load the package of a command that is reported by the diagnostics
+/// of the client context in the preamble of the root document.
+pub fn find_package_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let root_document = request.subset.documents.iter().find(|document| {
+        document
+            .data
+            .as_latex()
+            .map_or(false, |data| data.extras.has_document_environment)
+    })?;
+
+    let offset = find_package_insert_position(&root_document.data.as_latex()?.root)?;
+    let position = root_document.line_index.line_col_lsp(offset);
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| diagnostic.code == Some(NumberOrString::Number(MISSING_PACKAGE_CODE)))
+    {
+        let package = match diagnostic.data.as_ref().and_then(|data| data.as_str()) {
+            Some(package) => package,
+            None => continue,
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            root_document.uri.as_ref().clone().into(),
+            vec![TextEdit::new(
+                lsp_types::Range::new(position, position),
+                format!("\n\\usepackage{{{}}}", package),
+            )],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Load the {} package", package),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::{Diagnostic, Position, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_insert_after_last_package() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \usepackage{amsmath}
+                        \begin{document}
+                        \input{chapter}
+                        \end{document}"#},
+                ),
+                ("chapter.tex", r#"\includegraphics{foo}"#),
+            ])
+            .main("chapter.tex")
+            .build();
+
+        let uri = tester.uri("main.tex");
+        let mut request = tester.code_action();
+
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(MISSING_PACKAGE_CODE)),
+            source: Some("texlab".to_string()),
+            data: Some(serde_json::Value::String("graphicx".into())),
+            ..Diagnostic::new_simple(
+                Range::new_simple(0, 0, 0, 16),
+                "\\includegraphics requires the graphicx package".into(),
+            )
+        }];
+
+        let mut actions = Vec::new();
+        find_package_actions(&request, CancellationToken::none(), &mut actions);
+
+        let edit = match &actions[0] {
+            CodeActionOrCommand::CodeAction(action) => action.edit.clone().unwrap(),
+            CodeActionOrCommand::Command(_) => unreachable!(),
+        };
+
+        assert_eq!(
+            edit.changes.unwrap()[&uri.as_ref().clone().into()],
+            vec![TextEdit::new(
+                Range::new(Position::new(1, 20), Position::new(1, 20)),
+                "\n\\usepackage{graphicx}".into()
+            )]
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, TextEdit, WorkspaceEdit,
+};
+
+use crate::{features::FeatureRequest, find_quantities, is_siunitx_loaded, LineIndexExt};
+
+/// Offers to typeset a quantity like `5 cm` with `\SI` or `\qty`
+/// if the document uses the `siunitx` package.
+pub fn find_quantity_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    if !is_siunitx_loaded(&request.subset.documents) {
+        return None;
+    }
+
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    cancellation_token.result().ok()?;
+    let quantity = find_quantities(&data.root, &main_document.text)
+        .into_iter()
+        .find(|quantity| quantity.range.intersect(range).is_some())?;
+
+    for new_text in [quantity.to_qty(), quantity.to_si()] {
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(
+                main_document.line_index.line_col_lsp_range(quantity.range),
+                new_text.clone(),
+            )],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: format!("Replace with {}", new_text),
+            kind: Some(CodeActionKind::QUICKFIX),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn find_titles(text: &str) -> Vec<String> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(0)
+            .character(26)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_quantity_actions(&request, CancellationToken::none(), &mut actions);
+        actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action.title),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_siunitx() {
+        assert_eq!(
+            find_titles(r#"\usepackage{siunitx} Foo 5 cm"#),
+            vec![
+                "Replace with \\qty{5}{\\cm}",
+                "Replace with \\SI{5}{\\centi\\meter}"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_without_siunitx() {
+        assert!(find_titles(r#"\usepackage{amsmath} Foo 5 cm"#).is_empty());
+    }
+}
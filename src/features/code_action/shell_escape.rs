@@ -0,0 +1,116 @@
+use cancellation::CancellationToken;
+use lsp_types::{CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Command};
+
+use crate::{
+    features::{
+        find_disabled_shell_escape, EnableShellEscapeParams, FeatureRequest,
+        ENABLE_SHELL_ESCAPE_COMMAND,
+    },
+    syntax::latex::ExplicitLinkKind,
+    LineIndexExt,
+};
+
+/// Offers to enable shell escape in the `.latexmkrc` file of the project
+/// if the selection contains a package that requires it.
+pub fn find_shell_escape_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let link = data.extras.explicit_links.iter().find(|link| {
+        link.kind == ExplicitLinkKind::Package && link.stem_range.intersect(range).is_some()
+    })?;
+
+    cancellation_token.result().ok()?;
+    let (_, packages) = find_disabled_shell_escape(request)?;
+    if !packages.iter().any(|package| package == &link.stem) {
+        return None;
+    }
+
+    let params = EnableShellEscapeParams {
+        text_document: request.params.text_document.clone(),
+    };
+
+    let title = "Enable shell escape in .latexmkrc".to_string();
+    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        command: Some(Command::new(
+            title,
+            ENABLE_SHELL_ESCAPE_COMMAND.to_string(),
+            Some(vec![serde_json::to_value(params).ok()?]),
+        )),
+        ..CodeAction::default()
+    }));
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    const MAIN: &str = indoc! {r#"
+        \documentclass{article}
+        \usepackage{minted}
+        \begin{document}
+        \end{document}"#};
+
+    #[test]
+    fn test_disabled() {
+        let directory = tempfile::tempdir().unwrap();
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", MAIN)])
+            .main("main.tex")
+            .current_directory(directory.path().to_path_buf())
+            .line(1)
+            .character(14)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_shell_escape_actions(&request, CancellationToken::none(), &mut actions);
+
+        let command = match &actions[0] {
+            CodeActionOrCommand::CodeAction(action) => action.command.clone().unwrap(),
+            CodeActionOrCommand::Command(_) => unreachable!(),
+        };
+
+        assert_eq!(command.command, ENABLE_SHELL_ESCAPE_COMMAND);
+    }
+
+    #[test]
+    fn test_enabled() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(
+            directory.path().join(".latexmkrc"),
+            "$pdflatex = 'pdflatex -shell-escape %O %S';\n",
+        )
+        .unwrap();
+
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", MAIN)])
+            .main("main.tex")
+            .current_directory(directory.path().to_path_buf())
+            .line(1)
+            .character(14)
+            .build()
+            .code_action();
+
+        let mut actions = Vec::new();
+        find_shell_escape_actions(&request, CancellationToken::none(), &mut actions);
+        assert!(actions.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{FOOTNOTE_TIE_CODE, MISSING_TIE_CODE},
+    features::FeatureRequest,
+};
+
+/// Offers to insert or remove the non-breaking space
+/// that is reported by the diagnostics of the client context.
+pub fn find_tie_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    request.main_document().data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+    {
+        let (title, new_text) = match diagnostic.code {
+            Some(NumberOrString::Number(MISSING_TIE_CODE)) => ("Insert non-breaking space", "~"),
+            Some(NumberOrString::Number(FOOTNOTE_TIE_CODE)) => ("Remove non-breaking space", ""),
+            _ => continue,
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(diagnostic.range, new_text.to_string())],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Diagnostic, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_missing_tie() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"Figure \ref{fig:foo}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(7)
+            .build()
+            .code_action();
+
+        let range = Range::new_simple(0, 6, 0, 7);
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(MISSING_TIE_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "Use a non-breaking space (~) before \\ref".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_tie_actions(&request, CancellationToken::none(), &mut actions);
+        let edit = match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit,
+            _ => None,
+        };
+
+        let uri = request.params.text_document.uri.clone();
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, "~".into())]);
+        assert_eq!(edit, Some(WorkspaceEdit::new(expected_changes)));
+    }
+
+    #[test]
+    fn test_footnote_tie() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"foo~\footcite{bar}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build()
+            .code_action();
+
+        let range = Range::new_simple(0, 3, 0, 4);
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(FOOTNOTE_TIE_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "footnote".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_tie_actions(&request, CancellationToken::none(), &mut actions);
+        let edit = match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit,
+            _ => None,
+        };
+
+        let uri = request.params.text_document.uri.clone();
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, String::new())]);
+        assert_eq!(edit, Some(WorkspaceEdit::new(expected_changes)));
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{suspicious_char_replacement, HOMOGLYPH_CODE, INVISIBLE_CHARACTER_CODE},
+    features::FeatureRequest,
+    LineIndexExt,
+};
+
+/// Offers to remove or replace the invisible characters and homoglyphs
+/// that are reported by the diagnostics of the client context.
+pub fn find_unicode_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    cancellation_token.result().ok()?;
+
+    for diagnostic in request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| {
+            matches!(
+                diagnostic.code,
+                Some(NumberOrString::Number(
+                    INVISIBLE_CHARACTER_CODE | HOMOGLYPH_CODE
+                ))
+            )
+        })
+    {
+        let range = main_document.line_index.offset_lsp_range(diagnostic.range);
+        let c = match main_document
+            .text
+            .get(std::ops::Range::<usize>::from(range))
+            .and_then(|text| text.chars().next())
+        {
+            Some(c) => c,
+            None => continue,
+        };
+
+        let new_text = match suspicious_char_replacement(c) {
+            Some(new_text) => new_text,
+            None => continue,
+        };
+
+        let title = if new_text.is_empty() {
+            format!("Remove U+{:04X}", c as u32)
+        } else {
+            format!("Replace U+{:04X} with \"{}\"", c as u32, new_text)
+        };
+
+        let mut changes = HashMap::new();
+        changes.insert(
+            request.params.text_document.uri.clone(),
+            vec![TextEdit::new(diagnostic.range, new_text.to_string())],
+        );
+
+        actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+            title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            diagnostics: Some(vec![diagnostic.clone()]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            is_preferred: Some(true),
+            ..CodeAction::default()
+        }));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Diagnostic, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_homoglyph() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\ref{fig:cаt}")])
+            .main("main.tex")
+            .line(0)
+            .character(10)
+            .build()
+            .code_action();
+
+        let range = Range::new_simple(0, 10, 0, 11);
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(HOMOGLYPH_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "homoglyph".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_unicode_actions(&request, CancellationToken::none(), &mut actions);
+        let edit = match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => action.edit,
+            _ => None,
+        };
+
+        let uri = request.params.text_document.uri.clone();
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, "a".into())]);
+        assert_eq!(edit, Some(WorkspaceEdit::new(expected_changes)));
+    }
+
+    #[test]
+    fn test_invisible_character() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", "foo\u{200B}bar")])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build()
+            .code_action();
+
+        let range = Range::new_simple(0, 3, 0, 4);
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(INVISIBLE_CHARACTER_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(range, "invisible".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_unicode_actions(&request, CancellationToken::none(), &mut actions);
+        let (title, edit) = match actions.pop() {
+            Some(CodeActionOrCommand::CodeAction(action)) => (action.title, action.edit),
+            _ => unreachable!(),
+        };
+
+        let uri = request.params.text_document.uri.clone();
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, String::new())]);
+        assert_eq!(title, "Remove U+200B");
+        assert_eq!(edit, Some(WorkspaceEdit::new(expected_changes)));
+    }
+
+    #[test]
+    fn test_other_diagnostic() {
+        let mut request = FeatureTester::builder()
+            .files(vec![("main.tex", "foo bar")])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build()
+            .code_action();
+
+        request.params.context.diagnostics = vec![Diagnostic {
+            code: Some(NumberOrString::Number(INVISIBLE_CHARACTER_CODE)),
+            source: Some("texlab".to_string()),
+            ..Diagnostic::new_simple(Range::new_simple(0, 3, 0, 4), "invisible".into())
+        }];
+
+        let mut actions = Vec::new();
+        find_unicode_actions(&request, CancellationToken::none(), &mut actions);
+        assert!(actions.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, NumberOrString, TextEdit,
+    WorkspaceEdit,
+};
+
+use crate::{
+    diagnostics::{MIXED_LINE_ENDINGS_CODE, TRAILING_WHITESPACE_CODE},
+    features::FeatureRequest,
+    find_whitespace_issues, LineIndexExt,
+};
+
+/// Offers a single action that removes all trailing whitespace of the document
+/// and converts its line endings to the most common one.
+pub fn find_whitespace_actions(
+    request: &FeatureRequest<CodeActionParams>,
+    cancellation_token: &CancellationToken,
+    actions: &mut Vec<CodeActionOrCommand>,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let main_document = request.main_document();
+    let diagnostics: Vec<_> = request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+        .filter(|diagnostic| {
+            diagnostic.code == Some(NumberOrString::Number(TRAILING_WHITESPACE_CODE))
+                || diagnostic.code == Some(NumberOrString::Number(MIXED_LINE_ENDINGS_CODE))
+        })
+        .cloned()
+        .collect();
+
+    if diagnostics.is_empty() {
+        return None;
+    }
+
+    let issues = find_whitespace_issues(&main_document);
+    if issues.is_empty() {
+        return None;
+    }
+
+    let edits = issues
+        .edits()
+        .into_iter()
+        .map(|(range, new_text)| {
+            TextEdit::new(
+                main_document.line_index.line_col_lsp_range(range),
+                new_text.to_string(),
+            )
+        })
+        .collect();
+
+    let mut changes = HashMap::new();
+    changes.insert(request.params.text_document.uri.clone(), edits);
+
+    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+        title: "Fix all whitespace issues".to_string(),
+        kind: Some(CodeActionKind::SOURCE_FIX_ALL),
+        diagnostics: Some(diagnostics),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..CodeAction::default()
+    }));
+
+    Some(())
+}
@@ -0,0 +1,391 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    Color, ColorInformation, ColorPresentation, ColorPresentationParams, DocumentColorParams,
+    TextEdit,
+};
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    LineIndexExt,
+};
+
+use super::FeatureRequest;
+
+const PRESENTATION_MODELS: &[&str] = &["rgb", "RGB", "HTML"];
+
+pub fn find_document_colors(
+    request: FeatureRequest<DocumentColorParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<ColorInformation>> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let mut colors = Vec::new();
+    for spec in data.root.descendants().filter_map(find_color_spec) {
+        cancellation_token.result().ok()?;
+        if let Some(color) = parse_color(&spec.model, &spec.value) {
+            colors.push(ColorInformation {
+                range: main_document
+                    .line_index
+                    .line_col_lsp_range(spec.value_range),
+                color,
+            });
+        }
+    }
+    Some(colors)
+}
+
+pub fn find_color_presentations(
+    request: FeatureRequest<ColorPresentationParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<ColorPresentation>> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let spec = data
+        .root
+        .descendants()
+        .filter_map(find_color_spec)
+        .find(|spec| spec.value_range == range)?;
+
+    cancellation_token.result().ok()?;
+
+    let color = request.params.color;
+    let mut presentations = Vec::new();
+    let models = std::iter::once(spec.model.as_str()).chain(
+        PRESENTATION_MODELS
+            .iter()
+            .copied()
+            .filter(|model| *model != spec.model),
+    );
+
+    for model in models {
+        if let Some(value) = format_color(model, color) {
+            let additional_text_edits = if model == spec.model {
+                None
+            } else {
+                Some(vec![TextEdit::new(
+                    main_document
+                        .line_index
+                        .line_col_lsp_range(spec.model_range),
+                    model.to_string(),
+                )])
+            };
+
+            presentations.push(ColorPresentation {
+                label: format!("[{}]{{{}}}", model, value),
+                text_edit: Some(TextEdit::new(request.params.range, value)),
+                additional_text_edits,
+            });
+        }
+    }
+
+    Some(presentations)
+}
+
+#[derive(Debug)]
+struct ColorSpec {
+    model: String,
+    model_range: TextRange,
+    value: String,
+    value_range: TextRange,
+}
+
+fn find_color_spec(node: &latex::SyntaxNode) -> Option<ColorSpec> {
+    let (model, spec) = if let Some(definition) = latex::ColorDefinition::cast(node) {
+        (definition.model()?.key()?, definition.spec()?)
+    } else if let Some(reference) = latex::ColorReference::cast(node) {
+        (reference.model()?.key()?, reference.spec()?)
+    } else {
+        return None;
+    };
+
+    let value_range = TextRange::new(
+        spec.left_curly()?.text_range().end(),
+        spec.right_curly()?.text_range().start(),
+    );
+
+    Some(ColorSpec {
+        model: model.to_string(),
+        model_range: model.small_range(),
+        value: spec.content_text()?,
+        value_range,
+    })
+}
+
+fn parse_color(model: &str, value: &str) -> Option<Color> {
+    let components = || -> Option<Vec<f32>> {
+        value
+            .split(',')
+            .map(|component| component.trim().parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>()
+            .filter(|components| {
+                components
+                    .iter()
+                    .all(|component| (0.0..=1.0).contains(component))
+            })
+    };
+
+    let (red, green, blue) = match model {
+        "rgb" => match components()?.as_slice() {
+            [r, g, b] => (*r, *g, *b),
+            _ => return None,
+        },
+        "RGB" => {
+            let components = value
+                .split(',')
+                .map(|component| component.trim().parse::<u8>().ok())
+                .collect::<Option<Vec<_>>>()?;
+
+            match components.as_slice() {
+                [r, g, b] => (
+                    f32::from(*r) / 255.0,
+                    f32::from(*g) / 255.0,
+                    f32::from(*b) / 255.0,
+                ),
+                _ => return None,
+            }
+        }
+        "HTML" => {
+            let value = value.trim();
+            if value.len() != 6 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+
+            let channel = |i: usize| u8::from_str_radix(&value[i..i + 2], 16).ok();
+            (
+                f32::from(channel(0)?) / 255.0,
+                f32::from(channel(2)?) / 255.0,
+                f32::from(channel(4)?) / 255.0,
+            )
+        }
+        "gray" => match components()?.as_slice() {
+            [gray] => (*gray, *gray, *gray),
+            _ => return None,
+        },
+        "cmy" => match components()?.as_slice() {
+            [c, m, y] => (1.0 - c, 1.0 - m, 1.0 - y),
+            _ => return None,
+        },
+        "cmyk" => match components()?.as_slice() {
+            [c, m, y, k] => (
+                (1.0 - c) * (1.0 - k),
+                (1.0 - m) * (1.0 - k),
+                (1.0 - y) * (1.0 - k),
+            ),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(Color {
+        red,
+        green,
+        blue,
+        alpha: 1.0,
+    })
+}
+
+fn format_color(model: &str, color: Color) -> Option<String> {
+    let Color {
+        red, green, blue, ..
+    } = color;
+
+    let value = match model {
+        "rgb" => format_components(&[red, green, blue]),
+        "RGB" => [red, green, blue]
+            .iter()
+            .map(|component| to_byte(*component).to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+        "HTML" => format!(
+            "{:02X}{:02X}{:02X}",
+            to_byte(red),
+            to_byte(green),
+            to_byte(blue)
+        ),
+        "gray" if to_byte(red) == to_byte(green) && to_byte(green) == to_byte(blue) => {
+            format_components(&[red])
+        }
+        "cmy" => format_components(&[1.0 - red, 1.0 - green, 1.0 - blue]),
+        "cmyk" => {
+            let k = 1.0 - red.max(green).max(blue);
+            if k >= 1.0 {
+                format_components(&[0.0, 0.0, 0.0, 1.0])
+            } else {
+                format_components(&[
+                    (1.0 - red - k) / (1.0 - k),
+                    (1.0 - green - k) / (1.0 - k),
+                    (1.0 - blue - k) / (1.0 - k),
+                    k,
+                ])
+            }
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+fn to_byte(component: f32) -> u8 {
+    (component.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn format_components(components: &[f32]) -> String {
+    components
+        .iter()
+        .map(|component| {
+            let text = format!("{:.3}", component.clamp(0.0, 1.0));
+            text.trim_end_matches('0').trim_end_matches('.').to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    fn color(red: f32, green: f32, blue: f32) -> Color {
+        Color {
+            red,
+            green,
+            blue,
+            alpha: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_empty_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .build()
+            .document_color();
+
+        let actual = find_document_colors(request, CancellationToken::none()).unwrap();
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_bibtex() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.bib", "")])
+            .main("main.bib")
+            .build()
+            .document_color();
+
+        let actual = find_document_colors(request, CancellationToken::none());
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_definition_html() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\definecolor{foo}{HTML}{FF0000}"#)])
+            .main("main.tex")
+            .build()
+            .document_color();
+
+        let actual = find_document_colors(request, CancellationToken::none()).unwrap();
+        let expected = vec![ColorInformation {
+            range: Range::new_simple(0, 24, 0, 30),
+            color: color(1.0, 0.0, 0.0),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_reference_rgb() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\color[rgb]{0,0.5,1} \color{red}"#)])
+            .main("main.tex")
+            .build()
+            .document_color();
+
+        let actual = find_document_colors(request, CancellationToken::none()).unwrap();
+        let expected = vec![ColorInformation {
+            range: Range::new_simple(0, 12, 0, 19),
+            color: color(0.0, 0.5, 1.0),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_invalid_value() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\definecolor{foo}{rgb}{255,168,0}"#)])
+            .main("main.tex")
+            .build()
+            .document_color();
+
+        let actual = find_document_colors(request, CancellationToken::none()).unwrap();
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_presentation() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\definecolor{foo}{RGB}{255,0,0}"#)])
+            .main("main.tex")
+            .build()
+            .color_presentation(Range::new_simple(0, 23, 0, 30), color(0.0, 0.0, 1.0));
+
+        let actual = find_color_presentations(request, CancellationToken::none()).unwrap();
+        let expected = vec![
+            ColorPresentation {
+                label: "[RGB]{0,0,255}".into(),
+                text_edit: Some(TextEdit::new(
+                    Range::new_simple(0, 23, 0, 30),
+                    "0,0,255".into(),
+                )),
+                additional_text_edits: None,
+            },
+            ColorPresentation {
+                label: "[rgb]{0,0,1}".into(),
+                text_edit: Some(TextEdit::new(
+                    Range::new_simple(0, 23, 0, 30),
+                    "0,0,1".into(),
+                )),
+                additional_text_edits: Some(vec![TextEdit::new(
+                    Range::new_simple(0, 18, 0, 21),
+                    "rgb".into(),
+                )]),
+            },
+            ColorPresentation {
+                label: "[HTML]{0000FF}".into(),
+                text_edit: Some(TextEdit::new(
+                    Range::new_simple(0, 23, 0, 30),
+                    "0000FF".into(),
+                )),
+                additional_text_edits: Some(vec![TextEdit::new(
+                    Range::new_simple(0, 18, 0, 21),
+                    "HTML".into(),
+                )]),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_parse_cmyk() {
+        assert_eq!(parse_color("cmyk", "0,1,1,0.5"), Some(color(0.5, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_format_components() {
+        assert_eq!(
+            format_components(&[0.0, 0.5, 1.0, 1.0 / 3.0]),
+            "0,0.5,1,0.333"
+        );
+    }
+}
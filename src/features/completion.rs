@@ -1,17 +1,23 @@
 mod acronym_ref;
 mod argument;
 mod begin_command;
+mod biblatex;
 mod citation;
+mod class_option;
 mod color;
 mod color_model;
 mod component_command;
 mod component_environment;
+mod counter;
+mod crossref;
 mod entry_type;
 mod field;
 mod glossary_ref;
 mod import;
 mod include;
+mod index_entry;
 mod label;
+mod style;
 mod theorem;
 mod tikz_library;
 mod types;
@@ -22,16 +28,17 @@ mod util;
 use std::borrow::Cow;
 
 use cancellation::CancellationToken;
-use cstree::TextSize;
+use cstree::{TextRange, TextSize};
 use fuzzy_matcher::{skim::SkimMatcherV2, FuzzyMatcher};
 use itertools::Itertools;
 use lsp_types::{
     CompletionItem, CompletionList, CompletionParams, CompletionTextEdit, Documentation,
-    InsertTextFormat, MarkupContent, MarkupKind, TextEdit,
+    InsertReplaceEdit, InsertTextFormat, MarkupContent, MarkupKind, TextEdit,
 };
 use rustc_hash::FxHashSet;
 
 use crate::{
+    indentation,
     syntax::{bibtex, latex, CstNode},
     LineIndexExt,
 };
@@ -40,17 +47,23 @@ use self::{
     acronym_ref::complete_acronyms,
     argument::complete_arguments,
     begin_command::complete_begin_command,
-    citation::complete_citations,
+    biblatex::complete_biblatex_names,
+    citation::{complete_citations, complete_markdown_citations},
+    class_option::complete_class_options,
     color::complete_colors,
     color_model::complete_color_models,
     component_command::complete_component_commands,
     component_environment::complete_component_environments,
+    counter::complete_counters,
+    crossref::complete_crossrefs,
     entry_type::complete_entry_types,
     field::complete_fields,
     glossary_ref::complete_glossary_entries,
     import::complete_imports,
     include::complete_includes,
+    index_entry::complete_index_entries,
     label::complete_labels,
+    style::complete_styles,
     theorem::complete_theorem_environments,
     tikz_library::complete_tikz_libraries,
     types::{InternalCompletionItem, InternalCompletionItemData},
@@ -78,13 +91,20 @@ pub fn complete(
     log::debug!("[Completion] Cursor: {:?}", context.cursor);
     complete_entry_types(&context, &mut items, cancellation_token);
     complete_fields(&context, &mut items, cancellation_token);
+    complete_crossrefs(&context, &mut items, cancellation_token);
     complete_arguments(&context, &mut items, cancellation_token);
     complete_citations(&context, &mut items, cancellation_token);
+    complete_markdown_citations(&context, &mut items, cancellation_token);
     complete_imports(&context, &mut items, cancellation_token);
+    complete_class_options(&context, &mut items, cancellation_token);
+    complete_styles(&context, &mut items, cancellation_token);
+    complete_biblatex_names(&context, &mut items, cancellation_token);
     complete_colors(&context, &mut items, cancellation_token);
     complete_color_models(&context, &mut items, cancellation_token);
     complete_acronyms(&context, &mut items, cancellation_token);
     complete_glossary_entries(&context, &mut items, cancellation_token);
+    complete_counters(&context, &mut items, cancellation_token);
+    complete_index_entries(&context, &mut items, cancellation_token);
     complete_includes(&context, &mut items, cancellation_token);
     complete_labels(&context, &mut items, cancellation_token);
     complete_tikz_libraries(&context, &mut items, cancellation_token);
@@ -101,11 +121,21 @@ pub fn complete(
     preselect(&context, &mut items);
     score(&context, &mut items);
 
+    let limit = context
+        .request
+        .context
+        .options
+        .read()
+        .unwrap()
+        .completion_limit
+        .unwrap_or(COMPLETION_LIMIT);
+
     items.sort_by_key(|item| (!item.preselect, -item.score.unwrap_or(std::i64::MIN + 1)));
+    let match_count = items.iter().filter(|item| item.score.is_some()).count();
     let items: Vec<_> = items
         .into_iter()
-        .take(COMPLETION_LIMIT)
         .filter(|item| item.score.is_some())
+        .take(limit)
         .map(|item| convert_internal_items(&context, item))
         .enumerate()
         .map(|(i, item)| append_sort_text(item, i))
@@ -124,7 +154,7 @@ pub fn complete(
     {
         true
     } else {
-        items.len() >= COMPLETION_LIMIT
+        match_count > limit
     };
 
     Some(CompletionList {
@@ -183,7 +213,15 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
             token.text().trim_end().into()
         }
         Cursor::Bibtex(_) => "".into(),
-        Cursor::Nothing => "".into(),
+        Cursor::Nothing => match context.find_markdown_citation_key() {
+            Some((_, range)) => {
+                let text = &context.request.main_document().text;
+                text[usize::from(range.start())..usize::from(context.offset)]
+                    .to_string()
+                    .into()
+            }
+            None => "".into(),
+        },
     };
 
     let file_pattern = pattern.split('/').last().unwrap();
@@ -191,7 +229,7 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
     for item in items {
         item.score = match &item.data {
             InternalCompletionItemData::EntryType { ty } => {
-                matcher.fuzzy_match(&ty.name, &pattern[1..])
+                matcher.fuzzy_match(&ty.name, pattern.trim_start_matches('@'))
             }
             InternalCompletionItemData::Field { field } => {
                 matcher.fuzzy_match(&field.name, &pattern)
@@ -211,8 +249,10 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
             InternalCompletionItemData::Package { name } => matcher.fuzzy_match(&name, &pattern),
             InternalCompletionItemData::Color { name } => matcher.fuzzy_match(&name, &pattern),
             InternalCompletionItemData::ColorModel { name } => matcher.fuzzy_match(&name, &pattern),
-            InternalCompletionItemData::Acronym { name } => matcher.fuzzy_match(&name, &pattern),
-            InternalCompletionItemData::GlossaryEntry { name } => {
+            InternalCompletionItemData::Acronym { name, .. } => {
+                matcher.fuzzy_match(&name, &pattern)
+            }
+            InternalCompletionItemData::GlossaryEntry { name, .. } => {
                 matcher.fuzzy_match(&name, &pattern)
             }
             InternalCompletionItemData::File { name } => matcher.fuzzy_match(&name, file_pattern),
@@ -230,6 +270,13 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
             InternalCompletionItemData::TikzLibrary { name } => {
                 matcher.fuzzy_match(&name, &pattern)
             }
+            InternalCompletionItemData::Counter { name } => matcher.fuzzy_match(&name, &pattern),
+            InternalCompletionItemData::IndexEntry { name } => matcher.fuzzy_match(&name, &pattern),
+            InternalCompletionItemData::Style { name, .. } => matcher.fuzzy_match(&name, &pattern),
+            InternalCompletionItemData::ClassOption { name, .. } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
+            InternalCompletionItemData::BibMacro { name } => matcher.fuzzy_match(&name, &pattern),
         };
     }
 }
@@ -262,6 +309,13 @@ fn convert_internal_items(
         .line_index
         .line_col_lsp_range(item.range);
 
+    let is_command = matches!(
+        item.data,
+        InternalCompletionItemData::BeginCommand
+            | InternalCompletionItemData::ComponentCommand { .. }
+            | InternalCompletionItemData::UserCommand { .. }
+    );
+
     let mut new_item = match item.data {
         InternalCompletionItemData::EntryType { ty } => {
             let text_edit = TextEdit::new(range, (&ty.name).into());
@@ -332,7 +386,10 @@ fn convert_internal_items(
                         Structure::Snippet.completion_kind(),
                     )),
                     data: Some(serde_json::to_value(CompletionItemData::CommandSnippet).unwrap()),
-                    insert_text: Some("begin{$1}\n\t$0\n\\end{$1}".into()),
+                    insert_text: Some(format!(
+                        "begin{{$1}}\n{}$0\n\\end{{$1}}",
+                        indentation::unit(context.request.main_document())
+                    )),
                     insert_text_format: Some(InsertTextFormat::Snippet),
                     ..CompletionItem::new_simple("begin".into(), component_detail(&[]))
                 }
@@ -354,8 +411,7 @@ fn convert_internal_items(
             CompletionItem {
                 label: key.to_string(),
                 kind: Some(adjust_kind(&context.request, ty.completion_kind())),
-                filter_text: Some(text.clone()),
-                sort_text: Some(text),
+                filter_text: Some(text),
                 data: Some(
                     serde_json::to_value(CompletionItemData::Citation {
                         uri: uri.as_ref().clone(),
@@ -455,7 +511,7 @@ fn convert_internal_items(
                 ..CompletionItem::default()
             }
         }
-        InternalCompletionItemData::Acronym { name } => {
+        InternalCompletionItemData::Acronym { name, category } => {
             let text_edit = TextEdit::new(range, name.to_string());
             CompletionItem {
                 label: name.into(),
@@ -463,12 +519,13 @@ fn convert_internal_items(
                     &context.request,
                     Structure::GlossaryEntry.completion_kind(),
                 )),
+                detail: category,
                 data: Some(serde_json::to_value(CompletionItemData::Acronym).unwrap()),
                 text_edit: Some(CompletionTextEdit::Edit(text_edit)),
                 ..CompletionItem::default()
             }
         }
-        InternalCompletionItemData::GlossaryEntry { name } => {
+        InternalCompletionItemData::GlossaryEntry { name, category } => {
             let text_edit = TextEdit::new(range, name.to_string());
             CompletionItem {
                 label: name.into(),
@@ -476,6 +533,7 @@ fn convert_internal_items(
                     &context.request,
                     Structure::GlossaryEntry.completion_kind(),
                 )),
+                detail: category,
                 data: Some(serde_json::to_value(CompletionItemData::GlossaryEntry).unwrap()),
                 text_edit: Some(CompletionTextEdit::Edit(text_edit)),
                 ..CompletionItem::default()
@@ -579,11 +637,146 @@ fn convert_internal_items(
                 ..CompletionItem::default()
             }
         }
+        InternalCompletionItemData::Counter { name } => {
+            let text_edit = TextEdit::new(range, name.clone());
+            CompletionItem {
+                label: name,
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Counter.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Counter).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
+        InternalCompletionItemData::IndexEntry { name } => {
+            let text_edit = TextEdit::new(range, name.clone());
+            CompletionItem {
+                label: name,
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::IndexEntry.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::IndexEntry).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
+        InternalCompletionItemData::Style {
+            name,
+            documentation,
+        } => {
+            let text_edit = TextEdit::new(range, name.clone());
+            CompletionItem {
+                label: name,
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                documentation: documentation.map(|doc| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: doc.into(),
+                    })
+                }),
+                data: Some(serde_json::to_value(CompletionItemData::Style).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
+        InternalCompletionItemData::ClassOption {
+            name,
+            documentation,
+        } => {
+            let text_edit = TextEdit::new(range, name.into());
+            CompletionItem {
+                label: name.into(),
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                documentation: Some(Documentation::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: documentation.into(),
+                })),
+                data: Some(serde_json::to_value(CompletionItemData::ClassOption).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
+        InternalCompletionItemData::BibMacro { name } => {
+            let text_edit = TextEdit::new(range, name.clone());
+            CompletionItem {
+                label: name,
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::BibMacro).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
     };
     new_item.preselect = Some(item.preselect);
+    adjust_text_edit(context, item.range, is_command, &mut new_item);
     new_item
 }
 
+/// Makes the text edit of an item replace the whole word that is being completed,
+/// including the backslash of a command and the part of the word behind the cursor.
+/// This way, accepting an item does not depend on how the client determines the current word.
+fn adjust_text_edit(
+    context: &CursorContext<CompletionParams>,
+    mut range: TextRange,
+    is_command: bool,
+    item: &mut CompletionItem,
+) {
+    let mut new_text = match item.text_edit.take() {
+        Some(CompletionTextEdit::Edit(edit)) => edit.new_text,
+        Some(CompletionTextEdit::InsertAndReplace(edit)) => edit.new_text,
+        None => match item.insert_text.take() {
+            Some(insert_text) => insert_text,
+            None => return,
+        },
+    };
+
+    if is_command {
+        range = TextRange::new(range.start() - TextSize::from(1), range.end());
+        new_text = format!("\\{}", new_text);
+        let filter_text = item.filter_text.as_deref().unwrap_or(&item.label);
+        item.filter_text = Some(format!("\\{}", filter_text));
+    }
+
+    let supports_insert_replace = context
+        .request
+        .context
+        .client_capabilities
+        .lock()
+        .unwrap()
+        .text_document
+        .as_ref()
+        .and_then(|cap| cap.completion.as_ref())
+        .and_then(|cap| cap.completion_item.as_ref())
+        .and_then(|cap| cap.insert_replace_support)
+        == Some(true);
+
+    let line_index = &context.request.main_document().line_index;
+    item.text_edit = if supports_insert_replace && range.contains_inclusive(context.offset) {
+        Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+            new_text,
+            insert: line_index.line_col_lsp_range(TextRange::new(range.start(), context.offset)),
+            replace: line_index.line_col_lsp_range(range),
+        }))
+    } else {
+        Some(CompletionTextEdit::Edit(TextEdit::new(
+            line_index.line_col_lsp_range(range),
+            new_text,
+        )))
+    };
+}
+
 fn append_sort_text(mut item: CompletionItem, index: usize) -> CompletionItem {
     let sort_prefix = format!("{:0>2}", index);
     match &item.sort_text {
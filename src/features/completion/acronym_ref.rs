@@ -6,7 +6,10 @@ use crate::{
     syntax::{latex, CstNode},
 };
 
-use super::types::{InternalCompletionItem, InternalCompletionItemData};
+use super::{
+    types::{InternalCompletionItem, InternalCompletionItemData},
+    util::glossary_category,
+};
 
 pub fn complete_acronyms<'a>(
     context: &'a CursorContext<CompletionParams>,
@@ -20,18 +23,24 @@ pub fn complete_acronyms<'a>(
 
     for document in &context.request.subset.documents {
         if let Some(data) = document.data.as_latex() {
-            for name in data
+            for definition in data
                 .root
                 .descendants()
                 .filter_map(latex::AcronymDefinition::cast)
-                .filter_map(|node| node.name())
-                .filter_map(|name| name.key())
-                .map(|name| name.to_string())
             {
-                items.push(InternalCompletionItem::new(
-                    range,
-                    InternalCompletionItemData::Acronym { name },
-                ));
+                if let Some(name) = definition
+                    .name()
+                    .and_then(|name| name.key())
+                    .map(|name| name.to_string())
+                {
+                    items.push(InternalCompletionItem::new(
+                        range,
+                        InternalCompletionItemData::Acronym {
+                            name,
+                            category: glossary_category(definition.syntax()),
+                        },
+                    ));
+                }
             }
         }
     }
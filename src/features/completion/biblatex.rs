@@ -0,0 +1,279 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::CompletionParams;
+use rustc_hash::FxHashSet;
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    LANGUAGE_DATA,
+};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+/// Commands of `biblatex` whose first argument is the name of a field.
+const FIELD_COMMANDS: &[&str] = &[
+    "\\DeclareFieldFormat",
+    "\\DeclareFieldFormat*",
+    "\\DeclareListFormat",
+    "\\DeclareListFormat*",
+    "\\printfield",
+    "\\printlist",
+    "\\printnames",
+    "\\thefield",
+    "\\thelist",
+    "\\clearfield",
+    "\\clearlist",
+    "\\clearname",
+    "\\iffieldundef",
+    "\\iflistundef",
+    "\\ifnameundef",
+];
+
+/// Commands of `biblatex` whose first argument is the name of an entry type.
+const ENTRY_TYPE_COMMANDS: &[&str] = &[
+    "\\DeclareBibliographyDriver",
+    "\\ifentrytype",
+    "\\usedriver",
+];
+
+/// Commands of `biblatex` whose first argument is the name of a bibliography macro.
+const BIB_MACRO_COMMANDS: &[&str] = &[
+    "\\renewbibmacro",
+    "\\renewbibmacro*",
+    "\\usebibmacro",
+    "\\usebibmacro*",
+    "\\savebibmacro",
+    "\\restorebibmacro",
+    "\\ifbibmacroundef",
+];
+
+/// Commands of `biblatex` whose optional argument is a list of entry types.
+const FORMAT_COMMANDS: &[&str] = &[
+    "\\DeclareFieldFormat",
+    "\\DeclareFieldFormat*",
+    "\\DeclareListFormat",
+    "\\DeclareListFormat*",
+    "\\DeclareNameFormat",
+    "\\DeclareNameFormat*",
+];
+
+pub fn complete_biblatex_names<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    if let Some(range) = find_entry_type_option(context) {
+        complete_entry_types(range, items);
+        return Some(());
+    }
+
+    let (_, range, command) = context.find_generic_command_argument()?;
+    let is_first_argument = command
+        .syntax()
+        .children()
+        .filter_map(latex::CurlyGroup::cast)
+        .filter(|group| group.small_range().start() < context.offset)
+        .count()
+        == 1;
+
+    if !is_first_argument {
+        return None;
+    }
+
+    let command_name = command.name()?.text();
+    if FIELD_COMMANDS.contains(&command_name) {
+        for field in &LANGUAGE_DATA.fields {
+            items.push(InternalCompletionItem::new(
+                range,
+                InternalCompletionItemData::Field { field },
+            ));
+        }
+    } else if ENTRY_TYPE_COMMANDS.contains(&command_name) {
+        complete_entry_types(range, items);
+    } else if BIB_MACRO_COMMANDS.contains(&command_name) {
+        let mut names = FxHashSet::default();
+        for name in &LANGUAGE_DATA.bib_macros {
+            names.insert(name.clone());
+            items.push(InternalCompletionItem::new(
+                range,
+                InternalCompletionItemData::BibMacro { name: name.clone() },
+            ));
+        }
+
+        for document in &context.request.subset.documents {
+            if let Some(data) = document.data.as_latex() {
+                for name in data
+                    .root
+                    .descendants()
+                    .filter_map(latex::GenericCommand::cast)
+                    .filter(|command| {
+                        matches!(
+                            command.name().map(|name| name.text()),
+                            Some("\\newbibmacro") | Some("\\newbibmacro*")
+                        )
+                    })
+                    .filter_map(|command| {
+                        command
+                            .syntax()
+                            .children()
+                            .find_map(latex::CurlyGroup::cast)?
+                            .content_text()
+                    })
+                {
+                    cancellation_token.result().ok()?;
+
+                    if names.insert(name.clone()) {
+                        items.push(InternalCompletionItem::new(
+                            range,
+                            InternalCompletionItemData::BibMacro { name },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+fn complete_entry_types(range: TextRange, items: &mut Vec<InternalCompletionItem>) {
+    for ty in LANGUAGE_DATA
+        .entry_types
+        .iter()
+        .filter(|ty| !matches!(ty.name.as_str(), "preamble" | "string" | "comment"))
+    {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::EntryType { ty },
+        ));
+    }
+}
+
+fn find_entry_type_option(context: &CursorContext<CompletionParams>) -> Option<TextRange> {
+    let token = context.cursor.as_latex()?;
+    let group = token
+        .parent()
+        .ancestors()
+        .find_map(latex::MixedGroup::cast)?;
+
+    let is_inside = group.left_delim()?.text_range().end() <= context.offset
+        && group
+            .right_delim()
+            .map_or(true, |delim| context.offset <= delim.text_range().start());
+    if !is_inside {
+        return None;
+    }
+
+    let command = latex::GenericCommand::cast(group.syntax().parent()?)?;
+    if !FORMAT_COMMANDS.contains(&command.name()?.text()) {
+        return None;
+    }
+
+    if token.kind() == latex::WORD {
+        Some(token.text_range())
+    } else {
+        Some(TextRange::empty(context.offset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_biblatex_names(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_field_format() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\DeclareFieldFormat{tit}{\\mkbibemph{#1}}",
+            )])
+            .main("main.tex")
+            .line(0)
+            .character(23)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_biblatex_names(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "title"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(20.into(), 23.into()));
+        }
+    }
+
+    #[test]
+    fn test_field_format_entry_type() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\DeclareFieldFormat[article,b]{title}{#1}",
+            )])
+            .main("main.tex")
+            .line(0)
+            .character(29)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_biblatex_names(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "book"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(28.into(), 29.into()));
+        }
+    }
+
+    #[test]
+    fn test_bib_macro_inside_cite_command() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\newbibmacro*{foo}{}\n\\DeclareCiteCommand{\\cite}{\\usebibmacro{}}{}{}{}",
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(40)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_biblatex_names(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "foo"));
+        assert!(actual_items
+            .iter()
+            .any(|item| item.data.label() == "prenote"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(61.into(), 61.into()));
+        }
+    }
+}
@@ -8,6 +8,7 @@ use regex::Regex;
 
 use crate::{
     features::{cursor::CursorContext, lsp_kinds::Structure},
+    find_bibliographies_in_scope,
     syntax::{
         bibtex::{self, HasType},
         latex, CstNode,
@@ -34,6 +35,40 @@ pub fn complete_citations<'a>(
     };
 
     check_citation(context).or_else(|| check_acronym(context))?;
+    let scope = find_bibliographies_in_scope(
+        &context.request.subset,
+        context.request.main_document(),
+        context.offset,
+    );
+
+    for document in &context.request.subset.documents {
+        if scope
+            .as_ref()
+            .map_or(false, |scope| !scope.contains(&document.uri))
+        {
+            continue;
+        }
+
+        if let Some(data) = document.data.as_bibtex() {
+            for entry in data.root.children().filter_map(bibtex::Entry::cast) {
+                cancellation_token.result().ok()?;
+                if let Some(item) = make_item(document, entry, range) {
+                    items.push(item);
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+pub fn complete_markdown_citations<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+    let (_, range) = context.find_markdown_citation_key()?;
     for document in &context.request.subset.documents {
         if let Some(data) = document.data.as_bibtex() {
             for entry in data.root.children().filter_map(bibtex::Entry::cast) {
@@ -42,6 +77,19 @@ pub fn complete_citations<'a>(
                     items.push(item);
                 }
             }
+        } else if let Some(data) = document.data.as_markdown() {
+            for label in &data.parse.labels {
+                items.push(InternalCompletionItem::new(
+                    range,
+                    InternalCompletionItemData::Label {
+                        name: label.text.clone(),
+                        kind: Structure::Label,
+                        header: None,
+                        footer: None,
+                        text: label.text.clone(),
+                    },
+                ));
+            }
         }
     }
 
@@ -66,7 +114,7 @@ fn check_acronym(context: &CursorContext<CompletionParams>) -> Option<()> {
     Some(())
 }
 
-fn make_item<'a>(
+pub(super) fn make_item<'a>(
     document: &'a Document,
     entry: bibtex::Entry<'a>,
     range: TextRange,
@@ -169,6 +217,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_latex_bib_unit() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    "\\begin{bibunit}\n\\cite{}\n\\putbib[unit]\n\\end{bibunit}\n\\bibliography{main}",
+                ),
+                ("main.bib", "@article{foo,}"),
+                ("unit.bib", "@article{bar,}"),
+            ])
+            .main("main.tex")
+            .line(1)
+            .character(6)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_citations(&context, &mut actual_items, CancellationToken::none());
+
+        let keys: Vec<_> = actual_items
+            .into_iter()
+            .filter_map(|item| match item.data {
+                InternalCompletionItemData::Citation { key, .. } => Some(key),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(keys, vec!["bar"]);
+    }
+
     #[test]
     fn test_latex_two_words() {
         let request = FeatureTester::builder()
@@ -289,4 +368,63 @@ mod tests {
             assert_eq!(item.range, TextRange::new(54.into(), 57.into()));
         }
     }
+
+    #[test]
+    fn test_markdown_citation() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                ("main.qmd", "---\nbibliography: main.bib\n---\nSee [@f"),
+                ("main.bib", "@article{foo,}"),
+            ])
+            .main("main.qmd")
+            .line(3)
+            .character(7)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_markdown_citations(&context, &mut actual_items, CancellationToken::none());
+
+        assert_eq!(actual_items.len(), 1);
+        assert_eq!(actual_items[0].range, TextRange::new(37.into(), 38.into()));
+    }
+
+    #[test]
+    fn test_markdown_cross_reference() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.qmd", "![Foo](foo.png){#fig-foo}\n\nSee @")])
+            .main("main.qmd")
+            .line(2)
+            .character(5)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_markdown_citations(&context, &mut actual_items, CancellationToken::none());
+
+        assert_eq!(actual_items.len(), 1);
+        assert_eq!(actual_items[0].data.label(), "fig-foo");
+    }
+
+    #[test]
+    fn test_markdown_email() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                ("main.md", "---\nbibliography: main.bib\n---\nfoo@ba"),
+                ("main.bib", "@article{foo,}"),
+            ])
+            .main("main.md")
+            .line(3)
+            .character(6)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_markdown_citations(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
 }
@@ -0,0 +1,77 @@
+use cancellation::CancellationToken;
+use lsp_types::CompletionParams;
+
+use crate::{features::cursor::CursorContext, LANGUAGE_DATA};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_class_options<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let (_, range, include) = context.find_class_option()?;
+    let class_name = include.path_list()?.keys().next()?.to_string();
+    for option in LANGUAGE_DATA.class_options(&class_name) {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::ClassOption {
+                name: &option.name,
+                documentation: &option.documentation,
+            },
+        ));
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cstree::TextRange;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_class_options(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_koma_script() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\documentclass[a4paper,D]{scrartcl}")])
+            .main("main.tex")
+            .line(0)
+            .character(24)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_class_options(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "DIV"));
+        assert!(actual_items
+            .iter()
+            .all(|item| item.data.label() != "titlepage"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(23.into(), 24.into()));
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use cancellation::CancellationToken;
+use lsp_types::CompletionParams;
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{latex, CstNode},
+    LANGUAGE_DATA,
+};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_counters<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let (_, range, group) = context.find_curly_group_word()?;
+    latex::CounterReference::cast(group.syntax().parent()?)?;
+
+    for name in &LANGUAGE_DATA.counters {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::Counter { name: name.clone() },
+        ));
+    }
+
+    for document in &context.request.subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            for node in data.root.descendants() {
+                cancellation_token.result().ok()?;
+
+                if let Some(name) = latex::CounterDefinition::cast(node)
+                    .and_then(|counter| counter.name())
+                    .and_then(|name| name.key())
+                    .map(|name| name.to_string())
+                {
+                    items.push(InternalCompletionItem::new(
+                        range,
+                        InternalCompletionItemData::Counter { name },
+                    ));
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cstree::TextRange;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_counters(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_empty_bibtex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.bib", "")])
+            .main("main.bib")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_counters(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_simple() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\newcounter{foo}\n\\setcounter{f}{1}")])
+            .main("main.tex")
+            .line(1)
+            .character(13)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_counters(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "foo"));
+        assert!(actual_items
+            .iter()
+            .any(|item| item.data.label() == "section"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(29.into(), 30.into()));
+        }
+    }
+
+    #[test]
+    fn test_open_brace() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\newcounter{foo}\n\\value{")])
+            .main("main.tex")
+            .line(1)
+            .character(7)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_counters(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(!actual_items.is_empty());
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(24.into(), 24.into()));
+        }
+    }
+}
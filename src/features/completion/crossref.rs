@@ -0,0 +1,134 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::CompletionParams;
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{bibtex, CstNode},
+};
+
+use super::{citation::make_item, types::InternalCompletionItem};
+
+/// Fields whose values refer to the keys of other entries.
+const CROSSREF_FIELDS: &[&str] = &["crossref", "xref", "related"];
+
+pub fn complete_crossrefs<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let token = context.cursor.as_bibtex()?;
+    let range = if token.kind() == bibtex::WORD {
+        token.text_range()
+    } else {
+        TextRange::empty(context.offset)
+    };
+
+    let field = token.ancestors().find_map(bibtex::Field::cast)?;
+    let name = field.name()?;
+    if !CROSSREF_FIELDS.contains(&name.text().to_lowercase().as_str())
+        || field.equality_sign()?.text_range().end() > range.start()
+    {
+        return None;
+    }
+
+    let current_key = bibtex::Entry::cast(field.syntax().parent()?)
+        .and_then(|entry| entry.key())
+        .map(|key| key.to_string());
+
+    for document in &context.request.subset.documents {
+        if let Some(data) = document.data.as_bibtex() {
+            for entry in data.root.children().filter_map(bibtex::Entry::cast) {
+                cancellation_token.result().ok()?;
+                if entry.key().map(|key| key.to_string()) == current_key {
+                    continue;
+                }
+
+                if let Some(item) = make_item(document, entry, range) {
+                    items.push(item);
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::{completion::types::InternalCompletionItemData, testing::FeatureTester};
+
+    use super::*;
+
+    fn complete_keys(text: &str, character: u32) -> Vec<(String, TextRange)> {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    "\\addbibresource{main.bib}\n\\addbibresource{other.bib}",
+                ),
+                ("main.bib", text),
+                ("other.bib", "@book{baz,}"),
+            ])
+            .main("main.bib")
+            .line(0)
+            .character(character)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_crossrefs(&context, &mut actual_items, CancellationToken::none());
+
+        let mut keys: Vec<_> = actual_items
+            .into_iter()
+            .filter_map(|item| match item.data {
+                InternalCompletionItemData::Citation { key, .. } => Some((key, item.range)),
+                _ => None,
+            })
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_empty_bibtex_document() {
+        assert!(complete_keys("", 0).is_empty());
+    }
+
+    #[test]
+    fn test_crossref_empty() {
+        let keys = complete_keys("@inbook{foo, crossref = {}} @book{bar,}", 25);
+        assert_eq!(
+            keys,
+            vec![
+                ("bar".into(), TextRange::empty(25.into())),
+                ("baz".into(), TextRange::empty(25.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_related_word() {
+        let keys = complete_keys("@inbook{foo, related = {bar,ba}} @book{bar,}", 29);
+        assert_eq!(
+            keys,
+            vec![
+                ("bar".into(), TextRange::new(28.into(), 30.into())),
+                ("baz".into(), TextRange::new(28.into(), 30.into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_other_field() {
+        assert!(complete_keys("@inbook{foo, title = {}} @book{bar,}", 22).is_empty());
+    }
+
+    #[test]
+    fn test_field_name() {
+        assert!(complete_keys("@inbook{foo, crossref = {}} @book{bar,}", 15).is_empty());
+    }
+}
@@ -6,7 +6,10 @@ use crate::{
     syntax::{latex, CstNode},
 };
 
-use super::types::{InternalCompletionItem, InternalCompletionItemData};
+use super::{
+    types::{InternalCompletionItem, InternalCompletionItemData},
+    util::glossary_category,
+};
 
 pub fn complete_glossary_entries<'a>(
     context: &'a CursorContext<CompletionParams>,
@@ -30,7 +33,10 @@ pub fn complete_glossary_entries<'a>(
                 {
                     items.push(InternalCompletionItem::new(
                         range,
-                        InternalCompletionItemData::GlossaryEntry { name },
+                        InternalCompletionItemData::GlossaryEntry {
+                            name,
+                            category: glossary_category(node),
+                        },
                     ));
                 } else if let Some(name) = latex::AcronymDefinition::cast(node)
                     .and_then(|entry| entry.name())
@@ -39,7 +45,10 @@ pub fn complete_glossary_entries<'a>(
                 {
                     items.push(InternalCompletionItem::new(
                         range,
-                        InternalCompletionItemData::Acronym { name },
+                        InternalCompletionItemData::Acronym {
+                            name,
+                            category: glossary_category(node),
+                        },
                     ));
                 }
             }
@@ -111,6 +120,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_glossaries_extra() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\newabbreviation[category={unit}]{ohm}{\\Omega}{ohm}\n\\glsxtrnewsymbol{pi}{\\pi}\n\\glsxtrshort{o}",
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(14)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_glossary_entries(&context, &mut actual_items, CancellationToken::none());
+
+        assert_eq!(actual_items.len(), 2);
+        assert!(matches!(
+            &actual_items[0].data,
+            InternalCompletionItemData::Acronym { name, category }
+                if name == "ohm" && category.as_deref() == Some("unit")
+        ));
+        assert!(matches!(
+            &actual_items[1].data,
+            InternalCompletionItemData::GlossaryEntry { name, category }
+                if name == "pi" && category.is_none()
+        ));
+    }
+
     #[test]
     fn test_open_brace() {
         let request = FeatureTester::builder()
@@ -0,0 +1,89 @@
+use cancellation::CancellationToken;
+use lsp_types::CompletionParams;
+
+use crate::{features::cursor::CursorContext, find_index_entries};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_index_entries<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let main_document = context.request.main_document();
+    let range = find_index_entries(&main_document.data.as_latex()?.root)
+        .into_iter()
+        .map(|entry| entry.range)
+        .find(|range| range.contains_inclusive(context.offset))?;
+
+    for document in &context.request.subset.documents {
+        cancellation_token.result().ok()?;
+        if let Some(data) = document.data.as_latex() {
+            for entry in find_index_entries(&data.root) {
+                if document.uri == main_document.uri && entry.range == range {
+                    continue;
+                }
+
+                items.push(InternalCompletionItem::new(
+                    range,
+                    InternalCompletionItemData::IndexEntry { name: entry.name },
+                ));
+            }
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cstree::TextRange;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_index_entries(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_simple() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                ("main.tex", "\\index{fruit!apple}\n\\index{fr}"),
+                ("foo.tex", "\\index{vegetable}\\input{main}"),
+            ])
+            .main("main.tex")
+            .line(1)
+            .character(9)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_index_entries(&context, &mut actual_items, CancellationToken::none());
+
+        let mut labels: Vec<_> = actual_items.iter().map(|item| item.data.label()).collect();
+        labels.sort_unstable();
+        assert_eq!(labels, vec!["fruit!apple", "vegetable"]);
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(27.into(), 29.into()));
+        }
+    }
+}
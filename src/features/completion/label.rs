@@ -4,7 +4,7 @@ use lsp_types::CompletionParams;
 
 use crate::{
     features::{cursor::CursorContext, lsp_kinds::Structure},
-    render_label,
+    find_label_kind, find_label_prefixes, find_reference_word, render_label,
     syntax::{latex, CstNode},
     LabelledObject,
 };
@@ -19,6 +19,7 @@ pub fn complete_labels<'a>(
     cancellation_token.result().ok()?;
 
     let (range, is_math) = find_reference(context).or_else(|| find_reference_range(context))?;
+    let start = items.len();
 
     for document in &context.request.subset.documents {
         if let Some(data) = document.data.as_latex() {
@@ -33,66 +34,118 @@ pub fn complete_labels<'a>(
                     .and_then(|name| name.key())
                     .map(|name| name.to_string())
                 {
-                    match render_label(&context.request.subset, &name, Some(label)) {
-                        Some(rendered_label) => {
-                            let kind = match &rendered_label.object {
-                                LabelledObject::Section { .. } => Structure::Section,
-                                LabelledObject::Float { .. } => Structure::Float,
-                                LabelledObject::Theorem { .. } => Structure::Theorem,
-                                LabelledObject::Equation => Structure::Equation,
-                                LabelledObject::EnumItem => Structure::Item,
-                            };
-
-                            if is_math && kind != Structure::Equation {
-                                continue;
-                            }
-
-                            let header = rendered_label.detail();
-                            let footer = match &rendered_label.object {
-                                LabelledObject::Float { caption, .. } => Some(caption.clone()),
-                                _ => None,
-                            };
-
-                            let text = format!("{} {}", name, rendered_label.reference());
-
-                            let item = InternalCompletionItem::new(
-                                range,
-                                InternalCompletionItemData::Label {
-                                    name,
-                                    kind,
-                                    header,
-                                    footer,
-                                    text,
-                                },
-                            );
-                            items.push(item);
-                        }
-                        None => {
-                            let kind = Structure::Label;
-                            let header = None;
-                            let footer = None;
-                            let text = name.to_string();
-                            let item = InternalCompletionItem::new(
-                                range,
-                                InternalCompletionItemData::Label {
-                                    name,
-                                    kind,
-                                    header,
-                                    footer,
-                                    text,
-                                },
-                            );
-                            items.push(item);
-                        }
-                    }
+                    items.extend(create_item(context, range, is_math, name, Some(label)));
                 }
             }
+
+            for name in data
+                .extras
+                .label_names
+                .iter()
+                .filter(|name| name.is_definition)
+                .filter(|name| !name_has_label_definition(&data.root, name.range))
+            {
+                let name = name.text.to_string();
+                items.extend(create_item(context, range, is_math, name, None));
+            }
+        }
+    }
+
+    preselect_by_reference_word(context, &mut items[start..]);
+    Some(())
+}
+
+/// Preselects the labels whose kind matches the word in front of the reference
+/// like `Table` in `Table~\ref{}`. Labels without a definition are matched by their prefix.
+fn preselect_by_reference_word(
+    context: &CursorContext<CompletionParams>,
+    items: &mut [InternalCompletionItem],
+) -> Option<()> {
+    let main_document = context.request.main_document();
+    let command = context.cursor.as_latex()?.ancestors().find_map(|node| {
+        latex::LabelReference::cast(node)
+            .and_then(|reference| reference.command())
+            .or_else(|| latex::LabelReferenceRange::cast(node).and_then(|range| range.command()))
+    })?;
+
+    let (_, kind) = find_reference_word(&main_document.text, command.text_range().start())?;
+    let prefixes = find_label_prefixes(&context.request.subset);
+    for item in items {
+        let name = match &item.data {
+            InternalCompletionItemData::Label { name, .. } => name,
+            _ => continue,
+        };
+
+        if find_label_kind(&context.request.subset, &prefixes, name) == Some(kind) {
+            item.preselect = true;
         }
     }
 
     Some(())
 }
 
+fn create_item<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    range: TextRange,
+    is_math: bool,
+    name: String,
+    label: Option<latex::LabelDefinition<'a>>,
+) -> Option<InternalCompletionItem<'a>> {
+    let data = match render_label(&context.request.subset, &name, label) {
+        Some(rendered_label) => {
+            let kind = match &rendered_label.object {
+                LabelledObject::Section { .. } => Structure::Section,
+                LabelledObject::Float { .. } => Structure::Float,
+                LabelledObject::Theorem { .. } => Structure::Theorem,
+                LabelledObject::Equation => Structure::Equation,
+                LabelledObject::EnumItem => Structure::Item,
+            };
+
+            if is_math && kind != Structure::Equation {
+                return None;
+            }
+
+            let header = rendered_label.detail();
+            let footer = match &rendered_label.object {
+                LabelledObject::Float { caption, .. } => Some(caption.clone()),
+                _ => None,
+            };
+
+            let text = format!("{} {}", name, rendered_label.reference());
+            InternalCompletionItemData::Label {
+                name,
+                kind,
+                header,
+                footer,
+                text,
+            }
+        }
+        None => {
+            let text = name.clone();
+            InternalCompletionItemData::Label {
+                name,
+                kind: Structure::Label,
+                header: None,
+                footer: None,
+                text,
+            }
+        }
+    };
+
+    Some(InternalCompletionItem::new(range, data))
+}
+
+fn name_has_label_definition(root: &latex::SyntaxNode, range: TextRange) -> bool {
+    root.token_at_offset(range.start())
+        .right_biased()
+        .map_or(false, |token| {
+            token
+                .parent()
+                .ancestors()
+                .any(|node| node.kind() == latex::LABEL_DEFINITION)
+        })
+}
+
 fn find_reference(context: &CursorContext<CompletionParams>) -> Option<(TextRange, bool)> {
     let (_, range, group) = context.find_curly_group_word_list()?;
     let reference = latex::LabelReference::cast(group.syntax().parent()?)?;
@@ -168,6 +221,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_preselect_by_reference_word() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\begin{table}\\caption{Foo}\\label{tab:foo}\\end{table}\n\\label{fig:bar}\nTable~\\ref{}",
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(11)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_labels(&context, &mut actual_items, CancellationToken::none());
+
+        let preselected: Vec<_> = actual_items
+            .into_iter()
+            .filter(|item| item.preselect)
+            .map(|item| item.data.label().to_string())
+            .collect();
+        assert_eq!(preselected, vec!["tab:foo"]);
+    }
+
     #[test]
     fn test_simple_range() {
         let request = FeatureTester::builder()
@@ -0,0 +1,173 @@
+use cancellation::CancellationToken;
+use lsp_types::CompletionParams;
+use rustc_hash::FxHashSet;
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    LANGUAGE_DATA,
+};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_styles<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+    cancellation_token: &CancellationToken,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let (_, range, command) = context.find_generic_command_argument()?;
+    let command_name = command.name()?.text();
+    let styles = LANGUAGE_DATA.styles(command_name);
+    if styles.is_empty() {
+        return None;
+    }
+
+    let mut names = FxHashSet::default();
+    for style in styles {
+        names.insert(style.name.clone());
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::Style {
+                name: style.name.clone(),
+                documentation: Some(&style.documentation),
+            },
+        ));
+    }
+
+    if command_name == "\\bibliographystyle" {
+        let resolver = context.request.context.resolver.lock().unwrap();
+        for file_name in resolver
+            .files_by_name
+            .keys()
+            .filter(|file_name| file_name.ends_with(".bst"))
+        {
+            cancellation_token.result().ok()?;
+
+            let name = &file_name[0..file_name.len() - 4];
+            if names.insert(name.to_string()) {
+                items.push(InternalCompletionItem::new(
+                    range,
+                    InternalCompletionItemData::Style {
+                        name: name.to_string(),
+                        documentation: None,
+                    },
+                ));
+            }
+        }
+    } else {
+        for document in &context.request.subset.documents {
+            if let Some(data) = document.data.as_latex() {
+                for name in data
+                    .root
+                    .descendants()
+                    .filter_map(latex::GenericCommand::cast)
+                    .filter(|command| {
+                        matches!(
+                            command.name().map(|name| name.text()),
+                            Some("\\fancypagestyle") | Some("\\newpagestyle")
+                        )
+                    })
+                    .filter_map(|command| {
+                        command
+                            .syntax()
+                            .children()
+                            .find_map(latex::CurlyGroup::cast)?
+                            .content_text()
+                    })
+                {
+                    cancellation_token.result().ok()?;
+
+                    if names.insert(name.clone()) {
+                        items.push(InternalCompletionItem::new(
+                            range,
+                            InternalCompletionItemData::Style {
+                                name,
+                                documentation: None,
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cstree::TextRange;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_styles(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.is_empty());
+    }
+
+    #[test]
+    fn test_page_style() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\fancypagestyle{foo}{}\n\\pagestyle{p}",
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(12)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_styles(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "foo"));
+        assert!(actual_items.iter().any(|item| item.data.label() == "plain"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(34.into(), 35.into()));
+        }
+    }
+
+    #[test]
+    fn test_bibliography_style() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\bibliographystyle{")])
+            .main("main.tex")
+            .line(0)
+            .character(19)
+            .build()
+            .completion();
+
+        let context = CursorContext::new(request);
+        let mut actual_items = Vec::new();
+        complete_styles(&context, &mut actual_items, CancellationToken::none());
+
+        assert!(actual_items.iter().any(|item| item.data.label() == "alpha"));
+        assert!(actual_items
+            .iter()
+            .all(|item| item.data.label() != "headings"));
+        for item in actual_items {
+            assert_eq!(item.range, TextRange::new(19.into(), 19.into()));
+        }
+    }
+}
@@ -68,9 +68,11 @@ pub enum InternalCompletionItemData<'a> {
     },
     Acronym {
         name: String,
+        category: Option<String>,
     },
     GlossaryEntry {
         name: String,
+        category: Option<String>,
     },
     File {
         name: SmolStr,
@@ -97,6 +99,23 @@ pub enum InternalCompletionItemData<'a> {
     TikzLibrary {
         name: &'a str,
     },
+    Counter {
+        name: String,
+    },
+    IndexEntry {
+        name: String,
+    },
+    Style {
+        name: String,
+        documentation: Option<&'a str>,
+    },
+    ClassOption {
+        name: &'a str,
+        documentation: &'a str,
+    },
+    BibMacro {
+        name: String,
+    },
 }
 
 impl<'a> InternalCompletionItemData<'a> {
@@ -113,8 +132,8 @@ impl<'a> InternalCompletionItemData<'a> {
             Self::Package { name } => name,
             Self::Color { name } => name,
             Self::ColorModel { name } => name,
-            Self::Acronym { name } => name,
-            Self::GlossaryEntry { name } => name,
+            Self::Acronym { name, .. } => name,
+            Self::GlossaryEntry { name, .. } => name,
             Self::File { name } => name,
             Self::Directory { name } => name,
             Self::Label { name, .. } => name,
@@ -122,6 +141,11 @@ impl<'a> InternalCompletionItemData<'a> {
             Self::UserEnvironment { name } => name,
             Self::PgfLibrary { name } => name,
             Self::TikzLibrary { name } => name,
+            Self::Counter { name } => name,
+            Self::IndexEntry { name } => name,
+            Self::Style { name, .. } => name,
+            Self::ClassOption { name, .. } => name,
+            Self::BibMacro { name } => name,
         }
     }
 }
@@ -147,4 +171,9 @@ pub enum CompletionItemData {
     Argument,
     Acronym,
     GlossaryEntry,
+    Counter,
+    IndexEntry,
+    Style,
+    ClassOption,
+    BibMacro,
 }
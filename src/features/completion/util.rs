@@ -1,7 +1,13 @@
 use lsp_types::{CompletionItemKind, CompletionParams, Documentation, MarkupContent, MarkupKind};
 use smol_str::SmolStr;
 
-use crate::features::FeatureRequest;
+use crate::{
+    features::FeatureRequest,
+    syntax::{
+        latex::{self, HasKeyValueBody},
+        CstNode,
+    },
+};
 
 pub fn component_detail(file_names: &[SmolStr]) -> String {
     if file_names.is_empty() {
@@ -64,3 +70,28 @@ pub fn adjust_kind(
     }
     CompletionItemKind::Text
 }
+
+/// Returns the `category` option of a glossary entry or an abbreviation.
+pub fn glossary_category(definition: &latex::SyntaxNode) -> Option<String> {
+    definition
+        .children()
+        .filter_map(|node| {
+            latex::BrackGroupKeyValue::cast(node)
+                .and_then(|group| group.body())
+                .or_else(|| latex::CurlyGroupKeyValue::cast(node).and_then(|group| group.body()))
+        })
+        .flat_map(|body| body.pairs())
+        .filter(|pair| pair.key().map(|key| key.to_string()).as_deref() == Some("category"))
+        .find_map(|pair| pair.value())
+        .map(|value| {
+            value
+                .syntax()
+                .text()
+                .to_string()
+                .trim()
+                .trim_start_matches('{')
+                .trim_end_matches('}')
+                .trim()
+                .to_string()
+        })
+}
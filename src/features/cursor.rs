@@ -5,12 +5,36 @@ use lsp_types::{
 };
 
 use crate::{
-    syntax::{bibtex, latex, CstNode},
+    syntax::{
+        bibtex,
+        latex::{self, HasBrack},
+        markdown, CstNode,
+    },
     DocumentData, LineIndexExt,
 };
 
 use super::FeatureRequest;
 
+/// Commands whose arguments contain the `key=value` options of a package.
+const OPTION_COMMANDS: &[(&str, &str)] = &[
+    ("\\lstset", "listings"),
+    ("\\lstinputlisting", "listings"),
+    ("\\lstinline", "listings"),
+    ("\\ExecuteBibliographyOptions", "biblatex"),
+];
+
+/// Environments whose optional argument contains the `key=value` options of a package.
+const OPTION_ENVIRONMENTS: &[(&str, &str)] = &[("lstlisting", "listings")];
+
+/// The value of a `key=value` option like `style=ieee` in `\usepackage[style=ieee]{biblatex}`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KeyValueOption {
+    pub packages: Vec<String>,
+    pub key: String,
+    pub value: String,
+    pub range: TextRange,
+}
+
 #[derive(Debug)]
 pub enum Cursor {
     Latex(latex::SyntaxToken),
@@ -143,7 +167,7 @@ impl<P: HasPosition> CursorContext<P> {
                 let right = data.root.token_at_offset(offset).right_biased();
                 Cursor::new_bibtex(left, right)
             }
-            DocumentData::BuildLog(_) => None,
+            DocumentData::BuildLog(_) | DocumentData::Markdown(_) => None,
         };
 
         Self {
@@ -178,6 +202,13 @@ impl<P: HasPosition> CursorContext<P> {
         Some((key.to_string(), key.small_range()))
     }
 
+    pub fn find_markdown_citation_key(&self) -> Option<(String, TextRange)> {
+        let main_document = self.request.main_document();
+        main_document.data.as_markdown()?;
+        let range = markdown::find_citation_key_at(&main_document.text, self.offset)?;
+        Some((main_document.text[range].to_string(), range))
+    }
+
     pub fn find_entry_key(&self) -> Option<(String, TextRange)> {
         let word = self
             .cursor
@@ -196,7 +227,10 @@ impl<P: HasPosition> CursorContext<P> {
             .as_latex()
             .filter(|token| token.kind() == latex::WORD)?;
 
-        let key = latex::Key::cast(name.parent())?;
+        let key = match latex::Key::cast(name.parent()) {
+            Some(key) => key,
+            None => return self.find_key_value_label_name(name),
+        };
 
         if matches!(
             key.syntax().parent()?.parent()?.kind(),
@@ -208,6 +242,17 @@ impl<P: HasPosition> CursorContext<P> {
         }
     }
 
+    fn find_key_value_label_name(&self, name: &latex::SyntaxToken) -> Option<(String, TextRange)> {
+        let data = self.request.main_document().data.as_latex()?;
+        let label = data
+            .extras
+            .label_names
+            .iter()
+            .find(|label| label.range == name.text_range())?;
+
+        Some((label.text.to_string(), label.range))
+    }
+
     pub fn find_label_name_command(&self) -> Option<(String, TextRange)> {
         let node = self.cursor.as_latex()?.parent();
         if let Some(label) = latex::LabelDefinition::cast(node) {
@@ -250,6 +295,123 @@ impl<P: HasPosition> CursorContext<P> {
             .or_else(|| Some((String::new(), TextRange::empty(self.offset), group)))
     }
 
+    pub fn find_generic_command_argument(
+        &self,
+    ) -> Option<(String, TextRange, latex::GenericCommand)> {
+        let token = self.cursor.as_latex()?;
+
+        let group = latex::CurlyGroup::cast(token.parent())
+            .or_else(|| token.parent().parent().and_then(latex::CurlyGroup::cast))
+            .filter(|group| self.is_inside_latex_curly(group))?;
+
+        let command = latex::GenericCommand::cast(group.syntax().parent()?)?;
+        if token.kind() == latex::WORD {
+            Some((token.text().to_string(), token.text_range(), command))
+        } else {
+            Some((String::new(), TextRange::empty(self.offset), command))
+        }
+    }
+
+    pub fn find_class_option(&self) -> Option<(String, TextRange, latex::Include)> {
+        let token = self.cursor.as_latex()?;
+
+        let options = token
+            .parent()
+            .ancestors()
+            .find_map(latex::BrackGroupKeyValue::cast)
+            .filter(|options| {
+                options.small_range().contains(self.offset) || options.right_brack().is_none()
+            })?;
+
+        let include = latex::Include::cast(options.syntax().parent()?)
+            .filter(|include| include.syntax().kind() == latex::CLASS_INCLUDE)?;
+
+        match latex::Key::cast(token.parent()) {
+            Some(key) if token.kind() == latex::WORD => {
+                Some((key.to_string(), key.small_range(), include))
+            }
+            _ => Some((String::new(), TextRange::empty(self.offset), include)),
+        }
+    }
+
+    pub fn find_key_value_option(&self) -> Option<KeyValueOption> {
+        let token = self
+            .cursor
+            .as_latex()
+            .filter(|token| token.kind() == latex::WORD)?;
+
+        let text = latex::Text::cast(token.parent())?;
+        let parent = text.syntax().parent()?;
+        let (packages, key) = if let Some(value) = latex::Value::cast(parent) {
+            let pair = latex::KeyValuePair::cast(value.syntax().parent()?)?;
+            let include = pair
+                .syntax()
+                .ancestors()
+                .find_map(latex::Include::cast)
+                .filter(|include| {
+                    matches!(
+                        include.syntax().kind(),
+                        latex::PACKAGE_INCLUDE | latex::CLASS_INCLUDE
+                    )
+                })?;
+
+            let packages = include
+                .path_list()?
+                .keys()
+                .map(|key| key.to_string())
+                .collect();
+
+            (packages, pair.key()?.to_string())
+        } else {
+            let mut previous = parent
+                .children_with_tokens()
+                .take_while(|element| element.text_range().end() <= text.small_range().start())
+                .filter(|element| {
+                    element.as_token().map_or(true, |token| {
+                        !matches!(token.kind(), latex::WHITESPACE | latex::COMMENT)
+                    })
+                })
+                .collect::<Vec<_>>()
+                .into_iter()
+                .rev();
+
+            previous
+                .next()?
+                .into_token()
+                .filter(|token| token.kind() == latex::EQUALITY_SIGN)?;
+
+            let key = latex::Text::cast(previous.next()?.into_node()?)?
+                .words()
+                .last()?
+                .text()
+                .to_string();
+
+            let owner = parent.parent()?;
+            let package = if let Some(command) = latex::GenericCommand::cast(owner) {
+                let name = command.name()?.text();
+                OPTION_COMMANDS
+                    .iter()
+                    .find(|(command_name, _)| *command_name == name)?
+                    .1
+            } else {
+                let name = latex::Begin::cast(owner)?.name()?.key()?.to_string();
+                OPTION_ENVIRONMENTS
+                    .iter()
+                    .find(|(environment_name, _)| *environment_name == name)?
+                    .1
+            };
+
+            (vec![package.to_string()], key)
+        };
+
+        Some(KeyValueOption {
+            packages,
+            key,
+            value: token.text().to_string(),
+            range: token.text_range(),
+        })
+    }
+
     pub fn find_curly_group_word_list(
         &self,
     ) -> Option<(String, TextRange, latex::CurlyGroupWordList)> {
@@ -1,15 +1,22 @@
+mod bibliography;
 mod command;
+mod counter;
 mod document;
 mod entry;
+mod generated;
 mod label;
+mod option_value;
 mod string;
 
 use cancellation::CancellationToken;
 use lsp_types::{GotoDefinitionParams, GotoDefinitionResponse};
 
 use self::{
-    command::goto_command_definition, document::goto_document_definition,
-    entry::goto_entry_definition, label::goto_label_definition, string::goto_string_definition,
+    bibliography::goto_bibliography_definition, command::goto_command_definition,
+    counter::goto_counter_definition, document::goto_document_definition,
+    entry::goto_entry_definition, generated::goto_generated_definition,
+    label::goto_label_definition, option_value::goto_option_value_definition,
+    string::goto_string_definition,
 };
 
 use super::{cursor::CursorContext, FeatureRequest};
@@ -20,10 +27,14 @@ pub fn goto_definition(
 ) -> Option<GotoDefinitionResponse> {
     let context = CursorContext::new(request);
     log::debug!("[Definition] Cursor: {:?}", context.cursor);
-    let links = goto_command_definition(&context, cancellation_token)
+    let links = goto_generated_definition(&context, cancellation_token)
+        .or_else(|| goto_command_definition(&context, cancellation_token))
+        .or_else(|| goto_bibliography_definition(&context, cancellation_token))
         .or_else(|| goto_document_definition(&context, cancellation_token))
         .or_else(|| goto_entry_definition(&context, cancellation_token))
         .or_else(|| goto_label_definition(&context, cancellation_token))
-        .or_else(|| goto_string_definition(&context, cancellation_token))?;
+        .or_else(|| goto_string_definition(&context, cancellation_token))
+        .or_else(|| goto_counter_definition(&context, cancellation_token))
+        .or_else(|| goto_option_value_definition(&context, cancellation_token))?;
     Some(GotoDefinitionResponse::Link(links))
 }
@@ -0,0 +1,175 @@
+use cancellation::CancellationToken;
+use lsp_types::{GotoDefinitionParams, LocationLink, Range};
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::latex::{self, ExplicitLinkKind},
+    LineIndexExt, RangeExt,
+};
+
+pub fn goto_bibliography_definition(
+    context: &CursorContext<GotoDefinitionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<LocationLink>> {
+    let main_document = context.request.main_document();
+    let main_data = main_document.data.as_latex()?;
+
+    let name = context
+        .cursor
+        .as_latex()
+        .filter(|token| token.kind().is_command_name())?;
+
+    let include = name.parent();
+    let links: Vec<_> = if matches!(
+        include.kind(),
+        latex::BIBTEX_INCLUDE | latex::BIBLATEX_INCLUDE
+    ) {
+        main_data
+            .extras
+            .explicit_links
+            .iter()
+            .filter(|link| link.kind == ExplicitLinkKind::Bibtex)
+            .filter(|link| include.text_range().contains_range(link.stem_range))
+            .collect()
+    } else if name.text() == "\\printbibliography" {
+        context
+            .request
+            .subset
+            .documents
+            .iter()
+            .filter_map(|document| document.data.as_latex())
+            .flat_map(|data| data.extras.explicit_links.iter())
+            .filter(|link| link.kind == ExplicitLinkKind::Bibtex)
+            .collect()
+    } else {
+        return None;
+    };
+
+    let origin_selection_range = main_document
+        .line_index
+        .line_col_lsp_range(name.text_range());
+
+    let mut locations: Vec<LocationLink> = Vec::new();
+    for link in links {
+        cancellation_token.result().ok()?;
+        let target = link.targets.iter().find(|target| {
+            context
+                .request
+                .subset
+                .documents
+                .iter()
+                .any(|document| document.uri.as_ref() == target.as_ref())
+        });
+
+        if let Some(target) = target {
+            let target_uri = target.as_ref().clone().into();
+            if locations
+                .iter()
+                .all(|location| location.target_uri != target_uri)
+            {
+                locations.push(LocationLink {
+                    origin_selection_range: Some(origin_selection_range),
+                    target_uri,
+                    target_range: Range::new_simple(0, 0, 0, 0),
+                    target_selection_range: Range::new_simple(0, 0, 0, 0),
+                });
+            }
+        }
+    }
+
+    if locations.is_empty() {
+        None
+    } else {
+        Some(locations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .definition();
+
+        let context = CursorContext::new(request);
+        let actual_links = goto_bibliography_definition(&context, CancellationToken::none());
+
+        assert!(actual_links.is_none());
+    }
+
+    #[test]
+    fn test_bibliography() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("main.tex", r#"\bibliography{foo,bar}"#),
+                ("foo.bib", ""),
+                ("bar.bib", ""),
+                ("baz.bib", ""),
+            ])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build();
+        let foo_uri = tester.uri("foo.bib").as_ref().clone().into();
+        let bar_uri = tester.uri("bar.bib").as_ref().clone().into();
+
+        let request = tester.definition();
+        let context = CursorContext::new(request);
+        let actual_links =
+            goto_bibliography_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![
+            LocationLink {
+                origin_selection_range: Some(Range::new_simple(0, 0, 0, 13)),
+                target_uri: foo_uri,
+                target_range: Range::new_simple(0, 0, 0, 0),
+                target_selection_range: Range::new_simple(0, 0, 0, 0),
+            },
+            LocationLink {
+                origin_selection_range: Some(Range::new_simple(0, 0, 0, 13)),
+                target_uri: bar_uri,
+                target_range: Range::new_simple(0, 0, 0, 0),
+                target_selection_range: Range::new_simple(0, 0, 0, 0),
+            },
+        ];
+
+        assert_eq!(actual_links, expected_links);
+    }
+
+    #[test]
+    fn test_print_bibliography() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("main.tex", "\\addbibresource{foo.bib}\n\\printbibliography"),
+                ("foo.bib", ""),
+            ])
+            .main("main.tex")
+            .line(1)
+            .character(5)
+            .build();
+        let target_uri = tester.uri("foo.bib").as_ref().clone().into();
+
+        let request = tester.definition();
+        let context = CursorContext::new(request);
+        let actual_links =
+            goto_bibliography_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(1, 0, 1, 18)),
+            target_uri,
+            target_range: Range::new_simple(0, 0, 0, 0),
+            target_selection_range: Range::new_simple(0, 0, 0, 0),
+        }];
+
+        assert_eq!(actual_links, expected_links);
+    }
+}
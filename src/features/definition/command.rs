@@ -2,9 +2,7 @@ use cancellation::CancellationToken;
 use lsp_types::{GotoDefinitionParams, LocationLink};
 
 use crate::{
-    features::cursor::CursorContext,
-    syntax::{latex, CstNode},
-    LineIndexExt,
+    features::cursor::CursorContext, find_command_definitions, syntax::CstNode, LineIndexExt,
 };
 
 pub fn goto_command_definition(
@@ -22,37 +20,29 @@ pub fn goto_command_definition(
         .line_index
         .line_col_lsp_range(name.text_range());
 
-    for document in &context.request.subset.documents {
-        if let Some(data) = document.data.as_latex() {
-            for node in data.root.descendants() {
-                cancellation_token.result().ok()?;
-
-                if let Some(defintion) = latex::CommandDefinition::cast(node).filter(|def| {
-                    def.name()
-                        .and_then(|name| name.command())
-                        .map(|name| name.text())
-                        == Some(name.text())
-                }) {
-                    let target_selection_range = document
-                        .line_index
-                        .line_col_lsp_range(defintion.name()?.command()?.text_range());
-
-                    let target_range = document
-                        .line_index
-                        .line_col_lsp_range(defintion.small_range());
-
-                    return Some(vec![LocationLink {
-                        origin_selection_range: Some(origin_selection_range),
-                        target_uri: document.uri.as_ref().clone().into(),
-                        target_range,
-                        target_selection_range,
-                    }]);
-                }
-            }
-        }
-    }
+    let definitions = find_command_definitions(&context.request.subset, name.text());
+    cancellation_token.result().ok()?;
+
+    let info = definitions
+        .iter()
+        .find(|info| info.is_effective)
+        .or_else(|| definitions.first())?;
+
+    let document = info.document;
+    let target_selection_range = document
+        .line_index
+        .line_col_lsp_range(info.definition.name()?.command()?.text_range());
 
-    None
+    let target_range = document
+        .line_index
+        .line_col_lsp_range(info.definition.small_range());
+
+    Some(vec![LocationLink {
+        origin_selection_range: Some(origin_selection_range),
+        target_uri: document.uri.as_ref().clone().into(),
+        target_range,
+        target_selection_range,
+    }])
 }
 
 #[cfg(test)]
@@ -128,4 +118,39 @@ mod tests {
 
         assert_eq!(actual_links, expected_links);
     }
+
+    #[test]
+    fn test_effective_definition() {
+        let tester = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {
+                    r#"
+                        \newcommand{\foo}{foo}
+                        \providecommand{\foo}{bar}
+                        \renewcommand*{\foo}{baz}
+                        \providecommand{\foo}{qux}
+                        \foo
+                    "#
+                },
+            )])
+            .main("main.tex")
+            .line(4)
+            .character(2)
+            .build();
+        let target_uri = tester.uri("main.tex").as_ref().clone().into();
+
+        let request = tester.definition();
+        let context = CursorContext::new(request);
+        let actual_links = goto_command_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(4, 0, 4, 4)),
+            target_uri,
+            target_range: Range::new_simple(2, 0, 2, 25),
+            target_selection_range: Range::new_simple(2, 15, 2, 19),
+        }];
+
+        assert_eq!(actual_links, expected_links);
+    }
 }
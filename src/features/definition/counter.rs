@@ -0,0 +1,109 @@
+use cancellation::CancellationToken;
+use lsp_types::{GotoDefinitionParams, LocationLink};
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+pub fn goto_counter_definition(
+    context: &CursorContext<GotoDefinitionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<LocationLink>> {
+    let main_document = context.request.main_document();
+
+    let (name_text, name_range, group) = context.find_curly_group_word()?;
+    latex::CounterReference::cast(group.syntax().parent()?)?;
+
+    let origin_selection_range = main_document.line_index.line_col_lsp_range(name_range);
+
+    for document in &context.request.subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            for node in data.root.descendants() {
+                cancellation_token.result().ok()?;
+
+                if let Some(definition) = latex::CounterDefinition::cast(node) {
+                    let name = match definition.name().and_then(|name| name.key()) {
+                        Some(name) if name.to_string() == name_text => name,
+                        _ => continue,
+                    };
+
+                    return Some(vec![LocationLink {
+                        origin_selection_range: Some(origin_selection_range),
+                        target_uri: document.uri.as_ref().clone().into(),
+                        target_range: document
+                            .line_index
+                            .line_col_lsp_range(definition.small_range()),
+                        target_selection_range: document
+                            .line_index
+                            .line_col_lsp_range(name.small_range()),
+                    }]);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .definition();
+
+        let context = CursorContext::new(request);
+        let actual_links = goto_counter_definition(&context, CancellationToken::none());
+
+        assert!(actual_links.is_none());
+    }
+
+    #[test]
+    fn test_counter_definition() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("foo.tex", "\\newcounter{foo}[section]\n\\include{bar}"),
+                (
+                    "bar.tex",
+                    indoc! {
+                        r#"
+                            \stepcounter{foo}
+                            \arabic{foo}
+                        "#
+                    },
+                ),
+            ])
+            .main("bar.tex")
+            .line(1)
+            .character(10)
+            .build();
+        let target_uri = tester.uri("foo.tex").as_ref().clone().into();
+
+        let request = tester.definition();
+        let context = CursorContext::new(request);
+        let actual_links = goto_counter_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(1, 8, 1, 11)),
+            target_uri,
+            target_range: Range::new_simple(0, 0, 0, 25),
+            target_selection_range: Range::new_simple(0, 12, 0, 15),
+        }];
+
+        assert_eq!(actual_links, expected_links);
+    }
+}
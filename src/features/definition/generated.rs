@@ -0,0 +1,25 @@
+use cancellation::CancellationToken;
+use lsp_types::{GotoDefinitionParams, LocationLink};
+
+use crate::{
+    features::{cursor::CursorContext, find_generated_references},
+    LineIndexExt,
+};
+
+pub fn goto_generated_definition(
+    context: &CursorContext<GotoDefinitionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<LocationLink>> {
+    let main_document = context.request.main_document();
+    let reference = find_generated_references(context.request.workspace.as_ref(), main_document)
+        .into_iter()
+        .find(|reference| reference.range.contains_inclusive(context.offset))?;
+
+    cancellation_token.result().ok()?;
+    Some(vec![LocationLink {
+        origin_selection_range: Some(main_document.line_index.line_col_lsp_range(reference.range)),
+        target_uri: reference.target.uri,
+        target_range: reference.target.range,
+        target_selection_range: reference.target_selection,
+    }])
+}
@@ -37,6 +37,24 @@ pub fn goto_label_definition(
                         .line_col_lsp_range(target_selection_range),
                 }]);
             }
+
+            if let Some(name) = data
+                .extras
+                .label_names
+                .iter()
+                .find(|name| name.is_definition && name.text == name_text)
+            {
+                let target_range = render_label(&context.request.subset, &name_text, None)
+                    .map(|label| label.range)
+                    .unwrap_or(name.range);
+
+                return Some(vec![LocationLink {
+                    origin_selection_range: Some(origin_selection_range),
+                    target_uri: document.uri.as_ref().clone().into(),
+                    target_range: document.line_index.line_col_lsp_range(target_range),
+                    target_selection_range: document.line_index.line_col_lsp_range(name.range),
+                }]);
+            }
         }
     }
 
@@ -45,7 +63,10 @@ pub fn goto_label_definition(
 
 #[cfg(test)]
 mod tests {
-    use crate::features::testing::FeatureTester;
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
 
     use super::*;
 
@@ -80,4 +101,37 @@ mod tests {
 
         assert!(actual_links.is_none());
     }
+
+    #[test]
+    fn test_key_value_label() {
+        let tester = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {
+                    r#"
+                        \begin{lstlisting}[caption=Foo, label={lst:foo}]
+                        \end{lstlisting}
+                        \ref{lst:foo}
+                    "#
+                },
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(7)
+            .build();
+        let target_uri = tester.uri("main.tex").as_ref().clone().into();
+
+        let request = tester.definition();
+        let context = CursorContext::new(request);
+        let actual_links = goto_label_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(2, 5, 2, 12)),
+            target_uri,
+            target_range: Range::new_simple(0, 39, 0, 46),
+            target_selection_range: Range::new_simple(0, 39, 0, 46),
+        }];
+
+        assert_eq!(actual_links, expected_links);
+    }
 }
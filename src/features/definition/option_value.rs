@@ -0,0 +1,221 @@
+use cancellation::CancellationToken;
+use lsp_types::{GotoDefinitionParams, LocationLink, Range, Url};
+
+use crate::{
+    features::cursor::{CursorContext, KeyValueOption},
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    LineIndexExt, RangeExt,
+};
+
+pub fn goto_option_value_definition(
+    context: &CursorContext<GotoDefinitionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<LocationLink>> {
+    let option = context.find_key_value_option()?;
+    let origin_selection_range = context
+        .request
+        .main_document()
+        .line_index
+        .line_col_lsp_range(option.range);
+
+    let links: Vec<_> = if option.packages.iter().any(|package| package == "listings") {
+        find_language_definitions(context, &option, origin_selection_range, cancellation_token)
+    } else {
+        find_style_files(context, &option, origin_selection_range)
+    };
+
+    if links.is_empty() {
+        None
+    } else {
+        Some(links)
+    }
+}
+
+/// Finds the languages that are defined with `\lstdefinelanguage` in the project.
+fn find_language_definitions(
+    context: &CursorContext<GotoDefinitionParams>,
+    option: &KeyValueOption,
+    origin_selection_range: Range,
+    cancellation_token: &CancellationToken,
+) -> Vec<LocationLink> {
+    let mut links = Vec::new();
+    if option.key != "language" {
+        return links;
+    }
+
+    for document in &context.request.subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            for command in data
+                .root
+                .descendants()
+                .filter_map(latex::GenericCommand::cast)
+                .filter(|command| {
+                    command.name().map(|name| name.text()) == Some("\\lstdefinelanguage")
+                })
+            {
+                if cancellation_token.is_canceled() {
+                    return links;
+                }
+
+                let name = match command
+                    .syntax()
+                    .children()
+                    .find_map(latex::CurlyGroup::cast)
+                {
+                    Some(name) => name,
+                    None => continue,
+                };
+
+                if name
+                    .content_text()
+                    .map_or(false, |text| text.eq_ignore_ascii_case(&option.value))
+                {
+                    links.push(LocationLink {
+                        origin_selection_range: Some(origin_selection_range),
+                        target_uri: document.uri.as_ref().clone().into(),
+                        target_range: document
+                            .line_index
+                            .line_col_lsp_range(command.small_range()),
+                        target_selection_range: document
+                            .line_index
+                            .line_col_lsp_range(name.small_range()),
+                    });
+                }
+            }
+        }
+    }
+
+    links
+}
+
+/// Finds the files that implement a `biblatex` style like `authoryear.bbx` and `authoryear.cbx`.
+fn find_style_files(
+    context: &CursorContext<GotoDefinitionParams>,
+    option: &KeyValueOption,
+    origin_selection_range: Range,
+) -> Vec<LocationLink> {
+    if !option.packages.iter().any(|package| package == "biblatex") {
+        return Vec::new();
+    }
+
+    let extensions: &[&str] = match option.key.as_str() {
+        "style" => &["bbx", "cbx"],
+        "bibstyle" => &["bbx"],
+        "citestyle" => &["cbx"],
+        _ => &[],
+    };
+
+    let resolver = context.request.context.resolver.lock().unwrap();
+    let mut links = Vec::new();
+    for extension in extensions {
+        let file_name = format!("{}.{}", option.value, extension);
+        let target_uri = context
+            .request
+            .subset
+            .documents
+            .iter()
+            .find(|document| document.uri.path().ends_with(&format!("/{}", file_name)))
+            .map(|document| document.uri.as_ref().clone().into())
+            .or_else(|| {
+                resolver
+                    .files_by_name
+                    .get(file_name.as_str())
+                    .and_then(|path| Url::from_file_path(path).ok())
+            });
+
+        if let Some(target_uri) = target_uri {
+            links.push(LocationLink {
+                origin_selection_range: Some(origin_selection_range),
+                target_uri,
+                target_range: Range::new_simple(0, 0, 0, 0),
+                target_selection_range: Range::new_simple(0, 0, 0, 0),
+            });
+        }
+    }
+
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use rustc_hash::FxHashMap;
+
+    use crate::{distro::Resolver, features::testing::FeatureTester};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .definition();
+
+        let context = CursorContext::new(request);
+        let actual_links = goto_option_value_definition(&context, CancellationToken::none());
+
+        assert!(actual_links.is_none());
+    }
+
+    #[test]
+    fn test_listings_language() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("foo.tex", "\\lstdefinelanguage{Foo}{morekeywords={bar}}"),
+                ("main.tex", "\\include{foo}\n\\lstset{language=Foo}"),
+            ])
+            .main("main.tex")
+            .line(1)
+            .character(18)
+            .build();
+        let target_uri = tester.uri("foo.tex").as_ref().clone().into();
+
+        let context = CursorContext::new(tester.definition());
+        let actual_links =
+            goto_option_value_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(1, 17, 1, 20)),
+            target_uri,
+            target_range: Range::new_simple(0, 0, 0, 43),
+            target_selection_range: Range::new_simple(0, 18, 0, 23),
+        }];
+        assert_eq!(actual_links, expected_links);
+    }
+
+    #[test]
+    fn test_biblatex_style() {
+        let path = std::env::temp_dir().join("ieee.bbx");
+        let mut files_by_name = FxHashMap::default();
+        files_by_name.insert("ieee.bbx".into(), PathBuf::from(&path));
+
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\usepackage[style=ieee]{biblatex}")])
+            .main("main.tex")
+            .line(0)
+            .character(20)
+            .resolver(Resolver::new(files_by_name))
+            .build()
+            .definition();
+
+        let context = CursorContext::new(request);
+        let actual_links =
+            goto_option_value_definition(&context, CancellationToken::none()).unwrap();
+
+        let expected_links = vec![LocationLink {
+            origin_selection_range: Some(Range::new_simple(0, 18, 0, 22)),
+            target_uri: Url::from_file_path(path).unwrap(),
+            target_range: Range::new_simple(0, 0, 0, 0),
+            target_selection_range: Range::new_simple(0, 0, 0, 0),
+        }];
+        assert_eq!(actual_links, expected_links);
+    }
+}
@@ -0,0 +1,136 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{Range, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{extract_prose, LineIndexExt, ProseSegment};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetexParams {
+    pub text_document: TextDocumentIdentifier,
+
+    /// The part of the document that should be converted. The whole document is converted if the range is missing.
+    #[serde(default)]
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetexResult {
+    pub text: String,
+    pub mappings: Vec<DetexMapping>,
+}
+
+/// Maps a part of the plain text to the range of the document that produced it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetexMapping {
+    /// The offset of the part in the plain text (in UTF-16 code units).
+    pub offset: u32,
+
+    /// The length of the part in the plain text (in UTF-16 code units).
+    pub length: u32,
+
+    pub range: Range,
+
+    /// Whether the part is a placeholder of markup (like `X` for a formula) instead of a copy of the source.
+    pub is_placeholder: bool,
+}
+
+/// Converts the prose of a LaTeX document to plain text.
+/// Commands are removed and math, citations and references are replaced with placeholders
+/// (see `extract_prose`), so that the text can be passed to tools that do not understand LaTeX.
+pub fn detex(
+    request: FeatureRequest<DetexParams>,
+    _cancellation_token: &CancellationToken,
+) -> Option<DetexResult> {
+    let document = request.main_document();
+    let data = document.data.as_latex()?;
+    let range = request.params.range.map_or_else(
+        || TextRange::up_to((document.text.len() as u32).into()),
+        |range| document.line_index.offset_lsp_range(range),
+    );
+
+    let mut result = DetexResult {
+        text: String::new(),
+        mappings: Vec::new(),
+    };
+
+    let options = request.context.options.read().unwrap().prose.clone();
+    let mut offset = 0;
+    for segment in extract_prose(&data.root, &options) {
+        let (source_range, text, is_placeholder) = match &segment {
+            ProseSegment::Text(text_range) => match text_range.intersect(range) {
+                Some(text_range) if !text_range.is_empty() => {
+                    (text_range, &document.text[text_range], false)
+                }
+                _ => continue,
+            },
+            ProseSegment::Markup {
+                range: markup_range,
+                interpret_as,
+            } if !interpret_as.is_empty()
+                && range
+                    .intersect(*markup_range)
+                    .map_or(false, |range| !range.is_empty()) =>
+            {
+                (*markup_range, interpret_as.as_str(), true)
+            }
+            ProseSegment::Markup { .. } => continue,
+        };
+
+        let length = text.encode_utf16().count() as u32;
+        result.text.push_str(text);
+        result.mappings.push(DetexMapping {
+            offset,
+            length,
+            range: document.line_index.line_col_lsp_range(source_range),
+            is_placeholder,
+        });
+
+        offset += length;
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Position;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_range() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                    \documentclass{article}
+                    \begin{document}
+                    Foo \textbf{bär} $x$ baz.
+                    \end{document}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .detex(Some(Range::new(Position::new(2, 0), Position::new(2, 23))));
+
+        let actual = detex(request, CancellationToken::none()).unwrap();
+        assert_eq!(actual.text, "Foo bär X ba");
+
+        let mapping = &actual.mappings[3];
+        assert_eq!(mapping.offset, 8);
+        assert_eq!(mapping.length, 1);
+        assert!(mapping.is_placeholder);
+        assert_eq!(
+            mapping.range,
+            Range::new(Position::new(2, 17), Position::new(2, 20))
+        );
+    }
+}
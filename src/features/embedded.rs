@@ -0,0 +1,262 @@
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{Range, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{
+        latex::{self, HasBrack},
+        CstNode,
+    },
+    Document, LineIndexExt,
+};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedDocumentsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EmbeddedDocument {
+    pub language_id: String,
+    pub text: String,
+    pub range: Range,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DidChangeEmbeddedDocumentsParams {
+    pub text_document: TextDocumentIdentifier,
+    pub documents: Vec<EmbeddedDocument>,
+}
+
+pub fn find_embedded_documents(
+    request: FeatureRequest<EmbeddedDocumentsParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<EmbeddedDocument>> {
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    cancellation_token.result().ok()?;
+    Some(extract_embedded_documents(main_document))
+}
+
+pub fn extract_embedded_documents(document: &Document) -> Vec<EmbeddedDocument> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    let mut documents: Vec<_> = data
+        .root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .filter_map(|environment| make_embedded_document(document, environment))
+        .chain(
+            latex::find_lua_chunks(&document.text)
+                .into_iter()
+                .map(|range| EmbeddedDocument {
+                    language_id: "lua".to_string(),
+                    text: document.text[range].to_string(),
+                    range: document.line_index.line_col_lsp_range(range),
+                }),
+        )
+        .collect();
+
+    documents.sort_by_key(|document| (document.range.start.line, document.range.start.character));
+    documents
+}
+
+fn make_embedded_document(
+    document: &Document,
+    environment: latex::Environment,
+) -> Option<EmbeddedDocument> {
+    let begin = environment.begin()?;
+    let text = &document.text;
+    let mut start = usize::from(begin.small_range().end());
+    let language = match begin.name()?.key()?.to_string().as_str() {
+        "minted" => {
+            let rest = &text[start..];
+            let argument = rest.trim_start_matches(|c: char| c == ' ' || c == '\t');
+            let argument = argument.strip_prefix('{')?;
+            let length = argument.find('}')?;
+            start += rest.len() - argument.len() + length + 1;
+            argument[..length].trim().to_string()
+        }
+        "lstlisting" => begin
+            .options()
+            .and_then(|options| options.content_text())
+            .and_then(|options| find_listing_language(&options))
+            .unwrap_or_default(),
+        _ => return None,
+    };
+
+    let line_end = text[start..]
+        .find('\n')
+        .map_or(text.len(), |i| start + i + 1);
+    if text[start..line_end].trim().is_empty() {
+        start = line_end;
+    }
+
+    let end = usize::from(environment.end()?.small_range().start());
+    if start > end {
+        return None;
+    }
+
+    let range = TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32));
+    Some(EmbeddedDocument {
+        language_id: normalize_language(&language),
+        text: text[range].to_string(),
+        range: document.line_index.line_col_lsp_range(range),
+    })
+}
+
+fn find_listing_language(options: &str) -> Option<String> {
+    options
+        .split(',')
+        .filter_map(|option| option.split_once('='))
+        .find(|(key, _)| key.trim() == "language")
+        .map(|(_, value)| {
+            let value = value.trim().trim_start_matches('{').trim_end_matches('}');
+            match value.split_once(']') {
+                Some((dialect, language)) if dialect.starts_with('[') => {
+                    format!("{}{}", &dialect[1..], language)
+                }
+                _ => value.to_string(),
+            }
+        })
+}
+
+fn normalize_language(language: &str) -> String {
+    let language = language.trim().to_lowercase();
+    match language.as_str() {
+        "" | "text" => "plaintext",
+        "py" | "py3" | "python3" | "ipython" => "python",
+        "js" => "javascript",
+        "ts" => "typescript",
+        "sh" | "bash" | "zsh" => "shellscript",
+        "c++" | "cxx" => "cpp",
+        "c#" | "sharpc" => "csharp",
+        "rs" => "rust",
+        "rb" => "ruby",
+        "hs" => "haskell",
+        "yml" => "yaml",
+        "tex" => "latex",
+        _ => return language,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .build()
+            .embedded_documents();
+
+        let actual = find_embedded_documents(request, CancellationToken::none()).unwrap();
+        assert!(actual.is_empty());
+    }
+
+    #[test]
+    fn test_minted() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{minted}[linenos]{py}
+                def foo():
+                    pass
+                \end{minted}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .embedded_documents();
+
+        let actual = find_embedded_documents(request, CancellationToken::none()).unwrap();
+        let expected = vec![EmbeddedDocument {
+            language_id: "python".into(),
+            text: "def foo():\n    pass\n".into(),
+            range: Range::new_simple(1, 0, 3, 0),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_lstlisting() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{lstlisting}[caption=Foo, language={[Sharp]C}]
+                var x = 1;
+                \end{lstlisting}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .embedded_documents();
+
+        let actual = find_embedded_documents(request, CancellationToken::none()).unwrap();
+        let expected = vec![EmbeddedDocument {
+            language_id: "csharp".into(),
+            text: "var x = 1;\n".into(),
+            range: Range::new_simple(1, 0, 2, 0),
+        }];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_lua() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \directlua{tex.print(1)}
+                \begin{luacode}
+                x = 1 % 2
+                \end{luacode}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .embedded_documents();
+
+        let actual = find_embedded_documents(request, CancellationToken::none()).unwrap();
+        let expected = vec![
+            EmbeddedDocument {
+                language_id: "lua".into(),
+                text: "tex.print(1)".into(),
+                range: Range::new_simple(0, 11, 0, 23),
+            },
+            EmbeddedDocument {
+                language_id: "lua".into(),
+                text: "\nx = 1 % 2\n".into(),
+                range: Range::new_simple(1, 15, 3, 0),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_other_environment() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\begin{verbatim}foo\end{verbatim}"#)])
+            .main("main.tex")
+            .build()
+            .embedded_documents();
+
+        let actual = find_embedded_documents(request, CancellationToken::none()).unwrap();
+        assert!(actual.is_empty());
+    }
+}
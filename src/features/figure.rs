@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use cancellation::CancellationToken;
+use lsp_types::{TextDocumentPositionParams, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    indentation,
+    syntax::latex::{self, HasCurly},
+};
+
+use super::{matrix::escape_snippet, normalize_labels::slugify, FeatureRequest};
+
+pub const INSERT_FIGURE_COMMAND: &str = "texlab.insertFigure";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertFigureParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+
+    pub image: Url,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FigureSnippet {
+    pub snippet: String,
+}
+
+pub fn create_figure_snippet(
+    request: FeatureRequest<InsertFigureParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<FigureSnippet> {
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    let image = request.params.image.to_file_path().ok()?;
+    cancellation_token.result().ok()?;
+
+    let base_dir = find_base_directory(&request)?;
+    let graphics_paths = find_graphics_paths(&request);
+    let path = graphics_paths
+        .iter()
+        .map(|graphics_path| base_dir.join(graphics_path))
+        .chain(std::iter::once(base_dir.clone()))
+        .find_map(|dir| image.strip_prefix(dir).ok())
+        .unwrap_or(&image);
+
+    let path = path.to_str()?.replace('\\', "/");
+    let label = slugify(image.file_stem()?.to_str()?);
+    let unit = indentation::unit(main_document);
+    let snippet = format!(
+        "{}\n{unit}{}\n{unit}{}\n{unit}{}${{1}}}}\n{unit}{}${{2:{}}}}}\n{}$0",
+        escape_snippet("\\begin{figure}"),
+        escape_snippet("\\centering"),
+        escape_snippet(&format!("\\includegraphics{{{}}}", path)),
+        escape_snippet("\\caption{"),
+        escape_snippet("\\label{fig:"),
+        escape_snippet(&label),
+        escape_snippet("\\end{figure}"),
+        unit = unit,
+    );
+
+    Some(FigureSnippet { snippet })
+}
+
+/// Returns the directory that the image paths are relative to.
+fn find_base_directory(request: &FeatureRequest<InsertFigureParams>) -> Option<PathBuf> {
+    let options = request.context.options.read().unwrap();
+    if let Some(root_directory) = &options.root_directory {
+        return Some(request.context.current_directory.join(root_directory));
+    }
+
+    let root_document = request
+        .subset
+        .documents
+        .iter()
+        .find(|document| {
+            document
+                .data
+                .as_latex()
+                .map_or(false, |data| data.extras.has_document_environment)
+        })
+        .unwrap_or_else(|| request.main_document());
+
+    let path = root_document.uri.to_file_path().ok()?;
+    path.parent().map(Path::to_path_buf)
+}
+
+/// Finds the directories that are declared with `\graphicspath{{dir1/}{dir2/}}`.
+fn find_graphics_paths(request: &FeatureRequest<InsertFigureParams>) -> Vec<String> {
+    let mut paths = Vec::new();
+    for data in request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for command in data
+            .root
+            .descendants()
+            .filter_map(latex::GenericCommand::cast)
+            .filter(|command| command.name().map(|name| name.text()) == Some("\\graphicspath"))
+        {
+            let group = command
+                .syntax()
+                .children()
+                .find_map(latex::CurlyGroup::cast);
+
+            for dir in group
+                .into_iter()
+                .flat_map(|group| {
+                    group
+                        .syntax()
+                        .children()
+                        .filter_map(latex::CurlyGroup::cast)
+                })
+                .filter_map(|dir| dir.content_text())
+            {
+                paths.push(dir);
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, TextDocumentIdentifier};
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn create_snippet(text: &str, image: &str) -> Option<String> {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .build();
+
+        let uri = tester.uri("main.tex").as_ref().clone().into();
+        let image = tester.uri(image).as_ref().clone().into();
+        let request = tester.insert_figure(InsertFigureParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri),
+                Position::new(0, 0),
+            ),
+            image,
+        });
+
+        create_figure_snippet(request, CancellationToken::none()).map(|result| result.snippet)
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            create_snippet("", "images/Flow Chart.png").as_deref(),
+            Some(
+                [
+                    "\\\\begin{figure}",
+                    "\t\\\\centering",
+                    "\t\\\\includegraphics{images/Flow Chart.png}",
+                    "\t\\\\caption{${1}}",
+                    "\t\\\\label{fig:${2:flow-chart}}",
+                    "\\\\end{figure}$0",
+                ]
+                .join("\n")
+                .as_str()
+            )
+        );
+    }
+
+    #[test]
+    fn test_graphics_path() {
+        let snippet = create_snippet(
+            r#"\graphicspath{{figures/}{images/}}"#,
+            "images/plots/result.pdf",
+        )
+        .unwrap();
+
+        assert!(snippet.contains("\\\\includegraphics{plots/result.pdf}"));
+    }
+}
@@ -0,0 +1,289 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use cancellation::CancellationToken;
+use lsp_types::{
+    CreateFile, CreateFileOptions, CreateFilesParams, DocumentChangeOperation, DocumentChanges,
+    OneOf, OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextEdit, Url, WorkspaceEdit,
+};
+
+use crate::{syntax::latex, Workspace};
+
+const CHAPTER_TEMPLATE: &str = "\\chapter{${name}}\n\\label{chap:${name}}\n\n";
+
+const FIGURE_TEMPLATE: &str = "\\documentclass[tikz]{standalone}\n\n\\begin{document}\n\\begin{tikzpicture}\n\n\\end{tikzpicture}\n\\end{document}\n";
+
+const FRAME_TEMPLATE: &str = "\\begin{frame}{${name}}\n\n\\end{frame}\n";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum TemplateKind {
+    Chapter,
+    Figure,
+    Frame,
+}
+
+impl TemplateKind {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Chapter => "chapter",
+            Self::Figure => "figure",
+            Self::Frame => "frame",
+        }
+    }
+
+    fn builtin_text(self) -> &'static str {
+        match self {
+            Self::Chapter => CHAPTER_TEMPLATE,
+            Self::Figure => FIGURE_TEMPLATE,
+            Self::Frame => FRAME_TEMPLATE,
+        }
+    }
+}
+
+/// Returns the directory that contains the user-defined templates
+/// (for example `~/.config/texlab/templates` on Linux).
+pub fn template_directory() -> Option<PathBuf> {
+    let config_directory = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_directory.join("texlab").join("templates"))
+}
+
+pub fn create_files(
+    workspace: &dyn Workspace,
+    params: &CreateFilesParams,
+    template_directory: Option<&Path>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let mut operations = Vec::new();
+    for file in &params.files {
+        cancellation_token.result().ok()?;
+        let uri = match Url::parse(&file.uri) {
+            Ok(uri) => uri,
+            Err(_) => continue,
+        };
+
+        let path = match uri.to_file_path() {
+            Ok(path) if is_new_tex_file(&path) => path,
+            _ => continue,
+        };
+
+        let class = find_document_class(workspace, &path);
+        let text = match find_template(&path, class.as_deref(), template_directory) {
+            Some(text) => text,
+            None => continue,
+        };
+
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+            CreateFile {
+                uri: uri.clone(),
+                options: Some(CreateFileOptions {
+                    overwrite: Some(false),
+                    ignore_if_exists: Some(true),
+                }),
+                annotation_id: None,
+            },
+        )));
+
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: vec![OneOf::Left(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                text,
+            ))],
+        }));
+    }
+
+    if operations.is_empty() {
+        None
+    } else {
+        Some(WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            change_annotations: None,
+        })
+    }
+}
+
+fn is_new_tex_file(path: &Path) -> bool {
+    let is_tex = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map_or(false, |extension| extension.eq_ignore_ascii_case("tex"));
+
+    is_tex
+        && fs::metadata(path)
+            .map(|metadata| metadata.len() == 0)
+            .unwrap_or(true)
+}
+
+/// Finds the class of the closest root document that lives
+/// in one of the ancestor directories of the given path.
+fn find_document_class(workspace: &dyn Workspace, path: &Path) -> Option<String> {
+    workspace
+        .documents()
+        .into_iter()
+        .filter_map(|document| {
+            let directory = document.uri.to_file_path().ok()?.parent()?.to_path_buf();
+            if !path.starts_with(&directory) {
+                return None;
+            }
+
+            let class = document
+                .data
+                .as_latex()?
+                .extras
+                .explicit_links
+                .iter()
+                .find(|link| link.kind == latex::ExplicitLinkKind::Class)?
+                .stem
+                .to_string();
+
+            Some((directory.components().count(), class))
+        })
+        .max_by_key(|(depth, _)| *depth)
+        .map(|(_, class)| class)
+}
+
+fn find_template_kind(directory_name: &str, class: Option<&str>) -> Option<TemplateKind> {
+    match directory_name.to_lowercase().as_str() {
+        "figures" | "figure" | "figs" | "fig" | "tikz" => return Some(TemplateKind::Figure),
+        "frames" | "slides" => return Some(TemplateKind::Frame),
+        "chapters" | "chapter" => return Some(TemplateKind::Chapter),
+        _ => {}
+    }
+
+    match class? {
+        "beamer" => Some(TemplateKind::Frame),
+        "book" | "report" | "memoir" | "scrbook" | "scrreprt" => Some(TemplateKind::Chapter),
+        _ => None,
+    }
+}
+
+fn find_template(
+    path: &Path,
+    class: Option<&str>,
+    template_directory: Option<&Path>,
+) -> Option<String> {
+    let name = path.file_stem()?.to_str()?;
+    let directory_name = path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or_default();
+
+    let kind = find_template_kind(directory_name, class);
+    let user_text = template_directory.and_then(|template_dir| {
+        let mut candidates = Vec::new();
+        if !directory_name.is_empty() {
+            candidates.push(template_dir.join(format!("{}.tex", directory_name)));
+        }
+
+        if let Some(kind) = kind {
+            candidates.push(template_dir.join(format!("{}.tex", kind.name())));
+        }
+
+        candidates
+            .into_iter()
+            .filter(|candidate| candidate.is_file())
+            .find_map(|candidate| fs::read_to_string(candidate).ok())
+    });
+
+    let text = user_text.or_else(|| kind.map(|kind| kind.builtin_text().to_string()))?;
+    Some(text.replace("${name}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::FileCreate;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn create_text(tester: &FeatureTester, path: &str) -> Option<String> {
+        let params = CreateFilesParams {
+            files: vec![FileCreate {
+                uri: tester.uri(path).as_str().into(),
+            }],
+        };
+
+        let request = tester.link();
+        let edit = create_files(
+            request.workspace.as_ref(),
+            &params,
+            None,
+            CancellationToken::none(),
+        )?;
+
+        match edit.document_changes {
+            Some(DocumentChanges::Operations(operations)) => {
+                operations
+                    .into_iter()
+                    .find_map(|operation| match operation {
+                        DocumentChangeOperation::Edit(mut edit) => match edit.edits.pop()? {
+                            OneOf::Left(edit) => Some(edit.new_text),
+                            OneOf::Right(edit) => Some(edit.text_edit.new_text),
+                        },
+                        DocumentChangeOperation::Op(_) => None,
+                    })
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_chapter() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\documentclass{book}"#)])
+            .main("main.tex")
+            .build();
+
+        assert_eq!(
+            create_text(&tester, "chapters/intro.tex").as_deref(),
+            Some("\\chapter{intro}\n\\label{chap:intro}\n\n")
+        );
+    }
+
+    #[test]
+    fn test_figure() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\documentclass{book}"#)])
+            .main("main.tex")
+            .build();
+
+        assert_eq!(
+            create_text(&tester, "figures/plot.tex").as_deref(),
+            Some(FIGURE_TEMPLATE)
+        );
+    }
+
+    #[test]
+    fn test_beamer_frame() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\documentclass{beamer}"#)])
+            .main("main.tex")
+            .build();
+
+        assert_eq!(
+            create_text(&tester, "results.tex").as_deref(),
+            Some("\\begin{frame}{results}\n\n\\end{frame}\n")
+        );
+    }
+
+    #[test]
+    fn test_article() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\documentclass{article}"#)])
+            .main("main.tex")
+            .build();
+
+        assert_eq!(create_text(&tester, "foo.tex"), None);
+    }
+}
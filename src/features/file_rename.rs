@@ -0,0 +1,240 @@
+use std::path::{Component, Path, PathBuf};
+
+use cancellation::CancellationToken;
+use lsp_types::{RenameFilesParams, TextEdit, Url, WorkspaceEdit};
+
+use crate::{
+    features::workspace_edit::AnnotatedWorkspaceEdit,
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, ServerContext, Workspace,
+};
+
+#[derive(Debug)]
+struct FileMove {
+    old_path: PathBuf,
+    new_path: PathBuf,
+}
+
+impl FileMove {
+    fn apply(&self, path: &Path) -> Option<PathBuf> {
+        if path == self.old_path {
+            Some(self.new_path.clone())
+        } else {
+            let suffix = path.strip_prefix(&self.old_path).ok()?;
+            Some(self.new_path.join(suffix))
+        }
+    }
+}
+
+pub fn rename_files(
+    context: &ServerContext,
+    workspace: &dyn Workspace,
+    params: &RenameFilesParams,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let moves: Vec<_> = params
+        .files
+        .iter()
+        .filter_map(|file| {
+            let old_path = Url::parse(&file.old_uri).ok()?.to_file_path().ok()?;
+            let new_path = Url::parse(&file.new_uri).ok()?.to_file_path().ok()?;
+            Some(FileMove { old_path, new_path })
+        })
+        .collect();
+
+    let root_directory = context.options.read().unwrap().root_directory.clone();
+
+    let mut edit = AnnotatedWorkspaceEdit::default();
+    edit.annotate(
+        "include",
+        "Update the paths of the renamed files",
+        None,
+        false,
+    );
+    for document in workspace.documents() {
+        cancellation_token.result().ok()?;
+        for include_edit in find_include_edits(&document, root_directory.as_deref(), &moves) {
+            edit.push(
+                document.uri.as_ref().clone().into(),
+                "include",
+                include_edit,
+            );
+        }
+    }
+
+    if edit.is_empty() {
+        None
+    } else {
+        Some(edit.finish(&context.client_capabilities.lock().unwrap()))
+    }
+}
+
+fn find_include_edits(
+    document: &Document,
+    root_directory: Option<&Path>,
+    moves: &[FileMove],
+) -> Vec<TextEdit> {
+    let mut edits = Vec::new();
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return edits,
+    };
+
+    let base_directory = match root_directory
+        .map(Path::to_path_buf)
+        .or_else(|| Some(document.uri.to_file_path().ok()?.parent()?.to_path_buf()))
+    {
+        Some(base_directory) => base_directory,
+        None => return edits,
+    };
+
+    for include in data.root.descendants().filter_map(latex::Include::cast) {
+        let extensions: &[&str] = match include.syntax().kind() {
+            latex::LATEX_INCLUDE => &["tex"],
+            latex::BIBLATEX_INCLUDE | latex::BIBTEX_INCLUDE => &["bib"],
+            latex::GRAPHICS_INCLUDE => &["pdf", "png", "jpg", "jpeg", "bmp"],
+            latex::SVG_INCLUDE => &["svg"],
+            latex::INKSCAPE_INCLUDE => &["pdf", "eps", "ps", "png"],
+            latex::VERBATIM_INCLUDE => &[],
+            _ => continue,
+        };
+
+        for path in include
+            .path_list()
+            .into_iter()
+            .flat_map(|paths| paths.keys())
+        {
+            let text = path.to_string();
+            if let Some(new_text) = rename_include(&base_directory, &text, extensions, moves) {
+                edits.push(TextEdit::new(
+                    document.line_index.line_col_lsp_range(path.small_range()),
+                    new_text,
+                ));
+            }
+        }
+    }
+
+    edits
+}
+
+fn rename_include(
+    base_directory: &Path,
+    text: &str,
+    extensions: &[&str],
+    moves: &[FileMove],
+) -> Option<String> {
+    let old_path = normalize(&base_directory.join(text));
+    if let Some(new_path) = moves.iter().find_map(|file| file.apply(&old_path)) {
+        return relative_path(base_directory, &new_path);
+    }
+
+    for extension in extensions {
+        let old_path = normalize(&base_directory.join(format!("{}.{}", text, extension)));
+        if let Some(new_path) = moves.iter().find_map(|file| file.apply(&old_path)) {
+            let new_text = relative_path(base_directory, &new_path)?;
+            let stem = new_text
+                .strip_suffix(extension)
+                .and_then(|stem| stem.strip_suffix('.'));
+
+            return Some(stem.map(ToString::to_string).unwrap_or(new_text));
+        }
+    }
+
+    None
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                result.pop();
+            }
+            component => result.push(component),
+        }
+    }
+    result
+}
+
+fn relative_path(base_directory: &Path, path: &Path) -> Option<String> {
+    let base: Vec<_> = base_directory.components().collect();
+    let target: Vec<_> = path.components().collect();
+    let common_len = base.iter().zip(&target).take_while(|(a, b)| a == b).count();
+
+    if common_len == 0 {
+        return None;
+    }
+
+    let mut segments = Vec::new();
+    for _ in common_len..base.len() {
+        segments.push("..");
+    }
+
+    for component in &target[common_len..] {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_str()?),
+            _ => return None,
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use lsp_types::{FileRename, Range};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_rename_files() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    r#"\include{chapters/foo}\addbibresource{refs.bib}\includegraphics{foo.png}"#,
+                ),
+                ("chapters/foo.tex", r#"\input{../foo.png}"#),
+            ])
+            .main("main.tex")
+            .build();
+
+        let main_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let params = RenameFilesParams {
+            files: vec![
+                FileRename {
+                    old_uri: tester.uri("chapters").as_str().into(),
+                    new_uri: tester.uri("parts").as_str().into(),
+                },
+                FileRename {
+                    old_uri: tester.uri("refs.bib").as_str().into(),
+                    new_uri: tester.uri("bib/references.bib").as_str().into(),
+                },
+            ],
+        };
+
+        let request = tester.link();
+        let actual_edit = rename_files(
+            &request.context,
+            request.workspace.as_ref(),
+            &params,
+            CancellationToken::none(),
+        )
+        .unwrap();
+
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(
+            main_uri,
+            vec![
+                TextEdit::new(Range::new_simple(0, 9, 0, 21), "parts/foo".into()),
+                TextEdit::new(Range::new_simple(0, 38, 0, 46), "bib/references.bib".into()),
+            ],
+        );
+        assert_eq!(actual_edit, WorkspaceEdit::new(expected_changes));
+    }
+}
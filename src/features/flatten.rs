@@ -0,0 +1,411 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{TextDocumentIdentifier, Url};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encode_text,
+    syntax::{latex, CstNode},
+    Document, LatexDocumentData, Uri,
+};
+
+use super::{is_nested_path, FeatureRequest};
+
+pub const FLATTEN_PROJECT_COMMAND: &str = "texlab.flattenProject";
+
+const GRAPHICS_EXTENSIONS: &[&str] = &["pdf", "png", "jpg", "jpeg", "eps"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlattenProjectParams {
+    pub text_document: TextDocumentIdentifier,
+
+    /// The directory of the bundle (relative to the parent of the project directory).
+    #[serde(default)]
+    pub output_directory: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FlattenProjectResult {
+    pub main_file: Url,
+    pub copied_files: Vec<Url>,
+    pub warnings: Vec<String>,
+}
+
+/// Writes the root document of the project into a new directory after inlining
+/// the included files and the generated bibliography. Images and bibliography
+/// databases are copied next to it so that the directory can be submitted as is.
+pub fn flatten_project(
+    request: FeatureRequest<FlattenProjectParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<FlattenProjectResult> {
+    let root_document = request.root_document();
+
+    let root_path = root_document.uri.to_file_path().ok()?;
+    let base_directory = root_path.parent()?.to_path_buf();
+    let output_directory =
+        find_output_directory(&base_directory, request.params.output_directory.as_deref())?;
+
+    let mut flattener = Flattener::new(&request, base_directory);
+    let text = flattener.flatten(root_document, cancellation_token)?;
+
+    if let Err(why) = fs::create_dir_all(&output_directory) {
+        log::error!(
+            "Failed to create directory {}: {}",
+            output_directory.display(),
+            why
+        );
+        return None;
+    }
+
+    let main_path = output_directory.join(root_path.file_name()?);
+    if let Err(why) = fs::write(&main_path, encode_text(&text, root_document.encoding)) {
+        log::error!("Failed to write {}: {}", main_path.display(), why);
+        return None;
+    }
+
+    let mut copied_files = Vec::new();
+    for (source, name) in flattener.files {
+        let target = output_directory.join(name);
+        match fs::copy(&source, &target) {
+            Ok(_) => copied_files.extend(Url::from_file_path(target).ok()),
+            Err(why) => {
+                flattener
+                    .warnings
+                    .push(format!("Failed to copy {}: {}", source.display(), why))
+            }
+        }
+    }
+
+    Some(FlattenProjectResult {
+        main_file: Url::from_file_path(main_path).ok()?,
+        copied_files,
+        warnings: flattener.warnings,
+    })
+}
+
+/// Returns the directory of the bundle next to the project directory
+/// so that the flattened document does not become part of the project.
+/// Absolute paths and paths that lead back into the project are rejected.
+fn find_output_directory(
+    project_directory: &Path,
+    output_directory: Option<&Path>,
+) -> Option<PathBuf> {
+    let parent_directory = project_directory.parent()?;
+    let output_directory = match output_directory {
+        Some(directory) if is_nested_path(directory) => parent_directory.join(directory),
+        Some(directory) => {
+            log::warn!("Invalid output directory {}", directory.display());
+            return None;
+        }
+        None => parent_directory.join(format!(
+            "{}-flat",
+            project_directory.file_name()?.to_string_lossy()
+        )),
+    };
+
+    if output_directory.starts_with(project_directory) {
+        log::warn!(
+            "The output directory {} is inside of the project",
+            output_directory.display()
+        );
+        return None;
+    }
+
+    Some(output_directory)
+}
+
+struct Flattener<'a> {
+    request: &'a FeatureRequest<FlattenProjectParams>,
+    base_directory: PathBuf,
+    /// The documents that are currently being inlined.
+    include_stack: FxHashSet<Arc<Uri>>,
+    files: Vec<(PathBuf, String)>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Flattener<'a> {
+    fn new(request: &'a FeatureRequest<FlattenProjectParams>, base_directory: PathBuf) -> Self {
+        Self {
+            request,
+            base_directory,
+            include_stack: FxHashSet::default(),
+            files: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Returns the text of the document with all of its includes replaced.
+    fn flatten(
+        &mut self,
+        document: &Document,
+        cancellation_token: &CancellationToken,
+    ) -> Option<String> {
+        cancellation_token.result().ok()?;
+
+        let data = document.data.as_latex()?;
+        self.include_stack.insert(Arc::clone(&document.uri));
+
+        let mut edits: Vec<(TextRange, String)> = Vec::new();
+        for include in data.root.descendants().filter_map(latex::Include::cast) {
+            match include.syntax().kind() {
+                latex::LATEX_INCLUDE => {
+                    if let Some(text) = self.inline_documents(data, include, cancellation_token) {
+                        edits.push((include.small_range(), text));
+                    }
+                }
+                latex::BIBTEX_INCLUDE => {
+                    if let Some(text) = self.inline_bibliography(document) {
+                        edits.push((include.small_range(), text));
+                    }
+                }
+                latex::BIBLATEX_INCLUDE | latex::GRAPHICS_INCLUDE => {
+                    let extensions = if include.syntax().kind() == latex::GRAPHICS_INCLUDE {
+                        GRAPHICS_EXTENSIONS
+                    } else {
+                        &["bib"]
+                    };
+
+                    for path in include
+                        .path_list()
+                        .into_iter()
+                        .flat_map(|paths| paths.keys())
+                    {
+                        if let Some(name) = self.copy_file(&path.to_string(), extensions) {
+                            edits.push((path.small_range(), name));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut text = document.text.clone();
+        edits.sort_by_key(|(range, _)| range.start());
+        for (range, replacement) in edits.into_iter().rev() {
+            text.replace_range(
+                usize::from(range.start())..usize::from(range.end()),
+                &replacement,
+            );
+        }
+
+        self.include_stack.remove(&document.uri);
+        Some(text)
+    }
+
+    fn inline_documents(
+        &mut self,
+        data: &LatexDocumentData,
+        include: latex::Include,
+        cancellation_token: &CancellationToken,
+    ) -> Option<String> {
+        let mut texts = Vec::new();
+        for path in include.path_list()?.keys() {
+            let child = data
+                .extras
+                .explicit_links
+                .iter()
+                .find(|link| link.stem_range == path.small_range())
+                .and_then(|link| {
+                    link.targets
+                        .iter()
+                        .find_map(|target| self.request.workspace.get(target))
+                })
+                .filter(|child| child.data.as_latex().is_some());
+
+            match child {
+                Some(child) if !self.include_stack.contains(&child.uri) => {
+                    texts.push(self.flatten(&child, cancellation_token)?);
+                }
+                Some(_) => {
+                    self.warnings.push(format!(
+                        "Skipped the recursive inclusion of `{}`",
+                        path.to_string()
+                    ));
+                    return None;
+                }
+                None => {
+                    self.warnings.push(format!(
+                        "Could not find the included file `{}`",
+                        path.to_string()
+                    ));
+                    return None;
+                }
+            }
+        }
+
+        let text = texts.join("\n");
+        if include.command()?.text() == "\\include" {
+            Some(format!("\\clearpage\n{}\n\\clearpage", text))
+        } else {
+            Some(text)
+        }
+    }
+
+    /// Replaces `\bibliography` with the content of the `.bbl` file of the last build.
+    fn inline_bibliography(&mut self, document: &Document) -> Option<String> {
+        let text = self
+            .request
+            .subset
+            .documents
+            .iter()
+            .filter_map(|document| document.data.as_latex())
+            .flat_map(|data| data.extras.implicit_links.aux.iter())
+            .filter_map(|uri| uri.to_file_path().ok())
+            .find_map(|path| fs::read_to_string(path.with_extension("bbl")).ok());
+
+        match text {
+            Some(text) => Some(text.trim_end().to_string()),
+            None => {
+                self.warnings.push(format!(
+                    "Could not find the generated bibliography of {}; build the project first",
+                    document.uri.as_str()
+                ));
+                None
+            }
+        }
+    }
+
+    /// Schedules a file to be copied into the bundle and returns its new path.
+    fn copy_file(&mut self, path: &str, extensions: &[&str]) -> Option<String> {
+        let source = std::iter::once((self.base_directory.join(path), String::new()))
+            .chain(extensions.iter().map(|extension| {
+                (
+                    self.base_directory.join(format!("{}.{}", path, extension)),
+                    format!(".{}", extension),
+                )
+            }))
+            .find(|(source, _)| source.is_file());
+
+        let (source, suffix) = match source {
+            Some(source) => source,
+            None => {
+                self.warnings
+                    .push(format!("Could not find the file `{}`", path));
+                return None;
+            }
+        };
+
+        let name = flat_name(Path::new(path));
+        let file_name = format!("{}{}", name, suffix);
+        if !self
+            .files
+            .iter()
+            .any(|(_, other_name)| *other_name == file_name)
+        {
+            self.files.push((source, file_name));
+        }
+        Some(name)
+    }
+}
+
+/// Removes the directories of a relative path by joining its components with dashes.
+fn flat_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            std::path::Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_inline_includes() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    "\\documentclass{article}\n\\begin{document}\n\\include{chapter}\n\\end{document}",
+                ),
+                ("chapter.tex", "Foo\\input{section}"),
+                ("section.tex", "Bar"),
+            ])
+            .main("main.tex")
+            .build();
+
+        let base_directory = tester.uri("main.tex").to_file_path().unwrap();
+        let request = tester.flatten_project();
+        let root_document = request.main_document();
+        let mut flattener = Flattener::new(&request, base_directory.parent().unwrap().into());
+        let text = flattener
+            .flatten(root_document, CancellationToken::none())
+            .unwrap();
+
+        assert_eq!(
+            text,
+            "\\documentclass{article}\n\\begin{document}\n\\clearpage\nFooBar\n\\clearpage\n\\end{document}"
+        );
+        assert!(flattener.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_missing_graphics() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", "\\includegraphics{images/foo}")])
+            .main("main.tex")
+            .build();
+
+        let base_directory = tester.uri("main.tex").to_file_path().unwrap();
+        let request = tester.flatten_project();
+        let root_document = request.main_document();
+        let mut flattener = Flattener::new(&request, base_directory.parent().unwrap().into());
+        let text = flattener
+            .flatten(root_document, CancellationToken::none())
+            .unwrap();
+
+        assert_eq!(text, "\\includegraphics{images/foo}");
+        assert_eq!(flattener.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_default_output_directory() {
+        let project_directory = Path::new("/home/foo/paper");
+        assert_eq!(
+            find_output_directory(project_directory, None),
+            Some(PathBuf::from("/home/foo/paper-flat"))
+        );
+    }
+
+    #[test]
+    fn test_output_directory() {
+        let project_directory = Path::new("/home/foo/paper");
+        assert_eq!(
+            find_output_directory(project_directory, Some(Path::new("arxiv/paper"))),
+            Some(PathBuf::from("/home/foo/arxiv/paper"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_output_directory() {
+        let project_directory = Path::new("/home/foo/paper");
+        for directory in ["/tmp/paper", "../paper-flat", "paper/flat", "./paper"] {
+            assert_eq!(
+                find_output_directory(project_directory, Some(Path::new(directory))),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_flat_name() {
+        assert_eq!(
+            flat_name(Path::new("./images/../plots/foo")),
+            "images-plots-foo"
+        );
+    }
+}
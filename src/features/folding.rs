@@ -1,13 +1,31 @@
 use cancellation::CancellationToken;
-use lsp_types::{FoldingRange, FoldingRangeKind, FoldingRangeParams, Range};
+use cstree::{TextRange, TextSize};
+use lsp_types::{
+    FoldingRange, FoldingRangeKind, FoldingRangeParams, Range, TextDocumentIdentifier,
+};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     syntax::{bibtex, latex, CstNode},
-    DocumentData, LineIndexExt,
+    Document, DocumentData, LineIndexExt,
 };
 
 use super::FeatureRequest;
 
+pub const FOLD_ALL_ENVIRONMENTS_COMMAND: &str = "texlab.foldAllEnvironments";
+
+pub const FOLD_ALL_COMMENTS_COMMAND: &str = "texlab.foldAllComments";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FoldAllParams {
+    pub text_document: TextDocumentIdentifier,
+
+    /// Overrides the environments of the `texlab.folding.environments` option.
+    #[serde(default)]
+    pub environments: Option<Vec<String>>,
+}
+
 pub fn find_foldings(
     request: FeatureRequest<FoldingRangeParams>,
     token: &CancellationToken,
@@ -31,6 +49,8 @@ pub fn find_foldings(
                     foldings.push(folding);
                 }
             }
+
+            foldings.extend(find_comment_foldings(main_document));
         }
         DocumentData::Bibtex(data) => {
             for node in data.root.descendants() {
@@ -49,11 +69,119 @@ pub fn find_foldings(
                 }
             }
         }
-        DocumentData::BuildLog(_) => {}
+        DocumentData::BuildLog(_) | DocumentData::Markdown(_) => {}
     }
     foldings
 }
 
+/// Returns the foldings of the environments that should be folded in bulk
+/// (for example, when opening a long document).
+pub fn fold_all_environments(
+    request: FeatureRequest<FoldAllParams>,
+    _cancellation_token: &CancellationToken,
+) -> Vec<FoldingRange> {
+    let environments = request.params.environments.clone().unwrap_or_else(|| {
+        let options = request.context.options.read().unwrap();
+        options.folding.environments.clone()
+    });
+
+    find_environment_foldings(request.main_document(), &environments)
+}
+
+pub fn fold_all_comments(
+    request: FeatureRequest<FoldAllParams>,
+    _cancellation_token: &CancellationToken,
+) -> Vec<FoldingRange> {
+    find_comment_foldings(request.main_document())
+}
+
+/// Folds the given environments or all environments except `document` if none are given.
+fn find_environment_foldings(document: &Document, environments: &[String]) -> Vec<FoldingRange> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    data.root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .filter(|environment| {
+            let name = environment
+                .begin()
+                .and_then(|begin| begin.name())
+                .and_then(|name| name.key())
+                .map(|name| name.to_string());
+
+            match name.as_deref() {
+                Some(name) if environments.is_empty() => name != "document",
+                Some(name) => environments.iter().any(|environment| environment == name),
+                None => false,
+            }
+        })
+        .map(|environment| {
+            document
+                .line_index
+                .line_col_lsp_range(environment.small_range())
+        })
+        .map(create_range)
+        .collect()
+}
+
+/// Folds the blocks of at least two consecutive comment lines.
+fn find_comment_foldings(document: &Document) -> Vec<FoldingRange> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    let mut blocks: Vec<TextRange> = Vec::new();
+    let mut last_line = None;
+    for token in data
+        .root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == latex::COMMENT)
+    {
+        let range = token.text_range();
+        if data
+            .extras
+            .code_chunks
+            .iter()
+            .any(|chunk| chunk.contains_range(range))
+        {
+            continue;
+        }
+
+        let line_col = document.line_index.line_col(range.start());
+        let line_start = range.start() - TextSize::from(line_col.col);
+        if !document.text[TextRange::new(line_start, range.start())]
+            .trim()
+            .is_empty()
+        {
+            continue;
+        }
+
+        match blocks.last_mut() {
+            Some(block) if last_line.map_or(false, |line| line + 1 == line_col.line) => {
+                *block = block.cover(range);
+            }
+            _ => blocks.push(range),
+        }
+
+        last_line = Some(line_col.line);
+    }
+
+    blocks
+        .into_iter()
+        .map(|block| document.line_index.line_col_lsp_range(block))
+        .filter(|range| range.start.line < range.end.line)
+        .map(|range| FoldingRange {
+            kind: Some(FoldingRangeKind::Comment),
+            ..create_range(range)
+        })
+        .collect()
+}
+
 fn create_range(range: Range) -> FoldingRange {
     FoldingRange {
         start_line: range.start.line,
@@ -234,4 +362,45 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_fold_all() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                % foo
+                % bar
+                \begin{document}
+                \begin{proof}
+                    % baz
+                \end{proof}
+                \begin{figure}
+                    % qux
+                    % quux
+                \end{figure}
+                \end{document}"# },
+            )])
+            .main("main.tex")
+            .build()
+            .folding();
+
+        let document = request.main_document();
+        let lines = |foldings: Vec<FoldingRange>| {
+            foldings
+                .into_iter()
+                .map(|folding| (folding.start_line, folding.end_line))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            lines(find_environment_foldings(document, &[])),
+            vec![(3, 5), (6, 9)]
+        );
+        assert_eq!(
+            lines(find_environment_foldings(document, &["figure".to_string()])),
+            vec![(6, 9)]
+        );
+        assert_eq!(lines(find_comment_foldings(document)), vec![(0, 1), (7, 8)]);
+    }
 }
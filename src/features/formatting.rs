@@ -1,12 +1,18 @@
 mod bibtex_internal;
+mod diff;
 mod latexindent;
 
 use cancellation::CancellationToken;
 use lsp_types::{DocumentFormattingParams, TextEdit};
 
-use crate::{BibtexFormatter, LatexFormatter};
+use crate::{
+    find_whitespace_issues, BibtexFormatter, DocumentLanguage, LatexFormatter, LineIndexExt,
+};
 
-use self::{bibtex_internal::format_bibtex_internal, latexindent::format_with_latexindent};
+use self::{
+    bibtex_internal::format_bibtex_internal, diff::diff_text_edits,
+    latexindent::format_with_latexindent,
+};
 
 use super::FeatureRequest;
 
@@ -24,5 +30,77 @@ pub fn format_source_code(
     }
 
     edits = edits.or_else(|| format_with_latexindent(&request, cancellation_token));
+    if request
+        .context
+        .options
+        .read()
+        .unwrap()
+        .formatter_fix_whitespace
+    {
+        edits = Some(add_whitespace_edits(&request, edits.unwrap_or_default()));
+    }
+
+    edits.map(|edits| minimize_edits(&request, edits))
+}
+
+/// Adds the edits that remove trailing whitespace and mixed line endings
+/// unless they overlap with the edits of the formatter.
+fn add_whitespace_edits(
+    request: &FeatureRequest<DocumentFormattingParams>,
+    mut edits: Vec<TextEdit>,
+) -> Vec<TextEdit> {
+    let document = request.main_document();
+    if !matches!(
+        document.language(),
+        DocumentLanguage::Latex | DocumentLanguage::Bibtex
+    ) {
+        return edits;
+    }
+
+    let formatter_ranges: Vec<_> = edits
+        .iter()
+        .map(|edit| document.line_index.offset_lsp_range(edit.range))
+        .collect();
+
+    for (range, new_text) in find_whitespace_issues(&document).edits() {
+        if formatter_ranges
+            .iter()
+            .all(|other| other.end() < range.start() || range.end() < other.start())
+        {
+            edits.push(TextEdit::new(
+                document.line_index.line_col_lsp_range(range),
+                new_text.to_string(),
+            ));
+        }
+    }
+
     edits
 }
+
+/// Replaces the edits of a formatter with the smallest edits that produce the same text
+/// to preserve the cursor position and the folding state of the editor.
+fn minimize_edits(
+    request: &FeatureRequest<DocumentFormattingParams>,
+    mut edits: Vec<TextEdit>,
+) -> Vec<TextEdit> {
+    let document = request.main_document();
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+
+    let mut new_text = String::new();
+    let mut offset = 0;
+    for edit in &edits {
+        let range =
+            std::ops::Range::<usize>::from(document.line_index.offset_lsp_range(edit.range));
+        new_text.push_str(&document.text[offset..range.start]);
+        new_text.push_str(&edit.new_text);
+        offset = range.end;
+    }
+    new_text.push_str(&document.text[offset..]);
+
+    diff_text_edits(
+        document.language(),
+        &document.line_index,
+        &document.text,
+        &new_text,
+    )
+}
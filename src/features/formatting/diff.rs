@@ -0,0 +1,280 @@
+use cstree::{TextLen, TextRange, TextSize};
+use lsp_types::TextEdit;
+
+use crate::{
+    syntax::{bibtex, latex},
+    DocumentLanguage, LineIndex, LineIndexExt,
+};
+
+/// The maximum number of token insertions and deletions that are searched
+/// before the changed part of the document is replaced as a whole.
+const MAX_DISTANCE: usize = 1000;
+
+/// Computes the edits that turn `old_text` into `new_text` by comparing the tokens of both texts
+/// so that the unchanged parts of the document keep their position in the editor.
+pub fn diff_text_edits(
+    language: DocumentLanguage,
+    line_index: &LineIndex,
+    old_text: &str,
+    new_text: &str,
+) -> Vec<TextEdit> {
+    let old_tokens = tokenize(language, old_text);
+    let new_tokens = tokenize(language, new_text);
+    let old_words: Vec<_> = old_tokens
+        .iter()
+        .map(|range| slice(old_text, *range))
+        .collect();
+    let new_words: Vec<_> = new_tokens
+        .iter()
+        .map(|range| slice(new_text, *range))
+        .collect();
+
+    let prefix = old_words
+        .iter()
+        .zip(&new_words)
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let suffix = old_words[prefix..]
+        .iter()
+        .rev()
+        .zip(new_words[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+
+    let old_end = old_words.len() - suffix;
+    let new_end = new_words.len() - suffix;
+    let mut matches: Vec<_> = (0..prefix).map(|i| (i, i)).collect();
+    matches.extend(
+        find_matches(&old_words[prefix..old_end], &new_words[prefix..new_end])
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(i, j)| (prefix + i, prefix + j)),
+    );
+    matches.extend((0..suffix).map(|i| (old_end + i, new_end + i)));
+    matches.push((old_words.len(), new_words.len()));
+
+    let old_start = |i: usize| old_tokens.get(i).map_or(old_text.text_len(), |t| t.start());
+    let new_start = |j: usize| new_tokens.get(j).map_or(new_text.text_len(), |t| t.start());
+
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (old_index, new_index) in matches {
+        if i < old_index || j < new_index {
+            let old_range = TextRange::new(old_start(i), old_start(old_index));
+            let new_range = TextRange::new(new_start(j), new_start(new_index));
+            let (old_range, new_range) = trim_common_text(
+                slice(old_text, old_range),
+                slice(new_text, new_range),
+                old_range,
+                new_range,
+            );
+
+            edits.push(TextEdit::new(
+                line_index.line_col_lsp_range(old_range),
+                slice(new_text, new_range).to_string(),
+            ));
+        }
+
+        i = old_index + 1;
+        j = new_index + 1;
+    }
+
+    edits
+}
+
+fn slice(text: &str, range: TextRange) -> &str {
+    &text[std::ops::Range::<usize>::from(range)]
+}
+
+fn tokenize(language: DocumentLanguage, text: &str) -> Vec<TextRange> {
+    match language {
+        DocumentLanguage::Latex => latex::parse(text)
+            .root
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .map(|token| token.text_range())
+            .collect(),
+        DocumentLanguage::Bibtex => bibtex::parse(text)
+            .root
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .map(|token| token.text_range())
+            .collect(),
+        DocumentLanguage::BuildLog | DocumentLanguage::Markdown => text
+            .split_inclusive('\n')
+            .scan(TextSize::from(0), |start, line| {
+                let range = TextRange::at(*start, line.text_len());
+                *start = range.end();
+                Some(range)
+            })
+            .collect(),
+    }
+}
+
+/// Shrinks the changed ranges of both texts to the part that actually differs,
+/// for example the spaces that are removed from an indentation.
+fn trim_common_text(
+    old: &str,
+    new: &str,
+    old_range: TextRange,
+    new_range: TextRange,
+) -> (TextRange, TextRange) {
+    let prefix: usize = old
+        .chars()
+        .zip(new.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+
+    let suffix: usize = old[prefix..]
+        .chars()
+        .rev()
+        .zip(new[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+
+    let prefix = TextSize::from(prefix as u32);
+    let suffix = TextSize::from(suffix as u32);
+    (
+        TextRange::new(old_range.start() + prefix, old_range.end() - suffix),
+        TextRange::new(new_range.start() + prefix, new_range.end() - suffix),
+    )
+}
+
+/// Returns the indices of the tokens that both sequences have in common
+/// using the diff algorithm of Myers or `None` if the sequences are too different.
+fn find_matches(old: &[&str], new: &[&str]) -> Option<Vec<(usize, usize)>> {
+    let n = old.len() as isize;
+    let m = new.len() as isize;
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    for d in 0..=MAX_DISTANCE as isize {
+        let mut v = vec![0; 2 * d as usize + 1];
+        let mut is_done = false;
+        for k in (-d..=d).step_by(2) {
+            let mut x = if d == 0 {
+                0
+            } else {
+                let previous = &trace[d as usize - 1];
+                let get = |k: isize| previous[(k + d - 1) as usize];
+                if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+                    get(k + 1)
+                } else {
+                    get(k - 1) + 1
+                }
+            };
+
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+
+            v[(k + d) as usize] = x;
+            if x >= n && y >= m {
+                is_done = true;
+                break;
+            }
+        }
+
+        trace.push(v);
+        if is_done {
+            return Some(backtrack(&trace, n, m));
+        }
+    }
+
+    None
+}
+
+fn backtrack(trace: &[Vec<isize>], n: isize, m: isize) -> Vec<(usize, usize)> {
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..trace.len() as isize).rev() {
+        let k = x - y;
+        let previous = &trace[d as usize - 1];
+        let get = |k: isize| previous[(k + d - 1) as usize];
+        let previous_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+
+        let previous_x = get(previous_k);
+        let previous_y = previous_x - previous_k;
+        while x > previous_x && y > previous_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+
+        x = previous_x;
+        y = previous_y;
+    }
+
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        matches.push((x as usize, y as usize));
+    }
+
+    matches.reverse();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_edits(text: &str, edits: &[TextEdit]) -> String {
+        let line_index = LineIndex::new(text);
+        let mut result = text.to_string();
+        for edit in edits.iter().rev() {
+            let range = line_index.offset_lsp_range(edit.range);
+            result.replace_range(std::ops::Range::<usize>::from(range), &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_latex_indentation() {
+        let old_text = "\\begin{itemize}\n\\item foo\n    \\item bar\n\\end{itemize}\n";
+        let new_text = "\\begin{itemize}\n  \\item foo\n  \\item bar\n\\end{itemize}\n";
+        let edits = diff_text_edits(
+            DocumentLanguage::Latex,
+            &LineIndex::new(old_text),
+            old_text,
+            new_text,
+        );
+
+        assert_eq!(edits.len(), 2);
+        assert!(edits
+            .iter()
+            .all(|edit| edit.range.start.line == edit.range.end.line));
+        assert_eq!(apply_edits(old_text, &edits), new_text);
+    }
+
+    #[test]
+    fn test_bibtex_entries() {
+        let old_text = "@article{foo, title = {Foo}}\n\n@book{bar,author={Bar}}";
+        let new_text =
+            "@article{foo, title = {Foo}}\n\n@book{bar,\n    author = {Bar}\n}\n\n@misc{baz}";
+        let edits = diff_text_edits(
+            DocumentLanguage::Bibtex,
+            &LineIndex::new(old_text),
+            old_text,
+            new_text,
+        );
+
+        assert!(edits.iter().all(|edit| edit.range.start.line == 2));
+        assert_eq!(apply_edits(old_text, &edits), new_text);
+    }
+
+    #[test]
+    fn test_identical() {
+        let text = "\\section{Foo}\n";
+        let edits = diff_text_edits(DocumentLanguage::Latex, &LineIndex::new(text), text, text);
+
+        assert!(edits.is_empty());
+    }
+}
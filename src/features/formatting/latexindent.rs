@@ -8,7 +8,7 @@ use cstree::{TextLen, TextRange};
 use lsp_types::{DocumentFormattingParams, TextEdit};
 use tempfile::tempdir;
 
-use crate::{features::FeatureRequest, DocumentLanguage, LineIndexExt};
+use crate::{features::FeatureRequest, DocumentLanguage, LineIndexExt, Placeholders};
 
 pub fn format_with_latexindent(
     request: &FeatureRequest<DocumentFormattingParams>,
@@ -36,12 +36,20 @@ pub fn format_with_latexindent(
         })
         .unwrap_or_else(|| ".".into());
 
+    let tex_file = document.uri.to_file_path().ok();
+    let placeholders = Placeholders {
+        tex_file: tex_file.as_deref(),
+        workspace_folder: Some(&current_dir),
+        ..Placeholders::default()
+    };
+
     let local = match &options.latexindent.local {
-        Some(local) => format!("--local={}", local),
+        Some(local) => format!("--local={}", placeholders.expand(local)),
         None => "-l".to_string(),
     };
 
     let modify_line_breaks = options.latexindent.modify_line_breaks;
+    let executable = placeholders.expand(&options.latexindent.executable());
 
     drop(options);
 
@@ -74,7 +82,7 @@ pub fn format_with_latexindent(
     args.push(&local);
     args.push(name);
 
-    let output = Command::new("latexindent")
+    let output = Command::new(executable)
         .args(&args)
         .current_dir(current_dir)
         .stdin(Stdio::null())
@@ -1,6 +1,5 @@
 use std::{
     io,
-    path::Path,
     process::{Command, Stdio},
 };
 
@@ -10,6 +9,8 @@ use lsp_types::TextDocumentPositionParams;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
+use crate::Placeholders;
+
 use super::FeatureRequest;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize_repr, Deserialize_repr)]
@@ -30,22 +31,22 @@ pub fn execute_forward_search(
     request: FeatureRequest<TextDocumentPositionParams>,
     _cancellation_token: &CancellationToken,
 ) -> Option<ForwardSearchResult> {
-    let options = {
-        request
-            .context
-            .options
-            .read()
-            .unwrap()
-            .forward_search
-            .clone()
-            .unwrap_or_default()
+    let (options, root_directory) = {
+        let options = request.context.options.read().unwrap();
+        (
+            options.forward_search.clone().unwrap_or_default(),
+            options.root_directory.clone(),
+        )
     };
 
-    if options.executable.is_none() || options.args.is_none() {
-        return Some(ForwardSearchResult {
-            status: ForwardSearchStatus::UNCONFIGURED,
-        });
-    }
+    let (executable, args) = match (options.executable(), options.args()) {
+        (Some(executable), Some(args)) => (executable, args),
+        _ => {
+            return Some(ForwardSearchResult {
+                status: ForwardSearchStatus::UNCONFIGURED,
+            })
+        }
+    };
 
     let root_document = request
         .subset
@@ -77,16 +78,15 @@ pub fn execute_forward_search(
 
     let tex_path = request.main_document().uri.to_file_path().ok()?;
 
-    let args: Vec<String> = options
-        .args
-        .unwrap()
-        .into_iter()
-        .flat_map(|arg| {
-            replace_placeholder(&tex_path, &pdf_path, request.params.position.line, arg)
-        })
-        .collect();
+    let placeholders = Placeholders {
+        tex_file: Some(&tex_path),
+        pdf_file: Some(&pdf_path),
+        line_number: Some(request.params.position.line),
+        workspace_folder: root_directory.as_deref().or_else(|| tex_path.parent()),
+    };
 
-    let status = match run_process(options.executable.unwrap(), args) {
+    let args: Vec<String> = args.iter().map(|arg| placeholders.expand(arg)).collect();
+    let status = match run_process(placeholders.expand(&executable), args) {
         Ok(()) => ForwardSearchStatus::SUCCESS,
         Err(why) => {
             error!("Unable to execute forward search: {}", why);
@@ -96,23 +96,6 @@ pub fn execute_forward_search(
     Some(ForwardSearchResult { status })
 }
 
-fn replace_placeholder(
-    tex_file: &Path,
-    pdf_file: &Path,
-    line_number: u32,
-    argument: String,
-) -> Option<String> {
-    let result = if argument.starts_with('"') || argument.ends_with('"') {
-        argument
-    } else {
-        argument
-            .replace("%f", tex_file.to_str()?)
-            .replace("%p", pdf_file.to_str()?)
-            .replace("%l", &(line_number + 1).to_string())
-    };
-    Some(result)
-}
-
 fn run_process(executable: String, args: Vec<String>) -> io::Result<()> {
     Command::new(executable)
         .args(args)
@@ -0,0 +1,272 @@
+use std::sync::Arc;
+
+use cstree::TextRange;
+use lsp_types::{DocumentLink, Location, Url};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    find_label_definition, is_generated_file,
+    syntax::{
+        bibtex,
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Workspace, WorkspaceSubset,
+};
+
+static NEW_LABEL_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\newlabel\s*\{([^{}]+?)(?:@cref)?\}").unwrap());
+
+static CITATION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\(?:bibcite|bibitem)\s*(?:\[[^\]]*\])?\s*\{([^{}]+)\}").unwrap());
+
+static CONTENTS_LINE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?m)^\\contentsline\s*\{[a-z]+\}\{(.*?)\}\{[^{}]*\}(?:\{[^{}]*\})?%?\s*$").unwrap()
+});
+
+static NUMBER_LINE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\\numberline\s*\{[^{}]*\}").unwrap());
+
+/// A line of a generated file (`.aux`, `.toc` or `.bbl`) that refers to a location in the sources.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GeneratedReference {
+    /// The range of the label, the citation key or the title in the generated file.
+    pub range: TextRange,
+    pub target: Location,
+    pub target_selection: lsp_types::Range,
+}
+
+/// Finds the labels (`\newlabel`), the citation keys (`\bibcite` and `\bibitem`)
+/// and the sections (`\contentsline`) of a generated file and their locations in the sources.
+pub fn find_generated_references(
+    workspace: &dyn Workspace,
+    document: &Document,
+) -> Vec<GeneratedReference> {
+    if !is_generated_file(&document.uri) {
+        return Vec::new();
+    }
+
+    let subset = match find_source_subset(workspace, document) {
+        Some(subset) => subset,
+        None => return Vec::new(),
+    };
+
+    let text = &document.text;
+    let mut references = Vec::new();
+    for captures in NEW_LABEL_REGEX.captures_iter(text) {
+        let name = captures.get(1).unwrap();
+        references.extend(
+            find_label(&subset, name.as_str()).map(|(target, selection)| GeneratedReference {
+                range: text_range(name.start(), name.end()),
+                target,
+                target_selection: selection,
+            }),
+        );
+    }
+
+    for captures in CITATION_REGEX.captures_iter(text) {
+        let key = captures.get(1).unwrap();
+        references.extend(
+            find_entry(&subset, key.as_str()).map(|(target, selection)| GeneratedReference {
+                range: text_range(key.start(), key.end()),
+                target,
+                target_selection: selection,
+            }),
+        );
+    }
+
+    for captures in CONTENTS_LINE_REGEX.captures_iter(text) {
+        let title = captures.get(1).unwrap();
+        references.extend(
+            find_section(&subset, title.as_str()).map(|(target, selection)| GeneratedReference {
+                range: text_range(title.start(), title.end()),
+                target,
+                target_selection: selection,
+            }),
+        );
+    }
+
+    references.sort_by_key(|reference| reference.range.start());
+    references
+}
+
+/// Links the references of a generated file to the lines of the sources.
+pub fn find_generated_links(workspace: &dyn Workspace, document: &Document) -> Vec<DocumentLink> {
+    find_generated_references(workspace, document)
+        .into_iter()
+        .map(|reference| {
+            let mut target = reference.target.uri;
+            target.set_fragment(Some(&format!(
+                "L{}",
+                reference.target_selection.start.line + 1
+            )));
+
+            DocumentLink {
+                range: document.line_index.line_col_lsp_range(reference.range),
+                target: Some(target),
+                tooltip: None,
+                data: None,
+            }
+        })
+        .collect()
+}
+
+/// Returns the project of the document that produced the generated file.
+/// Unlike the `.aux` file, the `.toc` and `.bbl` files are not linked by the sources,
+/// so the project is found using the `.aux` file next to them.
+fn find_source_subset(workspace: &dyn Workspace, document: &Document) -> Option<WorkspaceSubset> {
+    let aux_uri = document.uri.with_extension("aux")?;
+    let source = workspace.documents().into_iter().find(|source| {
+        source.data.as_latex().map_or(false, |data| {
+            data.extras
+                .implicit_links
+                .aux
+                .iter()
+                .any(|uri| uri.as_ref() == &aux_uri)
+        })
+    })?;
+
+    workspace.subset(Arc::clone(&source.uri))
+}
+
+fn find_label(subset: &WorkspaceSubset, name: &str) -> Option<(Location, lsp_types::Range)> {
+    subset
+        .documents
+        .iter()
+        .filter(|document| !is_generated_file(&document.uri))
+        .find_map(|document| {
+            let data = document.data.as_latex()?;
+            let definition = find_label_definition(&data.root, name)?;
+            let selection = definition.name()?.key()?.small_range();
+            Some(location(document, definition.small_range(), selection))
+        })
+}
+
+fn find_entry(subset: &WorkspaceSubset, key: &str) -> Option<(Location, lsp_types::Range)> {
+    subset.documents.iter().find_map(|document| {
+        let data = document.data.as_bibtex()?;
+        data.root
+            .children()
+            .filter_map(bibtex::Entry::cast)
+            .find_map(|entry| {
+                let entry_key = entry
+                    .key()
+                    .filter(|entry_key| entry_key.to_string() == key)?;
+                Some(location(
+                    document,
+                    entry.small_range(),
+                    entry_key.small_range(),
+                ))
+            })
+    })
+}
+
+fn find_section(subset: &WorkspaceSubset, title: &str) -> Option<(Location, lsp_types::Range)> {
+    let title = normalize_title(&NUMBER_LINE_REGEX.replace_all(title, ""));
+    if title.is_empty() {
+        return None;
+    }
+
+    subset
+        .documents
+        .iter()
+        .filter(|document| !is_generated_file(&document.uri))
+        .find_map(|document| {
+            let data = document.data.as_latex()?;
+            data.root
+                .descendants()
+                .filter_map(latex::Section::cast)
+                .find_map(|section| {
+                    let name = section.name()?;
+                    let text = name.content_text()?;
+                    if normalize_title(&text) != title {
+                        return None;
+                    }
+
+                    let range =
+                        TextRange::new(section.small_range().start(), name.small_range().end());
+                    Some(location(document, range, name.small_range()))
+                })
+        })
+}
+
+/// Removes the whitespace since TeX writes commands like `\emph {foo}` to the `.toc` file.
+fn normalize_title(title: &str) -> String {
+    title.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+fn location(
+    document: &Document,
+    range: TextRange,
+    selection: TextRange,
+) -> (Location, lsp_types::Range) {
+    let uri: Url = document.uri.as_ref().clone().into();
+    (
+        Location::new(uri, document.line_index.line_col_lsp_range(range)),
+        document.line_index.line_col_lsp_range(selection),
+    )
+}
+
+fn text_range(start: usize, end: usize) -> TextRange {
+    TextRange::new((start as u32).into(), (end as u32).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_aux_and_toc() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \begin{document}
+                        \section{Foo \emph{bar}}\label{sec:foo}
+                        \cite{baz}
+                        \bibliography{main}
+                        \end{document}"#},
+                ),
+                ("main.bib", r#"@article{baz, title = {Baz}}"#),
+                (
+                    "main.aux",
+                    indoc! {r#"
+                        \newlabel{sec:foo}{{1}{1}}
+                        \bibcite{baz}{1}"#},
+                ),
+                (
+                    "main.toc",
+                    r#"\contentsline {section}{\numberline {1}Foo \emph  {bar}}{1}{section.1}%"#,
+                ),
+            ])
+            .main("main.toc")
+            .build();
+
+        let main_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let bib_uri: Url = tester.uri("main.bib").as_ref().clone().into();
+        let aux_uri = tester.uri("main.aux");
+        let request = tester.position();
+        let workspace = request.workspace.as_ref();
+
+        let find_targets = |document: &Document| {
+            find_generated_references(workspace, document)
+                .into_iter()
+                .map(|reference| (reference.target.uri, reference.target_selection.start.line))
+                .collect::<Vec<_>>()
+        };
+
+        let aux_document = workspace.get(&aux_uri).unwrap();
+        assert_eq!(
+            find_targets(&aux_document),
+            vec![(main_uri.clone(), 2), (bib_uri, 0)]
+        );
+        assert_eq!(find_targets(request.main_document()), vec![(main_uri, 2)]);
+    }
+}
@@ -1,10 +1,14 @@
 #[cfg(feature = "citation")]
 mod citation;
+mod class_option;
+mod command;
 mod component;
 mod entry_type;
 mod field;
 mod label;
+mod option_value;
 mod string_ref;
+mod style;
 
 use cancellation::CancellationToken;
 use cfg_if::cfg_if;
@@ -13,8 +17,10 @@ use lsp_types::{Hover, HoverParams};
 use crate::features::cursor::CursorContext;
 
 use self::{
+    class_option::find_class_option_hover, command::find_command_definition_hover,
     component::find_component_hover, entry_type::find_entry_type_hover, field::find_field_hover,
-    label::find_label_hover, string_ref::find_string_reference_hover,
+    label::find_label_hover, option_value::find_option_value_hover,
+    string_ref::find_string_reference_hover, style::find_style_hover,
 };
 
 use super::FeatureRequest;
@@ -35,8 +41,12 @@ pub fn find_hover(
 
     hover = hover
         .or_else(|| find_component_hover(&context, cabcellation_token))
+        .or_else(|| find_style_hover(&context, cabcellation_token))
+        .or_else(|| find_class_option_hover(&context, cabcellation_token))
+        .or_else(|| find_option_value_hover(&context, cabcellation_token))
         .or_else(|| find_string_reference_hover(&context, cabcellation_token))
         .or_else(|| find_field_hover(&context, cabcellation_token))
+        .or_else(|| find_command_definition_hover(&context, cabcellation_token))
         .or_else(|| find_entry_type_hover(&context, cabcellation_token));
 
     hover
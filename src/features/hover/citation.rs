@@ -1,7 +1,12 @@
 use cancellation::CancellationToken;
 use lsp_types::{Hover, HoverContents, HoverParams};
 
-use crate::{citation, features::cursor::CursorContext, LineIndexExt};
+use crate::{
+    citation::{self, CitationFields},
+    features::cursor::CursorContext,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
 
 pub fn find_citation_hover(
     context: &CursorContext<HoverParams>,
@@ -13,7 +18,22 @@ pub fn find_citation_hover(
     let (key_text, key_range) = context
         .find_citation_key_word()
         .or_else(|| context.find_citation_key_command())
-        .or_else(|| context.find_entry_key())?;
+        .or_else(|| context.find_entry_key())
+        .or_else(|| context.find_markdown_citation_key())?;
+
+    let fields = main_document
+        .data
+        .as_latex()
+        .and_then(|data| {
+            data.root
+                .descendants()
+                .filter_map(latex::Citation::cast)
+                .find(|citation| citation.small_range().contains_range(key_range))
+        })
+        .and_then(|citation| citation.command())
+        .map_or(CitationFields::Reference, |command| {
+            CitationFields::from_command(command.text())
+        });
 
     let contents = context
         .request
@@ -24,7 +44,7 @@ pub fn find_citation_hover(
             document
                 .data
                 .as_bibtex()
-                .and_then(|data| citation::render_citation(&data.root, &key_text))
+                .and_then(|data| citation::render_citation_fields(&data.root, &key_text, fields))
         })?;
 
     Some(Hover {
@@ -102,6 +122,35 @@ mod tests {
         assert_eq!(actual_hover, expected_hover);
     }
 
+    #[test]
+    fn test_inside_citetitle() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.bib",
+                    "@article{foo, author = {Foo Bar}, title = {Baz Qux}, year = 1337}",
+                ),
+                ("main.tex", "\\addbibresource{main.bib}\n\\citetitle{foo}"),
+            ])
+            .main("main.tex")
+            .line(1)
+            .character(12)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_citation_hover(&context, CancellationToken::none()).unwrap();
+
+        let expected_hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: "*Baz Qux*".into(),
+            }),
+            range: Some(Range::new_simple(1, 11, 1, 14)),
+        };
+        assert_eq!(actual_hover, expected_hover);
+    }
+
     #[test]
     fn test_inside_entry() {
         let request = FeatureTester::builder()
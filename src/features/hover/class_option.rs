@@ -0,0 +1,77 @@
+use cancellation::CancellationToken;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+
+use crate::{features::cursor::CursorContext, LineIndexExt, LANGUAGE_DATA};
+
+pub fn find_class_option_hover(
+    context: &CursorContext<HoverParams>,
+    _token: &CancellationToken,
+) -> Option<Hover> {
+    let (name, range, include) = context.find_class_option()?;
+    let class_name = include.path_list()?.keys().next()?.to_string();
+    let docs = LANGUAGE_DATA.class_option_documentation(&class_name, &name)?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: docs.to_string(),
+        }),
+        range: Some(
+            context
+                .request
+                .main_document()
+                .line_index
+                .line_col_lsp_range(range),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_class_option_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+
+    #[test]
+    fn test_known_option() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\documentclass[twocolumn]{article}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(18)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_class_option_hover(&context, CancellationToken::none()).unwrap();
+
+        let expected_hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: LANGUAGE_DATA
+                    .class_option_documentation("article", "twocolumn")
+                    .unwrap()
+                    .into(),
+            }),
+            range: Some(Range::new_simple(0, 15, 0, 24)),
+        };
+        assert_eq!(actual_hover, expected_hover);
+    }
+}
@@ -0,0 +1,170 @@
+use cancellation::CancellationToken;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+
+use crate::{
+    features::cursor::CursorContext, find_command_definitions, syntax::CstNode,
+    CommandDefinitionInfo, LineIndexExt,
+};
+
+pub fn find_command_definition_hover(
+    context: &CursorContext<HoverParams>,
+    _token: &CancellationToken,
+) -> Option<Hover> {
+    let main_document = context.request.main_document();
+    let name = context
+        .cursor
+        .as_latex()
+        .filter(|token| token.kind().is_command_name())?;
+
+    let definitions = find_command_definitions(&context.request.subset, name.text());
+    if definitions.is_empty() {
+        return None;
+    }
+
+    let mut value = format!(
+        "`{}` is defined {} in the project:\n\n",
+        name.text(),
+        if definitions.len() == 1 {
+            "once".to_string()
+        } else {
+            format!("{} times", definitions.len())
+        }
+    );
+
+    for (i, info) in definitions.iter().enumerate() {
+        value.push_str(&format!("{}. {}\n", i + 1, render_definition(info)?));
+    }
+
+    if let Some(info) = definitions.iter().find(|info| info.is_effective) {
+        value.push_str(&format!(
+            "\n```latex\n{}\n```",
+            info.definition.syntax().text().to_string().trim()
+        ));
+    }
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(
+            main_document
+                .line_index
+                .line_col_lsp_range(name.text_range()),
+        ),
+    })
+}
+
+fn render_definition(info: &CommandDefinitionInfo) -> Option<String> {
+    let file_name = info.document.uri.path_segments()?.last()?;
+    let line = info
+        .document
+        .line_index
+        .line_col(info.definition.small_range().start())
+        .line
+        + 1;
+
+    let status = if info.is_effective {
+        " (effective)"
+    } else if info.is_ignored {
+        " (ignored)"
+    } else {
+        " (overridden)"
+    };
+
+    Some(format!(
+        "`{}` in `{}` at line {}{}",
+        info.definition.command()?.text(),
+        file_name,
+        line,
+        status
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_command_definition_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+
+    #[test]
+    fn test_undefined_command() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "\\foo")])
+            .main("main.tex")
+            .line(0)
+            .character(2)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_command_definition_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+
+    #[test]
+    fn test_redefinitions() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {
+                    r#"
+                        \providecommand{\foo}{foo}
+                        \providecommand{\foo}{bar}
+                        \renewcommand{\foo}{baz}
+                        \foo
+                    "#
+                },
+            )])
+            .main("main.tex")
+            .line(3)
+            .character(2)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover =
+            find_command_definition_hover(&context, CancellationToken::none()).unwrap();
+
+        let expected_hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: indoc! {
+                    r#"
+                        `\foo` is defined 3 times in the project:
+
+                        1. `\providecommand` in `main.tex` at line 1 (overridden)
+                        2. `\providecommand` in `main.tex` at line 2 (ignored)
+                        3. `\renewcommand` in `main.tex` at line 3 (effective)
+
+                        ```latex
+                        \renewcommand{\foo}{baz}
+                        ```"#
+                }
+                .into(),
+            }),
+            range: Some(Range::new_simple(3, 0, 3, 4)),
+        };
+        assert_eq!(actual_hover, expected_hover);
+    }
+}
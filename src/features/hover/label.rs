@@ -1,7 +1,15 @@
 use cancellation::CancellationToken;
-use lsp_types::{Hover, HoverContents, HoverParams};
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
 
-use crate::{features::cursor::CursorContext, render_label, LineIndexExt};
+use crate::{
+    features::cursor::CursorContext,
+    render_label,
+    syntax::{latex, CstNode},
+    LineIndexExt,
+};
+
+/// Commands that print the title of the referenced object.
+const NAME_REFERENCE_COMMANDS: &[&str] = &["\\nameref", "\\Nameref"];
 
 pub fn find_label_hover(
     context: &CursorContext<HoverParams>,
@@ -14,13 +22,31 @@ pub fn find_label_hover(
         .or_else(|| context.find_label_name_command())?;
 
     let label = render_label(&context.request.subset, &name_text, None)?;
+    let contents = match label.title().filter(|_| is_name_reference(context)) {
+        Some(title) => MarkupContent {
+            kind: MarkupKind::PlainText,
+            value: title.to_string(),
+        },
+        None => label.documentation(),
+    };
 
     Some(Hover {
         range: Some(main_document.line_index.line_col_lsp_range(name_range)),
-        contents: HoverContents::Markup(label.documentation()),
+        contents: HoverContents::Markup(contents),
     })
 }
 
+fn is_name_reference(context: &CursorContext<HoverParams>) -> bool {
+    context
+        .cursor
+        .as_latex()
+        .and_then(|token| token.ancestors().find_map(latex::LabelReference::cast))
+        .and_then(|reference| reference.command())
+        .map_or(false, |command| {
+            NAME_REFERENCE_COMMANDS.contains(&command.text())
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use lsp_types::Range;
@@ -76,4 +102,54 @@ mod tests {
 
         assert_eq!(actual_hover.range.unwrap(), Range::new_simple(0, 20, 0, 27));
     }
+
+    #[test]
+    fn test_nameref() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\section{Foo Bar}\\label{sec:foo}\n\\nameref{sec:foo}",
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(10)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_label_hover(&context, CancellationToken::none()).unwrap();
+
+        assert_eq!(
+            actual_hover.contents,
+            HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::PlainText,
+                value: "Foo Bar".into(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_nameref_equation() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\begin{equation}\\label{eq:foo}x\\end{equation}\n\\nameref{eq:foo}",
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(10)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_label_hover(&context, CancellationToken::none()).unwrap();
+
+        assert_eq!(
+            actual_hover.contents,
+            HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::PlainText,
+                value: "Equation".into(),
+            })
+        );
+    }
 }
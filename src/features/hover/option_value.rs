@@ -0,0 +1,117 @@
+use cancellation::CancellationToken;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+
+use crate::{features::cursor::CursorContext, LineIndexExt, LANGUAGE_DATA};
+
+pub fn find_option_value_hover(
+    context: &CursorContext<HoverParams>,
+    _token: &CancellationToken,
+) -> Option<Hover> {
+    let option = context.find_key_value_option()?;
+    let docs = option.packages.iter().find_map(|package| {
+        LANGUAGE_DATA.option_value_documentation(package, &option.key, &option.value)
+    })?;
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: docs.to_string(),
+        }),
+        range: Some(
+            context
+                .request
+                .main_document()
+                .line_index
+                .line_col_lsp_range(option.range),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_option_value_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+
+    #[test]
+    fn test_package_option() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                r#"\usepackage[backend=biber, style=authoryear]{biblatex}"#,
+            )])
+            .main("main.tex")
+            .line(0)
+            .character(37)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_option_value_hover(&context, CancellationToken::none()).unwrap();
+
+        let expected_hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: LANGUAGE_DATA
+                    .option_value_documentation("biblatex", "style", "authoryear")
+                    .unwrap()
+                    .into(),
+            }),
+            range: Some(Range::new_simple(0, 33, 0, 43)),
+        };
+        assert_eq!(actual_hover, expected_hover);
+    }
+
+    #[test]
+    fn test_listings_option() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                r#"\lstset{basicstyle=\ttfamily, language = Python}"#,
+            )])
+            .main("main.tex")
+            .line(0)
+            .character(44)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_option_value_hover(&context, CancellationToken::none()).unwrap();
+
+        assert_eq!(actual_hover.range, Some(Range::new_simple(0, 41, 0, 47)));
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\foo{language=Python}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(16)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_option_value_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+}
@@ -0,0 +1,92 @@
+use cancellation::CancellationToken;
+use lsp_types::{Hover, HoverContents, HoverParams, MarkupContent, MarkupKind};
+
+use crate::{features::cursor::CursorContext, LineIndexExt, LANGUAGE_DATA};
+
+pub fn find_style_hover(
+    context: &CursorContext<HoverParams>,
+    _token: &CancellationToken,
+) -> Option<Hover> {
+    let (name, range, command) = context.find_generic_command_argument()?;
+    let docs = LANGUAGE_DATA.style_documentation(command.name()?.text(), &name)?;
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: docs.to_string(),
+        }),
+        range: Some(
+            context
+                .request
+                .main_document()
+                .line_index
+                .line_col_lsp_range(range),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_empty_latex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .line(0)
+            .character(0)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_style_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+
+    #[test]
+    fn test_known_bibliography_style() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\bibliographystyle{ieeetr}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(21)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_style_hover(&context, CancellationToken::none()).unwrap();
+
+        let expected_hover = Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: LANGUAGE_DATA
+                    .style_documentation("\\bibliographystyle", "ieeetr")
+                    .unwrap()
+                    .into(),
+            }),
+            range: Some(Range::new_simple(0, 19, 0, 25)),
+        };
+        assert_eq!(actual_hover, expected_hover);
+    }
+
+    #[test]
+    fn test_unknown_page_style() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\pagestyle{foo}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(12)
+            .build()
+            .hover();
+
+        let context = CursorContext::new(request);
+        let actual_hover = find_style_hover(&context, CancellationToken::none());
+
+        assert_eq!(actual_hover, None);
+    }
+}
@@ -0,0 +1,372 @@
+use cancellation::CancellationToken;
+use cstree::TextSize;
+use lsp_types::{Position, Range, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    count_words, find_equation_numbers,
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, LANGUAGE_DATA,
+};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    pub text_document: TextDocumentIdentifier,
+
+    /// The visible range of the document. All hints are returned if the range is missing.
+    #[serde(default)]
+    pub range: Option<Range>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHint {
+    pub position: Position,
+    pub label: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct SectionStatistics {
+    figures: usize,
+    tables: usize,
+    equations: usize,
+    words: usize,
+}
+
+impl SectionStatistics {
+    fn label(&self) -> String {
+        let mut parts = Vec::new();
+        for (count, singular, plural) in [
+            (self.figures, "figure", "figures"),
+            (self.tables, "table", "tables"),
+            (self.equations, "equation", "equations"),
+        ]
+        .iter()
+        {
+            if *count > 0 {
+                parts.push(pluralize(*count, singular, plural));
+            }
+        }
+
+        parts.push(pluralize(self.words, "word", "words"));
+        parts.join(", ")
+    }
+}
+
+pub fn find_inlay_hints(
+    request: FeatureRequest<InlayHintParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<InlayHint>> {
+    let options = request.context.options.read().unwrap().inlay_hints.clone();
+    let mut hints = Vec::new();
+    if options.section_statistics {
+        find_section_statistics_hints(&request, cancellation_token, &mut hints);
+    }
+
+    if options.equation_numbers {
+        find_equation_number_hints(&request, &mut hints);
+    }
+
+    if let Some(range) = request.params.range {
+        hints.retain(|hint| range.start <= hint.position && hint.position <= range.end);
+    }
+
+    Some(hints)
+}
+
+/// Shows the number of figures, tables, equations and words of a section
+/// at the end of the line of its heading, including the content of its subsections.
+fn find_section_statistics_hints(
+    request: &FeatureRequest<InlayHintParams>,
+    cancellation_token: &CancellationToken,
+    hints: &mut Vec<InlayHint>,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    for section in data.root.descendants().filter_map(latex::Section::cast) {
+        cancellation_token.result().ok()?;
+        let heading_end = match section.name() {
+            Some(name) => name.small_range().end(),
+            None => section.command()?.text_range().end(),
+        };
+
+        hints.push(InlayHint {
+            position: find_line_end(main_document, heading_end),
+            label: section_statistics(section).label(),
+        });
+    }
+
+    Some(())
+}
+
+/// Shows the numbers of the numbered formulas at the end of their rows once the document has been built.
+/// The numbers of labeled formulas are taken from the `.aux` file, the others are computed by replaying the counters
+/// unless the other documents of the project can change them.
+fn find_equation_number_hints(
+    request: &FeatureRequest<InlayHintParams>,
+    hints: &mut Vec<InlayHint>,
+) -> Option<()> {
+    let has_aux = request
+        .subset
+        .documents
+        .iter()
+        .any(|document| document.uri.as_str().ends_with(".aux"));
+
+    if !has_aux {
+        return None;
+    }
+
+    let main_document = request.main_document();
+    for equation in find_equation_numbers(&request.subset, main_document) {
+        hints.push(InlayHint {
+            position: find_line_end(main_document, equation.offset),
+            label: equation.text,
+        });
+    }
+
+    Some(())
+}
+
+/// Returns the end of the line that contains the offset without the trailing whitespace.
+fn find_line_end(document: &Document, offset: TextSize) -> Position {
+    let text = &document.text;
+    let line = &text[usize::from(offset)..];
+    let line_end = usize::from(offset) + line.find('\n').unwrap_or(line.len());
+    let line_end = text[..line_end].trim_end().len().max(offset.into());
+    document.line_index.line_col_lsp((line_end as u32).into())
+}
+
+fn section_statistics(section: latex::Section) -> SectionStatistics {
+    let mut statistics = SectionStatistics {
+        words: count_words(section.syntax()).words,
+        ..SectionStatistics::default()
+    };
+
+    for node in section.syntax().descendants() {
+        if is_display_math(node) {
+            // Nested environments like `split` belong to the enclosing formula.
+            if !node.ancestors().skip(1).any(is_display_math) {
+                statistics.equations += 1;
+            }
+        } else if let Some(name) = environment_name(node) {
+            match name.trim_end_matches('*') {
+                "figure" => statistics.figures += 1,
+                "table" => statistics.tables += 1,
+                _ => {}
+            }
+        }
+    }
+
+    statistics
+}
+
+fn is_display_math(node: &latex::SyntaxNode) -> bool {
+    node.kind() == latex::EQUATION
+        || environment_name(node).map_or(false, |name| {
+            LANGUAGE_DATA
+                .math_environments
+                .iter()
+                .any(|math_environment| *math_environment == name)
+        })
+}
+
+fn environment_name(node: &latex::SyntaxNode) -> Option<String> {
+    latex::Environment::cast(node)
+        .and_then(|environment| environment.begin()?.name()?.key())
+        .map(|name| name.to_string())
+}
+
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    format!("{} {}", count, if count == 1 { singular } else { plural })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_section_statistics() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                    \section{Foo}
+                    Foo bar.
+                    \begin{figure}\end{figure}
+                    \subsection{Bar}
+                    \[ x \]
+                    \begin{align*} y \end{align*}
+                    Baz."#},
+            )])
+            .main("main.tex")
+            .build()
+            .inlay_hints();
+
+        let mut actual = Vec::new();
+        find_section_statistics_hints(&request, CancellationToken::none(), &mut actual);
+
+        let expected = vec![
+            InlayHint {
+                position: Position::new(0, 13),
+                label: "1 figure, 2 equations, 5 words".into(),
+            },
+            InlayHint {
+                position: Position::new(3, 16),
+                label: "2 equations, 2 words".into(),
+            },
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_section_statistics_nested_math() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                    \section{Foo}
+                    \begin{equation}
+                    \begin{split}
+                    x &= y
+                    \end{split}
+                    \end{equation}
+                    \begin{gather*}
+                    \begin{aligned} a &= b \end{aligned}
+                    \end{gather*}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .inlay_hints();
+
+        let mut actual = Vec::new();
+        find_section_statistics_hints(&request, CancellationToken::none(), &mut actual);
+
+        let expected = vec![InlayHint {
+            position: Position::new(0, 13),
+            label: "2 equations, 1 word".into(),
+        }];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_equation_numbers() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \numberwithin{equation}{section}
+                        \begin{document}
+                        \section{Foo}
+                        \begin{equation}\label{eq:foo}
+                        x
+                        \end{equation}
+                        \begin{align}
+                        a &= b
+                        \\ c &= d \nonumber
+                        \\ e &= f \tag{*}
+                        \end{align}
+                        \section{Bar}
+                        \begin{align}
+                        \begin{cases} g \\ h \end{cases} \label{eq:bar}
+                        \end{align}
+                        \end{document}"#},
+                ),
+                (
+                    "main.aux",
+                    indoc! {r#"
+                        \newlabel{eq:foo}{{1.1}{1}}
+                        \newlabel{eq:bar}{{2.7}{1}}"#},
+                ),
+            ])
+            .main("main.tex")
+            .build()
+            .inlay_hints();
+
+        let mut actual = Vec::new();
+        find_equation_number_hints(&request, &mut actual);
+
+        let expected = vec![
+            InlayHint {
+                position: Position::new(5, 1),
+                label: "(1.1)".into(),
+            },
+            InlayHint {
+                position: Position::new(8, 6),
+                label: "(1.2)".into(),
+            },
+            InlayHint {
+                position: Position::new(10, 17),
+                label: "(*)".into(),
+            },
+            InlayHint {
+                position: Position::new(14, 47),
+                label: "(2.7)".into(),
+            },
+        ];
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_equation_numbers_included() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \begin{document}
+                        \section{Foo}
+                        \begin{equation}
+                        x
+                        \end{equation}
+                        \input{chapter}
+                        \end{document}"#},
+                ),
+                (
+                    "chapter.tex",
+                    indoc! {r#"
+                        \begin{equation}\label{eq:foo}
+                        y
+                        \end{equation}
+                        \begin{equation}
+                        z
+                        \end{equation}
+                        \begin{equation}
+                        w \tag{*}
+                        \end{equation}"#},
+                ),
+                ("main.aux", r#"\newlabel{eq:foo}{{2}{1}}"#),
+            ])
+            .main("chapter.tex")
+            .build()
+            .inlay_hints();
+
+        let mut actual = Vec::new();
+        find_equation_number_hints(&request, &mut actual);
+
+        let expected = vec![
+            InlayHint {
+                position: Position::new(1, 1),
+                label: "(2)".into(),
+            },
+            InlayHint {
+                position: Position::new(7, 9),
+                label: "(*)".into(),
+            },
+        ];
+
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,94 @@
+use cancellation::CancellationToken;
+use lsp_types::{Range, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{find_natural_languages, LineIndexExt};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageRegionsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageRegions {
+    pub main: Option<String>,
+    pub loaded: Vec<String>,
+    pub regions: Vec<LanguageRegionLocation>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageRegionLocation {
+    pub range: Range,
+    pub language: String,
+}
+
+pub fn find_language_regions(
+    request: FeatureRequest<LanguageRegionsParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<LanguageRegions> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let related: Vec<_> = request
+        .subset
+        .documents
+        .iter()
+        .skip(1)
+        .filter_map(|document| document.data.as_latex())
+        .map(|data| &data.root)
+        .collect();
+
+    cancellation_token.result().ok()?;
+    let languages = find_natural_languages(&data.root, &related);
+    let regions = languages
+        .regions
+        .into_iter()
+        .map(|region| LanguageRegionLocation {
+            range: main_document.line_index.line_col_lsp_range(region.range),
+            language: region.language,
+        })
+        .collect();
+
+    Some(LanguageRegions {
+        main: languages.main,
+        loaded: languages.loaded,
+        regions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_included_document() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    r#"\usepackage[ngerman, english]{babel}\include{chapter}"#,
+                ),
+                ("chapter.tex", r#"Foo \foreignlanguage{ngerman}{Bar}"#),
+            ])
+            .main("chapter.tex")
+            .build()
+            .language_regions();
+
+        let actual = find_language_regions(request, CancellationToken::none()).unwrap();
+        let expected = LanguageRegions {
+            main: Some("english".into()),
+            loaded: vec!["ngerman".into(), "english".into()],
+            regions: vec![LanguageRegionLocation {
+                range: Range::new_simple(0, 29, 0, 34),
+                language: "ngerman".into(),
+            }],
+        };
+        assert_eq!(actual, expected);
+    }
+}
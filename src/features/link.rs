@@ -1,9 +1,9 @@
 use cancellation::CancellationToken;
 use lsp_types::{DocumentLink, DocumentLinkParams};
 
-use crate::LineIndexExt;
+use crate::{is_generated_file, LineIndexExt};
 
-use super::FeatureRequest;
+use super::{find_build_log_links, find_generated_links, FeatureRequest};
 
 pub fn find_document_links(
     request: FeatureRequest<DocumentLinkParams>,
@@ -11,6 +11,14 @@ pub fn find_document_links(
 ) -> Vec<DocumentLink> {
     let mut links = Vec::new();
     let main_document = request.main_document();
+    if main_document.data.as_build_log().is_some() {
+        return find_build_log_links(request.workspace.as_ref(), main_document);
+    }
+
+    if is_generated_file(&main_document.uri) {
+        return find_generated_links(request.workspace.as_ref(), main_document);
+    }
+
     if let Some(data) = main_document.data.as_latex() {
         for include in &data.extras.explicit_links {
             for target in &include.targets {
@@ -25,6 +25,8 @@ pub enum Structure {
     Field,
     Argument,
     GlossaryEntry,
+    Counter,
+    IndexEntry,
 }
 
 impl Structure {
@@ -57,6 +59,8 @@ impl Structure {
             Self::Field => CompletionItemKind::Field,
             Self::Argument => CompletionItemKind::Value,
             Self::GlossaryEntry => CompletionItemKind::Keyword,
+            Self::Counter => CompletionItemKind::Variable,
+            Self::IndexEntry => CompletionItemKind::Text,
         }
     }
 
@@ -89,6 +93,8 @@ impl Structure {
             Self::Field => SymbolKind::Field,
             Self::Argument => SymbolKind::Number,
             Self::GlossaryEntry => unimplemented!(),
+            Self::Counter => SymbolKind::Variable,
+            Self::IndexEntry => SymbolKind::String,
         }
     }
 }
@@ -0,0 +1,147 @@
+use cancellation::CancellationToken;
+use lsp_types::TextDocumentPositionParams;
+use serde::{Deserialize, Serialize};
+
+use crate::{indentation, LineIndexExt};
+
+use super::{preview_math::find_math, FeatureRequest};
+
+pub const INSERT_MATRIX_COMMAND: &str = "texlab.insertMatrix";
+
+const MATRIX_ENVIRONMENTS: &[&str] = &[
+    "matrix", "pmatrix", "bmatrix", "Bmatrix", "vmatrix", "Vmatrix", "cases",
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertMatrixParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+
+    pub environment: String,
+
+    pub rows: usize,
+
+    pub columns: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatrixSnippet {
+    pub snippet: String,
+}
+
+pub fn create_matrix_snippet(
+    request: FeatureRequest<InsertMatrixParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<MatrixSnippet> {
+    let params = &request.params;
+    if !MATRIX_ENVIRONMENTS.contains(&params.environment.as_str())
+        || params.rows == 0
+        || params.columns == 0
+    {
+        return None;
+    }
+
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    let offset = main_document
+        .line_index
+        .offset_lsp(params.text_document_position.position);
+
+    let is_math = find_math(main_document, offset).map_or(false, |range| {
+        range.start() < offset && offset < range.end()
+    });
+
+    let unit = indentation::unit(main_document);
+    let mut lines = Vec::new();
+    for row in 0..params.rows {
+        let cells: Vec<_> = (0..params.columns)
+            .map(|column| format!("${{{}}}", row * params.columns + column + 1))
+            .collect();
+
+        let end = if row + 1 < params.rows {
+            escape_snippet(" \\\\")
+        } else {
+            String::new()
+        };
+        lines.push(format!("{}{}{}", unit, cells.join(" & "), end));
+    }
+
+    let matrix = format!(
+        "{}\n{}\n{}",
+        escape_snippet(&format!("\\begin{{{}}}", params.environment)),
+        lines.join("\n"),
+        escape_snippet(&format!("\\end{{{}}}", params.environment)),
+    );
+
+    let snippet = if is_math {
+        format!("{}$0", matrix)
+    } else {
+        format!(
+            "{}\n{}\n{}$0",
+            escape_snippet("\\["),
+            matrix,
+            escape_snippet("\\]")
+        )
+    };
+
+    Some(MatrixSnippet { snippet })
+}
+
+/// Escapes the characters that have a special meaning in snippets.
+pub(super) fn escape_snippet(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('$', "\\$")
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{Position, TextDocumentIdentifier};
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn create_snippet(text: &str, character: u32, environment: &str) -> Option<String> {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .build();
+
+        let uri = tester.uri("main.tex").as_ref().clone().into();
+        let request = tester.insert_matrix(InsertMatrixParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri),
+                Position::new(0, character),
+            ),
+            environment: environment.to_string(),
+            rows: 2,
+            columns: 2,
+        });
+
+        create_matrix_snippet(request, CancellationToken::none()).map(|result| result.snippet)
+    }
+
+    #[test]
+    fn test_inside_math() {
+        assert_eq!(
+            create_snippet("$x = $", 5, "pmatrix").as_deref(),
+            Some("\\\\begin{pmatrix}\n\t${1} & ${2} \\\\\\\\\n\t${3} & ${4}\n\\\\end{pmatrix}$0")
+        );
+    }
+
+    #[test]
+    fn test_outside_math() {
+        assert_eq!(
+            create_snippet("", 0, "cases").as_deref(),
+            Some("\\\\[\n\\\\begin{cases}\n\t${1} & ${2} \\\\\\\\\n\t${3} & ${4}\n\\\\end{cases}\n\\\\]$0")
+        );
+    }
+
+    #[test]
+    fn test_unknown_environment() {
+        assert_eq!(create_snippet("", 0, "tabular"), None);
+    }
+}
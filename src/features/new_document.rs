@@ -0,0 +1,371 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use lsp_types::{
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextEdit, Url, WorkspaceEdit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::config::PROJECT_CONFIG_FILE;
+
+use super::is_nested_path;
+
+pub const NEW_DOCUMENT_COMMAND: &str = "texlab.newDocument";
+
+const PROJECT_CONFIG_TEMPLATE: &str = "[build]\nonSave = true\n";
+
+const ARTICLE_TEMPLATE: &str = r#"\documentclass{article}
+
+\title{${title}}
+\author{${author}}
+\date{${date}}
+
+\begin{document}
+\maketitle
+
+\section{Introduction}
+\label{sec:introduction}
+
+\end{document}
+"#;
+
+const BEAMER_TEMPLATE: &str = r#"\documentclass{beamer}
+
+\title{${title}}
+\author{${author}}
+\date{${date}}
+
+\begin{document}
+\frame{\titlepage}
+
+\begin{frame}{Introduction}
+
+\end{frame}
+
+\end{document}
+"#;
+
+const LETTER_TEMPLATE: &str = r#"\documentclass{letter}
+
+\signature{${author}}
+\address{${address}}
+\date{${date}}
+
+\begin{document}
+\begin{letter}{${recipient}}
+\opening{Dear ${recipient},}
+
+\closing{Yours sincerely,}
+\end{letter}
+\end{document}
+"#;
+
+const IEEETRAN_TEMPLATE: &str = r#"\documentclass[conference]{IEEEtran}
+\usepackage{cite}
+\usepackage{amsmath,amssymb}
+\usepackage{graphicx}
+
+\begin{document}
+
+\title{${title}}
+
+\author{\IEEEauthorblockN{${author}}
+\IEEEauthorblockA{${affiliation}}}
+
+\maketitle
+
+\begin{abstract}
+
+\end{abstract}
+
+\begin{IEEEkeywords}
+
+\end{IEEEkeywords}
+
+\section{Introduction}
+\label{sec:introduction}
+
+\bibliographystyle{IEEEtran}
+\bibliography{references}
+
+\end{document}
+"#;
+
+/// The built-in project templates as pairs of the template name and its files.
+const BUILTIN_TEMPLATES: &[(&str, &[(&str, &str)])] = &[
+    ("article", &[("main.tex", ARTICLE_TEMPLATE)]),
+    ("beamer", &[("main.tex", BEAMER_TEMPLATE)]),
+    ("letter", &[("main.tex", LETTER_TEMPLATE)]),
+    (
+        "ieeetran",
+        &[("main.tex", IEEETRAN_TEMPLATE), ("references.bib", "")],
+    ),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewDocumentParams {
+    /// The directory of the new project.
+    pub directory: Url,
+
+    /// The name of a user-defined or built-in template.
+    pub template: String,
+
+    /// The values of the `${name}` placeholders of the template.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+}
+
+/// Creates the files of a new project from a template.
+/// The templates of the `projects` folder inside of the template directory
+/// take precedence over the built-in templates.
+/// Existing files are never overwritten.
+/// Template names that leave the `projects` folder are rejected.
+pub fn create_new_document(
+    params: &NewDocumentParams,
+    template_directory: Option<&Path>,
+) -> Option<WorkspaceEdit> {
+    if !is_nested_path(Path::new(&params.template)) {
+        return None;
+    }
+
+    let directory = params.directory.to_file_path().ok()?;
+    let mut files = template_directory
+        .map(|template_dir| template_dir.join("projects").join(&params.template))
+        .filter(|template_dir| template_dir.is_dir())
+        .map(|template_dir| read_user_template(&template_dir))
+        .or_else(|| find_builtin_template(&params.template))?;
+
+    if !files
+        .iter()
+        .any(|(path, _)| path == Path::new(PROJECT_CONFIG_FILE))
+    {
+        files.push((
+            PathBuf::from(PROJECT_CONFIG_FILE),
+            PROJECT_CONFIG_TEMPLATE.to_string(),
+        ));
+    }
+
+    let mut operations = Vec::new();
+    for (path, text) in files {
+        let path = directory.join(path);
+        if path.exists() {
+            continue;
+        }
+
+        let uri = match Url::from_file_path(&path) {
+            Ok(uri) => uri,
+            Err(()) => continue,
+        };
+
+        operations.push(DocumentChangeOperation::Op(ResourceOp::Create(
+            CreateFile {
+                uri: uri.clone(),
+                options: Some(CreateFileOptions {
+                    overwrite: Some(false),
+                    ignore_if_exists: Some(true),
+                }),
+                annotation_id: None,
+            },
+        )));
+
+        let text = expand_variables(&text, &params.variables);
+        if !text.is_empty() {
+            operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: vec![OneOf::Left(TextEdit::new(
+                    Range::new(Position::new(0, 0), Position::new(0, 0)),
+                    text,
+                ))],
+            }));
+        }
+    }
+
+    if operations.is_empty() {
+        None
+    } else {
+        Some(WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            change_annotations: None,
+        })
+    }
+}
+
+fn find_builtin_template(name: &str) -> Option<Vec<(PathBuf, String)>> {
+    let (_, files) = BUILTIN_TEMPLATES
+        .iter()
+        .find(|(template_name, _)| template_name.eq_ignore_ascii_case(name))?;
+
+    Some(
+        files
+            .iter()
+            .map(|(path, text)| (PathBuf::from(path), text.to_string()))
+            .collect(),
+    )
+}
+
+/// Reads the files of a template directory recursively.
+/// The returned paths are relative to the template directory.
+fn read_user_template(template_dir: &Path) -> Vec<(PathBuf, String)> {
+    let mut files = Vec::new();
+    let mut directories = vec![template_dir.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let entries = match fs::read_dir(&directory) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            if path.is_dir() {
+                directories.push(path);
+            } else if let (Ok(text), Ok(relative_path)) =
+                (fs::read_to_string(&path), path.strip_prefix(template_dir))
+            {
+                files.push((relative_path.to_path_buf(), text));
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+/// Replaces the `${name}` placeholders of a template.
+/// The date defaults to `\today` and unknown placeholders are left empty.
+fn expand_variables(text: &str, variables: &BTreeMap<String, String>) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+        let name = &rest[start + 2..end];
+        match variables.get(name) {
+            Some(value) => result.push_str(value),
+            None if name == "date" => result.push_str("\\today"),
+            None => {}
+        }
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn created_files(edit: WorkspaceEdit) -> Vec<(String, String)> {
+        let mut files = Vec::new();
+        if let Some(DocumentChanges::Operations(operations)) = edit.document_changes {
+            for operation in operations {
+                match operation {
+                    DocumentChangeOperation::Op(ResourceOp::Create(create)) => {
+                        let name = create.uri.path_segments().unwrap().last().unwrap();
+                        files.push((name.to_string(), String::new()));
+                    }
+                    DocumentChangeOperation::Edit(mut edit) => {
+                        if let Some(OneOf::Left(edit)) = edit.edits.pop() {
+                            files.last_mut().unwrap().1 = edit.new_text;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        files
+    }
+
+    #[test]
+    fn test_builtin_template() {
+        let directory = std::env::temp_dir()
+            .join("texlab-new-document")
+            .join("paper");
+        let mut variables = BTreeMap::new();
+        variables.insert("title".to_string(), "Foo".to_string());
+        let params = NewDocumentParams {
+            directory: Url::from_directory_path(directory).unwrap(),
+            template: "IEEEtran".into(),
+            variables,
+        };
+
+        let files = created_files(create_new_document(&params, None).unwrap());
+        let names: Vec<_> = files.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["main.tex", "references.bib", PROJECT_CONFIG_FILE]
+        );
+        assert!(files[0].1.contains("\\title{Foo}"));
+        assert!(files[0].1.contains("\\author{\\IEEEauthorblockN{}"));
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let params = NewDocumentParams {
+            directory: Url::from_directory_path(std::env::temp_dir()).unwrap(),
+            template: "foo".into(),
+            variables: BTreeMap::new(),
+        };
+
+        assert_eq!(create_new_document(&params, None), None);
+    }
+
+    #[test]
+    fn test_user_template() {
+        let template_dir = tempfile::tempdir().unwrap();
+        let project_dir = template_dir.path().join("projects").join("thesis");
+        fs::create_dir_all(&project_dir).unwrap();
+        fs::write(project_dir.join("main.tex"), "\\title{${title}}").unwrap();
+
+        let params = NewDocumentParams {
+            directory: Url::from_directory_path(template_dir.path().join("paper")).unwrap(),
+            template: "thesis".into(),
+            variables: BTreeMap::new(),
+        };
+
+        let files = created_files(create_new_document(&params, Some(template_dir.path())).unwrap());
+        assert_eq!(files[0], ("main.tex".to_string(), "\\title{}".to_string()));
+    }
+
+    #[test]
+    fn test_template_outside_of_template_directory() {
+        let template_dir = tempfile::tempdir().unwrap();
+        let secret_dir = template_dir.path().join("secret");
+        fs::create_dir_all(template_dir.path().join("projects")).unwrap();
+        fs::create_dir_all(&secret_dir).unwrap();
+        fs::write(secret_dir.join("main.tex"), "foo").unwrap();
+
+        for template in ["../secret", secret_dir.to_str().unwrap()] {
+            let params = NewDocumentParams {
+                directory: Url::from_directory_path(template_dir.path().join("paper")).unwrap(),
+                template: template.into(),
+                variables: BTreeMap::new(),
+            };
+
+            assert_eq!(
+                create_new_document(&params, Some(template_dir.path())),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_variables() {
+        let mut variables = BTreeMap::new();
+        variables.insert("author".to_string(), "Bar".to_string());
+        assert_eq!(
+            expand_variables("${author} ${date} ${foo}", &variables),
+            "Bar \\today "
+        );
+    }
+}
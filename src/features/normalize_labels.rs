@@ -0,0 +1,382 @@
+use std::collections::{BTreeMap, HashMap};
+
+use cancellation::CancellationToken;
+use lsp_types::{TextDocumentIdentifier, TextEdit, WorkspaceEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    render_label,
+    syntax::{latex, CstNode},
+    LabelledFloatKind, LabelledObject, LineIndexExt,
+};
+
+use super::{workspace_edit::AnnotatedWorkspaceEdit, FeatureRequest};
+
+pub const NORMALIZE_LABELS_COMMAND: &str = "texlab.normalizeLabels";
+
+const MAX_SLUG_WORDS: usize = 3;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeLabelsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizeLabelsResult {
+    pub edit: WorkspaceEdit,
+    pub renames: Vec<LabelRename>,
+    pub conflicts: Vec<LabelConflict>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelRename {
+    pub old_name: String,
+    pub new_name: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LabelConflict {
+    pub new_name: String,
+    pub labels: Vec<String>,
+}
+
+pub fn normalize_labels(
+    request: FeatureRequest<NormalizeLabelsParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<NormalizeLabelsResult> {
+    let mut renames: Vec<LabelRename> = Vec::new();
+    for document in &request.subset.documents {
+        cancellation_token.result().ok()?;
+        if let Some(data) = document.data.as_latex() {
+            for label in data
+                .root
+                .descendants()
+                .filter_map(latex::LabelDefinition::cast)
+            {
+                let old_name = match label.name().and_then(|name| name.key()) {
+                    Some(name) => name.to_string(),
+                    None => continue,
+                };
+
+                if renames.iter().any(|rename| rename.old_name == old_name) {
+                    continue;
+                }
+
+                let new_name = render_label(&request.subset, &old_name, Some(label))
+                    .and_then(|label| make_label_name(&label.object, &old_name))
+                    .unwrap_or_else(|| old_name.clone());
+
+                renames.push(LabelRename { old_name, new_name });
+            }
+        }
+    }
+
+    let conflicts = resolve_conflicts(&mut renames);
+    renames.retain(|rename| rename.old_name != rename.new_name);
+
+    let mut definition_counts: HashMap<String, usize> = HashMap::new();
+    for data in request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for name in data
+            .root
+            .descendants()
+            .filter_map(latex::LabelDefinition::cast)
+            .filter_map(|label| label.name()?.key())
+        {
+            *definition_counts.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    let mut edit = AnnotatedWorkspaceEdit::default();
+    for rename in &renames {
+        let is_ambiguous = definition_counts
+            .get(&rename.old_name)
+            .map_or(false, |count| *count > 1);
+
+        edit.annotate(
+            rename.old_name.clone(),
+            format!("Rename \"{}\" to \"{}\"", rename.old_name, rename.new_name),
+            if is_ambiguous {
+                Some(format!(
+                    "The label \"{}\" is defined more than once",
+                    rename.old_name
+                ))
+            } else {
+                None
+            },
+            is_ambiguous,
+        );
+    }
+
+    for document in &request.subset.documents {
+        cancellation_token.result().ok()?;
+        if let Some(data) = document.data.as_latex() {
+            for key in find_label_keys(&data.root) {
+                let name = key.to_string();
+                if let Some(rename) = renames.iter().find(|rename| rename.old_name == name) {
+                    edit.push(
+                        document.uri.as_ref().clone().into(),
+                        &rename.old_name,
+                        TextEdit::new(
+                            document.line_index.line_col_lsp_range(key.small_range()),
+                            rename.new_name.clone(),
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    let edit = edit.finish(&request.context.client_capabilities.lock().unwrap());
+
+    Some(NormalizeLabelsResult {
+        edit,
+        renames,
+        conflicts,
+    })
+}
+
+fn resolve_conflicts(renames: &mut [LabelRename]) -> Vec<LabelConflict> {
+    let mut conflicts = Vec::new();
+    loop {
+        let mut labels_by_name: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for rename in renames.iter() {
+            labels_by_name
+                .entry(rename.new_name.as_str())
+                .or_default()
+                .push(rename.old_name.as_str());
+        }
+
+        let new_conflicts: Vec<_> = labels_by_name
+            .into_iter()
+            .filter(|(_, labels)| labels.len() > 1)
+            .map(|(new_name, labels)| LabelConflict {
+                new_name: new_name.to_string(),
+                labels: labels.into_iter().map(ToString::to_string).collect(),
+            })
+            .collect();
+
+        if new_conflicts.is_empty() {
+            return conflicts;
+        }
+
+        for rename in renames.iter_mut() {
+            if new_conflicts
+                .iter()
+                .any(|conflict| conflict.labels.contains(&rename.old_name))
+            {
+                rename.new_name = rename.old_name.clone();
+            }
+        }
+
+        conflicts.extend(new_conflicts);
+    }
+}
+
+fn find_label_keys(root: &latex::SyntaxNode) -> Vec<latex::Key> {
+    let mut keys = Vec::new();
+    for node in root.descendants() {
+        if let Some(name) = latex::LabelDefinition::cast(node)
+            .and_then(|label| label.name())
+            .and_then(|name| name.key())
+        {
+            keys.push(name);
+        }
+
+        if let Some(names) = latex::LabelReference::cast(node).and_then(|label| label.name_list()) {
+            keys.extend(names.keys());
+        }
+
+        if let Some(label) = latex::LabelReferenceRange::cast(node) {
+            keys.extend(label.from().and_then(|name| name.key()));
+            keys.extend(label.to().and_then(|name| name.key()));
+        }
+    }
+    keys
+}
+
+fn make_label_name(object: &LabelledObject, old_name: &str) -> Option<String> {
+    let old_suffix = slugify(old_name.rsplit(':').next().unwrap_or(old_name));
+    let (prefix, text) = match object {
+        LabelledObject::Section { prefix, text } => {
+            let prefix = match *prefix {
+                "Part" => "part",
+                "Chapter" => "chap",
+                "Paragraph" | "Subparagraph" => "par",
+                _ => "sec",
+            };
+            (prefix.to_string(), Some(text.as_str()))
+        }
+        LabelledObject::Float { kind, caption } => {
+            let prefix = match kind {
+                LabelledFloatKind::Figure => "fig",
+                LabelledFloatKind::Table => "tab",
+                LabelledFloatKind::Listing => "lst",
+                LabelledFloatKind::Algorithm => "alg",
+            };
+            (prefix.to_string(), Some(caption.as_str()))
+        }
+        LabelledObject::Theorem { kind, description } => {
+            let kind = kind.to_lowercase();
+            let prefix = match kind.as_str() {
+                "theorem" => "thm".to_string(),
+                "lemma" => "lem".to_string(),
+                "definition" => "def".to_string(),
+                "corollary" => "cor".to_string(),
+                "proposition" => "prop".to_string(),
+                "remark" => "rem".to_string(),
+                "example" => "ex".to_string(),
+                _ => slugify(&kind),
+            };
+            (prefix, description.as_deref())
+        }
+        LabelledObject::Equation => ("eq".to_string(), None),
+        LabelledObject::EnumItem => ("item".to_string(), None),
+    };
+
+    let suffix = text
+        .map(slugify)
+        .filter(|suffix| !suffix.is_empty())
+        .unwrap_or(old_suffix);
+
+    if prefix.is_empty() || suffix.is_empty() {
+        None
+    } else {
+        Some(format!("{}:{}", prefix, suffix))
+    }
+}
+
+pub(super) fn slugify(text: &str) -> String {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            while chars.peek().map_or(false, |c| c.is_ascii_alphabetic()) {
+                chars.next();
+            }
+        } else if c.is_ascii_alphanumeric() {
+            word.push(c.to_ascii_lowercase());
+            continue;
+        }
+
+        if !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+    }
+
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words.truncate(MAX_SLUG_WORDS);
+    words.join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use lsp_types::{Range, Url};
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify(r#"The \emph{Flow} chart of X"#), "the-flow-chart");
+        assert_eq!(slugify("eq1"), "eq1");
+        assert_eq!(slugify("$\\alpha$"), "");
+    }
+
+    #[test]
+    fn test_normalize() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \section{Introduction}\label{intro}
+                        \begin{figure}
+                        \caption{Flow chart}
+                        \label{f1}
+                        \end{figure}
+                        \begin{equation}\label{eq:foo}\end{equation}
+                        \include{bar}"#},
+                ),
+                ("bar.tex", r#"\ref{intro} \cref{f1}"#),
+            ])
+            .main("main.tex")
+            .build();
+
+        let main_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let bar_uri: Url = tester.uri("bar.tex").as_ref().clone().into();
+        let request = tester.normalize_labels();
+        let actual = normalize_labels(request, CancellationToken::none()).unwrap();
+
+        assert_eq!(
+            actual.renames,
+            vec![
+                LabelRename {
+                    old_name: "intro".into(),
+                    new_name: "sec:introduction".into(),
+                },
+                LabelRename {
+                    old_name: "f1".into(),
+                    new_name: "fig:flow-chart".into(),
+                },
+            ]
+        );
+        assert!(actual.conflicts.is_empty());
+
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(
+            main_uri,
+            vec![
+                TextEdit::new(Range::new_simple(0, 29, 0, 34), "sec:introduction".into()),
+                TextEdit::new(Range::new_simple(3, 7, 3, 9), "fig:flow-chart".into()),
+            ],
+        );
+        expected_changes.insert(
+            bar_uri,
+            vec![
+                TextEdit::new(Range::new_simple(0, 5, 0, 10), "sec:introduction".into()),
+                TextEdit::new(Range::new_simple(0, 18, 0, 20), "fig:flow-chart".into()),
+            ],
+        );
+        assert_eq!(actual.edit, WorkspaceEdit::new(expected_changes));
+    }
+
+    #[test]
+    fn test_conflict() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                    \section{Foo}\label{a}
+                    \section{Foo}\label{b}
+                    \section{Bar}\label{sec:bar}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .normalize_labels();
+
+        let actual = normalize_labels(request, CancellationToken::none()).unwrap();
+        assert!(actual.renames.is_empty());
+        assert_eq!(
+            actual.conflicts,
+            vec![LabelConflict {
+                new_name: "sec:foo".into(),
+                labels: vec!["a".into(), "b".into()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use std::{path::Path, str::FromStr, sync::Arc};
+
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextDocumentIdentifier, TextEdit, Url, WorkspaceEdit,
+};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    find_caption_by_parent, find_label_number, find_section_numbers,
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LabelledFloatKind, LineIndexExt, Uri,
+};
+
+use super::{is_nested_path, structure::section_level, FeatureRequest};
+
+pub const EXPORT_OUTLINE_COMMAND: &str = "texlab.exportOutline";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutlineFormat {
+    Markdown,
+    Json,
+}
+
+impl Default for OutlineFormat {
+    fn default() -> Self {
+        Self::Markdown
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOutlineParams {
+    pub text_document: TextDocumentIdentifier,
+
+    #[serde(default)]
+    pub format: OutlineFormat,
+
+    #[serde(default)]
+    pub file_name: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineEntry {
+    /// Either `section` or the kind of the float like `figure` or `table`.
+    pub kind: String,
+
+    /// The title of the section or the caption of the float.
+    pub title: String,
+
+    pub number: Option<String>,
+    pub label: Option<String>,
+    pub location: Location,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<OutlineEntry>,
+}
+
+/// Creates a file next to the root document of the project that contains the sections
+/// and the floats of the project (following the included files) as a nested list.
+pub fn export_outline(
+    request: FeatureRequest<ExportOutlineParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let root_document = request.root_document();
+    let outline = collect_outline(&request, root_document);
+    cancellation_token.result().ok()?;
+
+    let (extension, text) = match request.params.format {
+        OutlineFormat::Markdown => ("md", render_markdown(&outline)),
+        OutlineFormat::Json => ("json", serde_json::to_string_pretty(&outline).ok()? + "\n"),
+    };
+
+    let file_name = match request
+        .params
+        .file_name
+        .as_deref()
+        .filter(|name| !name.is_empty())
+    {
+        Some(name) if is_nested_path(Path::new(name)) => name.to_string(),
+        Some(_) => return None,
+        None => {
+            let document_name = root_document.uri.path_segments()?.last()?;
+            let stem = document_name
+                .rsplit_once('.')
+                .map_or(document_name, |(stem, _)| stem);
+            format!("{}-outline.{}", stem, extension)
+        }
+    };
+
+    let root_path = root_document.uri.to_file_path().ok()?;
+    let uri = Url::from_file_path(root_path.parent()?.join(file_name)).ok()?;
+    let operations = vec![
+        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+            uri: uri.clone(),
+            options: Some(CreateFileOptions {
+                overwrite: Some(true),
+                ignore_if_exists: Some(false),
+            }),
+            annotation_id: None,
+        })),
+        DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+            edits: vec![OneOf::Left(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                text,
+            ))],
+        }),
+    ];
+
+    Some(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+/// Returns the outline of the project starting at the given document.
+fn collect_outline<P>(request: &FeatureRequest<P>, root_document: &Document) -> Vec<OutlineEntry> {
+    let mut collector = OutlineCollector {
+        request,
+        visited: FxHashSet::default(),
+        entries: Vec::new(),
+    };
+    collector.visit(root_document);
+
+    // Floats are placed below the innermost section, so they get the deepest level.
+    let mut roots: Vec<OutlineEntry> = Vec::new();
+    let mut stack: Vec<(i32, OutlineEntry)> = Vec::new();
+    for (level, entry) in collector.entries {
+        while stack.last().map_or(false, |(other, _)| *other >= level) {
+            let (_, child) = stack.pop().unwrap();
+            push_entry(&mut stack, &mut roots, child);
+        }
+
+        stack.push((level, entry));
+    }
+
+    while let Some((_, child)) = stack.pop() {
+        push_entry(&mut stack, &mut roots, child);
+    }
+
+    roots
+}
+
+fn push_entry(
+    stack: &mut [(i32, OutlineEntry)],
+    roots: &mut Vec<OutlineEntry>,
+    entry: OutlineEntry,
+) {
+    match stack.last_mut() {
+        Some((_, parent)) => parent.children.push(entry),
+        None => roots.push(entry),
+    }
+}
+
+struct OutlineCollector<'a, P> {
+    request: &'a FeatureRequest<P>,
+    visited: FxHashSet<Arc<Uri>>,
+    entries: Vec<(i32, OutlineEntry)>,
+}
+
+impl<'a, P> OutlineCollector<'a, P> {
+    fn visit(&mut self, document: &Document) -> Option<()> {
+        let data = document.data.as_latex()?;
+        if !self.visited.insert(Arc::clone(&document.uri)) {
+            return None;
+        }
+
+        let section_numbers = find_section_numbers(&self.request.subset, &data.root);
+
+        for node in data.root.descendants() {
+            if let Some(section) = latex::Section::cast(node) {
+                let label = find_label(node);
+                let number = label
+                    .as_deref()
+                    .and_then(|label| find_label_number(&self.request.subset, label))
+                    .map(ToString::to_string)
+                    .or_else(|| section_numbers.get(&section.small_range()).cloned());
+
+                let heading_end = section
+                    .name()
+                    .map_or(section.small_range().end(), |name| name.small_range().end());
+
+                self.entries.push((
+                    section_level(node.kind()),
+                    OutlineEntry {
+                        kind: "section".to_string(),
+                        title: section
+                            .name()
+                            .and_then(|name| name.content_text())
+                            .unwrap_or_default(),
+                        number,
+                        label,
+                        location: location(
+                            document,
+                            TextRange::new(section.small_range().start(), heading_end),
+                        ),
+                        children: Vec::new(),
+                    },
+                ));
+            } else if let Some(environment) = latex::Environment::cast(node) {
+                let kind = environment
+                    .begin()
+                    .and_then(|begin| begin.name())
+                    .and_then(|name| name.key())
+                    .map(|name| name.to_string())
+                    .filter(|name| !name.starts_with("sub"))
+                    .and_then(|name| LabelledFloatKind::from_str(name.trim_end_matches('*')).ok());
+
+                if let Some(kind) = kind {
+                    let label = find_label(node);
+                    let number = label
+                        .as_deref()
+                        .and_then(|label| find_label_number(&self.request.subset, label))
+                        .map(ToString::to_string);
+
+                    self.entries.push((
+                        i32::MAX,
+                        OutlineEntry {
+                            kind: kind.as_str().to_lowercase(),
+                            title: find_caption_by_parent(node).unwrap_or_default(),
+                            number,
+                            label,
+                            location: location(document, environment.small_range()),
+                            children: Vec::new(),
+                        },
+                    ));
+                }
+            } else if let Some(include) = latex::Include::cast(node)
+                .filter(|include| include.syntax().kind() == latex::LATEX_INCLUDE)
+            {
+                for path in include
+                    .path_list()
+                    .into_iter()
+                    .flat_map(|paths| paths.keys())
+                {
+                    let child = data
+                        .extras
+                        .explicit_links
+                        .iter()
+                        .find(|link| link.stem_range == path.small_range())
+                        .and_then(|link| {
+                            link.targets
+                                .iter()
+                                .find_map(|target| self.request.workspace.get(target))
+                        });
+
+                    if let Some(child) = child {
+                        self.visit(&child);
+                    }
+                }
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// Returns the first label that is defined directly inside of the given section or environment.
+fn find_label(node: &latex::SyntaxNode) -> Option<String> {
+    node.children()
+        .find_map(latex::LabelDefinition::cast)
+        .and_then(|label| label.name())
+        .and_then(|name| name.key())
+        .map(|name| name.to_string())
+}
+
+fn location(document: &Document, range: TextRange) -> Location {
+    Location::new(
+        document.uri.as_ref().clone().into(),
+        document.line_index.line_col_lsp_range(range),
+    )
+}
+
+fn render_markdown(outline: &[OutlineEntry]) -> String {
+    let mut text = String::new();
+    render_markdown_entries(outline, 0, &mut text);
+    text
+}
+
+fn render_markdown_entries(entries: &[OutlineEntry], depth: usize, text: &mut String) {
+    for entry in entries {
+        text.push_str(&"  ".repeat(depth));
+        text.push_str("- ");
+        if entry.kind == "section" {
+            if let Some(number) = &entry.number {
+                text.push_str(number);
+                text.push(' ');
+            }
+            text.push_str(&entry.title);
+        } else {
+            let mut kind = entry.kind.clone();
+            kind[..1].make_ascii_uppercase();
+            text.push_str(&kind);
+            if let Some(number) = &entry.number {
+                text.push(' ');
+                text.push_str(number);
+            }
+            text.push_str(": ");
+            text.push_str(&entry.title);
+        }
+
+        if let Some(label) = &entry.label {
+            text.push_str(&format!(" (`{}`)", label));
+        }
+
+        text.push('\n');
+        render_markdown_entries(&entry.children, depth + 1, text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_markdown() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \begin{document}
+                        \section{Foo}\label{sec:foo}
+                        \subsection{Bar}
+                        \input{figure}
+                        \section{Baz}
+                        \end{document}"#},
+                ),
+                (
+                    "figure.tex",
+                    indoc! {r#"
+                        \begin{figure}
+                        \caption{Qux}
+                        \label{fig:qux}
+                        \end{figure}"#},
+                ),
+            ])
+            .main("main.tex")
+            .build()
+            .position();
+
+        let root_document = request.root_document();
+        let actual = render_markdown(&collect_outline(&request, root_document));
+        let expected = indoc! {r#"
+            - 1 Foo (`sec:foo`)
+              - 1.1 Bar
+                - Figure: Qux (`fig:qux`)
+            - 2 Baz
+        "#};
+
+        assert_eq!(actual, expected);
+    }
+
+    fn create_outline(file_name: Option<&str>) -> Option<WorkspaceEdit> {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                    \documentclass{article}
+                    \begin{document}
+                    \section{Foo}
+                    \end{document}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .export_outline(file_name);
+
+        export_outline(request, CancellationToken::none())
+    }
+
+    fn created_uri(edit: &WorkspaceEdit) -> Url {
+        match edit.document_changes.as_ref().unwrap() {
+            DocumentChanges::Operations(operations) => match &operations[0] {
+                DocumentChangeOperation::Op(ResourceOp::Create(create)) => create.uri.clone(),
+                _ => unreachable!(),
+            },
+            DocumentChanges::Edits(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_default_file_name() {
+        let edit = create_outline(None).unwrap();
+        assert!(created_uri(&edit).as_str().ends_with("/main-outline.md"));
+    }
+
+    #[test]
+    fn test_nested_file_name() {
+        let edit = create_outline(Some("docs/outline.md")).unwrap();
+        assert!(created_uri(&edit).as_str().ends_with("/docs/outline.md"));
+    }
+
+    #[test]
+    fn test_parent_file_name() {
+        assert_eq!(create_outline(Some("../outline.md")), None);
+        assert_eq!(create_outline(Some("docs/../../outline.md")), None);
+    }
+
+    #[test]
+    fn test_absolute_file_name() {
+        assert_eq!(create_outline(Some("/tmp/outline.md")), None);
+    }
+}
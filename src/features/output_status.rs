@@ -0,0 +1,286 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use cancellation::CancellationToken;
+use lsp_types::{TextDocumentIdentifier, Url};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::DocumentLanguage;
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputStatusParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputState {
+    /// The PDF file has not been built yet.
+    Missing,
+    OutOfDate,
+    UpToDate,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputStatus {
+    pub state: OutputState,
+    pub pdf: Option<Url>,
+
+    /// The source files that have been modified after the PDF file, most recently modified first.
+    pub modified_files: Vec<Url>,
+}
+
+/// Compares the modification times of the source files of the project with the PDF file.
+/// The source files are the files of the project and the files inside of the project directory
+/// that are recorded in the `.fls` file of the last build (`-recorder`).
+pub fn find_output_status(
+    request: FeatureRequest<OutputStatusParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<OutputStatus> {
+    let root_document = request.root_document();
+    let data = root_document.data.as_latex()?;
+    let root_path = root_document.uri.to_file_path().ok()?;
+    let pdf_path = match data
+        .extras
+        .implicit_links
+        .pdf
+        .iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .find(|path| path.exists())
+    {
+        Some(path) => path,
+        None => {
+            return Some(OutputStatus {
+                state: OutputState::Missing,
+                pdf: None,
+                modified_files: Vec::new(),
+            })
+        }
+    };
+
+    let pdf_time = modification_time(&pdf_path)?;
+    let mut source_paths: FxHashSet<PathBuf> = request
+        .subset
+        .documents
+        .iter()
+        .filter(|document| {
+            matches!(
+                document.language(),
+                DocumentLanguage::Latex | DocumentLanguage::Bibtex
+            )
+        })
+        .filter_map(|document| document.uri.to_file_path().ok())
+        .filter(|path| !is_build_artifact(path))
+        .collect();
+
+    if let Ok(text) = fs::read_to_string(pdf_path.with_extension("fls")) {
+        let project_directory = root_path.parent()?;
+        source_paths.extend(
+            parse_recorder_file(&text)
+                .into_iter()
+                .filter(|path| path.starts_with(project_directory)),
+        );
+    }
+
+    cancellation_token.result().ok()?;
+
+    let mut modified_files: Vec<_> = source_paths
+        .into_iter()
+        .filter_map(|path| Some((modification_time(&path)?, path)))
+        .filter(|(time, _)| *time > pdf_time)
+        .collect();
+
+    modified_files.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+    let state = if modified_files.is_empty() {
+        OutputState::UpToDate
+    } else {
+        OutputState::OutOfDate
+    };
+
+    Some(OutputStatus {
+        state,
+        pdf: Url::from_file_path(pdf_path).ok(),
+        modified_files: modified_files
+            .into_iter()
+            .filter_map(|(_, path)| Url::from_file_path(path).ok())
+            .collect(),
+    })
+}
+
+fn modification_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+fn is_build_artifact(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|extension| extension.to_str()),
+        Some("aux" | "log" | "toc" | "lof" | "lot" | "bbl" | "blg" | "out" | "fls")
+    )
+}
+
+/// Returns the files that were read during the build and not written by it.
+fn parse_recorder_file(text: &str) -> Vec<PathBuf> {
+    let mut working_directory = PathBuf::new();
+    let mut inputs = Vec::new();
+    let mut outputs = FxHashSet::default();
+    for line in text.lines() {
+        if let Some(directory) = line.strip_prefix("PWD ") {
+            working_directory = PathBuf::from(directory);
+        } else if let Some(path) = line.strip_prefix("INPUT ") {
+            inputs.push(working_directory.join(path));
+        } else if let Some(path) = line.strip_prefix("OUTPUT ") {
+            outputs.insert(working_directory.join(path));
+        }
+    }
+
+    let mut visited = FxHashSet::default();
+    inputs
+        .into_iter()
+        .filter(|path| !outputs.contains(path) && !is_build_artifact(path))
+        .filter(|path| visited.insert(path.clone()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::OpenOptions, time::Duration};
+
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    const MAIN: &str = indoc! {r#"
+        \documentclass{article}
+        \begin{document}
+        \end{document}"#};
+
+    fn write_file(path: &Path, text: &str, seconds: u64) {
+        fs::write(path, text).unwrap();
+        OpenOptions::new()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    fn find_status(directory: &Path) -> OutputStatus {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", MAIN)])
+            .main("main.tex")
+            .current_directory(directory.to_path_buf())
+            .build()
+            .output_status();
+
+        find_output_status(request, CancellationToken::none()).unwrap()
+    }
+
+    fn file_url(path: &Path) -> Url {
+        Url::from_file_path(path).unwrap()
+    }
+
+    #[test]
+    fn test_missing() {
+        let directory = tempfile::tempdir().unwrap();
+        write_file(&directory.path().join("main.tex"), MAIN, 1000);
+
+        let status = find_status(directory.path());
+        assert_eq!(status.state, OutputState::Missing);
+        assert_eq!(status.pdf, None);
+        assert!(status.modified_files.is_empty());
+    }
+
+    #[test]
+    fn test_up_to_date() {
+        let directory = tempfile::tempdir().unwrap();
+        write_file(&directory.path().join("main.tex"), MAIN, 1000);
+        write_file(&directory.path().join("main.pdf"), "", 2000);
+
+        let status = find_status(directory.path());
+        assert_eq!(status.state, OutputState::UpToDate);
+        assert_eq!(
+            status.pdf,
+            Some(file_url(&directory.path().join("main.pdf")))
+        );
+        assert!(status.modified_files.is_empty());
+    }
+
+    #[test]
+    fn test_out_of_date() {
+        let directory = tempfile::tempdir().unwrap();
+        write_file(&directory.path().join("main.tex"), MAIN, 3000);
+        write_file(&directory.path().join("main.pdf"), "", 2000);
+
+        let status = find_status(directory.path());
+        assert_eq!(status.state, OutputState::OutOfDate);
+        assert_eq!(
+            status.modified_files,
+            vec![file_url(&directory.path().join("main.tex"))]
+        );
+    }
+
+    #[test]
+    fn test_recorder_file() {
+        let directory = tempfile::tempdir().unwrap();
+        let outside_directory = tempfile::tempdir().unwrap();
+        let figure_path = directory.path().join("figure.pdf");
+        let chapter_path = directory.path().join("chapter.tex");
+        let outside_path = outside_directory.path().join("outside.sty");
+        write_file(&directory.path().join("main.tex"), MAIN, 1000);
+        write_file(&directory.path().join("main.pdf"), "", 2000);
+        write_file(&figure_path, "", 3000);
+        write_file(&chapter_path, "", 4000);
+        write_file(&outside_path, "", 5000);
+        write_file(&directory.path().join("main.aux"), "", 6000);
+
+        let fls = format!(
+            "PWD {}\nINPUT main.tex\nINPUT {}\nINPUT chapter.tex\nINPUT figure.pdf\nOUTPUT main.aux\nINPUT main.aux\n",
+            directory.path().display(),
+            outside_path.display()
+        );
+        fs::write(directory.path().join("main.fls"), fls).unwrap();
+
+        let status = find_status(directory.path());
+        assert_eq!(status.state, OutputState::OutOfDate);
+        assert_eq!(
+            status.modified_files,
+            vec![file_url(&chapter_path), file_url(&figure_path)]
+        );
+    }
+
+    #[test]
+    fn test_parse_recorder_file() {
+        let text = indoc! {r#"
+            PWD /project
+            INPUT /usr/share/texlive/texmf-dist/tex/latex/base/article.cls
+            INPUT main.tex
+            OUTPUT main.aux
+            INPUT main.aux
+            INPUT chapters/intro.tex
+            INPUT main.tex
+            INPUT figure.pdf
+            OUTPUT main.pdf
+        "#};
+
+        assert_eq!(
+            parse_recorder_file(text),
+            vec![
+                PathBuf::from("/usr/share/texlive/texmf-dist/tex/latex/base/article.cls"),
+                PathBuf::from("/project/main.tex"),
+                PathBuf::from("/project/chapters/intro.tex"),
+                PathBuf::from("/project/figure.pdf"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{Range, TextDocumentIdentifier, TextEdit, WorkspaceEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::LineIndexExt;
+
+use super::{preview_math::find_math, FeatureRequest};
+
+pub const CONVERT_PASTE_COMMAND: &str = "texlab.convertPaste";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertPasteParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+    pub text: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Replacement {
+    Text(&'static str),
+    Math(&'static str),
+}
+
+pub fn convert_paste(
+    request: FeatureRequest<ConvertPasteParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    cancellation_token.result().ok()?;
+
+    let offset = main_document
+        .line_index
+        .offset_lsp(request.params.range.start);
+
+    let is_math = find_math(main_document, offset).map_or(false, |range| {
+        range.start() < offset && offset < range.end()
+    });
+
+    let edit = TextEdit::new(
+        request.params.range,
+        convert_text(&request.params.text, is_math),
+    );
+
+    let mut changes = HashMap::new();
+    changes.insert(main_document.uri.as_ref().clone().into(), vec![edit]);
+    Some(WorkspaceEdit::new(changes))
+}
+
+pub(super) fn convert_text(text: &str, is_math: bool) -> String {
+    let mut output = String::new();
+    let mut is_formula = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let replacement = find_replacement(c, is_math);
+        if is_formula && !matches!(replacement, Some(Replacement::Math(_))) {
+            output.push('$');
+            is_formula = false;
+        }
+
+        match replacement {
+            Some(Replacement::Math(command)) if !is_math => {
+                if !is_formula {
+                    output.push('$');
+                    is_formula = true;
+                }
+                output.push_str(command);
+            }
+            Some(Replacement::Math(command)) => {
+                output.push_str(command);
+                if is_control_word(command) && chars.peek().map_or(false, |c| c.is_alphabetic()) {
+                    output.push(' ');
+                }
+            }
+            Some(Replacement::Text(command)) => {
+                output.push_str(command);
+                if is_control_word(command)
+                    && chars
+                        .peek()
+                        .map_or(false, |c| c.is_alphabetic() || c.is_whitespace())
+                {
+                    output.push_str(if is_math { " " } else { "{}" });
+                }
+            }
+            None => output.push(c),
+        }
+    }
+
+    if is_formula {
+        output.push('$');
+    }
+
+    output
+}
+
+fn is_control_word(command: &str) -> bool {
+    command.starts_with('\\') && command.ends_with(|c: char| c.is_ascii_alphabetic())
+}
+
+fn find_replacement(c: char, is_math: bool) -> Option<Replacement> {
+    use Replacement::*;
+
+    let replacement = match c {
+        '%' => Text("\\%"),
+        '#' => Text("\\#"),
+        '&' if !is_math => Text("\\&"),
+        '$' if !is_math => Text("\\$"),
+        '_' if !is_math => Text("\\_"),
+        '{' if !is_math => Text("\\{"),
+        '}' if !is_math => Text("\\}"),
+        '~' if !is_math => Text("\\textasciitilde"),
+        '^' if !is_math => Text("\\textasciicircum"),
+        '\\' if !is_math => Text("\\textbackslash"),
+        '\u{201C}' | '\u{201E}' => Text("``"),
+        '\u{201D}' => Text("''"),
+        '\u{2018}' => Text("`"),
+        '\u{2019}' => Text("'"),
+        '\u{2013}' if !is_math => Text("--"),
+        '\u{2014}' if !is_math => Text("---"),
+        '\u{2013}' | '\u{2212}' => Math("-"),
+        '\u{2026}' => Text("\\ldots"),
+        '\u{00A0}' => Text("~"),
+        '\u{00A7}' => Text("\\S"),
+        '\u{00B6}' => Text("\\P"),
+        '\u{00A9}' => Text("\\textcopyright"),
+        '\u{00AE}' => Text("\\textregistered"),
+        '\u{2122}' => Text("\\texttrademark"),
+        '\u{2022}' => Text("\\textbullet"),
+        '\u{20AC}' => Text("\\texteuro"),
+        '\u{03B1}' => Math("\\alpha"),
+        '\u{03B2}' => Math("\\beta"),
+        '\u{03B3}' => Math("\\gamma"),
+        '\u{03B4}' => Math("\\delta"),
+        '\u{03B5}' => Math("\\epsilon"),
+        '\u{03B6}' => Math("\\zeta"),
+        '\u{03B7}' => Math("\\eta"),
+        '\u{03B8}' => Math("\\theta"),
+        '\u{03B9}' => Math("\\iota"),
+        '\u{03BA}' => Math("\\kappa"),
+        '\u{03BB}' => Math("\\lambda"),
+        '\u{03BC}' => Math("\\mu"),
+        '\u{03BD}' => Math("\\nu"),
+        '\u{03BE}' => Math("\\xi"),
+        '\u{03C0}' => Math("\\pi"),
+        '\u{03C1}' => Math("\\rho"),
+        '\u{03C3}' => Math("\\sigma"),
+        '\u{03C4}' => Math("\\tau"),
+        '\u{03C5}' => Math("\\upsilon"),
+        '\u{03C6}' => Math("\\phi"),
+        '\u{03C7}' => Math("\\chi"),
+        '\u{03C8}' => Math("\\psi"),
+        '\u{03C9}' => Math("\\omega"),
+        '\u{0393}' => Math("\\Gamma"),
+        '\u{0394}' => Math("\\Delta"),
+        '\u{0398}' => Math("\\Theta"),
+        '\u{039B}' => Math("\\Lambda"),
+        '\u{039E}' => Math("\\Xi"),
+        '\u{03A0}' => Math("\\Pi"),
+        '\u{03A3}' => Math("\\Sigma"),
+        '\u{03A6}' => Math("\\Phi"),
+        '\u{03A8}' => Math("\\Psi"),
+        '\u{03A9}' => Math("\\Omega"),
+        '\u{2264}' => Math("\\leq"),
+        '\u{2265}' => Math("\\geq"),
+        '\u{2260}' => Math("\\neq"),
+        '\u{2248}' => Math("\\approx"),
+        '\u{2261}' => Math("\\equiv"),
+        '\u{00B1}' => Math("\\pm"),
+        '\u{00D7}' => Math("\\times"),
+        '\u{00F7}' => Math("\\div"),
+        '\u{00B7}' | '\u{22C5}' => Math("\\cdot"),
+        '\u{221E}' => Math("\\infty"),
+        '\u{2192}' => Math("\\to"),
+        '\u{2190}' => Math("\\leftarrow"),
+        '\u{21D2}' => Math("\\Rightarrow"),
+        '\u{21D4}' => Math("\\Leftrightarrow"),
+        '\u{2211}' => Math("\\sum"),
+        '\u{220F}' => Math("\\prod"),
+        '\u{222B}' => Math("\\int"),
+        '\u{2202}' => Math("\\partial"),
+        '\u{2207}' => Math("\\nabla"),
+        '\u{2208}' => Math("\\in"),
+        '\u{2209}' => Math("\\notin"),
+        '\u{2282}' => Math("\\subset"),
+        '\u{2286}' => Math("\\subseteq"),
+        '\u{222A}' => Math("\\cup"),
+        '\u{2229}' => Math("\\cap"),
+        '\u{2200}' => Math("\\forall"),
+        '\u{2203}' => Math("\\exists"),
+        '\u{00AC}' => Math("\\neg"),
+        '\u{2227}' => Math("\\land"),
+        '\u{2228}' => Math("\\lor"),
+        '\u{2205}' => Math("\\emptyset"),
+        '\u{221A}' => Math("\\surd"),
+        '\u{00B0}' => Math("^\\circ"),
+        '\u{00B9}' => Math("^1"),
+        '\u{00B2}' => Math("^2"),
+        '\u{00B3}' => Math("^3"),
+        '\u{2115}' => Math("\\mathbb{N}"),
+        '\u{2124}' => Math("\\mathbb{Z}"),
+        '\u{211A}' => Math("\\mathbb{Q}"),
+        '\u{211D}' => Math("\\mathbb{R}"),
+        '\u{2102}' => Math("\\mathbb{C}"),
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Url;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_text() {
+        let actual = convert_text("“Quotes” – 50% of α≤β… done", false);
+        assert_eq!(
+            actual,
+            "``Quotes'' -- 50\\% of $\\alpha\\leq\\beta$\\ldots{} done"
+        );
+    }
+
+    #[test]
+    fn test_math() {
+        let actual = convert_text("α x ≤ πr²", true);
+        assert_eq!(actual, "\\alpha x \\leq \\pi r^2");
+    }
+
+    #[test]
+    fn test_special_characters() {
+        let actual = convert_text("a_b & {c} #1 ~", false);
+        assert_eq!(actual, "a\\_b \\& \\{c\\} \\#1 \\textasciitilde");
+    }
+
+    #[test]
+    fn test_workspace_edit() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", r#"Foo $x = $"#)])
+            .main("main.tex")
+            .build();
+
+        let uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let request = tester.convert_paste(Range::new_simple(0, 9, 0, 9), "α²");
+        let actual = convert_paste(request, CancellationToken::none()).unwrap();
+
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(
+            uri,
+            vec![TextEdit::new(
+                Range::new_simple(0, 9, 0, 9),
+                "\\alpha^2".into(),
+            )],
+        );
+        assert_eq!(actual, WorkspaceEdit::new(expected_changes));
+    }
+}
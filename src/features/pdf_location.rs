@@ -0,0 +1,106 @@
+use std::path::PathBuf;
+
+use cancellation::CancellationToken;
+use lsp_types::{Position, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{Document, SyncTexData};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocateInPdfParams {
+    pub text_document: TextDocumentIdentifier,
+    pub positions: Vec<Position>,
+}
+
+/// A rectangle on a page of the PDF file in PDF points,
+/// measured from the top left corner of the page.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfLocation {
+    pub page: u32,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Maps the given source positions to their locations in the PDF file
+/// using the SyncTeX file of the last build.
+/// The result contains one entry for every position.
+pub fn locate_in_pdf(
+    request: FeatureRequest<LocateInPdfParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<Option<PdfLocation>>> {
+    let root_document = request.root_document();
+
+    let data = SyncTexData::load(&find_pdf_path(root_document)?)?;
+    let tex_path = request.main_document().uri.to_file_path().ok()?;
+    cancellation_token.result().ok()?;
+
+    let locations = request
+        .params
+        .positions
+        .iter()
+        .map(|position| {
+            let record = data.find(&tex_path, position.line + 1)?;
+            let (y, height) = if record.kind.is_box() {
+                (record.y - record.height, record.height + record.depth)
+            } else {
+                (record.y, 0.0)
+            };
+
+            Some(PdfLocation {
+                page: record.page,
+                x: record.x,
+                y,
+                width: record.width.max(0.0),
+                height,
+            })
+        })
+        .collect();
+
+    Some(locations)
+}
+
+fn find_pdf_path(root_document: &Document) -> Option<PathBuf> {
+    root_document
+        .data
+        .as_latex()?
+        .extras
+        .implicit_links
+        .pdf
+        .iter()
+        .find_map(|uri| uri.to_file_path().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_without_synctex() {
+        let directory = tempfile::tempdir().unwrap();
+        fs::write(directory.path().join("main.pdf"), "").unwrap();
+
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                "\\documentclass{article}\n\\begin{document}\nFoo\n\\end{document}",
+            )])
+            .main("main.tex")
+            .current_directory(directory.path().to_path_buf())
+            .line(2)
+            .character(0)
+            .build()
+            .locate_in_pdf();
+
+        assert_eq!(locate_in_pdf(request, CancellationToken::none()), None);
+    }
+}
@@ -0,0 +1,504 @@
+use cancellation::CancellationToken;
+use cstree::{TextRange, TextSize};
+use lsp_types::{
+    CreateFile, CreateFileOptions, DocumentChangeOperation, DocumentChanges, Location, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, ResourceOp, TextDocumentEdit,
+    TextDocumentIdentifier, TextEdit, Url, WorkspaceEdit,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    indentation,
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, Uri,
+};
+
+use super::FeatureRequest;
+
+pub const EXTRACT_PREAMBLE_COMMAND: &str = "texlab.extractSharedPreamble";
+
+const MIN_SHARED_LINES: usize = 2;
+
+const DEFAULT_PACKAGE_NAME: &str = "preamble";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedPreamblesParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedPreamble {
+    pub text: String,
+    pub locations: Vec<Location>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtractPreambleParams {
+    pub text_document: TextDocumentIdentifier,
+
+    #[serde(default)]
+    pub package_name: Option<String>,
+}
+
+#[derive(Debug)]
+struct Preamble<'a> {
+    document: &'a Document,
+    lines: Vec<PreambleLine<'a>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PreambleLine<'a> {
+    text: &'a str,
+    range: TextRange,
+}
+
+#[derive(Debug)]
+struct SharedBlock<'a> {
+    lines: Vec<&'a str>,
+    occurrences: Vec<(usize, TextRange)>,
+}
+
+pub fn find_shared_preambles(
+    request: FeatureRequest<SharedPreamblesParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<SharedPreamble>> {
+    let documents = request.workspace.documents();
+    let preambles: Vec<_> = documents
+        .iter()
+        .filter_map(|doc| Preamble::new(doc))
+        .collect();
+    let blocks = find_shared_blocks(&preambles, cancellation_token)?;
+
+    let shared_preambles = blocks
+        .into_iter()
+        .map(|block| SharedPreamble {
+            text: block.text(
+                &preambles,
+                &indentation::unit(preambles[block.occurrences[0].0].document),
+            ),
+            locations: block
+                .occurrences
+                .iter()
+                .map(|(index, range)| {
+                    let document = preambles[*index].document;
+                    Location::new(
+                        document.uri.as_ref().clone().into(),
+                        document.line_index.line_col_lsp_range(*range),
+                    )
+                })
+                .collect(),
+        })
+        .collect();
+
+    Some(shared_preambles)
+}
+
+pub fn extract_shared_preamble(
+    request: FeatureRequest<ExtractPreambleParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let documents = request.workspace.documents();
+    let preambles: Vec<_> = documents
+        .iter()
+        .filter_map(|doc| Preamble::new(doc))
+        .collect();
+    let main_index = preambles
+        .iter()
+        .position(|preamble| preamble.document.uri == request.main_document().uri)?;
+
+    let block = find_shared_blocks(&preambles, cancellation_token)?
+        .into_iter()
+        .find(|block| {
+            block
+                .occurrences
+                .iter()
+                .any(|(index, _)| *index == main_index)
+        })?;
+
+    let package_name = request
+        .params
+        .package_name
+        .as_deref()
+        .map(|name| name.trim_end_matches(".sty"))
+        .filter(|name| !name.is_empty())
+        .unwrap_or(DEFAULT_PACKAGE_NAME);
+
+    let package_uri: Url = request
+        .main_document()
+        .uri
+        .join(&format!("{}.sty", package_name))
+        .ok()?;
+
+    let package_text = format!(
+        "\\NeedsTeXFormat{{LaTeX2e}}\n\\ProvidesPackage{{{}}}\n\n{}",
+        package_name,
+        block.text(&preambles, &indentation::unit(request.main_document()))
+    );
+
+    let mut operations = vec![
+        DocumentChangeOperation::Op(ResourceOp::Create(CreateFile {
+            uri: package_uri.clone(),
+            options: Some(CreateFileOptions {
+                overwrite: Some(false),
+                ignore_if_exists: Some(false),
+            }),
+            annotation_id: None,
+        })),
+        DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: package_uri.clone(),
+                version: None,
+            },
+            edits: vec![OneOf::Left(TextEdit::new(
+                Range::new(Position::new(0, 0), Position::new(0, 0)),
+                package_text,
+            ))],
+        }),
+    ];
+
+    for (index, range) in &block.occurrences {
+        let document = preambles[*index].document;
+        let package_path = relative_package_path(&document.uri, &package_uri, package_name);
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier {
+                uri: document.uri.as_ref().clone().into(),
+                version: None,
+            },
+            edits: vec![OneOf::Left(TextEdit::new(
+                document.line_index.line_col_lsp_range(*range),
+                format!(
+                    "{}\\usepackage{{{}}}\n",
+                    indentation::line_indent(&document.text, range.start().into()),
+                    package_path
+                ),
+            ))],
+        }));
+    }
+
+    Some(WorkspaceEdit {
+        changes: None,
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        change_annotations: None,
+    })
+}
+
+fn relative_package_path(document_uri: &Uri, package_uri: &Url, package_name: &str) -> String {
+    let directory = |uri: &Url| -> Vec<String> {
+        let mut segments: Vec<_> = uri
+            .path_segments()
+            .map(|segments| segments.map(ToString::to_string).collect())
+            .unwrap_or_default();
+        segments.pop();
+        segments
+    };
+
+    let document_dir = directory(document_uri);
+    let package_dir = directory(package_uri);
+    let common_len = document_dir
+        .iter()
+        .zip(&package_dir)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut path = String::new();
+    for _ in common_len..document_dir.len() {
+        path.push_str("../");
+    }
+
+    for segment in &package_dir[common_len..] {
+        path.push_str(segment);
+        path.push('/');
+    }
+
+    path.push_str(package_name);
+    path
+}
+
+impl<'a> Preamble<'a> {
+    fn new(document: &'a Document) -> Option<Self> {
+        let data = document.data.as_latex()?;
+        if !data.extras.has_document_environment {
+            return None;
+        }
+
+        let end = data
+            .root
+            .descendants()
+            .filter_map(latex::Environment::cast)
+            .find(|environment| {
+                environment
+                    .begin()
+                    .and_then(|begin| begin.name())
+                    .and_then(|name| name.key())
+                    .map_or(false, |name| name.to_string() == "document")
+            })?
+            .syntax()
+            .text_range()
+            .start();
+
+        let mut lines = Vec::new();
+        let mut offset = 0;
+        for line in document.text[..usize::from(end)].split_inclusive('\n') {
+            let range = TextRange::new(
+                TextSize::from(offset as u32),
+                TextSize::from((offset + line.len()) as u32),
+            );
+            offset += line.len();
+
+            let text = line.trim();
+            if text.is_empty() || text.starts_with('%') || text.starts_with("\\documentclass") {
+                continue;
+            }
+
+            lines.push(PreambleLine { text, range });
+        }
+
+        Some(Self { document, lines })
+    }
+
+    fn find_block(&self, block: &[&str]) -> Option<TextRange> {
+        self.lines
+            .windows(block.len())
+            .find(|window| {
+                window
+                    .iter()
+                    .zip(block)
+                    .all(|(line, text)| line.text == *text)
+            })
+            .map(|window| window[0].range.cover(window[window.len() - 1].range))
+    }
+}
+
+impl<'a> SharedBlock<'a> {
+    /// Returns the text of the first occurrence of the block
+    /// that is moved to the start of the line and indented with the given unit.
+    fn text(&self, preambles: &[Preamble], unit: &str) -> String {
+        let (index, range) = self.occurrences[0];
+        let text = indentation::reindent(&preambles[index].document.text[range], unit);
+        if text.ends_with('\n') {
+            text
+        } else {
+            format!("{}\n", text)
+        }
+    }
+}
+
+fn find_shared_blocks<'a>(
+    preambles: &[Preamble<'a>],
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<SharedBlock<'a>>> {
+    let mut blocks: Vec<SharedBlock> = Vec::new();
+    for (i, left) in preambles.iter().enumerate() {
+        for right in &preambles[i + 1..] {
+            cancellation_token.result().ok()?;
+
+            let (start, len) = match longest_common_run(&left.lines, &right.lines) {
+                Some((start, len)) if len >= MIN_SHARED_LINES => (start, len),
+                _ => continue,
+            };
+
+            let lines: Vec<_> = left.lines[start..start + len]
+                .iter()
+                .map(|line| line.text)
+                .collect();
+
+            if blocks.iter().any(|block| block.lines == lines) {
+                continue;
+            }
+
+            let occurrences = preambles
+                .iter()
+                .enumerate()
+                .filter_map(|(index, preamble)| Some((index, preamble.find_block(&lines)?)))
+                .collect();
+
+            blocks.push(SharedBlock { lines, occurrences });
+        }
+    }
+
+    blocks.sort_by(|a, b| {
+        b.lines
+            .len()
+            .cmp(&a.lines.len())
+            .then_with(|| b.occurrences.len().cmp(&a.occurrences.len()))
+    });
+    Some(blocks)
+}
+
+fn longest_common_run(left: &[PreambleLine], right: &[PreambleLine]) -> Option<(usize, usize)> {
+    let mut lengths = vec![vec![0; right.len() + 1]; left.len() + 1];
+    let mut best: Option<(usize, usize)> = None;
+    for (i, left_line) in left.iter().enumerate() {
+        for (j, right_line) in right.iter().enumerate() {
+            if left_line.text == right_line.text {
+                let len = lengths[i][j] + 1;
+                lengths[i + 1][j + 1] = len;
+                if best.map_or(true, |(_, best_len)| len > best_len) {
+                    best = Some((i + 1 - len, len));
+                }
+            }
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    fn tester(main: &str) -> FeatureTester<'_> {
+        FeatureTester::builder()
+            .files(vec![
+                (
+                    "foo.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \usepackage{amsmath}
+                        \usepackage{graphicx}
+                        \newcommand{\R}{\mathbb{R}}
+                        \begin{document}
+                        Foo
+                        \end{document}"#},
+                ),
+                (
+                    "bar.tex",
+                    indoc! {r#"
+                        \documentclass{beamer}
+                        \usepackage{graphicx}
+                        \newcommand{\R}{\mathbb{R}}
+                        \title{Bar}
+                        \begin{document}
+                        Bar
+                        \end{document}"#},
+                ),
+                ("baz.tex", r#"\usepackage{graphicx}"#),
+            ])
+            .main(main)
+            .build()
+    }
+
+    #[test]
+    fn test_shared_preambles() {
+        let tester = tester("foo.tex");
+        let foo_uri: Url = tester.uri("foo.tex").as_ref().clone().into();
+        let bar_uri: Url = tester.uri("bar.tex").as_ref().clone().into();
+        let request = tester.shared_preambles();
+
+        let mut actual = find_shared_preambles(request, CancellationToken::none()).unwrap();
+        assert_eq!(actual.len(), 1);
+
+        let mut preamble = actual.pop().unwrap();
+        preamble
+            .locations
+            .sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+
+        assert_eq!(
+            preamble,
+            SharedPreamble {
+                text: "\\usepackage{graphicx}\n\\newcommand{\\R}{\\mathbb{R}}\n".into(),
+                locations: vec![
+                    Location::new(bar_uri, Range::new_simple(1, 0, 3, 0)),
+                    Location::new(foo_uri, Range::new_simple(2, 0, 4, 0)),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_no_shared_preamble() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "foo.tex",
+                    "\\documentclass{article}\n\\usepackage{amsmath}\n\\begin{document}\\end{document}",
+                ),
+                (
+                    "bar.tex",
+                    "\\documentclass{article}\n\\usepackage{amsmath}\n\\begin{document}\\end{document}",
+                ),
+            ])
+            .main("foo.tex")
+            .build()
+            .extract_preamble(None);
+
+        assert_eq!(
+            extract_shared_preamble(request, CancellationToken::none()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract() {
+        let tester = tester("bar.tex");
+        let foo_uri: Url = tester.uri("foo.tex").as_ref().clone().into();
+        let bar_uri: Url = tester.uri("bar.tex").as_ref().clone().into();
+        let package_uri: Url = tester.uri("common.sty").as_ref().clone().into();
+        let request = tester.extract_preamble(Some("common"));
+
+        let edit = extract_shared_preamble(request, CancellationToken::none()).unwrap();
+        let mut operations = match edit.document_changes {
+            Some(DocumentChanges::Operations(operations)) => operations,
+            _ => panic!("expected document change operations"),
+        };
+
+        let mut document_edits: Vec<_> = operations
+            .split_off(2)
+            .into_iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Edit(edit) => Some(edit),
+                DocumentChangeOperation::Op(_) => None,
+            })
+            .collect();
+        document_edits.sort_by(|a, b| {
+            a.text_document
+                .uri
+                .as_str()
+                .cmp(b.text_document.uri.as_str())
+        });
+
+        assert!(matches!(
+            &operations[0],
+            DocumentChangeOperation::Op(ResourceOp::Create(create)) if create.uri == package_uri
+        ));
+        assert!(matches!(
+            &operations[1],
+            DocumentChangeOperation::Edit(edit) if edit.edits == vec![OneOf::Left(TextEdit::new(
+                Range::new_simple(0, 0, 0, 0),
+                "\\NeedsTeXFormat{LaTeX2e}\n\\ProvidesPackage{common}\n\n\\usepackage{graphicx}\n\\newcommand{\\R}{\\mathbb{R}}\n".into(),
+            ))]
+        ));
+
+        assert_eq!(
+            document_edits,
+            vec![
+                TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier {
+                        uri: bar_uri,
+                        version: None,
+                    },
+                    edits: vec![OneOf::Left(TextEdit::new(
+                        Range::new_simple(1, 0, 3, 0),
+                        "\\usepackage{common}\n".into(),
+                    ))],
+                },
+                TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier {
+                        uri: foo_uri,
+                        version: None,
+                    },
+                    edits: vec![OneOf::Left(TextEdit::new(
+                        Range::new_simple(2, 0, 4, 0),
+                        "\\usepackage{common}\n".into(),
+                    ))],
+                },
+            ]
+        );
+    }
+}
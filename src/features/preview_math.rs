@@ -0,0 +1,234 @@
+use std::{
+    fs,
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Position, Range, TextDocumentIdentifier};
+use serde::{Deserialize, Serialize};
+use tempfile::tempdir;
+
+use crate::{
+    syntax::{latex, CstNode},
+    Document, LineIndexExt, MathPreviewOptions, LANGUAGE_DATA,
+};
+
+const PREVIEW_PREAMBLE: &str = r#"\documentclass[preview]{standalone}
+\usepackage{amsmath}
+\usepackage{amssymb}
+\begin{document}
+"#;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewMathParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Option<Range>,
+    pub svg: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct MathPreviewer {
+    generation: AtomicUsize,
+    last_source: Mutex<Option<String>>,
+}
+
+impl MathPreviewer {
+    pub fn preview(
+        &self,
+        document: &Document,
+        position: Position,
+        options: &MathPreviewOptions,
+    ) -> Option<PreviewMathParams> {
+        let range = find_math(document, document.line_index.offset_lsp(position));
+        let source = range.map(|range| document.text[range].to_string());
+
+        // Only a different formula supersedes the render that is in progress.
+        let generation = {
+            let mut last_source = self.last_source.lock().unwrap();
+            if *last_source == source {
+                return None;
+            }
+            *last_source = source.clone();
+            self.generation.fetch_add(1, Ordering::SeqCst) + 1
+        };
+
+        let svg = source.and_then(|source| render_math(options, &source));
+        if self.generation.load(Ordering::SeqCst) != generation {
+            return None;
+        }
+
+        Some(PreviewMathParams {
+            text_document: TextDocumentIdentifier::new(document.uri.as_ref().clone().into()),
+            range: range.map(|range| document.line_index.line_col_lsp_range(range)),
+            svg,
+        })
+    }
+}
+
+pub(super) fn find_math(document: &Document, offset: TextSize) -> Option<TextRange> {
+    let data = document.data.as_latex()?;
+    data.root
+        .descendants()
+        .filter_map(find_math_range)
+        .find(|range| range.contains_inclusive(offset))
+}
+
+fn find_math_range(node: &latex::SyntaxNode) -> Option<TextRange> {
+    if let Some(formula) = latex::Formula::cast(node) {
+        return Some(formula.small_range());
+    }
+
+    if let Some(equation) = latex::Equation::cast(node) {
+        return Some(equation.small_range());
+    }
+
+    let environment = latex::Environment::cast(node)?;
+    let name = environment.begin()?.name()?.key()?.to_string();
+    if LANGUAGE_DATA
+        .math_environments
+        .iter()
+        .any(|math_environment| math_environment == &name)
+    {
+        Some(environment.small_range())
+    } else {
+        None
+    }
+}
+
+fn render_math(options: &MathPreviewOptions, source: &str) -> Option<String> {
+    let output = match &options.executable {
+        Some(executable) => {
+            let args = options
+                .args
+                .iter()
+                .flatten()
+                .map(|arg| arg.replace("%s", source));
+
+            Command::new(executable)
+                .args(args)
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?
+        }
+        None => {
+            let directory = tempdir().ok()?;
+            fs::write(
+                directory.path().join("preview.tex"),
+                format!("{}{}\n\\end{{document}}\n", PREVIEW_PREAMBLE, source),
+            )
+            .ok()?;
+
+            let status = Command::new("latex")
+                .args(&["-interaction=nonstopmode", "-halt-on-error", "preview.tex"])
+                .current_dir(directory.path())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .ok()?;
+
+            if !status.success() {
+                return None;
+            }
+
+            Command::new("dvisvgm")
+                .args(&["--no-fonts", "--stdout", "preview.dvi"])
+                .current_dir(directory.path())
+                .stdin(Stdio::null())
+                .stderr(Stdio::null())
+                .output()
+                .ok()?
+        }
+    };
+
+    let svg = String::from_utf8_lossy(&output.stdout).into_owned();
+    if output.status.success() && !svg.is_empty() {
+        Some(svg)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn find_math_at(text: &str, line: u32, character: u32) -> Option<String> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(line)
+            .character(character)
+            .build()
+            .position();
+
+        let document = request.main_document();
+        let offset = document.line_index.offset_lsp(request.params.position);
+        find_math(document, offset).map(|range| document.text[range].to_string())
+    }
+
+    #[test]
+    fn test_inline_formula() {
+        let actual = find_math_at(r#"Foo $a^2 + b^2$ bar"#, 0, 7);
+        assert_eq!(actual.as_deref(), Some("$a^2 + b^2$"));
+    }
+
+    #[test]
+    fn test_math_environment() {
+        let text = indoc! {r#"
+            \begin{align}
+                a &= b \text{ if $c$}
+            \end{align}
+            foo"#};
+
+        let actual = find_math_at(text, 1, 20);
+        assert_eq!(
+            actual.as_deref(),
+            Some("\\begin{align}\n    a &= b \\text{ if $c$}\n\\end{align}")
+        );
+    }
+
+    #[test]
+    fn test_same_formula() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"Foo $a^2 + b^2$ bar"#)])
+            .main("main.tex")
+            .build()
+            .position();
+
+        let document = request.main_document();
+        let options = MathPreviewOptions {
+            executable: Some("echo".into()),
+            args: Some(vec!["%s".into()]),
+        };
+
+        let previewer = MathPreviewer::default();
+        let preview = previewer.preview(document, Position::new(0, 6), &options);
+        assert_eq!(
+            preview.and_then(|params| params.svg).as_deref(),
+            Some("$a^2 + b^2$\n")
+        );
+
+        assert_eq!(
+            previewer.preview(document, Position::new(0, 9), &options),
+            None
+        );
+        assert_eq!(previewer.generation.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_outside_math() {
+        let actual = find_math_at(r#"Foo $a$ \begin{itemize}\item bar\end{itemize}"#, 0, 30);
+        assert_eq!(actual, None);
+    }
+}
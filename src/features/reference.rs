@@ -1,4 +1,6 @@
+mod bibliography;
 mod entry;
+mod index_entry;
 mod label;
 mod string;
 
@@ -6,7 +8,9 @@ use cancellation::CancellationToken;
 use lsp_types::{Location, ReferenceParams};
 
 use self::{
-    entry::find_entry_references, label::find_label_references, string::find_string_references,
+    bibliography::find_bibliography_references, entry::find_entry_references,
+    index_entry::find_index_entry_references, label::find_label_references,
+    string::find_string_references,
 };
 
 use super::{cursor::CursorContext, FeatureRequest};
@@ -21,5 +25,7 @@ pub fn find_all_references(
     find_label_references(&context, cancellation_token, &mut references);
     find_entry_references(&context, cancellation_token, &mut references);
     find_string_references(&context, cancellation_token, &mut references);
+    find_bibliography_references(&context, cancellation_token, &mut references);
+    find_index_entry_references(&context, cancellation_token, &mut references);
     Some(references)
 }
@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use cancellation::CancellationToken;
+use lsp_types::{Location, Range, ReferenceParams};
+
+use crate::{
+    features::cursor::CursorContext,
+    syntax::{bibtex, latex::ExplicitLinkKind},
+    DocumentData, LineIndexExt, RangeExt, Uri,
+};
+
+/// Finds the documents that include the bibliography under the cursor.
+/// Inside of a BibTeX document, every position that is not a word
+/// (like an entry key or a string name) refers to the document itself.
+pub fn find_bibliography_references(
+    context: &CursorContext<ReferenceParams>,
+    cancellation_token: &CancellationToken,
+    references: &mut Vec<Location>,
+) -> Option<()> {
+    let target = find_target(context)?;
+    if context.request.params.context.include_declaration {
+        references.push(Location::new(
+            target.as_ref().clone().into(),
+            Range::new_simple(0, 0, 0, 0),
+        ));
+    }
+
+    for document in &context.request.subset.documents {
+        cancellation_token.result().ok()?;
+
+        match &document.data {
+            DocumentData::Latex(data) => {
+                data.extras
+                    .explicit_links
+                    .iter()
+                    .filter(|link| link.kind == ExplicitLinkKind::Bibtex)
+                    .filter(|link| link.targets.contains(&target))
+                    .map(|link| document.line_index.line_col_lsp_range(link.stem_range))
+                    .for_each(|range| {
+                        references.push(Location::new(document.uri.as_ref().clone().into(), range));
+                    });
+            }
+            DocumentData::Markdown(data) if data.bibliography_targets.contains(&target) => {
+                references.push(Location::new(
+                    document.uri.as_ref().clone().into(),
+                    Range::new_simple(0, 0, 0, 0),
+                ));
+            }
+            DocumentData::Markdown(_) | DocumentData::Bibtex(_) | DocumentData::BuildLog(_) => {}
+        }
+    }
+    Some(())
+}
+
+fn find_target(context: &CursorContext<ReferenceParams>) -> Option<Arc<Uri>> {
+    let main_document = context.request.main_document();
+    match &main_document.data {
+        DocumentData::Bibtex(_) => {
+            let token = context.cursor.as_bibtex()?;
+            if token.kind() == bibtex::WORD {
+                return None;
+            }
+
+            Some(Arc::clone(&main_document.uri))
+        }
+        DocumentData::Latex(data) => {
+            let link = data
+                .extras
+                .explicit_links
+                .iter()
+                .filter(|link| link.kind == ExplicitLinkKind::Bibtex)
+                .find(|link| link.stem_range.contains_inclusive(context.offset))?;
+
+            link.targets
+                .iter()
+                .find(|target| {
+                    context
+                        .request
+                        .subset
+                        .documents
+                        .iter()
+                        .any(|document| document.uri.as_ref() == target.as_ref())
+                })
+                .cloned()
+        }
+        DocumentData::Markdown(_) | DocumentData::BuildLog(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_empty_bibtex_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.bib", "")])
+            .main("main.bib")
+            .line(0)
+            .character(0)
+            .build()
+            .reference();
+
+        let mut actual_references = Vec::new();
+        let context = CursorContext::new(request);
+        find_bibliography_references(&context, CancellationToken::none(), &mut actual_references);
+
+        assert!(actual_references.is_empty());
+    }
+
+    #[test]
+    fn test_bibtex_document() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("foo.tex", r#"\addbibresource{main.bib}"#),
+                ("bar.tex", r#"\bibliography{main}\include{foo}"#),
+                ("main.bib", r#"@article{foo,}"#),
+            ])
+            .main("main.bib")
+            .line(0)
+            .character(2)
+            .include_declaration(true)
+            .build();
+        let foo_uri = tester.uri("foo.tex");
+        let bar_uri = tester.uri("bar.tex");
+        let bib_uri = tester.uri("main.bib");
+
+        let mut actual_references = Vec::new();
+        let request = tester.reference();
+        let context = CursorContext::new(request);
+        find_bibliography_references(&context, CancellationToken::none(), &mut actual_references);
+        actual_references.sort_by(|a, b| a.uri.cmp(&b.uri));
+
+        let expected_references = vec![
+            Location::new(
+                bar_uri.as_ref().clone().into(),
+                Range::new_simple(0, 14, 0, 18),
+            ),
+            Location::new(
+                foo_uri.as_ref().clone().into(),
+                Range::new_simple(0, 16, 0, 24),
+            ),
+            Location::new(
+                bib_uri.as_ref().clone().into(),
+                Range::new_simple(0, 0, 0, 0),
+            ),
+        ];
+
+        assert_eq!(actual_references, expected_references);
+    }
+
+    #[test]
+    fn test_latex_include() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("main.tex", r#"\addbibresource{main.bib}"#),
+                ("main.bib", ""),
+            ])
+            .main("main.tex")
+            .line(0)
+            .character(18)
+            .build();
+        let uri = tester.uri("main.tex");
+
+        let mut actual_references = Vec::new();
+        let request = tester.reference();
+        let context = CursorContext::new(request);
+        find_bibliography_references(&context, CancellationToken::none(), &mut actual_references);
+
+        let expected_references = vec![Location::new(
+            uri.as_ref().clone().into(),
+            Range::new_simple(0, 16, 0, 24),
+        )];
+
+        assert_eq!(actual_references, expected_references);
+    }
+}
@@ -15,7 +15,8 @@ pub fn find_entry_references(
     let (key_text, _) = context
         .find_citation_key_word()
         .or_else(|| context.find_citation_key_command())
-        .or_else(|| context.find_entry_key())?;
+        .or_else(|| context.find_entry_key())
+        .or_else(|| context.find_markdown_citation_key())?;
 
     for document in &context.request.subset.documents {
         cancellation_token.result().ok()?;
@@ -44,6 +45,16 @@ pub fn find_entry_references(
                         references.push(Location::new(document.uri.as_ref().clone().into(), range));
                     });
             }
+            DocumentData::Markdown(data) => {
+                data.parse
+                    .citations
+                    .iter()
+                    .filter(|citation| citation.text == key_text)
+                    .map(|citation| document.line_index.line_col_lsp_range(citation.range))
+                    .for_each(|range| {
+                        references.push(Location::new(document.uri.as_ref().clone().into(), range));
+                    });
+            }
             DocumentData::Bibtex(_) | DocumentData::BuildLog(_) => {}
         }
     }
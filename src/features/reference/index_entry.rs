@@ -0,0 +1,80 @@
+use cancellation::CancellationToken;
+use lsp_types::{Location, ReferenceParams};
+
+use crate::{features::cursor::CursorContext, find_index_entries, LineIndexExt};
+
+pub fn find_index_entry_references(
+    context: &CursorContext<ReferenceParams>,
+    cancellation_token: &CancellationToken,
+    references: &mut Vec<Location>,
+) -> Option<()> {
+    cancellation_token.result().ok()?;
+
+    let main_document = context.request.main_document();
+    let key = find_index_entries(&main_document.data.as_latex()?.root)
+        .into_iter()
+        .find(|entry| entry.range.contains_inclusive(context.offset))?
+        .key();
+
+    for document in &context.request.subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            cancellation_token.result().ok()?;
+            for entry in find_index_entries(&data.root)
+                .into_iter()
+                .filter(|entry| entry.key() == key)
+            {
+                references.push(Location::new(
+                    document.uri.as_ref().clone().into(),
+                    document.line_index.line_col_lsp_range(entry.range),
+                ));
+            }
+        }
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_sort_key() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "foo.tex",
+                    r#"\index{Newton@\textsc{Newton}}\input{bar.tex}"#,
+                ),
+                ("bar.tex", r#"\index{Newton|textbf}\index{Leibniz}"#),
+            ])
+            .main("foo.tex")
+            .line(0)
+            .character(9)
+            .build();
+        let foo_uri = tester.uri("foo.tex");
+        let bar_uri = tester.uri("bar.tex");
+        let mut actual_references = Vec::new();
+
+        let request = tester.reference();
+        let context = CursorContext::new(request);
+        find_index_entry_references(&context, CancellationToken::none(), &mut actual_references);
+
+        actual_references.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+        let expected_references = vec![
+            Location::new(
+                bar_uri.as_ref().clone().into(),
+                Range::new_simple(0, 7, 0, 20),
+            ),
+            Location::new(
+                foo_uri.as_ref().clone().into(),
+                Range::new_simple(0, 7, 0, 29),
+            ),
+        ];
+        assert_eq!(actual_references, expected_references);
+    }
+}
@@ -1,11 +1,13 @@
-use std::collections::HashMap;
-
 use cancellation::CancellationToken;
 use cstree::{TextRange, TextSize};
 use lsp_types::{Range, RenameParams, TextEdit, WorkspaceEdit};
 
 use crate::{
-    features::cursor::{CursorContext, HasPosition},
+    features::{
+        cursor::{CursorContext, HasPosition},
+        workspace_edit::AnnotatedWorkspaceEdit,
+    },
+    syntax::{latex, CstNode},
     LineIndexExt,
 };
 
@@ -29,35 +31,62 @@ pub fn rename_command(
     cancellation_token.result().ok()?;
     prepare_command_rename(context, cancellation_token)?;
     let name = context.cursor.as_latex()?.text();
-    let mut changes = HashMap::new();
+    let is_defined = context
+        .request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.root.descendants())
+        .filter_map(latex::CommandDefinition::cast)
+        .filter_map(|definition| definition.name()?.command())
+        .any(|command| command.text() == name);
+
+    let mut edit = AnnotatedWorkspaceEdit::default();
+    edit.annotate(
+        "command",
+        format!("Rename the command \"{}\"", name),
+        if is_defined {
+            None
+        } else {
+            Some("The command is not defined inside of the project".to_string())
+        },
+        !is_defined,
+    );
+
     for document in &context.request.subset.documents {
         cancellation_token.result().ok()?;
 
         if let Some(data) = document.data.as_latex() {
-            let edits = data
+            for token in data
                 .root
                 .descendants_with_tokens()
                 .filter_map(|element| element.into_token())
                 .filter(|token| token.kind().is_command_name() && token.text() == name)
-                .map(|token| {
-                    let range = token.text_range();
-                    let range = document.line_index.line_col_lsp_range(TextRange::new(
-                        range.start() + TextSize::from(1),
-                        range.end(),
-                    ));
-                    TextEdit::new(range, context.request.params.new_name.clone())
-                })
-                .collect();
-
-            changes.insert(document.uri.as_ref().clone().into(), edits);
+            {
+                let range = token.text_range();
+                let range = document.line_index.line_col_lsp_range(TextRange::new(
+                    range.start() + TextSize::from(1),
+                    range.end(),
+                ));
+
+                edit.push(
+                    document.uri.as_ref().clone().into(),
+                    "command",
+                    TextEdit::new(range, context.request.params.new_name.clone()),
+                );
+            }
         }
     }
 
-    Some(WorkspaceEdit::new(changes))
+    let client_capabilities = context.request.context.client_capabilities.lock().unwrap();
+    Some(edit.finish(&client_capabilities))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use crate::{features::testing::FeatureTester, RangeExt};
 
     use super::*;
@@ -1,10 +1,11 @@
-use std::collections::HashMap;
-
 use cancellation::CancellationToken;
-use lsp_types::{Range, RenameParams, TextEdit, WorkspaceEdit};
+use lsp_types::{Range, RenameParams, TextEdit, Url, WorkspaceEdit};
 
 use crate::{
-    features::cursor::{CursorContext, HasPosition},
+    features::{
+        cursor::{CursorContext, HasPosition},
+        workspace_edit::AnnotatedWorkspaceEdit,
+    },
     syntax::{bibtex, latex, CstNode},
     DocumentData, LineIndexExt,
 };
@@ -15,7 +16,8 @@ pub fn prepare_entry_rename<P: HasPosition>(
 ) -> Option<Range> {
     let (_, range) = context
         .find_citation_key_word()
-        .or_else(|| context.find_entry_key())?;
+        .or_else(|| context.find_entry_key())
+        .or_else(|| context.find_markdown_citation_key())?;
 
     Some(
         context
@@ -34,47 +36,96 @@ pub fn rename_entry(
     prepare_entry_rename(context, cancellation_token)?;
     let (key_text, _) = context
         .find_citation_key_word()
-        .or_else(|| context.find_entry_key())?;
+        .or_else(|| context.find_entry_key())
+        .or_else(|| context.find_markdown_citation_key())?;
+
+    let new_name = &context.request.params.new_name;
+    let is_conflict = context
+        .request
+        .subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_bibtex())
+        .flat_map(|data| data.root.descendants().filter_map(bibtex::Entry::cast))
+        .filter_map(|entry| entry.key())
+        .any(|key| key.to_string() == *new_name);
+
+    let description = if is_conflict {
+        Some(format!("The entry \"{}\" already exists", new_name))
+    } else {
+        None
+    };
+
+    let mut edit = AnnotatedWorkspaceEdit::default();
+    edit.annotate(
+        "entry",
+        format!("Rename the entry \"{}\"", key_text),
+        description.clone(),
+        is_conflict,
+    );
+    edit.annotate(
+        "citation",
+        format!("Update the citations of \"{}\"", key_text),
+        description,
+        is_conflict,
+    );
 
-    let mut changes = HashMap::new();
     for document in &context.request.subset.documents {
         cancellation_token.result().ok()?;
-        match &document.data {
-            DocumentData::Latex(data) => {
-                let edits: Vec<_> = data
-                    .root
+        let uri: Url = document.uri.as_ref().clone().into();
+        let (id, ranges): (_, Vec<_>) = match &document.data {
+            DocumentData::Latex(data) => (
+                "citation",
+                data.root
                     .descendants()
                     .filter_map(latex::Citation::cast)
                     .filter_map(|citation| citation.key_list())
                     .flat_map(|keys| keys.keys())
                     .filter(|key| key.to_string() == key_text)
-                    .map(|key| document.line_index.line_col_lsp_range(key.small_range()))
-                    .map(|range| TextEdit::new(range, context.request.params.new_name.clone()))
-                    .collect();
-                changes.insert(document.uri.as_ref().clone().into(), edits);
-            }
-            DocumentData::Bibtex(data) => {
-                let edits: Vec<_> = data
-                    .root
+                    .map(|key| key.small_range())
+                    .collect(),
+            ),
+            DocumentData::Bibtex(data) => (
+                "entry",
+                data.root
                     .descendants()
                     .filter_map(bibtex::Entry::cast)
                     .filter_map(|entry| entry.key())
                     .filter(|key| key.to_string() == key_text)
-                    .map(|key| document.line_index.line_col_lsp_range(key.small_range()))
-                    .map(|range| TextEdit::new(range, context.request.params.new_name.clone()))
-                    .collect();
-                changes.insert(document.uri.as_ref().clone().into(), edits);
-            }
-            DocumentData::BuildLog(_) => {}
+                    .map(|key| key.small_range())
+                    .collect(),
+            ),
+            DocumentData::Markdown(data) => (
+                "citation",
+                data.parse
+                    .citations
+                    .iter()
+                    .filter(|citation| citation.text == key_text)
+                    .map(|citation| citation.range)
+                    .collect(),
+            ),
+            DocumentData::BuildLog(_) => continue,
+        };
+
+        for range in ranges {
+            edit.push(
+                uri.clone(),
+                id,
+                TextEdit::new(
+                    document.line_index.line_col_lsp_range(range),
+                    new_name.clone(),
+                ),
+            );
         }
     }
 
-    Some(WorkspaceEdit::new(changes))
+    let client_capabilities = context.request.context.client_capabilities.lock().unwrap();
+    Some(edit.finish(&client_capabilities))
 }
 
 #[cfg(test)]
 mod tests {
-    use lsp_types::TextEdit;
+    use std::collections::HashMap;
 
     use crate::{features::testing::FeatureTester, RangeExt};
 
@@ -1,11 +1,11 @@
-use std::collections::HashMap;
-
 use cancellation::CancellationToken;
 use lsp_types::{Range, RenameParams, TextEdit, WorkspaceEdit};
 
 use crate::{
-    features::cursor::{CursorContext, HasPosition},
-    syntax::{latex, CstNode},
+    features::{
+        cursor::{CursorContext, HasPosition},
+        workspace_edit::AnnotatedWorkspaceEdit,
+    },
     LineIndexExt,
 };
 
@@ -31,75 +31,124 @@ pub fn rename_label(
     prepare_label_rename(context, cancellation_token)?;
     let (name_text, _) = context.find_label_name_key()?;
 
-    let mut changes = HashMap::new();
+    let new_name = &context.request.params.new_name;
+    let is_conflict = *new_name != name_text
+        && context
+            .request
+            .subset
+            .documents
+            .iter()
+            .filter_map(|document| document.data.as_latex())
+            .flat_map(|data| data.extras.label_names.iter())
+            .any(|name| name.is_definition && name.text == *new_name);
+
+    let description = if is_conflict {
+        Some(format!("The label \"{}\" is already defined", new_name))
+    } else {
+        None
+    };
+
+    let mut edit = AnnotatedWorkspaceEdit::default();
+    edit.annotate(
+        "definition",
+        format!("Rename the definition of \"{}\"", name_text),
+        description.clone(),
+        is_conflict,
+    );
+    edit.annotate(
+        "reference",
+        format!("Update the references to \"{}\"", name_text),
+        description,
+        is_conflict,
+    );
+
     for document in &context.request.subset.documents {
         cancellation_token.result().ok()?;
         if let Some(data) = document.data.as_latex() {
-            let mut edits = Vec::new();
-            for node in data.root.descendants() {
-                if let Some(range) = latex::LabelDefinition::cast(node)
-                    .and_then(|label| label.name())
-                    .and_then(|name| name.key())
-                    .filter(|name| name.to_string() == name_text)
-                    .map(|name| document.line_index.line_col_lsp_range(name.small_range()))
-                {
-                    edits.push(TextEdit::new(
-                        range,
-                        context.request.params.new_name.clone(),
-                    ));
-                }
-
-                latex::LabelReference::cast(node)
-                    .and_then(|label| label.name_list())
-                    .into_iter()
-                    .flat_map(|label| label.keys())
-                    .filter(|name| name.to_string() == name_text)
-                    .map(|name| document.line_index.line_col_lsp_range(name.small_range()))
-                    .for_each(|range| {
-                        edits.push(TextEdit::new(
-                            range,
-                            context.request.params.new_name.clone(),
-                        ));
-                    });
-
-                if let Some(label) = latex::LabelReferenceRange::cast(node) {
-                    if let Some(name1) = label
-                        .from()
-                        .and_then(|name| name.key())
-                        .filter(|name| name.to_string() == name_text)
-                    {
-                        edits.push(TextEdit::new(
-                            document.line_index.line_col_lsp_range(name1.small_range()),
-                            context.request.params.new_name.clone(),
-                        ));
-                    }
-
-                    if let Some(name2) = label
-                        .from()
-                        .and_then(|name| name.key())
-                        .filter(|name| name.to_string() == name_text)
-                    {
-                        edits.push(TextEdit::new(
-                            document.line_index.line_col_lsp_range(name2.small_range()),
-                            context.request.params.new_name.clone(),
-                        ));
-                    }
-                }
+            for name in data
+                .extras
+                .label_names
+                .iter()
+                .filter(|name| name.text == name_text)
+            {
+                edit.push(
+                    document.uri.as_ref().clone().into(),
+                    if name.is_definition {
+                        "definition"
+                    } else {
+                        "reference"
+                    },
+                    TextEdit::new(
+                        document.line_index.line_col_lsp_range(name.range),
+                        new_name.clone(),
+                    ),
+                );
             }
-
-            changes.insert(document.uri.as_ref().clone().into(), edits);
         }
     }
 
-    Some(WorkspaceEdit::new(changes))
+    let client_capabilities = context.request.context.client_capabilities.lock().unwrap();
+    Some(edit.finish(&client_capabilities))
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
+    use lsp_types::{
+        ChangeAnnotationWorkspaceEditClientCapabilities, ClientCapabilities,
+        WorkspaceClientCapabilities, WorkspaceEditClientCapabilities,
+    };
+
     use crate::{features::testing::FeatureTester, RangeExt};
 
     use super::*;
 
+    fn needs_confirmation(text: &str, new_name: &str) -> Option<bool> {
+        let client_capabilities = ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                workspace_edit: Some(WorkspaceEditClientCapabilities {
+                    document_changes: Some(true),
+                    change_annotation_support: Some(
+                        ChangeAnnotationWorkspaceEditClientCapabilities::default(),
+                    ),
+                    ..WorkspaceEditClientCapabilities::default()
+                }),
+                ..WorkspaceClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .line(0)
+            .character(7)
+            .new_name(new_name)
+            .client_capabilities(client_capabilities)
+            .build()
+            .rename();
+
+        let context = CursorContext::new(request);
+        let actual_edit = rename_label(&context, CancellationToken::none()).unwrap();
+        actual_edit.change_annotations.unwrap()["definition"].needs_confirmation
+    }
+
+    #[test]
+    fn test_conflict() {
+        let text = r#"\label{foo}\label{bar}\ref{foo}"#;
+        assert_eq!(needs_confirmation(text, "bar"), Some(true));
+        assert_eq!(needs_confirmation(text, "baz"), Some(false));
+    }
+
+    #[test]
+    fn test_same_name() {
+        assert_eq!(
+            needs_confirmation(r#"\label{foo}\ref{foo}"#, "foo"),
+            Some(false)
+        );
+    }
+
     #[test]
     fn test_label() {
         let tester = FeatureTester::builder()
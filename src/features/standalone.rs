@@ -0,0 +1,289 @@
+use std::{
+    fs,
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use cancellation::CancellationToken;
+use lsp_types::{TextDocumentPositionParams, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    encode_text,
+    syntax::{latex, CstNode},
+    BuildOptions, LineIndexExt, WorkspaceSubset,
+};
+
+use super::FeatureRequest;
+
+pub const PREVIEW_ENVIRONMENT_COMMAND: &str = "texlab.previewEnvironment";
+
+/// Packages that change the page layout and do not work together with the `standalone` class.
+const IGNORED_PACKAGES: &[&str] = &["geometry", "fancyhdr", "standalone"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewEnvironmentParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+
+    /// The name of the environment to preview (`tikzpicture` by default).
+    /// The innermost environment is used if no enclosing environment has this name.
+    #[serde(default)]
+    pub environment: Option<String>,
+
+    /// Convert the PDF file to an SVG file with `dvisvgm`.
+    #[serde(default)]
+    pub svg: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewEnvironmentResult {
+    pub pdf_file: Option<Url>,
+    pub svg_file: Option<Url>,
+    pub log_file: Option<Url>,
+}
+
+/// Compiles the environment under the cursor as a `standalone` document in a temporary directory.
+/// The directory is kept until the next preview so that the client can display the produced files.
+pub fn preview_environment(
+    request: FeatureRequest<PreviewEnvironmentParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<PreviewEnvironmentResult> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let offset = main_document
+        .line_index
+        .offset_lsp(request.params.text_document_position.position);
+
+    let name = request
+        .params
+        .environment
+        .as_deref()
+        .unwrap_or("tikzpicture");
+
+    let environments: Vec<_> = data
+        .root
+        .token_at_offset(offset)
+        .right_biased()?
+        .ancestors()
+        .filter_map(latex::Environment::cast)
+        .collect();
+
+    let environment = environments
+        .iter()
+        .find(|environment| environment_name(environment).as_deref() == Some(name))
+        .or_else(|| environments.first())?;
+
+    let source = create_standalone_document(
+        &request.subset,
+        &main_document.text[environment.small_range()],
+    );
+
+    cancellation_token.result().ok()?;
+    let temp_dir = match tempfile::Builder::new().prefix("texlab-preview").tempdir() {
+        Ok(temp_dir) => temp_dir,
+        Err(why) => {
+            log::error!("Failed to create preview directory: {}", why);
+            return None;
+        }
+    };
+
+    let directory = temp_dir.path();
+    fs::write(
+        directory.join("preview.tex"),
+        encode_text(&source, main_document.encoding),
+    )
+    .ok()?;
+
+    let engine = find_engine(&request.context.options.read().unwrap().build);
+    let pdf_file = compile(
+        directory,
+        engine,
+        &["-interaction=nonstopmode", "preview.tex"],
+    )
+    .then(|| directory.join("preview.pdf"))
+    .filter(|path| path.exists());
+
+    let svg_file = if request.params.svg
+        && pdf_file.is_some()
+        && compile(
+            directory,
+            "dvisvgm",
+            &["--pdf", "--no-fonts", "--output=preview.svg", "preview.pdf"],
+        ) {
+        Some(directory.join("preview.svg")).filter(|path| path.exists())
+    } else {
+        None
+    };
+
+    let log_file = Some(directory.join("preview.log")).filter(|path| path.exists());
+    let result = PreviewEnvironmentResult {
+        pdf_file: pdf_file.and_then(|path| Url::from_file_path(path).ok()),
+        svg_file: svg_file.and_then(|path| Url::from_file_path(path).ok()),
+        log_file: log_file.and_then(|path| Url::from_file_path(path).ok()),
+    };
+
+    // Replacing the directory of the previous preview deletes it.
+    *request.context.preview_directory.lock().unwrap() = Some(temp_dir);
+    Some(result)
+}
+
+/// Returns the TeX engine that is used by the build command of the project.
+fn find_engine(options: &BuildOptions) -> &'static str {
+    let executable = options.executable();
+    let executable = Path::new(&executable)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default();
+
+    if let Some(engine) = ["pdflatex", "lualatex", "xelatex"]
+        .iter()
+        .find(|engine| **engine == executable)
+    {
+        return *engine;
+    }
+
+    let args = options.args();
+    let has_arg = |names: &[&str]| args.iter().any(|arg| names.contains(&arg.as_str()));
+    if has_arg(&["-lualatex", "-pdflua"]) {
+        "lualatex"
+    } else if has_arg(&["-xelatex", "-pdfxe"]) {
+        "xelatex"
+    } else {
+        "pdflatex"
+    }
+}
+
+fn compile(directory: &Path, executable: &str, args: &[&str]) -> bool {
+    Command::new(executable)
+        .args(args)
+        .current_dir(directory)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_or(false, |status| status.success())
+}
+
+/// Wraps the source of an environment into a `standalone` document that loads
+/// the packages and TikZ libraries of the project.
+fn create_standalone_document(subset: &WorkspaceSubset, environment: &str) -> String {
+    let mut packages = Vec::new();
+    let mut libraries = Vec::new();
+    for document in &subset.documents {
+        if let Some(data) = document.data.as_latex() {
+            for node in data.root.descendants() {
+                if let Some(include) = latex::Include::cast(node)
+                    .filter(|include| include.syntax().kind() == latex::PACKAGE_INCLUDE)
+                {
+                    let is_ignored = include.path_list().map_or(true, |paths| {
+                        paths
+                            .keys()
+                            .any(|path| IGNORED_PACKAGES.contains(&path.to_string().as_str()))
+                    });
+
+                    if !is_ignored {
+                        packages.push(&document.text[include.small_range()]);
+                    }
+                } else if let Some(import) = latex::TikzLibraryImport::cast(node) {
+                    libraries.push(&document.text[import.small_range()]);
+                }
+            }
+        }
+    }
+
+    let mut source = String::from("\\documentclass[border=2pt]{standalone}\n");
+    let mut preamble = Vec::new();
+    for line in packages.into_iter().chain(libraries) {
+        if !preamble.contains(&line) {
+            preamble.push(line);
+            source.push_str(line);
+            source.push('\n');
+        }
+    }
+
+    source.push_str("\\begin{document}\n");
+    source.push_str(environment);
+    source.push_str("\n\\end{document}\n");
+    source
+}
+
+fn environment_name(environment: &latex::Environment) -> Option<String> {
+    Some(environment.begin()?.name()?.key()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_standalone_document() {
+        let request = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    indoc! {r#"
+                        \documentclass{article}
+                        \usepackage[margin=1cm]{geometry}
+                        \usepackage{tikz}
+                        \usetikzlibrary{arrows.meta}
+                        \begin{document}
+                        \input{figure}
+                        \end{document}"#},
+                ),
+                (
+                    "figure.tex",
+                    indoc! {r#"
+                        \usetikzlibrary{arrows.meta}
+                        \begin{tikzpicture}
+                        \draw[->] (0,0) -- (1,1);
+                        \end{tikzpicture}"#},
+                ),
+            ])
+            .main("main.tex")
+            .build()
+            .position();
+
+        let actual =
+            create_standalone_document(&request.subset, "\\begin{tikzpicture}\\end{tikzpicture}");
+        let expected = indoc! {r#"
+            \documentclass[border=2pt]{standalone}
+            \usepackage{tikz}
+            \usetikzlibrary{arrows.meta}
+            \begin{document}
+            \begin{tikzpicture}\end{tikzpicture}
+            \end{document}
+        "#};
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_engine() {
+        let options = |executable: &str, args: &[&str]| BuildOptions {
+            executable: Some(executable.to_string()),
+            args: Some(args.iter().map(ToString::to_string).collect()),
+            ..BuildOptions::default()
+        };
+
+        assert_eq!(find_engine(&BuildOptions::default()), "pdflatex");
+        assert_eq!(
+            find_engine(&options("latexmk", &["-lualatex", "%f"])),
+            "lualatex"
+        );
+        assert_eq!(
+            find_engine(&options("latexmk", &["-pdfxe", "%f"])),
+            "xelatex"
+        );
+        assert_eq!(
+            find_engine(&options("/usr/bin/xelatex", &["%f"])),
+            "xelatex"
+        );
+    }
+}
@@ -0,0 +1,523 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{Range, TextDocumentIdentifier, TextDocumentPositionParams};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{
+        latex::{self, HasBrack, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt,
+};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentLocation {
+    pub name: String,
+    pub full_range: Range,
+    pub name_range: Range,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentContent {
+    pub name: String,
+    pub options: Option<String>,
+    pub body: String,
+    pub full_range: Range,
+    pub name_range: Range,
+    pub options_range: Option<Range>,
+    pub body_range: Range,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionsParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionLocation {
+    pub title: String,
+    pub level: i32,
+    pub full_range: Range,
+    pub command_range: Range,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NextSectionParams {
+    #[serde(flatten)]
+    pub text_document_position: TextDocumentPositionParams,
+
+    #[serde(default)]
+    pub backward: bool,
+}
+
+pub fn find_environments(
+    request: FeatureRequest<TextDocumentPositionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<EnvironmentLocation>> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let offset = main_document.line_index.offset_lsp(request.params.position);
+
+    let mut environments = Vec::new();
+    for node in data
+        .root
+        .token_at_offset(offset)
+        .right_biased()?
+        .ancestors()
+    {
+        cancellation_token.result().ok()?;
+
+        if let Some(environment) = latex::Environment::cast(node) {
+            if let Some(name) = environment.begin().and_then(|begin| begin.name()) {
+                let name_text = name.key().map(|key| key.to_string()).unwrap_or_default();
+
+                environments.push(EnvironmentLocation {
+                    name: name_text,
+                    full_range: main_document
+                        .line_index
+                        .line_col_lsp_range(environment.small_range()),
+                    name_range: main_document
+                        .line_index
+                        .line_col_lsp_range(name.small_range()),
+                });
+            }
+        }
+    }
+
+    Some(environments)
+}
+
+/// Returns the name, the options and the body of the innermost environment at the given position.
+pub fn extract_environment(
+    request: FeatureRequest<TextDocumentPositionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<EnvironmentContent> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let offset = main_document.line_index.offset_lsp(request.params.position);
+    cancellation_token.result().ok()?;
+
+    let environment = data
+        .root
+        .token_at_offset(offset)
+        .right_biased()?
+        .ancestors()
+        .find_map(latex::Environment::cast)?;
+
+    let begin = environment.begin()?;
+    let name = begin.name()?;
+    let options_range = begin.options().and_then(|options| {
+        Some(TextRange::new(
+            options.left_brack()?.text_range().end(),
+            options.right_brack()?.text_range().start(),
+        ))
+    });
+
+    let body_end = environment
+        .end()
+        .map_or(environment.small_range().end(), |end| {
+            end.small_range().start()
+        });
+    let body_range = TextRange::new(begin.small_range().end(), body_end);
+
+    Some(EnvironmentContent {
+        name: name.key().map(|key| key.to_string()).unwrap_or_default(),
+        options: options_range.map(|range| main_document.text[range].to_string()),
+        body: main_document.text[body_range].to_string(),
+        full_range: main_document
+            .line_index
+            .line_col_lsp_range(environment.small_range()),
+        name_range: main_document
+            .line_index
+            .line_col_lsp_range(name.small_range()),
+        options_range: options_range
+            .map(|range| main_document.line_index.line_col_lsp_range(range)),
+        body_range: main_document.line_index.line_col_lsp_range(body_range),
+    })
+}
+
+pub fn find_sections(
+    request: FeatureRequest<SectionsParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<SectionLocation>> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+
+    let mut sections = Vec::new();
+    for section in data.root.descendants().filter_map(latex::Section::cast) {
+        cancellation_token.result().ok()?;
+        sections.push(make_section_location(main_document, section)?);
+    }
+
+    Some(sections)
+}
+
+pub fn find_next_section(
+    request: FeatureRequest<NextSectionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<SectionLocation> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let offset = main_document
+        .line_index
+        .offset_lsp(request.params.text_document_position.position);
+
+    let mut sections = data.root.descendants().filter_map(latex::Section::cast);
+    let section = if request.params.backward {
+        sections
+            .take_while(|section| section.small_range().start() < offset)
+            .last()?
+    } else {
+        sections.find(|section| section.small_range().start() > offset)?
+    };
+
+    cancellation_token.result().ok()?;
+    make_section_location(main_document, section)
+}
+
+fn make_section_location(document: &Document, section: latex::Section) -> Option<SectionLocation> {
+    let command = section.command()?;
+    Some(SectionLocation {
+        title: section
+            .name()
+            .and_then(|name| name.content_text())
+            .unwrap_or_default(),
+        level: section_level(section.syntax().kind()),
+        full_range: document
+            .line_index
+            .line_col_lsp_range(section.small_range()),
+        command_range: document.line_index.line_col_lsp_range(command.text_range()),
+    })
+}
+
+pub fn find_matching_pair(
+    request: FeatureRequest<TextDocumentPositionParams>,
+    _cancellation_token: &CancellationToken,
+) -> Option<Range> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let offset = main_document.line_index.offset_lsp(request.params.position);
+
+    let token = data.root.token_at_offset(offset).right_biased()?;
+    let range = find_matching_delimiter(&token).or_else(|| find_matching_environment(&token))?;
+    Some(main_document.line_index.line_col_lsp_range(range))
+}
+
+fn find_matching_delimiter(token: &latex::SyntaxToken) -> Option<TextRange> {
+    let mut delimiters = token
+        .parent()
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|delimiter| {
+            matches!(
+                delimiter.kind(),
+                latex::L_CURLY
+                    | latex::R_CURLY
+                    | latex::BEGIN_EQUATION_NAME
+                    | latex::END_EQUATION_NAME
+                    | latex::DOLLAR
+            )
+        });
+
+    let first = delimiters.next()?;
+    let last = delimiters.last()?;
+    if first.text_range() == token.text_range() {
+        Some(last.text_range())
+    } else if last.text_range() == token.text_range() {
+        Some(first.text_range())
+    } else {
+        None
+    }
+}
+
+fn find_matching_environment(token: &latex::SyntaxToken) -> Option<TextRange> {
+    let node = token
+        .ancestors()
+        .find(|node| matches!(node.kind(), latex::BEGIN | latex::END))?;
+
+    let environment = latex::Environment::cast(node.parent()?)?;
+    if node.kind() == latex::BEGIN {
+        environment.end().map(|end| end.small_range())
+    } else {
+        environment.begin().map(|begin| begin.small_range())
+    }
+}
+
+pub fn section_level(kind: latex::SyntaxKind) -> i32 {
+    match kind {
+        latex::PART => -1,
+        latex::CHAPTER => 0,
+        latex::SECTION => 1,
+        latex::SUBSECTION => 2,
+        latex::SUBSUBSECTION => 3,
+        latex::PARAGRAPH => 4,
+        latex::SUBPARAGRAPH => 5,
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_environments_empty_document() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "")])
+            .main("main.tex")
+            .build()
+            .position();
+
+        let actual = find_environments(request, CancellationToken::none());
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_environments_nested() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{document}
+                \begin{itemize}
+                \item foo
+                \end{itemize}
+                \end{document}"#},
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(7)
+            .build()
+            .position();
+
+        let actual = find_environments(request, CancellationToken::none()).unwrap();
+        let expected = vec![
+            EnvironmentLocation {
+                name: "itemize".into(),
+                full_range: Range::new_simple(1, 0, 3, 13),
+                name_range: Range::new_simple(1, 6, 1, 15),
+            },
+            EnvironmentLocation {
+                name: "document".into(),
+                full_range: Range::new_simple(0, 0, 4, 14),
+                name_range: Range::new_simple(0, 6, 0, 16),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_extract_environment() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{document}
+                \begin{tikzpicture}[scale=2]
+                \draw (0,0) -- (1,1);
+                \end{tikzpicture}
+                \end{document}"#},
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(3)
+            .build()
+            .position();
+
+        let actual = extract_environment(request, CancellationToken::none()).unwrap();
+        let expected = EnvironmentContent {
+            name: "tikzpicture".into(),
+            options: Some("scale=2".into()),
+            body: "\n\\draw (0,0) -- (1,1);\n".into(),
+            full_range: Range::new_simple(1, 0, 3, 17),
+            name_range: Range::new_simple(1, 6, 1, 19),
+            options_range: Some(Range::new_simple(1, 20, 1, 27)),
+            body_range: Range::new_simple(1, 28, 3, 0),
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_environments_bibtex() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.bib", "@article{foo,}")])
+            .main("main.bib")
+            .build()
+            .position();
+
+        let actual = find_environments(request, CancellationToken::none());
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_sections() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \chapter{Foo}
+                \section{Bar}
+                bar
+                \subsection*{Baz}"#},
+            )])
+            .main("main.tex")
+            .build()
+            .sections();
+
+        let actual = find_sections(request, CancellationToken::none()).unwrap();
+        let expected = vec![
+            SectionLocation {
+                title: "Foo".into(),
+                level: 0,
+                full_range: Range::new_simple(0, 0, 3, 17),
+                command_range: Range::new_simple(0, 0, 0, 8),
+            },
+            SectionLocation {
+                title: "Bar".into(),
+                level: 1,
+                full_range: Range::new_simple(1, 0, 3, 17),
+                command_range: Range::new_simple(1, 0, 1, 8),
+            },
+            SectionLocation {
+                title: "Baz".into(),
+                level: 2,
+                full_range: Range::new_simple(3, 0, 3, 17),
+                command_range: Range::new_simple(3, 0, 3, 12),
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_next_section_forward() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \section{Foo}
+                foo
+                \section{Bar}"#},
+            )])
+            .main("main.tex")
+            .line(1)
+            .build()
+            .next_section(false);
+
+        let actual = find_next_section(request, CancellationToken::none()).unwrap();
+        assert_eq!(actual.title, "Bar");
+        assert_eq!(actual.command_range, Range::new_simple(2, 0, 2, 8));
+    }
+
+    #[test]
+    fn test_next_section_backward() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \section{Foo}
+                foo
+                \section{Bar}"#},
+            )])
+            .main("main.tex")
+            .line(1)
+            .build()
+            .next_section(true);
+
+        let actual = find_next_section(request, CancellationToken::none()).unwrap();
+        assert_eq!(actual.title, "Foo");
+    }
+
+    #[test]
+    fn test_next_section_last() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\section{Foo}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(3)
+            .build()
+            .next_section(false);
+
+        let actual = find_next_section(request, CancellationToken::none());
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_matching_pair_environment() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{foo}
+                bar
+                \end{foo}"#},
+            )])
+            .main("main.tex")
+            .line(0)
+            .character(2)
+            .build()
+            .position();
+
+        let actual = find_matching_pair(request, CancellationToken::none());
+        assert_eq!(actual, Some(Range::new_simple(2, 0, 2, 9)));
+    }
+
+    #[test]
+    fn test_matching_pair_environment_name() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \begin{foo}
+                bar
+                \end{foo}"#},
+            )])
+            .main("main.tex")
+            .line(2)
+            .character(6)
+            .build()
+            .position();
+
+        let actual = find_matching_pair(request, CancellationToken::none());
+        assert_eq!(actual, Some(Range::new_simple(0, 0, 0, 11)));
+    }
+
+    #[test]
+    fn test_matching_pair_curly_group() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\foo{bar}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(4)
+            .build()
+            .position();
+
+        let actual = find_matching_pair(request, CancellationToken::none());
+        assert_eq!(actual, Some(Range::new_simple(0, 8, 0, 9)));
+    }
+
+    #[test]
+    fn test_matching_pair_equation() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\[ x \]"#)])
+            .main("main.tex")
+            .line(0)
+            .character(5)
+            .build()
+            .position();
+
+        let actual = find_matching_pair(request, CancellationToken::none());
+        assert_eq!(actual, Some(Range::new_simple(0, 0, 0, 2)));
+    }
+}
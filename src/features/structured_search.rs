@@ -0,0 +1,254 @@
+use cancellation::CancellationToken;
+use cstree::TextRange;
+use lsp_types::{Range, Url};
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Workspace, LANGUAGE_DATA, VERBATIM_ENVIRONMENTS,
+};
+
+/// The maximum number of matches that are sent to the client.
+pub const SEARCH_LIMIT: usize = 1000;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SearchScope {
+    /// Everything except for comments.
+    Text,
+    Comment,
+    Math,
+    Caption,
+    /// The titles of sectioning commands.
+    Heading,
+    Verbatim,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredSearchParams {
+    pub query: String,
+
+    /// Interpret the query as a regular expression.
+    #[serde(default)]
+    pub regex: bool,
+
+    #[serde(default)]
+    pub case_sensitive: bool,
+
+    /// Only report the matches inside of one of these scopes.
+    /// Matches inside of verbatim environments are only reported if `verbatim` is requested.
+    #[serde(default)]
+    pub scopes: Vec<SearchScope>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMatch {
+    pub uri: Url,
+    pub range: Range,
+    pub text: String,
+
+    /// The scopes that contain the match.
+    pub scopes: Vec<SearchScope>,
+
+    /// The title of the innermost section that contains the match.
+    pub section: Option<String>,
+
+    /// The name of the innermost environment that contains the match.
+    pub environment: Option<String>,
+}
+
+/// Searches the LaTeX documents of the workspace for the query
+/// and keeps the matches that are inside of the requested scopes.
+pub fn structured_search(
+    workspace: &dyn Workspace,
+    params: &StructuredSearchParams,
+    token: &CancellationToken,
+) -> Vec<SearchMatch> {
+    let pattern = if params.regex {
+        params.query.clone()
+    } else {
+        regex::escape(&params.query)
+    };
+
+    let regex = match RegexBuilder::new(&pattern)
+        .case_insensitive(!params.case_sensitive)
+        .build()
+    {
+        Ok(regex) => regex,
+        Err(why) => {
+            log::warn!("Invalid search pattern {}: {}", pattern, why);
+            return Vec::new();
+        }
+    };
+
+    let mut documents = workspace.documents();
+    documents.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+
+    let mut matches = Vec::new();
+    for document in &documents {
+        if token.is_canceled() || matches.len() >= SEARCH_LIMIT {
+            break;
+        }
+
+        let data = match document.data.as_latex() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for found in regex.find_iter(&document.text) {
+            if found.as_str().is_empty() {
+                continue;
+            }
+
+            let range = TextRange::new((found.start() as u32).into(), (found.end() as u32).into());
+
+            if let Some(search_match) = classify_match(document, &data.root, range, params) {
+                matches.push(search_match);
+            }
+        }
+    }
+
+    matches.truncate(SEARCH_LIMIT);
+    matches
+}
+
+fn classify_match(
+    document: &Document,
+    root: &latex::SyntaxNode,
+    range: TextRange,
+    params: &StructuredSearchParams,
+) -> Option<SearchMatch> {
+    let token = root.token_at_offset(range.start()).right_biased()?;
+    let mut scopes = Vec::new();
+    if token.kind() == latex::COMMENT {
+        scopes.push(SearchScope::Comment);
+    } else {
+        scopes.push(SearchScope::Text);
+    }
+
+    let mut section = None;
+    let mut environment = None;
+    for node in token.parent().ancestors() {
+        match node.kind() {
+            latex::FORMULA | latex::EQUATION => scopes.push(SearchScope::Math),
+            latex::CAPTION => scopes.push(SearchScope::Caption),
+            _ => {}
+        }
+
+        if let Some(name) = latex::Environment::cast(node)
+            .and_then(|environment| environment.begin()?.name()?.key())
+            .map(|name| name.to_string())
+        {
+            if LANGUAGE_DATA
+                .math_environments
+                .iter()
+                .any(|math_environment| *math_environment == name)
+            {
+                scopes.push(SearchScope::Math);
+            } else if VERBATIM_ENVIRONMENTS.contains(&name.as_str()) {
+                scopes.push(SearchScope::Verbatim);
+            }
+
+            environment = environment.or(Some(name));
+        } else if let Some(node_section) = latex::Section::cast(node) {
+            let name = node_section.name();
+            if name.map_or(false, |name| name.small_range().contains_range(range)) {
+                scopes.push(SearchScope::Heading);
+            }
+
+            section = section.or_else(|| name?.content_text());
+        }
+    }
+
+    scopes.sort();
+    scopes.dedup();
+
+    let is_verbatim = scopes.contains(&SearchScope::Verbatim);
+    let is_requested = if params.scopes.is_empty() {
+        !is_verbatim
+    } else {
+        (!is_verbatim || params.scopes.contains(&SearchScope::Verbatim))
+            && scopes.iter().any(|scope| params.scopes.contains(scope))
+    };
+
+    if !is_requested {
+        return None;
+    }
+
+    Some(SearchMatch {
+        uri: document.uri.as_ref().clone().into(),
+        range: document.line_index.line_col_lsp_range(range),
+        text: document.text[range].to_string(),
+        scopes,
+        section,
+        environment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    fn search(text: &str, scopes: Vec<SearchScope>) -> Vec<SearchMatch> {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", text)])
+            .main("main.tex")
+            .build()
+            .position();
+
+        let params = StructuredSearchParams {
+            query: "foo".into(),
+            regex: false,
+            case_sensitive: false,
+            scopes,
+        };
+
+        structured_search(
+            request.workspace.as_ref(),
+            &params,
+            CancellationToken::none(),
+        )
+    }
+
+    const TEXT: &str = indoc! {r#"
+        \section{Foo}
+        foo $foo$ % foo
+        \begin{figure}
+        \caption{Foo}
+        \end{figure}
+        \begin{verbatim}foo\end{verbatim}"#};
+
+    #[test]
+    fn test_math() {
+        let actual = search(TEXT, vec![SearchScope::Math]);
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].section.as_deref(), Some("Foo"));
+        assert_eq!(actual[0].scopes, vec![SearchScope::Text, SearchScope::Math]);
+    }
+
+    #[test]
+    fn test_caption_and_heading() {
+        let actual: Vec<_> = search(TEXT, vec![SearchScope::Caption, SearchScope::Heading])
+            .into_iter()
+            .map(|search_match| (search_match.range.start.line, search_match.environment))
+            .collect();
+
+        assert_eq!(actual, vec![(0, None), (3, Some("figure".into()))]);
+    }
+
+    #[test]
+    fn test_default_scopes() {
+        let actual = search(TEXT, Vec::new());
+        assert_eq!(actual.len(), 5);
+    }
+}
@@ -7,21 +7,33 @@ use std::{cmp::Reverse, sync::Arc};
 
 use cancellation::CancellationToken;
 use lsp_types::{
-    DocumentSymbolParams, DocumentSymbolResponse, SymbolInformation, WorkspaceSymbolParams,
+    DocumentSymbol, DocumentSymbolParams, DocumentSymbolResponse, Location, SymbolInformation,
+    SymbolKind, WorkspaceSymbolParams,
 };
 
 use crate::{ClientCapabilitiesExt, Uri, Workspace};
 
 use self::{
     bibtex::find_bibtex_symbols, latex::find_latex_symbols, project_order::ProjectOrdering,
+    types::InternalSymbol,
 };
 
 use super::FeatureRequest;
 
+pub const SYMBOL_LIMIT: usize = 1000;
+
 pub fn find_document_symbols(
     req: FeatureRequest<DocumentSymbolParams>,
     token: &CancellationToken,
 ) -> DocumentSymbolResponse {
+    let limit = req
+        .context
+        .options
+        .read()
+        .unwrap()
+        .symbol_limit
+        .unwrap_or(SYMBOL_LIMIT);
+
     let mut buf = Vec::new();
     find_latex_symbols(&req.subset, &mut buf, token);
     find_bibtex_symbols(&req.subset, &mut buf, token);
@@ -32,11 +44,28 @@ pub fn find_document_symbols(
         .unwrap()
         .has_hierarchical_document_symbol_support()
     {
-        DocumentSymbolResponse::Nested(
-            buf.into_iter()
-                .map(|symbol| symbol.into_document_symbol())
-                .collect(),
-        )
+        let mut budget = limit;
+        let omitted = truncate_symbols(&mut buf, &mut budget);
+        let mut symbols: Vec<_> = buf
+            .into_iter()
+            .map(|symbol| symbol.into_document_symbol())
+            .collect();
+
+        if let Some(first) = omitted.first() {
+            #[allow(deprecated)]
+            symbols.push(DocumentSymbol {
+                name: truncation_marker(count_symbols(&omitted)),
+                detail: None,
+                kind: SymbolKind::Null,
+                deprecated: None,
+                range: first.full_range,
+                selection_range: first.selection_range,
+                children: None,
+                tags: None,
+            });
+        }
+
+        DocumentSymbolResponse::Nested(symbols)
     } else {
         let mut new_buf = Vec::new();
         for symbol in buf {
@@ -47,6 +76,7 @@ pub fn find_document_symbols(
             .map(|symbol| symbol.into_symbol_info(req.main_document().uri.as_ref().clone()))
             .collect();
         sort_symbols(req.workspace.as_ref(), &mut new_buf);
+        truncate_symbol_infos(&mut new_buf, limit);
         DocumentSymbolResponse::Flat(new_buf)
     }
 }
@@ -60,6 +90,7 @@ struct WorkspaceSymbol {
 pub fn find_workspace_symbols(
     workspace: &dyn Workspace,
     params: &WorkspaceSymbolParams,
+    limit: usize,
     token: &CancellationToken,
 ) -> Vec<SymbolInformation> {
     let mut symbols = Vec::new();
@@ -104,9 +135,58 @@ pub fn find_workspace_symbols(
         }
     }
     sort_symbols(workspace, &mut filtered);
+    truncate_symbol_infos(&mut filtered, limit);
     filtered
 }
 
+/// Keeps the first `budget` symbols of the tree (in pre-order) and returns the omitted subtrees.
+fn truncate_symbols(symbols: &mut Vec<InternalSymbol>, budget: &mut usize) -> Vec<InternalSymbol> {
+    let mut kept = Vec::new();
+    let mut omitted = Vec::new();
+    for mut symbol in symbols.drain(..) {
+        if *budget == 0 {
+            omitted.push(symbol);
+        } else {
+            *budget -= 1;
+            omitted.append(&mut truncate_symbols(&mut symbol.children, budget));
+            kept.push(symbol);
+        }
+    }
+
+    *symbols = kept;
+    omitted
+}
+
+fn count_symbols(symbols: &[InternalSymbol]) -> usize {
+    symbols
+        .iter()
+        .map(|symbol| 1 + count_symbols(&symbol.children))
+        .sum()
+}
+
+/// Truncates the symbols to the given limit and replaces the omitted symbols with a marker
+/// that points to the first omitted symbol.
+fn truncate_symbol_infos(symbols: &mut Vec<SymbolInformation>, limit: usize) {
+    if symbols.len() <= limit {
+        return;
+    }
+
+    let omitted = symbols.split_off(limit);
+    #[allow(deprecated)]
+    symbols.push(SymbolInformation {
+        name: truncation_marker(omitted.len()),
+        kind: SymbolKind::Null,
+        deprecated: None,
+        location: Location::clone(&omitted[0].location),
+        container_name: None,
+        tags: None,
+    });
+}
+
+fn truncation_marker(count: usize) -> String {
+    format!("... {} more symbols (see texlab.symbolLimit)", count)
+}
+
 fn sort_symbols(workspace: &dyn Workspace, symbols: &mut Vec<SymbolInformation>) {
     let ordering = ProjectOrdering::from(workspace);
     symbols.sort_by(|left, right| {
@@ -123,3 +203,46 @@ fn sort_symbols(workspace: &dyn Workspace, symbols: &mut Vec<SymbolInformation>)
         left_key.cmp(&right_key)
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::RangeExt;
+
+    use super::{types::InternalSymbolKind, *};
+
+    fn section(name: &str, line: u32, children: Vec<InternalSymbol>) -> InternalSymbol {
+        InternalSymbol {
+            name: name.to_string(),
+            label: None,
+            kind: InternalSymbolKind::Section,
+            deprecated: false,
+            full_range: Range::new_simple(line, 0, line, 1),
+            selection_range: Range::new_simple(line, 0, line, 1),
+            children,
+        }
+    }
+
+    #[test]
+    fn test_truncate_nested() {
+        let mut symbols = vec![
+            section(
+                "a",
+                0,
+                vec![section("b", 1, vec![]), section("c", 2, vec![])],
+            ),
+            section("d", 3, vec![section("e", 4, vec![])]),
+        ];
+
+        let omitted = truncate_symbols(&mut symbols, &mut 2);
+        assert_eq!(
+            symbols,
+            vec![section("a", 0, vec![section("b", 1, vec![])])]
+        );
+
+        let names: Vec<_> = omitted.iter().map(|symbol| symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "d"]);
+        assert_eq!(count_symbols(&omitted), 3);
+    }
+}
@@ -1,12 +1,14 @@
 use std::str::FromStr;
 
 use cancellation::CancellationToken;
+use cstree::TextRange;
 use lsp_types::Range;
+use rustc_hash::FxHashMap;
 use smol_str::SmolStr;
 use titlecase::titlecase;
 
 use crate::{
-    find_caption_by_parent, find_label_number,
+    find_caption_by_parent, find_label_number, find_section_numbers, find_theorem_numbers,
     syntax::{
         latex::{self, HasBrack, HasCurly},
         CstNode,
@@ -31,6 +33,8 @@ pub fn find_latex_symbols(
         subset,
         data,
         token,
+        section_numbers: find_section_numbers(subset, &data.root),
+        theorem_numbers: find_theorem_numbers(subset, &data.root),
     };
 
     let root = &context.data.root;
@@ -43,6 +47,8 @@ struct Context<'a> {
     subset: &'a WorkspaceSubset,
     data: &'a LatexDocumentData,
     token: &'a CancellationToken,
+    section_numbers: FxHashMap<TextRange, String>,
+    theorem_numbers: FxHashMap<TextRange, String>,
 }
 
 fn visit(context: &mut Context, node: &latex::SyntaxNode) -> Vec<InternalSymbol> {
@@ -119,38 +125,35 @@ fn visit_section(context: &mut Context, node: &latex::SyntaxNode) -> Option<Inte
     let group = section.name()?;
     let group_text = group.content_text()?;
 
-    let symbol = match find_label_by_parent(context, section.syntax()) {
+    let computed_number = context.section_numbers.get(&section.small_range()).cloned();
+
+    let (label, selection_range, number) = match find_label_by_parent(context, section.syntax()) {
         Some(NumberedLabel {
             name: label,
             range: selection_range,
             number,
-        }) => {
-            let name = match number {
-                Some(number) => format!("{} {}", number, group_text),
-                None => group_text.to_string(),
-            };
+        }) => (
+            Some(label),
+            selection_range,
+            number.map(Into::into).or(computed_number),
+        ),
+        None => (None, full_range, computed_number),
+    };
 
-            InternalSymbol {
-                name,
-                label: Some(label),
-                kind: InternalSymbolKind::Section,
-                deprecated: false,
-                full_range,
-                selection_range,
-                children: Vec::new(),
-            }
-        }
-        None => InternalSymbol {
-            name: group_text.to_string(),
-            label: None,
-            kind: InternalSymbolKind::Section,
-            deprecated: false,
-            full_range,
-            selection_range: full_range,
-            children: Vec::new(),
-        },
+    let name = match number {
+        Some(number) => format!("{} {}", number, group_text),
+        None => group_text,
     };
-    Some(symbol)
+
+    Some(InternalSymbol {
+        name,
+        label,
+        kind: InternalSymbolKind::Section,
+        deprecated: false,
+        full_range,
+        selection_range,
+        children: Vec::new(),
+    })
 }
 
 fn visit_enum_item(context: &mut Context, node: &latex::SyntaxNode) -> Option<InternalSymbol> {
@@ -414,48 +417,38 @@ fn visit_theorem(
         .line_index
         .line_col_lsp_range(node.small_range());
 
-    let symbol = match find_label_by_parent(context, node.syntax()) {
+    let computed_number = context.theorem_numbers.get(&node.small_range()).cloned();
+    let (label, selection_range, number) = match find_label_by_parent(context, node.syntax()) {
         Some(NumberedLabel {
             name: label,
             range: selection_range,
             number,
-        }) => {
-            let name = match (number, theorem_description) {
-                (Some(number), Some(desc)) => {
-                    format!("{} {} ({})", definition.description, number, desc)
-                }
-                (Some(number), None) => format!("{} {}", definition.description, number),
-                (None, Some(desc)) => format!("{} ({})", definition.description, desc),
-                (None, None) => definition.description.clone(),
-            };
+        }) => (
+            Some(label),
+            selection_range,
+            number.map(Into::into).or(computed_number),
+        ),
+        None => (None, full_range, computed_number),
+    };
 
-            InternalSymbol {
-                name,
-                label: Some(label),
-                kind: InternalSymbolKind::Theorem,
-                deprecated: false,
-                full_range,
-                selection_range,
-                children: Vec::new(),
-            }
-        }
-        None => {
-            let name = match theorem_description {
-                Some(desc) => format!("{} ({})", definition.description, desc),
-                None => definition.description.to_string(),
-            };
-            InternalSymbol {
-                name,
-                label: None,
-                kind: InternalSymbolKind::Theorem,
-                deprecated: false,
-                full_range,
-                selection_range: full_range,
-                children: Vec::new(),
-            }
+    let name = match (number, theorem_description) {
+        (Some(number), Some(desc)) => {
+            format!("{} {} ({})", definition.description, number, desc)
         }
+        (Some(number), None) => format!("{} {}", definition.description, number),
+        (None, Some(desc)) => format!("{} ({})", definition.description, desc),
+        (None, None) => definition.description.clone(),
     };
-    Some(symbol)
+
+    Some(InternalSymbol {
+        name,
+        label,
+        kind: InternalSymbolKind::Theorem,
+        deprecated: false,
+        full_range,
+        selection_range,
+        children: Vec::new(),
+    })
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
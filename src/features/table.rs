@@ -0,0 +1,257 @@
+use std::{collections::HashMap, fs};
+
+use cancellation::CancellationToken;
+use lsp_types::{Range, TextDocumentIdentifier, TextEdit, Url, WorkspaceEdit};
+use serde::{Deserialize, Serialize};
+
+use crate::{indentation, LineIndexExt};
+
+use super::{paste::convert_text, FeatureRequest};
+
+pub const CONVERT_TABLE_COMMAND: &str = "texlab.convertTable";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConvertTableParams {
+    pub text_document: TextDocumentIdentifier,
+
+    /// The selected CSV or TSV block that is replaced by the table.
+    pub range: Range,
+
+    /// A data file that is read instead of the selected text.
+    #[serde(default)]
+    pub file: Option<Url>,
+
+    /// Use the rules of the `booktabs` package instead of `\hline`.
+    #[serde(default)]
+    pub booktabs: bool,
+
+    /// Use the `S` column type of the `siunitx` package for numeric columns.
+    #[serde(default)]
+    pub siunitx: bool,
+}
+
+pub fn convert_table(
+    request: FeatureRequest<ConvertTableParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<WorkspaceEdit> {
+    let main_document = request.main_document();
+    main_document.data.as_latex()?;
+    let params = &request.params;
+
+    let data = match &params.file {
+        Some(file) => fs::read_to_string(file.to_file_path().ok()?).ok()?,
+        None => {
+            let range = main_document.line_index.offset_lsp_range(params.range);
+            main_document.text[range].to_string()
+        }
+    };
+
+    cancellation_token.result().ok()?;
+    let rows = parse_rows(&data);
+    if rows.is_empty() {
+        return None;
+    }
+
+    let start = main_document.line_index.offset_lsp(params.range.start);
+    let indent = indentation::line_indent(&main_document.text, start.into());
+
+    let edit = TextEdit::new(
+        params.range,
+        create_table(
+            &rows,
+            params.booktabs,
+            params.siunitx,
+            indent,
+            &indentation::unit(main_document),
+        ),
+    );
+
+    let mut changes = HashMap::new();
+    changes.insert(main_document.uri.as_ref().clone().into(), vec![edit]);
+    Some(WorkspaceEdit::new(changes))
+}
+
+/// Splits the data into cells. Tabs take precedence over semicolons and commas
+/// and cells can be quoted to include the separator.
+fn parse_rows(data: &str) -> Vec<Vec<String>> {
+    let lines: Vec<_> = data
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+
+    let separator = [b'\t', b';', b',']
+        .iter()
+        .copied()
+        .find(|c| lines.iter().all(|line| line.as_bytes().contains(c)))
+        .unwrap_or(b',') as char;
+
+    lines
+        .into_iter()
+        .map(|line| parse_row(line, separator))
+        .collect()
+}
+
+fn parse_row(line: &str, separator: char) -> Vec<String> {
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+    let mut is_quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if is_quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if is_quoted || cell.trim().is_empty() => is_quoted = !is_quoted,
+            c if c == separator && !is_quoted => {
+                cells.push(cell.trim().to_string());
+                cell.clear();
+            }
+            c => cell.push(c),
+        }
+    }
+    cells.push(cell.trim().to_string());
+    cells
+}
+
+fn is_number(cell: &str) -> bool {
+    cell.contains(|c: char| c.is_ascii_digit()) && cell.parse::<f64>().is_ok()
+}
+
+fn create_table(
+    rows: &[Vec<String>],
+    booktabs: bool,
+    siunitx: bool,
+    indent: &str,
+    unit: &str,
+) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let is_numeric = |column: usize, rows: &[Vec<String>]| {
+        let mut cells = rows
+            .iter()
+            .filter_map(|row| row.get(column))
+            .filter(|cell| !cell.is_empty())
+            .peekable();
+        cells.peek().is_some() && cells.all(|cell| is_number(cell))
+    };
+
+    // The first row is a header if it contains text in a column with numbers.
+    let has_header = rows.len() > 1
+        && (0..columns).any(|column| {
+            is_numeric(column, &rows[1..])
+                && rows[0].get(column).map_or(false, |cell| !is_number(cell))
+        });
+
+    let body = if has_header { &rows[1..] } else { rows };
+    let numeric_columns: Vec<_> = (0..columns)
+        .map(|column| is_numeric(column, body))
+        .collect();
+
+    let spec: String = numeric_columns
+        .iter()
+        .map(|is_numeric| match (is_numeric, siunitx) {
+            (true, true) => 'S',
+            (true, false) => 'r',
+            (false, _) => 'l',
+        })
+        .collect();
+
+    let (top, mid, bottom) = if booktabs {
+        ("\\toprule", "\\midrule", "\\bottomrule")
+    } else {
+        ("\\hline", "\\hline", "\\hline")
+    };
+
+    let format_row = |row: &Vec<String>, is_header: bool| {
+        let cells: Vec<_> = (0..columns)
+            .map(|column| {
+                let cell = convert_text(row.get(column).map_or("", String::as_str), false);
+                if is_header && siunitx && numeric_columns[column] && !cell.is_empty() {
+                    format!("{{{}}}", cell)
+                } else {
+                    cell
+                }
+            })
+            .collect();
+        format!("{}{}{} \\\\", indent, unit, cells.join(" & "))
+    };
+
+    let mut lines = vec![format!("\\begin{{tabular}}{{{}}}", spec)];
+    lines.push(format!("{}{}{}", indent, unit, top));
+    if has_header {
+        lines.push(format_row(&rows[0], true));
+        lines.push(format!("{}{}{}", indent, unit, mid));
+    }
+
+    lines.extend(body.iter().map(|row| format_row(row, false)));
+    lines.push(format!("{}{}{}", indent, unit, bottom));
+    lines.push(format!("{}\\end{{tabular}}", indent));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_csv_with_header() {
+        let rows = parse_rows("Name, Value\nfoo, 1.5\n\"b,ar\", 20\n");
+        let actual = create_table(&rows, true, false, "", "\t");
+        let expected = [
+            "\\begin{tabular}{lr}",
+            "\t\\toprule",
+            "\tName & Value \\\\",
+            "\t\\midrule",
+            "\tfoo & 1.5 \\\\",
+            "\tb,ar & 20 \\\\",
+            "\t\\bottomrule",
+            "\\end{tabular}",
+        ];
+        assert_eq!(actual, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_tsv_siunitx() {
+        let rows = parse_rows("x\ty\n1\t2e3\n");
+        let actual = create_table(&rows, false, true, "  ", "\t");
+        let expected = [
+            "\\begin{tabular}{SS}",
+            "  \t\\hline",
+            "  \t{x} & {y} \\\\",
+            "  \t\\hline",
+            "  \t1 & 2e3 \\\\",
+            "  \t\\hline",
+            "  \\end{tabular}",
+        ];
+        assert_eq!(actual, expected.join("\n"));
+    }
+
+    #[test]
+    fn test_workspace_edit() {
+        let tester = FeatureTester::builder()
+            .files(vec![("main.tex", "a;b_c\n1;2")])
+            .main("main.tex")
+            .build();
+
+        let uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let range = Range::new_simple(0, 0, 1, 3);
+        let actual = convert_table(tester.convert_table(range), CancellationToken::none());
+
+        let expected = [
+            "\\begin{tabular}{rr}",
+            "\t\\hline",
+            "\ta & b\\_c \\\\",
+            "\t\\hline",
+            "\t1 & 2 \\\\",
+            "\t\\hline",
+            "\\end{tabular}",
+        ];
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(uri, vec![TextEdit::new(range, expected.join("\n"))]);
+        assert_eq!(actual, Some(WorkspaceEdit::new(expected_changes)));
+    }
+}
@@ -0,0 +1,177 @@
+use std::process::{Command, Stdio};
+
+use cancellation::CancellationToken;
+use log::warn;
+use lsp_types::{TextDocumentPositionParams, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{component_db::COMPONENT_DATABASE, syntax::latex};
+
+use super::{cursor::CursorContext, FeatureRequest};
+
+pub const SHOW_DOCUMENTATION_COMMAND: &str = "texlab.showDocumentation";
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentationFile {
+    pub uri: Url,
+    pub score: f64,
+    pub language: Option<String>,
+    pub description: Option<String>,
+}
+
+pub fn show_documentation(
+    request: FeatureRequest<TextDocumentPositionParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<DocumentationFile>> {
+    let context = CursorContext::new(request);
+    let topic = find_documentation_topic(&context)?;
+    cancellation_token.result().ok()?;
+
+    let output = Command::new("texdoc")
+        .args(&["--list", "--machine", &topic])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|why| warn!("Failed to run texdoc: {}", why))
+        .ok()?;
+
+    Some(parse_texdoc_output(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+fn find_documentation_topic(context: &CursorContext<TextDocumentPositionParams>) -> Option<String> {
+    let main_document = context.request.main_document();
+    let data = main_document.data.as_latex()?;
+    if let Some(link) = data.extras.explicit_links.iter().find(|link| {
+        matches!(
+            link.kind,
+            latex::ExplicitLinkKind::Package | latex::ExplicitLinkKind::Class
+        ) && link.stem_range.contains_inclusive(context.offset)
+    }) {
+        return Some(link.stem.to_string());
+    }
+
+    let components = COMPONENT_DATABASE.linked_components(&context.request.subset);
+    let component = if let Some((name, _)) = context.find_environment_name() {
+        components
+            .into_iter()
+            .find(|component| component.environments.iter().any(|env| env == &name))?
+    } else {
+        let token = context
+            .cursor
+            .as_latex()
+            .filter(|token| token.kind().is_command_name())?;
+
+        let name = &token.text()[1..];
+        components.into_iter().find(|component| {
+            component
+                .commands
+                .iter()
+                .any(|command| command.name == name)
+        })?
+    };
+
+    match component.file_names.first() {
+        Some(file_name) => Some(file_name[..file_name.len() - 4].to_string()),
+        None => Some("latex2e".to_string()),
+    }
+}
+
+fn parse_texdoc_output(output: &str) -> Vec<DocumentationFile> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t').skip(1);
+            let score = fields.next()?.parse().ok()?;
+            let uri = Url::from_file_path(fields.next()?).ok()?;
+            let language = fields.next().filter(|field| !field.is_empty());
+            let description = fields.next().filter(|field| !field.is_empty());
+            Some(DocumentationFile {
+                uri,
+                score,
+                language: language.map(ToString::to_string),
+                description: description.map(ToString::to_string),
+            })
+        })
+        .filter(|file| file.score >= 0.0)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use crate::features::testing::FeatureTester;
+
+    use super::*;
+
+    #[test]
+    fn test_topic_package() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", r#"\usepackage{amsmath}"#)])
+            .main("main.tex")
+            .line(0)
+            .character(14)
+            .build()
+            .position();
+
+        let context = CursorContext::new(request);
+        let actual = find_documentation_topic(&context);
+        assert_eq!(actual.as_deref(), Some("amsmath"));
+    }
+
+    #[test]
+    fn test_topic_command() {
+        let request = FeatureTester::builder()
+            .files(vec![(
+                "main.tex",
+                indoc! {r#"
+                \usepackage{lipsum}
+                \lipsum"#},
+            )])
+            .main("main.tex")
+            .line(1)
+            .character(3)
+            .build()
+            .position();
+
+        let context = CursorContext::new(request);
+        let actual = find_documentation_topic(&context);
+        assert_eq!(actual.as_deref(), Some("lipsum"));
+    }
+
+    #[test]
+    fn test_topic_nothing() {
+        let request = FeatureTester::builder()
+            .files(vec![("main.tex", "foo")])
+            .main("main.tex")
+            .line(0)
+            .character(1)
+            .build()
+            .position();
+
+        let context = CursorContext::new(request);
+        let actual = find_documentation_topic(&context);
+        assert_eq!(actual, None);
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let output = indoc! {"
+            amsmath\t10\t/usr/share/texmf-dist/doc/latex/amsmath/amsldoc.pdf\ten\tUser manual
+            amsmath\t-10\t/usr/share/texmf-dist/doc/latex/amsmath/README.md\t\t
+        "};
+
+        let actual = parse_texdoc_output(output);
+        let expected = vec![DocumentationFile {
+            uri: Url::from_file_path("/usr/share/texmf-dist/doc/latex/amsmath/amsldoc.pdf")
+                .unwrap(),
+            score: 10.0,
+            language: Some("en".into()),
+            description: Some("User manual".into()),
+        }];
+        assert_eq!(actual, expected);
+    }
+}
@@ -0,0 +1,104 @@
+use cancellation::CancellationToken;
+use lsp_types::{Range, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{find_todos, LineIndexExt};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodosParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoFile {
+    pub uri: Url,
+    pub todos: Vec<TodoItem>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub range: Range,
+    pub kind: String,
+    pub text: String,
+}
+
+/// Collects the tasks of all documents that belong to the project of the given document.
+pub fn find_project_todos(
+    request: FeatureRequest<TodosParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<Vec<TodoFile>> {
+    let mut files = Vec::new();
+    for document in &request.subset.documents {
+        cancellation_token.result().ok()?;
+        let data = match document.data.as_latex() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        let todos: Vec<_> = find_todos(&data.root, &data.extras.code_chunks)
+            .into_iter()
+            .map(|todo| TodoItem {
+                range: document.line_index.line_col_lsp_range(todo.range),
+                kind: todo.kind,
+                text: todo.text,
+            })
+            .collect();
+
+        if !todos.is_empty() {
+            files.push(TodoFile {
+                uri: document.uri.as_ref().clone().into(),
+                todos,
+            });
+        }
+    }
+
+    files.sort_by(|a, b| a.uri.as_str().cmp(b.uri.as_str()));
+    Some(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_project() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                ("main.tex", "\\include{chapter}\n% TODO: Foo"),
+                ("chapter.tex", "\\todo{Bar}"),
+            ])
+            .main("main.tex")
+            .build();
+
+        let chapter_uri: Url = tester.uri("chapter.tex").as_ref().clone().into();
+        let main_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let actual = find_project_todos(tester.todos(), CancellationToken::none()).unwrap();
+
+        let expected = vec![
+            TodoFile {
+                uri: chapter_uri,
+                todos: vec![TodoItem {
+                    range: Range::new_simple(0, 0, 0, 10),
+                    kind: "TODO".into(),
+                    text: "Bar".into(),
+                }],
+            },
+            TodoFile {
+                uri: main_uri,
+                todos: vec![TodoItem {
+                    range: Range::new_simple(1, 2, 1, 11),
+                    kind: "TODO".into(),
+                    text: "Foo".into(),
+                }],
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+}
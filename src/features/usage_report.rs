@@ -0,0 +1,241 @@
+use std::collections::BTreeMap;
+
+use cancellation::CancellationToken;
+use lsp_types::{Location, TextDocumentIdentifier, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{bibtex, latex, CstNode},
+    DocumentData, LineIndexExt,
+};
+
+use super::FeatureRequest;
+
+pub const USAGE_REPORT_COMMAND: &str = "texlab.usageReport";
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    pub labels: Vec<UsageEntry>,
+    pub citations: Vec<UsageEntry>,
+    pub items: Vec<UsageReportItem>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageEntry {
+    pub name: String,
+    pub definitions: Vec<Location>,
+    pub references: Vec<Location>,
+}
+
+impl UsageEntry {
+    fn problem(&self) -> Option<&'static str> {
+        match (self.definitions.len(), self.references.len()) {
+            (0, _) => Some("undefined"),
+            (_, 0) => Some("unused"),
+            (1, _) => None,
+            (_, _) => Some("defined multiple times"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReportItem {
+    pub label: String,
+    pub description: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<Location>,
+}
+
+pub fn create_usage_report(
+    request: FeatureRequest<UsageReportParams>,
+    cancellation_token: &CancellationToken,
+) -> Option<UsageReport> {
+    let mut labels: BTreeMap<String, UsageEntry> = BTreeMap::new();
+    let mut citations: BTreeMap<String, UsageEntry> = BTreeMap::new();
+    for document in request.workspace.documents() {
+        cancellation_token.result().ok()?;
+        let uri: Url = document.uri.as_ref().clone().into();
+        let location =
+            |range| Location::new(uri.clone(), document.line_index.line_col_lsp_range(range));
+
+        match &document.data {
+            DocumentData::Latex(data) => {
+                for name in &data.extras.label_names {
+                    let entry = labels.entry(name.text.to_string()).or_default();
+                    if name.is_definition {
+                        entry.definitions.push(location(name.range));
+                    } else {
+                        entry.references.push(location(name.range));
+                    }
+                }
+
+                for key in data
+                    .root
+                    .descendants()
+                    .filter_map(latex::Citation::cast)
+                    .filter_map(|citation| citation.key_list())
+                    .flat_map(|keys| keys.keys())
+                    .filter(|key| key.to_string() != "*")
+                {
+                    citations
+                        .entry(key.to_string())
+                        .or_default()
+                        .references
+                        .push(location(key.small_range()));
+                }
+            }
+            DocumentData::Bibtex(data) => {
+                for key in data
+                    .root
+                    .children()
+                    .filter_map(bibtex::Entry::cast)
+                    .filter_map(|entry| entry.key())
+                {
+                    citations
+                        .entry(key.to_string())
+                        .or_default()
+                        .definitions
+                        .push(location(key.small_range()));
+                }
+            }
+            DocumentData::Markdown(data) => {
+                for citation in &data.parse.citations {
+                    citations
+                        .entry(citation.text.to_string())
+                        .or_default()
+                        .references
+                        .push(location(citation.range));
+                }
+            }
+            DocumentData::BuildLog(_) => {}
+        }
+    }
+
+    let labels = collect_entries(labels);
+    let citations = collect_entries(citations);
+    let items = labels
+        .iter()
+        .map(|entry| create_item(entry, "label"))
+        .chain(citations.iter().map(|entry| create_item(entry, "citation")))
+        .collect();
+
+    Some(UsageReport {
+        labels,
+        citations,
+        items,
+    })
+}
+
+fn collect_entries(entries: BTreeMap<String, UsageEntry>) -> Vec<UsageEntry> {
+    entries
+        .into_iter()
+        .map(|(name, entry)| UsageEntry { name, ..entry })
+        .collect()
+}
+
+fn create_item(entry: &UsageEntry, kind: &str) -> UsageReportItem {
+    UsageReportItem {
+        label: entry.name.clone(),
+        description: format!(
+            "{}: {} {}, {} {}",
+            kind,
+            entry.definitions.len(),
+            pluralize(entry.definitions.len(), "definition"),
+            entry.references.len(),
+            pluralize(entry.references.len(), "reference"),
+        ),
+        detail: entry.problem().map(ToString::to_string),
+        location: entry
+            .definitions
+            .first()
+            .or_else(|| entry.references.first())
+            .cloned(),
+    }
+}
+
+fn pluralize(count: usize, word: &str) -> String {
+    if count == 1 {
+        word.to_string()
+    } else {
+        format!("{}s", word)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Range;
+
+    use crate::{features::testing::FeatureTester, RangeExt};
+
+    use super::*;
+
+    #[test]
+    fn test_labels_and_citations() {
+        let tester = FeatureTester::builder()
+            .files(vec![
+                (
+                    "main.tex",
+                    r#"\label{foo}\label{bar}\ref{foo}\cite{baz}\addbibresource{main.bib}"#,
+                ),
+                ("main.bib", r#"@article{baz, title = {Baz}}"#),
+            ])
+            .main("main.tex")
+            .build();
+        let tex_uri: Url = tester.uri("main.tex").as_ref().clone().into();
+        let bib_uri: Url = tester.uri("main.bib").as_ref().clone().into();
+
+        let report = create_usage_report(tester.usage_report(), CancellationToken::none()).unwrap();
+
+        let expected_labels = vec![
+            UsageEntry {
+                name: "bar".into(),
+                definitions: vec![Location::new(
+                    tex_uri.clone(),
+                    Range::new_simple(0, 18, 0, 21),
+                )],
+                references: Vec::new(),
+            },
+            UsageEntry {
+                name: "foo".into(),
+                definitions: vec![Location::new(
+                    tex_uri.clone(),
+                    Range::new_simple(0, 7, 0, 10),
+                )],
+                references: vec![Location::new(
+                    tex_uri.clone(),
+                    Range::new_simple(0, 27, 0, 30),
+                )],
+            },
+        ];
+
+        let expected_citations = vec![UsageEntry {
+            name: "baz".into(),
+            definitions: vec![Location::new(bib_uri, Range::new_simple(0, 9, 0, 12))],
+            references: vec![Location::new(tex_uri, Range::new_simple(0, 37, 0, 40))],
+        }];
+
+        assert_eq!(report.labels, expected_labels);
+        assert_eq!(report.citations, expected_citations);
+        assert_eq!(
+            report
+                .items
+                .iter()
+                .map(|item| (item.label.as_str(), item.detail.as_deref()))
+                .collect::<Vec<_>>(),
+            vec![("bar", Some("unused")), ("foo", None), ("baz", None)]
+        );
+    }
+}
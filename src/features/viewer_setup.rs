@@ -0,0 +1,233 @@
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_EDITOR_COMMAND: &str = "code -g %f:%l";
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ViewerKind {
+    Zathura,
+    Sioyek,
+    Okular,
+    Skim,
+    SumatraPdf,
+}
+
+impl ViewerKind {
+    const ALL: &'static [Self] = &[
+        Self::Zathura,
+        Self::Sioyek,
+        Self::Okular,
+        Self::Skim,
+        Self::SumatraPdf,
+    ];
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewerSetupParams {
+    /// Restricts the result to a single viewer.
+    #[serde(default)]
+    pub viewer: Option<ViewerKind>,
+
+    /// The command that opens the editor at a line (`%f` and `%l` are replaced by the viewer).
+    #[serde(default)]
+    pub editor_command: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewerSetup {
+    pub viewer: ViewerKind,
+    pub name: String,
+    pub platforms: Vec<String>,
+
+    /// The value of `texlab.forwardSearch.executable`.
+    pub executable: String,
+
+    /// The value of `texlab.forwardSearch.args`.
+    pub args: Vec<String>,
+
+    /// The value of `texlab.forwardSearch.processName`.
+    pub process_name: String,
+
+    /// Describes how to set up and execute the inverse search in the viewer.
+    pub inverse_search: String,
+}
+
+/// Creates the forward search configuration and the inverse search instructions
+/// of the supported SyncTeX viewers.
+pub fn find_viewer_setups(params: &ViewerSetupParams) -> Vec<ViewerSetup> {
+    let editor_command = params
+        .editor_command
+        .as_deref()
+        .unwrap_or(DEFAULT_EDITOR_COMMAND);
+
+    ViewerKind::ALL
+        .iter()
+        .copied()
+        .filter(|viewer| params.viewer.map_or(true, |kind| kind == *viewer))
+        .map(|viewer| create_setup(viewer, editor_command))
+        .collect()
+}
+
+fn create_setup(viewer: ViewerKind, editor_command: &str) -> ViewerSetup {
+    let inverse_command =
+        |file: &str, line: &str| editor_command.replace("%f", file).replace("%l", line);
+
+    match viewer {
+        ViewerKind::Zathura => ViewerSetup {
+            viewer,
+            name: "Zathura".into(),
+            platforms: vec!["linux".into()],
+            executable: "zathura".into(),
+            args: vec![
+                "--synctex-forward".into(),
+                "%l:1:%f".into(),
+                "--synctex-editor-command".into(),
+                inverse_command("%{input}", "%{line}"),
+                "%p".into(),
+            ],
+            process_name: "zathura".into(),
+            inverse_search: "The inverse search is configured by the forward search arguments. \
+                Make sure that `set synctex true` is part of your `zathurarc` \
+                and press `Ctrl+Click` in the PDF document."
+                .into(),
+        },
+        ViewerKind::Sioyek => ViewerSetup {
+            viewer,
+            name: "Sioyek".into(),
+            platforms: vec!["linux".into(), "macos".into(), "windows".into()],
+            executable: "sioyek".into(),
+            args: vec![
+                "--reuse-window".into(),
+                "--inverse-search".into(),
+                inverse_command("%1", "%2"),
+                "--forward-search-file".into(),
+                "%f".into(),
+                "--forward-search-line".into(),
+                "%l".into(),
+                "%p".into(),
+            ],
+            process_name: "sioyek".into(),
+            inverse_search: "The inverse search is configured by the forward search arguments. \
+                Press `F4` to enter the SyncTeX mode and right-click in the PDF document."
+                .into(),
+        },
+        ViewerKind::Okular => ViewerSetup {
+            viewer,
+            name: "Okular".into(),
+            platforms: vec!["linux".into()],
+            executable: "okular".into(),
+            args: vec!["--unique".into(), "file:%p#src:%l%f".into()],
+            process_name: "okular".into(),
+            inverse_search: format!(
+                "Change the editor of Okular (Settings -> Configure Okular... -> Editor) \
+                 to \"Custom Text Editor\" and set the command to `{}`. \
+                 Press `Shift+Click` in the PDF document.",
+                inverse_command("%f", "%l")
+            ),
+        },
+        ViewerKind::Skim => {
+            let inverse_command = inverse_command("%file", "%line");
+            let (command, arguments) = inverse_command
+                .split_once(' ')
+                .unwrap_or((&inverse_command, ""));
+
+            ViewerSetup {
+                viewer,
+                name: "Skim".into(),
+                platforms: vec!["macos".into()],
+                executable: "/Applications/Skim.app/Contents/SharedSupport/displayline".into(),
+                args: vec!["-g".into(), "%l".into(), "%p".into(), "%f".into()],
+                process_name: "Skim".into(),
+                inverse_search: format!(
+                    "Select the \"Custom\" preset in the Skim preferences \
+                     (Skim -> Preferences -> Sync -> PDF-TeX Sync support), \
+                     set the command to `{}` and the arguments to `{}`. \
+                     Press `Shift+⌘+Click` in the PDF document.",
+                    command, arguments
+                ),
+            }
+        }
+        ViewerKind::SumatraPdf => ViewerSetup {
+            viewer,
+            name: "SumatraPDF".into(),
+            platforms: vec!["windows".into()],
+            executable: std::env::var("LOCALAPPDATA")
+                .map(|directory| format!("{}\\SumatraPDF\\SumatraPDF.exe", directory))
+                .unwrap_or_else(|_| "SumatraPDF.exe".into()),
+            args: vec![
+                "-reuse-instance".into(),
+                "%p".into(),
+                "-forward-search".into(),
+                "%f".into(),
+                "%l".into(),
+                "-inverse-search".into(),
+                quote_program(&inverse_command("\"%f\"", "%l")),
+            ],
+            process_name: "SumatraPDF".into(),
+            inverse_search: "The inverse search is configured by the forward search arguments. \
+                Press `Alt+DoubleClick` in the PDF document."
+                .into(),
+        },
+    }
+}
+
+/// Wraps the program of a command line in quotes, which also keeps the placeholders
+/// of the command from being expanded by the forward search.
+fn quote_program(command: &str) -> String {
+    let command = command.trim();
+    if command.starts_with('"') {
+        return command.to_string();
+    }
+
+    match command.find(char::is_whitespace) {
+        Some(index) => format!("\"{}\"{}", &command[..index], &command[index..]),
+        None => format!("\"{}\"", command),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_viewers() {
+        let setups = find_viewer_setups(&ViewerSetupParams::default());
+        assert_eq!(setups.len(), ViewerKind::ALL.len());
+    }
+
+    #[test]
+    fn test_editor_command() {
+        let params = ViewerSetupParams {
+            viewer: Some(ViewerKind::Zathura),
+            editor_command: Some("nvr --remote-silent +%l %f".into()),
+        };
+
+        let setups = find_viewer_setups(&params);
+        assert_eq!(setups.len(), 1);
+        assert_eq!(
+            setups[0].args,
+            vec![
+                "--synctex-forward",
+                "%l:1:%f",
+                "--synctex-editor-command",
+                "nvr --remote-silent +%{line} %{input}",
+                "%p",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sumatra_pdf_inverse_search() {
+        let params = ViewerSetupParams {
+            viewer: Some(ViewerKind::SumatraPdf),
+            editor_command: None,
+        };
+
+        let setups = find_viewer_setups(&params);
+        assert_eq!(setups.len(), 1);
+        assert_eq!(setups[0].args[5], "-inverse-search");
+        assert_eq!(setups[0].args[6], "\"code\" -g \"%f\":%l");
+    }
+}
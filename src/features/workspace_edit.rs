@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use lsp_types::{
+    AnnotatedTextEdit, ChangeAnnotation, ChangeAnnotationIdentifier, ClientCapabilities,
+    DocumentChanges, OneOf, OptionalVersionedTextDocumentIdentifier, TextDocumentEdit, TextEdit,
+    Url, WorkspaceEdit,
+};
+
+/// Collects the edits of a refactoring in annotated groups
+/// so that the client can show a meaningful preview of the changes.
+#[derive(Debug, Default)]
+pub struct AnnotatedWorkspaceEdit {
+    annotations: HashMap<ChangeAnnotationIdentifier, ChangeAnnotation>,
+    edits_by_uri: Vec<(Url, Vec<(TextEdit, ChangeAnnotationIdentifier)>)>,
+}
+
+impl AnnotatedWorkspaceEdit {
+    /// Registers a group of edits.
+    /// Edits that need confirmation are not applied by the client unless the user agrees.
+    pub fn annotate(
+        &mut self,
+        id: impl Into<ChangeAnnotationIdentifier>,
+        label: impl Into<String>,
+        description: Option<String>,
+        needs_confirmation: bool,
+    ) {
+        self.annotations.insert(
+            id.into(),
+            ChangeAnnotation {
+                label: label.into(),
+                needs_confirmation: Some(needs_confirmation),
+                description,
+            },
+        );
+    }
+
+    pub fn push(&mut self, uri: Url, id: &str, edit: TextEdit) {
+        let index = match self
+            .edits_by_uri
+            .iter()
+            .position(|(other, _)| *other == uri)
+        {
+            Some(index) => index,
+            None => {
+                self.edits_by_uri.push((uri, Vec::new()));
+                self.edits_by_uri.len() - 1
+            }
+        };
+
+        self.edits_by_uri[index].1.push((edit, id.to_string()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits_by_uri.is_empty()
+    }
+
+    /// Creates the workspace edit with change annotations if the client supports them
+    /// and falls back to the plain text edits otherwise.
+    pub fn finish(self, client_capabilities: &ClientCapabilities) -> WorkspaceEdit {
+        let supports_annotations = client_capabilities
+            .workspace
+            .as_ref()
+            .and_then(|cap| cap.workspace_edit.as_ref())
+            .map_or(false, |cap| {
+                cap.document_changes == Some(true) && cap.change_annotation_support.is_some()
+            });
+
+        if !supports_annotations {
+            let changes = self
+                .edits_by_uri
+                .into_iter()
+                .map(|(uri, edits)| (uri, edits.into_iter().map(|(edit, _)| edit).collect()))
+                .collect();
+
+            return WorkspaceEdit::new(changes);
+        }
+
+        let document_edits = self
+            .edits_by_uri
+            .into_iter()
+            .map(|(uri, edits)| TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                edits: edits
+                    .into_iter()
+                    .map(|(text_edit, annotation_id)| {
+                        OneOf::Right(AnnotatedTextEdit {
+                            text_edit,
+                            annotation_id,
+                        })
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        WorkspaceEdit {
+            changes: None,
+            document_changes: Some(DocumentChanges::Edits(document_edits)),
+            change_annotations: Some(self.annotations),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::{
+        ChangeAnnotationWorkspaceEditClientCapabilities, Range, WorkspaceClientCapabilities,
+        WorkspaceEditClientCapabilities,
+    };
+
+    use super::*;
+
+    fn create_edit() -> AnnotatedWorkspaceEdit {
+        let mut edit = AnnotatedWorkspaceEdit::default();
+        edit.annotate("foo", "Foo", None, false);
+        edit.annotate("bar", "Bar", Some("Baz".into()), true);
+        let uri = Url::parse("file:///main.tex").unwrap();
+        edit.push(
+            uri.clone(),
+            "foo",
+            TextEdit::new(Range::default(), "a".into()),
+        );
+        edit.push(uri, "bar", TextEdit::new(Range::default(), "b".into()));
+        edit
+    }
+
+    #[test]
+    fn test_without_annotation_support() {
+        let actual = create_edit().finish(&ClientCapabilities::default());
+
+        let mut expected_changes = HashMap::new();
+        expected_changes.insert(
+            Url::parse("file:///main.tex").unwrap(),
+            vec![
+                TextEdit::new(Range::default(), "a".into()),
+                TextEdit::new(Range::default(), "b".into()),
+            ],
+        );
+        assert_eq!(actual, WorkspaceEdit::new(expected_changes));
+    }
+
+    #[test]
+    fn test_with_annotation_support() {
+        let client_capabilities = ClientCapabilities {
+            workspace: Some(WorkspaceClientCapabilities {
+                workspace_edit: Some(WorkspaceEditClientCapabilities {
+                    document_changes: Some(true),
+                    change_annotation_support: Some(
+                        ChangeAnnotationWorkspaceEditClientCapabilities::default(),
+                    ),
+                    ..WorkspaceEditClientCapabilities::default()
+                }),
+                ..WorkspaceClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+
+        let actual = create_edit().finish(&client_capabilities);
+        let annotations = actual.change_annotations.unwrap();
+        assert_eq!(annotations["bar"].needs_confirmation, Some(true));
+        assert_eq!(annotations["foo"].needs_confirmation, Some(false));
+
+        match actual.document_changes {
+            Some(DocumentChanges::Edits(edits)) => {
+                assert_eq!(edits.len(), 1);
+                assert_eq!(edits[0].edits.len(), 2);
+            }
+            _ => panic!("expected document edits"),
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::{fs, path::Path};
+
+use regex::Regex;
+use rustc_hash::FxHashMap;
+
+use crate::Document;
+
+/// The number of spaces of an indentation level if the size is not configured.
+const DEFAULT_INDENT_SIZE: usize = 4;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum IndentStyle {
+    Tab,
+    Space,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct EditorConfig {
+    indent_style: Option<IndentStyle>,
+    indent_size: Option<usize>,
+}
+
+/// Returns the text of one indentation level for the text that is inserted into a document.
+/// The settings of `.editorconfig` files take precedence over the indentation
+/// that is used in the document. Tabs are used if neither of them is conclusive.
+pub fn unit(document: &Document) -> String {
+    let config = document
+        .uri
+        .to_file_path()
+        .ok()
+        .map(|path| read_editorconfig(&path))
+        .unwrap_or_default();
+
+    let detected = detect_indent_unit(&document.text);
+    match (config.indent_style, config.indent_size) {
+        (Some(IndentStyle::Tab), _) => "\t".to_string(),
+        (Some(IndentStyle::Space), Some(size)) => " ".repeat(size),
+        (Some(IndentStyle::Space), None) => detected
+            .filter(|unit| unit.starts_with(' '))
+            .unwrap_or_else(|| " ".repeat(DEFAULT_INDENT_SIZE)),
+        (None, _) => detected.unwrap_or_else(|| "\t".to_string()),
+    }
+}
+
+/// Returns the spaces and tabs at the start of a line.
+pub fn leading_whitespace(line: &str) -> &str {
+    &line[..line.len() - line.trim_start_matches(&[' ', '\t'][..]).len()]
+}
+
+/// Returns the leading whitespace of the line that contains the given offset.
+pub fn line_indent(text: &str, offset: usize) -> &str {
+    let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+    leading_whitespace(&text[line_start..])
+}
+
+/// Moves a block of lines to the start of the line and replaces every level
+/// of its remaining indentation with the given unit.
+pub fn reindent(text: &str, unit: &str) -> String {
+    let lines: Vec<_> = text.split_inclusive('\n').collect();
+    let common = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| leading_whitespace(line))
+        .reduce(|common, indent| {
+            let len = common
+                .chars()
+                .zip(indent.chars())
+                .take_while(|(a, b)| a == b)
+                .count();
+            &common[..len]
+        })
+        .unwrap_or_default();
+
+    let lines: Vec<_> = lines
+        .into_iter()
+        .map(|line| line.strip_prefix(common).unwrap_or(line))
+        .collect();
+
+    let source_unit = detect_indent_unit(&lines.concat());
+    let mut result = String::new();
+    for line in lines {
+        let indent = leading_whitespace(line);
+        match source_unit.as_deref() {
+            Some(source_unit) if indent.starts_with(source_unit) => {
+                let rest = indent.trim_start_matches(source_unit);
+                let levels = (indent.len() - rest.len()) / source_unit.len();
+                result.push_str(&unit.repeat(levels));
+                result.push_str(rest);
+                result.push_str(&line[indent.len()..]);
+            }
+            _ => result.push_str(line),
+        }
+    }
+    result
+}
+
+/// Infers the indentation unit from the leading whitespace of the lines of a document.
+fn detect_indent_unit(text: &str) -> Option<String> {
+    let mut tab_lines = 0;
+    let mut space_widths: FxHashMap<usize, usize> = FxHashMap::default();
+    let mut previous_width = 0;
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let indent = leading_whitespace(line);
+        if indent.starts_with('\t') {
+            tab_lines += 1;
+            previous_width = 0;
+        } else if !indent.contains('\t') {
+            let width = indent.len();
+            if width > previous_width {
+                *space_widths.entry(width - previous_width).or_default() += 1;
+            }
+            previous_width = width;
+        }
+    }
+
+    let space_lines: usize = space_widths.values().sum();
+    if tab_lines == 0 && space_lines == 0 {
+        None
+    } else if tab_lines >= space_lines {
+        Some("\t".to_string())
+    } else {
+        space_widths
+            .into_iter()
+            .max_by(|(width1, count1), (width2, count2)| {
+                count1.cmp(count2).then(width2.cmp(width1))
+            })
+            .map(|(width, _)| " ".repeat(width))
+    }
+}
+
+/// Reads the indentation settings of the given file from the `.editorconfig` files
+/// of its directory and its ancestors until a file with `root = true` is found.
+fn read_editorconfig(path: &Path) -> EditorConfig {
+    let file_name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(file_name) => file_name,
+        None => return EditorConfig::default(),
+    };
+
+    let mut texts = Vec::new();
+    for directory in path.ancestors().skip(1) {
+        if let Ok(text) = fs::read_to_string(directory.join(".editorconfig")) {
+            let is_root = parse_editorconfig(&text)
+                .into_iter()
+                .any(|(section, key, value)| section.is_none() && key == "root" && value == "true");
+            texts.push(text);
+            if is_root {
+                break;
+            }
+        }
+    }
+
+    let mut config = EditorConfig::default();
+    for text in texts.iter().rev() {
+        apply_editorconfig(&mut config, text, file_name);
+    }
+    config
+}
+
+fn apply_editorconfig(config: &mut EditorConfig, text: &str, file_name: &str) {
+    let mut tab_width = None;
+    for (section, key, value) in parse_editorconfig(text) {
+        if !section.map_or(false, |section| matches_section(section, file_name)) {
+            continue;
+        }
+
+        match key.as_str() {
+            "indent_style" if value == "tab" => config.indent_style = Some(IndentStyle::Tab),
+            "indent_style" if value == "space" => config.indent_style = Some(IndentStyle::Space),
+            "indent_size" => config.indent_size = value.parse().ok().or(tab_width),
+            "tab_width" => tab_width = value.parse().ok(),
+            _ => {}
+        }
+    }
+}
+
+/// Returns the properties of an `.editorconfig` file together with the section
+/// that they belong to as lowercase key-value pairs.
+fn parse_editorconfig(text: &str) -> Vec<(Option<&str>, String, String)> {
+    let mut properties = Vec::new();
+    let mut section = None;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            section = Some(name);
+        } else if let Some((key, value)) = line.split_once('=') {
+            properties.push((
+                section,
+                key.trim().to_lowercase(),
+                value.trim().to_lowercase(),
+            ));
+        }
+    }
+    properties
+}
+
+/// Checks if a section pattern like `*.{tex,sty}` matches the name of a file.
+/// Patterns that refer to directories are not supported.
+fn matches_section(pattern: &str, file_name: &str) -> bool {
+    let pattern = pattern.trim_start_matches("**/");
+    if pattern.contains('/') {
+        return false;
+    }
+
+    let mut regex = String::from("^");
+    let mut in_braces = false;
+    for c in pattern.chars() {
+        match c {
+            '*' => regex.push_str(".*"),
+            '?' => regex.push('.'),
+            '{' => {
+                in_braces = true;
+                regex.push_str("(?:");
+            }
+            '}' if in_braces => {
+                in_braces = false;
+                regex.push(')');
+            }
+            ',' if in_braces => regex.push('|'),
+            '[' | ']' => regex.push(c),
+            _ => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex).map_or(false, |regex| regex.is_match(file_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_spaces() {
+        let text = "\\begin{itemize}\n  \\item foo\n  \\begin{itemize}\n    \\item bar\n  \\end{itemize}\n\\end{itemize}";
+        assert_eq!(detect_indent_unit(text), Some("  ".to_string()));
+    }
+
+    #[test]
+    fn test_detect_tabs() {
+        let text = "\\begin{itemize}\n\t\\item foo\n\t\\item bar\n\\end{itemize}";
+        assert_eq!(detect_indent_unit(text), Some("\t".to_string()));
+    }
+
+    #[test]
+    fn test_detect_nothing() {
+        assert_eq!(detect_indent_unit("foo\nbar\n"), None);
+    }
+
+    #[test]
+    fn test_editorconfig() {
+        let text = "root = true\n\n[*]\nindent_style = tab\n\n[*.{tex,sty}]\nindent_style = space\nindent_size = 2\n";
+        let mut config = EditorConfig::default();
+        apply_editorconfig(&mut config, text, "main.tex");
+        assert_eq!(
+            config,
+            EditorConfig {
+                indent_style: Some(IndentStyle::Space),
+                indent_size: Some(2),
+            }
+        );
+
+        let mut config = EditorConfig::default();
+        apply_editorconfig(&mut config, text, "main.bib");
+        assert_eq!(config.indent_style, Some(IndentStyle::Tab));
+    }
+
+    #[test]
+    fn test_line_indent() {
+        let text = "foo\n  \tbar\n";
+        assert_eq!(line_indent(text, 7), "  \t");
+        assert_eq!(line_indent(text, 1), "");
+    }
+
+    #[test]
+    fn test_reindent() {
+        let text = "  \\makeatletter\n  \\def\\foo{\n    bar}\n\n  \\makeatother\n";
+        assert_eq!(
+            reindent(text, "\t"),
+            "\\makeatletter\n\\def\\foo{\n\tbar}\n\n\\makeatother\n"
+        );
+    }
+}
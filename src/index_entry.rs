@@ -0,0 +1,191 @@
+use cstree::TextRange;
+
+use crate::syntax::{
+    latex::{self, HasCurly},
+    CstNode,
+};
+
+/// A level of an index entry that is written as `sort@text` or `text`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IndexLevel {
+    pub sort_key: Option<String>,
+    pub text: String,
+}
+
+/// The argument of an `\index` command.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IndexEntry {
+    pub range: TextRange,
+    pub name: String,
+    pub levels: Vec<IndexLevel>,
+    pub encap: Option<String>,
+}
+
+impl IndexEntry {
+    /// Parses the argument of `\index` including `!` sublevels, `@` sort keys
+    /// and the `|` page format. The `"` character quotes the following character.
+    pub fn parse(range: TextRange, name: String) -> Self {
+        let mut parts = split_unquoted(&name, '|', 2).into_iter();
+        let levels = split_unquoted(parts.next().unwrap_or_default(), '!', usize::MAX)
+            .into_iter()
+            .map(|level| {
+                let mut parts = split_unquoted(level, '@', 2).into_iter();
+                let first = unquote(parts.next().unwrap_or_default());
+                match parts.next() {
+                    Some(text) => IndexLevel {
+                        sort_key: Some(first),
+                        text: unquote(text),
+                    },
+                    None => IndexLevel {
+                        sort_key: None,
+                        text: first,
+                    },
+                }
+            })
+            .collect();
+
+        let encap = parts.next().map(|encap| encap.trim().to_string());
+        Self {
+            range,
+            name,
+            levels,
+            encap,
+        }
+    }
+
+    /// Returns the key that determines the position of the entry in the index.
+    pub fn key(&self) -> String {
+        self.levels
+            .iter()
+            .map(|level| level.sort_key.as_deref().unwrap_or(&level.text))
+            .collect::<Vec<_>>()
+            .join("!")
+    }
+}
+
+/// Finds the arguments of all `\index` commands of a document.
+pub fn find_index_entries(root: &latex::SyntaxNode) -> Vec<IndexEntry> {
+    root.descendants()
+        .filter_map(latex::GenericCommand::cast)
+        .filter(|command| command.name().map(|name| name.text()) == Some("\\index"))
+        .filter_map(|command| {
+            let group = command
+                .syntax()
+                .children()
+                .find_map(latex::CurlyGroup::cast)?;
+
+            let end = group.right_curly().map_or_else(
+                || group.small_range().end(),
+                |curly| curly.text_range().start(),
+            );
+            let range = TextRange::new(group.left_curly()?.text_range().end(), end);
+
+            let text = group.syntax().text().to_string();
+            let offset = group.syntax().text_range().start();
+            let name =
+                &text[usize::from(range.start() - offset)..usize::from(range.end() - offset)];
+            Some(IndexEntry::parse(range, name.to_string()))
+        })
+        .collect()
+}
+
+fn split_unquoted(text: &str, separator: char, limit: usize) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices();
+    let mut previous = None;
+    while let Some((i, c)) = chars.next() {
+        if c == '"' && previous != Some('\\') {
+            chars.next();
+            previous = None;
+            continue;
+        }
+
+        if c == separator && parts.len() + 1 < limit {
+            parts.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+
+        previous = Some(c);
+    }
+
+    parts.push(&text[start..]);
+    parts
+}
+
+fn unquote(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    let mut previous = None;
+    while let Some(c) = chars.next() {
+        if c == '"' && previous != Some('\\') {
+            if let Some(next) = chars.next() {
+                result.push(next);
+            }
+
+            previous = None;
+        } else {
+            result.push(c);
+            previous = Some(c);
+        }
+    }
+    result.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(name: &str) -> IndexEntry {
+        IndexEntry::parse(TextRange::default(), name.to_string())
+    }
+
+    #[test]
+    fn test_levels() {
+        let entry = parse("fruit!Apple@\\textit{apple}|textbf");
+        assert_eq!(
+            entry.levels,
+            vec![
+                IndexLevel {
+                    sort_key: None,
+                    text: "fruit".into(),
+                },
+                IndexLevel {
+                    sort_key: Some("Apple".into()),
+                    text: "\\textit{apple}".into(),
+                },
+            ]
+        );
+        assert_eq!(entry.encap.as_deref(), Some("textbf"));
+        assert_eq!(entry.key(), "fruit!Apple");
+    }
+
+    #[test]
+    fn test_quoted() {
+        let entry = parse("exclamation mark \"! and \\\"a");
+        assert_eq!(
+            entry.levels,
+            vec![IndexLevel {
+                sort_key: None,
+                text: "exclamation mark ! and \\\"a".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let root = latex::parse("\\index{foo!bar} \\index{baz}").root;
+        let names: Vec<_> = find_index_entries(&root)
+            .into_iter()
+            .map(|entry| (entry.name, entry.range))
+            .collect();
+
+        assert_eq!(
+            names,
+            vec![
+                ("foo!bar".to_string(), TextRange::new(7.into(), 14.into())),
+                ("baz".to_string(), TextRange::new(23.into(), 26.into())),
+            ]
+        );
+    }
+}
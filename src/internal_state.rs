@@ -0,0 +1,279 @@
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt,
+    hash::{Hash, Hasher},
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, PoisonError},
+};
+
+use log::error;
+use lsp_types::ClientInfo;
+use rustc_hash::FxHasher;
+use serde::{Deserialize, Serialize};
+
+use crate::{distro::DistributionKind, Document, Options, ServerContext, Workspace};
+
+const MAX_PANIC_REPORTS: usize = 16;
+
+/// A minimized reproduction of a request that caused a panic.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicReport {
+    pub handler: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_hash: Option<String>,
+    pub params: serde_json::Value,
+    pub message: String,
+}
+
+/// Keeps the most recent panic reports so that they can be
+/// included in the `texlab/internalState` response.
+#[derive(Default)]
+pub struct PanicReports {
+    reports: Mutex<VecDeque<PanicReport>>,
+}
+
+impl PanicReports {
+    pub fn push(&self, report: PanicReport) {
+        let mut reports = self.reports.lock().unwrap_or_else(PoisonError::into_inner);
+        if reports.len() == MAX_PANIC_REPORTS {
+            reports.pop_front();
+        }
+        reports.push_back(report);
+    }
+
+    pub fn to_vec(&self) -> Vec<PanicReport> {
+        self.reports
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
+
+impl fmt::Debug for PanicReports {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.to_vec()).finish()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InternalState {
+    pub version: String,
+    pub distro: DistributionKind,
+    pub client_info: Option<ClientInfo>,
+    pub options: Options,
+    pub documents: Vec<DocumentState>,
+    pub panics: Vec<PanicReport>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentState {
+    pub uri: String,
+    pub language: String,
+    pub is_open: bool,
+    pub length: usize,
+    pub text_hash: String,
+}
+
+pub fn collect_internal_state(context: &ServerContext, workspace: &dyn Workspace) -> InternalState {
+    let mut documents: Vec<_> = workspace
+        .documents()
+        .into_iter()
+        .map(|document| DocumentState {
+            uri: document.uri.to_string(),
+            language: format!("{:?}", document.language()),
+            is_open: workspace.is_open(&document.uri),
+            length: document.text.len(),
+            text_hash: text_hash(&document.text),
+        })
+        .collect();
+    documents.sort_by(|a, b| a.uri.cmp(&b.uri));
+
+    // The state is requested after panics, so the locks might be poisoned.
+    InternalState {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        distro: *context
+            .distro_kind
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner),
+        client_info: context
+            .client_info
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone(),
+        options: context
+            .options
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone(),
+        documents,
+        panics: context.panic_reports.to_vec(),
+    }
+}
+
+/// Creates a report for a request or a job that panicked while processing the given document.
+/// Only the hash of the document is included to avoid leaking its content into the logs
+/// and the strings of the parameters are redacted for the same reason.
+pub fn create_panic_report(
+    handler: &str,
+    document: Option<&Document>,
+    params: serde_json::Value,
+    payload: &(dyn Any + Send),
+) -> PanicReport {
+    PanicReport {
+        handler: handler.to_string(),
+        uri: document.map(|document| document.uri.to_string()),
+        text_hash: document.map(|document| text_hash(&document.text)),
+        params: redact_params(params),
+        message: panic_message(payload),
+    }
+}
+
+/// Runs `f` and records a panic report instead of unwinding further if it panics.
+/// Every request handler and background job goes through this function
+/// so that a bug in a single feature cannot take down the server.
+pub fn catch_panic<T>(
+    context: &ServerContext,
+    handler: &str,
+    document: Option<&Document>,
+    params: serde_json::Value,
+    f: impl FnOnce() -> T,
+) -> Result<T, PanicReport> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let report = create_panic_report(handler, document, params, payload.as_ref());
+        error!(
+            "Panic in {}: {}",
+            handler,
+            serde_json::to_string(&report).unwrap_or_default()
+        );
+        context.panic_reports.push(report.clone());
+        report
+    })
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Replaces the strings of the request parameters with their length
+/// except for URIs and command names which are needed to reproduce the request.
+fn redact_params(params: serde_json::Value) -> serde_json::Value {
+    fn redact(key: Option<&str>, value: serde_json::Value) -> serde_json::Value {
+        match value {
+            serde_json::Value::String(text) if !key.map_or(false, is_public_key) => {
+                serde_json::Value::String(format!("<{} bytes>", text.len()))
+            }
+            serde_json::Value::Array(values) => {
+                values.into_iter().map(|value| redact(key, value)).collect()
+            }
+            serde_json::Value::Object(fields) => fields
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = redact(Some(key.as_str()), value);
+                    (key, value)
+                })
+                .collect(),
+            value => value,
+        }
+    }
+
+    fn is_public_key(key: &str) -> bool {
+        key == "uri" || key.ends_with("Uri") || key == "command" || key == "languageId"
+    }
+
+    redact(None, params)
+}
+
+fn text_hash(text: &str) -> String {
+    let mut hasher = FxHasher::default();
+    text.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_message() {
+        let payload = panic::catch_unwind(|| panic!("foo {}", 1)).unwrap_err();
+        assert_eq!(panic_message(payload.as_ref()), "foo 1");
+    }
+
+    #[test]
+    fn test_redact_params() {
+        let params = serde_json::json!({
+            "textDocument": { "uri": "file:///foo.tex", "version": 1 },
+            "contentChanges": [{ "text": "\\section{Secret}" }],
+            "arguments": ["secret"],
+        });
+
+        assert_eq!(
+            redact_params(params),
+            serde_json::json!({
+                "textDocument": { "uri": "file:///foo.tex", "version": 1 },
+                "contentChanges": [{ "text": "<16 bytes>" }],
+                "arguments": ["<6 bytes>"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_poisoned_panic_reports() {
+        let reports = PanicReports::default();
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+            let _guard = reports.reports.lock().unwrap();
+            panic!("foo");
+        }));
+
+        assert!(reports.to_vec().is_empty());
+    }
+
+    #[test]
+    fn test_panic_reports_bounded() {
+        let reports = PanicReports::default();
+        for i in 0..=MAX_PANIC_REPORTS {
+            reports.push(PanicReport {
+                handler: i.to_string(),
+                uri: None,
+                text_hash: None,
+                params: serde_json::Value::Null,
+                message: String::new(),
+            });
+        }
+
+        let reports = reports.to_vec();
+        assert_eq!(reports.len(), MAX_PANIC_REPORTS);
+        assert_eq!(reports[0].handler, "1");
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        let context = ServerContext::new(std::env::temp_dir());
+        assert_eq!(
+            catch_panic(&context, "foo", None, serde_json::Value::Null, || 42),
+            Ok(42)
+        );
+
+        let report = catch_panic(&context, "bar", None, serde_json::Value::Null, || {
+            panic!("baz")
+        })
+        .unwrap_err();
+        assert_eq!(report.handler, "bar");
+        assert_eq!(report.message, "baz");
+        assert_eq!(context.panic_reports.to_vec(), vec![report]);
+    }
+}
@@ -4,6 +4,7 @@ use cstree::TextRange;
 use lsp_types::{MarkupContent, MarkupKind};
 
 use crate::{
+    find_theorem_numbers,
     syntax::{
         latex::{self, HasBrack, HasCurly},
         CstNode,
@@ -118,6 +119,17 @@ impl RenderedLabel {
         }
     }
 
+    /// Returns the text that `\nameref` prints for the label:
+    /// the title of a section, the caption of a float or the description of a theorem.
+    pub fn title(&self) -> Option<&str> {
+        match &self.object {
+            Section { text, .. } => Some(text.as_str()),
+            Float { caption, .. } => Some(caption.as_str()),
+            Theorem { description, .. } => description.as_deref(),
+            Equation | EnumItem => None,
+        }
+    }
+
     pub fn documentation(&self) -> MarkupContent {
         MarkupContent {
             kind: MarkupKind::PlainText,
@@ -139,7 +151,12 @@ pub fn render_label<'a>(
         }
     }
 
-    label?.syntax().ancestors().find_map(|parent| {
+    let node = match label {
+        Some(label) => label.syntax(),
+        None => find_key_value_label_definition(subset, label_name)?,
+    };
+
+    node.ancestors().find_map(|parent| {
         render_label_float(parent, &mut number)
             .or_else(|| render_label_section(parent, &mut number))
             .or_else(|| render_label_enum_item(parent, &mut number))
@@ -164,6 +181,26 @@ pub fn find_label_definition<'a>(
         })
 }
 
+fn find_key_value_label_definition<'a>(
+    subset: &'a WorkspaceSubset,
+    label_name: &str,
+) -> Option<&'a latex::SyntaxNode> {
+    subset.documents.iter().find_map(|document| {
+        let data = document.data.as_latex()?;
+        let name = data
+            .extras
+            .label_names
+            .iter()
+            .find(|name| name.is_definition && name.text == label_name)?;
+
+        let token = data
+            .root
+            .token_at_offset(name.range.start())
+            .right_biased()?;
+        Some(token.parent())
+    })
+}
+
 pub fn find_label_number<'a>(subset: &'a WorkspaceSubset, label_name: &str) -> Option<&'a str> {
     subset.documents.iter().find_map(|document| {
         document
@@ -273,9 +310,14 @@ fn render_label_theorem(
         })
     })?;
 
+    let number = number.take().or_else(|| {
+        let root = parent.ancestors().last()?;
+        find_theorem_numbers(subset, root).remove(&environment.small_range())
+    });
+
     Some(RenderedLabel {
         range: environment.small_range(),
-        number: number.take(),
+        number,
         object: LabelledObject::Theorem {
             kind: theorem.description.clone(),
             description,
@@ -0,0 +1,268 @@
+use cstree::{TextRange, TextSize};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    render_label,
+    syntax::{latex, CstNode},
+    LabelledObject, WorkspaceSubset,
+};
+
+/// Words and label prefixes together with the kind of object that they refer to.
+const KIND_NAMES: &[(&str, &str)] = &[
+    ("fig", "Figure"),
+    ("figure", "Figure"),
+    ("subfig", "Figure"),
+    ("tab", "Table"),
+    ("tbl", "Table"),
+    ("table", "Table"),
+    ("part", "Part"),
+    ("chap", "Chapter"),
+    ("ch", "Chapter"),
+    ("chapter", "Chapter"),
+    ("sec", "Section"),
+    ("section", "Section"),
+    ("subsec", "Section"),
+    ("subsection", "Section"),
+    ("subsubsection", "Section"),
+    ("paragraph", "Section"),
+    ("subparagraph", "Section"),
+    ("app", "Section"),
+    ("appendix", "Section"),
+    ("eq", "Equation"),
+    ("eqn", "Equation"),
+    ("equation", "Equation"),
+    ("lst", "Listing"),
+    ("listing", "Listing"),
+    ("alg", "Algorithm"),
+    ("algorithm", "Algorithm"),
+    ("item", "Item"),
+    ("thm", "Theorem"),
+    ("theorem", "Theorem"),
+    ("lem", "Lemma"),
+    ("lemma", "Lemma"),
+    ("def", "Definition"),
+    ("definition", "Definition"),
+    ("prop", "Proposition"),
+    ("proposition", "Proposition"),
+    ("cor", "Corollary"),
+    ("corollary", "Corollary"),
+];
+
+/// Returns the kind of object that is named by a word like `Fig.`, `tables` or `sec`.
+pub fn find_kind_by_name(name: &str) -> Option<&'static str> {
+    let name = name.trim_end_matches('.').to_lowercase();
+    let find = |name: &str| {
+        KIND_NAMES
+            .iter()
+            .find(|(other, _)| *other == name)
+            .map(|(_, kind)| *kind)
+    };
+
+    find(&name).or_else(|| name.strip_suffix('s').and_then(find))
+}
+
+pub fn find_kind_by_object(object: &LabelledObject) -> Option<&'static str> {
+    match object {
+        LabelledObject::Section { prefix, .. } => find_kind_by_name(prefix),
+        LabelledObject::Float { kind, .. } => find_kind_by_name(kind.as_str()),
+        LabelledObject::Theorem { kind, .. } => find_kind_by_name(kind),
+        LabelledObject::Equation => Some("Equation"),
+        LabelledObject::EnumItem => Some("Item"),
+    }
+}
+
+/// Learns which kind of object is labelled with which prefix in the project,
+/// for example that the labels of figures start with `f:`.
+pub fn find_label_prefixes(subset: &WorkspaceSubset) -> FxHashMap<String, &'static str> {
+    let mut counts: FxHashMap<(String, &'static str), usize> = FxHashMap::default();
+    for data in subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for label in data
+            .root
+            .descendants()
+            .filter_map(latex::LabelDefinition::cast)
+        {
+            let name = match label.name().and_then(|name| name.key()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let prefix = match name.split_once(':') {
+                Some((prefix, _)) => prefix.to_string(),
+                None => continue,
+            };
+
+            if let Some(kind) = render_label(subset, &name, Some(label))
+                .and_then(|label| find_kind_by_object(&label.object))
+            {
+                *counts.entry((prefix, kind)).or_default() += 1;
+            }
+        }
+    }
+
+    let mut prefixes: FxHashMap<String, (&'static str, usize)> = FxHashMap::default();
+    for ((prefix, kind), count) in counts {
+        let best = prefixes.entry(prefix).or_insert((kind, count));
+        if count > best.1 || (count == best.1 && kind < best.0) {
+            *best = (kind, count);
+        }
+    }
+
+    prefixes
+        .into_iter()
+        .map(|(prefix, (kind, _))| (prefix, kind))
+        .collect()
+}
+
+/// Returns the kind of object of a label using its definition
+/// or the prefix conventions of the project if the label is not defined.
+pub fn find_label_kind(
+    subset: &WorkspaceSubset,
+    prefixes: &FxHashMap<String, &'static str>,
+    name: &str,
+) -> Option<&'static str> {
+    match render_label(subset, name, None) {
+        Some(label) => find_kind_by_object(&label.object),
+        None => {
+            let (prefix, _) = name.split_once(':')?;
+            prefixes
+                .get(prefix)
+                .copied()
+                .or_else(|| find_kind_by_name(prefix))
+        }
+    }
+}
+
+/// Finds a word like `Table` in `Table~\ref{...}` that names the kind of the referenced object.
+pub fn find_reference_word(
+    text: &str,
+    command_start: TextSize,
+) -> Option<(TextRange, &'static str)> {
+    let before = &text[..usize::from(command_start)];
+    let end = before.trim_end_matches(&[' ', '\t', '~'][..]).len();
+    if end == before.len() {
+        return None;
+    }
+
+    let word_end = before[..end].trim_end_matches('.').len();
+    let start = before[..word_end]
+        .trim_end_matches(|c: char| c.is_alphabetic())
+        .len();
+    if start == word_end {
+        return None;
+    }
+
+    let kind = find_kind_by_name(&before[start..word_end])?;
+    let range = TextRange::new(TextSize::from(start as u32), TextSize::from(end as u32));
+    Some((range, kind))
+}
+
+/// Returns the name of a kind in the style of the word that it replaces.
+pub fn adapt_kind_name(kind: &str, word: &str) -> String {
+    let mut name = if word.starts_with(char::is_lowercase) {
+        kind.to_lowercase()
+    } else {
+        kind.to_string()
+    };
+
+    let lower_word = word.trim_end_matches('.').to_lowercase();
+    if lower_word.ends_with('s') && !KIND_NAMES.iter().any(|(other, _)| *other == lower_word) {
+        name.push('s');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indoc::indoc;
+
+    use crate::{
+        create_workspace_fast, DocumentLanguage, ServerContext, Uri, Workspace, WorkspaceSource,
+    };
+
+    use super::*;
+
+    fn create_subset(text: &str) -> WorkspaceSubset {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(cx).unwrap();
+        let uri = Arc::new(Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap());
+        workspace.open(
+            Arc::clone(&uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+            WorkspaceSource::Client,
+        );
+        workspace.subset(uri).unwrap()
+    }
+
+    const TEXT: &str = indoc! {r#"
+        \section{Foo}\label{sec:foo}
+        \begin{figure}\caption{Bar}\label{f:bar}\end{figure}
+        \begin{table}\caption{Baz}\label{baz}\end{table}
+        \begin{equation}\label{eq:qux}x\end{equation}"#};
+
+    #[test]
+    fn test_find_kind_by_name() {
+        assert_eq!(find_kind_by_name("Fig."), Some("Figure"));
+        assert_eq!(find_kind_by_name("tables"), Some("Table"));
+        assert_eq!(find_kind_by_name("eq"), Some("Equation"));
+        assert_eq!(find_kind_by_name("Sec."), Some("Section"));
+        assert_eq!(find_kind_by_name("foo"), None);
+    }
+
+    #[test]
+    fn test_find_label_kind() {
+        let subset = create_subset(TEXT);
+        let prefixes = find_label_prefixes(&subset);
+        assert_eq!(
+            find_label_kind(&subset, &prefixes, "sec:foo"),
+            Some("Section")
+        );
+        assert_eq!(find_label_kind(&subset, &prefixes, "f:bar"), Some("Figure"));
+        assert_eq!(find_label_kind(&subset, &prefixes, "baz"), Some("Table"));
+        assert_eq!(
+            find_label_kind(&subset, &prefixes, "eq:qux"),
+            Some("Equation")
+        );
+    }
+
+    #[test]
+    fn test_find_label_kind_undefined() {
+        let subset = create_subset(TEXT);
+        let prefixes = find_label_prefixes(&subset);
+        assert_eq!(
+            find_label_kind(&subset, &prefixes, "f:undefined"),
+            Some("Figure")
+        );
+        assert_eq!(
+            find_label_kind(&subset, &prefixes, "tab:undefined"),
+            Some("Table")
+        );
+        assert_eq!(find_label_kind(&subset, &prefixes, "undefined"), None);
+    }
+
+    #[test]
+    fn test_find_reference_word() {
+        let text = "see Fig.~\\ref{fig:foo}";
+        assert_eq!(
+            find_reference_word(text, TextSize::from(9)),
+            Some((TextRange::new(4.into(), 8.into()), "Figure"))
+        );
+        assert_eq!(
+            find_reference_word("see \\ref{fig:foo}", TextSize::from(4)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_adapt_kind_name() {
+        assert_eq!(adapt_kind_name("Table", "figure"), "table");
+        assert_eq!(adapt_kind_name("Table", "Figures"), "Tables");
+        assert_eq!(adapt_kind_name("Section", "Fig."), "Section");
+    }
+}
@@ -28,6 +28,30 @@ pub struct BibtexFieldDoc {
     pub documentation: String,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleDoc {
+    pub name: String,
+    pub documentation: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassOptionDoc {
+    pub name: String,
+    pub classes: Vec<String>,
+    pub documentation: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OptionValueDoc {
+    pub name: String,
+    pub package: String,
+    pub keys: Vec<String>,
+    pub documentation: String,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LanguageData {
@@ -38,6 +62,12 @@ pub struct LanguageData {
     pub tikz_libraries: Vec<String>,
     pub math_environments: Vec<String>,
     pub enum_environments: Vec<String>,
+    pub counters: Vec<String>,
+    pub page_styles: Vec<StyleDoc>,
+    pub bibliography_styles: Vec<StyleDoc>,
+    pub bib_macros: Vec<String>,
+    pub option_values: Vec<OptionValueDoc>,
+    pub class_options: Vec<ClassOptionDoc>,
 }
 
 impl LanguageData {
@@ -59,6 +89,48 @@ impl LanguageData {
             .find(|field| field.name.to_lowercase() == name.to_lowercase())
             .map(|field| field.documentation.as_ref())
     }
+
+    /// Returns the known values of the style argument of the given command
+    /// (`\pagestyle`, `\thispagestyle` or `\bibliographystyle`).
+    pub fn styles(&self, command_name: &str) -> &[StyleDoc] {
+        match command_name {
+            "\\pagestyle" | "\\thispagestyle" => &self.page_styles,
+            "\\bibliographystyle" => &self.bibliography_styles,
+            _ => &[],
+        }
+    }
+
+    pub fn style_documentation(&self, command_name: &str, name: &str) -> Option<&str> {
+        self.styles(command_name)
+            .iter()
+            .find(|style| style.name == name)
+            .map(|style| style.documentation.as_ref())
+    }
+
+    pub fn class_options<'a>(
+        &'a self,
+        class_name: &'a str,
+    ) -> impl Iterator<Item = &'a ClassOptionDoc> + 'a {
+        self.class_options
+            .iter()
+            .filter(move |option| option.classes.iter().any(|class| class == class_name))
+    }
+
+    pub fn class_option_documentation(&self, class_name: &str, name: &str) -> Option<&str> {
+        self.class_options(class_name)
+            .find(|option| option.name == name)
+            .map(|option| option.documentation.as_ref())
+    }
+
+    /// Returns the documentation of a value of a `key=value` option of the given package
+    /// like `style=authoryear` of `biblatex`.
+    pub fn option_value_documentation(&self, package: &str, key: &str, name: &str) -> Option<&str> {
+        self.option_values
+            .iter()
+            .filter(|value| value.package == package && value.keys.iter().any(|k| k == key))
+            .find(|value| value.name.eq_ignore_ascii_case(name))
+            .map(|value| value.documentation.as_ref())
+    }
 }
 
 pub static LANGUAGE_DATA: Lazy<LanguageData> = Lazy::new(|| {
@@ -1,10 +1,16 @@
 use std::{ffi::OsStr, path::Path};
 
+use crate::{syntax::latex::is_literate_extension, Uri};
+
+/// The extensions of the files that are written by TeX and BibTeX during a build.
+const GENERATED_EXTENSIONS: &[&str] = &["aux", "toc", "lof", "lot", "bbl"];
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
 pub enum DocumentLanguage {
     Latex,
     Bibtex,
     BuildLog,
+    Markdown,
 }
 
 impl DocumentLanguage {
@@ -16,18 +22,47 @@ impl DocumentLanguage {
 
     pub fn by_extension(extension: &str) -> Option<Self> {
         match extension.to_lowercase().as_str() {
-            "tex" | "sty" | "cls" | "def" | "lco" | "aux" | "rnw" => Some(Self::Latex),
+            "tex" | "sty" | "cls" | "def" | "lco" | "aux" | "toc" | "lof" | "lot" | "bbl"
+            | "rnw" | "snw" | "pnw" | "stex" | "ptex" | "texw" => Some(Self::Latex),
             "bib" | "bibtex" => Some(Self::Bibtex),
-            "log" => Some(Self::BuildLog),
+            "log" | "ilg" | "glg" => Some(Self::BuildLog),
+            "md" | "markdown" | "qmd" | "rmd" => Some(Self::Markdown),
             _ => None,
         }
     }
 
     pub fn by_language_id(language_id: &str) -> Option<Self> {
         match language_id {
-            "latex" | "tex" => Some(Self::Latex),
+            "latex" | "tex" | "rnw" | "sweave" | "pweave" => Some(Self::Latex),
             "bibtex" | "bib" => Some(Self::Bibtex),
+            "markdown" | "quarto" | "rmd" => Some(Self::Markdown),
             _ => None,
         }
     }
 }
+
+/// Returns `true` if the file is generated by a build (like `.aux`, `.toc` and `.bbl` files)
+/// and should not be edited by the user.
+pub fn is_generated_file(uri: &Uri) -> bool {
+    is_generated_path(Path::new(uri.path()))
+}
+
+/// Returns `true` if the path has the extension of a file that is generated by a build.
+pub fn is_generated_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |extension| {
+            GENERATED_EXTENSIONS.contains(&extension.to_lowercase().as_str())
+        })
+}
+
+/// Returns `true` if the file is a LaTeX document that is written by the user
+/// (including literate documents like `.Rnw` files) instead of a package, a class or a generated file.
+pub fn is_latex_source(uri: &Uri) -> bool {
+    Path::new(uri.path())
+        .extension()
+        .and_then(OsStr::to_str)
+        .map_or(false, |extension| {
+            extension.eq_ignore_ascii_case("tex") || is_literate_extension(extension)
+        })
+}
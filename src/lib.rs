@@ -1,38 +1,81 @@
+pub mod arxiv;
+mod bib_unit;
 mod capabilities;
 #[cfg(feature = "citation")]
 pub mod citation;
 mod client;
+mod command_definition;
 pub mod component_db;
 mod config;
 mod context;
 pub mod diagnostics;
 mod dispatch;
 pub mod distro;
+mod encoding;
 pub mod features;
+mod indentation;
+mod index_entry;
+mod internal_state;
 mod label;
+mod label_kind;
 mod lang_data;
 mod language;
 mod line_index;
 mod line_index_ext;
+mod multiplexer;
+mod natural_language;
+mod numbering;
 mod options;
+mod placeholder;
+mod progress;
+mod prose;
+mod quantity;
 mod range;
+mod recent_edits;
 mod req_queue;
 mod server;
+mod synctex;
 pub mod syntax;
+pub mod tags;
+mod todo;
 mod uri;
+mod whitespace;
+mod word_count;
 mod workspace;
+mod workspace_stats;
 
 pub use self::{
+    bib_unit::{find_bib_units, find_bibliographies_in_scope, BibScopes, BibUnit},
     capabilities::ClientCapabilitiesExt,
+    command_definition::{find_command_definitions, CommandDefinitionInfo, CommandDefinitionKind},
     context::ServerContext,
+    encoding::{decode_text, encode_text, find_input_encoding},
+    index_entry::{find_index_entries, IndexEntry, IndexLevel},
     label::*,
+    label_kind::{
+        adapt_kind_name, find_kind_by_name, find_kind_by_object, find_label_kind,
+        find_label_prefixes, find_reference_word,
+    },
     lang_data::*,
-    language::DocumentLanguage,
+    language::{is_generated_file, is_generated_path, is_latex_source, DocumentLanguage},
     line_index::{LineCol, LineColUtf16, LineIndex},
     line_index_ext::LineIndexExt,
+    multiplexer::listen_shared,
+    natural_language::{find_natural_languages, LanguageRegion, NaturalLanguages},
+    numbering::{
+        find_equation_numbers, find_section_numbers, find_theorem_numbers, format_alph,
+        format_roman, EquationNumber,
+    },
     options::*,
+    placeholder::Placeholders,
+    prose::{extract_prose, ProseSegment},
+    quantity::{find_quantities, is_siunitx_loaded, Quantity},
     range::RangeExt,
     server::Server,
+    synctex::{SyncTexData, SyncTexRecord, SyncTexRecordKind},
+    todo::{find_todos, Todo},
     uri::Uri,
+    whitespace::{find_whitespace_issues, LineEnding, WhitespaceIssues, VERBATIM_ENVIRONMENTS},
+    word_count::{count_words, find_placeholder_text, PlaceholderText, WordCount},
     workspace::*,
 };
@@ -37,6 +37,9 @@ pub(crate) struct Utf16Char {
     pub(crate) start: TextSize,
     /// End offset of a character inside a line, zero-based
     pub(crate) end: TextSize,
+    /// The number of UTF-8 code units that the preceding characters of the line
+    /// need in addition to their UTF-16 code units
+    pub(crate) shift: u32,
 }
 
 impl Utf16Char {
@@ -53,6 +56,11 @@ impl Utf16Char {
             1
         }
     }
+
+    /// Returns the shift of the characters that follow this character.
+    fn shift_after(&self) -> u32 {
+        self.shift + u32::from(self.len()) - self.len_utf16() as u32
+    }
 }
 
 impl LineIndex {
@@ -64,6 +72,7 @@ impl LineIndex {
         let mut curr_row = 0.into();
         let mut curr_col = 0.into();
         let mut line = 0;
+        let mut shift = 0;
         for c in text.chars() {
             let c_len = TextSize::of(c);
             curr_row += c_len;
@@ -78,15 +87,19 @@ impl LineIndex {
 
                 // Prepare for processing the next line
                 curr_col = 0.into();
+                shift = 0;
                 line += 1;
                 continue;
             }
 
             if !c.is_ascii() {
-                utf16_chars.push(Utf16Char {
+                let c = Utf16Char {
                     start: curr_col,
                     end: curr_col + c_len,
-                });
+                    shift,
+                };
+                shift = c.shift_after();
+                utf16_chars.push(c);
             }
 
             curr_col += c_len;
@@ -146,17 +159,14 @@ impl LineIndex {
             .filter(|it| !it.is_empty())
     }
 
+    // Both conversions use a binary search instead of visiting the preceding characters
+    // to avoid quadratic behavior on very long lines (e.g. machine-generated files).
     fn utf8_to_utf16_col(&self, line: u32, col: TextSize) -> usize {
         let mut res: usize = col.into();
         if let Some(utf16_chars) = self.utf16_lines.get(&line) {
-            for c in utf16_chars {
-                if c.end <= col {
-                    res -= usize::from(c.len()) - c.len_utf16();
-                } else {
-                    // From here on, all utf16 characters come *after* the character we are mapping,
-                    // so we don't need to take them into account
-                    break;
-                }
+            let index = partition_point(utf16_chars, |c| c.end <= col);
+            if let Some(c) = index.checked_sub(1).map(|index| &utf16_chars[index]) {
+                res -= c.shift_after() as usize;
             }
         }
         res
@@ -164,19 +174,31 @@ impl LineIndex {
 
     fn utf16_to_utf8_col(&self, line: u32, mut col: u32) -> TextSize {
         if let Some(utf16_chars) = self.utf16_lines.get(&line) {
-            for c in utf16_chars {
-                if col > u32::from(c.start) {
-                    col += u32::from(c.len()) - c.len_utf16() as u32;
-                } else {
-                    // From here on, all utf16 characters come *after* the character we are mapping,
-                    // so we don't need to take them into account
-                    break;
-                }
+            let index = partition_point(utf16_chars, |c| col > u32::from(c.start) - c.shift);
+            if let Some(c) = index.checked_sub(1).map(|index| &utf16_chars[index]) {
+                col += c.shift_after();
             }
         }
 
         col.into()
     }
+
+    /// Returns the length of the longest line in UTF-8 code units.
+    pub fn max_line_length(&self, text_len: TextSize) -> TextSize {
+        let line_ends = self
+            .newlines
+            .iter()
+            .skip(1)
+            .copied()
+            .chain(iter::once(text_len));
+
+        self.newlines
+            .iter()
+            .zip(line_ends)
+            .map(|(start, end)| end - *start)
+            .max()
+            .unwrap_or_default()
+    }
 }
 
 /// Returns `idx` such that:
@@ -215,3 +237,31 @@ where
 
     left
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_conversion() {
+        let index = LineIndex::new("a\nbä€😀c\nd");
+        let offsets = [0, 1, 3, 6, 10, 11];
+        let columns = [0, 1, 2, 3, 5, 6];
+        for (offset, col) in offsets.iter().zip(columns.iter()) {
+            let line_col = LineCol {
+                line: 1,
+                col: *offset,
+            };
+            let utf16 = index.to_utf16(line_col);
+            assert_eq!(utf16, LineColUtf16 { line: 1, col: *col });
+            assert_eq!(index.to_utf8(utf16), line_col);
+        }
+    }
+
+    #[test]
+    fn test_max_line_length() {
+        let text = "a\nbcd\nef";
+        let index = LineIndex::new(text);
+        assert_eq!(index.max_line_length(TextSize::of(text)), TextSize::from(4));
+    }
+}
@@ -1,10 +1,20 @@
-use std::{env, fs::OpenOptions, io, path::PathBuf};
+use std::{
+    env,
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+    process,
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use log::LevelFilter;
 use lsp_server::Connection;
 use structopt::StructOpt;
-use texlab::Server;
+use texlab::{
+    arxiv::write_arxiv_issues, create_workspace_fast, listen_shared, tags::write_tags, Server,
+    ServerContext, Workspace,
+};
 
 /// An implementation of the Language Server Protocol for LaTeX
 #[derive(Debug, StructOpt)]
@@ -20,12 +30,64 @@ struct Opts {
     /// Write the logging output to FILE
     #[structopt(long, name = "FILE", parse(from_os_str))]
     log_file: Option<PathBuf>,
+
+    /// Share one server between all clients that connect to ADDRESS (e.g. 127.0.0.1:9257) over TCP.
+    /// The clients have to send the value of the TEXLAB_SECRET environment variable as `initializationOptions.secret`
+    #[structopt(long, name = "ADDRESS")]
+    listen: Option<String>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Write the symbols of the given documents (and their includes) to a ctags file
+    Tags {
+        /// Write the tags to FILE instead of stdout
+        #[structopt(short, long, name = "FILE", parse(from_os_str))]
+        output: Option<PathBuf>,
+
+        /// The documents to index
+        #[structopt(parse(from_os_str))]
+        files: Vec<PathBuf>,
+    },
+
+    /// Check the given documents (and their includes) for issues that prevent a submission to arXiv
+    Check {
+        /// The documents to check
+        #[structopt(parse(from_os_str))]
+        files: Vec<PathBuf>,
+    },
 }
 
 fn main() -> Result<()> {
-    let opts = Opts::from_args();
+    let mut opts = Opts::from_args();
+    let command = opts.command.take();
+    let listen = opts.listen.take();
     setup_logger(opts);
 
+    match (command, listen) {
+        (Some(_), Some(_)) => bail!("--listen cannot be combined with a subcommand"),
+        (Some(Command::Tags { output, files }), None) => generate_tags(output, files),
+        (Some(Command::Check { files }), None) => check_arxiv(files),
+        (None, Some(address)) => run_shared_server(&address),
+        (None, None) => run_server(),
+    }
+}
+
+fn run_shared_server(address: &str) -> Result<()> {
+    let secret = env::var("TEXLAB_SECRET")
+        .ok()
+        .filter(|secret| !secret.is_empty())
+        .context("--listen requires the TEXLAB_SECRET environment variable")?;
+
+    let connection = listen_shared(address, secret)?;
+    Server::with_connection(connection, env::current_dir()?, true)?.run()?;
+    Ok(())
+}
+
+fn run_server() -> Result<()> {
     let (connection, threads) = Connection::stdio();
     Server::with_connection(connection, env::current_dir()?, true)?.run()?;
     threads.join()?;
@@ -33,6 +95,40 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn generate_tags(output: Option<PathBuf>, files: Vec<PathBuf>) -> Result<()> {
+    let current_dir = env::current_dir()?;
+    let context = Arc::new(ServerContext::new(current_dir.clone()));
+    let workspace = create_workspace_fast(context)?;
+    for file in files {
+        workspace.load(file.canonicalize()?)?;
+    }
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+    };
+
+    write_tags(&workspace, &current_dir, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn check_arxiv(files: Vec<PathBuf>) -> Result<()> {
+    let current_dir = env::current_dir()?;
+    let context = Arc::new(ServerContext::new(current_dir.clone()));
+    let workspace = create_workspace_fast(context)?;
+    for file in files {
+        workspace.load(file.canonicalize()?)?;
+    }
+
+    let stdout = io::stdout();
+    let issue_count = write_arxiv_issues(&workspace, &current_dir, &mut stdout.lock())?;
+    if issue_count > 0 {
+        process::exit(1);
+    }
+    Ok(())
+}
+
 fn setup_logger(opts: Opts) {
     let verbosity_level = if !opts.quiet {
         match opts.verbosity {
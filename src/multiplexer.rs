@@ -0,0 +1,742 @@
+use std::{
+    io::{self, BufReader},
+    net::{Shutdown, TcpListener, ToSocketAddrs},
+    thread,
+};
+
+use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use log::{info, warn};
+use lsp_server::{
+    Connection, ErrorCode, Message, Notification, Request, RequestId, Response, ResponseError,
+};
+use lsp_types::{
+    DidChangeTextDocumentParams, DidOpenTextDocumentParams, MessageType, ShowMessageParams,
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde_json::json;
+
+use crate::{LineIndex, LineIndexExt};
+
+type ClientId = usize;
+
+/// The text of a document as seen by a client that does not own it.
+#[derive(Debug)]
+struct ShadowDocument {
+    version: i32,
+    text: String,
+}
+
+impl ShadowDocument {
+    fn apply(&mut self, params: DidChangeTextDocumentParams) {
+        self.version = params.text_document.version;
+        for change in params.content_changes {
+            match change.range {
+                Some(range) => {
+                    let range = LineIndex::new(&self.text).offset_lsp_range(range);
+                    let range = usize::from(range.start())..usize::from(range.end());
+                    if self.text.get(range.clone()).is_some() {
+                        self.text.replace_range(range, &change.text);
+                    }
+                }
+                None => self.text = change.text,
+            }
+        }
+    }
+}
+
+enum ClientEvent {
+    Connected(ClientId, Sender<Message>),
+    Received(ClientId, Message),
+    Disconnected(ClientId),
+}
+
+/// Listens for clients on the given address and shares one server between all of them
+/// (for example, two editors that work on the same project).
+/// Every client has to send the given secret as `initializationOptions.secret`
+/// since any local user can connect to the address.
+/// Returns the connection that the server should use.
+pub fn listen_shared(address: impl ToSocketAddrs, secret: String) -> io::Result<Connection> {
+    let listener = TcpListener::bind(address)?;
+    let (connection, server_connection) = Connection::memory();
+    let (event_sender, event_receiver) = unbounded();
+    thread::spawn(move || accept_clients(listener, event_sender));
+    thread::spawn(move || Multiplexer::new(server_connection, secret).run(event_receiver));
+    Ok(connection)
+}
+
+fn accept_clients(listener: TcpListener, events: Sender<ClientEvent>) {
+    for (client, stream) in listener.incoming().filter_map(Result::ok).enumerate() {
+        let reader = match stream.try_clone() {
+            Ok(reader) => reader,
+            Err(why) => {
+                warn!("Failed to accept client: {}", why);
+                continue;
+            }
+        };
+
+        info!("Client {} connected", client);
+        let (sender, receiver) = unbounded::<Message>();
+        thread::spawn(move || {
+            let mut writer = stream;
+            for message in receiver {
+                if message.write(&mut writer).is_err() {
+                    break;
+                }
+            }
+
+            // Also stops the reader if the multiplexer has dropped the client.
+            let _ = writer.shutdown(Shutdown::Both);
+        });
+
+        if events.send(ClientEvent::Connected(client, sender)).is_err() {
+            return;
+        }
+
+        let events = events.clone();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(reader);
+            while let Ok(Some(message)) = Message::read(&mut reader) {
+                if events.send(ClientEvent::Received(client, message)).is_err() {
+                    return;
+                }
+            }
+
+            let _ = events.send(ClientEvent::Disconnected(client));
+        });
+    }
+}
+
+/// Forwards the messages between the clients and the server:
+///
+/// - Clients that do not send the secret with their `initialize` request are disconnected.
+/// - The first client initializes the server and the other clients receive the same result.
+/// - The ids of the client requests are replaced, so that the responses can be sent back to the right client.
+/// - Requests of the server (like `workspace/configuration`) are sent to the oldest client.
+/// - The client that opens a document first owns it, so it is analyzed and built only once.
+///   The changes of the other clients are applied to a shadow copy of the document instead.
+///   When the owner closes the document, the next client that has it open takes over with the text of its copy.
+/// - Notifications of the server (like diagnostics and progress) are sent to all clients.
+/// - The server is shut down after the last client has disconnected.
+struct Multiplexer {
+    server: Connection,
+    secret: String,
+    clients: Vec<(ClientId, Sender<Message>)>,
+    unauthenticated_clients: FxHashMap<ClientId, Sender<Message>>,
+    next_request_id: i32,
+    pending_requests: FxHashMap<RequestId, (ClientId, RequestId)>,
+    initialize_request_id: Option<RequestId>,
+    initialize_result: Option<serde_json::Value>,
+    waiting_clients: Vec<(ClientId, RequestId)>,
+    is_initialized: bool,
+    deferred_messages: Vec<(ClientId, Message)>,
+    document_owners: FxHashMap<String, ClientId>,
+    shadow_documents: FxHashMap<(ClientId, String), ShadowDocument>,
+    rejected_documents: FxHashSet<(ClientId, String)>,
+}
+
+impl Multiplexer {
+    fn new(server: Connection, secret: String) -> Self {
+        Self {
+            server,
+            secret,
+            clients: Vec::new(),
+            unauthenticated_clients: FxHashMap::default(),
+            next_request_id: 0,
+            pending_requests: FxHashMap::default(),
+            initialize_request_id: None,
+            initialize_result: None,
+            waiting_clients: Vec::new(),
+            is_initialized: false,
+            deferred_messages: Vec::new(),
+            document_owners: FxHashMap::default(),
+            shadow_documents: FxHashMap::default(),
+            rejected_documents: FxHashSet::default(),
+        }
+    }
+
+    fn run(mut self, events: Receiver<ClientEvent>) {
+        let server_receiver = self.server.receiver.clone();
+        loop {
+            select! {
+                recv(events) -> event => match event {
+                    Ok(ClientEvent::Connected(client, sender)) => {
+                        self.unauthenticated_clients.insert(client, sender);
+                    }
+                    Ok(ClientEvent::Received(client, message)) => self.handle_client_message(client, message),
+                    Ok(ClientEvent::Disconnected(client)) => self.disconnect(client),
+                    Err(_) => break,
+                },
+                recv(server_receiver) -> message => match message {
+                    Ok(message) => self.handle_server_message(message),
+                    Err(_) => break,
+                },
+            }
+        }
+    }
+
+    fn handle_client_message(&mut self, client: ClientId, mut message: Message) {
+        // Unauthenticated clients are dropped after their first message unless it is
+        // an `initialize` request with the right secret, which also closes the connection.
+        if let Some(sender) = self.unauthenticated_clients.remove(&client) {
+            let request = match &mut message {
+                Message::Request(request) => request,
+                _ => return,
+            };
+
+            if !self.authenticate(request) {
+                warn!("Rejecting client {}: missing or wrong secret", client);
+                let response = Response {
+                    id: request.id.clone(),
+                    result: None,
+                    error: Some(ResponseError {
+                        code: ErrorCode::InvalidRequest as i32,
+                        message: "missing or wrong secret in initializationOptions".to_string(),
+                        data: None,
+                    }),
+                };
+                let _ = sender.send(response.into());
+                return;
+            }
+
+            self.clients.push((client, sender));
+        } else if !self.clients.iter().any(|(other, _)| *other == client) {
+            return;
+        }
+
+        let is_handshake = match &message {
+            Message::Request(request) => request.method == "initialize",
+            Message::Notification(notification) => notification.method == "initialized",
+            Message::Response(_) => false,
+        };
+
+        // The server expects the handshake before any other message.
+        if !self.is_initialized && !is_handshake {
+            self.deferred_messages.push((client, message));
+            return;
+        }
+
+        match message {
+            Message::Request(request) => self.handle_client_request(client, request),
+            Message::Response(response) => self.send_to_server(response),
+            Message::Notification(notification) => {
+                self.handle_client_notification(client, notification)
+            }
+        }
+    }
+
+    fn handle_client_request(&mut self, client: ClientId, mut request: Request) {
+        match request.method.as_str() {
+            "initialize" => {
+                if let Some(result) = &self.initialize_result {
+                    let response = Response {
+                        id: request.id,
+                        result: Some(result.clone()),
+                        error: None,
+                    };
+                    self.send_to_client(client, response);
+                    return;
+                } else if self.initialize_request_id.is_some() {
+                    self.waiting_clients.push((client, request.id));
+                    return;
+                }
+            }
+            "shutdown" => {
+                // The server is shut down after the last client has disconnected.
+                self.send_to_client(client, Response::new_ok(request.id, ()));
+                return;
+            }
+            _ => {}
+        }
+
+        let id = RequestId::from(self.next_request_id);
+        self.next_request_id += 1;
+        if request.method == "initialize" {
+            self.initialize_request_id = Some(id.clone());
+        }
+
+        let client_id = std::mem::replace(&mut request.id, id.clone());
+        self.pending_requests.insert(id, (client, client_id));
+        self.send_to_server(request);
+    }
+
+    fn handle_client_notification(&mut self, client: ClientId, mut notification: Notification) {
+        let uri = notification
+            .params
+            .get("textDocument")
+            .and_then(|document| document.get("uri"))
+            .and_then(|uri| uri.as_str())
+            .map(ToString::to_string);
+
+        match (notification.method.as_str(), uri) {
+            ("initialized", _) => {
+                if self.is_initialized {
+                    return;
+                }
+
+                self.is_initialized = true;
+                self.send_to_server(notification);
+                for (client, message) in std::mem::take(&mut self.deferred_messages) {
+                    self.handle_client_message(client, message);
+                }
+            }
+            ("exit", _) => self.disconnect(client),
+            ("$/cancelRequest", _) => {
+                let client_id = notification.params.get("id").cloned();
+                let id = self
+                    .pending_requests
+                    .iter()
+                    .find(|(_, (other, other_id))| {
+                        *other == client && serde_json::to_value(other_id).ok() == client_id
+                    })
+                    .map(|(id, _)| id.clone());
+
+                if let Some(id) = id {
+                    notification.params = json!({ "id": id });
+                    self.send_to_server(notification);
+                }
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let owner = *self.document_owners.entry(uri.clone()).or_insert(client);
+                if owner == client {
+                    self.send_to_server(notification);
+                } else if let Ok(params) =
+                    serde_json::from_value::<DidOpenTextDocumentParams>(notification.params)
+                {
+                    let document = ShadowDocument {
+                        version: params.text_document.version,
+                        text: params.text_document.text,
+                    };
+                    self.shadow_documents.insert((client, uri), document);
+                }
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.rejected_documents.remove(&(client, uri.clone()));
+                self.shadow_documents.remove(&(client, uri.clone()));
+                if self.document_owners.get(&uri) == Some(&client) {
+                    self.hand_over(uri);
+                }
+            }
+            ("textDocument/didChange", Some(uri))
+                if self.shadow_documents.contains_key(&(client, uri.clone())) =>
+            {
+                if let Ok(params) =
+                    serde_json::from_value::<DidChangeTextDocumentParams>(notification.params)
+                {
+                    if let Some(document) = self.shadow_documents.get_mut(&(client, uri.clone())) {
+                        document.apply(params);
+                    }
+                }
+
+                if self.rejected_documents.insert((client, uri.clone())) {
+                    let params = ShowMessageParams {
+                        typ: MessageType::Warning,
+                        message: format!(
+                            "TexLab: {} is open in another editor, so your changes are only analyzed after it is closed there",
+                            uri
+                        ),
+                    };
+                    self.send_to_client(
+                        client,
+                        Notification::new("window/showMessage".into(), params),
+                    );
+                }
+            }
+            (_, Some(uri)) if notification.method.starts_with("textDocument/") => {
+                if self
+                    .document_owners
+                    .get(&uri)
+                    .map_or(true, |owner| *owner == client)
+                {
+                    self.send_to_server(notification);
+                }
+            }
+            _ => self.send_to_server(notification),
+        }
+    }
+
+    fn handle_server_message(&mut self, message: Message) {
+        match message {
+            Message::Response(mut response) => {
+                if self.initialize_request_id.as_ref() == Some(&response.id) {
+                    self.initialize_result = response.result.clone();
+                    for (client, id) in std::mem::take(&mut self.waiting_clients) {
+                        let response = Response {
+                            id,
+                            result: response.result.clone(),
+                            error: response.error.clone(),
+                        };
+                        self.send_to_client(client, response);
+                    }
+                }
+
+                if let Some((client, id)) = self.pending_requests.remove(&response.id) {
+                    response.id = id;
+                    self.send_to_client(client, response);
+                }
+            }
+            Message::Request(request) => {
+                if let Some((client, _)) = self.clients.first() {
+                    self.send_to_client(*client, request);
+                }
+            }
+            Message::Notification(notification) => {
+                for (_, sender) in &self.clients {
+                    let _ = sender.send(notification.clone().into());
+                }
+            }
+        }
+    }
+
+    fn disconnect(&mut self, client: ClientId) {
+        self.unauthenticated_clients.remove(&client);
+        if !self.clients.iter().any(|(other, _)| *other == client) {
+            return;
+        }
+
+        info!("Client {} disconnected", client);
+        self.clients.retain(|(other, _)| *other != client);
+        self.rejected_documents
+            .retain(|(other, _)| *other != client);
+        self.shadow_documents
+            .retain(|(other, _), _| *other != client);
+        self.pending_requests
+            .retain(|_, (other, _)| *other != client);
+        self.waiting_clients.retain(|(other, _)| *other != client);
+        self.deferred_messages.retain(|(other, _)| *other != client);
+
+        let documents: Vec<_> = self
+            .document_owners
+            .iter()
+            .filter(|(_, owner)| **owner == client)
+            .map(|(uri, _)| uri.clone())
+            .collect();
+
+        for uri in documents {
+            if self.is_initialized {
+                self.hand_over(uri);
+            } else {
+                self.document_owners.remove(&uri);
+            }
+        }
+
+        if self.clients.is_empty() && self.is_initialized {
+            info!("Shutting down the server after the last client has disconnected");
+            let id = RequestId::from(self.next_request_id);
+            self.next_request_id += 1;
+            self.send_to_server(Request::new(id, "shutdown".into(), ()));
+            self.send_to_server(Notification::new("exit".into(), ()));
+        }
+    }
+
+    /// Passes a document whose owner has closed it to the oldest client that still has it open.
+    /// The server receives the text of that client, so that it matches the following changes.
+    /// The document is closed if no other client has it open.
+    fn hand_over(&mut self, uri: String) {
+        let new_owner = self
+            .clients
+            .iter()
+            .map(|(client, _)| *client)
+            .find(|client| self.shadow_documents.contains_key(&(*client, uri.clone())));
+
+        let document = new_owner.and_then(|client| {
+            self.rejected_documents.remove(&(client, uri.clone()));
+            self.shadow_documents.remove(&(client, uri.clone()))
+        });
+
+        match (new_owner, document) {
+            (Some(client), Some(document)) => {
+                self.document_owners.insert(uri.clone(), client);
+                let params = json!({
+                    "textDocument": { "uri": uri, "version": document.version },
+                    "contentChanges": [{ "text": document.text }],
+                });
+                self.send_to_server(Notification::new("textDocument/didChange".into(), params));
+            }
+            _ => {
+                self.document_owners.remove(&uri);
+                let params = json!({ "textDocument": { "uri": uri } });
+                self.send_to_server(Notification::new("textDocument/didClose".into(), params));
+            }
+        }
+    }
+
+    /// Checks the secret of the `initialize` request and removes it,
+    /// so that it does not end up in the logs of the server.
+    fn authenticate(&self, request: &mut Request) -> bool {
+        let secret = match request.method.as_str() {
+            "initialize" => request
+                .params
+                .get_mut("initializationOptions")
+                .and_then(|options| options.as_object_mut())
+                .and_then(|options| options.remove("secret")),
+            _ => None,
+        };
+
+        secret
+            .as_ref()
+            .and_then(|secret| secret.as_str())
+            .map_or(false, |secret| constant_time_eq(secret, &self.secret))
+    }
+
+    fn send_to_server(&self, message: impl Into<Message>) {
+        if let Err(why) = self.server.sender.send(message.into()) {
+            warn!("Failed to send message to the server: {}", why);
+        }
+    }
+
+    fn send_to_client(&self, client: ClientId, message: impl Into<Message>) {
+        if let Some((_, sender)) = self.clients.iter().find(|(other, _)| *other == client) {
+            let _ = sender.send(message.into());
+        }
+    }
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "secret";
+
+    fn initialize(id: i32, secret: &str) -> Message {
+        let params = json!({ "capabilities": {}, "initializationOptions": { "secret": secret } });
+        Request::new(id.into(), "initialize".into(), params).into()
+    }
+
+    fn connect(multiplexer: &mut Multiplexer, client: ClientId) -> Receiver<Message> {
+        let (sender, receiver) = unbounded();
+        multiplexer.unauthenticated_clients.insert(client, sender);
+        receiver
+    }
+
+    fn recv_request(receiver: &Receiver<Message>) -> Request {
+        match receiver.try_recv() {
+            Ok(Message::Request(request)) => request,
+            message => panic!("expected a request, got {:?}", message),
+        }
+    }
+
+    fn recv_response(receiver: &Receiver<Message>) -> Response {
+        match receiver.try_recv() {
+            Ok(Message::Response(response)) => response,
+            message => panic!("expected a response, got {:?}", message),
+        }
+    }
+
+    fn recv_notification(receiver: &Receiver<Message>) -> Notification {
+        match receiver.try_recv() {
+            Ok(Message::Notification(notification)) => notification,
+            message => panic!("expected a notification, got {:?}", message),
+        }
+    }
+
+    /// Creates a multiplexer with the given number of initialized clients.
+    fn setup(client_count: usize) -> (Multiplexer, Connection, Vec<Receiver<Message>>) {
+        let (server, connection) = Connection::memory();
+        let mut multiplexer = Multiplexer::new(connection, SECRET.to_string());
+        let mut clients = Vec::new();
+        for client in 0..client_count {
+            clients.push(connect(&mut multiplexer, client));
+            multiplexer.handle_client_message(client, initialize(0, SECRET));
+            if client == 0 {
+                let request = recv_request(&server.receiver);
+                multiplexer.handle_server_message(Response::new_ok(request.id, json!({})).into());
+            }
+
+            recv_response(&clients[client]);
+            let initialized = Notification::new("initialized".into(), json!({}));
+            multiplexer.handle_client_message(client, initialized.into());
+        }
+
+        while server.receiver.try_recv().is_ok() {}
+        (multiplexer, server, clients)
+    }
+
+    #[test]
+    fn test_handshake() {
+        let (server, connection) = Connection::memory();
+        let mut multiplexer = Multiplexer::new(connection, SECRET.to_string());
+        let first = connect(&mut multiplexer, 0);
+        let second = connect(&mut multiplexer, 1);
+
+        multiplexer.handle_client_message(0, initialize(5, SECRET));
+        let request = recv_request(&server.receiver);
+        assert_eq!(request.method, "initialize");
+        assert_eq!(request.params["initializationOptions"], json!({}));
+
+        // The server does not see the second handshake.
+        multiplexer.handle_client_message(1, initialize(7, SECRET));
+        assert!(server.receiver.try_recv().is_err());
+
+        let result = json!({ "capabilities": { "hoverProvider": true } });
+        multiplexer.handle_server_message(Response::new_ok(request.id, result.clone()).into());
+
+        let response = recv_response(&first);
+        assert_eq!(response.id, 5.into());
+        assert_eq!(response.result.as_ref(), Some(&result));
+
+        let response = recv_response(&second);
+        assert_eq!(response.id, 7.into());
+        assert_eq!(response.result.as_ref(), Some(&result));
+    }
+
+    #[test]
+    fn test_wrong_secret() {
+        let (mut multiplexer, server, _clients) = setup(1);
+        let client = connect(&mut multiplexer, 1);
+
+        multiplexer.handle_server_message(Notification::new("foo".into(), ()).into());
+        assert!(client.try_recv().is_err());
+
+        multiplexer.handle_client_message(1, initialize(3, "foo"));
+        let response = recv_response(&client);
+        assert_eq!(response.id, 3.into());
+        assert_eq!(
+            response.error.map(|error| error.code),
+            Some(ErrorCode::InvalidRequest as i32)
+        );
+
+        // The client is disconnected, so it cannot try again.
+        multiplexer.handle_client_message(1, initialize(4, SECRET));
+        let params = json!({ "settings": {} });
+        let notification = Notification::new("workspace/didChangeConfiguration".into(), params);
+        multiplexer.handle_client_message(1, notification.into());
+        assert!(server.receiver.try_recv().is_err());
+        assert!(client.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_request_ids() {
+        let (mut multiplexer, server, clients) = setup(2);
+        for client in 0..2 {
+            let request = Request::new(1.into(), "textDocument/hover".into(), json!({}));
+            multiplexer.handle_client_message(client, request.into());
+        }
+
+        let first = recv_request(&server.receiver);
+        let second = recv_request(&server.receiver);
+        assert_ne!(first.id, second.id);
+
+        let cancel = Notification::new("$/cancelRequest".into(), json!({ "id": 1 }));
+        multiplexer.handle_client_message(1, cancel.into());
+        let cancel = recv_notification(&server.receiver);
+        assert_eq!(cancel.params, json!({ "id": second.id }));
+
+        multiplexer.handle_server_message(Response::new_ok(second.id, "second").into());
+        multiplexer.handle_server_message(Response::new_ok(first.id, "first").into());
+
+        let response = recv_response(&clients[0]);
+        assert_eq!(response.id, 1.into());
+        assert_eq!(response.result, Some(json!("first")));
+
+        let response = recv_response(&clients[1]);
+        assert_eq!(response.id, 1.into());
+        assert_eq!(response.result, Some(json!("second")));
+    }
+
+    fn did_open(text: &str) -> Message {
+        let params = json!({
+            "textDocument": {
+                "uri": "file:///main.tex",
+                "languageId": "latex",
+                "version": 0,
+                "text": text,
+            }
+        });
+        Notification::new("textDocument/didOpen".into(), params).into()
+    }
+
+    fn did_change(version: i32, change: serde_json::Value) -> Message {
+        let params = json!({
+            "textDocument": { "uri": "file:///main.tex", "version": version },
+            "contentChanges": [change],
+        });
+        Notification::new("textDocument/didChange".into(), params).into()
+    }
+
+    fn did_close() -> Message {
+        let params = json!({ "textDocument": { "uri": "file:///main.tex" } });
+        Notification::new("textDocument/didClose".into(), params).into()
+    }
+
+    #[test]
+    fn test_document_owner() {
+        let (mut multiplexer, server, clients) = setup(2);
+        multiplexer.handle_client_message(0, did_open("foo"));
+        assert_eq!(
+            recv_notification(&server.receiver).method,
+            "textDocument/didOpen"
+        );
+
+        multiplexer.handle_client_message(1, did_open("foo"));
+        assert!(server.receiver.try_recv().is_err());
+
+        // The changes of the other client are kept back with a warning, but only once.
+        let change = json!({
+            "range": { "start": { "line": 0, "character": 3 }, "end": { "line": 0, "character": 3 } },
+            "text": "bar",
+        });
+        multiplexer.handle_client_message(1, did_change(1, change.clone()));
+        multiplexer.handle_client_message(1, did_change(2, change));
+        assert!(server.receiver.try_recv().is_err());
+        let message = recv_notification(&clients[1]);
+        assert_eq!(message.method, "window/showMessage");
+        assert_eq!(message.params["type"], json!(2));
+        assert!(clients[1].try_recv().is_err());
+
+        multiplexer.handle_client_message(0, did_change(1, json!({ "text": "baz" })));
+        assert_eq!(
+            recv_notification(&server.receiver).method,
+            "textDocument/didChange"
+        );
+
+        // The document is handed over with the text of the other client after the owner has disconnected.
+        multiplexer.disconnect(0);
+        let notification = recv_notification(&server.receiver);
+        assert_eq!(notification.method, "textDocument/didChange");
+        assert_eq!(notification.params["textDocument"]["version"], json!(2));
+        assert_eq!(
+            notification.params["contentChanges"],
+            json!([{ "text": "foobarbar" }])
+        );
+
+        multiplexer.handle_client_message(1, did_change(3, json!({ "text": "qux" })));
+        assert_eq!(
+            recv_notification(&server.receiver).params["contentChanges"],
+            json!([{ "text": "qux" }])
+        );
+    }
+
+    #[test]
+    fn test_document_close() {
+        let (mut multiplexer, server, _clients) = setup(3);
+        multiplexer.handle_client_message(0, did_open("foo"));
+        multiplexer.handle_client_message(1, did_open("foo"));
+        multiplexer.handle_client_message(2, did_open("foo"));
+        multiplexer.handle_client_message(1, did_close());
+        recv_notification(&server.receiver);
+
+        // The client that still has the document open takes over.
+        multiplexer.handle_client_message(0, did_close());
+        let notification = recv_notification(&server.receiver);
+        assert_eq!(notification.method, "textDocument/didChange");
+        assert_eq!(
+            notification.params["contentChanges"],
+            json!([{ "text": "foo" }])
+        );
+
+        multiplexer.handle_client_message(2, did_close());
+        assert_eq!(
+            recv_notification(&server.receiver).method,
+            "textDocument/didClose"
+        );
+        assert!(server.receiver.try_recv().is_err());
+    }
+}
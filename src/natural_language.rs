@@ -0,0 +1,294 @@
+use cstree::{TextRange, TextSize};
+
+use crate::syntax::{
+    latex::{self, HasCurly, HasKeyValueBody},
+    CstNode,
+};
+
+/// Package options of `babel` that do not denote a language.
+const BABEL_FLAGS: &[&str] = &[
+    "activeacute",
+    "activegrave",
+    "base",
+    "KeepShorthandsActive",
+    "noconfigs",
+    "nocase",
+    "provide",
+    "safe",
+    "showlanguages",
+    "silent",
+];
+
+/// Environments of `babel` and `polyglossia` that switch the language
+/// with the language given as the first argument.
+const LANGUAGE_ENVIRONMENTS: &[&str] = &["otherlanguage", "otherlanguage*", "hyphenrules"];
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LanguageRegion {
+    pub range: TextRange,
+    pub language: String,
+}
+
+/// The natural languages of a document as declared
+/// with the `babel` or `polyglossia` packages.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct NaturalLanguages {
+    pub main: Option<String>,
+    pub loaded: Vec<String>,
+    pub regions: Vec<LanguageRegion>,
+}
+
+impl NaturalLanguages {
+    /// Returns the language that is active at the given offset.
+    /// Nested regions take precedence over the regions that contain them.
+    pub fn language_at(&self, offset: TextSize) -> Option<&str> {
+        self.regions
+            .iter()
+            .filter(|region| region.range.contains(offset))
+            .max_by_key(|region| region.range.start())
+            .map(|region| region.language.as_str())
+            .or_else(|| self.main.as_deref())
+    }
+
+    fn load(&mut self, language: &str) {
+        if !self.loaded.iter().any(|name| name == language) {
+            self.loaded.push(language.to_string());
+        }
+    }
+}
+
+/// Finds the languages loaded with `babel` or `polyglossia` and
+/// the regions that are switched to a different language with
+/// `\selectlanguage`, `\foreignlanguage`, `\text<language>` and
+/// the language environments.
+///
+/// The package options are also read from the `related` documents
+/// because the languages are usually loaded in the preamble of the root document.
+pub fn find_natural_languages(
+    root: &latex::SyntaxNode,
+    related: &[&latex::SyntaxNode],
+) -> NaturalLanguages {
+    let mut languages = NaturalLanguages::default();
+    for node in related
+        .iter()
+        .copied()
+        .chain(std::iter::once(root))
+        .flat_map(|root| root.descendants())
+    {
+        if let Some(include) = latex::Include::cast(node) {
+            analyze_babel(&mut languages, include);
+        } else if let Some(command) = latex::GenericCommand::cast(node) {
+            analyze_polyglossia(&mut languages, command);
+        }
+    }
+
+    for node in root.descendants() {
+        if let Some(environment) = latex::Environment::cast(node) {
+            analyze_environment(&mut languages, environment);
+        } else if let Some(command) = latex::GenericCommand::cast(node) {
+            analyze_switch(&mut languages, root, command);
+        }
+    }
+
+    languages
+}
+
+fn analyze_babel(languages: &mut NaturalLanguages, include: latex::Include) -> Option<()> {
+    if include.syntax().kind() != latex::PACKAGE_INCLUDE
+        || !include
+            .path_list()?
+            .keys()
+            .any(|path| path.to_string() == "babel")
+    {
+        return None;
+    }
+
+    let mut last_language = None;
+    for pair in include.options()?.body()?.pairs() {
+        let key = pair.key()?.to_string();
+        match pair.value() {
+            Some(value) if key == "main" => {
+                let language = value.syntax().text().to_string().trim().to_string();
+                languages.load(&language);
+                languages.main = Some(language);
+            }
+            Some(_) => {}
+            None if BABEL_FLAGS.contains(&key.as_str()) => {}
+            None => {
+                languages.load(&key);
+                last_language = Some(key);
+            }
+        }
+    }
+
+    if languages.main.is_none() {
+        languages.main = last_language;
+    }
+    Some(())
+}
+
+fn analyze_polyglossia(
+    languages: &mut NaturalLanguages,
+    command: latex::GenericCommand,
+) -> Option<()> {
+    let name = command.name()?.text();
+    if !matches!(
+        name,
+        "\\setdefaultlanguage" | "\\setmainlanguage" | "\\setotherlanguage" | "\\setotherlanguages"
+    ) {
+        return None;
+    }
+
+    let argument = first_argument(command)?;
+    for language in argument.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        languages.load(language);
+        if matches!(name, "\\setdefaultlanguage" | "\\setmainlanguage") {
+            languages.main = Some(language.to_string());
+        }
+    }
+    Some(())
+}
+
+fn analyze_environment(
+    languages: &mut NaturalLanguages,
+    environment: latex::Environment,
+) -> Option<()> {
+    let name = environment.begin()?.name()?.key()?.to_string();
+    let language = if LANGUAGE_ENVIRONMENTS.contains(&name.as_str()) {
+        environment
+            .syntax()
+            .children()
+            .find_map(latex::CurlyGroup::cast)?
+            .content_text()?
+            .trim()
+            .to_string()
+    } else if languages.loaded.contains(&name) {
+        name
+    } else {
+        return None;
+    };
+
+    languages.regions.push(LanguageRegion {
+        range: environment.small_range(),
+        language,
+    });
+    Some(())
+}
+
+fn analyze_switch(
+    languages: &mut NaturalLanguages,
+    root: &latex::SyntaxNode,
+    command: latex::GenericCommand,
+) -> Option<()> {
+    let name = command.name()?.text();
+    let mut groups = command
+        .syntax()
+        .children()
+        .filter_map(latex::CurlyGroup::cast);
+    let (language, range) = match name {
+        "\\selectlanguage" => {
+            let language = groups.next()?.content_text()?;
+            let scope = command
+                .syntax()
+                .ancestors()
+                .find(|node| matches!(node.kind(), latex::CURLY_GROUP | latex::ENVIRONMENT))
+                .unwrap_or(root);
+
+            let range = TextRange::new(
+                command.syntax().text_range().end(),
+                scope.text_range().end(),
+            );
+            (language, range)
+        }
+        "\\foreignlanguage" => {
+            let language = groups.next()?.content_text()?;
+            (language, groups.next()?.small_range())
+        }
+        _ => {
+            let language = name.strip_prefix("\\text")?;
+            if !languages.loaded.iter().any(|name| name == language) {
+                return None;
+            }
+            (language.to_string(), groups.next()?.small_range())
+        }
+    };
+
+    languages.regions.push(LanguageRegion {
+        range,
+        language: language.trim().to_string(),
+    });
+    Some(())
+}
+
+fn first_argument(command: latex::GenericCommand) -> Option<String> {
+    command
+        .syntax()
+        .children()
+        .find_map(latex::CurlyGroup::cast)?
+        .content_text()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn language_of(text: &str, needle: &str) -> Option<String> {
+        let root = latex::parse(text).root;
+        let offset = TextSize::from(text.find(needle).unwrap() as u32);
+        find_natural_languages(&root, &[])
+            .language_at(offset)
+            .map(ToString::to_string)
+    }
+
+    #[test]
+    fn test_babel() {
+        let text = indoc! {
+            r#"
+                \usepackage[french, main=ngerman, english]{babel}
+                Foo
+                \begin{otherlanguage}{french}Bar\end{otherlanguage}
+                \foreignlanguage{english}{Baz}
+                {\selectlanguage{french}Qux} Quux
+            "#
+        };
+
+        let languages = find_natural_languages(&latex::parse(text).root, &[]);
+        assert_eq!(languages.loaded, vec!["french", "ngerman", "english"]);
+        assert_eq!(languages.main.as_deref(), Some("ngerman"));
+        assert_eq!(language_of(text, "Foo").as_deref(), Some("ngerman"));
+        assert_eq!(language_of(text, "Bar").as_deref(), Some("french"));
+        assert_eq!(language_of(text, "Baz").as_deref(), Some("english"));
+        assert_eq!(language_of(text, "Qux").as_deref(), Some("french"));
+        assert_eq!(language_of(text, "Quux").as_deref(), Some("ngerman"));
+    }
+
+    #[test]
+    fn test_babel_last_option() {
+        let languages = find_natural_languages(
+            &latex::parse(r#"\usepackage[safe=none, ngerman, english]{babel}"#).root,
+            &[],
+        );
+        assert_eq!(languages.main.as_deref(), Some("english"));
+    }
+
+    #[test]
+    fn test_polyglossia() {
+        let text = indoc! {
+            r#"
+                \setdefaultlanguage{english}
+                \setotherlanguages{german, french}
+                Foo \textgerman{Bar}
+                \begin{french}Baz\end{french}
+                \selectlanguage{german}
+                Qux
+            "#
+        };
+
+        assert_eq!(language_of(text, "Foo").as_deref(), Some("english"));
+        assert_eq!(language_of(text, "Bar").as_deref(), Some("german"));
+        assert_eq!(language_of(text, "Baz").as_deref(), Some("french"));
+        assert_eq!(language_of(text, "Qux").as_deref(), Some("german"));
+    }
+}
@@ -0,0 +1,723 @@
+use cstree::{TextRange, TextSize};
+use rustc_hash::FxHashMap;
+
+use crate::{
+    diagnostics::NUMBERED_ENVIRONMENTS,
+    find_label_number,
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, WorkspaceSubset,
+};
+
+/// The environments that number the whole formula instead of each row.
+const SINGLE_ROW_ENVIRONMENTS: &[&str] = &["equation", "multline"];
+
+#[derive(Debug, Default)]
+struct Counters {
+    values: FxHashMap<String, u32>,
+    parents: FxHashMap<String, String>,
+    appendix: Option<&'static str>,
+}
+
+impl Counters {
+    fn step(&mut self, name: &str) {
+        *self.values.entry(name.to_string()).or_default() += 1;
+        self.reset_children(name);
+    }
+
+    fn reset(&mut self, name: &str) {
+        self.values.insert(name.to_string(), 0);
+        self.reset_children(name);
+    }
+
+    fn reset_children(&mut self, name: &str) {
+        let children: Vec<_> = self
+            .parents
+            .iter()
+            .filter(|(_, parent)| parent.as_str() == name)
+            .map(|(child, _)| child.clone())
+            .collect();
+
+        for child in children {
+            self.reset(&child);
+        }
+    }
+
+    fn format(&self, name: &str) -> String {
+        let value = self.values.get(name).copied().unwrap_or_default();
+        let text = if self.appendix == Some(name) {
+            format_alph(value)
+        } else if name == "part" {
+            format_roman(value)
+        } else {
+            value.to_string()
+        };
+
+        match self.parents.get(name) {
+            Some(parent) if parent != name => format!("{}.{}", self.format(parent), text),
+            _ => text,
+        }
+    }
+}
+
+pub fn format_alph(value: u32) -> String {
+    match value {
+        1..=26 => char::from(b'A' + (value - 1) as u8).to_string(),
+        _ => value.to_string(),
+    }
+}
+
+pub fn format_roman(mut value: u32) -> String {
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut text = String::new();
+    for (number, numeral) in NUMERALS.iter() {
+        while value >= *number {
+            text.push_str(numeral);
+            value -= number;
+        }
+    }
+    text
+}
+
+fn section_counter(kind: latex::SyntaxKind) -> Option<(i32, &'static str)> {
+    match kind {
+        latex::PART => Some((-1, "part")),
+        latex::CHAPTER => Some((0, "chapter")),
+        latex::SECTION => Some((1, "section")),
+        latex::SUBSECTION => Some((2, "subsection")),
+        latex::SUBSUBSECTION => Some((3, "subsubsection")),
+        latex::PARAGRAPH => Some((4, "paragraph")),
+        latex::SUBPARAGRAPH => Some((5, "subparagraph")),
+        _ => None,
+    }
+}
+
+/// The number of a row of a numbered formula.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct EquationNumber {
+    /// The end of the content of the row.
+    pub offset: TextSize,
+
+    /// The number as it is typeset, including the parentheses.
+    pub text: String,
+
+    /// The label of the row, if any.
+    pub label: Option<String>,
+
+    /// Whether the number is given with `\tag` instead of the counter.
+    pub is_tagged: bool,
+}
+
+#[derive(Debug, Default)]
+struct Numbers {
+    sections: FxHashMap<TextRange, String>,
+    theorems: FxHashMap<TextRange, String>,
+    equations: Vec<EquationNumber>,
+}
+
+/// Replays the sectioning commands and theorem environments of the given tree
+/// to compute the numbers that LaTeX would assign to them.
+fn replay(
+    root: &latex::SyntaxNode,
+    counters_by_environment: &FxHashMap<&str, &str>,
+    mut counters: Counters,
+) -> Numbers {
+    let has_chapters = root.descendants().any(|node| node.kind() == latex::CHAPTER);
+    let top_counter = if has_chapters {
+        counters
+            .parents
+            .insert("section".to_string(), "chapter".to_string());
+        counters
+            .parents
+            .entry("equation".to_string())
+            .or_insert_with(|| "chapter".to_string());
+        "chapter"
+    } else {
+        "section"
+    };
+
+    for (child, parent) in [
+        ("subsection", "section"),
+        ("subsubsection", "subsection"),
+        ("paragraph", "subsubsection"),
+        ("subparagraph", "paragraph"),
+    ]
+    .iter()
+    {
+        counters
+            .parents
+            .insert(child.to_string(), parent.to_string());
+    }
+
+    let mut secnumdepth = if has_chapters { 2 } else { 3 };
+    let mut numbers = Numbers::default();
+    for node in root.descendants() {
+        if let Some(section) = latex::Section::cast(node) {
+            let (level, counter) = section_counter(node.kind()).unwrap();
+            let is_starred = section
+                .command()
+                .map_or(true, |command| command.text().ends_with('*'));
+
+            if !is_starred && level <= secnumdepth {
+                counters.step(counter);
+                numbers
+                    .sections
+                    .insert(section.small_range(), counters.format(counter));
+            }
+        } else if let Some(environment) = latex::Environment::cast(node) {
+            let name = environment
+                .begin()
+                .and_then(|begin| begin.name())
+                .and_then(|name| name.key())
+                .map(|name| name.to_string())
+                .unwrap_or_default();
+
+            if let Some(counter) = resolve_counter(counters_by_environment, &name) {
+                counters.step(counter);
+                numbers
+                    .theorems
+                    .insert(environment.small_range(), counters.format(counter));
+            } else if NUMBERED_ENVIRONMENTS.contains(&name.as_str()) {
+                let is_single_row = SINGLE_ROW_ENVIRONMENTS.contains(&name.as_str());
+                number_equations(
+                    environment,
+                    is_single_row,
+                    &mut counters,
+                    &mut numbers.equations,
+                );
+            }
+        } else if let Some(depth) = find_secnumdepth(node) {
+            secnumdepth = depth;
+        } else if latex::GenericCommand::cast(node)
+            .and_then(|command| command.name())
+            .map_or(false, |name| name.text() == "\\appendix")
+        {
+            counters.reset(top_counter);
+            counters.appendix = Some(top_counter);
+        }
+    }
+
+    numbers
+}
+
+/// Numbers the rows of a formula while respecting `\nonumber`, `\notag` and `\tag`.
+fn number_equations(
+    environment: latex::Environment,
+    is_single_row: bool,
+    counters: &mut Counters,
+    equations: &mut Vec<EquationNumber>,
+) {
+    for row in find_rows(environment, is_single_row) {
+        let commands = || {
+            environment
+                .syntax()
+                .descendants()
+                .filter(move |node| row.contains_range(node.text_range()))
+                .filter_map(latex::GenericCommand::cast)
+        };
+
+        let end = environment
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| row.contains_range(token.text_range()))
+            .filter(|token| !matches!(token.kind(), latex::WHITESPACE | latex::COMMENT))
+            .last();
+
+        // An empty row (like the one after a trailing `\\`) is not numbered.
+        let end = match end {
+            Some(token) => token.text_range().end(),
+            None => continue,
+        };
+
+        let is_unnumbered = commands().any(|command| {
+            command.name().map_or(false, |name| {
+                matches!(name.text(), "\\nonumber" | "\\notag")
+            })
+        });
+
+        if is_unnumbered {
+            continue;
+        }
+
+        let tag = commands().find_map(|command| {
+            let name = command.name()?;
+            let text = command
+                .syntax()
+                .children()
+                .find_map(latex::CurlyGroup::cast)?
+                .content_text()?;
+
+            match name.text() {
+                "\\tag" => Some(format!("({})", text.trim())),
+                "\\tag*" => Some(text.trim().to_string()),
+                _ => None,
+            }
+        });
+
+        let is_tagged = tag.is_some();
+        let text = tag.unwrap_or_else(|| {
+            counters.step("equation");
+            format!("({})", counters.format("equation"))
+        });
+
+        let label = environment
+            .syntax()
+            .descendants()
+            .filter(|node| row.contains_range(node.text_range()))
+            .filter_map(latex::LabelDefinition::cast)
+            .find_map(|label| Some(label.name()?.key()?.to_string()));
+
+        equations.push(EquationNumber {
+            offset: end,
+            text,
+            label,
+            is_tagged,
+        });
+    }
+}
+
+/// Splits the content of a formula at the line breaks (`\\`) that belong to it.
+fn find_rows(environment: latex::Environment, is_single_row: bool) -> Vec<TextRange> {
+    let (start, end) = match (environment.begin(), environment.end()) {
+        (Some(begin), Some(end)) => (begin.syntax().text_range().end(), end.small_range().start()),
+        _ => return Vec::new(),
+    };
+
+    let mut rows = Vec::new();
+    let mut row_start = start;
+    if !is_single_row {
+        for command in environment
+            .syntax()
+            .descendants()
+            .filter_map(latex::GenericCommand::cast)
+        {
+            let is_line_break = command.name().map_or(false, |name| name.text() == "\\\\");
+            let is_nested = command
+                .syntax()
+                .ancestors()
+                .find(|node| node.kind() == latex::ENVIRONMENT)
+                .map_or(false, |node| {
+                    node.text_range() != environment.syntax().text_range()
+                });
+
+            if is_line_break && !is_nested {
+                rows.push(TextRange::new(row_start, command.small_range().start()));
+                row_start = command.syntax().text_range().end();
+            }
+        }
+    }
+
+    rows.push(TextRange::new(row_start, end.max(row_start)));
+    rows
+}
+
+fn find_secnumdepth(node: &latex::SyntaxNode) -> Option<i32> {
+    let counter = latex::CounterReference::cast(node)?;
+    if counter.command()?.text() != "\\setcounter"
+        || counter.name()?.key()?.to_string() != "secnumdepth"
+    {
+        return None;
+    }
+
+    let value = latex::CurlyGroup::cast(node.next_sibling()?)?.content_text()?;
+    value.trim().parse().ok()
+}
+
+/// Computes the numbers of the sectioning commands inside of the given tree
+/// while respecting `\setcounter{secnumdepth}`, `\appendix` and starred commands.
+/// The numbers of labeled sections are taken from the `.aux` file if it exists.
+/// The result is keyed by the range of the section.
+pub fn find_section_numbers(
+    subset: &WorkspaceSubset,
+    root: &latex::SyntaxNode,
+) -> FxHashMap<TextRange, String> {
+    let counters_by_environment = FxHashMap::default();
+    let numbers = replay(root, &counters_by_environment, Counters::default()).sections;
+    let is_standalone = is_standalone(subset, root, &counters_by_environment);
+    apply_label_numbers(subset, root, numbers, is_standalone)
+}
+
+/// Computes the numbers of the theorem environments inside of the given tree
+/// by replaying the counters declared with `\newtheorem` and `\numberwithin`.
+/// The numbers of labeled environments are taken from the `.aux` file if it exists.
+/// The result is keyed by the range of the environment.
+pub fn find_theorem_numbers(
+    subset: &WorkspaceSubset,
+    root: &latex::SyntaxNode,
+) -> FxHashMap<TextRange, String> {
+    let (counters, counters_by_environment) = find_counters(subset);
+    if counters_by_environment.is_empty() {
+        return FxHashMap::default();
+    }
+
+    let is_standalone = is_standalone(subset, root, &counters_by_environment);
+    let numbers = replay(root, &counters_by_environment, counters).theorems;
+    apply_label_numbers(subset, root, numbers, is_standalone)
+}
+
+/// Computes the numbers of the rows of the numbered formulas inside of the given document
+/// by replaying the `equation` counter (including `\numberwithin` and `\tag`).
+/// The numbers of labeled rows are taken from the `.aux` file if it exists.
+pub fn find_equation_numbers(subset: &WorkspaceSubset, document: &Document) -> Vec<EquationNumber> {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return Vec::new(),
+    };
+
+    let (counters, counters_by_environment) = find_counters(subset);
+    let is_standalone = is_standalone(subset, &data.root, &counters_by_environment);
+    replay(&data.root, &counters_by_environment, counters)
+        .equations
+        .into_iter()
+        .filter_map(|mut equation| {
+            match equation
+                .label
+                .as_deref()
+                .and_then(|label| find_label_number(subset, label))
+            {
+                Some(number) => equation.text = format!("({})", number),
+                None if is_standalone || equation.is_tagged => {}
+                None => return None,
+            }
+
+            Some(equation)
+        })
+        .collect()
+}
+
+/// Replaces the computed numbers of the labeled nodes with the numbers from the `.aux` file.
+/// Since the tree is replayed on its own, the other numbers are only kept
+/// if no other document of the project can step the counters.
+fn apply_label_numbers(
+    subset: &WorkspaceSubset,
+    root: &latex::SyntaxNode,
+    mut numbers: FxHashMap<TextRange, String>,
+    is_standalone: bool,
+) -> FxHashMap<TextRange, String> {
+    let mut label_numbers = FxHashMap::default();
+    for label in root.descendants().filter_map(latex::LabelDefinition::cast) {
+        let range = label.syntax().ancestors().find_map(|node| {
+            latex::Section::cast(node)
+                .map(|section| section.small_range())
+                .or_else(|| latex::Environment::cast(node).map(|env| env.small_range()))
+        });
+
+        let number = label
+            .name()
+            .and_then(|name| name.key())
+            .and_then(|name| find_label_number(subset, &name.to_string()));
+
+        if let (Some(range), Some(number)) = (range, number) {
+            if numbers.contains_key(&range) {
+                label_numbers
+                    .entry(range)
+                    .or_insert_with(|| number.to_string());
+            }
+        }
+    }
+
+    if !is_standalone {
+        numbers.clear();
+    }
+
+    numbers.extend(label_numbers);
+    numbers
+}
+
+/// Checks if the given tree is the only document of the project that steps the counters.
+fn is_standalone(
+    subset: &WorkspaceSubset,
+    root: &latex::SyntaxNode,
+    counters_by_environment: &FxHashMap<&str, &str>,
+) -> bool {
+    subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .filter(|data| *data.root != **root)
+        .all(|data| !has_numbered_content(&data.root, counters_by_environment))
+}
+
+/// Checks if the tree contains commands that step the counters of the other documents.
+fn has_numbered_content(
+    root: &latex::SyntaxNode,
+    counters_by_environment: &FxHashMap<&str, &str>,
+) -> bool {
+    root.descendants().any(|node| {
+        latex::Section::cast(node).is_some()
+            || latex::Environment::cast(node)
+                .and_then(|environment| environment.begin()?.name()?.key())
+                .map_or(false, |name| {
+                    let name = name.to_string();
+                    NUMBERED_ENVIRONMENTS.contains(&name.as_str())
+                        || counters_by_environment.contains_key(name.as_str())
+                })
+    })
+}
+
+/// Collects the counters declared with `\newtheorem`, `\newcounter` and `\numberwithin`.
+fn find_counters(subset: &WorkspaceSubset) -> (Counters, FxHashMap<&str, &str>) {
+    let mut counters = Counters::default();
+    let mut counters_by_environment = FxHashMap::default();
+    for data in subset
+        .documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for theorem in &data.extras.theorem_environments {
+            let counter = theorem.counter.as_ref().unwrap_or(&theorem.name);
+            counters_by_environment.insert(theorem.name.as_str(), counter.as_str());
+            if let Some(parent) = &theorem.parent {
+                counters.parents.insert(counter.clone(), parent.clone());
+            }
+        }
+
+        for (counter, parent) in &data.extras.counter_parents {
+            counters.parents.insert(counter.clone(), parent.clone());
+        }
+    }
+
+    (counters, counters_by_environment)
+}
+
+fn resolve_counter<'a>(
+    counters_by_environment: &FxHashMap<&str, &'a str>,
+    environment_name: &str,
+) -> Option<&'a str> {
+    let mut counter = *counters_by_environment.get(environment_name)?;
+    for _ in 0..counters_by_environment.len() {
+        match counters_by_environment.get(counter) {
+            Some(&shared) if shared != counter => counter = shared,
+            _ => break,
+        }
+    }
+
+    Some(counter)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indoc::indoc;
+
+    use crate::{
+        create_workspace_fast, DocumentLanguage, ServerContext, Uri, Workspace, WorkspaceSource,
+    };
+
+    use super::*;
+
+    fn sorted(numbers: FxHashMap<TextRange, String>) -> Vec<String> {
+        let mut numbers: Vec<_> = numbers.into_iter().collect();
+        numbers.sort_by_key(|(range, _)| range.start());
+        numbers.into_iter().map(|(_, number)| number).collect()
+    }
+
+    /// Opens the given files and returns the subset of the first one.
+    fn create_subset(files: &[(&str, &str)]) -> WorkspaceSubset {
+        let cx = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let workspace = create_workspace_fast(Arc::clone(&cx)).unwrap();
+        for (name, text) in files {
+            let path = std::env::temp_dir().join(name);
+            let language = DocumentLanguage::by_path(&path).unwrap();
+            workspace.open(
+                Arc::new(Uri::from_file_path(path).unwrap()),
+                text.trim().to_string(),
+                language,
+                WorkspaceSource::Client,
+            );
+        }
+
+        let uri = Uri::from_file_path(std::env::temp_dir().join(files[0].0)).unwrap();
+        workspace.subset(Arc::new(uri)).unwrap()
+    }
+
+    fn find_root<'a>(subset: &'a WorkspaceSubset, name: &str) -> &'a latex::SyntaxNode {
+        let document = subset
+            .documents
+            .iter()
+            .find(|document| document.uri.as_str().ends_with(name))
+            .unwrap();
+
+        &document.data.as_latex().unwrap().root
+    }
+
+    fn find_numbers(text: &str) -> Vec<String> {
+        let subset = create_subset(&[("main.tex", text)]);
+        sorted(find_theorem_numbers(
+            &subset,
+            find_root(&subset, "main.tex"),
+        ))
+    }
+
+    fn find_sections(text: &str) -> Vec<String> {
+        let subset = create_subset(&[("main.tex", text)]);
+        sorted(find_section_numbers(
+            &subset,
+            find_root(&subset, "main.tex"),
+        ))
+    }
+
+    #[test]
+    fn test_shared_counter() {
+        let numbers = find_numbers(indoc! {
+            r#"
+                \newtheorem{thm}{Theorem}
+                \newtheorem{lem}[thm]{Lemma}
+                \begin{thm}\end{thm}
+                \begin{lem}\end{lem}
+                \begin{thm}\end{thm}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["1", "2", "3"]);
+    }
+
+    #[test]
+    fn test_number_within() {
+        let numbers = find_numbers(indoc! {
+            r#"
+                \newtheorem{thm}{Theorem}[section]
+                \newtheorem{lem}{Lemma}
+                \numberwithin{lem}{section}
+                \section{Foo}
+                \begin{thm}\end{thm}
+                \section*{Bar}
+                \section{Baz}
+                \begin{lem}\end{lem}
+                \begin{thm}\end{thm}
+                \begin{lem}\end{lem}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["1.1", "2.1", "2.1", "2.2"]);
+    }
+
+    #[test]
+    fn test_section_starred() {
+        let numbers = find_sections(indoc! {
+            r#"
+                \section{Foo}
+                \subsection{Bar}
+                \section*{Baz}
+                \section{Qux}
+                \subsection{Quux}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["1", "1.1", "2", "2.1"]);
+    }
+
+    #[test]
+    fn test_section_chapter_secnumdepth() {
+        let numbers = find_sections(indoc! {
+            r#"
+                \chapter{Foo}
+                \section{Bar}
+                \subsection{Baz}
+                \subsubsection{Qux}
+                \setcounter{secnumdepth}{3}
+                \subsubsection{Quux}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["1", "1.1", "1.1.1", "1.1.1.1"]);
+    }
+
+    #[test]
+    fn test_section_appendix() {
+        let numbers = find_sections(indoc! {
+            r#"
+                \part{Foo}
+                \section{Bar}
+                \appendix
+                \section{Baz}
+                \subsection{Qux}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["I", "1", "A", "A.1"]);
+    }
+
+    #[test]
+    fn test_section_included() {
+        let subset = create_subset(&[
+            (
+                "main.tex",
+                indoc! {r#"
+                    \section{Foo}
+                    \input{child}"#},
+            ),
+            (
+                "child.tex",
+                indoc! {r#"
+                    \section{Bar}\label{sec:bar}
+                    \section{Baz}"#},
+            ),
+            ("main.aux", r#"\newlabel{sec:bar}{{2}{1}}"#),
+        ]);
+
+        let numbers = sorted(find_section_numbers(
+            &subset,
+            find_root(&subset, "child.tex"),
+        ));
+        assert_eq!(numbers, vec!["2"]);
+    }
+
+    #[test]
+    fn test_theorem_included() {
+        let subset = create_subset(&[
+            (
+                "main.tex",
+                indoc! {r#"
+                    \newtheorem{thm}{Theorem}
+                    \begin{thm}\end{thm}
+                    \input{child}"#},
+            ),
+            (
+                "child.tex",
+                indoc! {r#"
+                    \begin{thm}\label{thm:foo}\end{thm}
+                    \begin{thm}\end{thm}"#},
+            ),
+            ("main.aux", r#"\newlabel{thm:foo}{{2}{1}}"#),
+        ]);
+
+        let numbers = sorted(find_theorem_numbers(
+            &subset,
+            find_root(&subset, "child.tex"),
+        ));
+        assert_eq!(numbers, vec!["2"]);
+    }
+
+    #[test]
+    fn test_theorem_label_without_aux() {
+        let numbers = find_numbers(indoc! {
+            r#"
+                \newtheorem{thm}{Theorem}
+                \begin{thm}\label{thm:foo}\end{thm}
+                \begin{thm}\end{thm}
+            "#
+        });
+
+        assert_eq!(numbers, vec!["1", "2"]);
+    }
+}
@@ -17,8 +17,15 @@ pub struct Options {
 
     pub formatter_line_length: Option<i32>,
 
+    #[serde(default)]
+    pub formatter_fix_whitespace: bool,
+
     pub diagnostics_delay: Option<u64>,
 
+    pub completion_limit: Option<usize>,
+
+    pub symbol_limit: Option<usize>,
+
     #[serde(default)]
     pub build: BuildOptions,
 
@@ -29,6 +36,30 @@ pub struct Options {
     pub latexindent: LatexindentOptions,
 
     pub forward_search: Option<ForwardSearchOptions>,
+
+    #[serde(default)]
+    pub math_preview: MathPreviewOptions,
+
+    #[serde(default)]
+    pub latex: LatexOptions,
+
+    #[serde(default)]
+    pub float_diagnostics: FloatDiagnosticsOptions,
+
+    #[serde(default)]
+    pub lint_rules: Vec<LintRule>,
+
+    #[serde(default)]
+    pub inlay_hints: InlayHintOptions,
+
+    #[serde(default)]
+    pub folding: FoldingOptions,
+
+    #[serde(default)]
+    pub language_tool: LanguageToolOptions,
+
+    #[serde(default)]
+    pub prose: ProseOptions,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
@@ -60,12 +91,138 @@ impl Default for LatexFormatter {
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LatexindentOptions {
+    pub executable: Option<String>,
+
     pub local: Option<String>,
 
     #[serde(default)]
     pub modify_line_breaks: bool,
 }
 
+impl LatexindentOptions {
+    pub fn executable(&self) -> String {
+        self.executable
+            .clone()
+            .unwrap_or_else(|| "latexindent".to_string())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatexOptions {
+    #[serde(default)]
+    pub citation_commands: Vec<String>,
+
+    #[serde(default)]
+    pub label_definition_commands: Vec<String>,
+
+    #[serde(default)]
+    pub label_reference_commands: Vec<String>,
+
+    #[serde(default)]
+    pub include_commands: Vec<String>,
+}
+
+/// Enables the hints of the `texlab/inlayHints` request.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct InlayHintOptions {
+    pub section_statistics: bool,
+    pub equation_numbers: bool,
+}
+
+/// Configures the `texlab.foldAllEnvironments` command.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FoldingOptions {
+    pub environments: Vec<String>,
+}
+
+/// Enables the grammar and style checks of a LanguageTool server.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LanguageToolOptions {
+    pub url: Option<String>,
+
+    pub language: Option<String>,
+
+    pub disabled_rules: Vec<String>,
+}
+
+/// Configures the prose that is checked by LanguageTool and returned by `texlab/detex`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ProseOptions {
+    pub exclude_placeholder_text: bool,
+}
+
+impl Default for ProseOptions {
+    fn default() -> Self {
+        Self {
+            exclude_placeholder_text: true,
+        }
+    }
+}
+
+/// Enables the rules that check `figure` and `table` environments.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FloatDiagnosticsOptions {
+    pub missing_caption: bool,
+
+    pub missing_label: bool,
+
+    pub label_before_caption: bool,
+
+    pub placement: bool,
+
+    pub oversized_content: bool,
+}
+
+impl Default for FloatDiagnosticsOptions {
+    fn default() -> Self {
+        Self {
+            missing_caption: true,
+            missing_label: true,
+            label_before_caption: true,
+            placement: false,
+            oversized_content: true,
+        }
+    }
+}
+
+/// A user-defined rule that reports the matches of a regular expression
+/// or the occurrences of a command.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LintRule {
+    pub pattern: Option<String>,
+
+    pub command: Option<String>,
+
+    pub message: String,
+
+    #[serde(default)]
+    pub severity: LintSeverity,
+
+    pub replacement: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintSeverity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Default for LintSeverity {
+    fn default() -> Self {
+        Self::Warning
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BuildOptions {
@@ -81,18 +238,27 @@ pub struct BuildOptions {
 
     #[serde(default)]
     pub forward_search_after: bool,
+
+    pub forward_search_delay: Option<u64>,
+
+    #[serde(default)]
+    pub install_missing_packages: bool,
+
+    #[serde(flatten)]
+    pub platform: PlatformOverrides,
 }
 
 impl BuildOptions {
     pub fn executable(&self) -> String {
-        self.executable
-            .as_ref()
-            .map(Clone::clone)
+        self.platform
+            .executable()
+            .or_else(|| self.executable.clone())
             .unwrap_or_else(|| "latexmk".to_string())
     }
 
     pub fn args(&self) -> Vec<String> {
-        self.args.as_ref().map(Clone::clone).unwrap_or_else(|| {
+        let args = self.platform.args().or_else(|| self.args.clone());
+        args.unwrap_or_else(|| {
             vec![
                 "-pdf".to_string(),
                 "-interaction=nonstopmode".to_string(),
@@ -114,9 +280,19 @@ pub struct ViewerOptions {
     pub args: Option<String>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MathPreviewOptions {
+    pub executable: Option<String>,
+
+    pub args: Option<Vec<String>>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ChktexOptions {
+    pub executable: Option<String>,
+
     #[serde(default)]
     pub on_open_and_save: bool,
 
@@ -124,8 +300,67 @@ pub struct ChktexOptions {
     pub on_edit: bool,
 }
 
+impl ChktexOptions {
+    pub fn executable(&self) -> String {
+        self.executable
+            .clone()
+            .unwrap_or_else(|| "chktex".to_string())
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ForwardSearchOptions {
     pub executable: Option<String>,
     pub args: Option<Vec<String>>,
+    pub process_name: Option<String>,
+
+    #[serde(flatten)]
+    pub platform: PlatformOverrides,
+}
+
+impl ForwardSearchOptions {
+    pub fn executable(&self) -> Option<String> {
+        self.platform
+            .executable()
+            .or_else(|| self.executable.clone())
+    }
+
+    pub fn args(&self) -> Option<Vec<String>> {
+        self.platform.args().or_else(|| self.args.clone())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct CommandOptions {
+    pub executable: Option<String>,
+    pub args: Option<Vec<String>>,
+}
+
+/// Replaces the executable or the arguments of a command on a specific platform.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct PlatformOverrides {
+    pub windows: Option<CommandOptions>,
+    pub macos: Option<CommandOptions>,
+    pub linux: Option<CommandOptions>,
+}
+
+impl PlatformOverrides {
+    pub fn current(&self) -> Option<&CommandOptions> {
+        if cfg!(windows) {
+            self.windows.as_ref()
+        } else if cfg!(target_os = "macos") {
+            self.macos.as_ref()
+        } else {
+            self.linux.as_ref()
+        }
+    }
+
+    pub fn executable(&self) -> Option<String> {
+        self.current()?.executable.clone()
+    }
+
+    pub fn args(&self) -> Option<Vec<String>> {
+        self.current()?.args.clone()
+    }
 }
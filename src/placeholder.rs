@@ -0,0 +1,122 @@
+use std::{env, path::Path};
+
+/// The values that can be substituted into the configured commands:
+///
+/// - `%f`: the TeX file
+/// - `%p`: the PDF file
+/// - `%l`: the (one-based) line number
+/// - `%dir`: the directory of the TeX file
+/// - `${workspaceFolder}`: the root directory of the project
+/// - `${env:NAME}` or `${NAME}`: the environment variable `NAME`
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Placeholders<'a> {
+    pub tex_file: Option<&'a Path>,
+    pub pdf_file: Option<&'a Path>,
+    pub line_number: Option<u32>,
+    pub workspace_folder: Option<&'a Path>,
+}
+
+impl<'a> Placeholders<'a> {
+    /// Expands all placeholders inside of the given argument.
+    /// Quoted arguments are passed through unchanged.
+    /// Unknown placeholders and placeholders without a value are kept as they are.
+    pub fn expand(&self, argument: &str) -> String {
+        if argument.starts_with('"') || argument.ends_with('"') {
+            return argument.to_string();
+        }
+
+        let mut result = String::new();
+        let mut rest = argument;
+        while let Some(index) = rest.find(&['%', '$'][..]) {
+            result.push_str(&rest[..index]);
+            rest = &rest[index..];
+
+            match self.expand_next(rest) {
+                Some((value, length)) => {
+                    result.push_str(&value);
+                    rest = &rest[length..];
+                }
+                None => {
+                    result.push_str(&rest[..1]);
+                    rest = &rest[1..];
+                }
+            }
+        }
+        result.push_str(rest);
+        result
+    }
+
+    fn expand_next(&self, text: &str) -> Option<(String, usize)> {
+        let path = |path: Option<&Path>| path.and_then(Path::to_str).map(ToString::to_string);
+
+        if text.starts_with("%dir") {
+            let directory = self.tex_file.and_then(Path::parent);
+            Some((path(directory)?, 4))
+        } else if text.starts_with("%f") {
+            Some((path(self.tex_file)?, 2))
+        } else if text.starts_with("%p") {
+            Some((path(self.pdf_file)?, 2))
+        } else if text.starts_with("%l") {
+            Some(((self.line_number? + 1).to_string(), 2))
+        } else if let Some(variable) = text.strip_prefix("${") {
+            let end = variable.find('}')?;
+            let name = &variable[..end];
+            let value = if name == "workspaceFolder" {
+                path(self.workspace_folder)?
+            } else {
+                env::var(name.strip_prefix("env:").unwrap_or(name)).ok()?
+            };
+            Some((value, end + 3))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_file_placeholders() {
+        let tex_file = PathBuf::from("/foo/bar.tex");
+        let pdf_file = PathBuf::from("/foo/bar.pdf");
+        let placeholders = Placeholders {
+            tex_file: Some(&tex_file),
+            pdf_file: Some(&pdf_file),
+            line_number: Some(41),
+            workspace_folder: None,
+        };
+
+        assert_eq!(
+            placeholders.expand("%l:%f:%p"),
+            "42:/foo/bar.tex:/foo/bar.pdf"
+        );
+        assert_eq!(placeholders.expand("--dir=%dir"), "--dir=/foo");
+        assert_eq!(placeholders.expand("\"%f\""), "\"%f\"");
+        assert_eq!(placeholders.expand("100%"), "100%");
+    }
+
+    #[test]
+    fn test_variables() {
+        let workspace_folder = PathBuf::from("/foo");
+        let placeholders = Placeholders {
+            workspace_folder: Some(&workspace_folder),
+            ..Placeholders::default()
+        };
+
+        env::set_var("TEXLAB_PLACEHOLDER_TEST", "bar");
+        assert_eq!(
+            placeholders.expand("${workspaceFolder}/${env:TEXLAB_PLACEHOLDER_TEST}"),
+            "/foo/bar"
+        );
+        assert_eq!(placeholders.expand("${TEXLAB_PLACEHOLDER_TEST}"), "bar");
+        assert_eq!(
+            placeholders.expand("${TEXLAB_UNDEFINED_VARIABLE}"),
+            "${TEXLAB_UNDEFINED_VARIABLE}"
+        );
+        assert_eq!(placeholders.expand("%f"), "%f");
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use cancellation::{CancellationToken, CancellationTokenSource};
+use crossbeam_channel::Sender;
+use log::warn;
+use lsp_server::Message;
+use lsp_types::{
+    notification::Progress, request::WorkDoneProgressCreate, NumberOrString, ProgressParams,
+    ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams,
+    WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use rustc_hash::FxHashMap;
+use uuid::Uuid;
+
+use crate::{client, req_queue::ReqQueue, ClientCapabilitiesExt, ServerContext};
+
+/// Keeps track of the cancellation sources of the running operations
+/// so that they can be cancelled with `window/workDoneProgress/cancel`.
+#[derive(Default)]
+pub struct ProgressTokens {
+    sources: Mutex<FxHashMap<String, CancellationTokenSource>>,
+}
+
+impl ProgressTokens {
+    fn insert(&self, token: String, token_source: CancellationTokenSource) {
+        self.sources.lock().unwrap().insert(token, token_source);
+    }
+
+    fn remove(&self, token: &str) -> Option<CancellationTokenSource> {
+        self.sources.lock().unwrap().remove(token)
+    }
+
+    pub fn cancel(&self, token: &NumberOrString) {
+        let token = match token {
+            NumberOrString::String(token) => token,
+            NumberOrString::Number(_) => return,
+        };
+
+        if let Some(token_source) = self.remove(token) {
+            token_source.cancel();
+        }
+    }
+}
+
+impl fmt::Debug for ProgressTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressTokens").finish()
+    }
+}
+
+/// Reports the progress of a long running operation to the client
+/// using `window/workDoneProgress` if the client supports it.
+/// The progress ends when the reporter is dropped.
+pub struct ProgressReporter<'a> {
+    context: &'a ServerContext,
+    lsp_sender: Sender<Message>,
+    token: Option<String>,
+    cancellation_token: Arc<CancellationToken>,
+}
+
+impl<'a> ProgressReporter<'a> {
+    pub fn begin(
+        context: &'a ServerContext,
+        req_queue: &Mutex<ReqQueue>,
+        lsp_sender: &Sender<Message>,
+        title: &str,
+        message: Option<String>,
+        cancellable: bool,
+    ) -> Self {
+        let token_source = CancellationTokenSource::new();
+        let cancellation_token = Arc::clone(token_source.token());
+        let mut reporter = Self {
+            context,
+            lsp_sender: lsp_sender.clone(),
+            token: None,
+            cancellation_token,
+        };
+
+        let supports_progress = context
+            .client_capabilities
+            .lock()
+            .unwrap()
+            .has_work_done_progress_support();
+
+        if !supports_progress {
+            return reporter;
+        }
+
+        let token = format!("texlab-{}", Uuid::new_v4());
+        let params = WorkDoneProgressCreateParams {
+            token: NumberOrString::String(token.clone()),
+        };
+
+        if let Err(why) =
+            client::send_request::<WorkDoneProgressCreate>(req_queue, lsp_sender, params)
+        {
+            warn!("Failed to create work done progress: {}", why);
+            return reporter;
+        }
+
+        context.progress_tokens.insert(token.clone(), token_source);
+
+        reporter.token = Some(token);
+        reporter.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.to_string(),
+            message,
+            cancellable: Some(cancellable),
+            percentage: None,
+        }));
+        reporter
+    }
+
+    pub fn report(&self, message: String, done: usize, total: usize) {
+        let percentage = if total == 0 {
+            100
+        } else {
+            (done * 100 / total) as u32
+        };
+
+        self.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+            cancellable: None,
+            message: Some(message),
+            percentage: Some(percentage),
+        }));
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.cancellation_token.is_canceled()
+    }
+
+    fn send(&self, value: WorkDoneProgress) {
+        if let Some(token) = &self.token {
+            let _ = client::send_notification::<Progress>(
+                &self.lsp_sender,
+                ProgressParams {
+                    token: NumberOrString::String(token.clone()),
+                    value: ProgressParamsValue::WorkDone(value),
+                },
+            );
+        }
+    }
+}
+
+impl<'a> Drop for ProgressReporter<'a> {
+    fn drop(&mut self) {
+        self.send(WorkDoneProgress::End(WorkDoneProgressEnd { message: None }));
+        if let Some(token) = &self.token {
+            self.context.progress_tokens.remove(token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, thread};
+
+    use lsp_types::{ClientCapabilities, WindowClientCapabilities};
+
+    use super::*;
+
+    fn create_context(work_done_progress: bool) -> ServerContext {
+        let context = ServerContext::new(PathBuf::new());
+        *context.client_capabilities.lock().unwrap() = ClientCapabilities {
+            window: Some(WindowClientCapabilities {
+                work_done_progress: Some(work_done_progress),
+                ..WindowClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+        context
+    }
+
+    fn progress_values(messages: Vec<Message>) -> Vec<(String, WorkDoneProgress)> {
+        messages
+            .into_iter()
+            .map(|message| match message {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, "$/progress");
+                    let params: ProgressParams =
+                        serde_json::from_value(notification.params).unwrap();
+                    let token = match params.token {
+                        NumberOrString::String(token) => token,
+                        NumberOrString::Number(_) => unreachable!(),
+                    };
+
+                    let ProgressParamsValue::WorkDone(value) = params.value;
+                    (token, value)
+                }
+                _ => panic!("unexpected message: {:?}", message),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_begin_report_end() {
+        let context = create_context(true);
+        let req_queue = Arc::new(Mutex::new(ReqQueue::default()));
+        let (lsp_sender, lsp_receiver) = crossbeam_channel::unbounded();
+
+        let client = {
+            let req_queue = Arc::clone(&req_queue);
+            let lsp_receiver = lsp_receiver.clone();
+            thread::spawn(move || match lsp_receiver.recv().unwrap() {
+                Message::Request(request) => {
+                    assert_eq!(request.method, "window/workDoneProgress/create");
+                    let data = req_queue.lock().unwrap().outgoing.complete(request.id);
+                    data.sender.send(Ok(serde_json::Value::Null)).unwrap();
+                }
+                message => panic!("unexpected message: {:?}", message),
+            })
+        };
+
+        let reporter = ProgressReporter::begin(
+            &context,
+            &req_queue,
+            &lsp_sender,
+            "Indexing",
+            Some("main.tex".into()),
+            true,
+        );
+        client.join().unwrap();
+
+        let token = reporter.token.clone().unwrap();
+        assert!(context
+            .progress_tokens
+            .sources
+            .lock()
+            .unwrap()
+            .contains_key(&token));
+
+        reporter.report("1/4".into(), 1, 4);
+        reporter.report("4/4".into(), 4, 4);
+        assert!(!reporter.is_canceled());
+        drop(reporter);
+
+        assert!(context.progress_tokens.sources.lock().unwrap().is_empty());
+        let values = progress_values(lsp_receiver.try_iter().collect());
+        assert!(values.iter().all(|(other, _)| *other == token));
+
+        let values: Vec<_> = values.into_iter().map(|(_, value)| value).collect();
+        assert_eq!(
+            values,
+            vec![
+                WorkDoneProgress::Begin(WorkDoneProgressBegin {
+                    title: "Indexing".into(),
+                    message: Some("main.tex".into()),
+                    cancellable: Some(true),
+                    percentage: None,
+                }),
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    cancellable: None,
+                    message: Some("1/4".into()),
+                    percentage: Some(25),
+                }),
+                WorkDoneProgress::Report(WorkDoneProgressReport {
+                    cancellable: None,
+                    message: Some("4/4".into()),
+                    percentage: Some(100),
+                }),
+                WorkDoneProgress::End(WorkDoneProgressEnd { message: None }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cancel() {
+        let context = create_context(true);
+        let token_source = CancellationTokenSource::new();
+        let cancellation_token = Arc::clone(token_source.token());
+        context
+            .progress_tokens
+            .insert("texlab-foo".into(), token_source);
+
+        context.progress_tokens.cancel(&NumberOrString::Number(42));
+        assert!(!cancellation_token.is_canceled());
+
+        context
+            .progress_tokens
+            .cancel(&NumberOrString::String("texlab-foo".into()));
+        assert!(cancellation_token.is_canceled());
+        assert!(context.progress_tokens.sources.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_without_work_done_progress_support() {
+        let context = create_context(false);
+        let req_queue = Mutex::new(ReqQueue::default());
+        let (lsp_sender, lsp_receiver) = crossbeam_channel::unbounded();
+
+        let reporter =
+            ProgressReporter::begin(&context, &req_queue, &lsp_sender, "Indexing", None, true);
+        assert_eq!(reporter.token, None);
+
+        reporter.report("1/2".into(), 1, 2);
+        assert!(!reporter.is_canceled());
+        drop(reporter);
+
+        assert!(lsp_receiver.try_recv().is_err());
+        assert!(context.progress_tokens.sources.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,342 @@
+use cstree::{NodeOrToken, TextRange};
+
+use crate::{
+    find_placeholder_text,
+    syntax::{latex, CstNode},
+    ProseOptions, LANGUAGE_DATA, VERBATIM_ENVIRONMENTS,
+};
+
+/// The text that placeholder commands like `\lipsum` are interpreted as
+/// if their placeholder text is part of the prose.
+const PLACEHOLDER_SENTENCE: &str = "Lorem ipsum dolor sit amet, consectetuer adipiscing elit.";
+
+/// Commands and the number of their leading arguments that are not typeset as text.
+const MARKUP_ARGUMENTS: &[(&str, usize)] = &[
+    ("\\foreignlanguage", 1),
+    ("\\selectlanguage", 1),
+    ("\\hspace", 1),
+    ("\\vspace", 1),
+    ("\\setlength", 2),
+    ("\\addtolength", 2),
+    ("\\setcounter", 2),
+    ("\\addtocounter", 2),
+    ("\\pagestyle", 1),
+    ("\\thispagestyle", 1),
+    ("\\bibliographystyle", 1),
+    ("\\url", 1),
+    ("\\href", 1),
+];
+
+/// A part of a document that is either prose or markup.
+/// The segments of a document cover its whole text, so that the offsets of a tool
+/// that checks the prose (like LanguageTool) can be mapped back to the document.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ProseSegment {
+    /// Text that is typeset as it is written.
+    Text(TextRange),
+
+    /// Markup that is typeset like the given text (usually nothing).
+    Markup {
+        range: TextRange,
+        interpret_as: String,
+    },
+}
+
+impl ProseSegment {
+    pub fn range(&self) -> TextRange {
+        match self {
+            Self::Text(range) | Self::Markup { range, .. } => *range,
+        }
+    }
+}
+
+/// Splits a document into prose and markup.
+/// Only the body of the document is considered prose.
+/// Math, citations and references are interpreted as a placeholder word
+/// so that the surrounding sentence stays intact.
+/// Placeholder text like `\lipsum` is either left out or interpreted as a sentence of Lorem ipsum.
+pub fn extract_prose(root: &latex::SyntaxNode, options: &ProseOptions) -> Vec<ProseSegment> {
+    let body = root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .find(|environment| environment_name(environment).as_deref() == Some("document"))
+        .map_or_else(
+            || root.text_range(),
+            |environment| environment.small_range(),
+        );
+
+    let mut builder = ProseBuilder {
+        body,
+        exclude_placeholder_text: options.exclude_placeholder_text,
+        segments: Vec::new(),
+    };
+
+    builder.visit(root);
+    builder.segments
+}
+
+struct ProseBuilder {
+    body: TextRange,
+    exclude_placeholder_text: bool,
+    segments: Vec<ProseSegment>,
+}
+
+impl ProseBuilder {
+    fn visit(&mut self, node: &latex::SyntaxNode) {
+        let mut argument_count = node
+            .first_token()
+            .filter(|_| node.kind() == latex::GENERIC_COMMAND)
+            .and_then(|name| {
+                MARKUP_ARGUMENTS
+                    .iter()
+                    .find(|(command, _)| *command == name.text())
+            })
+            .map_or(0, |(_, count)| *count);
+
+        // The arguments of an environment directly follow `\begin{...}`.
+        let mut is_argument_position = false;
+        for child in node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node)
+                    if node.kind() == latex::CURLY_GROUP
+                        && (argument_count > 0 || is_argument_position) =>
+                {
+                    argument_count = argument_count.saturating_sub(1);
+                    is_argument_position = is_argument_position && !ends_with_trivia(node);
+                    self.push_atom(node, "");
+                }
+                NodeOrToken::Node(node) => {
+                    is_argument_position = node.kind() == latex::BEGIN && !ends_with_trivia(node);
+                    if let Some(placeholder) = placeholder(node) {
+                        self.push_atom(node, placeholder);
+                    } else if latex::GenericCommand::cast(node)
+                        .and_then(find_placeholder_text)
+                        .is_some()
+                    {
+                        let text = if self.exclude_placeholder_text {
+                            ""
+                        } else {
+                            PLACEHOLDER_SENTENCE
+                        };
+                        self.push_atom(node, text);
+                    } else if is_excluded(node) {
+                        self.push_atom(node, "");
+                    } else {
+                        self.visit(node);
+                    }
+                }
+                NodeOrToken::Token(token) => {
+                    is_argument_position = false;
+                    self.push_token(token);
+                }
+            }
+        }
+    }
+
+    fn push_token(&mut self, token: &latex::SyntaxToken) {
+        let range = token.text_range();
+        if is_prose(token) && self.body.contains_range(range) {
+            self.push_text(range);
+        } else {
+            self.push_markup(range, "");
+        }
+    }
+
+    /// Replaces the node with the given text.
+    /// The trailing whitespace of the node is kept to separate the placeholder from the next word.
+    fn push_atom(&mut self, node: &latex::SyntaxNode, text: &str) {
+        let tokens: Vec<_> = node
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .collect();
+
+        let trivia_count = tokens
+            .iter()
+            .rev()
+            .take_while(|token| matches!(token.kind(), latex::WHITESPACE | latex::COMMENT))
+            .count();
+
+        let (atom, trivia) = tokens.split_at(tokens.len() - trivia_count);
+        if let (Some(first), Some(last)) = (atom.first(), atom.last()) {
+            let range = TextRange::new(first.text_range().start(), last.text_range().end());
+            self.push_markup(range, text);
+        }
+
+        for token in trivia {
+            self.push_token(token);
+        }
+    }
+
+    fn push_text(&mut self, range: TextRange) {
+        match self.segments.last_mut() {
+            Some(ProseSegment::Text(last)) => *last = last.cover(range),
+            _ => self.segments.push(ProseSegment::Text(range)),
+        }
+    }
+
+    fn push_markup(&mut self, range: TextRange, text: &str) {
+        match self.segments.last_mut() {
+            Some(ProseSegment::Markup {
+                range: last,
+                interpret_as,
+            }) => {
+                *last = last.cover(range);
+                interpret_as.push_str(text);
+            }
+            _ => self.segments.push(ProseSegment::Markup {
+                range,
+                interpret_as: text.to_string(),
+            }),
+        }
+    }
+}
+
+fn is_prose(token: &latex::SyntaxToken) -> bool {
+    let parent = token.parent().kind();
+    match token.kind() {
+        latex::WHITESPACE => true,
+        latex::WORD | latex::COMMA => parent == latex::TEXT,
+        latex::L_PAREN | latex::R_PAREN | latex::L_BRACK | latex::R_BRACK => {
+            parent == latex::MIXED_GROUP
+        }
+        latex::EQUALITY_SIGN => parent != latex::KEY_VALUE_PAIR,
+        _ => false,
+    }
+}
+
+fn ends_with_trivia(node: &latex::SyntaxNode) -> bool {
+    node.last_token().map_or(false, |token| {
+        matches!(token.kind(), latex::WHITESPACE | latex::COMMENT)
+    })
+}
+
+fn placeholder(node: &latex::SyntaxNode) -> Option<&'static str> {
+    match node.kind() {
+        latex::FORMULA
+        | latex::CITATION
+        | latex::LABEL_REFERENCE
+        | latex::LABEL_REFERENCE_RANGE
+        | latex::ACRONYM_REFERENCE
+        | latex::GLOSSARY_ENTRY_REFERENCE => Some("X"),
+        _ => None,
+    }
+}
+
+fn is_excluded(node: &latex::SyntaxNode) -> bool {
+    match node.kind() {
+        latex::EQUATION | latex::BRACK_GROUP => true,
+        latex::MIXED_GROUP => node
+            .parent()
+            .map_or(false, |parent| parent.kind() == latex::GENERIC_COMMAND),
+        latex::ENVIRONMENT => latex::Environment::cast(node)
+            .and_then(|environment| environment_name(&environment))
+            .map_or(false, |name| {
+                VERBATIM_ENVIRONMENTS.contains(&name.as_str())
+                    || LANGUAGE_DATA
+                        .math_environments
+                        .iter()
+                        .any(|math_environment| *math_environment == name)
+            }),
+        _ => false,
+    }
+}
+
+fn environment_name(environment: &latex::Environment) -> Option<String> {
+    Some(environment.begin()?.name()?.key()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_prose() {
+        let text = indoc! {r#"
+            \documentclass{article}
+            \begin{document}
+            Foo \emph{bar} $x$ (see \cite{baz}). % qux
+            \begin{align}
+            a = b
+            \end{align}
+            \end{document}"#};
+
+        let segments = extract_prose(&latex::parse(text).root, &ProseOptions::default());
+        let end = segments.last().unwrap().range().end();
+        assert_eq!(usize::from(end), text.len());
+
+        let prose: String = segments
+            .iter()
+            .map(|segment| match segment {
+                ProseSegment::Text(range) => &text[*range],
+                ProseSegment::Markup { interpret_as, .. } => interpret_as.as_str(),
+            })
+            .collect();
+
+        assert_eq!(prose.trim(), "Foo bar X (see X).");
+    }
+
+    fn render_prose(text: &str, options: &ProseOptions) -> String {
+        extract_prose(&latex::parse(text).root, options)
+            .iter()
+            .map(|segment| match segment {
+                ProseSegment::Text(range) => &text[*range],
+                ProseSegment::Markup { interpret_as, .. } => interpret_as.as_str(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exclude_placeholder_text() {
+        let text = "\\begin{document}\nFoo.\n\\lipsum[1-2]\nBar.\n\\end{document}";
+        let options = ProseOptions {
+            exclude_placeholder_text: true,
+        };
+        assert_eq!(render_prose(text, &options).trim(), "Foo.\n\nBar.");
+    }
+
+    #[test]
+    fn test_include_placeholder_text() {
+        let text = "\\begin{document}\nFoo.\n\\blindtext\nBar.\n\\end{document}";
+        let options = ProseOptions {
+            exclude_placeholder_text: false,
+        };
+        assert_eq!(
+            render_prose(text, &options).trim(),
+            format!("Foo.\n{}\nBar.", PLACEHOLDER_SENTENCE)
+        );
+    }
+
+    #[test]
+    fn test_lua_chunks() {
+        let text = indoc! {r#"
+            \begin{document}
+            Foo \directlua{tex.print("Helo wrold")} bar.
+            \begin{luacode}
+            tex.print("Helo wrold")
+            \end{luacode}
+            Baz.
+            \end{document}"#};
+
+        let root = latex::parse_with_options(
+            text,
+            &latex::find_lua_chunks(text),
+            &latex::CustomCommands::default(),
+        )
+        .root;
+
+        let prose: String = extract_prose(&root, &ProseOptions::default())
+            .iter()
+            .map(|segment| match segment {
+                ProseSegment::Text(range) => &text[*range],
+                ProseSegment::Markup { interpret_as, .. } => interpret_as.as_str(),
+            })
+            .collect();
+
+        assert!(prose.contains("Foo"));
+        assert!(prose.contains("bar."));
+        assert!(prose.contains("Baz."));
+        assert!(!prose.contains("wrold"));
+    }
+}
@@ -0,0 +1,182 @@
+use std::sync::Arc;
+
+use cstree::{TextRange, TextSize};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::{
+    syntax::{latex, CstNode},
+    Document, LANGUAGE_DATA,
+};
+
+static QUANTITY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(\d+(?:[.,]\d+)?)[ ~]?(\\%|[A-Za-z]+)").unwrap());
+
+/// Units written in plain text together with their `siunitx` macros (long and short form).
+const UNITS: &[(&str, &str, &str)] = &[
+    ("\\%", "\\percent", "\\percent"),
+    ("mm", "\\milli\\meter", "\\mm"),
+    ("cm", "\\centi\\meter", "\\cm"),
+    ("m", "\\meter", "\\m"),
+    ("km", "\\kilo\\meter", "\\km"),
+    ("mg", "\\milli\\gram", "\\mg"),
+    ("g", "\\gram", "\\g"),
+    ("kg", "\\kilo\\gram", "\\kg"),
+    ("ms", "\\milli\\second", "\\ms"),
+    ("s", "\\second", "\\s"),
+    ("min", "\\minute", "\\minute"),
+    ("h", "\\hour", "\\hour"),
+    ("Hz", "\\hertz", "\\Hz"),
+    ("kHz", "\\kilo\\hertz", "\\kHz"),
+    ("MHz", "\\mega\\hertz", "\\MHz"),
+    ("GHz", "\\giga\\hertz", "\\GHz"),
+    ("mA", "\\milli\\ampere", "\\mA"),
+    ("A", "\\ampere", "\\A"),
+    ("mV", "\\milli\\volt", "\\mV"),
+    ("V", "\\volt", "\\V"),
+    ("kV", "\\kilo\\volt", "\\kV"),
+    ("W", "\\watt", "\\W"),
+    ("kW", "\\kilo\\watt", "\\kW"),
+    ("J", "\\joule", "\\J"),
+    ("kJ", "\\kilo\\joule", "\\kJ"),
+    ("N", "\\newton", "\\N"),
+    ("Pa", "\\pascal", "\\Pa"),
+    ("kPa", "\\kilo\\pascal", "\\kPa"),
+    ("K", "\\kelvin", "\\K"),
+];
+
+/// A number with a unit that is written in plain text like `5 cm` or `10\%`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Quantity {
+    pub range: TextRange,
+    pub number: String,
+    pub unit: &'static str,
+    long_unit: &'static str,
+    short_unit: &'static str,
+}
+
+impl Quantity {
+    /// Returns the quantity written with `\SI` of `siunitx` version 2.
+    pub fn to_si(&self) -> String {
+        format!("\\SI{{{}}}{{{}}}", self.number, self.long_unit)
+    }
+
+    /// Returns the quantity written with `\qty` of `siunitx` version 3.
+    pub fn to_qty(&self) -> String {
+        format!("\\qty{{{}}}{{{}}}", self.number, self.short_unit)
+    }
+}
+
+/// Checks if one of the given documents loads the `siunitx` package.
+pub fn is_siunitx_loaded(documents: &[Arc<Document>]) -> bool {
+    documents
+        .iter()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.explicit_links.iter())
+        .any(|link| link.kind == latex::ExplicitLinkKind::Package && link.stem == "siunitx")
+}
+
+/// Finds the quantities inside of the text of a document.
+/// Numbers inside of formulas and command arguments are ignored.
+pub fn find_quantities(root: &latex::SyntaxNode, text: &str) -> Vec<Quantity> {
+    let mut quantities = Vec::new();
+    for captures in QUANTITY_REGEX.captures_iter(text) {
+        let full = captures.get(0).unwrap();
+        let number = captures.get(1).unwrap();
+        let unit = captures.get(2).unwrap();
+
+        let is_separated_before = !text[..full.start()]
+            .ends_with(|c: char| c.is_alphanumeric() || matches!(c, '.' | ',' | '\\' | '_' | '^'));
+        let is_separated_after = !text[full.end()..].starts_with(char::is_alphanumeric);
+        if !is_separated_before || !is_separated_after {
+            continue;
+        }
+
+        let (unit, long_unit, short_unit) = match UNITS
+            .iter()
+            .copied()
+            .find(|(name, _, _)| *name == unit.as_str())
+        {
+            Some(unit) => unit,
+            None => continue,
+        };
+
+        let start = TextSize::from(full.start() as u32);
+        if !is_plain_text(root, start) {
+            continue;
+        }
+
+        quantities.push(Quantity {
+            range: TextRange::new(start, TextSize::from(full.end() as u32)),
+            number: number.as_str().to_string(),
+            unit,
+            long_unit,
+            short_unit,
+        });
+    }
+    quantities
+}
+
+fn is_plain_text(root: &latex::SyntaxNode, offset: TextSize) -> bool {
+    let token = match root.token_at_offset(offset).right_biased() {
+        Some(token) => token,
+        None => return false,
+    };
+
+    token.kind() == latex::WORD
+        && token.parent().kind() == latex::TEXT
+        && token.parent().ancestors().all(|node| {
+            !matches!(
+                node.kind(),
+                latex::FORMULA
+                    | latex::EQUATION
+                    | latex::GENERIC_COMMAND
+                    | latex::KEY_VALUE_BODY
+                    | latex::BRACK_GROUP
+                    | latex::BRACK_GROUP_KEY_VALUE
+            ) && !is_math_environment(node)
+        })
+}
+
+fn is_math_environment(node: &latex::SyntaxNode) -> bool {
+    latex::Environment::cast(node)
+        .and_then(|environment| environment.begin()?.name()?.key())
+        .map_or(false, |name| {
+            LANGUAGE_DATA
+                .math_environments
+                .iter()
+                .any(|math_environment| *math_environment == name.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(text: &str) -> Vec<(String, String)> {
+        let root = latex::parse(text).root;
+        find_quantities(&root, text)
+            .into_iter()
+            .map(|quantity| (quantity.to_si(), quantity.to_qty()))
+            .collect()
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            find(r#"A width of 5 cm and 10\% of 2.5kg."#),
+            vec![
+                ("\\SI{5}{\\centi\\meter}".into(), "\\qty{5}{\\cm}".into()),
+                ("\\SI{10}{\\percent}".into(), "\\qty{10}{\\percent}".into()),
+                ("\\SI{2.5}{\\kilo\\gram}".into(), "\\qty{2.5}{\\kg}".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignored() {
+        assert!(
+            find(r#"$5 cm$ \vspace{5 cm} \includegraphics[width=5cm]{foo} H2O 5 cats"#).is_empty()
+        );
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use cstree::{TextRange, TextSize};
+use lsp_types::{Position, Range, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::{
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, LineIndexExt, Uri, Workspace,
+};
+
+/// The maximum number of structural locations that are remembered.
+pub const RECENT_EDIT_LIMIT: usize = 20;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StructureKind {
+    Document,
+    Section,
+    Environment,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentEditLocation {
+    pub uri: Url,
+    pub kind: StructureKind,
+
+    /// The title of the section, the name of the environment or the file name of the document.
+    pub name: String,
+
+    /// The range of the section, the environment or the document.
+    pub range: Range,
+
+    /// The position of the last edit inside of the location.
+    pub position: Position,
+}
+
+#[derive(Debug, Clone)]
+struct RecentEdit {
+    uri: Arc<Uri>,
+    offset: TextSize,
+}
+
+/// Remembers the sections and environments that were edited most recently across all documents.
+/// Only the position of the last edit is stored for each location
+/// so that the locations stay valid when their content changes.
+#[derive(Debug, Default)]
+pub struct RecentEdits {
+    edits: Mutex<VecDeque<RecentEdit>>,
+}
+
+impl RecentEdits {
+    /// Records an edit that replaced the given range of the previous version of the document
+    /// with a text of the given length and moves the recorded edits after it accordingly.
+    pub fn record(&self, new_document: &Document, range: TextRange, new_length: TextSize) {
+        let mut edits = self.edits.lock().unwrap();
+        for edit in edits.iter_mut().filter(|edit| edit.uri == new_document.uri) {
+            if edit.offset >= range.end() {
+                edit.offset = edit.offset - range.len() + new_length;
+            } else if edit.offset > range.start() {
+                edit.offset = range.start();
+            }
+        }
+
+        let offset = range.start() + new_length;
+        let (_, _, structure_range) = find_structure(new_document, offset);
+        edits.retain(|edit| {
+            edit.uri != new_document.uri || !structure_range.contains_inclusive(edit.offset)
+        });
+
+        edits.push_front(RecentEdit {
+            uri: Arc::clone(&new_document.uri),
+            offset,
+        });
+        edits.truncate(RECENT_EDIT_LIMIT);
+    }
+
+    /// Returns the edited locations, starting with the most recent one.
+    pub fn locations(&self, workspace: &dyn Workspace) -> Vec<RecentEditLocation> {
+        let mut locations: Vec<RecentEditLocation> = Vec::new();
+        for edit in self.edits.lock().unwrap().iter() {
+            let document = match workspace.get(&edit.uri) {
+                Some(document) => document,
+                None => continue,
+            };
+
+            let offset = clamp_offset(&document.text, edit.offset);
+            let (kind, name, range) = find_structure(&document, offset);
+            let location = RecentEditLocation {
+                uri: document.uri.as_ref().clone().into(),
+                kind,
+                name,
+                range: document.line_index.line_col_lsp_range(range),
+                position: document.line_index.line_col_lsp(offset),
+            };
+
+            if !locations
+                .iter()
+                .any(|other| other.uri == location.uri && other.range == location.range)
+            {
+                locations.push(location);
+            }
+        }
+
+        locations
+    }
+}
+
+/// Finds the innermost section or environment (except for the `document` environment)
+/// that contains the given offset.
+fn find_structure(document: &Document, offset: TextSize) -> (StructureKind, String, TextRange) {
+    let structure = document.data.as_latex().and_then(|data| {
+        data.root
+            .token_at_offset(offset)
+            .left_biased()?
+            .ancestors()
+            .find_map(|node| {
+                if let Some(section) = latex::Section::cast(node) {
+                    let title = section.name()?.content_text()?;
+                    Some((StructureKind::Section, title, section.small_range()))
+                } else if let Some(environment) = latex::Environment::cast(node) {
+                    let name = environment.begin()?.name()?.key()?.to_string();
+                    Some((StructureKind::Environment, name, environment.small_range()))
+                        .filter(|(_, name, _)| name != "document")
+                } else {
+                    None
+                }
+            })
+    });
+
+    structure.unwrap_or_else(|| {
+        let name = document
+            .uri
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default()
+            .to_string();
+
+        let range = TextRange::up_to(TextSize::of(document.text.as_str()));
+        (StructureKind::Document, name, range)
+    })
+}
+
+/// Moves the offset to a valid position in case the document was changed outside of the client.
+fn clamp_offset(text: &str, offset: TextSize) -> TextSize {
+    let mut offset = usize::from(offset).min(text.len());
+    while !text.is_char_boundary(offset) {
+        offset -= 1;
+    }
+
+    (offset as u32).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DocumentLanguage, ServerContext};
+
+    use super::*;
+
+    fn parse(text: &str) -> Document {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap();
+        Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        )
+    }
+
+    fn offsets(recent_edits: &RecentEdits) -> Vec<u32> {
+        recent_edits
+            .edits
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|edit| edit.offset.into())
+            .collect()
+    }
+
+    #[test]
+    fn test_find_structure() {
+        let document = parse("\\section{Foo}\n\\begin{proof}\nbar\n\\end{proof}");
+        let (kind, name, _) = find_structure(&document, 30.into());
+        assert_eq!(kind, StructureKind::Environment);
+        assert_eq!(name, "proof");
+
+        let (kind, name, _) = find_structure(&document, 10.into());
+        assert_eq!(kind, StructureKind::Section);
+        assert_eq!(name, "Foo");
+    }
+
+    #[test]
+    fn test_record() {
+        let recent_edits = RecentEdits::default();
+
+        let document = parse("\\section{Foo}\nbar\n\\section{Baz}\nquux");
+        recent_edits.record(&document, TextRange::empty(33.into()), 1.into());
+        recent_edits.record(&document, TextRange::empty(16.into()), 1.into());
+        assert_eq!(offsets(&recent_edits), vec![17, 35]);
+
+        let document = parse("\\section{Foo}\nbaar\n\\section{Baz}\nquux");
+        recent_edits.record(&document, TextRange::empty(15.into()), 1.into());
+        assert_eq!(offsets(&recent_edits), vec![16, 36]);
+    }
+}
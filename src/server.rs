@@ -1,25 +1,30 @@
 use std::{
+    ffi::OsStr,
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, PoisonError},
 };
 
 use anyhow::Result;
 use cancellation::{CancellationToken, CancellationTokenSource};
 use crossbeam_channel::Sender;
+use cstree::TextSize;
 use log::{error, info, warn};
-use lsp_server::{Connection, ErrorCode, Message, RequestId};
+use lsp_server::{Connection, ErrorCode, Message, RequestId, ResponseError};
 use lsp_types::{
     notification::{
-        Cancel, DidChangeConfiguration, DidChangeTextDocument, DidOpenTextDocument,
-        DidSaveTextDocument, PublishDiagnostics,
+        Cancel, DidChangeConfiguration, DidChangeTextDocument, DidOpenTextDocument, DidRenameFiles,
+        DidSaveTextDocument, PublishDiagnostics, ShowMessage, WorkDoneProgressCancel,
     },
     request::{
-        DocumentLinkRequest, FoldingRangeRequest, Formatting, GotoDefinition, PrepareRenameRequest,
-        References, Rename, SemanticTokensRangeRequest,
+        ApplyWorkspaceEdit, CodeActionRequest, ColorPresentationRequest, DocumentColor,
+        DocumentLinkRequest, ExecuteCommand, FoldingRangeRequest, Formatting, GotoDefinition,
+        PrepareRenameRequest, References, Rename, SemanticTokensRangeRequest, WillCreateFiles,
+        WillRenameFiles,
     },
     *,
 };
 use notification::DidCloseTextDocument;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use request::{
     Completion, DocumentHighlightRequest, DocumentSymbolRequest, HoverRequest,
     ResolveCompletionItem, WorkspaceSymbol,
@@ -28,21 +33,57 @@ use serde::Serialize;
 use threadpool::ThreadPool;
 
 use crate::{
-    client::send_notification,
+    client::{send_notification, send_request},
     component_db::COMPONENT_DATABASE,
-    config::{pull_config, push_config, register_config_capability},
+    config::{
+        project_config_path, pull_config, push_config, register_config_capability, reload_config,
+        PROJECT_CONFIG_FILE,
+    },
     create_workspace_full,
-    diagnostics::{DiagnosticsDebouncer, DiagnosticsManager, DiagnosticsMessage},
+    diagnostics::{
+        check_language_tool, DiagnosticsDebouncer, DiagnosticsManager, DiagnosticsMessage,
+    },
     dispatch::{NotificationDispatcher, RequestDispatcher},
-    distro::Distribution,
+    distro::{Distribution, DistroInfo},
     features::{
-        find_all_references, find_document_highlights, find_document_links, find_document_symbols,
-        find_foldings, find_hover, find_workspace_symbols, format_source_code, goto_definition,
-        prepare_rename_all, rename_all, BuildEngine, BuildParams, BuildResult, BuildStatus,
-        FeatureRequest, ForwardSearchResult,
+        analyze_citations, build_log_file_uri, check_arxiv, convert_paste, convert_table,
+        create_figure_snippet, create_files, create_matrix_snippet, create_new_document,
+        create_usage_report, detex, enable_shell_escape, export_bibliography, export_outline,
+        extract_embedded_documents, extract_environment, extract_shared_preamble,
+        find_all_references, find_code_actions, find_color_presentations, find_document_colors,
+        find_document_highlights, find_document_links, find_document_symbols,
+        find_embedded_documents, find_environments, find_foldings, find_hover, find_inlay_hints,
+        find_language_regions, find_matching_pair, find_next_section, find_output_status,
+        find_project_todos, find_sections, find_shared_preambles, find_viewer_setups,
+        find_workspace_symbols, flatten_project, fold_all_comments, fold_all_environments,
+        format_source_code, goto_definition, locate_in_pdf, normalize_labels, prepare_rename_all,
+        preview_environment, rename_all, rename_files, show_documentation, structured_search,
+        template_directory, BuildEngine, BuildLogDocument, BuildLogParams, BuildParams,
+        BuildResult, BuildStatus, CheckArxivParams, CitationAnalysis, CitationAnalysisParams,
+        ConvertPasteParams, ConvertTableParams, DetexParams, DetexResult,
+        DidChangeEmbeddedDocumentsParams, EmbeddedDocument, EmbeddedDocumentsParams,
+        EnableShellEscapeParams, EnvironmentContent, EnvironmentLocation, ExportBibliographyParams,
+        ExportOutlineParams, ExtractPreambleParams, FeatureRequest, FlattenProjectParams,
+        FoldAllParams, ForwardSearchResult, InlayHint, InlayHintParams, InsertFigureParams,
+        InsertMatrixParams, LanguageRegions, LanguageRegionsParams, LocateInPdfParams,
+        MathPreviewer, NewDocumentParams, NextSectionParams, NormalizeLabelsParams, OutputStatus,
+        OutputStatusParams, PdfLocation, PreviewEnvironmentParams, PreviewMathParams,
+        SectionLocation, SectionsParams, SharedPreamble, SharedPreamblesParams, TodoFile,
+        TodosParams, UsageReportParams, ViewerSetup, ViewerSetupParams, CHECK_ARXIV_COMMAND,
+        CONVERT_PASTE_COMMAND, CONVERT_TABLE_COMMAND, ENABLE_SHELL_ESCAPE_COMMAND,
+        EXPORT_BIBLIOGRAPHY_COMMAND, EXPORT_OUTLINE_COMMAND, EXTRACT_PREAMBLE_COMMAND,
+        FLATTEN_PROJECT_COMMAND, FOLD_ALL_COMMENTS_COMMAND, FOLD_ALL_ENVIRONMENTS_COMMAND,
+        INSERT_FIGURE_COMMAND, INSERT_MATRIX_COMMAND, NEW_DOCUMENT_COMMAND,
+        NORMALIZE_LABELS_COMMAND, PREVIEW_ENVIRONMENT_COMMAND, SHOW_DOCUMENTATION_COMMAND,
+        SYMBOL_LIMIT, USAGE_REPORT_COMMAND,
     },
+    internal_state::{catch_panic, collect_internal_state, InternalState},
+    progress::ProgressReporter,
+    recent_edits::{RecentEditLocation, RecentEdits},
     req_queue::{IncomingData, ReqQueue},
-    Document, DocumentLanguage, LineIndexExt, ServerContext, Uri, Workspace, WorkspaceSource,
+    workspace_stats::{collect_workspace_stats, WorkspaceStats},
+    ClientCapabilitiesExt, Document, DocumentLanguage, LineIndexExt, ServerContext, Uri, Workspace,
+    WorkspaceSource,
 };
 
 pub struct Server {
@@ -52,9 +93,13 @@ pub struct Server {
     workspace: Arc<dyn Workspace>,
     static_debouncer: DiagnosticsDebouncer,
     chktex_debouncer: DiagnosticsDebouncer,
+    language_tool_debouncer: DiagnosticsDebouncer,
     pool: ThreadPool,
     load_resolver: bool,
     build_engine: Arc<BuildEngine>,
+    math_previewer: Arc<MathPreviewer>,
+    recent_edits: RecentEdits,
+    config_watcher: Option<RecommendedWatcher>,
 }
 
 impl Server {
@@ -72,7 +117,10 @@ impl Server {
             create_static_debouncer(Arc::clone(&diag_manager), &connection, Arc::clone(&context));
 
         let chktex_debouncer =
-            create_chktex_debouncer(diag_manager, &connection, Arc::clone(&context));
+            create_chktex_debouncer(Arc::clone(&diag_manager), &connection, Arc::clone(&context));
+
+        let language_tool_debouncer =
+            create_language_tool_debouncer(diag_manager, &connection, Arc::clone(&context));
 
         Ok(Self {
             connection,
@@ -81,9 +129,13 @@ impl Server {
             workspace,
             static_debouncer,
             chktex_debouncer,
+            language_tool_debouncer,
             pool: threadpool::Builder::new().build(),
             load_resolver,
             build_engine: Arc::default(),
+            math_previewer: Arc::default(),
+            recent_edits: RecentEdits::default(),
+            config_watcher: None,
         })
     }
 
@@ -105,6 +157,29 @@ impl Server {
                 work_done_progress_options: WorkDoneProgressOptions::default(),
             }),
             folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+            color_provider: Some(ColorProviderCapability::Simple(true)),
+            execute_command_provider: Some(ExecuteCommandOptions {
+                commands: vec![
+                    SHOW_DOCUMENTATION_COMMAND.into(),
+                    CONVERT_PASTE_COMMAND.into(),
+                    NORMALIZE_LABELS_COMMAND.into(),
+                    EXTRACT_PREAMBLE_COMMAND.into(),
+                    USAGE_REPORT_COMMAND.into(),
+                    INSERT_MATRIX_COMMAND.into(),
+                    INSERT_FIGURE_COMMAND.into(),
+                    CONVERT_TABLE_COMMAND.into(),
+                    EXPORT_BIBLIOGRAPHY_COMMAND.into(),
+                    FLATTEN_PROJECT_COMMAND.into(),
+                    CHECK_ARXIV_COMMAND.into(),
+                    NEW_DOCUMENT_COMMAND.into(),
+                    PREVIEW_ENVIRONMENT_COMMAND.into(),
+                    EXPORT_OUTLINE_COMMAND.into(),
+                    FOLD_ALL_ENVIRONMENTS_COMMAND.into(),
+                    FOLD_ALL_COMMENTS_COMMAND.into(),
+                    ENABLE_SHELL_ESCAPE_COMMAND.into(),
+                ],
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
             definition_provider: Some(OneOf::Left(true)),
             references_provider: Some(OneOf::Left(true)),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
@@ -129,6 +204,7 @@ impl Server {
             })),
             document_highlight_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
             #[cfg(feature = "semantic")]
             semantic_tokens_provider: Some(
                 SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
@@ -141,6 +217,17 @@ impl Server {
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
             ),
+            workspace: Some(WorkspaceServerCapabilities {
+                workspace_folders: None,
+                file_operations: Some(WorkspaceFileOperationsServerCapabilities {
+                    did_create: None,
+                    will_create: Some(file_operation_options("**/*.tex")),
+                    did_rename: Some(file_operation_options("**/*")),
+                    will_rename: Some(file_operation_options("**/*")),
+                    did_delete: None,
+                    will_delete: None,
+                }),
+            }),
             ..ServerCapabilities::default()
         }
     }
@@ -163,11 +250,27 @@ impl Server {
             .initialize_finish(id, serde_json::to_value(result)?)?;
 
         let cx = Arc::clone(&self.context);
+        let req_queue = Arc::clone(&self.req_queue);
+        let sender = self.connection.sender.clone();
         if self.load_resolver {
-            self.pool.execute(move || {
+            self.execute_job("detect_distribution", move || {
+                let progress_reporter = ProgressReporter::begin(
+                    &cx,
+                    &req_queue,
+                    &sender,
+                    "Detecting TeX distribution",
+                    None,
+                    false,
+                );
                 let distro = Distribution::detect();
                 info!("Detected distribution: {}", distro.kind);
+                *cx.distro_kind.lock().unwrap() = distro.kind;
                 *cx.resolver.lock().unwrap() = distro.resolver;
+                drop(progress_reporter);
+
+                let build_executable = cx.options.read().unwrap().build.executable();
+                let info = DistroInfo::new(distro.kind, distro.version, &build_executable);
+                report_missing_tools(&sender, &info, &build_executable).unwrap();
             });
         }
 
@@ -177,25 +280,77 @@ impl Server {
         let sender = self.connection.sender.clone();
         let context = Arc::clone(&self.context);
         let workspace = Arc::clone(&self.workspace);
-        self.pool.execute(move || {
+        self.execute_job("index_workspace", move || {
+            reload_config(&sender, &context);
             register_config_capability(&req_queue, &sender, &context.client_capabilities);
-            pull_config(
+            pull_config(&req_queue, &sender, &context);
+
+            reindex_workspace(
+                &context,
                 &req_queue,
                 &sender,
-                &context.options,
-                &context.client_capabilities.lock().unwrap(),
+                workspace.as_ref(),
+                "Indexing workspace",
             );
+        });
+        self.watch_project_config();
+        Ok(())
+    }
 
-            for document in workspace.documents() {
-                workspace.open(
-                    Arc::clone(&document.uri),
-                    document.text.clone(),
-                    document.language(),
-                    WorkspaceSource::Client,
+    fn watch_project_config(&mut self) {
+        let path = project_config_path(&self.context);
+        let directory = match path.parent() {
+            Some(directory) => directory.to_path_buf(),
+            None => return,
+        };
+
+        let req_queue = Arc::clone(&self.req_queue);
+        let sender = self.connection.sender.clone();
+        let context = Arc::clone(&self.context);
+        let workspace = Arc::clone(&self.workspace);
+        let watcher = Watcher::new_immediate(move |event: notify::Result<notify::Event>| {
+            let event = match event {
+                Ok(event) if !event.kind.is_access() => event,
+                _ => return,
+            };
+
+            if !event
+                .paths
+                .iter()
+                .any(|path| path.file_name() == Some(OsStr::new(PROJECT_CONFIG_FILE)))
+            {
+                return;
+            }
+
+            info!("Reloading {}", PROJECT_CONFIG_FILE);
+            let old_options = context.options.read().unwrap().latex.clone();
+            reload_config(&sender, &context);
+            if context.options.read().unwrap().latex != old_options {
+                reindex_workspace(
+                    &context,
+                    &req_queue,
+                    &sender,
+                    workspace.as_ref(),
+                    "Reindexing workspace",
                 );
             }
         });
-        Ok(())
+
+        let mut watcher: RecommendedWatcher = match watcher {
+            Ok(watcher) => watcher,
+            Err(why) => {
+                warn!(
+                    "Failed to create watcher for {}: {}",
+                    PROJECT_CONFIG_FILE, why
+                );
+                return;
+            }
+        };
+
+        match watcher.watch(&directory, RecursiveMode::NonRecursive) {
+            Ok(()) => self.config_watcher = Some(watcher),
+            Err(why) => warn!("Failed to watch {}: {}", PROJECT_CONFIG_FILE, why),
+        }
     }
 
     fn register_diagnostics_handler(&mut self) {
@@ -235,7 +390,30 @@ impl Server {
     }
 
     fn did_change_configuration(&self, params: DidChangeConfigurationParams) -> Result<()> {
-        push_config(&self.context.options, params.settings);
+        let old_options = self.context.options.read().unwrap().latex.clone();
+        push_config(&self.connection.sender, &self.context, params.settings);
+        if self.context.options.read().unwrap().latex == old_options {
+            return Ok(());
+        }
+
+        let context = Arc::clone(&self.context);
+        let req_queue = Arc::clone(&self.req_queue);
+        let sender = self.connection.sender.clone();
+        let workspace = Arc::clone(&self.workspace);
+        self.execute_job("reindex_workspace", move || {
+            reindex_workspace(
+                &context,
+                &req_queue,
+                &sender,
+                workspace.as_ref(),
+                "Reindexing workspace",
+            );
+        });
+        Ok(())
+    }
+
+    fn cancel_progress(&self, params: WorkDoneProgressCancelParams) -> Result<()> {
+        self.context.progress_tokens.cancel(&params.token);
         Ok(())
     }
 
@@ -249,6 +427,8 @@ impl Server {
             WorkspaceSource::Client,
         );
 
+        self.update_embedded_documents(None, &document)?;
+
         let should_lint = { self.context.options.read().unwrap().chktex.on_open_and_save };
         if let Some(document) = self
             .workspace
@@ -262,6 +442,29 @@ impl Server {
                     document,
                 })?;
         };
+
+        self.check_language_tool(document.uri.as_ref())?;
+        Ok(())
+    }
+
+    fn check_language_tool(&self, uri: &Uri) -> Result<()> {
+        let is_enabled = {
+            self.context
+                .options
+                .read()
+                .unwrap()
+                .language_tool
+                .url
+                .is_some()
+        };
+        if let Some(document) = self.workspace.get(uri).filter(|_| is_enabled) {
+            self.language_tool_debouncer
+                .sender
+                .send(DiagnosticsMessage::Analyze {
+                    workspace: Arc::clone(&self.workspace),
+                    document,
+                })?;
+        }
         Ok(())
     }
 
@@ -278,12 +481,20 @@ impl Server {
             .unwrap_or(DocumentLanguage::Latex);
 
         let new_document = match &old_document {
-            Some(old_document) => params
-                .content_changes
-                .into_iter()
-                .fold(Arc::clone(&old_document), |old_document, change| {
-                    self.merge_text_changes(&old_document, language, change)
-                }),
+            Some(old_document) => params.content_changes.into_iter().fold(
+                Arc::clone(&old_document),
+                |old_document, change| {
+                    let range = change
+                        .range
+                        .map(|range| old_document.line_index.offset_lsp_range(range));
+                    let new_length = TextSize::of(change.text.as_str());
+                    let new_document = self.merge_text_changes(&old_document, language, change);
+                    if let Some(range) = range {
+                        self.recent_edits.record(&new_document, range, new_length);
+                    }
+                    new_document
+                },
+            ),
             None => self.workspace.open(
                 Arc::clone(&uri),
                 params.content_changes.pop().unwrap().text,
@@ -304,6 +515,8 @@ impl Server {
             .positions_by_uri
             .insert(Arc::clone(&uri), Position::new(line, 0));
 
+        self.update_embedded_documents(old_document.as_deref(), &new_document)?;
+
         let should_lint = { self.context.options.read().unwrap().chktex.on_edit };
         if should_lint {
             self.chktex_debouncer
@@ -317,6 +530,64 @@ impl Server {
         Ok(())
     }
 
+    fn update_embedded_documents(
+        &self,
+        old_document: Option<&Document>,
+        new_document: &Document,
+    ) -> Result<()> {
+        if !self
+            .context
+            .client_capabilities
+            .lock()
+            .unwrap()
+            .has_embedded_documents_support()
+        {
+            return Ok(());
+        }
+
+        let old_documents = old_document
+            .map(extract_embedded_documents)
+            .unwrap_or_default();
+        let new_documents = extract_embedded_documents(new_document);
+        if old_documents != new_documents {
+            send_notification::<DidChangeEmbeddedDocumentsNotification>(
+                &self.connection.sender,
+                DidChangeEmbeddedDocumentsParams {
+                    text_document: TextDocumentIdentifier::new(
+                        new_document.uri.as_ref().clone().into(),
+                    ),
+                    documents: new_documents,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn did_change_cursor_position(&self, params: TextDocumentPositionParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.into());
+        let position = params.position;
+        self.build_engine
+            .positions_by_uri
+            .insert(Arc::clone(&uri), position);
+
+        if let Some(document) = self.workspace.get(&uri) {
+            let sender = self.connection.sender.clone();
+            let cx = Arc::clone(&self.context);
+            let math_previewer = Arc::clone(&self.math_previewer);
+            self.execute_job("preview_math", move || {
+                let options = { cx.options.read().unwrap().math_preview.clone() };
+                if let Some(params) = math_previewer.preview(&document, position, &options) {
+                    if let Err(why) = send_notification::<PreviewMathNotification>(&sender, params)
+                    {
+                        warn!("Failed to send the math preview: {}", why);
+                    }
+                }
+            });
+        }
+        Ok(())
+    }
+
     fn merge_text_changes(
         &self,
         old_document: &Document,
@@ -363,7 +634,7 @@ impl Server {
             let lsp_sender = self.connection.sender.clone();
             let req_queue = Arc::clone(&self.req_queue);
             let build_engine = Arc::clone(&self.build_engine);
-            self.pool.execute(move || {
+            self.execute_job("build_on_save", move || {
                 build_engine
                     .build(request, CancellationToken::none(), &req_queue, &lsp_sender)
                     .unwrap_or_else(|why| {
@@ -384,6 +655,8 @@ impl Server {
                     document,
                 })?;
         };
+
+        self.check_language_tool(&uri)?;
         Ok(())
     }
 
@@ -397,9 +670,8 @@ impl Server {
         let req_queue = Arc::clone(&self.req_queue);
         let sender = self.connection.sender.clone();
         let cx = Arc::clone(&self.context);
-        self.pool.execute(move || {
-            let client_capabilities = &cx.client_capabilities.lock().unwrap().clone();
-            pull_config(&req_queue, &sender, &cx.options, &client_capabilities);
+        self.execute_job("pull_config", move || {
+            pull_config(&req_queue, &sender, &cx);
         });
 
         Some(FeatureRequest {
@@ -420,6 +692,56 @@ impl Server {
         Ok(())
     }
 
+    /// Runs a request handler on the thread pool and responds with an error if it panics.
+    fn execute_request<R, F>(
+        &self,
+        id: RequestId,
+        handler: &'static str,
+        params: serde_json::Value,
+        document: Option<Arc<Document>>,
+        token: &Arc<CancellationToken>,
+        job: F,
+    ) where
+        R: Serialize,
+        F: FnOnce(&CancellationToken) -> Result<R, ResponseError> + Send + 'static,
+    {
+        let sender = self.connection.sender.clone();
+        let context = Arc::clone(&self.context);
+        let token = Arc::clone(token);
+        self.pool.execute(move || {
+            let result = catch_panic(&context, handler, document.as_deref(), params, || {
+                job(&token)
+            });
+
+            let response = match result {
+                Ok(_) if token.is_canceled() => cancel_response(id),
+                Ok(Ok(result)) => lsp_server::Response::new_ok(id, result),
+                Ok(Err(error)) => lsp_server::Response {
+                    id,
+                    result: None,
+                    error: Some(error),
+                },
+                Err(report) => lsp_server::Response::new_err(
+                    id,
+                    ErrorCode::InternalError as i32,
+                    format!("texlab panicked: {}", report.message),
+                ),
+            };
+
+            if let Err(why) = sender.send(response.into()) {
+                warn!("Failed to send response: {}", why);
+            }
+        });
+    }
+
+    /// Runs a background job on the thread pool and records a panic report if it panics.
+    fn execute_job(&self, handler: &'static str, job: impl FnOnce() + Send + 'static) {
+        let context = Arc::clone(&self.context);
+        self.pool.execute(move || {
+            let _ = catch_panic(&context, handler, None, serde_json::Value::Null, job);
+        });
+    }
+
     fn handle_feature_request<P, R, H>(
         &self,
         id: RequestId,
@@ -429,24 +751,22 @@ impl Server {
         handler: H,
     ) -> Result<()>
     where
-        P: Send + 'static,
+        P: Serialize + Send + 'static,
         R: Serialize,
         H: FnOnce(FeatureRequest<P>, &CancellationToken) -> R + Send + 'static,
     {
         match self.feature_request(uri, params) {
             Some(req) => {
-                let sender = self.connection.sender.clone();
-                let token = Arc::clone(token);
-                self.pool.execute(move || {
-                    let result = handler(req, &token);
-                    if token.is_canceled() {
-                        sender.send(cancel_response(id).into()).unwrap();
-                    } else {
-                        sender
-                            .send(lsp_server::Response::new_ok(id, result).into())
-                            .unwrap();
-                    }
-                });
+                let document = Arc::clone(&req.subset.documents[0]);
+                let params = serde_json::to_value(&req.params).unwrap_or_default();
+                self.execute_request(
+                    id,
+                    std::any::type_name::<H>(),
+                    params,
+                    Some(document),
+                    token,
+                    move |token| Ok(handler(req, token)),
+                );
             }
             None => {
                 self.send_feature_error(id)?;
@@ -461,7 +781,10 @@ impl Server {
         params: DocumentLinkParams,
         token: &Arc<CancellationToken>,
     ) -> Result<()> {
-        let uri = Arc::new(params.text_document.uri.clone().into());
+        let uri = Arc::new(
+            build_log_file_uri(&params.text_document.uri)
+                .unwrap_or_else(|| params.text_document.uri.clone().into()),
+        );
         self.handle_feature_request(id, params, uri, token, find_document_links)?;
         Ok(())
     }
@@ -477,28 +800,96 @@ impl Server {
         Ok(())
     }
 
+    fn structured_search(
+        &self,
+        id: RequestId,
+        params: StructuredSearchParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let workspace = Arc::clone(&self.workspace);
+        let json = serde_json::to_value(&params).unwrap_or_default();
+        self.execute_request(id, "structured_search", json, None, token, move |token| {
+            Ok(structured_search(workspace.as_ref(), &params, token))
+        });
+        Ok(())
+    }
+
     fn workspace_symbols(
         &self,
         id: RequestId,
         params: WorkspaceSymbolParams,
         token: &Arc<CancellationToken>,
     ) -> Result<()> {
-        let sender = self.connection.sender.clone();
         let workspace = Arc::clone(&self.workspace);
-        let token = Arc::clone(token);
-        self.pool.execute(move || {
-            let result = find_workspace_symbols(workspace.as_ref(), &params, &token);
-            if token.is_canceled() {
-                sender.send(cancel_response(id).into()).unwrap();
-            } else {
-                sender
-                    .send(lsp_server::Response::new_ok(id, result).into())
-                    .unwrap();
-            }
+        let limit = self
+            .context
+            .options
+            .read()
+            .unwrap()
+            .symbol_limit
+            .unwrap_or(SYMBOL_LIMIT);
+
+        let json = serde_json::to_value(&params).unwrap_or_default();
+        self.execute_request(id, "workspace_symbols", json, None, token, move |token| {
+            Ok(find_workspace_symbols(
+                workspace.as_ref(),
+                &params,
+                limit,
+                token,
+            ))
+        });
+        Ok(())
+    }
+
+    fn will_create_files(
+        &self,
+        id: RequestId,
+        params: CreateFilesParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let workspace = Arc::clone(&self.workspace);
+        let json = serde_json::to_value(&params).unwrap_or_default();
+        self.execute_request(id, "will_create_files", json, None, token, move |token| {
+            let template_directory = template_directory();
+            Ok(create_files(
+                workspace.as_ref(),
+                &params,
+                template_directory.as_deref(),
+                token,
+            ))
+        });
+        Ok(())
+    }
+
+    fn will_rename_files(
+        &self,
+        id: RequestId,
+        params: RenameFilesParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let context = Arc::clone(&self.context);
+        let workspace = Arc::clone(&self.workspace);
+        let json = serde_json::to_value(&params).unwrap_or_default();
+        self.execute_request(id, "will_rename_files", json, None, token, move |token| {
+            Ok(rename_files(&context, workspace.as_ref(), &params, token))
         });
         Ok(())
     }
 
+    fn did_rename_files(&self, params: RenameFilesParams) -> Result<()> {
+        for file in params.files {
+            let path = match Url::parse(&file.new_uri).map(|uri| uri.to_file_path()) {
+                Ok(Ok(path)) if path.is_file() => path,
+                _ => continue,
+            };
+
+            if let Err(why) = self.workspace.reload(path) {
+                warn!("Failed to reload renamed file: {}", why);
+            }
+        }
+        Ok(())
+    }
+
     #[cfg(feature = "completion")]
     fn completion(
         &self,
@@ -530,10 +921,9 @@ impl Server {
         mut item: CompletionItem,
         token: &Arc<CancellationToken>,
     ) -> Result<()> {
-        let sender = self.connection.sender.clone();
-        let token = Arc::clone(token);
         let workspace = Arc::clone(&self.workspace);
-        self.pool.execute(move || {
+        let json = serde_json::to_value(&item).unwrap_or_default();
+        self.execute_request(id, "completion_resolve", json, None, token, move |_| {
             match serde_json::from_value(item.data.clone().unwrap()).unwrap() {
                 crate::features::CompletionItemData::Package
                 | crate::features::CompletionItemData::Class => {
@@ -545,7 +935,7 @@ impl Server {
                 crate::features::CompletionItemData::Citation { uri, key } => {
                     if let Some(document) = workspace.get(&uri) {
                         if let Some(data) = document.data.as_bibtex() {
-                            let markup = crate::citation::render_citation(&data.root, &key);
+                            let markup = crate::citation::render_citation_preview(&data.root, &key);
                             item.documentation = markup.map(Documentation::MarkupContent);
                         }
                     }
@@ -554,13 +944,7 @@ impl Server {
             };
 
             drop(workspace);
-            if token.is_canceled() {
-                sender.send(cancel_response(id).into()).unwrap();
-            } else {
-                sender
-                    .send(lsp_server::Response::new_ok(id, item).into())
-                    .unwrap();
-            }
+            Ok(item)
         });
         Ok(())
     }
@@ -576,6 +960,28 @@ impl Server {
         Ok(())
     }
 
+    fn document_color(
+        &self,
+        id: RequestId,
+        params: DocumentColorParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_document_colors)?;
+        Ok(())
+    }
+
+    fn color_presentation(
+        &self,
+        id: RequestId,
+        params: ColorPresentationParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_color_presentations)?;
+        Ok(())
+    }
+
     fn references(
         &self,
         id: RequestId,
@@ -693,6 +1099,17 @@ impl Server {
         Ok(())
     }
 
+    fn code_action(
+        &self,
+        id: RequestId,
+        params: CodeActionParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_code_actions)?;
+        Ok(())
+    }
+
     #[cfg(feature = "semantic")]
     fn semantic_tokens_range(
         &self,
@@ -761,123 +1178,702 @@ impl Server {
         Ok(())
     }
 
-    fn process_messages(&self) -> Result<()> {
-        for msg in &self.connection.receiver {
-            match msg {
-                Message::Request(request) => {
-                    if self.connection.handle_shutdown(&request)? {
-                        return Ok(());
-                    }
+    fn environments(
+        &self,
+        id: RequestId,
+        params: TextDocumentPositionParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_environments)?;
+        Ok(())
+    }
 
-                    let token = self.register_incoming_request(request.id.clone());
-                    if let Some(response) = RequestDispatcher::new(request)
-                        .on::<DocumentLinkRequest, _>(|id, params| {
-                            self.document_link(id, params, &token)
-                        })?
-                        .on::<FoldingRangeRequest, _>(|id, params| {
-                            self.folding_range(id, params, &token)
-                        })?
-                        .on::<References, _>(|id, params| self.references(id, params, &token))?
-                        .on::<HoverRequest, _>(|id, params| self.hover(id, params, &token))?
-                        .on::<DocumentSymbolRequest, _>(|id, params| {
-                            self.document_symbols(id, params, &token)
-                        })?
-                        .on::<WorkspaceSymbol, _>(|id, params| {
-                            self.workspace_symbols(id, params, &token)
-                        })?
-                        .on::<Completion, _>(|id, params| {
-                            #[cfg(feature = "completion")]
-                            self.completion(id, params, &token)?;
-                            Ok(())
-                        })?
-                        .on::<ResolveCompletionItem, _>(|id, params| {
-                            #[cfg(feature = "completion")]
-                            self.completion_resolve(id, params, &token)?;
-                            Ok(())
-                        })?
-                        .on::<GotoDefinition, _>(|id, params| {
-                            self.goto_definition(id, params, &token)
-                        })?
-                        .on::<PrepareRenameRequest, _>(|id, params| {
-                            self.prepare_rename(id, params, &token)
-                        })?
-                        .on::<Rename, _>(|id, params| self.rename(id, params, &token))?
-                        .on::<DocumentHighlightRequest, _>(|id, params| {
-                            self.document_highlight(id, params, &token)
-                        })?
-                        .on::<Formatting, _>(|id, params| self.formatting(id, params, &token))?
-                        .on::<BuildRequest, _>(|id, params| self.build(id, params, &token))?
-                        .on::<ForwardSearchRequest, _>(|id, params| {
-                            self.forward_search(id, params, &token)
-                        })?
-                        .on::<SemanticTokensRangeRequest, _>(|id, params| {
-                            self.semantic_tokens_range(id, params, &token)
-                        })?
-                        .default()
-                    {
-                        self.connection.sender.send(response.into())?;
-                    }
-                }
-                Message::Notification(notification) => {
-                    NotificationDispatcher::new(notification)
-                        .on::<Cancel, _>(|params| self.cancel(params))?
-                        .on::<DidChangeConfiguration, _>(|params| {
-                            self.did_change_configuration(params)
-                        })?
-                        .on::<DidOpenTextDocument, _>(|params| self.did_open(params))?
-                        .on::<DidChangeTextDocument, _>(|params| self.did_change(params))?
-                        .on::<DidSaveTextDocument, _>(|params| self.did_save(params))?
-                        .on::<DidCloseTextDocument, _>(|params| self.did_close(params))?
-                        .default();
-                }
-                Message::Response(response) => {
-                    let mut req_queue = self.req_queue.lock().unwrap();
-                    let data = req_queue.outgoing.complete(response.id);
-                    let result = match response.error {
-                        Some(error) => Err(error),
-                        None => Ok(response.result.unwrap_or_default()),
-                    };
-                    data.sender.send(result)?;
-                }
-            }
-        }
+    fn extract_environment(
+        &self,
+        id: RequestId,
+        params: TextDocumentPositionParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, extract_environment)?;
         Ok(())
     }
 
-    pub fn run(mut self) -> Result<()> {
-        self.initialize()?;
-        self.process_messages()?;
-        drop(self.static_debouncer);
-        drop(self.chktex_debouncer);
-        self.pool.join();
+    fn inlay_hints(
+        &self,
+        id: RequestId,
+        params: InlayHintParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_inlay_hints)?;
         Ok(())
     }
-}
 
-fn create_static_debouncer(
-    manager: Arc<Mutex<DiagnosticsManager>>,
-    conn: &Connection,
-    context: Arc<ServerContext>,
-) -> DiagnosticsDebouncer {
-    let sender = conn.sender.clone();
-    DiagnosticsDebouncer::launch(context, move |workspace, document| {
-        let mut manager = manager.lock().unwrap();
-        manager.update_static(workspace.as_ref(), Arc::clone(&document.uri));
-        if let Err(why) = publish_diagnostics(&sender, workspace.as_ref(), &manager) {
-            warn!("Failed to publish diagnostics: {}", why);
-        }
-    })
-}
+    fn sections(
+        &self,
+        id: RequestId,
+        params: SectionsParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_sections)?;
+        Ok(())
+    }
 
-fn create_chktex_debouncer(
-    manager: Arc<Mutex<DiagnosticsManager>>,
-    conn: &Connection,
-    context: Arc<ServerContext>,
-) -> DiagnosticsDebouncer {
-    let sender = conn.sender.clone();
-    DiagnosticsDebouncer::launch(Arc::clone(&context), move |workspace, document| {
-        let options = { context.options.read().unwrap().clone() };
-        let mut manager = manager.lock().unwrap();
+    fn citation_analysis(
+        &self,
+        id: RequestId,
+        params: CitationAnalysisParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, analyze_citations)?;
+        Ok(())
+    }
+
+    fn todos(
+        &self,
+        id: RequestId,
+        params: TodosParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_project_todos)?;
+        Ok(())
+    }
+
+    fn build_log(
+        &self,
+        id: RequestId,
+        params: BuildLogParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(
+            build_log_file_uri(&params.text_document.uri)
+                .unwrap_or_else(|| params.text_document.uri.clone().into()),
+        );
+        self.handle_feature_request(id, params, uri, token, open_build_log)?;
+        Ok(())
+    }
+
+    fn locate_in_pdf(
+        &self,
+        id: RequestId,
+        params: LocateInPdfParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, locate_in_pdf)?;
+        Ok(())
+    }
+
+    fn output_status(
+        &self,
+        id: RequestId,
+        params: OutputStatusParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_output_status)?;
+        Ok(())
+    }
+
+    fn detex(
+        &self,
+        id: RequestId,
+        params: DetexParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, detex)?;
+        Ok(())
+    }
+
+    fn language_regions(
+        &self,
+        id: RequestId,
+        params: LanguageRegionsParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_language_regions)?;
+        Ok(())
+    }
+
+    fn matching_pair(
+        &self,
+        id: RequestId,
+        params: TextDocumentPositionParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_matching_pair)?;
+        Ok(())
+    }
+
+    fn next_section(
+        &self,
+        id: RequestId,
+        params: NextSectionParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(
+            params
+                .text_document_position
+                .text_document
+                .uri
+                .clone()
+                .into(),
+        );
+        self.handle_feature_request(id, params, uri, token, find_next_section)?;
+        Ok(())
+    }
+
+    fn embedded_documents(
+        &self,
+        id: RequestId,
+        params: EmbeddedDocumentsParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_embedded_documents)?;
+        Ok(())
+    }
+
+    fn shared_preambles(
+        &self,
+        id: RequestId,
+        params: SharedPreamblesParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        self.handle_feature_request(id, params, uri, token, find_shared_preambles)?;
+        Ok(())
+    }
+
+    fn distro_info(&self, id: RequestId, token: &Arc<CancellationToken>) -> Result<()> {
+        let cx = Arc::clone(&self.context);
+        let json = serde_json::Value::Null;
+        self.execute_request(id, "distro_info", json, None, token, move |_| {
+            let build_executable = cx.options.read().unwrap().build.executable();
+            Ok(DistroInfo::detect(&build_executable))
+        });
+        Ok(())
+    }
+
+    fn viewer_setup(&self, id: RequestId, params: ViewerSetupParams) -> Result<()> {
+        let setups = find_viewer_setups(&params);
+        self.connection
+            .sender
+            .send(lsp_server::Response::new_ok(id, setups).into())?;
+        Ok(())
+    }
+
+    fn internal_state(&self, id: RequestId) -> Result<()> {
+        let state = collect_internal_state(&self.context, self.workspace.as_ref());
+        self.connection
+            .sender
+            .send(lsp_server::Response::new_ok(id, state).into())?;
+        Ok(())
+    }
+
+    fn recent_edits(&self, id: RequestId) -> Result<()> {
+        let locations = self.recent_edits.locations(self.workspace.as_ref());
+        self.connection
+            .sender
+            .send(lsp_server::Response::new_ok(id, locations).into())?;
+        Ok(())
+    }
+
+    fn workspace_stats(&self, id: RequestId) -> Result<()> {
+        let stats = collect_workspace_stats(self.workspace.as_ref());
+        self.connection
+            .sender
+            .send(lsp_server::Response::new_ok(id, stats).into())?;
+        Ok(())
+    }
+
+    fn execute_command(
+        &self,
+        id: RequestId,
+        params: ExecuteCommandParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let arguments = params.arguments.into_iter().next();
+        match params.command.as_str() {
+            SHOW_DOCUMENTATION_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<TextDocumentPositionParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, show_documentation)?;
+                    }
+                    None => {
+                        self.send_invalid_command_error(id, "expected a text document position")?
+                    }
+                }
+            }
+            NEW_DOCUMENT_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<NewDocumentParams>(arg).ok())
+                {
+                    Some(params) => self.new_document(id, params, token)?,
+                    None => self.send_invalid_command_error(id, "expected a template")?,
+                }
+            }
+            CONVERT_PASTE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<ConvertPasteParams>(arg).ok())
+                {
+                    Some(params) => self.convert_paste(id, params, token)?,
+                    None => self.send_invalid_command_error(id, "expected paste parameters")?,
+                }
+            }
+            NORMALIZE_LABELS_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<NormalizeLabelsParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, normalize_labels)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            EXTRACT_PREAMBLE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<ExtractPreambleParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(
+                            id,
+                            params,
+                            uri,
+                            token,
+                            extract_shared_preamble,
+                        )?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            EXPORT_BIBLIOGRAPHY_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<ExportBibliographyParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, export_bibliography)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            EXPORT_OUTLINE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<ExportOutlineParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, export_outline)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            FOLD_ALL_ENVIRONMENTS_COMMAND => {
+                match arguments.and_then(|arg| serde_json::from_value::<FoldAllParams>(arg).ok()) {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, fold_all_environments)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            FOLD_ALL_COMMENTS_COMMAND => {
+                match arguments.and_then(|arg| serde_json::from_value::<FoldAllParams>(arg).ok()) {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, fold_all_comments)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            ENABLE_SHELL_ESCAPE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<EnableShellEscapeParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, enable_shell_escape)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            FLATTEN_PROJECT_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<FlattenProjectParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, flatten_project)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            PREVIEW_ENVIRONMENT_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<PreviewEnvironmentParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(
+                            params
+                                .text_document_position
+                                .text_document
+                                .uri
+                                .clone()
+                                .into(),
+                        );
+                        self.handle_feature_request(id, params, uri, token, preview_environment)?;
+                    }
+                    None => {
+                        self.send_invalid_command_error(id, "expected a text document position")?
+                    }
+                }
+            }
+            CHECK_ARXIV_COMMAND => {
+                match arguments.and_then(|arg| serde_json::from_value::<CheckArxivParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, check_arxiv)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            USAGE_REPORT_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<UsageReportParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, create_usage_report)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected a text document")?,
+                }
+            }
+            INSERT_MATRIX_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<InsertMatrixParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(
+                            params
+                                .text_document_position
+                                .text_document
+                                .uri
+                                .clone()
+                                .into(),
+                        );
+                        self.handle_feature_request(id, params, uri, token, create_matrix_snippet)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected matrix parameters")?,
+                }
+            }
+            INSERT_FIGURE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<InsertFigureParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(
+                            params
+                                .text_document_position
+                                .text_document
+                                .uri
+                                .clone()
+                                .into(),
+                        );
+                        self.handle_feature_request(id, params, uri, token, create_figure_snippet)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected figure parameters")?,
+                }
+            }
+            CONVERT_TABLE_COMMAND => {
+                match arguments
+                    .and_then(|arg| serde_json::from_value::<ConvertTableParams>(arg).ok())
+                {
+                    Some(params) => {
+                        let uri = Arc::new(params.text_document.uri.clone().into());
+                        self.handle_feature_request(id, params, uri, token, convert_table)?;
+                    }
+                    None => self.send_invalid_command_error(id, "expected table parameters")?,
+                }
+            }
+            _ => self.send_invalid_command_error(id, "unknown command")?,
+        };
+        Ok(())
+    }
+
+    fn convert_paste(
+        &self,
+        id: RequestId,
+        params: ConvertPasteParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone().into());
+        let req_queue = Arc::clone(&self.req_queue);
+        let sender = self.connection.sender.clone();
+        self.handle_feature_request(id, params, uri, token, move |request, token| {
+            if let Some(edit) = convert_paste(request, token) {
+                let params = ApplyWorkspaceEditParams {
+                    label: Some("Paste".to_string()),
+                    edit,
+                };
+
+                if let Err(why) = send_request::<ApplyWorkspaceEdit>(&req_queue, &sender, params) {
+                    error!("Failed to apply pasted text: {}", why);
+                }
+            }
+        })?;
+        Ok(())
+    }
+
+    fn new_document(
+        &self,
+        id: RequestId,
+        params: NewDocumentParams,
+        token: &Arc<CancellationToken>,
+    ) -> Result<()> {
+        let req_queue = Arc::clone(&self.req_queue);
+        let sender = self.connection.sender.clone();
+        let json = serde_json::to_value(&params).unwrap_or_default();
+        self.execute_request(id, "new_document", json, None, token, move |token| {
+            let template_directory = template_directory();
+            let edit =
+                create_new_document(&params, template_directory.as_deref()).ok_or_else(|| {
+                    ResponseError {
+                        code: ErrorCode::InvalidParams as i32,
+                        message: format!(
+                            "Cannot create a project from the template \"{}\"",
+                            params.template
+                        ),
+                        data: None,
+                    }
+                })?;
+
+            let applied = !token.is_canceled()
+                && match send_request::<ApplyWorkspaceEdit>(
+                    &req_queue,
+                    &sender,
+                    ApplyWorkspaceEditParams {
+                        label: Some("New document".to_string()),
+                        edit,
+                    },
+                ) {
+                    Ok(response) => response.applied,
+                    Err(why) => {
+                        error!("Failed to create the new document: {}", why);
+                        false
+                    }
+                };
+
+            Ok(applied)
+        });
+        Ok(())
+    }
+
+    fn send_invalid_command_error(&self, id: RequestId, message: &str) -> Result<()> {
+        let resp =
+            lsp_server::Response::new_err(id, ErrorCode::InvalidParams as i32, message.to_string());
+        self.connection.sender.send(resp.into())?;
+        Ok(())
+    }
+
+    fn dispatch_request(
+        &self,
+        request: lsp_server::Request,
+        token: &Arc<CancellationToken>,
+    ) -> Result<Option<lsp_server::Response>> {
+        let response = RequestDispatcher::new(request)
+            .on::<DocumentLinkRequest, _>(|id, params| self.document_link(id, params, token))?
+            .on::<FoldingRangeRequest, _>(|id, params| self.folding_range(id, params, token))?
+            .on::<DocumentColor, _>(|id, params| self.document_color(id, params, token))?
+            .on::<ColorPresentationRequest, _>(|id, params| {
+                self.color_presentation(id, params, token)
+            })?
+            .on::<References, _>(|id, params| self.references(id, params, token))?
+            .on::<HoverRequest, _>(|id, params| self.hover(id, params, token))?
+            .on::<DocumentSymbolRequest, _>(|id, params| self.document_symbols(id, params, token))?
+            .on::<WorkspaceSymbol, _>(|id, params| self.workspace_symbols(id, params, token))?
+            .on::<WillCreateFiles, _>(|id, params| self.will_create_files(id, params, token))?
+            .on::<WillRenameFiles, _>(|id, params| self.will_rename_files(id, params, token))?
+            .on::<Completion, _>(|id, params| {
+                #[cfg(feature = "completion")]
+                self.completion(id, params, token)?;
+                Ok(())
+            })?
+            .on::<ResolveCompletionItem, _>(|id, params| {
+                #[cfg(feature = "completion")]
+                self.completion_resolve(id, params, token)?;
+                Ok(())
+            })?
+            .on::<GotoDefinition, _>(|id, params| self.goto_definition(id, params, token))?
+            .on::<PrepareRenameRequest, _>(|id, params| self.prepare_rename(id, params, token))?
+            .on::<Rename, _>(|id, params| self.rename(id, params, token))?
+            .on::<DocumentHighlightRequest, _>(|id, params| {
+                self.document_highlight(id, params, token)
+            })?
+            .on::<Formatting, _>(|id, params| self.formatting(id, params, token))?
+            .on::<CodeActionRequest, _>(|id, params| self.code_action(id, params, token))?
+            .on::<ExecuteCommand, _>(|id, params| self.execute_command(id, params, token))?
+            .on::<BuildRequest, _>(|id, params| self.build(id, params, token))?
+            .on::<ForwardSearchRequest, _>(|id, params| self.forward_search(id, params, token))?
+            .on::<SemanticTokensRangeRequest, _>(|id, params| {
+                self.semantic_tokens_range(id, params, token)
+            })?
+            .on::<EnvironmentsRequest, _>(|id, params| self.environments(id, params, token))?
+            .on::<ExtractEnvironmentRequest, _>(|id, params| {
+                self.extract_environment(id, params, token)
+            })?
+            .on::<SectionsRequest, _>(|id, params| self.sections(id, params, token))?
+            .on::<InlayHintRequest, _>(|id, params| self.inlay_hints(id, params, token))?
+            .on::<CitationAnalysisRequest, _>(|id, params| {
+                self.citation_analysis(id, params, token)
+            })?
+            .on::<TodosRequest, _>(|id, params| self.todos(id, params, token))?
+            .on::<BuildLogRequest, _>(|id, params| self.build_log(id, params, token))?
+            .on::<LocateInPdfRequest, _>(|id, params| self.locate_in_pdf(id, params, token))?
+            .on::<OutputStatusRequest, _>(|id, params| self.output_status(id, params, token))?
+            .on::<DetexRequest, _>(|id, params| self.detex(id, params, token))?
+            .on::<LanguageRegionsRequest, _>(|id, params| self.language_regions(id, params, token))?
+            .on::<MatchingPairRequest, _>(|id, params| self.matching_pair(id, params, token))?
+            .on::<NextSectionRequest, _>(|id, params| self.next_section(id, params, token))?
+            .on::<EmbeddedDocumentsRequest, _>(|id, params| {
+                self.embedded_documents(id, params, token)
+            })?
+            .on::<SharedPreamblesRequest, _>(|id, params| self.shared_preambles(id, params, token))?
+            .on::<DistroInfoRequest, _>(|id, ()| self.distro_info(id, token))?
+            .on::<ViewerSetupRequest, _>(|id, params| self.viewer_setup(id, params))?
+            .on::<InternalStateRequest, _>(|id, ()| self.internal_state(id))?
+            .on::<WorkspaceStatsRequest, _>(|id, ()| self.workspace_stats(id))?
+            .on::<RecentEditsRequest, _>(|id, ()| self.recent_edits(id))?
+            .on::<StructuredSearchRequest, _>(|id, params| {
+                self.structured_search(id, params, token)
+            })?
+            .default();
+        Ok(response)
+    }
+
+    fn dispatch_notification(&self, notification: lsp_server::Notification) -> Result<()> {
+        NotificationDispatcher::new(notification)
+            .on::<Cancel, _>(|params| self.cancel(params))?
+            .on::<WorkDoneProgressCancel, _>(|params| self.cancel_progress(params))?
+            .on::<DidChangeConfiguration, _>(|params| self.did_change_configuration(params))?
+            .on::<DidOpenTextDocument, _>(|params| self.did_open(params))?
+            .on::<DidChangeTextDocument, _>(|params| self.did_change(params))?
+            .on::<DidSaveTextDocument, _>(|params| self.did_save(params))?
+            .on::<DidCloseTextDocument, _>(|params| self.did_close(params))?
+            .on::<DidRenameFiles, _>(|params| self.did_rename_files(params))?
+            .on::<DidChangeCursorPositionNotification, _>(|params| {
+                self.did_change_cursor_position(params)
+            })?
+            .default();
+        Ok(())
+    }
+
+    fn process_messages(&self) -> Result<()> {
+        for msg in &self.connection.receiver {
+            match msg {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+
+                    let id = request.id.clone();
+                    let handler = request.method.clone();
+                    let params = request.params.clone();
+                    let token = self.register_incoming_request(id.clone());
+                    match catch_panic(&self.context, &handler, None, params, || {
+                        self.dispatch_request(request, &token)
+                    }) {
+                        Ok(response) => {
+                            if let Some(response) = response? {
+                                self.connection.sender.send(response.into())?;
+                            }
+                        }
+                        Err(report) => {
+                            let response = lsp_server::Response::new_err(
+                                id,
+                                ErrorCode::InternalError as i32,
+                                format!("texlab panicked: {}", report.message),
+                            );
+                            self.connection.sender.send(response.into())?;
+                        }
+                    }
+                }
+                Message::Notification(notification) => {
+                    let handler = notification.method.clone();
+                    if let Ok(result) = catch_panic(
+                        &self.context,
+                        &handler,
+                        None,
+                        serde_json::Value::Null,
+                        || self.dispatch_notification(notification),
+                    ) {
+                        result?;
+                    }
+                }
+                Message::Response(response) => {
+                    let mut req_queue = self.req_queue.lock().unwrap();
+                    let data = req_queue.outgoing.complete(response.id);
+                    let result = match response.error {
+                        Some(error) => Err(error),
+                        None => Ok(response.result.unwrap_or_default()),
+                    };
+                    data.sender.send(result)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn run(mut self) -> Result<()> {
+        self.initialize()?;
+        self.process_messages()?;
+        drop(self.static_debouncer);
+        drop(self.chktex_debouncer);
+        drop(self.language_tool_debouncer);
+        self.pool.join();
+        Ok(())
+    }
+}
+
+fn create_static_debouncer(
+    manager: Arc<Mutex<DiagnosticsManager>>,
+    conn: &Connection,
+    context: Arc<ServerContext>,
+) -> DiagnosticsDebouncer {
+    let sender = conn.sender.clone();
+    DiagnosticsDebouncer::launch(Arc::clone(&context), move |workspace, document| {
+        let options = { context.options.read().unwrap().clone() };
+        let mut manager = manager.lock().unwrap_or_else(PoisonError::into_inner);
+        manager.update_static(workspace.as_ref(), Arc::clone(&document.uri), &options);
+        if let Err(why) = publish_diagnostics(&sender, workspace.as_ref(), &manager) {
+            warn!("Failed to publish diagnostics: {}", why);
+        }
+    })
+}
+
+fn create_chktex_debouncer(
+    manager: Arc<Mutex<DiagnosticsManager>>,
+    conn: &Connection,
+    context: Arc<ServerContext>,
+) -> DiagnosticsDebouncer {
+    let sender = conn.sender.clone();
+    DiagnosticsDebouncer::launch(Arc::clone(&context), move |workspace, document| {
+        let options = { context.options.read().unwrap().clone() };
+        let mut manager = manager.lock().unwrap_or_else(PoisonError::into_inner);
         manager.update_chktex(workspace.as_ref(), Arc::clone(&document.uri), &options);
         if let Err(why) = publish_diagnostics(&sender, workspace.as_ref(), &manager) {
             warn!("Failed to publish diagnostics: {}", why);
@@ -885,13 +1881,32 @@ fn create_chktex_debouncer(
     })
 }
 
+/// Checks the document without locking the diagnostics
+/// since the LanguageTool server might take a while to respond.
+fn create_language_tool_debouncer(
+    manager: Arc<Mutex<DiagnosticsManager>>,
+    conn: &Connection,
+    context: Arc<ServerContext>,
+) -> DiagnosticsDebouncer {
+    let sender = conn.sender.clone();
+    DiagnosticsDebouncer::launch(Arc::clone(&context), move |workspace, document| {
+        let options = { context.options.read().unwrap().clone() };
+        let diagnostics = check_language_tool(workspace.as_ref(), &document, &options);
+        let mut manager = manager.lock().unwrap_or_else(PoisonError::into_inner);
+        manager.update_language_tool(&document, diagnostics.unwrap_or_default());
+        if let Err(why) = publish_diagnostics(&sender, workspace.as_ref(), &manager) {
+            warn!("Failed to publish diagnostics: {}", why);
+        }
+    })
+}
+
 fn publish_diagnostics(
     sender: &Sender<lsp_server::Message>,
     workspace: &dyn Workspace,
     diag_manager: &DiagnosticsManager,
 ) -> Result<()> {
     for document in workspace.documents() {
-        let diagnostics = diag_manager.publish(Arc::clone(&document.uri));
+        let diagnostics = diag_manager.publish(&document);
         send_notification::<PublishDiagnostics>(
             sender,
             PublishDiagnosticsParams {
@@ -904,6 +1919,92 @@ fn publish_diagnostics(
     Ok(())
 }
 
+fn report_missing_tools(
+    lsp_sender: &Sender<Message>,
+    info: &DistroInfo,
+    build_executable: &str,
+) -> Result<()> {
+    for tool in info.missing_tools() {
+        warn!(
+            "Unable to find {}: {}",
+            tool.name,
+            tool.install_hint.as_deref().unwrap_or_default()
+        );
+    }
+
+    if let Some(tool) = info
+        .missing_tools()
+        .find(|tool| tool.name == build_executable)
+    {
+        send_notification::<ShowMessage>(
+            lsp_sender,
+            ShowMessageParams {
+                typ: MessageType::Warning,
+                message: format!(
+                    "TexLab: The build tool `{}` could not be found. {}",
+                    tool.name,
+                    tool.install_hint.as_deref().unwrap_or_default()
+                ),
+            },
+        )?;
+    }
+    Ok(())
+}
+
+fn reindex_workspace(
+    context: &ServerContext,
+    req_queue: &Mutex<ReqQueue>,
+    sender: &Sender<Message>,
+    workspace: &dyn Workspace,
+    title: &str,
+) {
+    let progress_reporter = ProgressReporter::begin(context, req_queue, sender, title, None, true);
+    let documents = workspace.documents();
+    for (index, document) in documents.iter().enumerate() {
+        if progress_reporter.is_canceled() {
+            info!("{} was cancelled", title);
+            break;
+        }
+
+        progress_reporter.report(
+            format!(
+                "{}/{} {}",
+                index + 1,
+                documents.len(),
+                document.uri.as_str()
+            ),
+            index,
+            documents.len(),
+        );
+
+        let source = if workspace.is_open(&document.uri) {
+            WorkspaceSource::Client
+        } else {
+            WorkspaceSource::Server
+        };
+
+        workspace.open(
+            Arc::clone(&document.uri),
+            document.text.clone(),
+            document.language(),
+            source,
+        );
+    }
+}
+
+fn file_operation_options(glob: &str) -> FileOperationRegistrationOptions {
+    FileOperationRegistrationOptions {
+        filters: vec![FileOperationFilter {
+            scheme: Some("file".into()),
+            pattern: FileOperationPattern {
+                glob: glob.into(),
+                matches: None,
+                options: None,
+            },
+        }],
+    }
+}
+
 fn cancel_response(id: RequestId) -> lsp_server::Response {
     lsp_server::Response::new_err(
         id,
@@ -931,3 +2032,237 @@ impl lsp_types::request::Request for ForwardSearchRequest {
 
     const METHOD: &'static str = "textDocument/forwardSearch";
 }
+
+struct EnvironmentsRequest;
+
+impl lsp_types::request::Request for EnvironmentsRequest {
+    type Params = TextDocumentPositionParams;
+
+    type Result = Option<Vec<EnvironmentLocation>>;
+
+    const METHOD: &'static str = "texlab/environments";
+}
+
+struct ExtractEnvironmentRequest;
+
+impl lsp_types::request::Request for ExtractEnvironmentRequest {
+    type Params = TextDocumentPositionParams;
+
+    type Result = Option<EnvironmentContent>;
+
+    const METHOD: &'static str = "texlab/extractEnvironment";
+}
+
+struct SectionsRequest;
+
+impl lsp_types::request::Request for SectionsRequest {
+    type Params = SectionsParams;
+
+    type Result = Option<Vec<SectionLocation>>;
+
+    const METHOD: &'static str = "texlab/sections";
+}
+
+struct InlayHintRequest;
+
+impl lsp_types::request::Request for InlayHintRequest {
+    type Params = InlayHintParams;
+
+    type Result = Option<Vec<InlayHint>>;
+
+    const METHOD: &'static str = "texlab/inlayHints";
+}
+
+struct CitationAnalysisRequest;
+
+impl lsp_types::request::Request for CitationAnalysisRequest {
+    type Params = CitationAnalysisParams;
+
+    type Result = Option<CitationAnalysis>;
+
+    const METHOD: &'static str = "texlab/citationAnalysis";
+}
+
+struct TodosRequest;
+
+impl lsp_types::request::Request for TodosRequest {
+    type Params = TodosParams;
+
+    type Result = Option<Vec<TodoFile>>;
+
+    const METHOD: &'static str = "texlab/todos";
+}
+
+struct BuildLogRequest;
+
+impl lsp_types::request::Request for BuildLogRequest {
+    type Params = BuildLogParams;
+
+    type Result = Option<BuildLogDocument>;
+
+    const METHOD: &'static str = "texlab/buildLog";
+}
+
+struct LocateInPdfRequest;
+
+impl lsp_types::request::Request for LocateInPdfRequest {
+    type Params = LocateInPdfParams;
+
+    type Result = Option<Vec<Option<PdfLocation>>>;
+
+    const METHOD: &'static str = "texlab/locateInPdf";
+}
+
+struct OutputStatusRequest;
+
+impl lsp_types::request::Request for OutputStatusRequest {
+    type Params = OutputStatusParams;
+
+    type Result = Option<OutputStatus>;
+
+    const METHOD: &'static str = "texlab/outputStatus";
+}
+
+struct DetexRequest;
+
+impl lsp_types::request::Request for DetexRequest {
+    type Params = DetexParams;
+
+    type Result = Option<DetexResult>;
+
+    const METHOD: &'static str = "texlab/detex";
+}
+
+struct LanguageRegionsRequest;
+
+impl lsp_types::request::Request for LanguageRegionsRequest {
+    type Params = LanguageRegionsParams;
+
+    type Result = Option<LanguageRegions>;
+
+    const METHOD: &'static str = "texlab/languageRegions";
+}
+
+struct MatchingPairRequest;
+
+impl lsp_types::request::Request for MatchingPairRequest {
+    type Params = TextDocumentPositionParams;
+
+    type Result = Option<Range>;
+
+    const METHOD: &'static str = "texlab/matchingPair";
+}
+
+struct NextSectionRequest;
+
+impl lsp_types::request::Request for NextSectionRequest {
+    type Params = NextSectionParams;
+
+    type Result = Option<SectionLocation>;
+
+    const METHOD: &'static str = "texlab/nextSection";
+}
+
+struct EmbeddedDocumentsRequest;
+
+impl lsp_types::request::Request for EmbeddedDocumentsRequest {
+    type Params = EmbeddedDocumentsParams;
+
+    type Result = Option<Vec<EmbeddedDocument>>;
+
+    const METHOD: &'static str = "texlab/embeddedDocuments";
+}
+
+struct DidChangeEmbeddedDocumentsNotification;
+
+impl lsp_types::notification::Notification for DidChangeEmbeddedDocumentsNotification {
+    type Params = DidChangeEmbeddedDocumentsParams;
+
+    const METHOD: &'static str = "texlab/didChangeEmbeddedDocuments";
+}
+
+struct SharedPreamblesRequest;
+
+impl lsp_types::request::Request for SharedPreamblesRequest {
+    type Params = SharedPreamblesParams;
+
+    type Result = Option<Vec<SharedPreamble>>;
+
+    const METHOD: &'static str = "texlab/sharedPreambles";
+}
+
+struct DistroInfoRequest;
+
+impl lsp_types::request::Request for DistroInfoRequest {
+    type Params = ();
+
+    type Result = DistroInfo;
+
+    const METHOD: &'static str = "texlab/distroInfo";
+}
+
+struct ViewerSetupRequest;
+
+impl lsp_types::request::Request for ViewerSetupRequest {
+    type Params = ViewerSetupParams;
+
+    type Result = Vec<ViewerSetup>;
+
+    const METHOD: &'static str = "texlab/viewerSetup";
+}
+
+struct InternalStateRequest;
+
+impl lsp_types::request::Request for InternalStateRequest {
+    type Params = ();
+
+    type Result = InternalState;
+
+    const METHOD: &'static str = "texlab/internalState";
+}
+
+struct StructuredSearchRequest;
+
+impl lsp_types::request::Request for StructuredSearchRequest {
+    type Params = StructuredSearchParams;
+
+    type Result = Vec<SearchMatch>;
+
+    const METHOD: &'static str = "texlab/structuredSearch";
+}
+
+struct RecentEditsRequest;
+
+impl lsp_types::request::Request for RecentEditsRequest {
+    type Params = ();
+
+    type Result = Vec<RecentEditLocation>;
+
+    const METHOD: &'static str = "texlab/recentEdits";
+}
+
+struct WorkspaceStatsRequest;
+
+impl lsp_types::request::Request for WorkspaceStatsRequest {
+    type Params = ();
+
+    type Result = WorkspaceStats;
+
+    const METHOD: &'static str = "texlab/workspaceStats";
+}
+
+struct DidChangeCursorPositionNotification;
+
+impl lsp_types::notification::Notification for DidChangeCursorPositionNotification {
+    type Params = TextDocumentPositionParams;
+
+    const METHOD: &'static str = "texlab/didChangeCursorPosition";
+}
+
+struct PreviewMathNotification;
+
+impl lsp_types::notification::Notification for PreviewMathNotification {
+    type Params = PreviewMathParams;
+
+    const METHOD: &'static str = "texlab/previewMath";
+}
@@ -0,0 +1,260 @@
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+
+/// The number of scaled points in a PDF point (big point).
+const SCALED_POINTS_PER_BP: f64 = 65536.0 * 72.27 / 72.0;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SyncTexRecordKind {
+    VBox,
+    HBox,
+    VoidVBox,
+    VoidHBox,
+    Kern,
+    Glue,
+    Math,
+    Current,
+}
+
+impl SyncTexRecordKind {
+    pub fn is_box(self) -> bool {
+        matches!(
+            self,
+            Self::VBox | Self::HBox | Self::VoidVBox | Self::VoidHBox
+        )
+    }
+}
+
+/// A node of the typeset output that points back to a line of an input file.
+/// The coordinates are in PDF points relative to the top left corner of the page.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncTexRecord {
+    pub kind: SyncTexRecordKind,
+    pub tag: u32,
+    pub line: u32,
+    pub page: u32,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub depth: f64,
+}
+
+/// The contents of a `.synctex` file that is written by the TeX engine with `-synctex=1`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SyncTexData {
+    pub inputs: Vec<(u32, PathBuf)>,
+    pub records: Vec<SyncTexRecord>,
+}
+
+impl SyncTexData {
+    /// Reads the SyncTeX file next to the given PDF file.
+    /// Relative input paths are resolved against the directory of the PDF file.
+    pub fn load(pdf_path: &Path) -> Option<Self> {
+        let directory = pdf_path.parent()?;
+        let text = fs::read(pdf_path.with_extension("synctex.gz"))
+            .ok()
+            .and_then(|bytes| {
+                let mut text = String::new();
+                GzDecoder::new(&bytes[..]).read_to_string(&mut text).ok()?;
+                Some(text)
+            })
+            .or_else(|| fs::read_to_string(pdf_path.with_extension("synctex")).ok())?;
+
+        Some(Self::parse(&text, directory))
+    }
+
+    pub fn parse(text: &str, directory: &Path) -> Self {
+        let mut data = Self::default();
+        let mut unit = 1.0;
+        let mut magnification = 1.0;
+        let mut x_offset = 0.0;
+        let mut y_offset = 0.0;
+        let mut page = 0;
+        for line in text.lines() {
+            if let Some(input) = line.strip_prefix("Input:") {
+                if let Some((tag, path)) = input.split_once(':') {
+                    if let Ok(tag) = tag.parse() {
+                        data.inputs.push((tag, directory.join(path.trim())));
+                    }
+                }
+                continue;
+            }
+
+            if let Some(value) = line.strip_prefix("Unit:") {
+                unit = value.trim().parse().unwrap_or(1.0);
+            } else if let Some(value) = line.strip_prefix("Magnification:") {
+                magnification = value.trim().parse::<f64>().unwrap_or(1000.0) / 1000.0;
+            } else if let Some(value) = line.strip_prefix("X Offset:") {
+                x_offset = value.trim().parse().unwrap_or(0.0);
+            } else if let Some(value) = line.strip_prefix("Y Offset:") {
+                y_offset = value.trim().parse().unwrap_or(0.0);
+            } else if let Some(value) = line.strip_prefix('{') {
+                page = value.trim().parse().unwrap_or(page);
+            } else {
+                let mut chars = line.chars();
+                let kind = match chars.next() {
+                    Some('[') => SyncTexRecordKind::VBox,
+                    Some('(') => SyncTexRecordKind::HBox,
+                    Some('v') => SyncTexRecordKind::VoidVBox,
+                    Some('h') => SyncTexRecordKind::VoidHBox,
+                    Some('k') => SyncTexRecordKind::Kern,
+                    Some('g') => SyncTexRecordKind::Glue,
+                    Some('$') => SyncTexRecordKind::Math,
+                    Some('x') => SyncTexRecordKind::Current,
+                    _ => continue,
+                };
+
+                let to_bp = |value: f64, offset: f64| {
+                    (value * unit + offset) * magnification / SCALED_POINTS_PER_BP
+                };
+
+                if let Some(record) = parse_record(kind, chars.as_str()) {
+                    let (tag, line, x, y, width, height, depth) = record;
+                    data.records.push(SyncTexRecord {
+                        kind,
+                        tag,
+                        line,
+                        page,
+                        x: to_bp(x, x_offset),
+                        y: to_bp(y, y_offset),
+                        width: to_bp(width, 0.0),
+                        height: to_bp(height, 0.0),
+                        depth: to_bp(depth, 0.0),
+                    });
+                }
+            }
+        }
+        data
+    }
+
+    /// Finds the record that belongs to the given line (one-based) of an input file.
+    /// If the line itself did not produce any output, the record of the closest line is used.
+    pub fn find(&self, path: &Path, line: u32) -> Option<&SyncTexRecord> {
+        let tags: Vec<_> = self
+            .inputs
+            .iter()
+            .filter(|(_, input)| input == path)
+            .map(|(tag, _)| *tag)
+            .collect();
+
+        self.records
+            .iter()
+            .filter(|record| tags.contains(&record.tag) && record.line > 0)
+            .min_by_key(|record| {
+                (
+                    (i64::from(record.line) - i64::from(line)).abs(),
+                    !record.kind.is_box(),
+                )
+            })
+    }
+}
+
+type RawRecord = (u32, u32, f64, f64, f64, f64, f64);
+
+/// Parses a record like `1,10:4736286,5490891:26673152,655360,0`
+/// where the column after the line number is optional.
+fn parse_record(kind: SyncTexRecordKind, text: &str) -> Option<RawRecord> {
+    let mut parts = text.split(':');
+    let mut link = parts.next()?.split(',');
+    let tag = link.next()?.parse().ok()?;
+    let line = link.next()?.parse().ok()?;
+
+    let mut point = parts.next()?.split(',');
+    let x = point.next()?.parse().ok()?;
+    let y = point.next()?.parse().ok()?;
+
+    let mut size = parts
+        .next()
+        .into_iter()
+        .flat_map(|size| size.split(','))
+        .map(|value| value.parse::<f64>().ok());
+
+    let (width, height, depth) = if kind.is_box() {
+        (size.next()??, size.next()??, size.next()??)
+    } else if kind == SyncTexRecordKind::Kern {
+        (size.next()??, 0.0, 0.0)
+    } else {
+        (0.0, 0.0, 0.0)
+    };
+
+    Some((tag, line, x, y, width, height, depth))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const SYNCTEX: &str = indoc!(
+        r#"
+            SyncTeX Version:1
+            Input:1:/project/./main.tex
+            Output:pdf
+            Magnification:1000
+            Unit:1
+            X Offset:0
+            Y Offset:0
+            Content:
+            !121
+            {1
+            [1,3:4736286,4736286:30785863,45088768,0
+            (1,3:4736286,5490891:30785863,655360,0
+            g1,3:5000000,5490891
+            k1,3:6000000,5490891:-65536
+            )
+            Input:2:chapter.tex
+            (2,1:4736286,7000000:30785863,655360,0
+            $2,1:8000000,7000000
+            )
+            ]
+            }1
+            {2
+            h1,8:4736286,4736286:65536,65536,0
+            }2
+            Postamble:
+        "#
+    );
+
+    #[test]
+    fn test_parse() {
+        let data = SyncTexData::parse(SYNCTEX, Path::new("/project"));
+        assert_eq!(
+            data.inputs,
+            vec![
+                (1, PathBuf::from("/project/./main.tex")),
+                (2, PathBuf::from("/project/chapter.tex"))
+            ]
+        );
+        assert_eq!(data.records.len(), 7);
+
+        let kern = &data.records[3];
+        assert_eq!(kern.kind, SyncTexRecordKind::Kern);
+        assert!((kern.width + 72.0 / 72.27).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_find() {
+        let data = SyncTexData::parse(SYNCTEX, Path::new("/project"));
+        let main_path = Path::new("/project/main.tex");
+
+        let record = data.find(main_path, 3).unwrap();
+        assert_eq!(record.kind, SyncTexRecordKind::VBox);
+        assert_eq!(record.page, 1);
+        assert!((record.x - 72.0).abs() < 1e-6);
+
+        let record = data.find(main_path, 7).unwrap();
+        assert_eq!(record.kind, SyncTexRecordKind::VoidHBox);
+        assert_eq!(record.page, 2);
+
+        let record = data.find(Path::new("/project/chapter.tex"), 1).unwrap();
+        assert_eq!(record.kind, SyncTexRecordKind::HBox);
+        assert_eq!(data.find(Path::new("/project/other.tex"), 1), None);
+    }
+}
@@ -3,6 +3,7 @@ use cstree::TextRange;
 pub mod bibtex;
 pub mod build_log;
 pub mod latex;
+pub mod markdown;
 
 pub trait CstNode<'a> {
     type Lang: cstree::Language;
@@ -6,6 +6,10 @@ use super::{
     SyntaxNode,
 };
 
+/// The maximum nesting depth of brace groups.
+/// Deeper groups are parsed as an error to avoid overflowing the stack on pathological input.
+const MAX_DEPTH: usize = 256;
+
 #[derive(Debug, Clone)]
 pub struct Parse {
     pub root: SyntaxNode,
@@ -14,6 +18,7 @@ pub struct Parse {
 struct Parser<'a> {
     lexer: Lexer<'a>,
     builder: GreenNodeBuilder<'static, 'static>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
@@ -21,6 +26,7 @@ impl<'a> Parser<'a> {
         Self {
             lexer,
             builder: GreenNodeBuilder::new(),
+            depth: 0,
         }
     }
 
@@ -254,6 +260,12 @@ impl<'a> Parser<'a> {
     }
 
     fn brace_group(&mut self) {
+        if self.depth >= MAX_DEPTH {
+            self.deep_brace_group();
+            return;
+        }
+
+        self.depth += 1;
         self.builder.start_node(BRACE_GROUP.into());
         self.eat();
 
@@ -281,6 +293,27 @@ impl<'a> Parser<'a> {
         self.expect(R_CURLY);
 
         self.builder.finish_node();
+        self.depth -= 1;
+    }
+
+    /// Skips a brace group (including its nested groups) without descending into it.
+    fn deep_brace_group(&mut self) {
+        self.builder.start_node(ERROR.into());
+        let mut balance = 0;
+        while let Some(kind) = self.peek() {
+            match kind {
+                L_CURLY => balance += 1,
+                R_CURLY => balance -= 1,
+                PREAMBLE_TYPE | STRING_TYPE | COMMENT_TYPE | ENTRY_TYPE => break,
+                _ => {}
+            }
+
+            self.eat();
+            if balance <= 0 {
+                break;
+            }
+        }
+        self.builder.finish_node();
     }
 
     fn quote_group(&mut self) {
@@ -422,4 +455,17 @@ mod tests {
             r#"@article(foo, author = {Foo Bar}, title = {Hello})"#
         ));
     }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 100_000;
+        let text = format!(
+            "@article{{foo, title = {}x{}}}",
+            "{".repeat(depth),
+            "}".repeat(depth)
+        );
+        let root = parse(&text).root;
+        assert_eq!(usize::from(root.text_range().len()), text.len());
+        assert!(root.descendants().any(|node| node.kind() == ERROR));
+    }
 }
@@ -2,13 +2,18 @@ mod analysis;
 mod cst;
 mod kind;
 mod lexer;
+mod literate;
+mod lua;
 mod parser;
 
 pub use self::{
     analysis::*,
     cst::*,
     kind::SyntaxKind::{self, *},
-    parser::{parse, Parse},
+    lexer::CustomCommands,
+    literate::{find_code_chunks, is_literate_extension},
+    lua::{find_lua_chunks, LUA_COMMANDS, LUA_ENVIRONMENTS},
+    parser::{parse, parse_with_options, Parse},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1,4 +1,5 @@
 mod command;
+mod counter;
 mod distro_file;
 mod environment;
 mod explicit_link;
@@ -13,8 +14,9 @@ use crate::syntax::latex;
 pub use self::types::*;
 use self::{
     command::analyze_command,
+    counter::analyze_counter_parent,
     environment::analyze_begin,
-    explicit_link::{analyze_import, analyze_include},
+    explicit_link::{analyze_bib_unit_resources, analyze_import, analyze_include},
     implicit_link::analyze_implicit_links,
     label_name::analyze_label_name,
     label_number::analyze_label_number,
@@ -31,6 +33,8 @@ pub fn analyze(context: &mut LatexAnalyzerContext, root: &latex::SyntaxNode) {
             .or_else(|| analyze_label_name(context, node))
             .or_else(|| analyze_label_number(context, node))
             .or_else(|| analyze_theorem_definition(context, node));
+        analyze_counter_parent(context, node);
+        analyze_bib_unit_resources(context, node);
     }
     context.extras.has_document_environment = context.extras.environment_names.contains("document");
 }
@@ -0,0 +1,45 @@
+use crate::syntax::{
+    latex::{self, HasCurly},
+    CstNode,
+};
+
+use super::LatexAnalyzerContext;
+
+pub fn analyze_counter_parent(
+    context: &mut LatexAnalyzerContext,
+    node: &latex::SyntaxNode,
+) -> Option<()> {
+    analyze_counter_definition(context, node).or_else(|| analyze_number_within(context, node))
+}
+
+fn analyze_counter_definition(
+    context: &mut LatexAnalyzerContext,
+    node: &latex::SyntaxNode,
+) -> Option<()> {
+    let counter = latex::CounterDefinition::cast(node)?;
+    let name = counter.name()?.key()?.to_string();
+    let parent = counter.parent()?.key()?.to_string();
+    context.extras.counter_parents.insert(name, parent);
+    Some(())
+}
+
+fn analyze_number_within(
+    context: &mut LatexAnalyzerContext,
+    node: &latex::SyntaxNode,
+) -> Option<()> {
+    let command = latex::GenericCommand::cast(node)?;
+    if command.name()?.text() != "\\numberwithin" {
+        return None;
+    }
+
+    let mut groups = command
+        .syntax()
+        .children()
+        .filter_map(latex::CurlyGroup::cast)
+        .filter_map(|group| group.content_text());
+
+    let name = groups.next()?.trim().to_string();
+    let parent = groups.next()?.trim().to_string();
+    context.extras.counter_parents.insert(name, parent);
+    Some(())
+}
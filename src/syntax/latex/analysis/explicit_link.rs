@@ -70,3 +70,53 @@ pub fn analyze_import(context: &mut LatexAnalyzerContext, node: &latex::SyntaxNo
     });
     Some(())
 }
+
+/// Links the bibliographies of `\putbib[refs]` and `\defaultbibliography{refs}` (`bibunits`)
+/// and the local resources of `\begin{refsection}[refs.bib]` (`biblatex`).
+pub fn analyze_bib_unit_resources(
+    context: &mut LatexAnalyzerContext,
+    node: &latex::SyntaxNode,
+) -> Option<()> {
+    let group = if let Some(command) = latex::GenericCommand::cast(node) {
+        match command.name()?.text() {
+            "\\putbib" => command
+                .syntax()
+                .children()
+                .find_map(latex::BrackGroup::cast)?
+                .syntax(),
+            "\\defaultbibliography" => command
+                .syntax()
+                .children()
+                .find_map(latex::CurlyGroup::cast)?
+                .syntax(),
+            _ => return None,
+        }
+    } else {
+        let begin = latex::Begin::cast(node)?;
+        if begin.name()?.key()?.to_string() != "refsection" {
+            return None;
+        }
+        begin.options()?.syntax()
+    };
+
+    for word in group
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == latex::WORD)
+    {
+        let stem = word.text().to_string();
+        let targets = vec![
+            Arc::new(context.base_uri.join(&stem).ok()?.into()),
+            Arc::new(context.base_uri.join(&format!("{}.bib", stem)).ok()?.into()),
+        ];
+
+        context.extras.explicit_links.push(ExplicitLink {
+            kind: ExplicitLinkKind::Bibtex,
+            stem: stem.into(),
+            stem_range: word.text_range(),
+            targets,
+        });
+    }
+
+    Some(())
+}
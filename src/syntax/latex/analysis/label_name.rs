@@ -1,3 +1,4 @@
+use cstree::NodeOrToken;
 use latex::LabelReferenceRange;
 
 use crate::syntax::{latex, CstNode};
@@ -11,6 +12,7 @@ pub fn analyze_label_name(
     analyze_label_definition_name(context, node)
         .or_else(|| analyze_label_reference_name(context, node))
         .or_else(|| analyze_label_reference_range_name(context, node))
+        .or_else(|| analyze_key_value_label_name(context, node))
 }
 
 fn analyze_label_definition_name(
@@ -64,3 +66,57 @@ fn analyze_label_reference_range_name(
     }
     Some(())
 }
+
+fn analyze_key_value_label_name(
+    context: &mut LatexAnalyzerContext,
+    node: &latex::SyntaxNode,
+) -> Option<()> {
+    if !matches!(node.kind(), latex::BRACK_GROUP | latex::MIXED_GROUP) {
+        return None;
+    }
+
+    let mut is_label_key = false;
+    let mut is_label_value = false;
+    for element in node.children_with_tokens() {
+        match element {
+            NodeOrToken::Token(token) if token.kind() == latex::EQUALITY_SIGN => {
+                is_label_value = is_label_key;
+                is_label_key = false;
+            }
+            NodeOrToken::Token(token)
+                if matches!(token.kind(), latex::WHITESPACE | latex::COMMENT) => {}
+            NodeOrToken::Token(_) => {
+                is_label_key = false;
+                is_label_value = false;
+            }
+            NodeOrToken::Node(node) => {
+                if is_label_value {
+                    if let Some(name) = find_key_value_label_word(node) {
+                        context.extras.label_names.push(LabelName {
+                            text: name.text().into(),
+                            range: name.text_range(),
+                            is_definition: true,
+                        });
+                    }
+                }
+
+                is_label_value = false;
+                is_label_key = latex::Text::cast(node)
+                    .and_then(|text| text.words().last())
+                    .map_or(false, |word| word.text() == "label");
+            }
+        }
+    }
+
+    Some(())
+}
+
+fn find_key_value_label_word(node: &latex::SyntaxNode) -> Option<&latex::SyntaxToken> {
+    let text = if node.kind() == latex::CURLY_GROUP {
+        node.children().find_map(latex::Text::cast)?
+    } else {
+        latex::Text::cast(node)?
+    };
+
+    text.words().next()
+}
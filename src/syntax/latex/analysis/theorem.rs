@@ -13,11 +13,24 @@ pub fn analyze_theorem_definition(
     let name = theorem.name()?.key()?.to_string();
     let description = theorem.description()?;
     let description = description.content_text()?;
+    let counter = theorem
+        .counter()
+        .and_then(|counter| counter.key())
+        .map(|counter| counter.to_string());
+    let parent = theorem
+        .parent()
+        .and_then(|parent| parent.key())
+        .map(|parent| parent.to_string());
 
     context
         .extras
         .theorem_environments
-        .push(TheoremEnvironment { name, description });
+        .push(TheoremEnvironment {
+            name,
+            description,
+            counter,
+            parent,
+        });
 
     Some(())
 }
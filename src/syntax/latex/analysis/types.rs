@@ -24,6 +24,8 @@ pub struct Extras {
     pub label_names: Vec<LabelName>,
     pub label_numbers_by_name: FxHashMap<String, String>,
     pub theorem_environments: Vec<TheoremEnvironment>,
+    pub counter_parents: FxHashMap<String, String>,
+    pub code_chunks: Vec<TextRange>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
@@ -31,6 +33,9 @@ pub struct ImplicitLinks {
     pub aux: Vec<Arc<Uri>>,
     pub log: Vec<Arc<Uri>>,
     pub pdf: Vec<Arc<Uri>>,
+    /// The transcripts of `makeindex` and `makeglossaries`.
+    pub ilg: Vec<Arc<Uri>>,
+    pub glg: Vec<Arc<Uri>>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
@@ -63,6 +68,8 @@ impl ExplicitLink {
 pub struct TheoremEnvironment {
     pub name: String,
     pub description: String,
+    pub counter: Option<String>,
+    pub parent: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
@@ -428,6 +428,10 @@ impl<'a> Include<'a> {
         self.syntax().first_token()
     }
 
+    pub fn options(&self) -> Option<BrackGroupKeyValue<'a>> {
+        self.syntax().children().find_map(BrackGroupKeyValue::cast)
+    }
+
     pub fn path_list(&self) -> Option<CurlyGroupWordList<'a>> {
         self.syntax().children().find_map(CurlyGroupWordList::cast)
     }
@@ -525,6 +529,20 @@ impl<'a> TheoremDefinition<'a> {
     pub fn description(&self) -> Option<CurlyGroup<'a>> {
         self.syntax().children().find_map(CurlyGroup::cast)
     }
+
+    pub fn counter(&self) -> Option<BrackGroupWord<'a>> {
+        self.syntax()
+            .children()
+            .take_while(|node| node.kind() != CURLY_GROUP)
+            .find_map(BrackGroupWord::cast)
+    }
+
+    pub fn parent(&self) -> Option<BrackGroupWord<'a>> {
+        self.syntax()
+            .children()
+            .skip_while(|node| node.kind() != CURLY_GROUP)
+            .find_map(BrackGroupWord::cast)
+    }
 }
 
 cst_node!(CommandDefinition, COMMAND_DEFINITION, MATH_OPERATOR);
@@ -620,6 +638,14 @@ impl<'a> ColorReference<'a> {
     pub fn name(&self) -> Option<CurlyGroupWord<'a>> {
         self.syntax().children().find_map(CurlyGroupWord::cast)
     }
+
+    pub fn model(&self) -> Option<BrackGroupWord<'a>> {
+        self.syntax().children().find_map(BrackGroupWord::cast)
+    }
+
+    pub fn spec(&self) -> Option<CurlyGroup<'a>> {
+        self.syntax().children().find_map(CurlyGroup::cast)
+    }
 }
 
 cst_node!(GlossaryEntryReference, GLOSSARY_ENTRY_REFERENCE);
@@ -646,6 +672,18 @@ impl<'a> GlossaryEntryDefinition<'a> {
     }
 }
 
+cst_node!(EnvironmentDefinition, ENVIRONMENT_DEFINITION);
+
+impl<'a> EnvironmentDefinition<'a> {
+    pub fn command(&self) -> Option<&'a SyntaxToken> {
+        self.syntax().first_token()
+    }
+
+    pub fn name(&self) -> Option<CurlyGroupWord<'a>> {
+        self.syntax().children().find_map(CurlyGroupWord::cast)
+    }
+}
+
 cst_node!(TikzLibraryImport, TIKZ_LIBRARY_IMPORT);
 
 impl<'a> TikzLibraryImport<'a> {
@@ -657,3 +695,31 @@ impl<'a> TikzLibraryImport<'a> {
         self.syntax().children().find_map(CurlyGroupWordList::cast)
     }
 }
+
+cst_node!(CounterDefinition, COUNTER_DEFINITION);
+
+impl<'a> CounterDefinition<'a> {
+    pub fn command(&self) -> Option<&'a SyntaxToken> {
+        self.syntax().first_token()
+    }
+
+    pub fn name(&self) -> Option<CurlyGroupWord<'a>> {
+        self.syntax().children().find_map(CurlyGroupWord::cast)
+    }
+
+    pub fn parent(&self) -> Option<BrackGroupWord<'a>> {
+        self.syntax().children().find_map(BrackGroupWord::cast)
+    }
+}
+
+cst_node!(CounterReference, COUNTER_REFERENCE);
+
+impl<'a> CounterReference<'a> {
+    pub fn command(&self) -> Option<&'a SyntaxToken> {
+        self.syntax().first_token()
+    }
+
+    pub fn name(&self) -> Option<CurlyGroupWord<'a>> {
+        self.syntax().children().find_map(CurlyGroupWord::cast)
+    }
+}
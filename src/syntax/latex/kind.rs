@@ -49,6 +49,7 @@ pub enum SyntaxKind {
     COMMAND_DEFINITION_NAME,
     MATH_OPERATOR_NAME,
     GLOSSARY_ENTRY_DEFINITION_NAME,
+    GLOSSARY_SYMBOL_DEFINITION_NAME,
     GLOSSARY_ENTRY_REFERENCE_NAME,
     ACRONYM_DEFINITION_NAME,
     ACRONYM_DECLARATION_NAME,
@@ -59,6 +60,8 @@ pub enum SyntaxKind {
     COLOR_SET_DEFINITION_NAME,
     TIKZ_LIBRARY_IMPORT_NAME,
     ENVIRONMENT_DEFINIITION_NAME,
+    COUNTER_DEFINITION_NAME,
+    COUNTER_REFERENCE_NAME,
 
     PREAMBLE,
     TEXT,
@@ -119,6 +122,8 @@ pub enum SyntaxKind {
     COLOR_SET_DEFINITION,
     TIKZ_LIBRARY_IMPORT,
     ENVIRONMENT_DEFINITION,
+    COUNTER_DEFINITION,
+    COUNTER_REFERENCE,
     ROOT,
 }
 
@@ -159,6 +164,7 @@ impl SyntaxKind {
                 | COMMAND_DEFINITION_NAME
                 | MATH_OPERATOR_NAME
                 | GLOSSARY_ENTRY_DEFINITION_NAME
+                | GLOSSARY_SYMBOL_DEFINITION_NAME
                 | GLOSSARY_ENTRY_REFERENCE_NAME
                 | ACRONYM_DEFINITION_NAME
                 | ACRONYM_DECLARATION_NAME
@@ -168,6 +174,8 @@ impl SyntaxKind {
                 | COLOR_DEFINITION_NAME
                 | COLOR_SET_DEFINITION_NAME
                 | TIKZ_LIBRARY_IMPORT_NAME
+                | COUNTER_DEFINITION_NAME
+                | COUNTER_REFERENCE_NAME
         )
     }
 }
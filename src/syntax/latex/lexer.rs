@@ -1,4 +1,7 @@
+use cstree::TextRange;
 use logos::Logos;
+use rustc_hash::FxHashMap;
+use smol_str::SmolStr;
 
 use super::kind::SyntaxKind;
 
@@ -43,7 +46,7 @@ enum Token {
     #[regex(r"\$\$?")]
     DOLLAR,
 
-    #[regex(r"\\([^\r\n]|[@a-zA-Z:_]+\*?)?")]
+    #[regex(r"\\([^\r\n]|[@a-zA-Z]+\*?)?")]
     GENERIC_COMMAND_NAME,
 
     #[regex(r"\\begin")]
@@ -121,7 +124,7 @@ enum Token {
     #[regex(r"\\label")]
     LABEL_DEFINITION_NAME,
 
-    #[regex(r"\\ref|\\vref|\\Vref|\\autoref|\\pageref|\\cref|\\Cref|\\cref*|\\Cref*|\\namecref|\\nameCref|\\lcnamecref|\\namecrefs|\\nameCrefs|\\lcnamecrefs|\\labelcref|\\labelcpageref|\\eqref")]
+    #[regex(r"\\ref|\\vref|\\Vref|\\autoref|\\nameref|\\Nameref|\\fullref|\\pageref|\\cref|\\Cref|\\cref*|\\Cref*|\\namecref|\\nameCref|\\lcnamecref|\\namecrefs|\\nameCrefs|\\lcnamecrefs|\\labelcref|\\labelcpageref|\\eqref")]
     LABEL_REFERENCE_NAME,
 
     #[regex(r"\\crefrange\*?|\\Crefrange\*?")]
@@ -130,7 +133,7 @@ enum Token {
     #[regex(r"\\newlabel")]
     LABEL_NUMBER_NAME,
 
-    #[regex(r"\\newcommand\*?|\\renewcommand|\\DeclareRobustCommand")]
+    #[regex(r"\\newcommand\*?|\\renewcommand\*?|\\providecommand\*?|\\DeclareRobustCommand\*?")]
     COMMAND_DEFINITION_NAME,
 
     #[regex(r"\\DeclareMathOperator\*?")]
@@ -139,10 +142,13 @@ enum Token {
     #[regex(r"\\newglossaryentry")]
     GLOSSARY_ENTRY_DEFINITION_NAME,
 
-    #[regex(r"\\gls|\\Gls|\\GLS|\\glspl|\\Glspl|\\GLSpl|\\glsdisp|\\glslink|\\glstext|\\Glstext|\\GLStext|\\glsfirst|\\Glsfirst|\\GLSfirst|\\glsplural|\\Glsplural|\\GLSplural|\\glsfirstplural|\\Glsfirstplural|\\GLSfirstplural|\\glsname|\\Glsname|\\GLSname|\\glssymbol|\\Glssymbol|\\glsdesc|\\Glsdesc|\\GLSdesc|\\glsuseri|\\Glsuseri|\\GLSuseri|\\glsuserii|\\Glsuserii|\\GLSuserii|\\glsuseriii|\\Glsuseriii|\\GLSuseriii|\\glsuseriv|\\Glsuseriv|\\GLSuseriv|\\glsuserv|\\Glsuserv|\\GLSuserv|\\glsuservi|\\Glsuservi|\\GLSuservi")]
+    #[regex(r"\\glsxtrnewsymbol|\\glsxtrnewnumber")]
+    GLOSSARY_SYMBOL_DEFINITION_NAME,
+
+    #[regex(r"\\gls|\\Gls|\\GLS|\\glspl|\\Glspl|\\GLSpl|\\glsdisp|\\glslink|\\glstext|\\Glstext|\\GLStext|\\glsfirst|\\Glsfirst|\\GLSfirst|\\glsplural|\\Glsplural|\\GLSplural|\\glsfirstplural|\\Glsfirstplural|\\GLSfirstplural|\\glsname|\\Glsname|\\GLSname|\\glssymbol|\\Glssymbol|\\glsdesc|\\Glsdesc|\\GLSdesc|\\glsuseri|\\Glsuseri|\\GLSuseri|\\glsuserii|\\Glsuserii|\\GLSuserii|\\glsuseriii|\\Glsuseriii|\\GLSuseriii|\\glsuseriv|\\Glsuseriv|\\GLSuseriv|\\glsuserv|\\Glsuserv|\\GLSuserv|\\glsuservi|\\Glsuservi|\\GLSuservi|\\glsxtrshort|\\Glsxtrshort|\\GLSxtrshort|\\glsxtrlong|\\Glsxtrlong|\\GLSxtrlong|\\glsxtrfull|\\Glsxtrfull|\\GLSxtrfull|\\glsxtrshortpl|\\Glsxtrshortpl|\\GLSxtrshortpl|\\glsxtrlongpl|\\Glsxtrlongpl|\\GLSxtrlongpl|\\glsxtrfullpl|\\Glsxtrfullpl|\\GLSxtrfullpl")]
     GLOSSARY_ENTRY_REFERENCE_NAME,
 
-    #[regex(r"\\newacronym")]
+    #[regex(r"\\newacronym|\\newabbreviation")]
     ACRONYM_DEFINITION_NAME,
 
     #[regex(r"\\DeclareAcronym")]
@@ -168,6 +174,41 @@ enum Token {
 
     #[regex(r"\\newenvironment|\\newenvironment*")]
     ENVIRONMENT_DEFINITION_NAME,
+
+    #[regex(r"\\newcounter")]
+    COUNTER_DEFINITION_NAME,
+
+    #[regex(r"\\setcounter|\\addtocounter|\\stepcounter|\\refstepcounter|\\value|\\arabic|\\alph|\\Alph|\\roman|\\Roman|\\fnsymbol")]
+    COUNTER_REFERENCE_NAME,
+}
+
+/// Additional command names that are classified like the built-in commands of a given kind.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct CustomCommands {
+    kinds: FxHashMap<SmolStr, SyntaxKind>,
+}
+
+impl CustomCommands {
+    pub fn insert(&mut self, name: &str, kind: SyntaxKind) {
+        let name = name.strip_prefix('\\').unwrap_or(name);
+        self.kinds.insert(format!("\\{}", name).into(), kind);
+    }
+
+    fn classify(&self, kind: SyntaxKind, text: &str) -> SyntaxKind {
+        if kind == SyntaxKind::GENERIC_COMMAND_NAME {
+            self.kinds.get(text).copied().unwrap_or(kind)
+        } else {
+            kind
+        }
+    }
+}
+
+/// Returns `true` if the command consists of letters (as opposed to `\%` or `\section*`).
+fn is_control_word(text: &str) -> bool {
+    text.len() > 1
+        && text[1..]
+            .chars()
+            .all(|c| c.is_ascii_alphabetic() || c == '@')
 }
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -177,18 +218,76 @@ pub struct Lexer<'a> {
 
 impl<'a> Lexer<'a> {
     pub fn new(text: &'a str) -> Self {
+        Self::with_options(text, &[], &CustomCommands::default())
+    }
+
+    pub fn with_options(
+        text: &'a str,
+        code_chunks: &[TextRange],
+        custom_commands: &CustomCommands,
+    ) -> Self {
         let mut tokens = Vec::new();
-        let mut lexer = Token::lexer(text);
-        while let Some(kind) = lexer.next() {
-            tokens.push((
-                unsafe { std::mem::transmute::<Token, SyntaxKind>(kind) },
-                lexer.slice(),
-            ));
+        let mut expl_syntax = false;
+        let mut offset = 0;
+        for chunk in code_chunks {
+            let start = usize::from(chunk.start());
+            let end = usize::from(chunk.end());
+            Self::tokenize(
+                &text[offset..start],
+                custom_commands,
+                &mut expl_syntax,
+                &mut tokens,
+            );
+            tokens.push((SyntaxKind::COMMENT, &text[start..end]));
+            offset = end;
         }
+
+        Self::tokenize(
+            &text[offset..],
+            custom_commands,
+            &mut expl_syntax,
+            &mut tokens,
+        );
         tokens.reverse();
         Self { tokens }
     }
 
+    fn tokenize(
+        text: &'a str,
+        custom_commands: &CustomCommands,
+        expl_syntax: &mut bool,
+        tokens: &mut Vec<(SyntaxKind, &'a str)>,
+    ) {
+        let mut lexer = Token::lexer(text);
+        while let Some(kind) = lexer.next() {
+            let mut kind = unsafe { std::mem::transmute::<Token, SyntaxKind>(kind) };
+
+            // Inside of expl3 code, colons and underscores are letters (e.g. `\cs_new:Npn`).
+            if *expl_syntax && kind.is_command_name() && is_control_word(lexer.slice()) {
+                let length = lexer
+                    .remainder()
+                    .find(|c: char| !c.is_ascii_alphabetic() && !matches!(c, '@' | ':' | '_'))
+                    .unwrap_or_else(|| lexer.remainder().len());
+
+                if length > 0 {
+                    lexer.bump(length);
+                    kind = SyntaxKind::GENERIC_COMMAND_NAME;
+                }
+            }
+
+            match lexer.slice() {
+                "\\ExplSyntaxOn"
+                | "\\ProvidesExplPackage"
+                | "\\ProvidesExplClass"
+                | "\\ProvidesExplFile" => *expl_syntax = true,
+                "\\ExplSyntaxOff" => *expl_syntax = false,
+                _ => {}
+            }
+
+            tokens.push((custom_commands.classify(kind, lexer.slice()), lexer.slice()));
+        }
+    }
+
     pub fn peek(&self) -> Option<SyntaxKind> {
         self.tokens.last().map(|(kind, _)| *kind)
     }
@@ -249,4 +348,78 @@ mod tests {
     fn test_invalid_parameter() {
         assert_debug_snapshot!(verify(r#"#"#))
     }
+
+    #[test]
+    fn test_custom_commands() {
+        let mut custom_commands = CustomCommands::default();
+        custom_commands.insert("myref", SyntaxKind::LABEL_REFERENCE_NAME);
+
+        let mut tokens = Lexer::with_options(r#"\myref{foo}"#, &[], &custom_commands).tokens;
+        tokens.reverse();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::LABEL_REFERENCE_NAME, r#"\myref"#),
+                (SyntaxKind::L_CURLY, "{"),
+                (SyntaxKind::WORD, "foo"),
+                (SyntaxKind::R_CURLY, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_custom_command_kinds() {
+        let mut custom_commands = CustomCommands::default();
+        custom_commands.insert("\\mycite", SyntaxKind::CITATION_NAME);
+        custom_commands.insert("mylabel", SyntaxKind::LABEL_DEFINITION_NAME);
+        custom_commands.insert("myinput", SyntaxKind::LATEX_INCLUDE_NAME);
+
+        let mut tokens = Lexer::with_options(
+            r#"\mycite{foo} \mylabel \myinput \mycites mycite"#,
+            &[],
+            &custom_commands,
+        )
+        .tokens;
+        tokens.reverse();
+
+        assert_eq!(
+            tokens,
+            vec![
+                (SyntaxKind::CITATION_NAME, r#"\mycite"#),
+                (SyntaxKind::L_CURLY, "{"),
+                (SyntaxKind::WORD, "foo"),
+                (SyntaxKind::R_CURLY, "}"),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::LABEL_DEFINITION_NAME, r#"\mylabel"#),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::LATEX_INCLUDE_NAME, r#"\myinput"#),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::GENERIC_COMMAND_NAME, r#"\mycites"#),
+                (SyntaxKind::WHITESPACE, " "),
+                (SyntaxKind::WORD, "mycite"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expl_syntax() {
+        let actual: Vec<_> =
+            verify(r#"\foo_bar: \ExplSyntaxOn \cs_new:Npn \ExplSyntaxOff \foo_bar:"#)
+                .into_iter()
+                .filter(|(kind, _)| kind.is_command_name())
+                .map(|(_, text)| text)
+                .collect();
+
+        assert_eq!(
+            actual,
+            vec![
+                r#"\foo"#,
+                r#"\ExplSyntaxOn"#,
+                r#"\cs_new:Npn"#,
+                r#"\ExplSyntaxOff"#,
+                r#"\foo"#
+            ]
+        );
+    }
 }
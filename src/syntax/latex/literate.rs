@@ -0,0 +1,96 @@
+use cstree::{TextRange, TextSize};
+
+pub fn is_literate_extension(extension: &str) -> bool {
+    matches!(
+        extension.to_lowercase().as_str(),
+        "rnw" | "snw" | "pnw" | "stex" | "ptex" | "texw"
+    )
+}
+
+pub fn find_code_chunks(text: &str) -> Vec<TextRange> {
+    let mut chunks = Vec::new();
+    let mut chunk_start = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end();
+        match chunk_start {
+            None if content.starts_with("<<") && content.ends_with(">>=") => {
+                chunk_start = Some(offset);
+            }
+            Some(start)
+                if content == "@" || content.starts_with("@ ") || content.starts_with("@\t") =>
+            {
+                let end = offset + content.len();
+                chunks.push(TextRange::new(
+                    TextSize::from(start as u32),
+                    TextSize::from(end as u32),
+                ));
+                chunk_start = None;
+            }
+            _ => {}
+        };
+
+        offset += line.len();
+    }
+
+    if let Some(start) = chunk_start {
+        chunks.push(TextRange::new(
+            TextSize::from(start as u32),
+            TextSize::from(text.len() as u32),
+        ));
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_no_chunks() {
+        let actual = find_code_chunks(r#"\section{Foo} <<bar>>= @"#);
+        assert_eq!(actual, Vec::new());
+    }
+
+    #[test]
+    fn test_multiple_chunks() {
+        let text = indoc! {r#"
+            \section{Foo}
+            <<bar, echo=FALSE>>=
+            x <- c(1, 2, 3)
+            @
+            \Sexpr{x}
+            <<>>=
+            plot(x)
+            @ % end
+        "#};
+
+        let actual: Vec<_> = find_code_chunks(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+
+        let expected = vec![
+            "<<bar, echo=FALSE>>=\nx <- c(1, 2, 3)\n@",
+            "<<>>=\nplot(x)\n@ % end",
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_unterminated_chunk() {
+        let text = "foo\n<<>>=\nbar\n";
+        let actual = find_code_chunks(text);
+        assert_eq!(actual, vec![TextRange::new(4.into(), 14.into())]);
+    }
+
+    #[test]
+    fn test_literate_extension() {
+        assert!(is_literate_extension("Rnw"));
+        assert!(is_literate_extension("ptex"));
+        assert!(!is_literate_extension("tex"));
+    }
+}
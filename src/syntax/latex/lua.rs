@@ -0,0 +1,125 @@
+use cstree::{TextRange, TextSize};
+
+/// Commands whose argument is executed by LuaTeX.
+pub const LUA_COMMANDS: &[&str] = &["\\directlua", "\\latelua", "\\luaexec", "\\luadirect"];
+
+/// Environments of the `luacode` package whose content is executed by LuaTeX.
+pub const LUA_ENVIRONMENTS: &[&str] = &["luacode", "luacode*", "luacodestar"];
+
+/// Finds the Lua code inside of the arguments of `\directlua` (and similar commands)
+/// and inside of the `luacode` environments so that it can be treated as an opaque chunk.
+pub fn find_lua_chunks(text: &str) -> Vec<TextRange> {
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while let Some(index) = text[offset..].find(&['\\', '%'][..]) {
+        let start = offset + index;
+        let rest = &text[start..];
+        if rest.starts_with('%') {
+            offset = rest.find('\n').map_or(text.len(), |i| start + i + 1);
+            continue;
+        }
+
+        let chunk = if let Some(name) = LUA_COMMANDS.iter().find(|name| is_command(rest, name)) {
+            find_argument(text, start + name.len())
+        } else if let Some(name) = rest
+            .strip_prefix("\\begin{")
+            .and_then(|rest| rest.split_once('}'))
+            .map(|(name, _)| name)
+            .filter(|name| LUA_ENVIRONMENTS.contains(name))
+        {
+            let begin = start + "\\begin{}".len() + name.len();
+            let end = text[begin..]
+                .find(&format!("\\end{{{}}}", name))
+                .map_or(text.len(), |index| begin + index);
+            Some((begin, end))
+        } else {
+            None
+        };
+
+        offset = match chunk {
+            Some((chunk_start, chunk_end)) => {
+                if chunk_start < chunk_end {
+                    chunks.push(TextRange::new(
+                        TextSize::from(chunk_start as u32),
+                        TextSize::from(chunk_end as u32),
+                    ));
+                }
+
+                chunk_end
+            }
+            // Skip the escaped character of control symbols like `\%`.
+            None => start + 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+        };
+    }
+
+    chunks
+}
+
+fn is_command(text: &str, name: &str) -> bool {
+    text.strip_prefix(name).map_or(false, |rest| {
+        !rest.starts_with(|c: char| c.is_ascii_alphabetic())
+    })
+}
+
+/// Returns the range inside of the braces of the argument that starts at the given offset.
+/// Like TeX, the braces need to be balanced and TeX comments are skipped.
+fn find_argument(text: &str, offset: usize) -> Option<(usize, usize)> {
+    let rest = &text[offset..];
+    let argument = rest.trim_start();
+    let argument = argument.strip_prefix('{')?;
+    let start = offset + rest.len() - argument.len();
+
+    let mut depth = 1;
+    let mut chars = argument.char_indices();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '%' => {
+                chars.find(|(_, c)| *c == '\n');
+            }
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some((start, start + index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Some((start, text.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    #[test]
+    fn test_lua_chunks() {
+        let text = indoc! {r#"
+            % \directlua{foo}
+            \directlua{tex.print("\{" .. {1, 2}[1]) % }
+            }
+            \directluax{bar}
+            \begin{luacode*}
+            local x = 1 % 2
+            \end{luacode*}
+        "#};
+
+        let actual: Vec<_> = find_lua_chunks(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect();
+
+        let expected = vec![
+            "tex.print(\"\\{\" .. {1, 2}[1]) % }\n",
+            "\nlocal x = 1 % 2\n",
+        ];
+        assert_eq!(actual, expected);
+    }
+}
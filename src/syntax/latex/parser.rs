@@ -1,11 +1,15 @@
-use cstree::GreenNodeBuilder;
+use cstree::{GreenNodeBuilder, TextRange};
 
 use super::{
-    lexer::Lexer,
+    lexer::{CustomCommands, Lexer},
     SyntaxKind::{self, *},
     SyntaxNode,
 };
 
+/// The maximum nesting depth of groups, commands and environments.
+/// Deeper content is parsed as an error to avoid overflowing the stack on pathological input.
+const MAX_DEPTH: usize = 256;
+
 #[derive(Clone)]
 pub struct Parse {
     pub root: SyntaxNode,
@@ -30,13 +34,15 @@ impl Default for ParserContext {
 struct Parser<'a> {
     lexer: Lexer<'a>,
     builder: GreenNodeBuilder<'static, 'static>,
+    depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(text: &'a str) -> Self {
+    pub fn new(lexer: Lexer<'a>) -> Self {
         Self {
-            lexer: Lexer::new(text),
+            lexer,
             builder: GreenNodeBuilder::new(),
+            depth: 0,
         }
     }
 
@@ -95,6 +101,12 @@ impl<'a> Parser<'a> {
     }
 
     fn content(&mut self, context: ParserContext) {
+        if self.depth >= MAX_DEPTH {
+            self.deep_content();
+            return;
+        }
+
+        self.depth += 1;
         match self.peek().unwrap() {
             WHITESPACE | COMMENT => self.eat(),
             L_CURLY if context.allow_environment => self.curly_group(),
@@ -142,6 +154,7 @@ impl<'a> Parser<'a> {
             COMMAND_DEFINITION_NAME => self.command_definition(),
             MATH_OPERATOR_NAME => self.math_operator(),
             GLOSSARY_ENTRY_DEFINITION_NAME => self.glossary_entry_definition(),
+            GLOSSARY_SYMBOL_DEFINITION_NAME => self.glossary_symbol_definition(),
             GLOSSARY_ENTRY_REFERENCE_NAME => self.glossary_entry_reference(),
             ACRONYM_DEFINITION_NAME => self.acronym_definition(),
             ACRONYM_DECLARATION_NAME => self.acronym_declaration(),
@@ -152,8 +165,32 @@ impl<'a> Parser<'a> {
             COLOR_SET_DEFINITION_NAME => self.color_set_definition(),
             TIKZ_LIBRARY_IMPORT_NAME => self.tikz_library_import(),
             ENVIRONMENT_DEFINIITION_NAME => self.environment_definition(),
+            COUNTER_DEFINITION_NAME => self.counter_definition(),
+            COUNTER_REFERENCE_NAME => self.counter_reference(),
             _ => unreachable!(),
         }
+        self.depth -= 1;
+    }
+
+    /// Skips the next token (or the whole curly group) without descending into it.
+    /// The closing braces of the skipped group are consumed as well,
+    /// so that they are not mistaken for the end of the enclosing group.
+    fn deep_content(&mut self) {
+        self.builder.start_node(ERROR.into());
+        let mut balance = 0;
+        while let Some(kind) = self.peek() {
+            match kind {
+                L_CURLY => balance += 1,
+                R_CURLY => balance -= 1,
+                _ => {}
+            }
+
+            self.eat();
+            if balance <= 0 {
+                break;
+            }
+        }
+        self.builder.finish_node();
     }
 
     fn text(&mut self, context: ParserContext) {
@@ -983,6 +1020,30 @@ impl<'a> Parser<'a> {
         self.builder.finish_node();
     }
 
+    fn glossary_symbol_definition(&mut self) {
+        self.builder.start_node(GLOSSARY_ENTRY_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        if self.lexer.peek() == Some(L_BRACK) {
+            self.brack_group_key_value();
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_word();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        self.builder.finish_node();
+    }
+
     fn glossary_entry_reference(&mut self) {
         self.builder.start_node(GLOSSARY_ENTRY_REFERENCE.into());
         self.eat();
@@ -1098,10 +1159,21 @@ impl<'a> Parser<'a> {
         self.eat();
         self.trivia();
 
-        if self.lexer.peek() == Some(L_CURLY) {
-            self.curly_group_word();
-        } else {
-            self.builder.token(MISSING.into(), "");
+        match self.lexer.peek() {
+            Some(L_BRACK) => {
+                self.brack_group_word();
+                if self.lexer.peek() == Some(L_CURLY) {
+                    self.curly_group();
+                } else {
+                    self.builder.token(MISSING.into(), "");
+                }
+            }
+            Some(L_CURLY) => {
+                self.curly_group_word();
+            }
+            Some(_) | None => {
+                self.builder.token(MISSING.into(), "");
+            }
         }
 
         self.builder.finish_node();
@@ -1198,10 +1270,50 @@ impl<'a> Parser<'a> {
 
         self.builder.finish_node();
     }
+
+    fn counter_definition(&mut self) {
+        self.builder.start_node(COUNTER_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_word();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        if self.lexer.peek() == Some(L_BRACK) {
+            self.brack_group_word();
+        }
+
+        self.builder.finish_node();
+    }
+
+    fn counter_reference(&mut self) {
+        self.builder.start_node(COUNTER_REFERENCE.into());
+        self.eat();
+        self.trivia();
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_word();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        self.builder.finish_node();
+    }
 }
 
 pub fn parse(text: &str) -> Parse {
-    Parser::new(text).parse()
+    Parser::new(Lexer::new(text)).parse()
+}
+
+pub fn parse_with_options(
+    text: &str,
+    code_chunks: &[TextRange],
+    custom_commands: &CustomCommands,
+) -> Parse {
+    Parser::new(Lexer::with_options(text, code_chunks, custom_commands)).parse()
 }
 
 #[cfg(test)]
@@ -1595,6 +1707,11 @@ mod tests {
         assert_debug_snapshot!(setup(r#"\newglossaryentry{foo}{bar = baz, qux,}"#));
     }
 
+    #[test]
+    fn test_glossary_symbol_definition() {
+        assert_debug_snapshot!(setup(r#"\glsxtrnewsymbol[category=unit]{ohm}{Ohm}"#));
+    }
+
     #[test]
     fn test_glossary_entry_reference_simple() {
         assert_debug_snapshot!(setup(r#"\gls{foo}"#));
@@ -1657,6 +1774,11 @@ mod tests {
         assert_debug_snapshot!(setup(r#"\color{black}"#));
     }
 
+    #[test]
+    fn test_color_reference_model() {
+        assert_debug_snapshot!(setup(r#"\color[rgb]{1,0,0}"#));
+    }
+
     #[test]
     fn test_color_definition_simple() {
         assert_debug_snapshot!(setup(r#"\definecolor{foo}{rgb}{255,168,0}"#));
@@ -1708,4 +1830,35 @@ mod tests {
             r#"\DeclareAcronym{eg}{short = e.g,long = for example,tag = abbrev}"#
         ));
     }
+
+    #[test]
+    fn test_counter_definition() {
+        assert_debug_snapshot!(setup(r#"\newcounter{foo}[section]"#));
+    }
+
+    #[test]
+    fn test_counter_reference() {
+        assert_debug_snapshot!(setup(r#"\setcounter{foo}{2}"#));
+    }
+
+    #[test]
+    fn test_deep_nesting() {
+        let depth = 100_000;
+        let text = format!("{}x{}", "{".repeat(depth), "}".repeat(depth));
+        let root = parse(&text).root;
+        assert_eq!(usize::from(root.text_range().len()), text.len());
+        assert!(root.descendants().any(|node| node.kind() == ERROR));
+    }
+
+    #[test]
+    fn test_deep_nesting_environments() {
+        let depth = 10_000;
+        let text = format!(
+            "{}{}",
+            "\\begin{foo}\\foo{".repeat(depth),
+            "}\\end{foo}".repeat(depth)
+        );
+        let root = parse(&text).root;
+        assert_eq!(usize::from(root.text_range().len()), text.len());
+    }
 }
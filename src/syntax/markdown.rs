@@ -0,0 +1,310 @@
+use cstree::{TextRange, TextSize};
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Span {
+    pub text: String,
+    pub range: TextRange,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+pub struct Parse {
+    pub bibliographies: Vec<Span>,
+    pub citations: Vec<Span>,
+    pub labels: Vec<Span>,
+}
+
+pub fn parse(text: &str) -> Parse {
+    let frontmatter = find_frontmatter(text);
+    let bibliographies = frontmatter
+        .map(|range| find_bibliographies(text, range))
+        .unwrap_or_default();
+
+    let code_blocks = find_code_blocks(text);
+    let mut citations = Vec::new();
+    let mut labels = Vec::new();
+    for (start, _) in text.match_indices('@') {
+        let is_excluded = frontmatter
+            .iter()
+            .chain(&code_blocks)
+            .any(|range| range.contains(TextSize::from(start as u32)));
+
+        if !is_excluded && is_citation_start(text, start) {
+            citations.extend(make_key_span(text, start + 1));
+        }
+    }
+
+    for (start, _) in text.match_indices("{#") {
+        labels.extend(make_key_span(text, start + 2));
+    }
+
+    for range in &code_blocks {
+        let block = &text[*range];
+        for (start, _) in block.match_indices("#| label:") {
+            let start = usize::from(range.start()) + start + "#| label:".len();
+            let start = start + (text[start..].len() - text[start..].trim_start_matches(' ').len());
+            labels.extend(make_key_span(text, start));
+        }
+    }
+
+    Parse {
+        bibliographies,
+        citations,
+        labels,
+    }
+}
+
+pub fn find_citation_key_at(text: &str, offset: TextSize) -> Option<TextRange> {
+    let offset = usize::from(offset);
+    let start = text[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| is_key_char(*c))
+        .last()
+        .map_or(offset, |(i, _)| i);
+
+    if !text[..start].ends_with('@') || !is_citation_start(text, start - 1) {
+        return None;
+    }
+
+    let end = text[offset..]
+        .char_indices()
+        .find(|(_, c)| !is_key_char(*c))
+        .map_or(text.len(), |(i, _)| offset + i);
+
+    let end = offset.max(start + trim_key(&text[start..end]).len());
+    Some(TextRange::new(
+        TextSize::from(start as u32),
+        TextSize::from(end as u32),
+    ))
+}
+
+fn is_citation_start(text: &str, at_sign: usize) -> bool {
+    text[..at_sign].chars().last().map_or(true, |c| {
+        c.is_whitespace() || matches!(c, '[' | ';' | '(' | '-')
+    })
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || "_:.#$%&+?<>~/-".contains(c)
+}
+
+fn trim_key(key: &str) -> &str {
+    key.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_')
+}
+
+fn make_key_span(text: &str, start: usize) -> Option<Span> {
+    let end = text[start..]
+        .char_indices()
+        .find(|(_, c)| !is_key_char(*c))
+        .map_or(text.len(), |(i, _)| start + i);
+
+    let key = trim_key(&text[start..end]);
+    if key.is_empty() {
+        return None;
+    }
+
+    Some(Span {
+        text: key.into(),
+        range: TextRange::at(TextSize::from(start as u32), TextSize::of(key)),
+    })
+}
+
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
+
+fn find_frontmatter(text: &str) -> Option<TextRange> {
+    let mut lines = lines(text);
+    let (_, first_line) = lines.next()?;
+    if first_line.trim_end() != "---" {
+        return None;
+    }
+
+    let (start, line) = lines.find(|(_, line)| matches!(line.trim_end(), "---" | "..."))?;
+    Some(TextRange::new(
+        TextSize::from(0),
+        TextSize::from((start + line.len()) as u32),
+    ))
+}
+
+fn find_bibliographies(text: &str, frontmatter: TextRange) -> Vec<Span> {
+    let mut bibliographies = Vec::new();
+    let mut is_list = false;
+    for (start, line) in lines(&text[frontmatter]) {
+        if let Some(value) = line.strip_prefix("bibliography:") {
+            let value_start = start + "bibliography:".len();
+            let value = value.trim_end();
+            if value.trim().is_empty() {
+                is_list = true;
+            } else if let Some(items) = value.trim_start().strip_prefix('[') {
+                let mut item_start = value_start + (value.len() - items.len());
+                for item in items.trim_end_matches(']').split(',') {
+                    bibliographies.extend(make_value_span(item_start, item));
+                    item_start += item.len() + 1;
+                }
+            } else {
+                bibliographies.extend(make_value_span(value_start, value));
+            }
+        } else if is_list && line.trim_start().starts_with('-') {
+            let item = line.trim_start();
+            let item_start = start + (line.len() - item.len()) + 1;
+            bibliographies.extend(make_value_span(item_start, item[1..].trim_end()));
+        } else if !line.starts_with(char::is_whitespace) {
+            is_list = false;
+        }
+    }
+
+    bibliographies
+}
+
+fn make_value_span(start: usize, value: &str) -> Option<Span> {
+    let is_delimiter = |c: char| c.is_whitespace() || c == '"' || c == '\'';
+    let trimmed = value.trim_start_matches(is_delimiter);
+    let start = start + (value.len() - trimmed.len());
+    let trimmed = trimmed.trim_end_matches(is_delimiter);
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    Some(Span {
+        text: trimmed.into(),
+        range: TextRange::at(TextSize::from(start as u32), TextSize::of(trimmed)),
+    })
+}
+
+fn find_code_blocks(text: &str) -> Vec<TextRange> {
+    let mut blocks = Vec::new();
+    let mut block_start = None;
+    for (start, line) in lines(text) {
+        let fence = line.trim_start();
+        if fence.starts_with("```") || fence.starts_with("~~~") {
+            match block_start {
+                Some(block_start_offset) => {
+                    let end = start + line.len();
+                    blocks.push(TextRange::new(
+                        TextSize::from(block_start_offset as u32),
+                        TextSize::from(end as u32),
+                    ));
+                    block_start = None;
+                }
+                None => block_start = Some(start),
+            }
+        }
+    }
+
+    if let Some(start) = block_start {
+        blocks.push(TextRange::new(
+            TextSize::from(start as u32),
+            TextSize::of(text),
+        ));
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn texts(spans: &[Span]) -> Vec<&str> {
+        spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_bibliography_single() {
+        let text = indoc! {r#"
+            ---
+            title: Foo
+            bibliography: "refs.bib"
+            ---
+        "#};
+
+        let parse = parse(text);
+        assert_eq!(texts(&parse.bibliographies), vec!["refs.bib"]);
+        assert_eq!(&text[parse.bibliographies[0].range], "refs.bib");
+    }
+
+    #[test]
+    fn test_bibliography_list() {
+        let text = indoc! {r#"
+            ---
+            bibliography:
+              - foo.bib
+              - bar.bib
+            csl: ieee.csl
+            ---
+        "#};
+
+        let parse = parse(text);
+        assert_eq!(texts(&parse.bibliographies), vec!["foo.bib", "bar.bib"]);
+        assert_eq!(&text[parse.bibliographies[1].range], "bar.bib");
+    }
+
+    #[test]
+    fn test_bibliography_inline_list() {
+        let text = "---\nbibliography: [foo.bib, 'bar.bib']\n---\n";
+        let parse = parse(text);
+        assert_eq!(texts(&parse.bibliographies), vec!["foo.bib", "bar.bib"]);
+        assert_eq!(&text[parse.bibliographies[1].range], "bar.bib");
+    }
+
+    #[test]
+    fn test_citations() {
+        let text = indoc! {r#"
+            As shown by @foo, this works [see @bar, p. 3; -@baz].
+            Mail me at foo@example.com.
+
+            ```python
+            @decorator
+            ```
+        "#};
+
+        let parse = parse(text);
+        assert_eq!(texts(&parse.citations), vec!["foo", "bar", "baz"]);
+    }
+
+    #[test]
+    fn test_labels() {
+        let text = indoc! {r#"
+            ## Introduction {#sec-intro}
+
+            ![Foo](foo.png){#fig-foo}
+
+            ```{r}
+            #| label: fig-plot
+            plot(x)
+            ```
+        "#};
+
+        let parse = parse(text);
+        assert_eq!(
+            texts(&parse.labels),
+            vec!["sec-intro", "fig-foo", "fig-plot"]
+        );
+    }
+
+    #[test]
+    fn test_citation_key_at() {
+        let text = "See [@foo; @ba";
+        let range = find_citation_key_at(text, TextSize::from(14)).unwrap();
+        assert_eq!(&text[range], "ba");
+
+        let range = find_citation_key_at(text, TextSize::from(7)).unwrap();
+        assert_eq!(&text[range], "foo");
+
+        assert_eq!(find_citation_key_at(text, TextSize::from(2)), None);
+    }
+
+    #[test]
+    fn test_citation_key_at_empty() {
+        let text = "See @";
+        let range = find_citation_key_at(text, TextSize::from(5)).unwrap();
+        assert_eq!(range, TextRange::empty(TextSize::from(5)));
+    }
+}
@@ -0,0 +1,226 @@
+use std::{
+    io::{self, Write},
+    path::Path,
+};
+
+use cstree::TextRange;
+
+use crate::{
+    syntax::{
+        bibtex,
+        latex::{self, HasCurly},
+        CstNode,
+    },
+    Document, DocumentData, Workspace,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum TagKind {
+    Section,
+    Label,
+    Citation,
+    Command,
+    Environment,
+}
+
+impl TagKind {
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Section => 's',
+            Self::Label => 'l',
+            Self::Citation => 'b',
+            Self::Command => 'c',
+            Self::Environment => 'e',
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
+pub struct Tag {
+    pub name: String,
+    pub line: u32,
+    pub kind: TagKind,
+}
+
+pub fn find_tags(document: &Document) -> Vec<Tag> {
+    let mut ranges = Vec::new();
+    match &document.data {
+        DocumentData::Latex(data) => {
+            for node in data.root.descendants() {
+                ranges.extend(
+                    find_section_tag(node)
+                        .or_else(|| find_label_tag(node))
+                        .or_else(|| find_command_tag(node))
+                        .or_else(|| find_environment_tag(node)),
+                );
+            }
+        }
+        DocumentData::Bibtex(data) => {
+            for entry in data.root.children().filter_map(bibtex::Entry::cast) {
+                if let Some(key) = entry.key() {
+                    ranges.push((key.to_string(), key.small_range(), TagKind::Citation));
+                }
+            }
+        }
+        DocumentData::BuildLog(_) | DocumentData::Markdown(_) => {}
+    }
+
+    ranges
+        .into_iter()
+        .map(|(name, range, kind)| Tag {
+            name: name.replace(|c: char| c == '\t' || c == '\r' || c == '\n', " "),
+            line: document.line_index.line_col(range.start()).line,
+            kind,
+        })
+        .collect()
+}
+
+fn find_section_tag(node: &latex::SyntaxNode) -> Option<(String, TextRange, TagKind)> {
+    let section = latex::Section::cast(node)?;
+    let name = section.name()?.content_text()?;
+    Some((name, section.small_range(), TagKind::Section))
+}
+
+fn find_label_tag(node: &latex::SyntaxNode) -> Option<(String, TextRange, TagKind)> {
+    let label = latex::LabelDefinition::cast(node)?;
+    let name = label.name()?.key()?;
+    Some((name.to_string(), name.small_range(), TagKind::Label))
+}
+
+fn find_command_tag(node: &latex::SyntaxNode) -> Option<(String, TextRange, TagKind)> {
+    let definition = latex::CommandDefinition::cast(node)?;
+    let name = definition.name()?.command()?;
+    Some((name.text().into(), name.text_range(), TagKind::Command))
+}
+
+fn find_environment_tag(node: &latex::SyntaxNode) -> Option<(String, TextRange, TagKind)> {
+    let definition = latex::EnvironmentDefinition::cast(node)?;
+    let name = definition.name()?.key()?;
+    Some((name.to_string(), name.small_range(), TagKind::Environment))
+}
+
+pub fn write_tags(
+    workspace: &dyn Workspace,
+    base_dir: &Path,
+    writer: &mut impl Write,
+) -> io::Result<()> {
+    let mut entries = Vec::new();
+    for document in workspace.documents() {
+        let path = match document.uri.to_file_path() {
+            Ok(path) => path,
+            Err(()) => continue,
+        };
+
+        let path = path.strip_prefix(base_dir).unwrap_or(&path);
+        let path = path.to_string_lossy().into_owned();
+        for tag in find_tags(&document) {
+            entries.push((tag, path.clone()));
+        }
+    }
+
+    entries.sort_by(|(tag1, path1), (tag2, path2)| {
+        (&tag1.name, path1, tag1.line).cmp(&(&tag2.name, path2, tag2.line))
+    });
+
+    writeln!(writer, "!_TAG_FILE_FORMAT\t2\t/extended format/")?;
+    writeln!(writer, "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted/")?;
+    writeln!(writer, "!_TAG_PROGRAM_NAME\ttexlab\t//")?;
+    writeln!(
+        writer,
+        "!_TAG_PROGRAM_VERSION\t{}\t//",
+        env!("CARGO_PKG_VERSION")
+    )?;
+
+    for (tag, path) in entries {
+        writeln!(
+            writer,
+            "{}\t{}\t{};\"\t{}",
+            tag.name,
+            path,
+            tag.line + 1,
+            tag.kind.as_char()
+        )?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use indoc::indoc;
+
+    use crate::{DocumentLanguage, ServerContext, Uri};
+
+    use super::*;
+
+    fn parse(name: &str, text: &str, language: DocumentLanguage) -> Document {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join(name)).unwrap();
+        Document::parse(context, Arc::new(uri), text.to_string(), language)
+    }
+
+    #[test]
+    fn test_latex() {
+        let document = parse(
+            "main.tex",
+            indoc! {r#"
+                \newcommand{\foo}{bar}
+                \newenvironment{baz}{}{}
+                \section{Qux}
+                \label{sec:qux}"#},
+            DocumentLanguage::Latex,
+        );
+
+        let actual = find_tags(&document);
+        let expected = vec![
+            Tag {
+                name: "\\foo".into(),
+                line: 0,
+                kind: TagKind::Command,
+            },
+            Tag {
+                name: "baz".into(),
+                line: 1,
+                kind: TagKind::Environment,
+            },
+            Tag {
+                name: "Qux".into(),
+                line: 2,
+                kind: TagKind::Section,
+            },
+            Tag {
+                name: "sec:qux".into(),
+                line: 3,
+                kind: TagKind::Label,
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_bibtex() {
+        let document = parse(
+            "main.bib",
+            indoc! {r#"
+                @article{foo,}
+                @book{bar, author = {Baz}}"#},
+            DocumentLanguage::Bibtex,
+        );
+
+        let actual = find_tags(&document);
+        let expected = vec![
+            Tag {
+                name: "foo".into(),
+                line: 0,
+                kind: TagKind::Citation,
+            },
+            Tag {
+                name: "bar".into(),
+                line: 1,
+                kind: TagKind::Citation,
+            },
+        ];
+        assert_eq!(actual, expected);
+    }
+}
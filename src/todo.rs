@@ -0,0 +1,98 @@
+use cstree::{TextRange, TextSize};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::syntax::{
+    latex::{self, HasCurly},
+    CstNode,
+};
+
+static TODO_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\b(TODO|FIXME|XXX)\b:?\s*(.*)").unwrap());
+
+/// A task that is marked with `TODO` or `FIXME` in a comment
+/// or with the `\todo` command of the `todonotes` package.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Todo {
+    pub range: TextRange,
+    pub kind: String,
+    pub text: String,
+}
+
+/// The code chunks (e.g. embedded Lua) are lexed as comments but do not contain tasks.
+pub fn find_todos(root: &latex::SyntaxNode, code_chunks: &[TextRange]) -> Vec<Todo> {
+    let mut todos = Vec::new();
+    for element in root.descendants_with_tokens() {
+        if let Some(comment) = element.as_token().filter(|token| {
+            token.kind() == latex::COMMENT
+                && !code_chunks
+                    .iter()
+                    .any(|chunk| chunk.contains_range(token.text_range()))
+        }) {
+            if let Some(captures) = TODO_REGEX.captures(comment.text()) {
+                let marker = captures.get(1).unwrap();
+                let start = comment.text_range().start() + TextSize::from(marker.start() as u32);
+                todos.push(Todo {
+                    range: TextRange::new(start, comment.text_range().end()),
+                    kind: marker.as_str().to_string(),
+                    text: captures[2].trim().to_string(),
+                });
+            }
+        } else if let Some(command) = element
+            .as_node()
+            .and_then(|node| latex::GenericCommand::cast(node))
+            .filter(|command| command.name().map(|name| name.text()) == Some("\\todo"))
+        {
+            let text = command
+                .syntax()
+                .children()
+                .find_map(latex::CurlyGroup::cast)
+                .and_then(|group| group.content_text())
+                .unwrap_or_default();
+
+            todos.push(Todo {
+                range: command.small_range(),
+                kind: "TODO".to_string(),
+                text,
+            });
+        }
+    }
+    todos
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lua_chunks() {
+        let text = "\\directlua{-- TODO: Foo}\n% TODO: Bar";
+        let code_chunks = latex::find_lua_chunks(text);
+        let root =
+            latex::parse_with_options(text, &code_chunks, &latex::CustomCommands::default()).root;
+
+        let todos: Vec<_> = find_todos(&root, &code_chunks)
+            .into_iter()
+            .map(|todo| todo.text)
+            .collect();
+        assert_eq!(todos, vec!["Bar".to_string()]);
+    }
+
+    #[test]
+    fn test_comments_and_commands() {
+        let text = "% TODO: Foo\nBar % FIXME Baz\n\\todo[inline]{Qux}\n% Quux";
+        let todos: Vec<_> = find_todos(&latex::parse(text).root, &[])
+            .into_iter()
+            .map(|todo| (todo.kind, todo.text))
+            .collect();
+
+        assert_eq!(
+            todos,
+            vec![
+                ("TODO".to_string(), "Foo".to_string()),
+                ("FIXME".to_string(), "Baz".to_string()),
+                ("TODO".to_string(), "Qux".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use cstree::TextRange;
+
+use crate::{syntax::latex, Document};
+
+/// Environments whose content is printed as is, including its whitespace.
+pub const VERBATIM_ENVIRONMENTS: &[&str] = &["asy", "lstlisting", "minted", "verbatim", "Verbatim"];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct WhitespaceIssues {
+    /// The whitespace at the end of the lines outside of verbatim environments.
+    pub trailing_whitespace: Vec<TextRange>,
+    /// The line endings that differ from the most common line ending of the file.
+    pub mixed_line_endings: Vec<TextRange>,
+    pub line_ending: LineEnding,
+}
+
+impl WhitespaceIssues {
+    pub fn is_empty(&self) -> bool {
+        self.trailing_whitespace.is_empty() && self.mixed_line_endings.is_empty()
+    }
+
+    /// Returns the edits that fix all issues, ordered by their position.
+    pub fn edits(&self) -> Vec<(TextRange, &'static str)> {
+        let mut edits: Vec<_> = self
+            .trailing_whitespace
+            .iter()
+            .map(|range| (*range, ""))
+            .chain(
+                self.mixed_line_endings
+                    .iter()
+                    .map(|range| (*range, self.line_ending.as_str())),
+            )
+            .collect();
+
+        edits.sort_by_key(|(range, _)| range.start());
+        edits
+    }
+}
+
+pub fn find_whitespace_issues(document: &Document) -> WhitespaceIssues {
+    let text = &document.text;
+    let mut trailing_whitespace = Vec::new();
+    let mut lf_endings = Vec::new();
+    let mut crlf_endings = Vec::new();
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let content = line
+            .strip_suffix("\r\n")
+            .or_else(|| line.strip_suffix('\n'))
+            .unwrap_or(line);
+
+        let end = offset + content.len();
+        let start = offset + content.trim_end_matches(&[' ', '\t'][..]).len();
+        if start < end && !is_verbatim(document, start) {
+            trailing_whitespace.push(text_range(start, end));
+        }
+
+        match line.len() - content.len() {
+            1 => lf_endings.push(text_range(end, end + 1)),
+            2 => crlf_endings.push(text_range(end, end + 2)),
+            _ => {}
+        }
+
+        offset += line.len();
+    }
+
+    let (line_ending, mixed_line_endings) = if crlf_endings.len() > lf_endings.len() {
+        (LineEnding::CrLf, lf_endings)
+    } else {
+        (LineEnding::Lf, crlf_endings)
+    };
+
+    WhitespaceIssues {
+        trailing_whitespace,
+        mixed_line_endings,
+        line_ending,
+    }
+}
+
+fn is_verbatim(document: &Document, offset: usize) -> bool {
+    let data = match document.data.as_latex() {
+        Some(data) => data,
+        None => return false,
+    };
+
+    let offset = (offset as u32).into();
+    if data
+        .extras
+        .code_chunks
+        .iter()
+        .any(|chunk| chunk.contains_inclusive(offset))
+    {
+        return true;
+    }
+
+    data.root
+        .token_at_offset(offset)
+        .right_biased()
+        .into_iter()
+        .flat_map(|token| token.ancestors())
+        .filter_map(latex::Environment::cast)
+        .filter_map(|environment| environment.begin()?.name()?.key())
+        .any(|name| VERBATIM_ENVIRONMENTS.contains(&name.to_string().as_str()))
+}
+
+fn text_range(start: usize, end: usize) -> TextRange {
+    TextRange::new((start as u32).into(), (end as u32).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{DocumentLanguage, ServerContext, Uri};
+
+    use super::*;
+
+    fn find_issues(text: &str) -> WhitespaceIssues {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join("main.tex")).unwrap();
+        let document = Document::parse(
+            context,
+            Arc::new(uri),
+            text.to_string(),
+            DocumentLanguage::Latex,
+        );
+
+        find_whitespace_issues(&document)
+    }
+
+    #[test]
+    fn test_trailing_whitespace() {
+        let issues = find_issues("foo \n\\begin{verbatim}\nbar \n\\end{verbatim}\nbaz\t");
+        assert_eq!(
+            issues.trailing_whitespace,
+            vec![text_range(3, 4), text_range(45, 46)]
+        );
+        assert!(issues.mixed_line_endings.is_empty());
+    }
+
+    #[test]
+    fn test_mixed_line_endings() {
+        let issues = find_issues("foo\r\nbar \r\nbaz\nqux");
+        assert_eq!(issues.line_ending, LineEnding::CrLf);
+        assert_eq!(issues.trailing_whitespace, vec![text_range(8, 9)]);
+        assert_eq!(issues.mixed_line_endings, vec![text_range(14, 15)]);
+        assert_eq!(
+            issues.edits(),
+            vec![(text_range(8, 9), ""), (text_range(14, 15), "\r\n")]
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use cstree::TextRange;
+
+use crate::{
+    syntax::{latex, CstNode},
+    LANGUAGE_DATA,
+};
+
+/// The approximate number of words of a paragraph of `lipsum` or `blindtext`.
+const PARAGRAPH_WORD_COUNT: usize = 100;
+
+/// A command like `\lipsum` or `\blindtext` that prints placeholder text.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct PlaceholderText {
+    pub range: TextRange,
+    pub name: String,
+    pub paragraphs: usize,
+}
+
+impl PlaceholderText {
+    /// Returns the approximate number of words that are printed by the command.
+    pub fn word_count(&self) -> usize {
+        self.paragraphs * PARAGRAPH_WORD_COUNT
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct WordCount {
+    /// The number of words that are written in the document itself.
+    pub words: usize,
+    pub placeholders: Vec<PlaceholderText>,
+}
+
+impl WordCount {
+    /// Returns the approximate number of words that are printed by placeholder commands.
+    pub fn placeholder_words(&self) -> usize {
+        self.placeholders
+            .iter()
+            .map(PlaceholderText::word_count)
+            .sum()
+    }
+}
+
+/// Counts the words of the body of a document.
+/// Math, optional arguments and the preamble are not counted
+/// and placeholder text is reported separately.
+pub fn count_words(root: &latex::SyntaxNode) -> WordCount {
+    let body = root
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .find(|environment| environment_name(environment).as_deref() == Some("document"))
+        .map_or(root, |environment| environment.syntax());
+
+    let mut count = WordCount::default();
+    for element in body.descendants_with_tokens() {
+        if let Some(word) = element
+            .as_token()
+            .filter(|token| token.kind() == latex::WORD)
+            .filter(|token| token.parent().kind() == latex::TEXT)
+            .filter(|token| token.text().chars().any(char::is_alphanumeric))
+        {
+            if !word.parent().ancestors().any(is_excluded) {
+                count.words += 1;
+            }
+        } else if let Some(placeholder) = element
+            .as_node()
+            .and_then(|node| latex::GenericCommand::cast(node))
+            .and_then(find_placeholder_text)
+        {
+            count.placeholders.push(placeholder);
+        }
+    }
+
+    count
+}
+
+pub fn find_placeholder_text(command: latex::GenericCommand) -> Option<PlaceholderText> {
+    let name = command.name()?.text();
+    let argument = command
+        .syntax()
+        .children()
+        .find_map(latex::MixedGroup::cast)
+        .map(|group| {
+            group
+                .syntax()
+                .descendants_with_tokens()
+                .filter_map(|element| element.into_token())
+                .filter(|token| token.kind() == latex::WORD)
+                .map(|token| token.text())
+                .collect::<String>()
+        });
+
+    let paragraphs = match name.trim_end_matches('*') {
+        "\\lipsum" => argument
+            .as_deref()
+            .and_then(parse_paragraph_range)
+            .unwrap_or(7),
+        "\\blindtext" => argument.and_then(|text| text.parse().ok()).unwrap_or(1),
+        "\\Blindtext" => argument.and_then(|text| text.parse().ok()).unwrap_or(5),
+        "\\blinddocument" => 10,
+        "\\Blinddocument" => 30,
+        _ => return None,
+    };
+
+    Some(PlaceholderText {
+        range: command.small_range(),
+        name: name.to_string(),
+        paragraphs,
+    })
+}
+
+/// Parses the paragraphs of `\lipsum` like `3` or `2-5`.
+fn parse_paragraph_range(text: &str) -> Option<usize> {
+    match text.split_once('-') {
+        Some((start, end)) => {
+            let start: usize = start.trim().parse().ok()?;
+            let end: usize = end.trim().parse().ok()?;
+            Some(end.saturating_sub(start) + 1)
+        }
+        None => text.trim().parse::<usize>().ok().map(|_| 1),
+    }
+}
+
+fn is_excluded(node: &latex::SyntaxNode) -> bool {
+    match node.kind() {
+        latex::FORMULA | latex::EQUATION | latex::BRACK_GROUP => true,
+        latex::MIXED_GROUP => node
+            .parent()
+            .map_or(false, |parent| parent.kind() == latex::GENERIC_COMMAND),
+        latex::ENVIRONMENT => latex::Environment::cast(node)
+            .and_then(|environment| environment_name(&environment))
+            .map_or(false, |name| {
+                LANGUAGE_DATA
+                    .math_environments
+                    .iter()
+                    .any(|math_environment| *math_environment == name)
+            }),
+        _ => false,
+    }
+}
+
+fn environment_name(environment: &latex::Environment) -> Option<String> {
+    Some(environment.begin()?.name()?.key()?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        let text = "\\documentclass{article}\n\\usepackage{amsmath}\n\\begin{document}\n\\section{Foo Bar}\nBaz $x + y$ (qux).\n\\begin{align}\na = b\n\\end{align}\n\\end{document}";
+        let count = count_words(&latex::parse(text).root);
+        assert_eq!(count.words, 4);
+        assert!(count.placeholders.is_empty());
+    }
+
+    #[test]
+    fn test_placeholders() {
+        let text = "Foo\n\\lipsum[2-5]\n\\lipsum*[3]\n\\blindtext[2]\n\\lipsum";
+        let count = count_words(&latex::parse(text).root);
+        let paragraphs: Vec<_> = count
+            .placeholders
+            .iter()
+            .map(|placeholder| placeholder.paragraphs)
+            .collect();
+
+        assert_eq!(count.words, 1);
+        assert_eq!(paragraphs, vec![4, 1, 2, 7]);
+        assert_eq!(count.placeholder_words(), 14 * PARAGRAPH_WORD_COUNT);
+    }
+}
@@ -1,8 +1,9 @@
 use std::{fs, path::PathBuf, sync::Arc};
 
 use anyhow::Result;
+use encoding_rs::Encoding;
 
-use crate::{DocumentLanguage, Uri};
+use crate::{decode_text, DocumentLanguage, Uri};
 
 use super::Document;
 
@@ -26,6 +27,20 @@ pub trait Workspace: Send + Sync {
         text: String,
         language: DocumentLanguage,
         source: WorkspaceSource,
+    ) -> Arc<Document> {
+        self.open_with_encoding(uri, text, language, source, None)
+    }
+
+    /// Opens a document whose text has been decoded from the given encoding.
+    /// If the encoding is unknown, the encoding that is declared with `inputenc`
+    /// or the encoding of the previous version of the document is used.
+    fn open_with_encoding(
+        &self,
+        uri: Arc<Uri>,
+        text: String,
+        language: DocumentLanguage,
+        source: WorkspaceSource,
+        encoding: Option<&'static Encoding>,
     ) -> Arc<Document>;
 
     fn register_open_handler(&self, handler: OpenHandler);
@@ -38,13 +53,14 @@ pub trait Workspace: Send + Sync {
         }
 
         let data = fs::read(&path)?;
-        let text = String::from_utf8_lossy(&data).into_owned();
+        let (text, encoding) = decode_text(&data);
         if let Some(language) = DocumentLanguage::by_path(&path) {
-            Ok(Some(self.open(
+            Ok(Some(self.open_with_encoding(
                 uri,
                 text,
                 language,
                 WorkspaceSource::Server,
+                Some(encoding),
             )))
         } else {
             Ok(None)
@@ -59,13 +75,14 @@ pub trait Workspace: Send + Sync {
         }
 
         let data = fs::read(&path)?;
-        let text = String::from_utf8_lossy(&data).into_owned();
+        let (text, encoding) = decode_text(&data);
         if let Some(language) = DocumentLanguage::by_path(&path) {
-            Ok(Some(self.open(
+            Ok(Some(self.open_with_encoding(
                 uri,
                 text,
                 language,
                 WorkspaceSource::Server,
+                Some(encoding),
             )))
         } else {
             Ok(None)
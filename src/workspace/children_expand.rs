@@ -25,7 +25,12 @@ where
     fn expand(workspace: &dyn Workspace, document: &Document) {
         if let Some(data) = document.data.as_latex() {
             let extras = &data.extras;
-            let mut all_targets = vec![&extras.implicit_links.aux, &extras.implicit_links.log];
+            let mut all_targets = vec![
+                &extras.implicit_links.aux,
+                &extras.implicit_links.log,
+                &extras.implicit_links.ilg,
+                &extras.implicit_links.glg,
+            ];
             for link in &extras.explicit_links {
                 if link
                     .as_component_name()
@@ -47,19 +52,29 @@ where
                     }
                 }
             });
+        } else if let Some(data) = document.data.as_markdown() {
+            data.bibliography_targets
+                .iter()
+                .filter(|uri| uri.scheme() == "file")
+                .filter_map(|uri| uri.to_file_path().ok())
+                .for_each(|path| {
+                    let _ = workspace.load(path);
+                });
         }
     }
 }
 
 impl<W: Workspace> Workspace for ChildrenExpander<W> {
-    fn open(
+    fn open_with_encoding(
         &self,
         uri: Arc<Uri>,
         text: String,
         language: DocumentLanguage,
         source: WorkspaceSource,
+        encoding: Option<&'static Encoding>,
     ) -> Arc<Document> {
-        self.workspace.open(uri, text, language, source)
+        self.workspace
+            .open_with_encoding(uri, text, language, source, encoding)
     }
 
     fn register_open_handler(&self, handler: OpenHandler) {
@@ -1,14 +1,20 @@
-use std::{fmt, sync::Arc};
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use derive_more::From;
+use encoding_rs::{Encoding, UTF_8};
 
 use crate::{
     line_index::LineIndex,
     syntax::{
         bibtex, build_log,
         latex::{self, LatexAnalyzerContext},
+        markdown,
     },
-    DocumentLanguage, ServerContext, Uri,
+    DocumentLanguage, LatexOptions, ServerContext, Uri,
 };
 
 #[derive(Debug, Clone)]
@@ -22,11 +28,18 @@ pub struct BibtexDocumentData {
     pub root: bibtex::SyntaxNode,
 }
 
+#[derive(Debug, Clone)]
+pub struct MarkdownDocumentData {
+    pub parse: markdown::Parse,
+    pub bibliography_targets: Vec<Arc<Uri>>,
+}
+
 #[derive(Debug, Clone, From)]
 pub enum DocumentData {
     Latex(LatexDocumentData),
     Bibtex(BibtexDocumentData),
     BuildLog(build_log::Parse),
+    Markdown(MarkdownDocumentData),
 }
 
 impl DocumentData {
@@ -35,6 +48,7 @@ impl DocumentData {
             Self::Latex(_) => DocumentLanguage::Latex,
             Self::Bibtex(_) => DocumentLanguage::Bibtex,
             Self::BuildLog(_) => DocumentLanguage::BuildLog,
+            Self::Markdown(_) => DocumentLanguage::Markdown,
         }
     }
 
@@ -61,6 +75,14 @@ impl DocumentData {
             None
         }
     }
+
+    pub fn as_markdown(&self) -> Option<&MarkdownDocumentData> {
+        if let Self::Markdown(data) = self {
+            Some(data)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -69,6 +91,10 @@ pub struct Document {
     pub text: String,
     pub line_index: LineIndex,
     pub data: DocumentData,
+    pub parse_duration: Duration,
+
+    /// The encoding of the file on disk, which is used when the server writes the document.
+    pub encoding: &'static Encoding,
 }
 
 impl fmt::Debug for Document {
@@ -84,20 +110,45 @@ impl Document {
         text: String,
         language: DocumentLanguage,
     ) -> Self {
+        let start = Instant::now();
         let line_index = LineIndex::new(&text);
         let data = match language {
             DocumentLanguage::Latex => {
-                let root = latex::parse(&text).root;
+                let mut code_chunks = if is_literate(&uri) {
+                    latex::find_code_chunks(&text)
+                } else {
+                    Vec::new()
+                };
+
+                // Embedded Lua code is opaque like the code chunks of literate documents.
+                let lua_chunks: Vec<_> = latex::find_lua_chunks(&text)
+                    .into_iter()
+                    .filter(|lua_chunk| {
+                        !code_chunks
+                            .iter()
+                            .any(|chunk| chunk.intersect(*lua_chunk).is_some())
+                    })
+                    .collect();
+                code_chunks.extend(lua_chunks);
+                code_chunks.sort_by_key(|chunk| chunk.start());
+                let options = context.options.read().unwrap();
+                let custom_commands = custom_commands(&options.latex);
+                let root = latex::parse_with_options(&text, &code_chunks, &custom_commands).root;
 
-                let base_uri = match &context.options.read().unwrap().root_directory {
+                let base_uri = match &options.root_directory {
                     Some(root_dir) => Uri::from_directory_path(root_dir)
                         .map(Arc::new)
                         .unwrap_or_else(|()| Arc::clone(&uri)),
                     None => Arc::clone(&uri),
                 };
+                drop(options);
+
                 let mut context = LatexAnalyzerContext {
                     inner: context,
-                    extras: latex::Extras::default(),
+                    extras: latex::Extras {
+                        code_chunks,
+                        ..latex::Extras::default()
+                    },
                     document_uri: Arc::clone(&uri),
                     base_uri,
                 };
@@ -111,6 +162,21 @@ impl Document {
                 BibtexDocumentData { root }.into()
             }
             DocumentLanguage::BuildLog => DocumentData::BuildLog(build_log::parse(&text)),
+            DocumentLanguage::Markdown => {
+                let parse = markdown::parse(&text);
+                let bibliography_targets = parse
+                    .bibliographies
+                    .iter()
+                    .filter_map(|bibliography| uri.join(&bibliography.text).ok())
+                    .map(|target| Arc::new(Uri::from(target)))
+                    .collect();
+
+                MarkdownDocumentData {
+                    parse,
+                    bibliography_targets,
+                }
+                .into()
+            }
         };
 
         Self {
@@ -118,6 +184,8 @@ impl Document {
             text,
             line_index,
             data,
+            parse_duration: start.elapsed(),
+            encoding: UTF_8,
         }
     }
 
@@ -125,3 +193,86 @@ impl Document {
         self.data.language()
     }
 }
+
+fn custom_commands(options: &LatexOptions) -> latex::CustomCommands {
+    let mut custom_commands = latex::CustomCommands::default();
+    let lists = [
+        (&options.citation_commands, latex::CITATION_NAME),
+        (
+            &options.label_definition_commands,
+            latex::LABEL_DEFINITION_NAME,
+        ),
+        (
+            &options.label_reference_commands,
+            latex::LABEL_REFERENCE_NAME,
+        ),
+        (&options.include_commands, latex::LATEX_INCLUDE_NAME),
+    ];
+
+    for (names, kind) in &lists {
+        for name in names.iter() {
+            custom_commands.insert(name, *kind);
+        }
+    }
+    custom_commands
+}
+
+fn is_literate(uri: &Uri) -> bool {
+    uri.path_segments()
+        .and_then(|segments| segments.last())
+        .and_then(|file_name| file_name.rsplit_once('.'))
+        .map_or(false, |(_, extension)| {
+            latex::is_literate_extension(extension)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::CstNode;
+
+    use super::*;
+
+    #[test]
+    fn test_custom_commands() {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        {
+            let mut options = context.options.write().unwrap();
+            options.latex.citation_commands = vec!["mycite".into()];
+            options.latex.label_definition_commands = vec!["\\mylabel".into()];
+            options.latex.include_commands = vec!["myinput".into()];
+        }
+
+        let uri = Arc::new(Uri::parse("http://example.com/main.tex").unwrap());
+        let text = r#"\mycite{foo}\mylabel{bar}\myinput{baz}"#.to_string();
+        let document = Document::parse(context, uri, text, DocumentLanguage::Latex);
+        let data = document.data.as_latex().unwrap();
+
+        let citation_keys: Vec<_> = data
+            .root
+            .descendants()
+            .filter_map(latex::Citation::cast)
+            .filter_map(|citation| citation.key_list())
+            .flat_map(|keys| keys.keys())
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(citation_keys, vec!["foo"]);
+
+        let label_names: Vec<_> = data
+            .root
+            .descendants()
+            .filter_map(latex::LabelDefinition::cast)
+            .filter_map(|label| label.name()?.key())
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(label_names, vec!["bar"]);
+
+        let stems: Vec<_> = data
+            .extras
+            .explicit_links
+            .iter()
+            .filter(|link| link.kind == latex::ExplicitLinkKind::Latex)
+            .map(|link| link.stem.as_str())
+            .collect();
+        assert_eq!(stems, vec!["baz"]);
+    }
+}
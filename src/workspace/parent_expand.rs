@@ -4,7 +4,8 @@ use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rustc_hash::FxHashSet;
 
 use crate::{
-    Document, DocumentLanguage, OpenHandler, Uri, Workspace, WorkspaceSource, WorkspaceSubset,
+    is_generated_file, is_generated_path, Document, DocumentLanguage, OpenHandler, Uri, Workspace,
+    WorkspaceSource, WorkspaceSubset,
 };
 
 pub struct ParentExpander<W> {
@@ -21,16 +22,17 @@ impl<W> Workspace for ParentExpander<W>
 where
     W: Workspace + Send + Sync + 'static,
 {
-    fn open(
+    fn open_with_encoding(
         &self,
         uri: Arc<Uri>,
         text: String,
         language: DocumentLanguage,
         source: WorkspaceSource,
+        encoding: Option<&'static Encoding>,
     ) -> Arc<Document> {
-        let document = self
-            .workspace
-            .open(Arc::clone(&uri), text, language, source);
+        let document =
+            self.workspace
+                .open_with_encoding(Arc::clone(&uri), text, language, source, encoding);
 
         let all_current_paths = self
             .workspace
@@ -39,7 +41,8 @@ where
             .filter_map(|doc| doc.uri.to_file_path().ok())
             .collect::<FxHashSet<_>>();
 
-        if uri.scheme() == "file" {
+        // Generated files are loaded through the implicit links of their sources instead.
+        if uri.scheme() == "file" && !is_generated_file(&uri) {
             if let Ok(mut path) = uri.to_file_path() {
                 while path.pop() && !self.has_parent(Arc::clone(&uri)).unwrap_or(false) {
                     let mut files = Vec::new();
@@ -55,6 +58,7 @@ where
                                 Some(DocumentLanguage::Latex)
                             )
                         })
+                        .filter(|path| !is_generated_path(path))
                         .filter(|path| !all_current_paths.contains(path))
                         .for_each(|path| {
                             files.push(path);
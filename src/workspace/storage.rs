@@ -1,11 +1,12 @@
 use std::sync::{Arc, Mutex};
 
+use encoding_rs::{Encoding, UTF_8};
 use petgraph::{graphmap::UnGraphMap, visit::Dfs};
 use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
-    Document, DocumentLanguage, OpenHandler, ServerContext, Uri, Workspace, WorkspaceSource,
-    WorkspaceSubset,
+    find_input_encoding, Document, DocumentLanguage, OpenHandler, ServerContext, Uri, Workspace,
+    WorkspaceSource, WorkspaceSubset,
 };
 
 #[derive(Clone)]
@@ -28,20 +29,24 @@ impl Storage {
 }
 
 impl Workspace for Storage {
-    fn open(
+    fn open_with_encoding(
         &self,
         uri: Arc<Uri>,
         text: String,
         language: DocumentLanguage,
         source: WorkspaceSource,
+        encoding: Option<&'static Encoding>,
     ) -> Arc<Document> {
         log::debug!("(Re)Loading document: {}", uri);
-        let document = Arc::new(Document::parse(
-            Arc::clone(&self.context),
-            Arc::clone(&uri),
-            text,
-            language,
-        ));
+        let encoding = encoding
+            .or_else(|| find_input_encoding(&text))
+            .or_else(|| self.get(&uri).map(|document| document.encoding))
+            .unwrap_or(UTF_8);
+
+        let mut document =
+            Document::parse(Arc::clone(&self.context), Arc::clone(&uri), text, language);
+        document.encoding = encoding;
+        let document = Arc::new(document);
         {
             self.documents_by_uri
                 .lock()
@@ -120,6 +125,15 @@ impl Workspace for Storage {
                         }
                     }
                 }
+            } else if let Some(data) = document
+                .as_ref()
+                .and_then(|document| document.data.as_markdown())
+            {
+                for target in &data.bibliography_targets {
+                    if let Some(j) = all_current_uris.iter().position(|uri| uri == target) {
+                        edges.push((i, j, ()));
+                    }
+                }
             }
         }
 
@@ -46,14 +46,17 @@ where
 }
 
 impl<W: Workspace> Workspace for DocumentWatcher<W> {
-    fn open(
+    fn open_with_encoding(
         &self,
         uri: Arc<Uri>,
         text: String,
         language: DocumentLanguage,
         source: WorkspaceSource,
+        encoding: Option<&'static Encoding>,
     ) -> Arc<Document> {
-        let document = self.workspace.open(uri, text, language, source);
+        let document = self
+            .workspace
+            .open_with_encoding(uri, text, language, source, encoding);
         if document.uri.scheme() == "file" {
             if let Ok(mut path) = document.uri.to_file_path() {
                 path.pop();
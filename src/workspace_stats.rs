@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    count_words,
+    syntax::{bibtex, latex},
+    Document, DocumentData, Workspace,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStats {
+    pub documents: Vec<DocumentStats>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub uri: String,
+    pub language: String,
+    pub length: usize,
+    pub error_count: usize,
+    pub missing_count: usize,
+    pub node_count: usize,
+
+    /// The number of words of the document body without placeholder text.
+    pub word_count: usize,
+
+    /// The approximate number of words printed by commands like `\lipsum` or `\blindtext`.
+    pub placeholder_word_count: usize,
+
+    /// The duration of the last parse (including the analysis) in microseconds.
+    pub parse_duration: u64,
+}
+
+/// Collects the parse statistics of all documents.
+/// The documents that took the longest to parse come first.
+pub fn collect_workspace_stats(workspace: &dyn Workspace) -> WorkspaceStats {
+    let mut documents: Vec<_> = workspace
+        .documents()
+        .iter()
+        .map(|document| collect_document_stats(document))
+        .collect();
+
+    documents.sort_by(|a, b| {
+        b.parse_duration
+            .cmp(&a.parse_duration)
+            .then_with(|| a.uri.cmp(&b.uri))
+    });
+
+    WorkspaceStats { documents }
+}
+
+fn collect_document_stats(document: &Document) -> DocumentStats {
+    let (error_count, missing_count, node_count) = match &document.data {
+        DocumentData::Latex(data) => {
+            let mut counts = (0, 0, 0);
+            for element in data.root.descendants_with_tokens() {
+                match element.kind() {
+                    latex::ERROR => counts.0 += 1,
+                    latex::MISSING => counts.1 += 1,
+                    _ => {}
+                }
+
+                if element.as_node().is_some() {
+                    counts.2 += 1;
+                }
+            }
+            counts
+        }
+        DocumentData::Bibtex(data) => {
+            let mut counts = (0, 0, 0);
+            for element in data.root.descendants_with_tokens() {
+                match element.kind() {
+                    bibtex::ERROR => counts.0 += 1,
+                    bibtex::MISSING => counts.1 += 1,
+                    _ => {}
+                }
+
+                if element.as_node().is_some() {
+                    counts.2 += 1;
+                }
+            }
+            counts
+        }
+        DocumentData::BuildLog(_) | DocumentData::Markdown(_) => (0, 0, 0),
+    };
+
+    let (word_count, placeholder_word_count) = document
+        .data
+        .as_latex()
+        .map(|data| count_words(&data.root))
+        .map_or((0, 0), |count| (count.words, count.placeholder_words()));
+
+    DocumentStats {
+        uri: document.uri.to_string(),
+        language: format!("{:?}", document.language()),
+        length: document.text.len(),
+        error_count,
+        missing_count,
+        node_count,
+        word_count,
+        placeholder_word_count,
+        parse_duration: document.parse_duration.as_micros() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{DocumentLanguage, ServerContext, Uri};
+
+    use super::*;
+
+    fn parse(name: &str, text: &str, language: DocumentLanguage) -> Document {
+        let context = Arc::new(ServerContext::new(std::env::temp_dir()));
+        let uri = Uri::from_file_path(std::env::temp_dir().join(name)).unwrap();
+        Document::parse(context, Arc::new(uri), text.to_string(), language)
+    }
+
+    #[test]
+    fn test_latex_missing_tokens() {
+        let document = parse("main.tex", "\\section{Foo", DocumentLanguage::Latex);
+        let stats = collect_document_stats(&document);
+        assert_eq!(stats.language, "Latex");
+        assert_eq!(stats.missing_count, 1);
+        assert!(stats.node_count > 0);
+    }
+
+    #[test]
+    fn test_latex_word_count() {
+        let document = parse(
+            "main.tex",
+            "\\begin{document}\nFoo bar.\n\\lipsum[1-2]\n\\end{document}",
+            DocumentLanguage::Latex,
+        );
+        let stats = collect_document_stats(&document);
+        assert_eq!(stats.word_count, 2);
+        assert_eq!(stats.placeholder_word_count, 200);
+    }
+
+    #[test]
+    fn test_bibtex_valid() {
+        let document = parse(
+            "main.bib",
+            "@article{foo, author = {Bar}}",
+            DocumentLanguage::Bibtex,
+        );
+        let stats = collect_document_stats(&document);
+        assert_eq!(stats.error_count, 0);
+        assert_eq!(stats.missing_count, 0);
+    }
+}
@@ -182,7 +182,11 @@ mod bibtex {
 
 mod latex {
     use insta::assert_json_snapshot;
-    use lsp_types::ClientCapabilities;
+    use lsp_types::{
+        ClientCapabilities, CompletionClientCapabilities, CompletionItemCapability,
+        CompletionTextEdit, InsertReplaceEdit, Range, TextDocumentClientCapabilities,
+    };
+    use texlab::RangeExt;
 
     use super::*;
 
@@ -774,6 +778,43 @@ mod latex {
         Ok(())
     }
 
+    #[test]
+    fn test_user_command_insert_replace() -> Result<()> {
+        let server = ServerTester::launch_new_instance()?;
+        let capabilities = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                completion: Some(CompletionClientCapabilities {
+                    completion_item: Some(CompletionItemCapability {
+                        insert_replace_support: Some(true),
+                        ..CompletionItemCapability::default()
+                    }),
+                    ..CompletionClientCapabilities::default()
+                }),
+                ..TextDocumentClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+        server.initialize(capabilities, None)?;
+        let uri = server.open("main.tex", "\\foobar\n\\fooba", "latex", false)?;
+        let list = server.complete(uri, 1, 3)?;
+        let item = list
+            .items
+            .into_iter()
+            .find(|item| item.label == "foobar")
+            .unwrap();
+
+        assert_eq!(item.filter_text.as_deref(), Some("\\foobar"));
+        assert_eq!(
+            item.text_edit,
+            Some(CompletionTextEdit::InsertAndReplace(InsertReplaceEdit {
+                new_text: "\\foobar".into(),
+                insert: Range::new_simple(1, 0, 1, 3),
+                replace: Range::new_simple(1, 0, 1, 6),
+            }))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_user_environment() -> Result<()> {
         let server = ServerTester::launch_new_instance()?;